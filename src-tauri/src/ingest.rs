@@ -0,0 +1,98 @@
+//! Drag-and-Drop Ingestion
+//!
+//! Files dropped on the window are copied to `artifacts/uploads/` like `/v1/files` uploads.
+//! Documents are chunked into vector memory under their file id, and images are described by
+//! the `vision` tool with the description remembered. Each file then gets a `file-ingested`
+//! event with its upload record and the memory ids created (or the error).
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, Window, WindowEvent};
+
+use rust_agency::memory::documents::{extract_text, ingest_text};
+use rust_agency::memory::entry::MemorySource;
+use rust_agency::memory::MemoryEntry;
+use rust_agency::orchestrator::Kind;
+use rust_agency::tools::{ToolCall, UploadKind, UploadStore};
+
+use crate::AgencyState;
+
+/// Largest file accepted from a drop
+const MAX_DROP_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Serialize)]
+struct Ingested {
+    path: PathBuf,
+    /// The upload record, once copied
+    file: Option<serde_json::Value>,
+    memory_ids: Vec<String>,
+    error: Option<String>,
+}
+
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+        for path in paths.clone() {
+            let app = window.app_handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = app.emit("nexus-event", format!("📥 Ingesting {}...", path.display()));
+                let mut ingested = Ingested { path: path.clone(), file: None, memory_ids: Vec::new(), error: None };
+                if let Err(e) = ingest_file(&app, &path, &mut ingested).await {
+                    ingested.error = Some(e);
+                }
+                let _ = app.emit("file-ingested", ingested);
+            });
+        }
+    }
+}
+
+async fn ingest_file(app: &AppHandle, path: &Path, ingested: &mut Ingested) -> Result<(), String> {
+    let state = app.try_state::<AgencyState>().ok_or("the agency is still starting")?;
+    let metadata = tokio::fs::metadata(path).await.map_err(|e| e.to_string())?;
+    if !metadata.is_file() {
+        return Err("only files can be ingested".to_string());
+    }
+    if metadata.len() > MAX_DROP_BYTES {
+        return Err(format!("larger than {} MB", MAX_DROP_BYTES / 1024 / 1024));
+    }
+    let bytes = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+    let filename = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let uploads = UploadStore::default();
+    let mut file = uploads.store(&filename, &bytes, None).await.map_err(|e| e.to_string())?;
+    let source = format!("file://{}", file.path.display());
+    ingested.file = Some(file.to_openai());
+
+    ingested.memory_ids = match file.kind {
+        kind if kind.is_document() => {
+            let text = extract_text(&file.path).await.map_err(|e| e.to_string())?;
+            let ids = ingest_text(state.memory.as_ref(), &text, &file.id, &source, &["upload", "drop"]).await.map_err(|e| e.to_string())?;
+            file.chunks = ids.len();
+            uploads.save(&file).await.map_err(|e| e.to_string())?;
+            ids
+        }
+        UploadKind::Image => {
+            let call = ToolCall {
+                name: "vision".to_string(),
+                parameters: serde_json::json!({ "action": "describe", "image_source": file.id }),
+            };
+            let output = state.tools.execute(&call).await.map_err(|e| e.to_string())?;
+            if !output.success {
+                return Err(output.error.unwrap_or(output.summary));
+            }
+            let description = output.data["description"].as_str().unwrap_or(&output.summary).to_string();
+            let mut entry = MemoryEntry::new(format!("Image '{}' ({}): {}", file.filename, file.id, description), "DocumentIngestor", MemorySource::Codebase);
+            entry.metadata.context = file.id.clone();
+            entry.metadata.kind = Kind::Evidence;
+            entry.metadata.tags.extend(["upload", "drop", "image"].map(String::from));
+            entry.metadata.grounding_holon = Some(source);
+            let id = state.memory.store(entry).await.map_err(|e| e.to_string())?;
+            state.memory.persist().await.map_err(|e| e.to_string())?;
+            vec![id]
+        }
+        // Kept for tools to read; there is no text to remember
+        _ => Vec::new(),
+    };
+    ingested.file = Some(file.to_openai());
+    let _ = app.emit("nexus-event", format!("✅ Ingested {} as {} ({} memories)", file.filename, file.id, ingested.memory_ids.len()));
+    Ok(())
+}
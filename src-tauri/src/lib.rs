@@ -1,33 +1,70 @@
 use tauri::{Emitter, Manager};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::{Mutex, broadcast};
 use rust_agency::orchestrator::Supervisor;
-use rust_agency::agent::{Speaker, LLMProvider};
+use rust_agency::agent::{AgentType, Speaker, LLMProvider};
+use rust_agency::agent::provider::{create_provider_by_type, SwitchableProvider};
 use rust_agency::memory::{Memory, VectorMemory, MemoryManager, EpisodicMemory};
-use rust_agency::orchestrator::{SessionManager, profile::ProfileManager};
+use rust_agency::memory::episodic::ConversationTurn;
+use rust_agency::orchestrator::conversations::{apply_profile, ConversationSummary};
+use rust_agency::orchestrator::{AnswerSplitter, ConversationRegistry, SessionManager, StreamChunk, TurnEvent, KILL_SWITCH, profile::{AgencyProfile, ProfileManager}};
+use futures_util::StreamExt;
 use rust_agency::tools::{
     ToolRegistry, WebSearchTool, CodeExecTool, MemoryQueryTool, 
     KnowledgeGraphTool, ArtifactTool, SandboxTool, CodebaseTool, 
     SystemTool, ForgeTool, VisualizationTool, 
-    SpeakerRsTool, ScienceTool, VisionTool, ModelManager
+    SpeakerRsTool, ScienceTool, VisionTool, ModelManager,
+    SkillLoader, CatalogConfig, CatalogItem, fetch_catalog, install_catalog_items
 };
 
+mod ingest;
+mod notifications;
+mod settings;
+mod tray;
+mod voice;
+use settings::{DesktopSettings, SettingsStore};
+
 struct AgencyState {
     supervisor: Arc<Mutex<Supervisor>>,
     speaker: Arc<Mutex<Speaker>>,
     current_task: Arc<Mutex<Option<tokio::task::AbortHandle>>>,
     episodic_memory: Arc<Mutex<EpisodicMemory>>,
+    tools: Arc<ToolRegistry>,
+    /// Long-term memory shared by every conversation
+    memory: Arc<dyn Memory>,
+    /// Conversations besides the main one, each able to run a turn at the same time
+    conversations: Arc<ConversationRegistry>,
+    session_tasks: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
+    /// Conversation `send_query` goes to; `None` for the main one
+    active_session: Arc<Mutex<Option<String>>>,
+    profile: Arc<ProfileManager>,
+    /// Shared by every conversation; rebuilt when the provider settings change
+    provider: Arc<SwitchableProvider>,
+    settings: Arc<Mutex<DesktopSettings>>,
+    settings_store: Arc<SettingsStore>,
 }
 
+/// Set once the embedded listener has been started
+static EARS_STARTED: AtomicBool = AtomicBool::new(false);
+
 #[tauri::command]
 async fn send_query(
     query: String, 
     state: tauri::State<'_, AgencyState>, 
     app: tauri::AppHandle
 ) -> Result<(), String> {
-    let supervisor = state.supervisor.clone();
+    // The conversation picked with `switch_session`, else the main one
+    let active = state.active_session.lock().await.clone();
+    let supervisor = match active {
+        Some(id) => state.conversations.get(&id).await.map_err(|e| e.to_string())?,
+        None => state.supervisor.clone(),
+    };
     let current_task = state.current_task.clone();
     let speaker = state.speaker.clone();
+    let tts = state.settings.lock().await.tts;
     let app_handle = app.clone();
 
     // Abort existing
@@ -35,42 +72,59 @@ async fn send_query(
         let mut task_guard = current_task.lock().await;
         if let Some(handle) = task_guard.take() {
             handle.abort();
+            state.tools.cancel_running();
             app.emit("nexus-event", "STATE:ABORTED").unwrap();
         }
     }
 
-    let handle = tokio::spawn(async move {
+    let handle = KILL_SWITCH.spawn(async move {
         app_handle.emit("nexus-event", "🚀 Request: Orchestrating Agency...").unwrap();
         
         let mut sup = supervisor.lock().await;
-        let result = sup.handle(&query).await;
-
-        match result {
-            Ok(res) => {
-                app_handle.emit("nexus-event", format!("FINAL_ANSWER:{}", res.answer)).unwrap();
-                if let Some(pub_obj) = res.publication {
-                    app_handle.emit("nexus-event", format!("RELIABILITY:{}", pub_obj.reliability)).unwrap();
-                    let assurance = serde_json::json!({
-                        "latency": pub_obj.telemetry.latency_ms,
-                        "tools": pub_obj.telemetry.tool_calls,
-                        "evidence": pub_obj.telemetry.evidence_count,
-                        "scale": format!("{:?}", pub_obj.telemetry.scale),
-                        "model": pub_obj.telemetry.model
-                    });
-                    app_handle.emit("nexus-event", format!("ASSURANCE:{}", assurance)).unwrap();
+        let events = sup.handle_stream(&query);
+        tokio::pin!(events);
+        let mut splitter = AnswerSplitter::default();
+
+        // Typed events go out as `turn-event`; tokens and the answer are mirrored on `nexus-event`
+        while let Some(event) = events.next().await {
+            match event {
+                TurnEvent::TokenChunk { ref text } => {
+                    for message in stream_messages(&mut splitter, text) {
+                        let _ = app_handle.emit("nexus-event", message);
+                    }
                 }
-                
-                // Speak the answer
-                let to_speak = res.answer.clone();
-                let spk = speaker.clone();
-                tokio::spawn(async move {
-                    let mut s = spk.lock().await;
-                    let _ = s.say(&to_speak).await;
-                });
-            }
-            Err(e) => {
-                app_handle.emit("nexus-event", format!("ANSWER:Error: {}", e)).unwrap();
+                TurnEvent::FinalAnswer { ref answer, ref publication, .. } => {
+                    app_handle.emit("nexus-event", format!("FINAL_ANSWER:{}", answer)).unwrap();
+                    notifications::notify(&app_handle, "Task complete", answer);
+                    if let Some(pub_obj) = publication {
+                        app_handle.emit("nexus-event", format!("RELIABILITY:{}", pub_obj.reliability)).unwrap();
+                        let assurance = serde_json::json!({
+                            "latency": pub_obj.telemetry.latency_ms,
+                            "tools": pub_obj.telemetry.tool_calls,
+                            "evidence": pub_obj.telemetry.evidence_count,
+                            "scale": format!("{:?}", pub_obj.telemetry.scale),
+                            "model": pub_obj.telemetry.model
+                        });
+                        app_handle.emit("nexus-event", format!("ASSURANCE:{}", assurance)).unwrap();
+                    }
+
+                    // Speak the answer
+                    if tts {
+                        let to_speak = answer.clone();
+                        let spk = speaker.clone();
+                        tokio::spawn(async move {
+                            let mut s = spk.lock().await;
+                            let _ = s.say(&to_speak).await;
+                        });
+                    }
+                }
+                TurnEvent::Error { ref message } => {
+                    app_handle.emit("nexus-event", format!("ANSWER:Error: {}", message)).unwrap();
+                    notifications::notify(&app_handle, "Task failed", message);
+                }
+                _ => {}
             }
+            let _ = app_handle.emit("turn-event", &event);
         }
         app_handle.emit("nexus-event", "STATE:TURN_COMPLETE").unwrap();
     });
@@ -79,30 +133,464 @@ async fn send_query(
     Ok(())
 }
 
+/// `nexus-event` messages for a streamed token: `THOUGHT:` until the answer marker, then
+/// `STATE:ANSWER_START` and `ANSWER:`
+fn stream_messages(splitter: &mut AnswerSplitter, text: &str) -> Vec<String> {
+    splitter.push(text).into_iter().map(|chunk| match chunk {
+        StreamChunk::Thought(text) => format!("THOUGHT:{}", text),
+        StreamChunk::AnswerStart => "STATE:ANSWER_START".to_string(),
+        StreamChunk::Answer(text) => format!("ANSWER:{}", text),
+    }).collect()
+}
+
+/// A turn in conversation `session_id`, reported as `session-event` payloads
+/// (`{ "session_id", "message" }`) carrying the same messages as `nexus-event`
+#[tauri::command]
+async fn send_session_query(
+    session_id: String,
+    query: String,
+    state: tauri::State<'_, AgencyState>,
+    app: tauri::AppHandle
+) -> Result<(), String> {
+    let supervisor = state.conversations.get(&session_id).await.map_err(|e| e.to_string())?;
+    if let Some(handle) = state.session_tasks.lock().await.remove(&session_id) {
+        handle.abort();
+    }
+
+    let id = session_id.clone();
+    let handle = KILL_SWITCH.spawn(async move {
+        let emit = |message: String| {
+            let _ = app.emit("session-event", serde_json::json!({ "session_id": id, "message": message }));
+        };
+        let mut supervisor = supervisor.lock().await;
+        let events = supervisor.handle_stream(&query);
+        tokio::pin!(events);
+        let mut splitter = AnswerSplitter::default();
+        while let Some(event) = events.next().await {
+            match event {
+                TurnEvent::TokenChunk { text } => stream_messages(&mut splitter, &text).into_iter().for_each(&emit),
+                TurnEvent::FinalAnswer { answer, .. } => {
+                    notifications::notify(&app, "Task complete", &answer);
+                    emit(format!("FINAL_ANSWER:{}", answer));
+                }
+                TurnEvent::Error { message } => {
+                    notifications::notify(&app, "Task failed", &message);
+                    emit(format!("ANSWER:Error: {}", message));
+                }
+                _ => {}
+            }
+        }
+        emit("STATE:TURN_COMPLETE".to_string());
+    });
+    state.session_tasks.lock().await.insert(session_id, handle.abort_handle());
+    Ok(())
+}
+
+/// Open and saved conversations, most recently updated first
+#[tauri::command]
+async fn list_sessions(state: tauri::State<'_, AgencyState>) -> Result<Vec<ConversationSummary>, String> {
+    state.conversations.summaries().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn create_session(title: Option<String>, state: tauri::State<'_, AgencyState>) -> Result<ConversationSummary, String> {
+    state.conversations.create(title.as_deref()).await.map_err(|e| e.to_string())
+}
+
+/// Send later `send_query` calls to conversation `session_id` (`None` for the main one); returns its messages
+#[tauri::command]
+async fn switch_session(session_id: Option<String>, state: tauri::State<'_, AgencyState>) -> Result<Vec<ConversationTurn>, String> {
+    let episodic_memory = match session_id {
+        Some(ref id) => {
+            let supervisor = state.conversations.get(id).await.map_err(|e| e.to_string())?;
+            let episodic_memory = supervisor.lock().await.episodic_memory.clone();
+            episodic_memory
+        }
+        None => state.episodic_memory.clone(),
+    };
+    *state.active_session.lock().await = session_id;
+    let turns = episodic_memory.lock().await.get_turns();
+    Ok(turns)
+}
+
+/// Rename a conversation; an empty title goes back to its first message
+#[tauri::command]
+async fn rename_session(session_id: String, title: String, state: tauri::State<'_, AgencyState>) -> Result<ConversationSummary, String> {
+    state.conversations.rename(&session_id, Some(&title)).await.map_err(|e| e.to_string())?;
+    Ok(state.conversations.summary(&session_id).await)
+}
+
+/// Stop any running turn and delete the conversation with its saved history
+#[tauri::command]
+async fn delete_session(session_id: String, state: tauri::State<'_, AgencyState>) -> Result<bool, String> {
+    if let Some(handle) = state.session_tasks.lock().await.remove(&session_id) {
+        handle.abort();
+    }
+    let mut active = state.active_session.lock().await;
+    if active.as_deref() == Some(session_id.as_str()) {
+        *active = None;
+    }
+    drop(active);
+    state.conversations.delete(&session_id).await.map_err(|e| e.to_string())
+}
+
+/// Stop any running turn and close the conversation; its saved history is kept
+#[tauri::command]
+async fn close_session(session_id: String, state: tauri::State<'_, AgencyState>) -> Result<bool, String> {
+    if let Some(handle) = state.session_tasks.lock().await.remove(&session_id) {
+        handle.abort();
+    }
+    Ok(state.conversations.close(&session_id).await)
+}
+
 #[tauri::command]
 async fn stop_inference(state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
     let mut task_guard = state.current_task.lock().await;
     if let Some(handle) = task_guard.take() {
+        // Aborting the turn drops its model stream, which ends the provider's generation, and
+        // its in-flight tool futures; also wake calls awaiting elsewhere
         handle.abort();
+        state.tools.cancel_running();
         app.emit("nexus-event", "STATE:STOPPED").unwrap();
     }
     Ok(())
 }
 
+/// Emergency stop: abort every turn, sub-agent and tool process until `rearm_kill_switch`
+#[tauri::command]
+async fn panic_stop(reason: Option<String>, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<usize, String> {
+    let reason = reason.unwrap_or_else(|| "Panic stop from desktop".to_string());
+    // Running turns hold the supervisor lock; engaging first makes them return
+    let mut aborted = KILL_SWITCH.engage(reason.as_str());
+    state.current_task.lock().await.take();
+    state.session_tasks.lock().await.clear();
+    aborted += state.supervisor.lock().await.panic_stop(&reason).await;
+    app.emit("nexus-event", "STATE:HALTED").unwrap();
+    tray::refresh(&app).await;
+    Ok(aborted)
+}
+
+#[tauri::command]
+async fn rearm_kill_switch(state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
+    state.supervisor.lock().await.rearm();
+    app.emit("nexus-event", "STATE:REARMED").unwrap();
+    tray::refresh(&app).await;
+    Ok(())
+}
+
+/// Tool calls waiting for a decision; new ones also arrive as `approval-request` events
+#[tauri::command]
+async fn list_pending_approvals(state: tauri::State<'_, AgencyState>) -> Result<Vec<rust_agency::safety::PendingApproval>, String> {
+    let approvals = state.supervisor.lock().await.approvals.clone();
+    Ok(approvals.pending().await)
+}
+
+/// Approve a queued tool call; the resumed turn reports through `nexus-event` like `send_query`
+#[tauri::command]
+async fn approve_action(id: String, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
+    let supervisor = approval_owner(&state, &id).await?;
+    let handle = tokio::spawn(async move {
+        app.emit("approval-resolved", serde_json::json!({ "id": id, "approved": true })).unwrap();
+        app.emit("nexus-event", "🔓 Approval granted: resuming turn...").unwrap();
+        match supervisor.lock().await.approve(&id).await {
+            Ok(res) => app.emit("nexus-event", format!("FINAL_ANSWER:{}", res.answer)).unwrap(),
+            Err(e) => app.emit("nexus-event", format!("ANSWER:Error: {}", e)).unwrap(),
+        }
+        app.emit("nexus-event", "STATE:TURN_COMPLETE").unwrap();
+    });
+    *state.current_task.lock().await = Some(handle.abort_handle());
+    Ok(())
+}
+
+#[tauri::command]
+async fn reject_action(id: String, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
+    let supervisor = approval_owner(&state, &id).await?;
+    let result = supervisor.lock().await.reject(&id).await;
+    result.map_err(|e| e.to_string())?;
+    app.emit("approval-resolved", serde_json::json!({ "id": id, "approved": false })).unwrap();
+    Ok(())
+}
+
+// Older names of the approval commands
+#[tauri::command]
+async fn list_approvals(state: tauri::State<'_, AgencyState>) -> Result<Vec<rust_agency::safety::PendingApproval>, String> {
+    list_pending_approvals(state).await
+}
+
+#[tauri::command]
+async fn approve_tool_call(id: String, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
+    approve_action(id, state, app).await
+}
+
+#[tauri::command]
+async fn reject_tool_call(id: String, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<(), String> {
+    reject_action(id, state, app).await
+}
+
+/// Forward every newly queued approval, from any conversation, as an `approval-request` event
+fn forward_approval_requests(app: tauri::AppHandle, approvals: Arc<rust_agency::safety::ApprovalQueue>) {
+    tauri::async_runtime::spawn(async move {
+        let mut events = rust_agency::orchestrator::AGENCY_EVENT_BUS.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(rust_agency::orchestrator::AgencyEvent::ApprovalRequested { id, .. }) => {
+                    if let Some(pending) = approvals.get(&id).await {
+                        let _ = app.emit("approval-request", pending);
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// The supervisor of the conversation a queued approval belongs to
+async fn approval_owner(state: &AgencyState, id: &str) -> Result<Arc<Mutex<Supervisor>>, String> {
+    let entry = state.supervisor.lock().await.approvals.get(id).await;
+    match entry.and_then(|e| e.conversation) {
+        Some(conversation) => state.conversations.get(&conversation).await.map_err(|e| e.to_string()),
+        None => Ok(state.supervisor.clone()),
+    }
+}
+
 #[tauri::command]
 async fn clear_memory(state: tauri::State<'_, AgencyState>) -> Result<(), String> {
     state.supervisor.lock().await.clear_history().await.map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Memories matching `query`, best first, or the most recent when it is empty
+#[tauri::command]
+async fn memory_search(query: String, limit: Option<usize>, state: tauri::State<'_, AgencyState>) -> Result<Vec<rust_agency::memory::MemoryEntry>, String> {
+    let limit = limit.unwrap_or(20);
+    let entries = if query.trim().is_empty() {
+        state.memory.get_recent(limit).await
+    } else {
+        state.memory.search(&query, limit, None, None).await
+    };
+    // Embeddings are of no use to the UI and dwarf the text
+    Ok(entries.map_err(|e| e.to_string())?.into_iter().map(|e| rust_agency::memory::MemoryEntry { embedding: None, ..e }).collect())
+}
+
+/// Forget the memories with `ids`, returning how many were removed
+#[tauri::command]
+async fn memory_delete(ids: Vec<String>, state: tauri::State<'_, AgencyState>) -> Result<usize, String> {
+    let before = state.memory.count().await.map_err(|e| e.to_string())?;
+    state.memory.prune(ids).await.map_err(|e| e.to_string())?;
+    state.memory.persist().await.map_err(|e| e.to_string())?;
+    let after = state.memory.count().await.map_err(|e| e.to_string())?;
+    Ok(before.saturating_sub(after))
+}
+
+#[derive(Serialize)]
+struct MemoryStats {
+    total: usize,
+    /// Entries in RAM; the rest have been consolidated to disk
+    hot: usize,
+    /// Breakdowns of the hot entries
+    by_source: BTreeMap<String, usize>,
+    by_kind: BTreeMap<String, usize>,
+    by_agent: BTreeMap<String, usize>,
+    oldest: Option<chrono::DateTime<chrono::Utc>>,
+    newest: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[tauri::command]
+async fn memory_stats(state: tauri::State<'_, AgencyState>) -> Result<MemoryStats, String> {
+    let total = state.memory.count().await.map_err(|e| e.to_string())?;
+    let entries = state.memory.get_recent(usize::MAX).await.map_err(|e| e.to_string())?;
+    let label = |value: serde_json::Value| value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+    let mut stats = MemoryStats {
+        total,
+        hot: entries.len(),
+        by_source: BTreeMap::new(),
+        by_kind: BTreeMap::new(),
+        by_agent: BTreeMap::new(),
+        oldest: entries.iter().map(|e| e.timestamp).min(),
+        newest: entries.iter().map(|e| e.timestamp).max(),
+    };
+    for entry in &entries {
+        *stats.by_source.entry(label(serde_json::json!(entry.metadata.source))).or_default() += 1;
+        *stats.by_kind.entry(label(serde_json::json!(entry.metadata.kind))).or_default() += 1;
+        *stats.by_agent.entry(entry.metadata.agent.clone()).or_default() += 1;
+    }
+    Ok(stats)
+}
+
+#[tauri::command]
+async fn get_profile(state: tauri::State<'_, AgencyState>) -> Result<AgencyProfile, String> {
+    state.profile.load().await.map_err(|e| e.to_string())
+}
+
+/// Problems with `profile`; empty when it can be applied
+#[tauri::command]
+async fn validate_profile(profile: serde_json::Value) -> Result<Vec<String>, String> {
+    Ok(match serde_json::from_value::<AgencyProfile>(profile) {
+        Ok(profile) => profile.validate().err().unwrap_or_default(),
+        Err(e) => vec![e.to_string()],
+    })
+}
+
+/// Save and hot-apply the profile; returns the changed fields that still need a restart
+#[tauri::command]
+async fn update_profile(profile: AgencyProfile, state: tauri::State<'_, AgencyState>, app: tauri::AppHandle) -> Result<Vec<&'static str>, String> {
+    profile.validate().map_err(|errors| errors.join("; "))?;
+    let previous = state.profile.load().await.map_err(|e| e.to_string())?;
+    state.profile.save(&profile).await.map_err(|e| e.to_string())?;
+    apply_profile(state.supervisor.clone(), profile.clone());
+    state.conversations.set_profile(&profile).await;
+    app.emit("nexus-event", "STATE:PROFILE_UPDATED").unwrap();
+    Ok(profile.restart_required(&previous))
+}
+
+#[derive(Serialize)]
+struct SettingsView {
+    #[serde(flatten)]
+    settings: DesktopSettings,
+    /// From the agency profile
+    default_models: HashMap<AgentType, String>,
+    /// Whether each API key is set; the keys never leave the keychain
+    api_keys: BTreeMap<String, bool>,
+}
+
+#[tauri::command]
+async fn get_settings(state: tauri::State<'_, AgencyState>) -> Result<SettingsView, String> {
+    let profile = state.profile.load().await.map_err(|e| e.to_string())?;
+    Ok(SettingsView {
+        settings: state.settings.lock().await.clone(),
+        default_models: profile.default_models,
+        api_keys: settings::api_key_status(),
+    })
+}
+
+/// Save and apply settings. `api_keys` maps variables such as `OPENAI_API_KEY` to new keys (empty
+/// deletes one); `default_models` replaces the profile's. Returns the changed fields that still
+/// need a restart.
+#[tauri::command]
+async fn set_settings(
+    settings: DesktopSettings,
+    api_keys: Option<HashMap<String, String>>,
+    default_models: Option<HashMap<AgentType, String>>,
+    state: tauri::State<'_, AgencyState>,
+    app: tauri::AppHandle,
+) -> Result<Vec<&'static str>, String> {
+    settings.validate().map_err(|errors| errors.join("; "))?;
+    let profile = match default_models {
+        Some(models) => {
+            let mut profile = state.profile.load().await.map_err(|e| e.to_string())?;
+            profile.default_models = models;
+            profile.validate().map_err(|errors| errors.join("; "))?;
+            Some(profile)
+        }
+        None => None,
+    };
+
+    let api_keys = api_keys.unwrap_or_default();
+    if let Some(var) = api_keys.keys().find(|var| !settings::API_KEY_VARS.contains(&var.as_str())) {
+        return Err(format!("Unknown API key '{}'", var));
+    }
+    for (var, key) in &api_keys {
+        settings::store_api_key(var, key)?;
+    }
+    let previous = state.settings.lock().await.clone();
+    if settings.push_to_talk != previous.push_to_talk {
+        voice::register_shortcut(&app, &settings.push_to_talk)?;
+    }
+    state.settings_store.save(&settings)?;
+    settings.apply_env();
+    if !api_keys.is_empty() || settings.provider != previous.provider || settings.provider_urls != previous.provider_urls {
+        // Later model calls of every conversation go to the new provider
+        state.provider.switch_to(create_provider_by_type(&settings.provider)).await;
+    }
+    if settings.ears {
+        start_ears();
+    }
+    if let Some(profile) = profile {
+        state.profile.save(&profile).await.map_err(|e| e.to_string())?;
+        apply_profile(state.supervisor.clone(), profile.clone());
+        state.conversations.set_profile(&profile).await;
+    }
+
+    tray::set_close_to_tray(settings.close_to_tray);
+
+    let restart_required = settings.restart_required(&previous);
+    *state.settings.lock().await = settings;
+    app.emit("nexus-event", "STATE:SETTINGS_UPDATED").unwrap();
+    tray::refresh(&app).await;
+    Ok(restart_required)
+}
+
+/// Register Markdown skills and dynamic tools, e.g. after installing them from a catalog
+async fn load_extensions(tools: &ToolRegistry) {
+    if let Ok(skills) = SkillLoader::discover_skills("skills").await {
+        for skill in skills {
+            tools.register_instance(skill).await;
+        }
+    }
+    let _ = tools.load_dynamic_tools("standard_tools").await;
+    let _ = tools.load_dynamic_tools("custom_tools").await;
+}
+
+#[derive(Serialize)]
+struct CatalogReport {
+    items: Vec<CatalogItem>,
+    installed: Vec<String>,
+}
+
+/// Compare the catalog at `source` (a git URL, or a URL or path to an `index.json`) with what is
+/// installed; items named in `install` are then installed and loaded, if all are trusted
+#[tauri::command]
+async fn sync_catalog(source: String, install: Option<Vec<String>>, state: tauri::State<'_, AgencyState>) -> Result<CatalogReport, String> {
+    let config = CatalogConfig::from_env();
+    let items = fetch_catalog(&source, &config).await.map_err(|e| format!("{:#}", e))?;
+    let installed = match install {
+        Some(names) if !names.is_empty() => {
+            let installed = install_catalog_items(&items, &names, &config).await.map_err(|e| format!("{:#}", e))?;
+            load_extensions(&state.tools).await;
+            installed
+        }
+        _ => Vec::new(),
+    };
+    Ok(CatalogReport { items, installed })
+}
+
+/// Start the embedded Whisper listener unless it already runs.
+/// Must run in a dedicated thread due to cpal !Send constraints on macOS
+fn start_ears() {
+    if EARS_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            if let Err(e) = rust_agency::services::listener::run_listener_server().await {
+                eprintln!("❌ Embedded Listener crashed: {}", e);
+            }
+        });
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_log::Builder::default().build())
     .plugin(tauri_plugin_shell::init())
+    .plugin(tauri_plugin_notification::init())
+    .plugin(tauri_plugin_global_shortcut::Builder::new().with_handler(voice::on_shortcut).build())
+    .on_window_event(|window, event| {
+        tray::on_window_event(window, event);
+        ingest::on_window_event(window, event);
+    })
     .setup(|app| {
         // Initialize Core Infrastructure
         let handle = app.handle().clone();
+        tray::build(&handle)?;
+        handle.manage(voice::PushToTalk::default());
+        notifications::forward_events(handle.clone());
         
         tauri::async_runtime::spawn(async move {
             // Load Env
@@ -112,9 +600,18 @@ pub fn run() {
             // Manually load .env since we might be in a bundle
             dotenv::dotenv().ok();
 
-            // DESKTOP DEFAULT: If no provider is specified, default to "ollama" for the standalone app.
-            if std::env::var("AGENCY_PROVIDER").is_err() {
-                std::env::set_var("AGENCY_PROVIDER", "ollama");
+            // Settings saved from the app override `.env`; without any the provider defaults to
+            // "ollama" for the standalone app
+            settings::load_api_keys();
+            let settings_store = Arc::new(SettingsStore::new("config/desktop_settings.json"));
+            let desktop_settings = settings_store.load().unwrap_or_else(|e| {
+                eprintln!("⚠️  {}; using defaults", e);
+                DesktopSettings::from_env()
+            });
+            desktop_settings.apply_env();
+            tray::set_close_to_tray(desktop_settings.close_to_tray);
+            if let Err(e) = voice::register_shortcut(&handle, &desktop_settings.push_to_talk) {
+                eprintln!("⚠️  Push-to-talk unavailable: {}", e);
             }
             if std::env::var("OLLAMA_HOST").is_err() {
                 std::env::set_var("OLLAMA_HOST", "http://localhost:11434");
@@ -153,8 +650,9 @@ pub fn run() {
             tools.register_instance(VisionTool::new()).await;
             tools.register_instance(ForgeTool::new("custom_tools", tools.clone())).await;
             tools.register_instance(SystemTool::new(manager.clone())).await;
+            load_extensions(&tools).await;
 
-            let profile_manager = ProfileManager::new("config/agency_profile.json");
+            let profile_manager = Arc::new(ProfileManager::new("config/agency_profile.json"));
             let profile = profile_manager.load().await.unwrap_or_default();
 
             let mut supervisor = Supervisor::new_with_provider(provider.clone(), tools.clone())
@@ -166,6 +664,8 @@ pub fn run() {
                 .with_max_retries(2);
 
             let _ = supervisor.load_session().await;
+            let conversations = Arc::new(ConversationRegistry::from_env(&supervisor));
+            forward_approval_requests(handle.clone(), supervisor.approvals.clone());
             let shared_supervisor = Arc::new(Mutex::new(supervisor));
 
             // Manage State
@@ -174,22 +674,22 @@ pub fn run() {
                 speaker: shared_speaker,
                 current_task: Arc::new(Mutex::new(None)),
                 episodic_memory,
+                tools,
+                memory,
+                conversations,
+                session_tasks: Arc::new(Mutex::new(HashMap::new())),
+                active_session: Arc::new(Mutex::new(None)),
+                profile: profile_manager,
+                provider,
+                settings: Arc::new(Mutex::new(desktop_settings.clone())),
+                settings_store,
             });
 
+            tray::refresh(&handle).await;
+
             // EMBEDDED SERVICE: Listener (Whisper)
-            // Must run in a dedicated thread due to cpal !Send constraints on macOS
-            if std::env::var("AGENCY_ENABLE_EARS").unwrap_or_default() == "1" {
-                std::thread::spawn(|| {
-                    let rt = tokio::runtime::Builder::new_current_thread()
-                        .enable_all()
-                        .build()
-                        .unwrap();
-                    rt.block_on(async {
-                        if let Err(e) = rust_agency::services::listener::run_listener_server().await {
-                            eprintln!("❌ Embedded Listener crashed: {}", e);
-                        }
-                    });
-                });
+            if desktop_settings.ears {
+                start_ears();
             }
 
             // ──────────────────────────────────────────────────────────────────────────
@@ -231,7 +731,7 @@ pub fn run() {
 
         Ok(())
     })
-    .invoke_handler(tauri::generate_handler![send_query, stop_inference, clear_memory])
+    .invoke_handler(tauri::generate_handler![send_query, send_session_query, list_sessions, create_session, switch_session, rename_session, delete_session, close_session, stop_inference, panic_stop, rearm_kill_switch, clear_memory, memory_search, memory_delete, memory_stats, list_pending_approvals, approve_action, reject_action, list_approvals, approve_tool_call, reject_tool_call, get_profile, validate_profile, update_profile, get_settings, set_settings, sync_catalog])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
\ No newline at end of file
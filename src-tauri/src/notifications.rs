@@ -0,0 +1,74 @@
+//! Native Notifications
+//!
+//! OS notifications for work that finishes or needs attention while the window is unfocused
+//! or hidden to the tray: finished turns, scheduled runs, webhooks and sub-agents, tool calls
+//! waiting for approval and exceeded budgets. Agency-wide ones come from `AGENCY_EVENT_BUS`.
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast;
+
+use rust_agency::orchestrator::{AgencyEvent, AGENCY_EVENT_BUS};
+
+/// Longest notification body; answers are cut short
+const BODY_LEN: usize = 200;
+
+/// Title and body for the events worth a notification
+fn notification_for(event: &AgencyEvent) -> Option<(String, String)> {
+    let outcome = |success: bool| if success { "finished" } else { "failed" };
+    match event {
+        AgencyEvent::ApprovalRequested { tool, .. } => {
+            Some(("Approval needed".to_string(), format!("{} is waiting for your approval", tool)))
+        }
+        AgencyEvent::BudgetStatus { scope, exceeded: Some(cap), .. } => {
+            Some(("Budget exceeded".to_string(), format!("{:?} {} cap reached; work stopped", scope, cap)))
+        }
+        AgencyEvent::ScheduledRunFinished { name, success, answer, .. } => {
+            Some((format!("Scheduled run {}: {}", outcome(*success), name), answer.clone()))
+        }
+        AgencyEvent::WebhookHandled { hook_id, success, answer, .. } => {
+            Some((format!("Webhook {}: {}", outcome(*success), hook_id), answer.clone()))
+        }
+        AgencyEvent::SubAgentFinished { task_id, success, answer, .. } => {
+            Some((format!("Sub-agent {}: {}", outcome(*success), task_id), answer.clone()))
+        }
+        _ => None,
+    }
+}
+
+fn window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .is_some_and(|window| window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false))
+}
+
+/// Show a notification unless the user is looking at the app
+pub fn notify(app: &AppHandle, title: &str, body: &str) {
+    if window_focused(app) {
+        return;
+    }
+    let body: String = match body.char_indices().nth(BODY_LEN) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    };
+    if let Err(e) = app.notification().builder().title(title).body(body.trim()).show() {
+        eprintln!("⚠️  Notification failed: {}", e);
+    }
+}
+
+/// Notify about agency events for as long as the app runs
+pub fn forward_events(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut events = AGENCY_EVENT_BUS.subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Some((title, body)) = notification_for(&event) {
+                        notify(&app, &title, &body);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
@@ -0,0 +1,175 @@
+//! Desktop Settings
+//!
+//! Provider, provider URLs, the voice toggles, close-to-tray and the push-to-talk shortcut, saved to `config/desktop_settings.json`, plus
+//! API keys kept in the OS keychain rather than `.env`. Providers read their configuration from
+//! the environment, so applying settings sets the same variables `.env` would and rebuilds the
+//! provider.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Keychain service the API keys are stored under (the bundle identifier)
+const KEYCHAIN_SERVICE: &str = "com.agency.sovereign";
+
+/// API keys the providers read, each stored as its own keychain entry
+pub const API_KEY_VARS: &[&str] = &[
+    "OPENAI_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "GEMINI_API_KEY",
+    "ZAI_API_KEY",
+    "OLLAMA_API_KEY",
+    "LLAMACPP_API_KEY",
+];
+
+/// Provider endpoints that can be set from the app
+pub const URL_VARS: &[&str] = &["OLLAMA_HOST", "OPENAI_BASE_URL", "ANTHROPIC_BASE_URL", "GEMINI_BASE_URL", "LLAMACPP_BASE_URL"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopSettings {
+    /// As in `AGENCY_PROVIDER`, e.g. `ollama`, `openai`, `anthropic`
+    pub provider: String,
+    /// Endpoint URLs keyed by variable name (one of `URL_VARS`)
+    pub provider_urls: BTreeMap<String, String>,
+    /// Speak final answers
+    pub tts: bool,
+    /// Run the embedded Whisper listener
+    pub ears: bool,
+    /// Closing the window hides it to the tray instead of quitting
+    pub close_to_tray: bool,
+    /// Global shortcut held to talk, e.g. `CommandOrControl+Shift+Space`; empty disables
+    pub push_to_talk: String,
+}
+
+impl Default for DesktopSettings {
+    fn default() -> Self {
+        Self {
+            provider: "ollama".to_string(),
+            provider_urls: BTreeMap::new(),
+            tts: true,
+            ears: false,
+            close_to_tray: true,
+            push_to_talk: "CommandOrControl+Shift+Space".to_string(),
+        }
+    }
+}
+
+impl DesktopSettings {
+    /// What the environment (and `.env`) configures, for the first start
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: std::env::var("AGENCY_PROVIDER").unwrap_or(defaults.provider),
+            provider_urls: URL_VARS.iter()
+                .filter_map(|var| std::env::var(var).ok().map(|url| (var.to_string(), url)))
+                .collect(),
+            tts: defaults.tts,
+            ears: std::env::var("AGENCY_ENABLE_EARS").unwrap_or_default() == "1",
+            close_to_tray: defaults.close_to_tray,
+            push_to_talk: defaults.push_to_talk,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.provider.trim().is_empty() {
+            errors.push("provider must not be empty".to_string());
+        }
+        for (var, url) in &self.provider_urls {
+            if !URL_VARS.contains(&var.as_str()) {
+                errors.push(format!("provider_urls.{}: unknown setting", var));
+            } else if !(url.starts_with("http://") || url.starts_with("https://")) {
+                errors.push(format!("provider_urls.{}: '{}' is not an http(s) URL", var, url));
+            }
+        }
+        if !self.push_to_talk.trim().is_empty() {
+            if let Err(e) = self.push_to_talk.parse::<tauri_plugin_global_shortcut::Shortcut>() {
+                errors.push(format!("push_to_talk: '{}' is not a shortcut ({})", self.push_to_talk, e));
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Set the variables the providers read
+    pub fn apply_env(&self) {
+        std::env::set_var("AGENCY_PROVIDER", &self.provider);
+        for (var, url) in &self.provider_urls {
+            std::env::set_var(var, url);
+        }
+    }
+
+    /// Changed fields that only take effect after a restart
+    pub fn restart_required(&self, previous: &DesktopSettings) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        // A running listener cannot be stopped; starting one needs no restart
+        if previous.ears && !self.ears {
+            fields.push("ears");
+        }
+        fields
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+}
+
+impl SettingsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Saved settings, or the environment's before the first save
+    pub fn load(&self) -> Result<DesktopSettings, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", self.path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DesktopSettings::from_env()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn save(&self, settings: &DesktopSettings) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| e.to_string())
+    }
+}
+
+fn keychain_entry(var: &str) -> Result<keyring::Entry, String> {
+    if !API_KEY_VARS.contains(&var) {
+        return Err(format!("Unknown API key '{}'", var));
+    }
+    keyring::Entry::new(KEYCHAIN_SERVICE, var).map_err(|e| e.to_string())
+}
+
+/// Store `key` for `var` in the keychain and the environment; an empty key deletes it
+pub fn store_api_key(var: &str, key: &str) -> Result<(), String> {
+    let entry = keychain_entry(var)?;
+    if key.is_empty() {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        std::env::remove_var(var);
+    } else {
+        entry.set_password(key).map_err(|e| e.to_string())?;
+        std::env::set_var(var, key);
+    }
+    Ok(())
+}
+
+/// Which API keys are available, from the keychain or the environment; never the keys themselves
+pub fn api_key_status() -> BTreeMap<String, bool> {
+    API_KEY_VARS.iter().map(|var| (var.to_string(), std::env::var(var).is_ok_and(|k| !k.is_empty()))).collect()
+}
+
+/// Put keychain keys into the environment, over any from `.env`
+pub fn load_api_keys() {
+    for var in API_KEY_VARS {
+        if let Ok(key) = keychain_entry(var).and_then(|entry| entry.get_password().map_err(|e| e.to_string())) {
+            std::env::set_var(var, key);
+        }
+    }
+}
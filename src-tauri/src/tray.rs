@@ -0,0 +1,137 @@
+//! System Tray
+//!
+//! Tray icon with quick actions: show the window, start a new query, pause the agency (the
+//! kill switch) and mute speech. With `close_to_tray` set, closing the window hides it while
+//! the Supervisor and listener keep running; "Quit" in the tray ends the app.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Window, WindowEvent, Wry};
+
+use rust_agency::orchestrator::KILL_SWITCH;
+
+use crate::AgencyState;
+
+/// Whether closing the window hides it; follows the `close_to_tray` setting
+static CLOSE_TO_TRAY: AtomicBool = AtomicBool::new(true);
+
+/// Toggles whose checkmarks follow the agency's state
+struct TrayToggles {
+    pause: CheckMenuItem<Wry>,
+    mute: CheckMenuItem<Wry>,
+}
+
+pub fn set_close_to_tray(enabled: bool) {
+    CLOSE_TO_TRAY.store(enabled, Ordering::SeqCst);
+}
+
+pub fn build(app: &AppHandle) -> tauri::Result<TrayIcon> {
+    let show = MenuItem::with_id(app, "show", "Show Agency", true, None::<&str>)?;
+    let new_query = MenuItem::with_id(app, "new_query", "New Query", true, None::<&str>)?;
+    let pause = CheckMenuItem::with_id(app, "pause", "Pause Agency", true, KILL_SWITCH.is_engaged(), None::<&str>)?;
+    let mute = CheckMenuItem::with_id(app, "mute", "Mute Speech", true, false, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[
+        &show,
+        &new_query,
+        &PredefinedMenuItem::separator(app)?,
+        &pause,
+        &mute,
+        &PredefinedMenuItem::separator(app)?,
+        &quit,
+    ])?;
+    app.manage(TrayToggles { pause, mute });
+
+    let mut tray = TrayIconBuilder::with_id("agency")
+        .tooltip("Agency")
+        .menu(&menu)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)
+}
+
+/// Hide instead of closing, so the agency keeps running in the background
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if CLOSE_TO_TRAY.load(Ordering::SeqCst) {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Sync the checkmarks with the kill switch and the speech setting
+pub async fn refresh(app: &AppHandle) {
+    let (Some(toggles), Some(state)) = (app.try_state::<TrayToggles>(), app.try_state::<AgencyState>()) else { return };
+    let tts = state.settings.lock().await.tts;
+    let _ = toggles.pause.set_checked(KILL_SWITCH.is_engaged());
+    let _ = toggles.mute.set_checked(!tts);
+}
+
+fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id.as_ref() {
+        "show" => show_window(app),
+        "new_query" => {
+            show_window(app);
+            let _ = app.emit("nexus-event", "STATE:NEW_QUERY");
+        }
+        "pause" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                toggle_pause(&app).await;
+                refresh(&app).await;
+            });
+        }
+        "mute" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                toggle_mute(&app).await;
+                refresh(&app).await;
+            });
+        }
+        "quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+async fn toggle_pause(app: &AppHandle) {
+    // Commands are still starting up until the state is managed
+    if app.try_state::<AgencyState>().is_none() {
+        return;
+    }
+    let result = if KILL_SWITCH.is_engaged() {
+        crate::rearm_kill_switch(app.state(), app.clone()).await
+    } else {
+        crate::panic_stop(Some("Paused from the tray".to_string()), app.state(), app.clone()).await.map(|_| ())
+    };
+    if let Err(e) = result {
+        eprintln!("⚠️  Tray pause failed: {}", e);
+    }
+}
+
+async fn toggle_mute(app: &AppHandle) {
+    let Some(state) = app.try_state::<AgencyState>() else { return };
+    let mut settings = state.settings.lock().await;
+    settings.tts = !settings.tts;
+    if let Err(e) = state.settings_store.save(&settings) {
+        eprintln!("⚠️  Could not save settings: {}", e);
+    }
+    drop(settings);
+    let _ = app.emit("nexus-event", "STATE:SETTINGS_UPDATED");
+}
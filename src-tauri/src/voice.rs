@@ -0,0 +1,89 @@
+//! Push-to-Talk
+//!
+//! A global shortcut (the `push_to_talk` setting) records from the microphone while held and
+//! sends the transcription to `send_query`, for hands-free use without the always-on listener.
+//! Whisper is loaded on the first use.
+
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tokio::sync::OnceCell;
+
+use rust_agency::services::listener::{Recording, WhisperTranscriber};
+
+#[derive(Default)]
+pub struct PushToTalk {
+    transcriber: OnceCell<Arc<WhisperTranscriber>>,
+    recording: std::sync::Mutex<Option<Recording>>,
+}
+
+impl PushToTalk {
+    async fn transcriber(&self) -> Result<Arc<WhisperTranscriber>, String> {
+        self.transcriber.get_or_try_init(|| async {
+            tokio::task::spawn_blocking(WhisperTranscriber::load).await
+                .map_err(|e| e.to_string())?
+                .map(Arc::new)
+                .map_err(|e| e.to_string())
+        }).await.cloned()
+    }
+}
+
+/// Bind `shortcut` (e.g. `CommandOrControl+Shift+Space`) in place of any earlier one; empty unbinds
+pub fn register_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| e.to_string())?;
+    if shortcut.trim().is_empty() {
+        return Ok(());
+    }
+    let shortcut: Shortcut = shortcut.parse().map_err(|e| format!("Invalid shortcut '{}': {}", shortcut, e))?;
+    shortcuts.register(shortcut).map_err(|e| e.to_string())
+}
+
+/// Pressing starts a recording, releasing transcribes it and runs the query
+pub fn on_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    let Some(ptt) = app.try_state::<PushToTalk>() else { return };
+    let mut recording = ptt.recording.lock().unwrap();
+    match event.state() {
+        // Key repeat presses again while held
+        ShortcutState::Pressed if recording.is_none() => match Recording::start() {
+            Ok(started) => {
+                *recording = Some(started);
+                let _ = app.emit("nexus-event", "STATE:LISTENING");
+            }
+            Err(e) => {
+                let _ = app.emit("nexus-event", format!("ANSWER:Error: Push-to-talk: {}", e));
+            }
+        },
+        ShortcutState::Released => {
+            if let Some(finished) = recording.take() {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = app.emit("nexus-event", "STATE:TRANSCRIBING");
+                    if let Err(e) = transcribe_and_send(&app, finished).await {
+                        let _ = app.emit("nexus-event", format!("ANSWER:Error: Push-to-talk: {}", e));
+                    }
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+async fn transcribe_and_send(app: &AppHandle, recording: Recording) -> Result<(), String> {
+    let (pcm, sample_rate) = tokio::task::spawn_blocking(move || recording.stop()).await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let transcriber = app.state::<PushToTalk>().transcriber().await?;
+    let text = transcriber.transcribe(pcm, sample_rate).await.map_err(|e| e.to_string())?;
+    let text = text.trim();
+    if text.len() < 2 {
+        let _ = app.emit("nexus-event", "STATE:NO_SPEECH");
+        return Ok(());
+    }
+    let _ = app.emit("nexus-event", format!("TRANSCRIPT:{}", text));
+    // Commands are still starting up until the state is managed
+    if app.try_state::<crate::AgencyState>().is_none() {
+        return Err("the agency is still starting".to_string());
+    }
+    crate::send_query(text.to_string(), app.state(), app.clone()).await
+}
@@ -166,6 +166,8 @@ impl AutonomousMachine {
             total_reward,
             extrinsic_reward,
             intrinsic_reward,
+            turn_id: None,
+            feedback: None,
         });
 
         // FPF Integration: Update Ledger and Portfolio
@@ -0,0 +1,270 @@
+//! Provider Load Balancing
+//!
+//! Spreads requests across several equivalent `LLMProvider` endpoints (e.g. multiple Ollama
+//! instances) with round-robin or least-latency selection. Endpoints that fail repeatedly are
+//! taken out of rotation for a cooldown period and requests fail over to the next candidate.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use super::provider::{ChatMessage, GenerationOptions, LLMProvider};
+
+/// How the next endpoint is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStrategy {
+    RoundRobin,
+    LeastLatency,
+}
+
+/// Health snapshot of a single endpoint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EndpointHealth {
+    pub consecutive_failures: u32,
+    /// Exponentially weighted moving average of request latency
+    pub avg_latency_ms: f64,
+    pub in_flight: usize,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    #[serde(skip)]
+    last_failure: Option<Instant>,
+}
+
+/// Provider that balances requests over N endpoints with per-endpoint health tracking
+pub struct BalancedProvider {
+    endpoints: Vec<Arc<dyn LLMProvider>>,
+    health: Vec<StdMutex<EndpointHealth>>,
+    strategy: BalanceStrategy,
+    next: AtomicUsize,
+    failure_threshold: u32,
+    cooldown: Duration,
+    lock: Arc<Mutex<()>>,
+}
+
+impl BalancedProvider {
+    pub fn new(endpoints: Vec<Arc<dyn LLMProvider>>, strategy: BalanceStrategy) -> Self {
+        let health = endpoints.iter().map(|_| StdMutex::new(EndpointHealth::default())).collect();
+        Self {
+            endpoints,
+            health,
+            strategy,
+            next: AtomicUsize::new(0),
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+            // Endpoints are independent machines, so don't serialise on a shared hardware lock
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = threshold.max(1);
+        self
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Current health of every endpoint, in registration order
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.health.iter().map(|h| h.lock().unwrap().clone()).collect()
+    }
+
+    fn is_available(&self, idx: usize) -> bool {
+        let h = self.health[idx].lock().unwrap();
+        h.consecutive_failures < self.failure_threshold
+            || h.last_failure.map(|t| t.elapsed() >= self.cooldown).unwrap_or(true)
+    }
+
+    /// Endpoint indices in the order they should be tried for the next request
+    fn candidates(&self) -> Vec<usize> {
+        let n = self.endpoints.len();
+        let mut order: Vec<usize> = match self.strategy {
+            BalanceStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed) % n.max(1);
+                (0..n).map(|i| (start + i) % n).collect()
+            }
+            BalanceStrategy::LeastLatency => {
+                let mut scored: Vec<(usize, f64)> = (0..n).map(|i| {
+                    let h = self.health[i].lock().unwrap();
+                    // Queue depth counts as extra latency so a fast endpoint isn't flooded
+                    (i, h.avg_latency_ms * (1.0 + h.in_flight as f64))
+                }).collect();
+                scored.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                scored.into_iter().map(|(i, _)| i).collect()
+            }
+        };
+        // Unhealthy endpoints are kept as a last resort rather than dropped
+        order.sort_by_key(|&i| !self.is_available(i));
+        order
+    }
+
+    fn record_start(&self, idx: usize) {
+        let mut h = self.health[idx].lock().unwrap();
+        h.in_flight += 1;
+        h.total_requests += 1;
+    }
+
+    fn record_result(&self, idx: usize, started: Instant, ok: bool) {
+        let mut h = self.health[idx].lock().unwrap();
+        h.in_flight = h.in_flight.saturating_sub(1);
+        if ok {
+            let latency = started.elapsed().as_secs_f64() * 1000.0;
+            h.avg_latency_ms = if h.avg_latency_ms == 0.0 { latency } else { 0.8 * h.avg_latency_ms + 0.2 * latency };
+            h.consecutive_failures = 0;
+        } else {
+            h.consecutive_failures += 1;
+            h.total_failures += 1;
+            h.last_failure = Some(Instant::now());
+        }
+    }
+
+    async fn dispatch<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn LLMProvider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = anyhow::anyhow!("BalancedProvider has no endpoints");
+        for idx in self.candidates() {
+            self.record_start(idx);
+            let started = Instant::now();
+            match call(self.endpoints[idx].clone()).await {
+                Ok(value) => {
+                    self.record_result(idx, started, true);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    self.record_result(idx, started, false);
+                    warn!("Endpoint #{} failed, trying next: {}", idx, e);
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
+}
+
+#[async_trait]
+impl LLMProvider for BalancedProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.dispatch(|p| {
+            let (prompt, system) = (prompt.clone(), system.clone());
+            async move { p.generate_with_options(model, prompt, system, options).await }
+        }).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.dispatch(|p| {
+            let (prompt, system) = (prompt.clone(), system.clone());
+            async move { p.generate_stream_with_options(model, prompt, system, options).await }
+        }).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        self.dispatch(|p| {
+            let messages = messages.clone();
+            async move { p.generate_chat(model, messages, options).await }
+        }).await
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.dispatch(|p| {
+            let messages = messages.clone();
+            async move { p.generate_chat_stream(model, messages, options).await }
+        }).await
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        // Endpoints serve the same models, so any of them can count
+        match self.endpoints.first() {
+            Some(p) => p.count_tokens(model, text).await,
+            None => Ok(super::tokenizer::count_tiktoken(model, text)),
+        }
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        // Healthy as long as one endpoint answers
+        self.dispatch(|p| async move { p.health_check(model).await }).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.lock.clone()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        match self.endpoints.first() {
+            Some(p) => p.notify(message).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Endpoint {
+        name: &'static str,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl LLMProvider for Endpoint {
+        async fn generate(&self, _model: &str, _prompt: String, _system: Option<String>) -> Result<String> {
+            if self.fail { anyhow::bail!("down") }
+            Ok(self.name.to_string())
+        }
+        async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+            let reply = self.generate(model, prompt, system).await?;
+            Ok(Box::pin(futures::stream::once(async move { Ok(reply) })))
+        }
+        fn get_lock(&self) -> Arc<Mutex<()>> {
+            Arc::new(Mutex::new(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_rotates() {
+        let balanced = BalancedProvider::new(vec![
+            Arc::new(Endpoint { name: "a", fail: false }),
+            Arc::new(Endpoint { name: "b", fail: false }),
+        ], BalanceStrategy::RoundRobin);
+
+        let first = balanced.generate("m", "p".into(), None).await.unwrap();
+        let second = balanced.generate("m", "p".into(), None).await.unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoint_is_skipped() {
+        let balanced = BalancedProvider::new(vec![
+            Arc::new(Endpoint { name: "dead", fail: true }),
+            Arc::new(Endpoint { name: "alive", fail: false }),
+        ], BalanceStrategy::RoundRobin).with_failure_threshold(1);
+
+        for _ in 0..4 {
+            assert_eq!(balanced.generate("m", "p".into(), None).await.unwrap(), "alive");
+        }
+        // After the first failure the dead endpoint is demoted and never tried again within the cooldown
+        let health = balanced.health();
+        assert_eq!(health[0].total_failures, 1);
+        assert_eq!(health[1].total_requests, 4);
+    }
+}
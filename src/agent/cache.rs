@@ -1,13 +1,23 @@
 //! LLM Response Cache
 //! 
-//! Provides a simple in-memory cache for LLM responses to avoid redundant computations.
+//! Caches LLM responses to avoid redundant computations. Entries are bounded in number,
+//! optionally expire after a TTL, can be persisted to SQLite so restarts keep the cache warm,
+//! and in semantic mode near-identical prompts (by embedding similarity) hit as well.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
 use tokio::sync::RwLock;
 use sha2::{Sha256, Digest};
 use async_trait::async_trait;
+use tracing::warn;
 use crate::agent::LLMProvider;
+use crate::agent::provider::{GenerationOptions, ChatMessage, flatten_chat};
+use crate::agent::cost::{CostTracker, COST_TRACKER, estimate_tokens, metered_stream};
 use futures_util::stream::BoxStream;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -15,52 +25,355 @@ struct CacheKey {
     model: String,
     prompt_hash: [u8; 32],
     system_hash: [u8; 32],
+    options_hash: [u8; 32],
+}
+
+impl CacheKey {
+    /// Entries that only differ in prompt may answer each other in semantic mode
+    fn same_scope(&self, other: &CacheKey) -> bool {
+        self.model == other.model && self.system_hash == other.system_hash && self.options_hash == other.options_hash
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    response: String,
+    created_at: DateTime<Utc>,
+    last_access: DateTime<Utc>,
+    /// Normalised prompt embedding (semantic mode only)
+    embedding: Option<Vec<f32>>,
+}
+
+/// Embeds prompts for semantic cache lookups
+pub trait PromptEmbedder: Send + Sync {
+    fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// fastembed-backed embedder (AllMiniLML6V2, same model as the vector memory), loaded on first use
+#[derive(Default)]
+pub struct FastEmbedder {
+    model: StdMutex<Option<fastembed::TextEmbedding>>,
+}
+
+impl PromptEmbedder for FastEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut model = self.model.lock().unwrap();
+        if model.is_none() {
+            *model = Some(fastembed::TextEmbedding::try_new(
+                fastembed::InitOptions::new(fastembed::EmbeddingModel::AllMiniLML6V2)
+            )?);
+        }
+        let mut embeddings = model.as_mut().unwrap().embed(vec![text.to_string()], None)?;
+        embeddings.pop().ok_or_else(|| anyhow::anyhow!("Embedding model returned no vectors"))
+    }
+}
+
+struct SemanticIndex {
+    embedder: Arc<dyn PromptEmbedder>,
+    threshold: f32,
 }
 
 /// A cache for LLM responses
 pub struct LLMCache {
-    responses: Arc<RwLock<HashMap<CacheKey, String>>>,
+    responses: Arc<RwLock<HashMap<CacheKey, CacheEntry>>>,
+    ttl: Option<Duration>,
+    max_entries: usize,
+    db_path: Option<PathBuf>,
+    semantic: Option<SemanticIndex>,
 }
 
 impl LLMCache {
     pub fn new() -> Self {
         Self {
             responses: Arc::new(RwLock::new(HashMap::new())),
+            ttl: None,
+            max_entries: 10_000,
+            db_path: None,
+            semantic: None,
         }
     }
 
+    /// Cache backed by a SQLite file; unexpired entries are loaded immediately and entries
+    /// older than `ttl` are deleted from the file
+    pub async fn persistent(db_path: impl AsRef<Path>, ttl: Option<Duration>) -> Result<Self> {
+        let path = db_path.as_ref().to_path_buf();
+        let path_clone = path.clone();
+        let rows = tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path_clone)?;
+            conn.execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS llm_cache (
+                    model TEXT NOT NULL,
+                    prompt_hash BLOB NOT NULL,
+                    system_hash BLOB NOT NULL,
+                    options_hash BLOB NOT NULL,
+                    response TEXT NOT NULL,
+                    embedding BLOB,
+                    created_at TEXT NOT NULL,
+                    PRIMARY KEY (model, prompt_hash, system_hash, options_hash)
+                );
+                "#,
+                [],
+            )?;
+            let mut stmt = conn.prepare(
+                "SELECT model, prompt_hash, system_hash, options_hash, response, embedding, created_at FROM llm_cache ORDER BY created_at"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, Vec<u8>>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, Option<Vec<u8>>>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            })?.collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok::<_, anyhow::Error>(rows)
+        }).await??;
+
+        let cache = Self { db_path: Some(path), ttl, ..Self::new() };
+        let mut expired = Vec::new();
+        {
+            let mut responses = cache.responses.write().await;
+            for (model, prompt_hash, system_hash, options_hash, response, embedding, created_at) in rows {
+                let (Ok(prompt_hash), Ok(system_hash), Ok(options_hash)) =
+                    (prompt_hash.try_into(), system_hash.try_into(), options_hash.try_into()) else { continue };
+                let created_at = DateTime::parse_from_rfc3339(&created_at).map(|t| t.with_timezone(&Utc)).unwrap_or_else(|_| Utc::now());
+                let key = CacheKey { model, prompt_hash, system_hash, options_hash };
+                let entry = CacheEntry {
+                    response,
+                    created_at,
+                    last_access: created_at,
+                    embedding: embedding.and_then(|b| bincode::deserialize(&b).ok()),
+                };
+                if cache.is_expired(&entry) {
+                    expired.push(key);
+                } else {
+                    responses.insert(key, entry);
+                }
+            }
+        }
+        cache.delete_persisted(expired).await;
+        Ok(cache)
+    }
+
+    /// Cache configured from `AGENCY_LLM_CACHE` (SQLite path, or `memory`), `AGENCY_LLM_CACHE_TTL_SECS`,
+    /// `AGENCY_LLM_CACHE_MAX` and `AGENCY_LLM_CACHE_SEMANTIC` (similarity threshold, e.g. `0.95`)
+    pub async fn from_env() -> Self {
+        let path = std::env::var("AGENCY_LLM_CACHE")
+            .unwrap_or_else(|_| crate::utils::data_path("agency_llm_cache.db").to_string_lossy().into_owned());
+        let ttl = std::env::var("AGENCY_LLM_CACHE_TTL_SECS").ok().and_then(|v| v.parse().ok()).map(Duration::from_secs);
+        let mut cache = if path == "memory" {
+            Self::new()
+        } else {
+            match Self::persistent(&path, ttl).await {
+                Ok(cache) => cache,
+                Err(e) => {
+                    warn!("LLM cache persistence unavailable at {}: {}. Using in-memory cache.", path, e);
+                    Self::new()
+                }
+            }
+        };
+        if let Some(ttl) = ttl {
+            cache = cache.with_ttl(ttl);
+        }
+        if let Some(max) = std::env::var("AGENCY_LLM_CACHE_MAX").ok().and_then(|v| v.parse().ok()) {
+            cache = cache.with_max_entries(max);
+        }
+        if let Some(threshold) = std::env::var("AGENCY_LLM_CACHE_SEMANTIC").ok().and_then(|v| v.parse().ok()) {
+            cache = cache.with_semantic(Arc::new(FastEmbedder::default()), threshold);
+        }
+        cache
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Serve prompts whose embedding has cosine similarity >= `threshold` with a cached prompt
+    pub fn with_semantic(mut self, embedder: Arc<dyn PromptEmbedder>, threshold: f32) -> Self {
+        self.semantic = Some(SemanticIndex { embedder, threshold });
+        self
+    }
+
     fn hash(text: &str) -> [u8; 32] {
         let mut hasher = Sha256::new();
         hasher.update(text.as_bytes());
         hasher.finalize().into()
     }
 
-    pub async fn get(&self, model: &str, prompt: &str, system: Option<&str>) -> Option<String> {
-        let key = CacheKey {
+    fn key(model: &str, prompt: &str, system: Option<&str>, options: &GenerationOptions) -> CacheKey {
+        CacheKey {
             model: model.to_string(),
             prompt_hash: Self::hash(prompt),
             system_hash: Self::hash(system.unwrap_or("")),
-        };
-        
-        let responses = self.responses.read().await;
-        responses.get(&key).cloned()
+            options_hash: Self::hash(&serde_json::to_string(options).unwrap_or_default()),
+        }
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        match self.ttl {
+            Some(ttl) => Utc::now().signed_duration_since(entry.created_at).to_std().map(|age| age > ttl).unwrap_or(false),
+            None => false,
+        }
+    }
+
+    async fn embed(&self, prompt: &str) -> Option<Vec<f32>> {
+        let semantic = self.semantic.as_ref()?;
+        let embedder = semantic.embedder.clone();
+        let prompt = prompt.to_string();
+        match tokio::task::spawn_blocking(move || embedder.embed(&prompt)).await {
+            Ok(Ok(mut embedding)) => {
+                let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+                if norm > 0.0 { for x in &mut embedding { *x /= norm; } }
+                Some(embedding)
+            }
+            Ok(Err(e)) => {
+                warn!("Semantic cache embedding failed: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    pub async fn get(&self, model: &str, prompt: &str, system: Option<&str>) -> Option<String> {
+        self.get_with_options(model, prompt, system, &GenerationOptions::default()).await
     }
 
     pub async fn set(&self, model: &str, prompt: &str, system: Option<&str>, response: String) {
-        let key = CacheKey {
-            model: model.to_string(),
-            prompt_hash: Self::hash(prompt),
-            system_hash: Self::hash(system.unwrap_or("")),
+        self.set_with_options(model, prompt, system, &GenerationOptions::default(), response).await
+    }
+
+    /// Lookup keyed on sampling parameters as well, so a deterministic and a creative run never share entries
+    pub async fn get_with_options(&self, model: &str, prompt: &str, system: Option<&str>, options: &GenerationOptions) -> Option<String> {
+        let key = Self::key(model, prompt, system, options);
+        let mut expired = Vec::new();
+        let hit = {
+            let mut responses = self.responses.write().await;
+            match responses.get_mut(&key) {
+                Some(entry) if !self.is_expired(entry) => {
+                    entry.last_access = Utc::now();
+                    Some(entry.response.clone())
+                }
+                Some(_) => {
+                    responses.remove(&key);
+                    expired.push(key.clone());
+                    None
+                }
+                None => None,
+            }
         };
-        
+        self.delete_persisted(expired).await;
+        if hit.is_some() || self.semantic.is_none() {
+            return hit;
+        }
+
+        // Semantic fallback: nearest cached prompt in the same model/system/options scope
+        let embedding = self.embed(prompt).await?;
+        let threshold = self.semantic.as_ref()?.threshold;
         let mut responses = self.responses.write().await;
-        responses.insert(key, response);
+        let best = responses.iter()
+            .filter(|(k, e)| k.same_scope(&key) && !self.is_expired(e))
+            .filter_map(|(k, e)| {
+                let score: f32 = e.embedding.as_ref()?.iter().zip(&embedding).map(|(a, b)| a * b).sum();
+                Some((k.clone(), score))
+            })
+            .filter(|(_, score)| *score >= threshold)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+        let entry = responses.get_mut(&best.0)?;
+        entry.last_access = Utc::now();
+        tracing::debug!("Semantic cache hit (similarity {:.3})", best.1);
+        Some(entry.response.clone())
+    }
+
+    pub async fn set_with_options(&self, model: &str, prompt: &str, system: Option<&str>, options: &GenerationOptions, response: String) {
+        let key = Self::key(model, prompt, system, options);
+        let now = Utc::now();
+        let entry = CacheEntry { response, created_at: now, last_access: now, embedding: self.embed(prompt).await };
+
+        let mut evicted = Vec::new();
+        {
+            let mut responses = self.responses.write().await;
+            responses.insert(key.clone(), entry.clone());
+            if self.ttl.is_some() {
+                let stale: Vec<CacheKey> = responses.iter().filter(|(_, e)| self.is_expired(e)).map(|(k, _)| k.clone()).collect();
+                for k in stale {
+                    responses.remove(&k);
+                    evicted.push(k);
+                }
+            }
+            while responses.len() > self.max_entries {
+                let Some(lru) = responses.iter().min_by_key(|(_, e)| e.last_access).map(|(k, _)| k.clone()) else { break };
+                responses.remove(&lru);
+                evicted.push(lru);
+            }
+        }
+
+        self.persist(key, entry).await;
+        self.delete_persisted(evicted).await;
+    }
+
+    async fn persist(&self, key: CacheKey, entry: CacheEntry) {
+        let Some(path) = self.db_path.clone() else { return };
+        let _ = tokio::task::spawn_blocking(move || {
+            let result = Connection::open(&path).and_then(|conn| {
+                let embedding = entry.embedding.as_ref().and_then(|e| bincode::serialize(e).ok());
+                conn.execute(
+                    "INSERT OR REPLACE INTO llm_cache (model, prompt_hash, system_hash, options_hash, response, embedding, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![key.model, key.prompt_hash.to_vec(), key.system_hash.to_vec(), key.options_hash.to_vec(), entry.response, embedding, entry.created_at.to_rfc3339()],
+                )
+            });
+            if let Err(e) = result {
+                warn!("Failed to persist LLM cache entry: {}", e);
+            }
+        }).await;
+    }
+
+    async fn delete_persisted(&self, keys: Vec<CacheKey>) {
+        let Some(path) = self.db_path.clone() else { return };
+        if keys.is_empty() {
+            return;
+        }
+        let _ = tokio::task::spawn_blocking(move || {
+            let result = Connection::open(&path).and_then(|conn| {
+                for key in keys {
+                    conn.execute(
+                        "DELETE FROM llm_cache WHERE model = ?1 AND prompt_hash = ?2 AND system_hash = ?3 AND options_hash = ?4",
+                        params![key.model, key.prompt_hash.to_vec(), key.system_hash.to_vec(), key.options_hash.to_vec()],
+                    )?;
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                warn!("Failed to prune LLM cache: {}", e);
+            }
+        }).await;
+    }
+
+    pub async fn len(&self) -> usize {
+        self.responses.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.responses.read().await.is_empty()
     }
 
     #[allow(dead_code)]
     pub async fn clear(&self) {
         let mut responses = self.responses.write().await;
         responses.clear();
+        if let Some(path) = self.db_path.clone() {
+            let _ = tokio::task::spawn_blocking(move || {
+                Connection::open(&path).and_then(|conn| conn.execute("DELETE FROM llm_cache", []))
+            }).await;
+        }
     }
 }
 
@@ -70,44 +383,129 @@ impl Default for LLMCache {
     }
 }
 
-/// Provider that wraps another provider with a cache
+/// Provider that wraps another provider with a cache and meters usage
 pub struct CachedProvider {
     inner: Arc<dyn LLMProvider>,
     cache: Arc<LLMCache>,
+    cost: Arc<CostTracker>,
 }
 
 impl CachedProvider {
     pub fn new(inner: Arc<dyn LLMProvider>, cache: Arc<LLMCache>) -> Self {
-        Self { inner, cache }
+        Self { inner, cache, cost: COST_TRACKER.clone() }
+    }
+
+    pub fn with_cost_tracker(mut self, cost: Arc<CostTracker>) -> Self {
+        self.cost = cost;
+        self
     }
+
+    /// Prompt size as counted by the wrapped provider's tokenizer
+    async fn input_tokens(&self, model: &str, prompt: &str, system: Option<&str>) -> u64 {
+        let text = match system {
+            Some(system) => format!("{}\n{}", system, prompt),
+            None => prompt.to_string(),
+        };
+        self.inner.count_tokens(model, &text).await
+            .map(|n| n as u64)
+            .unwrap_or_else(|_| estimate_tokens(&text))
+    }
+}
+
+/// Audit a call that reached the wrapped provider; cache hits are not provider calls
+fn audit_call<T>(model: &str, call: &str, input_tokens: u64, output_tokens: Option<u64>, result: &anyhow::Result<T>) {
+    crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::ProviderCall, model, serde_json::json!({
+        "call": call,
+        "input_tokens": input_tokens,
+        "output_tokens": output_tokens,
+        "success": result.is_ok(),
+        "error": result.as_ref().err().map(|e| e.to_string()),
+    }));
 }
 
 #[async_trait]
 impl LLMProvider for CachedProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> anyhow::Result<String> {
-        if let Some(cached) = self.cache.get(model, &prompt, system.as_deref()).await {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> anyhow::Result<BoxStream<'static, anyhow::Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> anyhow::Result<String> {
+        let input_tokens = self.input_tokens(model, &prompt, system.as_deref()).await;
+        if let Some(cached) = self.cache.get_with_options(model, &prompt, system.as_deref(), options).await {
             tracing::debug!("LLM Cache Hit for model {}", model);
+            self.cost.record(model, input_tokens, estimate_tokens(&cached), true);
             return Ok(cached);
         }
 
-        let response = self.inner.generate(model, prompt.clone(), system.clone()).await?;
-        self.cache.set(model, &prompt, system.as_deref(), response.clone()).await;
+        let response = self.inner.generate_with_options(model, prompt.clone(), system.clone(), options).await;
+        audit_call(model, "generate", input_tokens, response.as_ref().ok().map(|r| estimate_tokens(r)), &response);
+        let response = response?;
+        self.cost.record(model, input_tokens, estimate_tokens(&response), false);
+        self.cache.set_with_options(model, &prompt, system.as_deref(), options, response.clone()).await;
         Ok(response)
     }
 
-    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> anyhow::Result<BoxStream<'static, anyhow::Result<String>>> {
-        if let Some(cached) = self.cache.get(model, &prompt, system.as_deref()).await {
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> anyhow::Result<BoxStream<'static, anyhow::Result<String>>> {
+        let input_tokens = self.input_tokens(model, &prompt, system.as_deref()).await;
+        if let Some(cached) = self.cache.get_with_options(model, &prompt, system.as_deref(), options).await {
             tracing::debug!("LLM Cache Hit for model {}", model);
+            self.cost.record(model, input_tokens, estimate_tokens(&cached), true);
             return Ok(Box::pin(futures_util::stream::once(async move { Ok(cached) })));
         }
 
         let model = model.to_string();
         
-        let stream = self.inner.generate_stream(&model, prompt, system).await?;
+        let stream = self.inner.generate_stream_with_options(&model, prompt, system, options).await;
+        audit_call(&model, "stream", input_tokens, None, &stream);
+        let stream = stream?;
 
         // For now, CachedProvider::generate_stream will just not cache the result of a miss
         // to avoid complexity with collecting chunks in a stream.
-        Ok(Box::pin(stream))
+        Ok(metered_stream(self.cost.clone(), model, input_tokens, stream))
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> anyhow::Result<String> {
+        let (prompt, system) = flatten_chat(&messages);
+        let input_tokens = self.input_tokens(model, &prompt, system.as_deref()).await;
+        if let Some(cached) = self.cache.get_with_options(model, &prompt, system.as_deref(), options).await {
+            tracing::debug!("LLM Cache Hit for model {}", model);
+            self.cost.record(model, input_tokens, estimate_tokens(&cached), true);
+            return Ok(cached);
+        }
+
+        let response = self.inner.generate_chat(model, messages, options).await;
+        audit_call(model, "chat", input_tokens, response.as_ref().ok().map(|r| estimate_tokens(r)), &response);
+        let response = response?;
+        self.cost.record(model, input_tokens, estimate_tokens(&response), false);
+        self.cache.set_with_options(model, &prompt, system.as_deref(), options, response.clone()).await;
+        Ok(response)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> anyhow::Result<BoxStream<'static, anyhow::Result<String>>> {
+        let (prompt, system) = flatten_chat(&messages);
+        let input_tokens = self.input_tokens(model, &prompt, system.as_deref()).await;
+        if let Some(cached) = self.cache.get_with_options(model, &prompt, system.as_deref(), options).await {
+            tracing::debug!("LLM Cache Hit for model {}", model);
+            self.cost.record(model, input_tokens, estimate_tokens(&cached), true);
+            return Ok(Box::pin(futures_util::stream::once(async move { Ok(cached) })));
+        }
+
+        let stream = self.inner.generate_chat_stream(model, messages, options).await;
+        audit_call(model, "chat_stream", input_tokens, None, &stream);
+        let stream = stream?;
+        Ok(metered_stream(self.cost.clone(), model.to_string(), input_tokens, stream))
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> anyhow::Result<usize> {
+        self.inner.count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> anyhow::Result<()> {
+        self.inner.health_check(model).await
     }
 
     fn get_lock(&self) -> Arc<tokio::sync::Mutex<()>> {
@@ -151,4 +549,73 @@ mod tests {
         let cached = cache.get("m", "p", None).await;
         assert!(cached.is_none());
     }
+
+    #[tokio::test]
+    async fn test_cache_persists_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("llm_cache.db");
+
+        let cache = LLMCache::persistent(&path, None).await.unwrap();
+        cache.set("m", "p", Some("s"), "r".into()).await;
+        drop(cache);
+
+        let reopened = LLMCache::persistent(&path, None).await.unwrap();
+        assert_eq!(reopened.get("m", "p", Some("s")).await.unwrap(), "r");
+        drop(reopened);
+
+        // Expired rows are neither loaded nor kept in the file
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let expired = LLMCache::persistent(&path, Some(Duration::from_millis(20))).await.unwrap();
+        assert!(expired.is_empty().await);
+        let reopened = LLMCache::persistent(&path, None).await.unwrap();
+        assert!(reopened.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_cache_ttl_and_size_bound() {
+        let cache = LLMCache::new().with_ttl(Duration::from_millis(20));
+        cache.set("m", "p", None, "r".into()).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(cache.get("m", "p", None).await.is_none());
+
+        let bounded = LLMCache::new().with_max_entries(2);
+        for p in ["a", "b", "c"] {
+            bounded.set("m", p, None, p.into()).await;
+        }
+        assert_eq!(bounded.len().await, 2);
+        assert!(bounded.get("m", "a", None).await.is_none());
+    }
+
+    /// Bag-of-letters embedding: prompts that differ only in case/punctuation collide
+    struct LetterEmbedder;
+
+    impl PromptEmbedder for LetterEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let mut v = vec![0.0; 26];
+            for c in text.to_lowercase().chars().filter(|c| c.is_ascii_lowercase()) {
+                v[(c as u8 - b'a') as usize] += 1.0;
+            }
+            Ok(v)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semantic_hit() {
+        let cache = LLMCache::new().with_semantic(Arc::new(LetterEmbedder), 0.99);
+        cache.set("m", "What is Rust?", None, "a language".into()).await;
+
+        assert_eq!(cache.get("m", "what is rust", None).await.unwrap(), "a language");
+        assert!(cache.get("m", "Explain tokio", None).await.is_none());
+        assert!(cache.get("other", "what is rust", None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_keyed_on_options() {
+        let cache = LLMCache::new();
+        let deterministic = GenerationOptions { temperature: Some(0.0), ..Default::default() };
+        cache.set_with_options("m", "p", None, &deterministic, "r".into()).await;
+
+        assert!(cache.get("m", "p", None).await.is_none());
+        assert_eq!(cache.get_with_options("m", "p", None, &deterministic).await.unwrap(), "r");
+    }
 }
@@ -0,0 +1,381 @@
+//! Provider Circuit Breakers
+//!
+//! Per-model circuit breakers and periodic health probes. A model whose requests keep failing
+//! is opened and fails fast until a cooldown elapses; the next request (or probe) is let through
+//! half-open and decides whether the circuit closes again. The `Router` and the Supervisor's
+//! escalation loop consult `CIRCUITS` to skip intelligence tiers whose model is down.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex, RwLock};
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use super::provider::{ChatMessage, GenerationOptions, LLMProvider};
+
+lazy_static! {
+    /// Process-wide breakers shared by the Supervisor, Router and health prober
+    pub static ref CIRCUITS: Arc<CircuitRegistry> = Arc::new(CircuitRegistry::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the reset timeout elapses
+    Open,
+    /// A single trial request is allowed through
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Closed → Open after `failure_threshold` consecutive failures, Open → HalfOpen after
+/// `reset_timeout`, HalfOpen → Closed on success or back to Open on failure
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    inner: StdMutex<BreakerInner>,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            inner: StdMutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+        }
+    }
+
+    /// Current state, accounting for an elapsed reset timeout
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// Whether the model is worth routing to (closed, or ready for a half-open trial)
+    pub fn is_available(&self) -> bool {
+        self.state() != CircuitState::Open
+    }
+
+    /// Reserve permission for one request. Half-open circuits admit a single trial.
+    pub fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        self.refresh(&mut inner);
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if inner.trial_in_flight => false,
+            CircuitState::HalfOpen => {
+                inner.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.trial_in_flight = false;
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.trial_in_flight = false;
+        if inner.state == CircuitState::HalfOpen || inner.consecutive_failures >= self.failure_threshold {
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    fn refresh(&self, inner: &mut BreakerInner) {
+        if inner.state == CircuitState::Open
+            && inner.opened_at.map(|t| t.elapsed() >= self.reset_timeout).unwrap_or(true)
+        {
+            inner.state = CircuitState::HalfOpen;
+            inner.trial_in_flight = false;
+        }
+    }
+}
+
+/// Snapshot of one model's breaker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitStatus {
+    pub model: String,
+    pub state: CircuitState,
+}
+
+/// Breakers keyed by model name, created on first use
+pub struct CircuitRegistry {
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+    failure_threshold: u32,
+    reset_timeout: Duration,
+}
+
+impl CircuitRegistry {
+    pub fn new() -> Self {
+        let failure_threshold = std::env::var("AGENCY_CIRCUIT_THRESHOLD").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3);
+        let reset_secs = std::env::var("AGENCY_CIRCUIT_RESET_SECS").ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        Self::with_settings(failure_threshold, Duration::from_secs(reset_secs))
+    }
+
+    pub fn with_settings(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            breakers: RwLock::new(HashMap::new()),
+            failure_threshold,
+            reset_timeout,
+        }
+    }
+
+    pub fn breaker(&self, model: &str) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().unwrap().get(model) {
+            return breaker.clone();
+        }
+        self.breakers.write().unwrap()
+            .entry(model.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(self.failure_threshold, self.reset_timeout)))
+            .clone()
+    }
+
+    /// Models that have never been seen are assumed healthy
+    pub fn is_available(&self, model: &str) -> bool {
+        self.breakers.read().unwrap().get(model).map(|b| b.is_available()).unwrap_or(true)
+    }
+
+    pub fn snapshot(&self) -> Vec<CircuitStatus> {
+        let mut statuses: Vec<CircuitStatus> = self.breakers.read().unwrap().iter()
+            .map(|(model, b)| CircuitStatus { model: model.clone(), state: b.state() })
+            .collect();
+        statuses.sort_by(|a, b| a.model.cmp(&b.model));
+        statuses
+    }
+}
+
+impl Default for CircuitRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Provider wrapper that guards every model behind its circuit breaker
+pub struct CircuitBreakerProvider {
+    inner: Arc<dyn LLMProvider>,
+    circuits: Arc<CircuitRegistry>,
+}
+
+impl CircuitBreakerProvider {
+    pub fn new(inner: Arc<dyn LLMProvider>) -> Self {
+        Self { inner, circuits: CIRCUITS.clone() }
+    }
+
+    pub fn with_circuits(mut self, circuits: Arc<CircuitRegistry>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
+    async fn guarded<T, Fut>(&self, model: &str, call: Fut) -> Result<T>
+    where
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let breaker = self.circuits.breaker(model);
+        if !breaker.try_acquire() {
+            anyhow::bail!("Circuit open for model '{}', skipping request", model);
+        }
+        match call.await {
+            Ok(value) => {
+                breaker.record_success();
+                Ok(value)
+            }
+            Err(e) => {
+                breaker.record_failure();
+                if breaker.state() == CircuitState::Open {
+                    warn!("Circuit opened for model '{}': {}", model, e);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for CircuitBreakerProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.guarded(model, self.inner.generate_with_options(model, prompt, system, options)).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.guarded(model, self.inner.generate_stream_with_options(model, prompt, system, options)).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        self.guarded(model, self.inner.generate_chat(model, messages, options)).await
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.guarded(model, self.inner.generate_chat_stream(model, messages, options)).await
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        self.inner.count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        self.inner.health_check(model).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.inner.get_lock()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        self.inner.notify(message).await
+    }
+}
+
+/// Periodically probes a set of models and feeds the results into their breakers
+pub struct HealthProber {
+    provider: Arc<dyn LLMProvider>,
+    models: Vec<String>,
+    circuits: Arc<CircuitRegistry>,
+    interval: Duration,
+}
+
+impl HealthProber {
+    pub fn new(provider: Arc<dyn LLMProvider>, models: Vec<String>, interval: Duration) -> Self {
+        let mut models = models;
+        models.sort();
+        models.dedup();
+        Self { provider, models, circuits: CIRCUITS.clone(), interval }
+    }
+
+    pub fn with_circuits(mut self, circuits: Arc<CircuitRegistry>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
+    /// Probe every model once
+    pub async fn probe_all(&self) {
+        for model in &self.models {
+            let breaker = self.circuits.breaker(model);
+            match self.provider.health_check(model).await {
+                Ok(()) => {
+                    if breaker.state() != CircuitState::Closed {
+                        info!("Health probe: model '{}' recovered", model);
+                    }
+                    breaker.record_success();
+                }
+                Err(e) => {
+                    warn!("Health probe failed for model '{}': {}", model, e);
+                    breaker.record_failure();
+                }
+            }
+        }
+    }
+
+    pub async fn start(&self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        loop {
+            ticker.tick().await;
+            self.probe_all().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Flaky {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl LLMProvider for Flaky {
+        async fn generate(&self, _model: &str, _prompt: String, _system: Option<String>) -> Result<String> {
+            if self.fail { anyhow::bail!("connection refused") }
+            Ok("ok".to_string())
+        }
+        async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+            let reply = self.generate(model, prompt, system).await?;
+            Ok(Box::pin(futures::stream::once(async move { Ok(reply) })))
+        }
+        fn get_lock(&self) -> Arc<Mutex<()>> {
+            Arc::new(Mutex::new(()))
+        }
+    }
+
+    #[test]
+    fn test_breaker_state_machine() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(0));
+        breaker.record_failure();
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        // Zero reset timeout: an open circuit is immediately ready for a half-open trial
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire(), "only one trial request while half-open");
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_fails_fast() {
+        let circuits = Arc::new(CircuitRegistry::with_settings(2, Duration::from_secs(60)));
+        let provider = CircuitBreakerProvider::new(Arc::new(Flaky { fail: true })).with_circuits(circuits.clone());
+
+        for _ in 0..2 {
+            let err = provider.generate("heavy", "p".into(), None).await.unwrap_err();
+            assert!(err.to_string().contains("connection refused"));
+        }
+        assert!(!circuits.is_available("heavy"));
+        let err = provider.generate("heavy", "p".into(), None).await.unwrap_err();
+        assert!(err.to_string().contains("Circuit open"));
+        assert!(circuits.is_available("standard"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_closes_circuit() {
+        let circuits = Arc::new(CircuitRegistry::with_settings(1, Duration::from_secs(60)));
+        circuits.breaker("m").record_failure();
+        assert!(!circuits.is_available("m"));
+
+        let prober = HealthProber::new(Arc::new(Flaky { fail: false }), vec!["m".to_string()], Duration::from_secs(60))
+            .with_circuits(circuits.clone());
+        prober.probe_all().await;
+        assert!(circuits.is_available("m"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Cost Accounting
+//!
+//! Tracks token usage and spend per model. Every request that passes through
+//! `CachedProvider` is recorded; the Supervisor takes a mark at the start of a turn and
+//! attaches the turn's usage to the `Publication` telemetry, and `/v1/usage` exposes the totals.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+lazy_static! {
+    /// Process-wide tracker shared by all cached providers and the server
+    pub static ref COST_TRACKER: Arc<CostTracker> = Arc::new(CostTracker::load_or_default("config/pricing.json"));
+}
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_mtok: f64, output_per_mtok: f64) -> Self {
+        Self { input_per_mtok, output_per_mtok }
+    }
+
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input_per_mtok + output_tokens as f64 * self.output_per_mtok) / 1_000_000.0
+    }
+}
+
+/// A single metered request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Served from the LLM cache (no spend)
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Aggregated usage over a set of records
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageSummary {
+    pub requests: u64,
+    pub cache_hits: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub by_model: HashMap<String, ModelUsage>,
+}
+
+impl UsageSummary {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        if record.cached {
+            self.cache_hits += 1;
+        }
+        self.input_tokens += record.input_tokens;
+        self.output_tokens += record.output_tokens;
+        self.cost_usd += record.cost_usd;
+
+        let entry = self.by_model.entry(record.model.clone()).or_default();
+        entry.requests += 1;
+        entry.input_tokens += record.input_tokens;
+        entry.output_tokens += record.output_tokens;
+        entry.cost_usd += record.cost_usd;
+    }
+}
+
+/// Requests kept individually for `recent` and `summary_since`; older ones only count in the totals
+const MAX_RECENT_RECORDS: usize = 10_000;
+
+#[derive(Default)]
+struct Ledger {
+    total: UsageSummary,
+    recent: VecDeque<UsageRecord>,
+    /// Requests recorded so far; the sequence number of the next one
+    recorded: usize,
+}
+
+/// Pricing table plus running totals and the most recent metered requests
+pub struct CostTracker {
+    prices: RwLock<HashMap<String, ModelPrice>>,
+    ledger: Mutex<Ledger>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        let prices = [
+            ("gpt-4o-mini", ModelPrice::new(0.15, 0.60)),
+            ("gpt-4o", ModelPrice::new(2.50, 10.00)),
+            ("gpt-4.1-mini", ModelPrice::new(0.40, 1.60)),
+            ("gpt-4.1", ModelPrice::new(2.00, 8.00)),
+            ("claude-3-5-haiku", ModelPrice::new(0.80, 4.00)),
+            ("claude-haiku", ModelPrice::new(1.00, 5.00)),
+            ("claude-sonnet", ModelPrice::new(3.00, 15.00)),
+            ("claude-opus", ModelPrice::new(15.00, 75.00)),
+            ("gemini-2.0-flash-lite", ModelPrice::new(0.075, 0.30)),
+            ("gemini-2.5-flash", ModelPrice::new(0.30, 2.50)),
+            ("gemini-2.5-pro", ModelPrice::new(1.25, 10.00)),
+            ("glm-4-flash", ModelPrice::new(0.0, 0.0)),
+            ("glm-4", ModelPrice::new(0.14, 0.14)),
+        ];
+        Self {
+            prices: RwLock::new(prices.into_iter().map(|(k, v)| (k.to_string(), v)).collect()),
+            ledger: Mutex::new(Ledger::default()),
+        }
+    }
+
+    /// Built-in table with overrides from a JSON file of `{ "model": { "input_per_mtok": .., "output_per_mtok": .. } }`
+    pub fn load_or_default(path: impl AsRef<Path>) -> Self {
+        let tracker = Self::new();
+        if let Ok(content) = std::fs::read_to_string(path.as_ref()) {
+            match serde_json::from_str::<HashMap<String, ModelPrice>>(&content) {
+                Ok(overrides) => {
+                    for (model, price) in overrides {
+                        tracker.set_price(model, price);
+                    }
+                }
+                Err(e) => tracing::warn!("Ignoring malformed pricing table {:?}: {}", path.as_ref(), e),
+            }
+        }
+        tracker
+    }
+
+    pub fn set_price(&self, model: impl Into<String>, price: ModelPrice) {
+        self.prices.write().unwrap().insert(model.into(), price);
+    }
+
+    /// Longest-prefix price lookup; unknown (typically local) models are free
+    pub fn price_for(&self, model: &str) -> ModelPrice {
+        let prices = self.prices.read().unwrap();
+        prices.iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| *price)
+            .unwrap_or(ModelPrice::new(0.0, 0.0))
+    }
+
+    pub fn record(&self, model: &str, input_tokens: u64, output_tokens: u64, cached: bool) -> UsageRecord {
+        let cost_usd = if cached { 0.0 } else { self.price_for(model).cost(input_tokens, output_tokens) };
+        let record = UsageRecord {
+            timestamp: Utc::now(),
+            model: model.to_string(),
+            input_tokens,
+            output_tokens,
+            cost_usd,
+            cached,
+        };
+        crate::orchestrator::budget::meter_tokens(input_tokens + output_tokens, cost_usd);
+        let mut ledger = self.ledger.lock().unwrap();
+        ledger.total.add(&record);
+        ledger.recent.push_back(record.clone());
+        if ledger.recent.len() > MAX_RECENT_RECORDS {
+            ledger.recent.pop_front();
+        }
+        ledger.recorded += 1;
+        record
+    }
+
+    /// Position marker for computing per-turn usage with `summary_since`
+    pub fn mark(&self) -> usize {
+        self.ledger.lock().unwrap().recorded
+    }
+
+    /// Usage recorded after `mark`, as far back as the retained records reach
+    pub fn summary_since(&self, mark: usize) -> UsageSummary {
+        let ledger = self.ledger.lock().unwrap();
+        if mark == 0 {
+            return ledger.total.clone();
+        }
+        let first_retained = ledger.recorded - ledger.recent.len();
+        let mut summary = UsageSummary::default();
+        for record in ledger.recent.iter().skip(mark.saturating_sub(first_retained)) {
+            summary.add(record);
+        }
+        summary
+    }
+
+    pub fn summary(&self) -> UsageSummary {
+        self.summary_since(0)
+    }
+
+    /// Most recent records, newest last
+    pub fn recent(&self, limit: usize) -> Vec<UsageRecord> {
+        let ledger = self.ledger.lock().unwrap();
+        ledger.recent.iter().skip(ledger.recent.len().saturating_sub(limit)).cloned().collect()
+    }
+}
+
+impl Default for CostTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rough token estimate for text the provider tokenizer never sees (e.g. streamed output)
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Wrap a token stream so its output is metered once the stream completes
+pub fn metered_stream(
+    tracker: Arc<CostTracker>,
+    model: String,
+    input_tokens: u64,
+    stream: BoxStream<'static, anyhow::Result<String>>,
+) -> BoxStream<'static, anyhow::Result<String>> {
+    let metered = futures_util::stream::unfold(Some((stream, String::new())), move |state| {
+        let tracker = tracker.clone();
+        let model = model.clone();
+        async move {
+            let (mut s, mut output) = state?;
+            match s.next().await {
+                Some(Ok(chunk)) => {
+                    output.push_str(&chunk);
+                    Some((Ok(chunk), Some((s, output))))
+                }
+                Some(Err(e)) => Some((Err(e), Some((s, output)))),
+                None => {
+                    tracker.record(&model, input_tokens, estimate_tokens(&output), false);
+                    None
+                }
+            }
+        }
+    });
+    Box::pin(metered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_lookup_prefers_longest_prefix() {
+        let tracker = CostTracker::new();
+        assert_eq!(tracker.price_for("gpt-4o-mini-2024-07-18"), ModelPrice::new(0.15, 0.60));
+        assert_eq!(tracker.price_for("gpt-4o-2024-08-06"), ModelPrice::new(2.50, 10.00));
+        assert_eq!(tracker.price_for("qwen2.5:3b-q4"), ModelPrice::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_turn_summary() {
+        let tracker = CostTracker::new();
+        tracker.record("gpt-4o", 1_000_000, 0, false);
+        let mark = tracker.mark();
+        tracker.record("gpt-4o", 0, 1_000_000, false);
+        tracker.record("gpt-4o", 500, 500, true);
+
+        let turn = tracker.summary_since(mark);
+        assert_eq!(turn.requests, 2);
+        assert_eq!(turn.cache_hits, 1);
+        assert!((turn.cost_usd - 10.0).abs() < 1e-9);
+        assert!((tracker.summary().cost_usd - 12.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_records_are_bounded() {
+        let tracker = CostTracker::new();
+        for _ in 0..MAX_RECENT_RECORDS + 5 {
+            tracker.record("gpt-4o", 1, 1, false);
+        }
+        let mark = tracker.mark();
+        tracker.record("gpt-4o", 1, 1, false);
+
+        assert_eq!(tracker.summary().requests as usize, MAX_RECENT_RECORDS + 6);
+        assert_eq!(tracker.summary_since(mark).requests, 1);
+        assert_eq!(tracker.recent(usize::MAX).len(), MAX_RECENT_RECORDS);
+    }
+}
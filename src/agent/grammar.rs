@@ -0,0 +1,160 @@
+//! GBNF Grammar Generation
+//!
+//! Builds llama.cpp GBNF grammars from tool JSON schemas so that local models can only
+//! emit well-formed SNS turns: thought lines followed by either a `→` tool call whose
+//! name and parameters match a registered tool, or a `🎯` final answer.
+
+use serde_json::Value;
+
+const JSON_PRIMITIVES: &str = r#"value ::= object | array | string | number | ("true" | "false" | "null")
+object ::= "{" ws ( string ws ":" ws value ( ws "," ws string ws ":" ws value )* )? ws "}"
+array ::= "[" ws ( value ( ws "," ws value )* )? ws "]"
+string ::= "\"" ( [^"\\\x7F\x00-\x1F] | "\\" ( ["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] ) )* "\""
+number ::= "-"? [0-9]+ ( "." [0-9]+ )? ( [eE] [-+]? [0-9]+ )?
+integer ::= "-"? [0-9]+
+boolean ::= "true" | "false"
+ws ::= [ \t\n]*
+"#;
+
+/// Build a grammar for a ReAct turn over the given `(tool name, parameters schema)` pairs
+pub fn react_grammar(tools: &[(String, Value)], require_thought: bool) -> String {
+    let mut rules = Vec::new();
+
+    let thought = if require_thought { "thought-line+" } else { "thought-line*" };
+    if tools.is_empty() {
+        rules.push(format!("root ::= {} answer-line", thought));
+    } else {
+        rules.push(format!("root ::= {} ( action-line | answer-line )", thought));
+        rules.push("action-line ::= \"→ \" tool-call \"\\n\"?".to_string());
+
+        let alternatives: Vec<String> = tools.iter().map(|(name, _)| format!("tool-{}", rule_name(name))).collect();
+        rules.push(format!("tool-call ::= {}", alternatives.join(" | ")));
+
+        for (name, schema) in tools {
+            let id = rule_name(name);
+            let params_rule = format!("params-{}", id);
+            rules.push(format!(
+                "tool-{} ::= \"{{\" ws \"\\\"name\\\"\" ws \":\" ws \"\\\"{}\\\"\" ws \",\" ws \"\\\"parameters\\\"\" ws \":\" ws {} ws \"}}\"",
+                id, escape_literal(name), params_rule
+            ));
+            schema_rules(&params_rule, schema, &mut rules);
+        }
+    }
+
+    rules.push("thought-line ::= ( \"🧠\" | \"⚡\" ) [^\\n]* \"\\n\"".to_string());
+    rules.push("answer-line ::= \"🎯\" [^\\x00]*".to_string());
+
+    let mut grammar = rules.join("\n");
+    grammar.push('\n');
+    grammar.push_str(JSON_PRIMITIVES);
+    grammar
+}
+
+/// Emit a rule named `name` matching `schema`, plus any nested rules it needs
+fn schema_rules(name: &str, schema: &Value, rules: &mut Vec<String>) {
+    if let Some(options) = schema["enum"].as_array() {
+        let literals: Vec<String> = options.iter()
+            .map(|v| format!("\"{}\"", escape_literal(&v.to_string())))
+            .collect();
+        if !literals.is_empty() {
+            rules.push(format!("{} ::= {}", name, literals.join(" | ")));
+            return;
+        }
+    }
+
+    let body = match schema["type"].as_str() {
+        Some("string") => "string".to_string(),
+        Some("integer") => "integer".to_string(),
+        Some("number") => "number".to_string(),
+        Some("boolean") => "boolean".to_string(),
+        Some("array") => {
+            let item_rule = format!("{}-item", name);
+            if schema["items"].is_object() {
+                schema_rules(&item_rule, &schema["items"], rules);
+                format!("\"[\" ws ( {item} ( ws \",\" ws {item} )* )? ws \"]\"", item = item_rule)
+            } else {
+                "array".to_string()
+            }
+        }
+        Some("object") => object_body(name, schema, rules),
+        _ => "value".to_string(),
+    };
+    rules.push(format!("{} ::= {}", name, body));
+}
+
+/// Objects with declared properties list required keys first (in schema order) followed by
+/// optional keys. Schemas without required keys fall back to a generic object.
+fn object_body(name: &str, schema: &Value, rules: &mut Vec<String>) -> String {
+    let Some(properties) = schema["properties"].as_object() else {
+        return "object".to_string();
+    };
+    let required: Vec<&str> = schema["required"].as_array()
+        .map(|r| r.iter().filter_map(|v| v.as_str()).filter(|k| properties.contains_key(*k)).collect())
+        .unwrap_or_default();
+    if required.is_empty() {
+        return "object".to_string();
+    }
+
+    let mut member = |key: &str| -> String {
+        let rule = format!("{}-{}", name, rule_name(key));
+        schema_rules(&rule, &properties[key], rules);
+        format!("\"\\\"{}\\\"\" ws \":\" ws {}", escape_literal(key), rule)
+    };
+
+    let mut parts = Vec::new();
+    for (i, key) in required.iter().enumerate() {
+        let m = member(key);
+        parts.push(if i == 0 { m } else { format!("ws \",\" ws {}", m) });
+    }
+    for key in properties.keys().filter(|k| !required.contains(&k.as_str())) {
+        parts.push(format!("( ws \",\" ws {} )?", member(key)));
+    }
+
+    format!("\"{{\" ws {} ws \"}}\"", parts.join(" "))
+}
+
+/// GBNF rule names are restricted to `[a-zA-Z0-9-]`
+fn rule_name(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' }).collect()
+}
+
+fn escape_literal(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_grammar_lists_tools_and_required_params() {
+        let tools = vec![(
+            "web_search".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer" },
+                    "mode": { "type": "string", "enum": ["fast", "deep"] }
+                },
+                "required": ["query"]
+            }),
+        )];
+        let grammar = react_grammar(&tools, true);
+
+        assert!(grammar.starts_with("root ::= thought-line+ ( action-line | answer-line )"));
+        assert!(grammar.contains("tool-call ::= tool-web-search"));
+        assert!(grammar.contains("\\\"web_search\\\""));
+        assert!(grammar.contains("params-web-search-query ::= string"));
+        assert!(grammar.contains("params-web-search-mode ::= \"\\\"fast\\\"\" | \"\\\"deep\\\"\""));
+        assert!(grammar.contains("( ws \",\" ws \"\\\"limit\\\"\""));
+    }
+
+    #[test]
+    fn test_grammar_without_tools_only_answers() {
+        let grammar = react_grammar(&[], false);
+        assert!(grammar.starts_with("root ::= thought-line* answer-line"));
+        assert!(!grammar.contains("tool-call"));
+    }
+}
@@ -0,0 +1,322 @@
+//! GRPO Fine-Tuning
+//!
+//! Fine-tunes the local reasoner (`models::reasoner`, Qwen-2.5) on the agency's own tasks
+//! with Group Relative Policy Optimization: each prompt gets a group of sampled completions,
+//! a `RewardModel` scores them, and each completion's advantage is its reward relative to
+//! the group. A frozen copy of the starting weights anchors the KL penalty. Trained weights
+//! are checkpointed as safetensors and loaded back with `load_checkpoint`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use candle_core::safetensors::MmapedSafetensors;
+use candle_core::{DType, Device, Tensor, D};
+use candle_nn::{VarBuilder, VarMap};
+use candle_transformers::generation::LogitsProcessor;
+use serde::{Deserialize, Serialize};
+use tokenizers::Tokenizer;
+use tracing::info;
+
+use crate::agent::rl::{ExperienceBuffer, GRPOTrainer};
+use crate::models::reasoner::{Config as ReasonerConfig, ReasonerModel};
+use crate::orchestrator::aggregation::{Candidate, RewardModel, ScaleElasticity};
+use crate::orchestrator::AssuranceLevel;
+
+/// Checkpoint fine-tuned by default; small enough to train on a laptop
+pub const REASONER_REPO: &str = "Qwen/Qwen2.5-0.5B-Instruct";
+
+/// Qwen end-of-text and end-of-turn tokens
+const EOS_TOKENS: [u32; 2] = [151643, 151645];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrpoConfig {
+    /// Completions sampled per prompt
+    pub group_size: usize,
+    pub max_new_tokens: usize,
+    /// Longer prompts keep their last tokens
+    pub max_prompt_tokens: usize,
+    pub temperature: f64,
+    /// KL penalty against the reference model
+    pub beta: f32,
+    /// PPO-style clipping of the policy ratio
+    pub clip_epsilon: f32,
+    pub learning_rate: f64,
+    /// Where `train_on` saves the policy weights
+    pub checkpoint: PathBuf,
+}
+
+impl Default for GrpoConfig {
+    fn default() -> Self {
+        Self {
+            group_size: 4,
+            max_new_tokens: 256,
+            max_prompt_tokens: 512,
+            temperature: 0.8,
+            beta: 0.04,
+            clip_epsilon: 0.2,
+            learning_rate: 1e-6,
+            checkpoint: PathBuf::from("data/reasoner_grpo.safetensors"),
+        }
+    }
+}
+
+impl GrpoConfig {
+    /// `AGENCY_GRPO_GROUP_SIZE`, `AGENCY_GRPO_MAX_NEW_TOKENS`, `AGENCY_GRPO_LR` and `AGENCY_GRPO_CHECKPOINT`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok();
+        Self {
+            group_size: var("AGENCY_GRPO_GROUP_SIZE").and_then(|v| v.parse().ok()).unwrap_or(defaults.group_size),
+            max_new_tokens: var("AGENCY_GRPO_MAX_NEW_TOKENS").and_then(|v| v.parse().ok()).unwrap_or(defaults.max_new_tokens),
+            learning_rate: var("AGENCY_GRPO_LR").and_then(|v| v.parse().ok()).unwrap_or(defaults.learning_rate),
+            checkpoint: var("AGENCY_GRPO_CHECKPOINT").map(PathBuf::from).unwrap_or(defaults.checkpoint),
+            ..defaults
+        }
+    }
+}
+
+/// One sampled completion and its reward
+#[derive(Debug, Clone)]
+pub struct Rollout {
+    pub prompt: String,
+    pub completion: String,
+    pub reward: f32,
+    prompt_ids: Vec<u32>,
+    completion_ids: Vec<u32>,
+}
+
+/// Outcome of `GrpoFineTuner::train_on`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GrpoReport {
+    /// Prompts whose group gave a learning signal (rewards not all equal)
+    pub steps: usize,
+    pub mean_reward: f32,
+    pub last_loss: Option<f32>,
+}
+
+/// Distinct queries from the agency's logged experiences, newest first
+pub fn prompts_from_experiences(buffer: &ExperienceBuffer, limit: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    buffer.experiences.iter().rev()
+        .map(|e| e.query.clone())
+        .filter(|q| !q.trim().is_empty() && seen.insert(q.clone()))
+        .take(limit)
+        .collect()
+}
+
+pub struct GrpoFineTuner {
+    /// Trainable weights, all held in `varmap`
+    policy: ReasonerModel,
+    /// Frozen starting weights for the KL penalty
+    reference: ReasonerModel,
+    varmap: VarMap,
+    trainer: GRPOTrainer,
+    tokenizer: Tokenizer,
+    device: Device,
+    config: GrpoConfig,
+    seed: u64,
+}
+
+impl GrpoFineTuner {
+    /// Build a trainable policy from safetensors `weights`, plus a frozen reference copy
+    pub fn load(weights: &[PathBuf], tokenizer: Tokenizer, model_config: &ReasonerConfig, config: GrpoConfig, device: Device) -> anyhow::Result<Self> {
+        let safetensors = unsafe { MmapedSafetensors::multi(weights)? };
+
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &device);
+        // Create an untied LM head up front when the checkpoint has one
+        if safetensors.get("lm_head.weight").is_ok() {
+            vb.get((model_config.vocab_size, model_config.hidden_size), "lm_head.weight")?;
+        }
+        let policy = ReasonerModel::new(model_config, vb)?;
+        copy_weights(&varmap, &safetensors, &device)?;
+
+        let reference = ReasonerModel::new(model_config, unsafe { VarBuilder::from_mmaped_safetensors(weights, DType::F32, &device)? })?;
+        let trainer = GRPOTrainer::new(config.beta, &varmap, config.learning_rate)?;
+        Ok(Self { policy, reference, varmap, trainer, tokenizer, device, config, seed: 42 })
+    }
+
+    /// Download `repo` from the Hugging Face hub and load it on the CPU
+    pub async fn from_hub(repo: &str, config: GrpoConfig) -> anyhow::Result<Self> {
+        let repo = repo.to_string();
+        tokio::task::spawn_blocking(move || {
+            use hf_hub::{api::sync::ApiBuilder, Repo};
+            let api = ApiBuilder::new().with_token(std::env::var("HF_TOKEN").ok()).build()?;
+            let model_config = if repo.contains("1.5B") { ReasonerConfig::qwen_1_5b() } else { ReasonerConfig::qwen_0_5b() };
+            let hub = api.repo(Repo::new(repo, hf_hub::RepoType::Model));
+            let tokenizer = Tokenizer::from_file(hub.get("tokenizer.json")?).map_err(anyhow::Error::msg)?;
+            let weights = hub.get("model.safetensors")?;
+            Self::load(&[weights], tokenizer, &model_config, config, Device::Cpu)
+        }).await?
+    }
+
+    pub fn config(&self) -> &GrpoConfig {
+        &self.config
+    }
+
+    pub fn save_checkpoint(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(self.varmap.save(path)?)
+    }
+
+    /// Resume from weights saved by `save_checkpoint`; the reference model is unchanged
+    pub fn load_checkpoint(&mut self, path: &Path) -> anyhow::Result<()> {
+        let safetensors = unsafe { MmapedSafetensors::new(path)? };
+        copy_weights(&self.varmap, &safetensors, &self.device)
+    }
+
+    fn encode_prompt(&self, prompt: &str) -> anyhow::Result<Vec<u32>> {
+        let text = format!("<|im_start|>user\n{}<|im_end|>\n<|im_start|>assistant\n", prompt);
+        let ids = self.tokenizer.encode(text, false).map_err(anyhow::Error::msg)?.get_ids().to_vec();
+        Ok(ids[ids.len().saturating_sub(self.config.max_prompt_tokens)..].to_vec())
+    }
+
+    fn sample(&mut self, prompt_ids: &[u32]) -> anyhow::Result<Vec<u32>> {
+        self.seed += 1;
+        let mut sampler = LogitsProcessor::new(self.seed, Some(self.config.temperature), Some(0.95));
+        let mut tokens = prompt_ids.to_vec();
+        let mut completion = Vec::new();
+        self.policy.clear_cache();
+        for step in 0..self.config.max_new_tokens {
+            let start = if step == 0 { 0 } else { tokens.len() - 1 };
+            let input = Tensor::new(&tokens[start..], &self.device)?.unsqueeze(0)?.to_dtype(DType::I64)?;
+            let logits = self.policy.forward(&input, start)?.flatten_all()?.to_dtype(DType::F32)?.detach();
+            let next = sampler.sample(&logits)?;
+            if EOS_TOKENS.contains(&next) {
+                break;
+            }
+            tokens.push(next);
+            completion.push(next);
+        }
+        self.policy.clear_cache();
+        Ok(completion)
+    }
+
+    /// Mean log-probability `model` gives the completion tokens after the prompt
+    fn sequence_log_prob(model: &mut ReasonerModel, device: &Device, prompt_ids: &[u32], completion_ids: &[u32]) -> anyhow::Result<Tensor> {
+        let ids: Vec<u32> = prompt_ids.iter().chain(completion_ids).copied().collect();
+        let input = Tensor::new(ids.as_slice(), device)?.unsqueeze(0)?.to_dtype(DType::I64)?;
+        model.clear_cache();
+        let logits = model.forward_full(&input, 0)?.squeeze(0)?.to_dtype(DType::F32)?;
+        model.clear_cache();
+        // The logits at position t predict token t + 1
+        let predictions = logits.narrow(0, prompt_ids.len() - 1, completion_ids.len())?;
+        let log_probs = candle_nn::ops::log_softmax(&predictions, D::Minus1)?;
+        let targets = Tensor::new(completion_ids, device)?.unsqueeze(1)?;
+        Ok(log_probs.gather(&targets, 1)?.squeeze(1)?.mean_all()?)
+    }
+
+    /// Sample `group_size` completions for `prompt` and score them with `reward`
+    pub async fn collect_rollouts(&mut self, prompt: &str, reward: &dyn RewardModel) -> anyhow::Result<Vec<Rollout>> {
+        let prompt_ids = self.encode_prompt(prompt)?;
+        let mut rollouts = Vec::with_capacity(self.config.group_size);
+        for _ in 0..self.config.group_size {
+            let completion_ids = self.sample(&prompt_ids)?;
+            let completion = self.tokenizer.decode(&completion_ids, true).map_err(anyhow::Error::msg)?;
+            rollouts.push(Rollout { prompt: prompt.to_string(), completion, reward: 0.0, prompt_ids: prompt_ids.clone(), completion_ids });
+        }
+
+        let candidates: Vec<Candidate> = rollouts.iter().map(|r| Candidate {
+            agent_id: "Reasoner".to_string(),
+            answer: r.completion.clone(),
+            quality_score: 0.0,
+            risk_score: 0.0,
+            novelty_score: 0.0,
+            cost_tokens: r.completion_ids.len() as u32,
+            assurance: AssuranceLevel::L0,
+            reward_score: None,
+            scale_elasticity: ScaleElasticity::Unknown,
+        }).collect();
+        let scores = reward.score(prompt, &candidates).await?;
+        for (rollout, score) in rollouts.iter_mut().zip(scores) {
+            rollout.reward = score;
+        }
+        Ok(rollouts)
+    }
+
+    /// One optimizer step on a group; `None` when the group carries no signal
+    /// (fewer than two completions, or all rewarded the same)
+    pub fn train_step(&mut self, rollouts: &[Rollout]) -> anyhow::Result<Option<f32>> {
+        let group: Vec<&Rollout> = rollouts.iter().filter(|r| !r.completion_ids.is_empty()).collect();
+        let rewards: Vec<f32> = group.iter().map(|r| r.reward).collect();
+        if group.len() < 2 || rewards.iter().all(|r| (r - rewards[0]).abs() < f32::EPSILON) {
+            return Ok(None);
+        }
+        let advantages = self.trainer.calculate_advantages(&rewards);
+
+        let mut log_probs = Vec::with_capacity(group.len());
+        let mut ref_log_probs = Vec::with_capacity(group.len());
+        for rollout in &group {
+            log_probs.push(Self::sequence_log_prob(&mut self.policy, &self.device, &rollout.prompt_ids, &rollout.completion_ids)?);
+            ref_log_probs.push(Self::sequence_log_prob(&mut self.reference, &self.device, &rollout.prompt_ids, &rollout.completion_ids)?.detach());
+        }
+        let log_probs = Tensor::stack(&log_probs, 0)?;
+        // Rollouts were sampled from the current weights, so they are also the old policy
+        let old_log_probs = log_probs.detach();
+        let ref_log_probs = Tensor::stack(&ref_log_probs, 0)?;
+        let advantages = Tensor::new(advantages.as_slice(), &self.device)?;
+
+        let loss = self.trainer.clipped_loss(&log_probs, &old_log_probs, &ref_log_probs, &advantages, self.config.clip_epsilon)?;
+        self.trainer.step(&loss)?;
+        Ok(Some(loss.to_scalar::<f32>()?))
+    }
+
+    /// Collect rollouts and step once per prompt, then save the checkpoint
+    pub async fn train_on(&mut self, prompts: &[String], reward: &dyn RewardModel) -> anyhow::Result<GrpoReport> {
+        let mut report = GrpoReport::default();
+        let mut reward_sum = 0.0;
+        let mut scored = 0;
+        for prompt in prompts {
+            let rollouts = self.collect_rollouts(prompt, reward).await?;
+            reward_sum += rollouts.iter().map(|r| r.reward).sum::<f32>();
+            scored += rollouts.len();
+            if let Some(loss) = self.train_step(&rollouts)? {
+                report.steps += 1;
+                report.last_loss = Some(loss);
+                info!("GRPO step {} on '{}': loss {:.4}", report.steps, prompt.chars().take(60).collect::<String>(), loss);
+            }
+        }
+        report.mean_reward = if scored > 0 { reward_sum / scored as f32 } else { 0.0 };
+        if report.steps > 0 {
+            self.save_checkpoint(&self.config.checkpoint)?;
+        }
+        Ok(report)
+    }
+}
+
+/// Copy every tensor the varmap holds from `safetensors`; missing names are an error
+fn copy_weights(varmap: &VarMap, safetensors: &MmapedSafetensors, device: &Device) -> anyhow::Result<()> {
+    let vars = varmap.data().lock().unwrap();
+    for (name, var) in vars.iter() {
+        let tensor = safetensors.load(name, device)?.to_dtype(DType::F32)?;
+        var.set(&tensor)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::rl::Experience;
+
+    #[test]
+    fn test_prompts_from_experiences_dedupes_newest_first() {
+        let mut buffer = ExperienceBuffer::new(10);
+        for query in ["sort a list", "parse a date", "sort a list", " "] {
+            buffer.record(Experience {
+                query: query.into(),
+                steps: Vec::new(),
+                answer: String::new(),
+                total_reward: 0.0,
+                extrinsic_reward: 0.0,
+                intrinsic_reward: 0.0,
+                turn_id: None,
+                feedback: None,
+            });
+        }
+        assert_eq!(prompts_from_experiences(&buffer, 10), vec!["sort a list".to_string(), "parse a date".to_string()]);
+        assert_eq!(prompts_from_experiences(&buffer, 1).len(), 1);
+    }
+}
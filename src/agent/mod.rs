@@ -10,22 +10,33 @@ mod background;
 pub mod provider;
 mod ctm;
 mod cache;
+pub mod registry;
+pub mod grammar;
+pub mod balancer;
+pub mod cost;
+pub mod tokenizer;
+pub mod circuit;
 pub mod nqd;
 pub mod speaker_rs;
 pub mod rl;
+pub mod grpo;
 pub mod training;
 
 pub use speaker_rs::Speaker;
-pub use react::{ReActAgent, ReActStep, AgentResponse, SimpleAgent};
+pub use react::{ReActAgent, ReActStep, AgentResponse, PausedReAct, SimpleAgent};
 pub use reflection::Reflector;
 pub use types::{AgentType, AgentConfig};
 pub use autonomous::AutonomousMachine;
 pub use background::BackgroundThoughtMachine;
 pub use ctm::ContinuousThoughtMachine;
-pub use provider::{LLMProvider, OllamaProvider, OpenAICompatibleProvider, CandleProvider, RemoteNexusProvider, PublishingProvider};
-pub use cache::{LLMCache, CachedProvider};
+pub use provider::{LLMProvider, OllamaProvider, OpenAICompatibleProvider, CandleProvider, RemoteNexusProvider, PublishingProvider, OpenAIProvider, LlamaCppProvider, AnthropicProvider, GeminiProvider, GenerationOptions, JsonSchemaFormat, ChatMessage, ChatRole, ChatImage};
+pub use cache::{LLMCache, CachedProvider, PromptEmbedder, FastEmbedder};
+pub use balancer::{BalancedProvider, BalanceStrategy};
+pub use cost::{CostTracker, UsageSummary, COST_TRACKER};
+pub use circuit::{CircuitBreakerProvider, CircuitRegistry, CircuitState, HealthProber, CIRCUITS};
+pub use registry::{AgentTypeRegistry, CustomAgentType};
 pub use nqd::NQDPortfolio;
-pub use provider::dynamic_provider;
+pub use provider::{dynamic_provider, fallback_providers_from_env};
 pub use pai_core::uap::{SovereignAgent, UapTask, UapStep, UapTaskStatus, UapStepStatus, UapArtifact};
 
 use async_trait::async_trait;
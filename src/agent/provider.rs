@@ -18,16 +18,277 @@ use candle_transformers::models::llama as llama_model;
 use candle_transformers::models::quantized_llama;
 use crate::models::reasoner::{ReasonerModel, Config as ReasonerConfig};
 use tokenizers::Tokenizer;
+use super::tokenizer;
 
 // Truly global lock to protect hardware across all instances
 lazy_static! {
     static ref GLOBAL_HW_LOCK: Arc<Mutex<()>> = Arc::new(Mutex::new(()));
 }
 
+/// Sampling parameters for a single generation request.
+///
+/// Every field is optional; `None` means "use the provider's default".
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop: Vec<String>,
+    /// GBNF grammar constraining the output (honoured by llama.cpp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grammar: Option<String>,
+    /// Tools (`name`, `description`, `parameters`) offered for native tool calling; providers
+    /// that support it render the model's calls in the `→` action syntax
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<serde_json::Value>,
+    /// JSON schema the whole response must match (honoured by OpenAI as `response_format`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<JsonSchemaFormat>,
+}
+
+/// A named JSON schema for structured output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+}
+
+/// The `tools` in `options` whose names native tool-calling APIs accept (`[A-Za-z0-9_-]{1,64}`)
+fn native_tools(options: &GenerationOptions) -> impl Iterator<Item = &serde_json::Value> {
+    options.tools.iter().filter(|tool| {
+        tool["name"].as_str().is_some_and(|name| {
+            (1..=64).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+    })
+}
+
+impl GenerationOptions {
+    /// Merge these options into an OpenAI-style request body
+    pub fn apply_to_openai_body(&self, body: &mut serde_json::Value, default_temperature: Option<f32>) {
+        if let Some(t) = self.temperature.or(default_temperature) {
+            body["temperature"] = json!(t);
+        }
+        if let Some(p) = self.top_p {
+            body["top_p"] = json!(p);
+        }
+        if let Some(k) = self.top_k {
+            // Not part of the OpenAI spec, but honoured by vLLM, llama.cpp and most compatible servers
+            body["top_k"] = json!(k);
+        }
+        if let Some(m) = self.max_tokens {
+            body["max_tokens"] = json!(m);
+        }
+        if !self.stop.is_empty() {
+            body["stop"] = json!(self.stop);
+        }
+    }
+
+    /// Ollama-style `options` object
+    pub fn to_ollama_options(&self) -> serde_json::Value {
+        let mut opts = json!({});
+        if let Some(t) = self.temperature { opts["temperature"] = json!(t); }
+        if let Some(p) = self.top_p { opts["top_p"] = json!(p); }
+        if let Some(k) = self.top_k { opts["top_k"] = json!(k); }
+        if let Some(m) = self.max_tokens { opts["num_predict"] = json!(m); }
+        if !self.stop.is_empty() { opts["stop"] = json!(self.stop); }
+        opts
+    }
+
+    /// Returns the stop sequence that `text` ends with, if any
+    pub fn matched_stop(&self, text: &str) -> Option<&str> {
+        self.stop.iter().find(|s| !s.is_empty() && text.ends_with(s.as_str())).map(|s| s.as_str())
+    }
+
+    /// Build a candle logits processor honouring temperature, top-k and top-p
+    fn logits_processor(&self, seed: u64) -> LogitsProcessor {
+        use candle_transformers::generation::Sampling;
+        let temperature = self.temperature.unwrap_or(0.7) as f64;
+        let sampling = if temperature <= 0.0 {
+            Sampling::ArgMax
+        } else {
+            match (self.top_k, self.top_p) {
+                (Some(k), Some(p)) => Sampling::TopKThenTopP { k: k as usize, p: p as f64, temperature },
+                (Some(k), None) => Sampling::TopK { k: k as usize, temperature },
+                (None, Some(p)) => Sampling::TopP { p: p as f64, temperature },
+                (None, None) => Sampling::TopP { p: 0.9, temperature },
+            }
+        };
+        LogitsProcessor::from_sampling(seed, sampling)
+    }
+}
+
+/// Role of a chat message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl ChatRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        }
+    }
+}
+
+/// A single message in a chat-formatted conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    /// Inline images for vision-capable providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ChatImage>,
+}
+
+/// A base64-encoded image attached to a chat message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatImage {
+    pub mime_type: String,
+    /// Base64 payload without the `data:` prefix
+    pub data: String,
+}
+
+impl ChatImage {
+    /// Load and encode an image file, inferring the MIME type from its extension
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        use base64::{engine::general_purpose, Engine as _};
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to read image {}", path.display()))?;
+        let mime_type = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("webp") => "image/webp",
+            Some("gif") => "image/gif",
+            _ => "image/png",
+        };
+        Ok(Self { mime_type: mime_type.to_string(), data: general_purpose::STANDARD.encode(bytes) })
+    }
+
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+}
+
+impl ChatMessage {
+    pub fn new(role: ChatRole, content: impl Into<String>) -> Self {
+        Self { role, content: content.into(), images: Vec::new() }
+    }
+
+    pub fn with_image(mut self, image: ChatImage) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::Assistant, content)
+    }
+
+    pub fn tool(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::Tool, content)
+    }
+
+    /// Build the conventional `[system?, user]` pair used by prompt-based generation
+    pub fn from_prompt(prompt: String, system: Option<String>) -> Vec<ChatMessage> {
+        let mut messages = Vec::new();
+        if let Some(sys) = system {
+            messages.push(ChatMessage::system(sys));
+        }
+        messages.push(ChatMessage::user(prompt));
+        messages
+    }
+
+    /// OpenAI-style message object.
+    /// Tool results are sent as `user` messages because they carry no `tool_call_id`.
+    pub fn to_openai_json(&self) -> serde_json::Value {
+        let role = match self.role {
+            ChatRole::Tool => "user",
+            other => other.as_str(),
+        };
+        json!({ "role": role, "content": self.content })
+    }
+}
+
+/// Next item of a streaming response read in a spawned task, or `None` once the caller dropped
+/// the receiving stream. Ending the reader drops the response, closing the connection so the
+/// server stops generating.
+async fn next_unless_closed<S, T>(stream: &mut S, tx: &tokio::sync::mpsc::UnboundedSender<T>) -> Option<S::Item>
+where
+    S: futures_util::Stream + Unpin,
+{
+    tokio::select! {
+        item = stream.next() => item,
+        _ = tx.closed() => None,
+    }
+}
+
+/// Flatten a chat transcript into a `(prompt, system)` pair for providers without a native chat API
+pub fn flatten_chat(messages: &[ChatMessage]) -> (String, Option<String>) {
+    let mut system_parts = Vec::new();
+    let mut prompt = String::new();
+    for msg in messages {
+        match msg.role {
+            ChatRole::System => system_parts.push(msg.content.as_str()),
+            ChatRole::User => prompt.push_str(&format!("## User\n{}\n\n", msg.content)),
+            ChatRole::Assistant => prompt.push_str(&format!("## Assistant\n{}\n\n", msg.content)),
+            ChatRole::Tool => prompt.push_str(&format!("## Observation\n{}\n\n", msg.content)),
+        }
+    }
+    prompt.push_str("## Assistant\n");
+    let system = if system_parts.is_empty() { None } else { Some(system_parts.join("\n\n")) };
+    (prompt, system)
+}
+
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String>;
     async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>>;
+    /// Generate with explicit sampling parameters.
+    /// Providers that cannot honour them fall back to `generate`.
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, _options: &GenerationOptions) -> Result<String> {
+        self.generate(model, prompt, system).await
+    }
+    /// Streaming variant of `generate_with_options`
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, _options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream(model, prompt, system).await
+    }
+    /// Generate from a role-tagged message list.
+    /// Providers without a native chat API receive a flattened prompt.
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let (prompt, system) = flatten_chat(&messages);
+        self.generate_with_options(model, prompt, system, options).await
+    }
+    /// Streaming variant of `generate_chat`
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let (prompt, system) = flatten_chat(&messages);
+        self.generate_stream_with_options(model, prompt, system, options).await
+    }
+    /// Number of tokens `text` occupies for `model`.
+    /// Defaults to the tiktoken BPE, which is exact for OpenAI and close for other hosted models.
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        Ok(tokenizer::count_tiktoken(model, text))
+    }
+    /// Liveness probe for `model`, used by the circuit breakers. Defaults to a one-token generation.
+    async fn health_check(&self, model: &str) -> Result<()> {
+        let options = GenerationOptions { max_tokens: Some(1), ..Default::default() };
+        self.generate_with_options(model, "ping".to_string(), None, &options).await.map(|_| ())
+    }
     /// Get a clone of the hardware lock
     fn get_lock(&self) -> Arc<Mutex<()>>;
     /// Send a notification message back to the user/UI
@@ -51,7 +312,15 @@ impl PublishingProvider {
 #[async_trait]
 impl LLMProvider for PublishingProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut stream = self.generate_stream(model, prompt, system).await?;
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model, prompt, system, options).await?;
         let mut full_text = String::new();
         while let Some(chunk) = stream.next().await {
             full_text.push_str(&chunk?);
@@ -59,8 +328,46 @@ impl LLMProvider for PublishingProvider {
         Ok(full_text)
     }
 
-    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        let stream = self.inner.generate_stream(model, prompt, system).await?;
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let stream = self.inner.generate_stream_with_options(model, prompt, system, options).await?;
+        Ok(self.publish(stream))
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_chat_stream(model, messages, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
+        }
+        Ok(full_text)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let stream = self.inner.generate_chat_stream(model, messages, options).await?;
+        Ok(self.publish(stream))
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        self.inner.count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        self.inner.health_check(model).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.inner.get_lock()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        let _ = self.tx.send(message.to_string());
+        self.inner.notify(message).await
+    }
+}
+
+impl PublishingProvider {
+    /// Mirror every token of `stream` onto the broadcast channel
+    fn publish(&self, stream: BoxStream<'static, Result<String>>) -> BoxStream<'static, Result<String>> {
         let tx = self.tx.clone();
 
         let mapped_stream = futures_util::stream::unfold((stream, String::new(), false), move |(mut s, mut buffer, mut answer_detected)| {
@@ -88,16 +395,7 @@ impl LLMProvider for PublishingProvider {
             }
         });
 
-        Ok(Box::pin(mapped_stream))
-    }
-
-    fn get_lock(&self) -> Arc<Mutex<()>> {
-        self.inner.get_lock()
-    }
-
-    async fn notify(&self, message: &str) -> Result<()> {
-        let _ = self.tx.send(message.to_string());
-        self.inner.notify(message).await
+        Box::pin(mapped_stream)
     }
 }
 
@@ -318,7 +616,15 @@ impl CandleProvider {
 #[async_trait]
 impl LLMProvider for CandleProvider {
     async fn generate(&self, model_name: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut stream = self.generate_stream(model_name, prompt, system).await?;
+        self.generate_with_options(model_name, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model_name: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model_name, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model_name: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model_name, prompt, system, options).await?;
         let mut full_text = String::new();
         while let Some(chunk) = stream.next().await {
             full_text.push_str(&chunk?);
@@ -326,7 +632,7 @@ impl LLMProvider for CandleProvider {
         Ok(full_text)
     }
 
-    async fn generate_stream(&self, model_name: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+    async fn generate_stream_with_options(&self, model_name: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
         let lock = self.lock.clone();
         let device = self.device.clone();
         
@@ -358,13 +664,18 @@ impl LLMProvider for CandleProvider {
 
                 let add_bos = !model_name_lower.contains("qwen") && !has_bos;
                 let mut tokens = tokenizer.encode(full_prompt, add_bos).map_err(anyhow::Error::msg)?.get_ids().to_vec();
-                let mut lp = LogitsProcessor::new(42, Some(0.7), Some(0.9));
+                let mut lp = options.logits_processor(42);
+                let stop_options = options.clone();
+                let max_steps = options.max_tokens.map(|m| m as usize).unwrap_or(1024);
 
                 tokio::task::spawn_blocking(move || {
                     let mut cache = futures::executor::block_on(cache_lock.lock());
                     cache.clear();
                     
-                    for step in 0..1024 {
+                    let mut generated = String::new();
+                    for step in 0..max_steps {
+                        // Stop generating once the caller dropped the stream (e.g. stop_inference)
+                        if tx.is_closed() { break; }
                         let _guard = futures::executor::block_on(lock.lock());
                         let context_size = if step > 0 { 1 } else { tokens.len() };
                         let start_pos = tokens.len().saturating_sub(context_size);
@@ -398,7 +709,9 @@ impl LLMProvider for CandleProvider {
                         match tokenizer.decode(&[next_token], true) {
                             Ok(chunk) => {
                                 tokens.push(next_token);
+                                generated.push_str(&chunk);
                                 if tx.send(Ok(chunk)).is_err() { break; }
+                                if stop_options.matched_stop(&generated).is_some() { break; }
                             },
                             Err(e) => { let _ = tx.send(Err(anyhow::anyhow!("Decode error: {}", e))); break; }
                         }
@@ -446,12 +759,16 @@ impl LLMProvider for CandleProvider {
 
                 let add_bos = !model_name_lower.contains("qwen") && !has_bos;
                 let mut tokens = tokenizer.encode(full_prompt, add_bos).map_err(anyhow::Error::msg)?.get_ids().to_vec();
-                let mut lp = LogitsProcessor::new(42, Some(0.7), Some(0.9));
+                let mut lp = options.logits_processor(42);
+                let stop_options = options.clone();
+                let max_steps = options.max_tokens.map(|m| m as usize).unwrap_or(1024);
 
                 tokio::task::spawn_blocking(move || {
                     let mut model = futures::executor::block_on(model_mutex.lock());
                     
-                    for step in 0..1024 {
+                    let mut generated = String::new();
+                    for step in 0..max_steps {
+                        if tx.is_closed() { break; }
                         let _guard = futures::executor::block_on(lock.lock());
                         let context_size = if step > 0 { 1 } else { tokens.len() };
                         let start_pos = tokens.len().saturating_sub(context_size);
@@ -493,7 +810,9 @@ impl LLMProvider for CandleProvider {
                         match tokenizer.decode(&[next_token], true) {
                             Ok(chunk) => {
                                 tokens.push(next_token);
+                                generated.push_str(&chunk);
                                 if tx.send(Ok(chunk)).is_err() { break; }
+                                if stop_options.matched_stop(&generated).is_some() { break; }
                             },
                             Err(e) => { let _ = tx.send(Err(anyhow::anyhow!("Decode error: {}", e))); break; }
                         }
@@ -514,13 +833,17 @@ impl LLMProvider for CandleProvider {
 
                 let add_bos = false; // Qwen models don't use BOS
                 let mut tokens = tokenizer.encode(full_prompt, add_bos).map_err(anyhow::Error::msg)?.get_ids().to_vec();
-                let mut lp = LogitsProcessor::new(42, Some(0.7), Some(0.9));
+                let mut lp = options.logits_processor(42);
+                let stop_options = options.clone();
+                let max_steps = options.max_tokens.map(|m| m as usize).unwrap_or(2048);
 
                 tokio::task::spawn_blocking(move || {
                     let mut model = futures::executor::block_on(model_mutex.lock());
                     model.clear_cache();
                     
-                    for step in 0..2048 {
+                    let mut generated = String::new();
+                    for step in 0..max_steps {
+                        if tx.is_closed() { break; }
                         let _guard = futures::executor::block_on(lock.lock());
                         let context_size = if step > 0 { 1 } else { tokens.len() };
                         let start_pos = tokens.len().saturating_sub(context_size);
@@ -554,7 +877,9 @@ impl LLMProvider for CandleProvider {
                         match tokenizer.decode(&[next_token], true) {
                             Ok(chunk) => {
                                 tokens.push(next_token);
+                                generated.push_str(&chunk);
                                 if tx.send(Ok(chunk)).is_err() { break; }
+                                if stop_options.matched_stop(&generated).is_some() { break; }
                             },
                             Err(e) => { let _ = tx.send(Err(anyhow::anyhow!("Decode error: {}", e))); break; }
                         }
@@ -569,6 +894,17 @@ impl LLMProvider for CandleProvider {
         Ok(Box::pin(stream))
     }
 
+    async fn count_tokens(&self, model_name: &str, text: &str) -> Result<usize> {
+        // Only count with an already-loaded tokenizer; loading weights just to count isn't worth it
+        let models = self.models.lock().await;
+        Ok(match models.get(model_name) {
+            Some(LoadedModel::Llama(_, _, tok))
+            | Some(LoadedModel::Quantized(_, tok))
+            | Some(LoadedModel::Reasoner(_, tok)) => tokenizer::count_hf(tok, text),
+            None => tokenizer::estimate(text),
+        })
+    }
+
     fn get_lock(&self) -> Arc<Mutex<()>> {
         self.lock.clone()
     }
@@ -591,7 +927,15 @@ impl OllamaProvider {
 #[async_trait]
 impl LLMProvider for OllamaProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut stream = self.generate_stream(model, prompt, system).await?;
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model, prompt, system, options).await?;
         let mut full_text = String::new();
         while let Some(chunk) = stream.next().await {
             full_text.push_str(&chunk?);
@@ -599,21 +943,40 @@ impl LLMProvider for OllamaProvider {
         Ok(full_text)
     }
 
-    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        use ollama_rs::generation::chat::{request::ChatMessageRequest, ChatMessage};
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, generation: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), generation).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_chat_stream(model, messages, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
+        }
+        Ok(full_text)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, chat: Vec<ChatMessage>, generation: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        use ollama_rs::generation::chat::{request::ChatMessageRequest, ChatMessage as OllamaMessage};
         use ollama_rs::models::ModelOptions;        
         let client = self.client.clone();
         let model = model.to_string();
 
-        let mut messages = Vec::new();
-        if let Some(sys) = system {
-            messages.push(ChatMessage::system(sys));
-        }
-        messages.push(ChatMessage::user(prompt));
+        let messages: Vec<OllamaMessage> = chat.into_iter().map(|m| match m.role {
+            ChatRole::System => OllamaMessage::system(m.content),
+            ChatRole::User => OllamaMessage::user(m.content),
+            ChatRole::Assistant => OllamaMessage::assistant(m.content),
+            ChatRole::Tool => OllamaMessage::tool(m.content),
+        }).collect();
 
         let mut options = ModelOptions::default();
         options = options.num_ctx(4096);
         options = options.num_thread(4);
+        if let Some(t) = generation.temperature { options = options.temperature(t); }
+        if let Some(p) = generation.top_p { options = options.top_p(p); }
+        if let Some(k) = generation.top_k { options = options.top_k(k); }
+        if let Some(m) = generation.max_tokens { options = options.num_predict(m as i32); }
+        if !generation.stop.is_empty() { options = options.stop(generation.stop.clone()); }
 
         let request = ChatMessageRequest::new(model, messages).options(options);
 
@@ -652,17 +1015,30 @@ impl RemoteNexusProvider {
 
 #[async_trait]
 impl LLMProvider for RemoteNexusProvider {
-    async fn generate(&self, _model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut messages = Vec::new();
-        if let Some(sys) = system {
-            messages.push(json!({ "role": "system", "content": sys }));
-        }
-        messages.push(json!({ "role": "user", "content": prompt }));
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
 
-        let body = json!({
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.generate_chat(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, _model: &str, chat: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let messages: Vec<serde_json::Value> = chat.iter().map(ChatMessage::to_openai_json).collect();
+
+        let mut body = json!({
             "messages": messages,
             "max_tokens": 1024,
         });
+        options.apply_to_openai_body(&mut body, None);
 
         let res = self.client.post(&self.url)
             .json(&body)
@@ -675,18 +1051,15 @@ impl LLMProvider for RemoteNexusProvider {
         Ok(res["choices"][0]["message"]["content"].as_str().map(|s| s.to_string()).unwrap_or_else(|| "No response from Remote Nexus".to_string()))
     }
 
-    async fn generate_stream(&self, _model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        let mut messages = Vec::new();
-        if let Some(sys) = system {
-            messages.push(json!({ "role": "system", "content": sys }));
-        }
-        messages.push(json!({ "role": "user", "content": prompt }));
+    async fn generate_chat_stream(&self, _model: &str, chat: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let messages: Vec<serde_json::Value> = chat.iter().map(ChatMessage::to_openai_json).collect();
 
-        let body = json!({
+        let mut body = json!({
             "messages": messages,
             "max_tokens": 1024,
             "stream": true,
         });
+        options.apply_to_openai_body(&mut body, None);
 
         let res = self.client.post(&self.url)
             .json(&body)
@@ -697,7 +1070,7 @@ impl LLMProvider for RemoteNexusProvider {
 
         tokio::task::spawn(async move {
             let mut stream = res.bytes_stream();
-            while let Some(item) = stream.next().await {
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
                 match item {
                     Ok(bytes) => {
                         let text = String::from_utf8_lossy(&bytes);
@@ -765,7 +1138,15 @@ impl OpenAICompatibleProvider {
 #[async_trait]
 impl LLMProvider for OpenAICompatibleProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut stream = self.generate_stream(model, prompt, system).await?;
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model, prompt, system, options).await?;
         let mut full_text = String::new();
         while let Some(chunk) = stream.next().await {
             full_text.push_str(&chunk?);
@@ -773,19 +1154,28 @@ impl LLMProvider for OpenAICompatibleProvider {
         Ok(full_text)
     }
 
-    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        let mut messages = Vec::new();
-        if let Some(sys) = system {
-            messages.push(json!({ "role": "system", "content": sys }));
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_chat_stream(model, messages, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
         }
-        messages.push(json!({ "role": "user", "content": prompt }));
+        Ok(full_text)
+    }
 
-        let body = json!({
+    async fn generate_chat_stream(&self, model: &str, chat: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let messages: Vec<serde_json::Value> = chat.iter().map(ChatMessage::to_openai_json).collect();
+
+        let mut body = json!({
             "model": model,
             "messages": messages,
-            "temperature": 0.7,
             "stream": true,
         });
+        options.apply_to_openai_body(&mut body, Some(0.7));
 
         let mut request = self.client.post(format!("{}/chat/completions", self.base_url.trim_end_matches('/')))
             .header("Accept-Language", "en-US,en")
@@ -862,7 +1252,15 @@ impl OllamaCloudProvider {
 #[async_trait]
 impl LLMProvider for OllamaCloudProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let mut stream = self.generate_stream(model, prompt, system).await?;
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model, prompt, system, options).await?;
         let mut full_text = String::new();
         while let Some(chunk) = stream.next().await {
             full_text.push_str(&chunk?);
@@ -870,17 +1268,30 @@ impl LLMProvider for OllamaCloudProvider {
         Ok(full_text)
     }
 
-    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        let mut messages = Vec::new();
-        if let Some(sys) = system {
-            messages.push(json!({ "role": "system", "content": sys }));
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_chat_stream(model, messages, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
         }
-        messages.push(json!({ "role": "user", "content": prompt }));
+        Ok(full_text)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, chat: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        // Ollama's chat API accepts the `tool` role natively
+        let messages: Vec<serde_json::Value> = chat.iter()
+            .map(|m| json!({ "role": m.role.as_str(), "content": m.content }))
+            .collect();
 
         let body = json!({
             "model": model,
             "messages": messages,
             "stream": true,
+            "options": options.to_ollama_options(),
         });
 
         let mut request = self.client.post(&self.url)
@@ -905,7 +1316,7 @@ impl LLMProvider for OllamaCloudProvider {
             let mut stream = res.bytes_stream();
             let mut buffer = String::new();
 
-            while let Some(item) = stream.next().await {
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
                 match item {
                     Ok(bytes) => {
                         let chunk = String::from_utf8_lossy(&bytes);
@@ -952,62 +1363,814 @@ impl LLMProvider for OllamaCloudProvider {
     }
 }
 
-pub struct SwitchableProvider {
-    inner: Arc<RwLock<Arc<dyn LLMProvider>>>,
+/// llama.cpp `llama-server` provider with GBNF grammar-constrained decoding
+pub struct LlamaCppProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    lock: Arc<Mutex<()>>,
 }
 
-impl SwitchableProvider {
-    pub fn new(initial: Arc<dyn LLMProvider>) -> Self {
+impl LlamaCppProvider {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(initial)),
+            client: Client::new(),
+            base_url,
+            api_key,
+            lock: GLOBAL_HW_LOCK.clone(),
         }
     }
 
-    pub async fn switch_to(&self, new_provider: Arc<dyn LLMProvider>) {
-        let mut lock = self.inner.write().await;
-        *lock = new_provider;
+    fn build_body(model: &str, chat: &[ChatMessage], options: &GenerationOptions, stream: bool) -> serde_json::Value {
+        let mut body = json!({
+            "model": model,
+            "messages": chat.iter().map(ChatMessage::to_openai_json).collect::<Vec<_>>(),
+            "stream": stream,
+        });
+        options.apply_to_openai_body(&mut body, None);
+        if let Some(ref grammar) = options.grammar {
+            body["grammar"] = json!(grammar);
+        }
+        body
+    }
+
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut request = self.client.post(format!("{}/v1/chat/completions", self.base_url.trim_end_matches('/')))
+            .json(body);
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+            error!("❌ llama.cpp server Error ({}): {}", status, text);
+            return Err(anyhow::anyhow!("llama.cpp server Error ({}): {}", status, text));
+        }
+        Ok(res)
     }
 }
 
 #[async_trait]
-impl LLMProvider for SwitchableProvider {
+impl LLMProvider for LlamaCppProvider {
     async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
-        let provider = self.inner.read().await.clone();
-        provider.generate(model, prompt, system).await
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
     }
 
     async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
-        let provider = self.inner.read().await.clone();
-        provider.generate_stream(model, prompt, system).await
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
     }
 
-    fn get_lock(&self) -> Arc<Mutex<()>> {
-        GLOBAL_HW_LOCK.clone()
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.generate_chat(model, ChatMessage::from_prompt(prompt, system), options).await
     }
 
-    async fn notify(&self, message: &str) -> Result<()> {
-        let provider = self.inner.read().await.clone();
-        provider.notify(message).await
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
     }
-}
 
-pub fn create_provider_by_type(provider_type: &str) -> Arc<dyn LLMProvider> {
-    match provider_type.to_lowercase().as_str() {
-        "ollama" => {
-            let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost".to_string());
-            let port = std::env::var("OLLAMA_PORT").unwrap_or_else(|_| "11434".to_string())
-                .parse::<u16>().unwrap_or(11434);
-            
-            println!("🦙 Initializing Ollama Provider at {}:{}...", host, port);
-            let client = ollama_rs::Ollama::new(host, port);
-            Arc::new(OllamaProvider::new(client))
-        }
-        "turbo" | "ollama-cloud" | "ollama-hosted" => {
-            let api_key = std::env::var("OLLAMA_API_KEY").ok();
-            println!("☁️  Initializing Ollama Cloud Hosted Inference at https://ollama.com/api...");
-            Arc::new(OllamaCloudProvider::new(api_key))
-        }
-        "openai" | "cloud" => {
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let body = Self::build_body(model, &messages, options, false);
+        let res = self.send(&body).await?.json::<serde_json::Value>().await?;
+        Ok(res["choices"][0]["message"]["content"].as_str().unwrap_or_default().to_string())
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let body = Self::build_body(model, &messages, options, true);
+        let res = self.send(&body).await?;
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::task::spawn(async move {
+            let mut stream = res.bytes_stream();
+            let mut buffer = String::new();
+
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
+                match item {
+                    Ok(bytes) => {
+                        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+                        while let Some(newline_idx) = buffer.find('\n') {
+                            let line = buffer[..newline_idx].trim().to_string();
+                            buffer = buffer[newline_idx + 1..].to_string();
+
+                            let Some(data) = line.strip_prefix("data: ") else { continue };
+                            if data == "[DONE]" { return; }
+                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
+                                if let Some(content) = json["choices"][0]["delta"]["content"].as_str() {
+                                    let _ = tx.send(Ok(content.to_string()));
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(anyhow::anyhow!("llama.cpp stream error: {}", e)));
+                        break;
+                    }
+                }
+            }
+        });
+
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|val| (val, rx))
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.lock.clone()
+    }
+}
+
+/// First-class OpenAI provider: image inputs, native tool calling (`GenerationOptions::tools`)
+/// and structured output (`GenerationOptions::json_schema`)
+pub struct OpenAIProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl OpenAIProvider {
+    pub fn new(api_key: Option<String>) -> Self {
+        let base_url = std::env::var("OPENAI_BASE_URL").unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
+        Self::with_base_url(base_url, api_key)
+    }
+
+    pub fn with_base_url(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            base_url,
+            api_key,
+            lock: GLOBAL_HW_LOCK.clone(),
+        }
+    }
+
+    fn message_json(msg: &ChatMessage) -> serde_json::Value {
+        let mut value = msg.to_openai_json();
+        if !msg.images.is_empty() {
+            let mut parts = vec![json!({ "type": "text", "text": msg.content })];
+            for image in &msg.images {
+                parts.push(json!({ "type": "image_url", "image_url": { "url": image.data_url() } }));
+            }
+            value["content"] = json!(parts);
+        }
+        value
+    }
+
+    fn build_body(&self, model: &str, chat: &[ChatMessage], options: &GenerationOptions, stream: bool) -> serde_json::Value {
+        let mut body = json!({
+            "model": model,
+            "messages": chat.iter().map(Self::message_json).collect::<Vec<_>>(),
+            "stream": stream,
+        });
+        options.apply_to_openai_body(&mut body, None);
+        // OpenAI rejects `top_k`
+        if let Some(obj) = body.as_object_mut() {
+            obj.remove("top_k");
+        }
+        let tools: Vec<_> = native_tools(options).map(|t| json!({ "type": "function", "function": t })).collect();
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+        }
+        if let Some(ref format) = options.json_schema {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": { "name": format.name, "schema": format.schema, "strict": true }
+            });
+        }
+        body
+    }
+
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut request = self.client.post(format!("{}/chat/completions", self.base_url.trim_end_matches('/')))
+            .json(body);
+        if let Some(ref key) = self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+            error!("❌ OpenAI API Error ({}): {}", status, text);
+            return Err(anyhow::anyhow!("OpenAI API Error ({}): {}", status, text));
+        }
+        Ok(res)
+    }
+
+    /// Render a native tool call in the SNS action syntax understood by the ReAct parser
+    fn render_tool_call(name: &str, arguments: &str) -> String {
+        let parameters: serde_json::Value = serde_json::from_str(arguments).unwrap_or_else(|_| json!({}));
+        format!("\n→ {}\n", json!({ "name": name, "parameters": parameters }))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OpenAIProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.generate_chat(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let body = self.build_body(model, &messages, options, false);
+        let res = self.send(&body).await?.json::<serde_json::Value>().await?;
+
+        let message = &res["choices"][0]["message"];
+        let mut output = message["content"].as_str().unwrap_or_default().to_string();
+        for call in message["tool_calls"].as_array().cloned().unwrap_or_default() {
+            output.push_str(&Self::render_tool_call(
+                call["function"]["name"].as_str().unwrap_or_default(),
+                call["function"]["arguments"].as_str().unwrap_or("{}"),
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let body = self.build_body(model, &messages, options, true);
+        let res = self.send(&body).await?;
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::task::spawn(async move {
+            let mut stream = res.bytes_stream();
+            let mut buffer = String::new();
+            // Tool call fragments keyed by index: (name, arguments)
+            let mut calls: std::collections::BTreeMap<u64, (String, String)> = std::collections::BTreeMap::new();
+
+            let flush = |calls: &mut std::collections::BTreeMap<u64, (String, String)>, tx: &tokio::sync::mpsc::UnboundedSender<Result<String>>| {
+                for (_, (name, args)) in std::mem::take(calls) {
+                    let _ = tx.send(Ok(OpenAIProvider::render_tool_call(&name, &args)));
+                }
+            };
+
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
+                match item {
+                    Ok(bytes) => {
+                        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+                        while let Some(newline_idx) = buffer.find('\n') {
+                            let line = buffer[..newline_idx].trim().to_string();
+                            buffer = buffer[newline_idx + 1..].to_string();
+
+                            let Some(data) = line.strip_prefix("data: ") else { continue };
+                            if data == "[DONE]" {
+                                flush(&mut calls, &tx);
+                                return;
+                            }
+                            let Ok(json) = serde_json::from_str::<serde_json::Value>(data) else { continue };
+                            let choice = &json["choices"][0];
+
+                            if let Some(content) = choice["delta"]["content"].as_str() {
+                                let _ = tx.send(Ok(content.to_string()));
+                            }
+                            for call in choice["delta"]["tool_calls"].as_array().cloned().unwrap_or_default() {
+                                let entry = calls.entry(call["index"].as_u64().unwrap_or(0)).or_default();
+                                if let Some(name) = call["function"]["name"].as_str() {
+                                    entry.0.push_str(name);
+                                }
+                                if let Some(args) = call["function"]["arguments"].as_str() {
+                                    entry.1.push_str(args);
+                                }
+                            }
+                            if !choice["finish_reason"].is_null() {
+                                flush(&mut calls, &tx);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(anyhow::anyhow!("OpenAI stream error: {}", e)));
+                        break;
+                    }
+                }
+            }
+            flush(&mut calls, &tx);
+        });
+
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|val| (val, rx))
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.lock.clone()
+    }
+}
+
+/// Anthropic Messages API provider (Claude models), with native tool use for the
+/// `GenerationOptions::tools` of each request
+pub struct AnthropicProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl AnthropicProvider {
+    const API_VERSION: &'static str = "2023-06-01";
+    const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            base_url,
+            api_key,
+            lock: GLOBAL_HW_LOCK.clone(),
+        }
+    }
+
+    /// Build a Messages API request body. System messages are hoisted into `system`,
+    /// tool observations are sent as user turns, and consecutive turns with the same
+    /// role are merged because the API requires strict user/assistant alternation.
+    fn build_body(&self, model: &str, chat: Vec<ChatMessage>, options: &GenerationOptions, stream: bool) -> serde_json::Value {
+        let mut system_parts = Vec::new();
+        let mut messages: Vec<(&'static str, String)> = Vec::new();
+        for msg in chat {
+            let role = match msg.role {
+                ChatRole::System => {
+                    system_parts.push(msg.content);
+                    continue;
+                }
+                ChatRole::Assistant => "assistant",
+                ChatRole::User | ChatRole::Tool => "user",
+            };
+            match messages.last_mut() {
+                Some((last_role, content)) if *last_role == role => {
+                    content.push_str("\n\n");
+                    content.push_str(&msg.content);
+                }
+                _ => messages.push((role, msg.content)),
+            }
+        }
+
+        let mut body = json!({
+            "model": model,
+            "max_tokens": options.max_tokens.unwrap_or(Self::DEFAULT_MAX_TOKENS),
+            "messages": messages.into_iter().map(|(role, content)| json!({ "role": role, "content": content })).collect::<Vec<_>>(),
+            "stream": stream,
+        });
+        if !system_parts.is_empty() {
+            body["system"] = json!(system_parts.join("\n\n"));
+        }
+        if let Some(t) = options.temperature { body["temperature"] = json!(t); }
+        if let Some(p) = options.top_p { body["top_p"] = json!(p); }
+        if let Some(k) = options.top_k { body["top_k"] = json!(k); }
+        if !options.stop.is_empty() { body["stop_sequences"] = json!(options.stop); }
+        let tools: Vec<_> = native_tools(options)
+            .map(|t| json!({ "name": t["name"], "description": t["description"], "input_schema": t["parameters"] }))
+            .collect();
+        if !tools.is_empty() { body["tools"] = json!(tools); }
+        body
+    }
+
+    async fn send(&self, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let mut request = self.client.post(format!("{}/messages", self.base_url.trim_end_matches('/')))
+            .header("anthropic-version", Self::API_VERSION)
+            .json(body);
+
+        if let Some(ref key) = self.api_key {
+            request = request.header("x-api-key", key);
+        }
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+            error!("❌ Anthropic API Error ({}): {}", status, text);
+            return Err(anyhow::anyhow!("Anthropic API Error ({}): {}", status, text));
+        }
+        Ok(res)
+    }
+
+    /// Render a `tool_use` block in the SNS action syntax understood by the ReAct parser
+    fn render_tool_use(name: &str, input: &serde_json::Value) -> String {
+        format!("\n→ {}\n", json!({ "name": name, "parameters": input }))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for AnthropicProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.generate_chat(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let body = self.build_body(model, messages, options, false);
+        let res = self.send(&body).await?.json::<serde_json::Value>().await?;
+
+        let mut output = String::new();
+        for block in res["content"].as_array().cloned().unwrap_or_default() {
+            match block["type"].as_str() {
+                Some("text") => output.push_str(block["text"].as_str().unwrap_or_default()),
+                Some("tool_use") => output.push_str(&Self::render_tool_use(
+                    block["name"].as_str().unwrap_or_default(),
+                    &block["input"],
+                )),
+                _ => {}
+            }
+        }
+        Ok(output)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let body = self.build_body(model, messages, options, true);
+        let res = self.send(&body).await?;
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::task::spawn(async move {
+            let mut stream = res.bytes_stream();
+            let mut buffer = String::new();
+            // Tool input arrives as partial JSON and is emitted once its block closes
+            let mut tool: Option<(String, String)> = None;
+
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
+                match item {
+                    Ok(bytes) => {
+                        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+                        while let Some(newline_idx) = buffer.find('\n') {
+                            let line = buffer[..newline_idx].trim().to_string();
+                            buffer = buffer[newline_idx + 1..].to_string();
+
+                            let Some(data) = line.strip_prefix("data: ") else { continue };
+                            let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else { continue };
+
+                            match event["type"].as_str() {
+                                Some("content_block_start") if event["content_block"]["type"] == "tool_use" => {
+                                    let name = event["content_block"]["name"].as_str().unwrap_or_default().to_string();
+                                    tool = Some((name, String::new()));
+                                }
+                                Some("content_block_delta") => {
+                                    let delta = &event["delta"];
+                                    match delta["type"].as_str() {
+                                        Some("text_delta") => {
+                                            let _ = tx.send(Ok(delta["text"].as_str().unwrap_or_default().to_string()));
+                                        }
+                                        Some("input_json_delta") => {
+                                            if let Some((_, ref mut input)) = tool {
+                                                input.push_str(delta["partial_json"].as_str().unwrap_or_default());
+                                            }
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                                Some("content_block_stop") => {
+                                    if let Some((name, input)) = tool.take() {
+                                        let input = serde_json::from_str(&input).unwrap_or_else(|_| json!({}));
+                                        let _ = tx.send(Ok(AnthropicProvider::render_tool_use(&name, &input)));
+                                    }
+                                }
+                                Some("message_stop") => return,
+                                Some("error") => {
+                                    let _ = tx.send(Err(anyhow::anyhow!("Anthropic stream error: {}", event["error"])));
+                                    return;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(anyhow::anyhow!("Anthropic stream error: {}", e)));
+                        break;
+                    }
+                }
+            }
+        });
+
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|val| (val, rx))
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.lock.clone()
+    }
+}
+
+/// Google Gemini provider (Generative Language API)
+pub struct GeminiProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl GeminiProvider {
+    pub fn new(base_url: String, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            client,
+            base_url,
+            api_key,
+            lock: GLOBAL_HW_LOCK.clone(),
+        }
+    }
+
+    /// Gemini model for a Router scale tier
+    pub fn model_for_scale(class: crate::orchestrator::ScaleClass) -> &'static str {
+        use crate::orchestrator::ScaleClass;
+        match class {
+            ScaleClass::Logic | ScaleClass::Tiny => "gemini-2.0-flash-lite",
+            ScaleClass::Standard => "gemini-2.5-flash",
+            ScaleClass::Heavy => "gemini-2.5-pro",
+        }
+    }
+
+    /// Accept native Gemini names as-is; anything else (e.g. a local tier default) maps to Flash
+    fn resolve_model(model: &str) -> String {
+        if model.starts_with("gemini") {
+            model.to_string()
+        } else {
+            debug!("Non-Gemini model '{}' requested; using Flash", model);
+            Self::model_for_scale(crate::orchestrator::ScaleClass::Standard).to_string()
+        }
+    }
+
+    fn build_body(chat: Vec<ChatMessage>, options: &GenerationOptions) -> serde_json::Value {
+        let mut system_parts = Vec::new();
+        let mut contents: Vec<serde_json::Value> = Vec::new();
+        for msg in chat {
+            let role = match msg.role {
+                ChatRole::System => {
+                    system_parts.push(msg.content);
+                    continue;
+                }
+                ChatRole::Assistant => "model",
+                ChatRole::User | ChatRole::Tool => "user",
+            };
+            let mut parts = vec![json!({ "text": msg.content })];
+            for image in msg.images {
+                parts.push(json!({ "inline_data": { "mime_type": image.mime_type, "data": image.data } }));
+            }
+            contents.push(json!({ "role": role, "parts": parts }));
+        }
+
+        let mut config = json!({});
+        if let Some(t) = options.temperature { config["temperature"] = json!(t); }
+        if let Some(p) = options.top_p { config["topP"] = json!(p); }
+        if let Some(k) = options.top_k { config["topK"] = json!(k); }
+        if let Some(m) = options.max_tokens { config["maxOutputTokens"] = json!(m); }
+        if !options.stop.is_empty() { config["stopSequences"] = json!(options.stop); }
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": config,
+        });
+        if !system_parts.is_empty() {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system_parts.join("\n\n") }] });
+        }
+        body
+    }
+
+    fn extract_text(response: &serde_json::Value) -> String {
+        response["candidates"][0]["content"]["parts"].as_array()
+            .map(|parts| parts.iter().filter_map(|p| p["text"].as_str()).collect::<String>())
+            .unwrap_or_default()
+    }
+
+    async fn send(&self, model: &str, method: &str, body: &serde_json::Value) -> Result<reqwest::Response> {
+        let url = format!("{}/models/{}:{}", self.base_url.trim_end_matches('/'), Self::resolve_model(model), method);
+        let mut request = self.client.post(url).json(body);
+        if let Some(ref key) = self.api_key {
+            request = request.header("x-goog-api-key", key);
+        }
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+            error!("❌ Gemini API Error ({}): {}", status, text);
+            return Err(anyhow::anyhow!("Gemini API Error ({}): {}", status, text));
+        }
+        Ok(res)
+    }
+}
+
+#[async_trait]
+impl LLMProvider for GeminiProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.generate_chat(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_chat_stream(model, ChatMessage::from_prompt(prompt, system), options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let body = Self::build_body(messages, options);
+        let res = self.send(model, "generateContent", &body).await?.json::<serde_json::Value>().await?;
+        Ok(Self::extract_text(&res))
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let body = Self::build_body(messages, options);
+        let res = self.send(model, "streamGenerateContent?alt=sse", &body).await?;
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::task::spawn(async move {
+            let mut stream = res.bytes_stream();
+            let mut buffer = String::new();
+
+            while let Some(item) = next_unless_closed(&mut stream, &tx).await {
+                match item {
+                    Ok(bytes) => {
+                        buffer.push_str(&String::from_utf8_lossy(&bytes));
+
+                        while let Some(newline_idx) = buffer.find('\n') {
+                            let line = buffer[..newline_idx].trim().to_string();
+                            buffer = buffer[newline_idx + 1..].to_string();
+
+                            let Some(data) = line.strip_prefix("data: ") else { continue };
+                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
+                                let text = GeminiProvider::extract_text(&json);
+                                if !text.is_empty() {
+                                    let _ = tx.send(Ok(text));
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(anyhow::anyhow!("Gemini stream error: {}", e)));
+                        break;
+                    }
+                }
+            }
+        });
+
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|val| (val, rx))
+        });
+        Ok(Box::pin(stream))
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.lock.clone()
+    }
+}
+
+pub struct SwitchableProvider {
+    inner: Arc<RwLock<Arc<dyn LLMProvider>>>,
+}
+
+impl SwitchableProvider {
+    pub fn new(initial: Arc<dyn LLMProvider>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(initial)),
+        }
+    }
+
+    pub async fn switch_to(&self, new_provider: Arc<dyn LLMProvider>) {
+        let mut lock = self.inner.write().await;
+        *lock = new_provider;
+    }
+}
+
+#[async_trait]
+impl LLMProvider for SwitchableProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        let provider = self.inner.read().await.clone();
+        provider.generate(model, prompt, system).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        let provider = self.inner.read().await.clone();
+        provider.generate_stream(model, prompt, system).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let provider = self.inner.read().await.clone();
+        provider.generate_with_options(model, prompt, system, options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let provider = self.inner.read().await.clone();
+        provider.generate_stream_with_options(model, prompt, system, options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let provider = self.inner.read().await.clone();
+        provider.generate_chat(model, messages, options).await
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let provider = self.inner.read().await.clone();
+        provider.generate_chat_stream(model, messages, options).await
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        let provider = self.inner.read().await.clone();
+        provider.count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        let provider = self.inner.read().await.clone();
+        provider.health_check(model).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        GLOBAL_HW_LOCK.clone()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        let provider = self.inner.read().await.clone();
+        provider.notify(message).await
+    }
+}
+
+pub fn create_provider_by_type(provider_type: &str) -> Arc<dyn LLMProvider> {
+    match provider_type.to_lowercase().as_str() {
+        "ollama" if std::env::var("OLLAMA_HOSTS").is_ok() => {
+            // Comma-separated list of Ollama base URLs, e.g. "http://gpu1:11434,http://gpu2:11434"
+            let hosts = std::env::var("OLLAMA_HOSTS").unwrap_or_default();
+            let endpoints: Vec<Arc<dyn LLMProvider>> = hosts.split(',')
+                .filter_map(|h| reqwest::Url::parse(h.trim()).ok())
+                .map(|url| {
+                    let host = format!("{}://{}", url.scheme(), url.host_str().unwrap_or("localhost"));
+                    let port = url.port().unwrap_or(11434);
+                    Arc::new(OllamaProvider::new(ollama_rs::Ollama::new(host, port))) as Arc<dyn LLMProvider>
+                })
+                .collect();
+            let strategy = match std::env::var("AGENCY_BALANCE_STRATEGY").unwrap_or_default().as_str() {
+                "least_latency" => super::balancer::BalanceStrategy::LeastLatency,
+                _ => super::balancer::BalanceStrategy::RoundRobin,
+            };
+
+            println!("⚖️  Initializing Balanced Ollama Provider across {} endpoints ({:?})...", endpoints.len(), strategy);
+            Arc::new(super::balancer::BalancedProvider::new(endpoints, strategy))
+        }
+        "ollama" => {
+            let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost".to_string());
+            let port = std::env::var("OLLAMA_PORT").unwrap_or_else(|_| "11434".to_string())
+                .parse::<u16>().unwrap_or(11434);
+            
+            println!("🦙 Initializing Ollama Provider at {}:{}...", host, port);
+            let client = ollama_rs::Ollama::new(host, port);
+            Arc::new(OllamaProvider::new(client))
+        }
+        "turbo" | "ollama-cloud" | "ollama-hosted" => {
+            let api_key = std::env::var("OLLAMA_API_KEY").ok();
+            println!("☁️  Initializing Ollama Cloud Hosted Inference at https://ollama.com/api...");
+            Arc::new(OllamaCloudProvider::new(api_key))
+        }
+        "openai" => {
+            let api_key = std::env::var("OPENAI_API_KEY").ok();
+
+            println!("☁️  Initializing OpenAI Provider...");
+            Arc::new(OpenAIProvider::new(api_key))
+        }
+        "cloud" | "openai-compatible" => {
             let base_url = std::env::var("OPENAI_BASE_URL").unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
             let api_key = std::env::var("OPENAI_API_KEY").ok();
             
@@ -1021,6 +2184,27 @@ pub fn create_provider_by_type(provider_type: &str) -> Arc<dyn LLMProvider> {
             println!("🚀 Initializing Z.ai (Zhipu AI) Provider at {}...", base_url);
             Arc::new(OpenAICompatibleProvider::new(base_url, api_key))
         }
+        "anthropic" | "claude" => {
+            let base_url = std::env::var("ANTHROPIC_BASE_URL").unwrap_or_else(|_| "https://api.anthropic.com/v1".to_string());
+            let api_key = std::env::var("ANTHROPIC_API_KEY").ok();
+
+            println!("🧩 Initializing Anthropic Provider at {}...", base_url);
+            Arc::new(AnthropicProvider::new(base_url, api_key))
+        }
+        "gemini" | "google" => {
+            let base_url = std::env::var("GEMINI_BASE_URL").unwrap_or_else(|_| "https://generativelanguage.googleapis.com/v1beta".to_string());
+            let api_key = std::env::var("GEMINI_API_KEY").or_else(|_| std::env::var("GOOGLE_API_KEY")).ok();
+
+            println!("✨ Initializing Google Gemini Provider at {}...", base_url);
+            Arc::new(GeminiProvider::new(base_url, api_key))
+        }
+        "llamacpp" | "llama.cpp" | "llama-server" => {
+            let base_url = std::env::var("LLAMACPP_BASE_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+            let api_key = std::env::var("LLAMACPP_API_KEY").ok();
+
+            println!("🦙 Initializing llama.cpp server Provider at {}...", base_url);
+            Arc::new(LlamaCppProvider::new(base_url, api_key))
+        }
         "candle" | "native" => {
             println!("🦀 Initializing Native Candle (Rust) Provider...");
             Arc::new(CandleProvider::new().expect("Failed to initialize Candle provider"))
@@ -1032,8 +2216,32 @@ pub fn create_provider_by_type(provider_type: &str) -> Arc<dyn LLMProvider> {
     }
 }
 
+/// Provider types (as in `AGENCY_PROVIDER`) that run inference on this machine or its LAN
+pub fn is_local_provider(provider_type: &str) -> bool {
+    matches!(
+        provider_type.to_lowercase().as_str(),
+        "ollama" | "llamacpp" | "llama.cpp" | "llama-server" | "candle" | "native"
+    )
+}
+
+/// Ordered fallback providers from `AGENCY_FALLBACK_PROVIDERS` (e.g. "ollama,openai")
+pub fn fallback_providers_from_env() -> Vec<Arc<dyn LLMProvider>> {
+    std::env::var("AGENCY_FALLBACK_PROVIDERS")
+        .map(|list| {
+            list.split(',')
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .map(create_provider_by_type)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn dynamic_provider() -> Arc<SwitchableProvider> {
-    let provider_type = std::env::var("AGENCY_PROVIDER").unwrap_or_else(|_| "zai".to_string());
+    // An explicit AGENCY_PROVIDER wins; otherwise a configured OpenAI key selects OpenAI
+    let provider_type = std::env::var("AGENCY_PROVIDER").unwrap_or_else(|_| {
+        if std::env::var("OPENAI_API_KEY").is_ok() { "openai".to_string() } else { "zai".to_string() }
+    });
     let initial = create_provider_by_type(&provider_type);
     Arc::new(SwitchableProvider::new(initial))
 }
@@ -1056,4 +2264,96 @@ mod tests {
         assert!(!response.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_flatten_chat() {
+        let messages = vec![
+            ChatMessage::system("be terse"),
+            ChatMessage::user("list files"),
+            ChatMessage::assistant("→ {\"name\": \"ls\"}"),
+            ChatMessage::tool("👁️ a.rs"),
+        ];
+        let (prompt, system) = flatten_chat(&messages);
+        assert_eq!(system.as_deref(), Some("be terse"));
+        assert!(prompt.contains("## User\nlist files"));
+        assert!(prompt.contains("## Observation\n👁️ a.rs"));
+        assert!(prompt.ends_with("## Assistant\n"));
+        assert_eq!(messages[3].to_openai_json()["role"], "user");
+    }
+
+    #[test]
+    fn test_anthropic_body_merges_roles() {
+        let provider = AnthropicProvider::new("http://localhost".to_string(), None);
+        let messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("q"),
+            ChatMessage::assistant("→ call"),
+            ChatMessage::tool("👁️ result"),
+            ChatMessage::user("follow-up"),
+        ];
+        let options = GenerationOptions {
+            top_k: Some(5),
+            stop: vec!["END".to_string()],
+            tools: vec![json!({ "name": "web_search", "description": "Search", "parameters": { "type": "object" } })],
+            ..Default::default()
+        };
+        let body = provider.build_body("claude-sonnet", messages, &options, false);
+
+        assert_eq!(body["system"], "sys");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 3);
+        assert_eq!(body["messages"][2]["content"], "👁️ result\n\nfollow-up");
+        assert_eq!(body["top_k"], 5);
+        assert_eq!(body["stop_sequences"][0], "END");
+        assert_eq!(body["max_tokens"], 4096);
+        assert_eq!(body["tools"][0]["name"], "web_search");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+    }
+
+    #[test]
+    fn test_gemini_body_and_model_mapping() {
+        let image = ChatImage { mime_type: "image/png".to_string(), data: "AAAA".to_string() };
+        let messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("what is this?").with_image(image),
+            ChatMessage::assistant("a square"),
+        ];
+        let body = GeminiProvider::build_body(messages, &GenerationOptions { max_tokens: Some(64), ..Default::default() });
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "sys");
+        assert_eq!(body["contents"][0]["parts"][1]["inline_data"]["mime_type"], "image/png");
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 64);
+
+        assert_eq!(GeminiProvider::resolve_model("gemini-2.5-pro"), "gemini-2.5-pro");
+        assert_eq!(GeminiProvider::resolve_model("qwen2.5:3b-q4"), "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn test_openai_body_images_tools_schema() {
+        let provider = OpenAIProvider::with_base_url("http://localhost".to_string(), None);
+        let image = ChatImage { mime_type: "image/png".to_string(), data: "AAAA".to_string() };
+        let messages = vec![ChatMessage::user("describe").with_image(image)];
+        let options = GenerationOptions {
+            top_k: Some(10),
+            tools: vec![
+                json!({ "name": "vision", "parameters": { "type": "object" } }),
+                json!({ "name": "mcp:fs/read", "parameters": { "type": "object" } }),
+            ],
+            json_schema: Some(JsonSchemaFormat { name: "answer".to_string(), schema: json!({ "type": "object" }) }),
+            ..Default::default()
+        };
+        let body = provider.build_body("gpt-4o", &messages, &options, false);
+
+        assert_eq!(body["messages"][0]["content"][1]["image_url"]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(body["tools"][0]["type"], "function");
+        // Names the API would reject are left to the prompt
+        assert_eq!(body["tools"].as_array().unwrap().len(), 1);
+        assert_eq!(body["response_format"]["json_schema"]["name"], "answer");
+        assert!(provider.build_body("gpt-4o", &messages, &GenerationOptions::default(), false).get("tools").is_none());
+        assert!(body.get("top_k").is_none());
+        assert!(body.get("grammar").is_none());
+
+        assert_eq!(OpenAIProvider::render_tool_call("vision", "{\"action\":\"capture_screen\"}").trim(),
+            "→ {\"name\":\"vision\",\"parameters\":{\"action\":\"capture_screen\"}}");
+    }
 }
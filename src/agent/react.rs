@@ -10,7 +10,7 @@ use std::collections::HashMap;
 use tracing::{debug, info, warn};
 use futures_util::StreamExt;
 
-use super::{Agent, AgentConfig, AgentType, is_action_query, LLMProvider, OllamaProvider, OpenAICompatibleProvider, AgentResult, AgentError};
+use super::{Agent, AgentConfig, AgentType, is_action_query, LLMProvider, OllamaProvider, OpenAICompatibleProvider, AgentResult, AgentError, ChatMessage, ChatImage, GenerationOptions};
 use crate::memory::Memory;
 use crate::tools::{ToolCall, ToolRegistry};
 use pai_core::{HookManager, HookEvent, HookEventType, HookAction};
@@ -32,6 +32,9 @@ pub struct ReActStep {
     pub is_final: bool,
     /// The final answer (if is_final is true)
     pub answer: Option<String>,
+    /// Image files produced by the actions (e.g. VisionTool captures), forwarded to vision-capable providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
 }
 
 impl ReActStep {
@@ -42,6 +45,7 @@ impl ReActStep {
             observations: Vec::new(),
             is_final: false,
             answer: None,
+            images: Vec::new(),
         }
     }
 
@@ -62,10 +66,16 @@ impl ReActStep {
             observations: Vec::new(),
             is_final: true,
             answer: Some(answer.into()),
+            images: Vec::new(),
         }
     }
 }
 
+fn is_image_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    [".png", ".jpg", ".jpeg", ".webp", ".gif"].iter().any(|ext| lower.ends_with(ext))
+}
+
 /// Response from an agent execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentResponse {
@@ -87,6 +97,20 @@ pub struct AgentResponse {
     pub cost_tokens: u32,
     /// Pending approval for HITL
     pub pending_approval: Option<crate::safety::ApprovalRequest>,
+    /// Loop state to continue from once the pending call is approved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<PausedReAct>,
+}
+
+/// A ReAct loop stopped at a tool call awaiting approval. The last step holds the calls
+/// that were held back; `ReActAgent::resume` runs them and carries on from there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausedReAct {
+    pub config: AgentConfig,
+    pub query: String,
+    #[serde(default)]
+    pub context: Option<String>,
+    pub steps: Vec<ReActStep>,
 }
 
 impl AgentResponse {
@@ -102,6 +126,7 @@ impl AgentResponse {
             reliability: 1.0,
             cost_tokens: 0,
             pending_approval: None,
+            paused: None,
         }
     }
 
@@ -120,6 +145,11 @@ impl AgentResponse {
         self
     }
 
+    pub fn with_paused(mut self, paused: PausedReAct) -> Self {
+        self.paused = Some(paused);
+        self
+    }
+
     pub fn failure(error: impl Into<String>, steps: Vec<ReActStep>, agent_type: AgentType) -> Self {
         let error = error.into();
         Self {
@@ -132,6 +162,7 @@ impl AgentResponse {
             reliability: 0.0,
             cost_tokens: 0,
             pending_approval: None,
+            paused: None,
         }
     }
 }
@@ -140,6 +171,8 @@ impl AgentResponse {
 #[derive(Clone)]
 pub struct ReActAgent {
     provider: Arc<dyn LLMProvider>,
+    /// Providers tried in order once the primary exhausts its retries
+    fallback_providers: Vec<Arc<dyn LLMProvider>>,
     config: AgentConfig,
     tools: Arc<ToolRegistry>,
     memory: Option<Arc<dyn Memory>>,
@@ -147,6 +180,10 @@ pub struct ReActAgent {
     pub pai_hooks: Option<Arc<HookManager>>,
     pub pai_memory: Option<Arc<pai_core::memory::TieredMemoryManager>>,
     pub recovery: Option<Arc<pai_core::recovery::RecoveryJournal>>,
+    /// Receives tool observations when the turn is streamed with `Supervisor::handle_stream`
+    turn_events: Option<tokio::sync::mpsc::UnboundedSender<crate::orchestrator::TurnEvent>>,
+    /// Supervisor turn the tool calls are journaled under; a fresh id per execution otherwise
+    turn_id: Option<String>,
 }
 
 impl ReActAgent {
@@ -163,6 +200,7 @@ impl ReActAgent {
 
         Self {
             provider,
+            fallback_providers: Vec::new(),
             config,
             tools,
             memory: None,
@@ -170,6 +208,8 @@ impl ReActAgent {
             pai_hooks: None,
             pai_memory: None,
             recovery: None,
+            turn_events: None,
+            turn_id: None,
         }
     }
 
@@ -180,6 +220,7 @@ impl ReActAgent {
     ) -> Self {
         Self {
             provider,
+            fallback_providers: Vec::new(),
             config,
             tools,
             memory: None,
@@ -187,6 +228,8 @@ impl ReActAgent {
             pai_hooks: None,
             pai_memory: None,
             recovery: None,
+            turn_events: None,
+            turn_id: None,
         }
     }
 
@@ -210,6 +253,11 @@ impl ReActAgent {
         self
     }
 
+    pub fn with_fallback_providers(mut self, providers: Vec<Arc<dyn LLMProvider>>) -> Self {
+        self.fallback_providers = providers;
+        self
+    }
+
     pub fn with_memory(mut self, memory: Arc<dyn Memory>) -> Self {
         self.memory = Some(memory);
         self
@@ -219,6 +267,16 @@ impl ReActAgent {
         self.safety = Some(safety);
         self
     }
+
+    pub fn with_turn_events(mut self, tx: Option<tokio::sync::mpsc::UnboundedSender<crate::orchestrator::TurnEvent>>) -> Self {
+        self.turn_events = tx;
+        self
+    }
+
+    pub fn with_turn_id(mut self, turn_id: Option<String>) -> Self {
+        self.turn_id = turn_id;
+        self
+    }
 }
 
 #[async_trait]
@@ -305,40 +363,44 @@ impl SovereignAgent for ReActAgent {
 }
 
 impl ReActAgent {
-    /// Build the ReAct prompt
-    async fn build_react_prompt(&self, query: &str, steps: &[
-ReActStep],
- context: Option<&str>) -> String {
-        let mut prompt = String::new();
-
-        if let Some(ctx) = context {
-            prompt.push_str(&format!("## Context
-{}
-
-", ctx));
-        }
+    /// Forged tools not yet promoted, offered on top of the agent's allowed tools
+    async fn lab_tools(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tools.tool_names().await.into_iter()
+            .filter(|n| !self.config.allowed_tools.contains(n) && n != "forge_tool")
+            .filter(|n| self.tools.tool_source(n) == "custom")
+            .collect();
+        names.sort();
+        names
+    }
 
-        prompt.push_str("## Available Tools
-");
-        prompt.push_str("Standard Tools:\n");
-        prompt.push_str(&self.tools.generate_filtered_tools_prompt(&self.config.allowed_tools).await);
+    /// Build the ReAct conversation as role-tagged chat messages.
+    ///
+    /// The system message carries the persona, tool surface and response format; the user
+    /// message carries context and the query; each previous step becomes an assistant turn
+    /// followed by a tool turn holding its observations.
+    async fn build_react_messages(&self, query: &str, steps: &[ReActStep], context: Option<&str>) -> Vec<ChatMessage> {
+        let mut system = String::new();
+        system.push_str(&self.config.system_prompt);
+        system.push_str("\n\n");
+
+        system.push_str("## Available Tools\n");
+        system.push_str("Standard Tools:\n");
+        system.push_str(&self.tools.generate_filtered_tools_prompt(&self.config.allowed_tools).await);
         
         // SOTA: Laboratory Surface (FPF Principle)
         // Show dynamic tools that are currently in the 'laboratory'
-        let lab_tools = self.tools.tool_names().await.into_iter()
-            .filter(|n| !self.config.allowed_tools.contains(n) && n != "forge_tool")
-            .collect::<Vec<_>>();
-            
+        let lab_tools = self.lab_tools().await;
+
         if !lab_tools.is_empty() {
-            prompt.push_str("\nLaboratory (Experimental) Tools:\n");
-            prompt.push_str("NOTE: These tools are currently in the laboratory. Successful use will promote them to the standard set.\n");
-            prompt.push_str(&self.tools.generate_filtered_tools_prompt(&lab_tools).await);
+            system.push_str("\nLaboratory (Experimental) Tools:\n");
+            system.push_str("NOTE: These tools are currently in the laboratory. Successful use will promote them to the standard set.\n");
+            system.push_str(&self.tools.generate_filtered_tools_prompt(&lab_tools).await);
         }
         
-        prompt.push_str("\n");
+        system.push('\n');
 
         if self.config.reasoning_enabled {
-            prompt.push_str(r###"## Response Format (SNS-Core)
+            system.push_str(r###"## Response Format (SNS-Core)
 
 Respond using the EXACT symbolic format below. Use symbols to save tokens.
 
@@ -361,7 +423,7 @@ EXAMPLE OF TOOL CALL:
 
 "###);
         } else {
-            prompt.push_str(r###"## Response Format (SNS-Core)
+            system.push_str(r###"## Response Format (SNS-Core)
 Respond directly. Use → for tool calls (JSON). Use 🎯 for final response.
 
 RULES:
@@ -371,32 +433,44 @@ RULES:
 "###);
         }
 
-        prompt.push_str(&format!("## User Query
-{}
+        let mut user = String::new();
+        if let Some(ctx) = context {
+            user.push_str(&format!("## Context\n{}\n\n", ctx));
+        }
+        user.push_str(&format!("## User Query\n{}", query));
 
-", query));
+        let mut messages = vec![ChatMessage::system(system), ChatMessage::user(user)];
 
-        if !steps.is_empty() {
-            prompt.push_str("## Trace
-");
-            for step in steps {
-                if !step.thought.is_empty() {
-                    prompt.push_str(&format!("⚡ {}\n", step.thought));
+        for step in steps {
+            let mut turn = String::new();
+            if !step.thought.is_empty() {
+                turn.push_str(&format!("⚡ {}\n", step.thought));
+            }
+            for action in &step.actions {
+                if let Ok(action_json) = serde_json::to_string(action) {
+                    turn.push_str(&format!("→ {}\n", action_json));
                 }
-                for action in &step.actions {
-                    if let Ok(action_json) = serde_json::to_string(action) {
-                        prompt.push_str(&format!("→ {}\n", action_json));
+            }
+            if !turn.is_empty() {
+                messages.push(ChatMessage::assistant(turn.trim_end()));
+            }
+            if !step.observations.is_empty() {
+                let obs = step.observations.iter()
+                    .map(|o| format!("👁️ {}", o))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let mut tool_msg = ChatMessage::tool(obs);
+                for path in &step.images {
+                    match ChatImage::from_path(path) {
+                        Ok(image) => tool_msg = tool_msg.with_image(image),
+                        Err(e) => warn!("Skipping image observation {}: {}", path, e),
                     }
                 }
-                for obs in &step.observations {
-                    prompt.push_str(&format!("👁️ {}\n", obs));
-                }
-                prompt.push_str("\n");
+                messages.push(tool_msg);
             }
         }
 
-        prompt.push_str("Continue:\n");
-        prompt
+        messages
     }
 
     /// Parse the LLM response using strict Tags
@@ -669,25 +743,74 @@ RULES:
         results
     }
 
-    /// Execute a single step of the ReAct loop with streaming
-    pub async fn step_stream(&self, query: &str, steps: &[ReActStep], context: Option<&str>) -> AgentResult<ReActStep> {
-        let prompt = self.build_react_prompt(query, steps, context).await;
-        let system = Some(self.config.system_prompt.clone());
-        
-        debug!("ReAct prompt (streaming):\n{}", prompt);
-        info!("   ⏳ Iteration starting (model: {})...", self.config.model);
+    /// Run one chat completion against the primary provider, retrying with exponential
+    /// backoff and then failing over to each fallback provider in order.
+    async fn chat_with_failover(&self, messages: Vec<ChatMessage>, stream: bool) -> AgentResult<String> {
+        let mut options = self.config.generation_options();
+        // Only the tools shown in the prompt may be named
+        let mut names = self.config.allowed_tools.clone();
+        names.extend(self.lab_tools().await);
+        options.tools = self.tools.tool_definitions(&names).await;
+        if self.config.constrained_decoding {
+            options.grammar = Some(self.tools.generate_grammar(&names, self.config.reasoning_enabled).await);
+        }
+        let providers = std::iter::once(&self.provider).chain(self.fallback_providers.iter());
+        let mut last_error = String::from("no provider available");
 
-        let mut stream = self.provider.generate_stream(&self.config.model, prompt, system).await
-            .map_err(|e| AgentError::Provider(e.to_string()))?;
+        for (idx, provider) in providers.enumerate() {
+            if idx > 0 {
+                warn!("Failing over to fallback provider #{}", idx);
+                let _ = self.provider.notify(&format!("⚠️ Provider unavailable, failing over (#{})...", idx)).await;
+            }
+
+            for attempt in 0..=self.config.max_retries {
+                if attempt > 0 {
+                    let delay = self.config.retry_backoff_ms.saturating_mul(1 << (attempt - 1).min(6));
+                    warn!("Retrying LLM call in {}ms (attempt {}/{})", delay, attempt, self.config.max_retries);
+                    tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                }
+
+                let result = if stream {
+                    Self::collect_stream(provider.as_ref(), &self.config.model, messages.clone(), &options).await
+                } else {
+                    provider.generate_chat(&self.config.model, messages.clone(), &options).await
+                };
+
+                match result {
+                    Ok(content) => return Ok(content),
+                    Err(e) => {
+                        warn!("LLM call failed: {}", e);
+                        last_error = e.to_string();
+                    }
+                }
+            }
+        }
+
+        Err(AgentError::Provider(last_error))
+    }
+
+    async fn collect_stream(provider: &dyn LLMProvider, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> anyhow::Result<String> {
+        let mut stream = provider.generate_chat_stream(model, messages, options).await?;
         let mut full_content = String::new();
 
-        while let Some(chunk_res) = stream.next().await {
-            let chunk = chunk_res.map_err(|e| AgentError::Provider(e.to_string()))?;
-            full_content.push_str(&chunk);
+        while let Some(chunk) = stream.next().await {
+            full_content.push_str(&chunk?);
             // SOTA: No token-by-token printing to stdout to avoid IO bottlenecks.
             // Tokens are streamed to the UI via the provider's internal tx channel.
         }
 
+        Ok(full_content)
+    }
+
+    /// Execute a single step of the ReAct loop with streaming
+    pub async fn step_stream(&self, query: &str, steps: &[ReActStep], context: Option<&str>) -> AgentResult<ReActStep> {
+        let messages = self.build_react_messages(query, steps, context).await;
+        
+        debug!("ReAct messages (streaming): {:?}", messages);
+        info!("   ⏳ Iteration starting (model: {})...", self.config.model);
+
+        let full_content = self.chat_with_failover(messages, true).await?;
+
         debug!("Full streamed response:\n{}", full_content);
 
         self.parse_response(&full_content, query)
@@ -697,13 +820,11 @@ RULES:
     pub async fn step(&self, query: &str, steps: &[
 ReActStep],
  context: Option<&str>) -> AgentResult<ReActStep> {
-        let prompt = self.build_react_prompt(query, steps, context).await;
-        let system = Some(self.config.system_prompt.clone());
+        let messages = self.build_react_messages(query, steps, context).await;
         
-        debug!("ReAct prompt:\n{}", prompt);
+        debug!("ReAct messages: {:?}", messages);
 
-        let content = self.provider.generate(&self.config.model, prompt, system).await
-            .map_err(|e| AgentError::Provider(e.to_string()))?;
+        let content = self.chat_with_failover(messages, false).await?;
 
         debug!("LLM response:\n{}", content);
 
@@ -742,18 +863,41 @@ impl Agent for ReActAgent {
 }
 
 impl ReActAgent {
+    #[tracing::instrument(name = "agent", skip_all, fields(agent = %self.config.agent_type, model = %self.config.model))]
     pub async fn execute_with_steering(
         &self, 
         query: &str, 
         context: Option<&str>,
-        mut steering_rx: Option<tokio::sync::mpsc::Receiver<String>>
+        steering_rx: Option<tokio::sync::mpsc::Receiver<String>>
     ) -> AgentResult<AgentResponse> {
         info!("ReAct agent starting execution for query: {}", query);
-        
-        let mut steps = Vec::new();
+        self.run_loop(query, context, Vec::new(), None, steering_rx).await
+    }
+
+    /// Continue a loop that paused for approval: the held-back calls run first, then the
+    /// agent reasons on from their observations. Approve the calls on the safety guard first.
+    #[tracing::instrument(name = "agent_resume", skip_all, fields(agent = %self.config.agent_type, model = %self.config.model))]
+    pub async fn resume(&self, paused: PausedReAct) -> AgentResult<AgentResponse> {
+        info!("ReAct agent resuming paused execution for query: {}", paused.query);
+        let mut steps = paused.steps;
+        let pending = steps.pop();
+        self.run_loop(&paused.query, paused.context.as_deref(), steps, pending, None).await
+    }
+
+    async fn run_loop(
+        &self,
+        query: &str,
+        context: Option<&str>,
+        mut steps: Vec<ReActStep>,
+        mut pending: Option<ReActStep>,
+        mut steering_rx: Option<tokio::sync::mpsc::Receiver<String>>
+    ) -> AgentResult<AgentResponse> {
+        let turn_id = self.turn_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let caller = crate::tools::ToolCaller::for_config(&self.config).with_turn(turn_id);
         
         for iteration in 0..self.config.max_iterations {
             debug!("ReAct iteration {}", iteration + 1);
+            crate::orchestrator::KILL_SWITCH.check()?;
             
             // Check for steering messages BEFORE the turn
             if let Some(ref mut rx) = steering_rx {
@@ -764,11 +908,25 @@ impl ReActAgent {
                 }
             }
 
+            // Results of sub-agents this turn spawned arrive as observations
+            if let Some(parent_turn) = crate::orchestrator::event_bus::current_turn() {
+                for child in crate::orchestrator::SUBAGENTS.take_results(&parent_turn) {
+                    let mut step = ReActStep::thought(format!("[SUB-AGENT {}]: finished", child.task_id));
+                    step.observations.push(child.observation());
+                    steps.push(step);
+                }
+            }
+
             let _ = self.provider.notify("STATE:THOUGHT_START").await;
             let _ = self.provider.notify(&format!("STATE:MODEL:{}", self.config.model)).await;
             let _ = self.provider.notify(&format!("\n[ITERATION {}]\n", iteration + 1)).await;
             
-            let mut step = match self.step_stream(query, &steps, context).await {
+            // A resumed loop starts with the step that was waiting for approval
+            let next = match pending.take() {
+                Some(step) => Ok(step),
+                None => self.step_stream(query, &steps, context).await,
+            };
+            let mut step = match next {
                 Ok(s) => {
                     for action in &s.actions {
                         let msg = format!("🔧 Using Tool: {}...", action.name);
@@ -815,19 +973,43 @@ impl ReActAgent {
             if !step.actions.is_empty() {
                 // SOTA: Human-in-the-Loop (HITL) Check (FPF Principle: Verifiable Autonomy)
                 if let Some(ref safety_mutex) = self.safety {
-                    let guard = safety_mutex.lock().await;
+                    let mut guard = safety_mutex.lock().await;
+                    let denials: Vec<String> = step.actions.iter()
+                        .filter_map(|action| guard.policy_denial(&action.name, &action.parameters).map(|reason| format!("{}: {}", action.name, reason)))
+                        .collect();
+                    if !denials.is_empty() {
+                        warn!("Tool calls refused by safety policy: {:?}", denials);
+                        let mut blocked_step = step.clone();
+                        blocked_step.observations = vec![format!(
+                            "SAFETY POLICY BLOCKED: {}. Choose a different approach.",
+                            denials.join("; ")
+                        )];
+                        steps.push(blocked_step);
+                        continue;
+                    }
                     for action in &step.actions {
                         if let Some(request) = guard.needs_human_approval(&action.name, &action.parameters, self.tools.clone()).await {
                             info!("🚨 HITL triggered for tool: {}. Pausing execution for approval.", action.name);
                             let _ = self.provider.notify(&format!("\n🚨 HITL REQUIRED: {}\n", request.rationale)).await;
                             
                             steps.push(step);
+                            let paused = PausedReAct {
+                                config: self.config.clone(),
+                                query: query.to_string(),
+                                context: context.map(str::to_string),
+                                steps: steps.clone(),
+                            };
                             self.normalize_steps(&mut steps);
                             
                             return Ok(AgentResponse::success("Awaiting human approval for sensitive operation.", steps, self.config.agent_type)
-                                .with_approval(request));
+                                .with_approval(request)
+                                .with_paused(paused));
                         }
                     }
+                    // Each approval covers the one call it was granted for
+                    for action in &step.actions {
+                        guard.consume_approval(&action.name, &action.parameters);
+                    }
                 }
 
                 // Loop Guard: Check for redundant tool calls
@@ -911,11 +1093,20 @@ impl ReActAgent {
                     });
                 }
 
-                let results = self.tools.execute_parallel(&step.actions).await;
+                // The registry publishes each chunk as a `ToolProgress` event while the calls run;
+                // the chunks are kept here so a call that errors out still reports what it printed
+                let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+                let results = self.tools.execute_parallel_streaming_as(&step.actions, &caller, Some(progress_tx)).await;
+                let mut streamed: HashMap<String, String> = HashMap::new();
+                while let Ok(progress) = progress_rx.try_recv() {
+                    streamed.entry(progress.tool).or_default().push_str(&progress.chunk);
+                }
                 
                 let mut observations = Vec::new();
+                let mut images = Vec::new();
                 for (i, res) in results.into_iter().enumerate() {
                     let action = &step.actions[i];
+                    let success = res.is_ok();
                     let mut obs = match res {
                         Ok(output) => {
                             crate::emit_event!(crate::orchestrator::AgencyEvent::ToolCallFinished { 
@@ -926,6 +1117,12 @@ impl ReActAgent {
                             
                             // SOTA: Tool Promotion (Laboratory graduation)
                             let _ = self.tools.promote_tool(&action.name).await;
+
+                            if let Some(path) = output.data.get("path").and_then(|p| p.as_str()) {
+                                if is_image_path(path) {
+                                    images.push(path.to_string());
+                                }
+                            }
                             
                             // SOTA: TOON Data Optimization (FPF Principle: Token Sovereignty)
                             // If the tool data is complex (not just summary), use TOON notation.
@@ -941,13 +1138,27 @@ impl ReActAgent {
                                 success: false 
                             });
                             let _ = self.provider.notify(&format!("\n❌ Tool failed: {}\n", e)).await;
-                            format!("Tool execution failed: {}", e)
+                            match streamed.get(&action.name) {
+                                Some(partial) => format!("Tool execution failed: {}\nPartial output:\n{}", e, partial),
+                                None => format!("Tool execution failed: {}", e),
+                            }
                         },
                     };
                     
                     // Context Compression: Truncate tool outputs if they are too long
                     use crate::utils::truncate::{truncate_text, TruncationPolicy};
                     obs = truncate_text(&obs, TruncationPolicy::Bytes(1500));
+                    // Untrusted content is flagged before the model reasons over it
+                    if success && crate::safety::INJECTION_DETECTOR.is_enabled() {
+                        let tool = action.name.clone();
+                        let raw = obs.clone();
+                        obs = tokio::task::spawn_blocking(move || crate::safety::INJECTION_DETECTOR.guard(&tool, raw))
+                            .await
+                            .unwrap_or(obs);
+                    }
+                    if let Some(ref tx) = self.turn_events {
+                        let _ = tx.send(crate::orchestrator::TurnEvent::ToolObservation { tool: action.name.clone(), success, observation: obs.clone() });
+                    }
                     observations.push(obs);
                 }
 
@@ -957,6 +1168,7 @@ impl ReActAgent {
                     observations,
                     is_final: false,
                     answer: None,
+                    images,
                 };
                 steps.push(step_with_obs);
             } else {
@@ -1024,7 +1236,7 @@ impl SimpleAgent {
 
         let _ = self.provider.notify(&format!("STATE:MODEL:{}", self.config.model)).await;
 
-        let content = self.provider.generate(&self.config.model, prompt, system).await
+        let content = self.provider.generate_with_options(&self.config.model, prompt, system, &self.config.generation_options()).await
             .map_err(|e| AgentError::Provider(e.to_string()))?;
         
         // MVPK Projection: Extract Thought (TechView) and Answer (PlainView)
@@ -1140,7 +1352,7 @@ impl SimpleAgent {
         let _ = self.provider.notify(&format!("STATE:MODEL:{}", self.config.model)).await;
 
         // Use streaming generation
-        let mut stream = self.provider.generate_stream(&self.config.model, prompt, system).await
+        let mut stream = self.provider.generate_stream_with_options(&self.config.model, prompt, system, &self.config.generation_options()).await
             .map_err(|e| AgentError::Provider(e.to_string()))?;
         let mut full_response = String::new();
         
@@ -1222,4 +1434,61 @@ mod tests {
         let action = agent.extract_tag(response, "[ACTION]");
         assert_eq!(action.expect("Failed to extract action"), "{\"name\": \"get_weather\", \"parameters\": {\"location\": \"Seattle\"}}");
     }
+
+    #[tokio::test]
+    async fn test_tool_surface_is_allowed_plus_lab_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let custom = dir.path().join("custom");
+        std::fs::create_dir_all(&custom).unwrap();
+        std::fs::write(custom.join("word_count.json"), serde_json::to_string(&serde_json::json!({
+            "name": "word_count", "description": "Count words", "parameters": { "type": "object" },
+            "language": "python", "script_path": "word_count.py"
+        })).unwrap()).unwrap();
+        let tools = Arc::new(ToolRegistry::new(&custom, dir.path().join("standard")));
+        tools.load_dynamic_tools(&custom).await.unwrap();
+        tools.register::<crate::tools::ScienceTool>().await;
+
+        let mut config = AgentConfig::new(AgentType::GeneralChat, &AgencyProfile::default());
+        config.allowed_tools = vec![];
+        let agent = ReActAgent::new(Ollama::default(), config, tools);
+        // Built-in tools outside the allow list stay hidden; forged ones are on trial
+        assert_eq!(agent.lab_tools().await, vec!["word_count".to_string()]);
+
+        let definitions = agent.tools.tool_definitions(&["word_count".to_string(), "missing".to_string()]).await;
+        assert_eq!(definitions, vec![serde_json::json!({ "name": "word_count", "description": "Count words", "parameters": { "type": "object" } })]);
+    }
+
+    struct StaticProvider {
+        reply: Option<&'static str>,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LLMProvider for StaticProvider {
+        async fn generate(&self, _model: &str, _prompt: String, _system: Option<String>) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.reply.map(|r| r.to_string()).ok_or_else(|| anyhow::anyhow!("connection refused"))
+        }
+        async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> anyhow::Result<futures::stream::BoxStream<'static, anyhow::Result<String>>> {
+            let reply = self.generate(model, prompt, system).await?;
+            Ok(Box::pin(futures::stream::once(async move { Ok(reply) })))
+        }
+        fn get_lock(&self) -> Arc<tokio::sync::Mutex<()>> {
+            Arc::new(tokio::sync::Mutex::new(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_failover() {
+        let primary = Arc::new(StaticProvider { reply: None, calls: Default::default() });
+        let fallback = Arc::new(StaticProvider { reply: Some("🎯 recovered"), calls: Default::default() });
+        let config = AgentConfig::new(AgentType::GeneralChat, &AgencyProfile::default()).with_retry(2, 1);
+        let agent = ReActAgent::new_with_provider(primary.clone(), config, Arc::new(ToolRegistry::default()))
+            .with_fallback_providers(vec![fallback.clone()]);
+
+        let step = agent.step("hello", &[], None).await.unwrap();
+        assert_eq!(primary.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(fallback.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(step.answer.unwrap_or_default().contains("recovered"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,209 @@
+//! Agent Type Registry
+//!
+//! Runtime-registrable agent types. A custom type (e.g. "SecurityAuditor") is defined in
+//! `config/agent_types.json` and layered on top of one of the built-in `AgentType`s, which
+//! still drives routing and the ReAct behaviour, while the prompt, model and tool bindings
+//! come from the definition.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::types::{AgentType, AgentConfig};
+use crate::orchestrator::profile::AgencyProfile;
+
+/// A user-defined agent type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomAgentType {
+    /// Unique name, e.g. "SecurityAuditor"
+    pub name: String,
+    /// Built-in type this agent is derived from
+    #[serde(default = "default_base")]
+    pub base: AgentType,
+    #[serde(default)]
+    pub description: String,
+    /// Role prompt; the agency context is appended as for built-in types
+    pub system_prompt: String,
+    /// Model override (falls back to the scale-selected model)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Tool bindings (falls back to the base type's tools when empty)
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_iterations: Option<usize>,
+    /// Query keywords that route to this agent
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+fn default_base() -> AgentType {
+    AgentType::Reasoner
+}
+
+impl CustomAgentType {
+    /// Build an `AgentConfig` for this type
+    pub fn to_config(&self, profile: &AgencyProfile) -> AgentConfig {
+        let mut config = AgentConfig::new(self.base, profile);
+        config.agent_name = Some(self.name.clone());
+        config.system_prompt = format!("{}\n\nAGENCY CONTEXT (U.BoundedContext):\n- Name: {}\n- Mission: {}\n- Traits: {}",
+            self.system_prompt, profile.name, profile.mission, profile.traits.join(", "));
+        if let Some(ref model) = self.model {
+            config.model = model.clone();
+        }
+        if !self.allowed_tools.is_empty() {
+            config.allowed_tools = self.allowed_tools.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature;
+        }
+        if let Some(max_iterations) = self.max_iterations {
+            config.max_iterations = max_iterations;
+        }
+        config
+    }
+
+    /// Whether any of the keywords occur in the query
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.keywords.iter().any(|k| !k.is_empty() && query.contains(&k.to_lowercase()))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    agents: Vec<CustomAgentType>,
+}
+
+/// Registry of custom agent types, keyed by lowercase name
+#[derive(Debug, Default)]
+pub struct AgentTypeRegistry {
+    types: RwLock<HashMap<String, CustomAgentType>>,
+}
+
+impl AgentTypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load definitions from a JSON file of the form `{ "agents": [ ... ] }`.
+    /// A missing file yields an empty registry.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let registry = Self::new();
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(registry);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let file: RegistryFile = serde_json::from_str(&content)?;
+        for agent in file.agents {
+            registry.register(agent);
+        }
+        Ok(registry)
+    }
+
+    /// Persist all definitions back to disk
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut agents: Vec<CustomAgentType> = self.types.read().unwrap().values().cloned().collect();
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+        let content = serde_json::to_string_pretty(&RegistryFile { agents })?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Register (or replace) a custom agent type
+    pub fn register(&self, agent: CustomAgentType) {
+        self.types.write().unwrap().insert(agent.name.to_lowercase(), agent);
+    }
+
+    pub fn unregister(&self, name: &str) -> Option<CustomAgentType> {
+        self.types.write().unwrap().remove(&name.to_lowercase())
+    }
+
+    pub fn get(&self, name: &str) -> Option<CustomAgentType> {
+        self.types.read().unwrap().get(&name.to_lowercase()).cloned()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.types.read().unwrap().values().map(|a| a.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.read().unwrap().is_empty()
+    }
+
+    /// First custom type whose keywords match the query (by name order, for determinism)
+    pub fn match_query(&self, query: &str) -> Option<CustomAgentType> {
+        let types = self.types.read().unwrap();
+        let mut matches: Vec<&CustomAgentType> = types.values().filter(|a| a.matches(query)).collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        matches.first().map(|a| (*a).clone())
+    }
+
+    /// Build the `AgentConfig` for a registered type
+    pub fn config_for(&self, name: &str, profile: &AgencyProfile) -> Option<AgentConfig> {
+        self.get(name).map(|a| a.to_config(profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auditor() -> CustomAgentType {
+        CustomAgentType {
+            name: "SecurityAuditor".to_string(),
+            base: AgentType::Coder,
+            description: "Audits code for vulnerabilities".to_string(),
+            system_prompt: "You are a security auditor.".to_string(),
+            model: Some("glm-4".to_string()),
+            allowed_tools: vec!["codebase_explorer".to_string()],
+            temperature: Some(0.0),
+            max_iterations: None,
+            keywords: vec!["audit".to_string(), "cve".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_register_and_build_config() {
+        let registry = AgentTypeRegistry::new();
+        registry.register(auditor());
+
+        let config = registry.config_for("securityauditor", &AgencyProfile::default()).unwrap();
+        assert_eq!(config.agent_type, AgentType::Coder);
+        assert_eq!(config.model, "glm-4");
+        assert_eq!(config.allowed_tools, vec!["codebase_explorer".to_string()]);
+        assert!(config.system_prompt.starts_with("You are a security auditor."));
+        assert_eq!(config.temperature, 0.0);
+    }
+
+    #[test]
+    fn test_match_query() {
+        let registry = AgentTypeRegistry::new();
+        registry.register(auditor());
+        assert!(registry.match_query("Please AUDIT the login handler").is_some());
+        assert!(registry.match_query("write a poem").is_none());
+    }
+
+    #[test]
+    fn test_load_save_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent_types.json");
+
+        assert!(AgentTypeRegistry::load(&path).unwrap().is_empty());
+
+        let registry = AgentTypeRegistry::new();
+        registry.register(auditor());
+        registry.save(&path).unwrap();
+
+        let loaded = AgentTypeRegistry::load(&path).unwrap();
+        assert_eq!(loaded.get("SecurityAuditor"), Some(auditor()));
+    }
+}
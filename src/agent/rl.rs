@@ -22,6 +22,12 @@ pub struct Experience {
     pub extrinsic_reward: f32,
     /// Intrinsic reward from curiosity (Novelty/Diversity)
     pub intrinsic_reward: f32,
+    /// Supervisor turn that produced the answer, for attaching feedback later
+    #[serde(default)]
+    pub turn_id: Option<String>,
+    /// User rating in [0, 1]; overrides the logged reward when training the reward model
+    #[serde(default)]
+    pub feedback: Option<f32>,
 }
 
 /// A buffer for collecting experiences during autonomous operation
@@ -52,6 +58,24 @@ impl ExperienceBuffer {
         self.experiences.drain(0..count).collect()
     }
 
+    /// Attach a user rating to every experience logged for `turn_id`; returns how many were rated
+    pub fn apply_feedback(&mut self, turn_id: &str, score: f32) -> usize {
+        let mut rated = 0;
+        for experience in self.experiences.iter_mut().filter(|e| e.turn_id.as_deref() == Some(turn_id)) {
+            experience.feedback = Some(score.clamp(0.0, 1.0));
+            rated += 1;
+        }
+        rated
+    }
+
+    pub fn len(&self) -> usize {
+        self.experiences.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.experiences.is_empty()
+    }
+
     pub fn clear(&mut self) {
         self.experiences.clear();
     }
@@ -127,6 +151,29 @@ impl GRPOTrainer {
         Ok(loss)
     }
 
+    /// Full GRPO objective: clipped ratio against the sampling policy, k3 KL estimate against
+    /// the reference. loss = -mean[min(r * A, clip(r, 1-eps, 1+eps) * A) - beta * KL]
+    pub fn clipped_loss(
+        &self,
+        log_probs: &Tensor,
+        old_log_probs: &Tensor,
+        ref_log_probs: &Tensor,
+        advantages: &Tensor,
+        clip_epsilon: f32,
+    ) -> Result<Tensor> {
+        let ratio = (log_probs - old_log_probs)?.exp()?;
+        let unclipped = (&ratio * advantages)?;
+        let clipped = (ratio.clamp(1.0 - clip_epsilon, 1.0 + clip_epsilon)? * advantages)?;
+        let surrogate = unclipped.minimum(&clipped)?;
+
+        // KL ≈ exp(ref - log_p) - (ref - log_p) - 1
+        let log_ratio = (ref_log_probs - log_probs)?;
+        let kl = ((log_ratio.exp()? - &log_ratio)? - 1.0)?;
+
+        let beta_kl = kl.affine(self.beta as f64, 0.0)?;
+        (surrogate - beta_kl)?.neg()?.mean_all()
+    }
+
     pub fn step(&self, loss: &Tensor) -> Result<()> {
         let grads = loss.backward()?;
         let mut opt = self.optimizer.lock().unwrap();
@@ -57,8 +57,9 @@ impl Speaker {
             return Ok(());
         }
 
+        let moderated = crate::safety::OUTPUT_MODERATOR.moderate(text);
         let url = format!("{}/say", self.server_url);
-        let payload = json!({ "text": text });
+        let payload = json!({ "text": moderated.text });
 
         info!("Speaker: Sending text to server...");
         let resp = self.client.post(&url)
@@ -80,6 +81,17 @@ impl Speaker {
         }
         Ok(())
     }
+
+    /// Render `text` on the Speaker Server as WAV bytes instead of playing it locally
+    pub async fn synthesize_wav(&self, text: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/synthesize", self.server_url);
+        let response = self.client.post(&url)
+            .json(&json!({ "text": text }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
 }
 
 impl Default for Speaker {
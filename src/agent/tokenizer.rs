@@ -0,0 +1,91 @@
+//! Token Counting
+//!
+//! Provider-appropriate token counts. OpenAI-family models are counted with their tiktoken
+//! BPE (o200k for the 4o/4.1/o-series, cl100k otherwise); hosted models without a public
+//! tokenizer use cl100k as a close approximation. Local Candle models count with the HF
+//! tokenizer they were loaded with (see `CandleProvider::count_tokens`).
+
+use lazy_static::lazy_static;
+use tiktoken_rs::CoreBPE;
+
+lazy_static! {
+    static ref CL100K: Option<CoreBPE> = tiktoken_rs::cl100k_base().ok();
+    static ref O200K: Option<CoreBPE> = tiktoken_rs::o200k_base().ok();
+}
+
+/// Context window assumed for unknown (typically local Ollama/Candle) models
+pub const DEFAULT_CONTEXT_WINDOW: usize = 4096;
+
+fn uses_o200k(model: &str) -> bool {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4", "chatgpt-4o"].iter().any(|p| model.starts_with(p))
+}
+
+/// Count tokens with the tiktoken encoding matching `model`, falling back to a
+/// character heuristic if the BPE tables could not be loaded
+pub fn count_tiktoken(model: &str, text: &str) -> usize {
+    let bpe = if uses_o200k(model) { O200K.as_ref() } else { CL100K.as_ref() };
+    match bpe {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => estimate(text),
+    }
+}
+
+/// Count tokens with a HuggingFace tokenizer
+pub fn count_hf(tokenizer: &tokenizers::Tokenizer, text: &str) -> usize {
+    tokenizer.encode(text, false)
+        .map(|encoding| encoding.len())
+        .unwrap_or_else(|_| estimate(text))
+}
+
+/// Rough ~4 characters per token estimate
+pub fn estimate(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Context window of `model` in tokens. `AGENCY_CONTEXT_WINDOW` overrides the table, as
+/// does a `context_window` on the model's route in the routing matrix.
+pub fn context_window(model: &str) -> usize {
+    if let Some(window) = std::env::var("AGENCY_CONTEXT_WINDOW").ok().and_then(|v| v.parse().ok()) {
+        return window;
+    }
+    if let Some(window) = crate::orchestrator::ROUTING.current().context_window(model) {
+        return window;
+    }
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let table: [(&str, usize); 9] = [
+        ("gpt-4.1", 1_000_000),
+        ("gpt-4o", 128_000),
+        ("gpt-5", 400_000),
+        ("o1", 200_000),
+        ("o3", 200_000),
+        ("o4", 200_000),
+        ("claude", 200_000),
+        ("gemini", 1_000_000),
+        ("glm-4", 128_000),
+    ];
+    table.iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, window)| *window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiktoken_counts_words_not_chars() {
+        let text = "The quick brown fox jumps over the lazy dog.";
+        assert_eq!(count_tiktoken("gpt-4", text), 10);
+        assert_eq!(count_tiktoken("gpt-4o-mini", text), 10);
+        assert_eq!(count_tiktoken("gpt-4", ""), 0);
+    }
+
+    #[test]
+    fn test_context_window_lookup() {
+        assert_eq!(context_window("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window("models/gemini-2.5-pro"), 1_000_000);
+        assert_eq!(context_window("qwen2.5:3b-q4"), DEFAULT_CONTEXT_WINDOW);
+    }
+}
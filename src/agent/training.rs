@@ -1,42 +1,53 @@
 //! Training Loop Supervisor
 //!
 //! Manages the continuous reinforcement learning process (Online Learning)
-//! and offline fine-tuning tasks.
+//! and offline fine-tuning tasks. `TrainingLoop` fine-tunes the local reasoner with GRPO
+//! (see `agent::grpo`); `RewardTrainingLoop` fits the reward model the Supervisor scores
+//! candidates with from its logged experiences and user feedback.
 
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::time::Duration;
 use tracing::{info, warn};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::{AdamW, Linear, Module, Optimizer, ParamsAdamW, VarBuilder, VarMap};
+use tokenizers::Tokenizer;
 
-use crate::agent::rl::{ExperienceBuffer, GRPOTrainer};
-use crate::models::reasoner::ReasonerModel;
-use candle_nn::VarMap;
+use crate::agent::grpo::{prompts_from_experiences, GrpoFineTuner};
+use crate::agent::rl::{Experience, ExperienceBuffer};
+use crate::models::reasoner::{Config as ReasonerConfig, ReasonerModel};
+use crate::orchestrator::aggregation::{Candidate, RewardModel};
 
+/// Periodically fine-tunes the local reasoner with GRPO on queries the agency has handled.
+/// Prompts are read from the experience buffer without draining it (the reward training
+/// loop owns that), and each prompt is trained on once.
 pub struct TrainingLoop {
     buffer: Arc<Mutex<ExperienceBuffer>>,
-    trainer: Arc<Mutex<GRPOTrainer>>,
-    model: Arc<Mutex<ReasonerModel>>,
+    tuner: Arc<Mutex<GrpoFineTuner>>,
+    reward: Arc<dyn RewardModel>,
     batch_size: usize,
+    interval: Duration,
     running: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl TrainingLoop {
     pub fn new(
         buffer: Arc<Mutex<ExperienceBuffer>>,
-        model: Arc<Mutex<ReasonerModel>>,
-        varmap: &VarMap, // In a real scenario, this needs to be passed correctly
-    ) -> anyhow::Result<Self> {
-        // Initialize GRPO with standard params
-        // Note: In a real integration, we'd need to ensure the VarMap matches the model's vars
-        let trainer = GRPOTrainer::new(0.04, varmap, 1e-6)?;
-        
-        Ok(Self {
+        tuner: GrpoFineTuner,
+        reward: Arc<dyn RewardModel>,
+    ) -> Self {
+        Self {
             buffer,
-            trainer: Arc::new(Mutex::new(trainer)),
-            model,
+            tuner: Arc::new(Mutex::new(tuner)),
+            reward,
             batch_size: 4, // Small batch for local training
+            interval: Duration::from_secs(std::env::var("AGENCY_GRPO_INTERVAL").ok().and_then(|v| v.parse().ok()).unwrap_or(1800)),
             running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-        })
+        }
     }
 
     pub async fn start(&self) {
@@ -47,44 +58,35 @@ impl TrainingLoop {
 
         info!("🚀 Online Learning Loop Started");
         let buffer = self.buffer.clone();
-        let _trainer = self.trainer.clone();
-        let _model = self.model.clone();
+        let tuner = self.tuner.clone();
+        let reward = self.reward.clone();
         let running = self.running.clone();
         let batch_size = self.batch_size;
+        let mut interval = tokio::time::interval(self.interval);
 
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(30)); // Check every 30s
+            let mut trained: HashSet<String> = HashSet::new();
 
             while running.load(std::sync::atomic::Ordering::SeqCst) {
                 interval.tick().await;
 
                 // 1. Check for data
-                let batch = {
-                    let mut buf = buffer.lock().await;
-                    buf.pop_batch(batch_size)
+                let prompts: Vec<String> = {
+                    let buf = buffer.lock().await;
+                    prompts_from_experiences(&buf, buf.len())
                 };
-
+                let batch: Vec<String> = prompts.into_iter().filter(|p| !trained.contains(p)).take(batch_size).collect();
                 if batch.is_empty() {
                     continue;
                 }
 
-                info!("🎓 Training Step: Processing batch of {} experiences", batch.len());
-
-                // 2. Compute Loss and Update
-                // Note: This is a simplified view. Real GRPO requires:
-                // - Re-running forward pass to get current log_probs
-                // - Comparing with ref_log_probs (which implies a frozen reference model)
-                // - Calculating advantages from rewards
-                
-                // For this implementation, we assume the trainer handles the math if provided tensors.
-                // Since we can't easily get tensors from the Experience struct (it's just text),
-                // a full implementation would need to re-tokenize and re-run the model here.
-                
-                // Placeholder for the heavy lifting:
-                // let loss = trainer.lock().await.calculate_loss(...);
-                // trainer.lock().await.step(&loss);
-                
-                info!("✅ Training Step Complete. Weights updated.");
+                // 2. Rollouts, advantages and optimizer steps; the checkpoint is saved after the batch
+                info!("🎓 Training Step: Processing batch of {} prompts", batch.len());
+                match tuner.lock().await.train_on(&batch, reward.as_ref()).await {
+                    Ok(report) => info!("✅ Training Step Complete: {} updates, mean reward {:.3}", report.steps, report.mean_reward),
+                    Err(e) => warn!("GRPO training step failed: {}", e),
+                }
+                trained.extend(batch);
             }
             info!("🛑 Training Loop Stopped");
         });
@@ -93,4 +95,291 @@ impl TrainingLoop {
     pub fn stop(&self) {
         self.running.store(false, std::sync::atomic::Ordering::SeqCst);
     }
-}
\ No newline at end of file
+}
+
+/// Backbone the trained reward model scores with
+pub const REWARD_BACKBONE_REPO: &str = "Qwen/Qwen2.5-0.5B-Instruct";
+
+/// An answer labelled for reward-model fitting
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardSample {
+    pub query: String,
+    pub answer: String,
+    /// Target score in [0, 1]
+    pub label: f32,
+}
+
+impl RewardSample {
+    /// User feedback wins over the reward logged when the candidate was scored
+    pub fn from_experience(experience: &Experience) -> Self {
+        Self {
+            query: experience.query.clone(),
+            answer: experience.answer.clone(),
+            label: experience.feedback.unwrap_or(experience.extrinsic_reward).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Linear scoring head over backbone features; the only trained weights
+pub struct RewardHead {
+    varmap: VarMap,
+    linear: Linear,
+    device: Device,
+}
+
+impl RewardHead {
+    pub fn new(hidden_size: usize, device: Device) -> anyhow::Result<Self> {
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DType::F32, &device);
+        let linear = candle_nn::linear(hidden_size, 1, vb.pp("reward_head"))?;
+        Ok(Self { varmap, linear, device })
+    }
+
+    /// Scores in [0, 1] for `features` of shape (n, hidden)
+    pub fn score(&self, features: &Tensor) -> candle_core::Result<Tensor> {
+        candle_nn::ops::sigmoid(&self.linear.forward(features)?)
+    }
+
+    /// Fit to `labels` by mean squared error; returns the final loss
+    pub fn fit(&self, features: &Tensor, labels: &[f32], epochs: usize, learning_rate: f64) -> anyhow::Result<f32> {
+        let targets = Tensor::from_slice(labels, (labels.len(), 1), &self.device)?;
+        let mut optimizer = AdamW::new(self.varmap.all_vars(), ParamsAdamW { lr: learning_rate, ..Default::default() })?;
+        let mut loss = 0.0;
+        for _ in 0..epochs.max(1) {
+            let mse = candle_nn::loss::mse(&self.score(features)?, &targets)?;
+            optimizer.backward_step(&mse)?;
+            loss = mse.to_scalar::<f32>()?;
+        }
+        Ok(loss)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(self.varmap.save(path)?)
+    }
+
+    pub fn load(&mut self, path: &Path) -> anyhow::Result<()> {
+        Ok(self.varmap.load(path)?)
+    }
+}
+
+/// Reward model fitted from logged candidates and user feedback: a `RewardHead` on the
+/// last-token hidden state of a frozen `ReasonerModel`
+pub struct TrainedRewardModel {
+    backbone: Arc<Mutex<ReasonerModel>>,
+    tokenizer: Tokenizer,
+    head: Mutex<RewardHead>,
+    device: Device,
+    /// Longer inputs keep their last tokens
+    max_tokens: usize,
+}
+
+impl TrainedRewardModel {
+    pub fn new(backbone: ReasonerModel, tokenizer: Tokenizer, device: Device) -> anyhow::Result<Self> {
+        let head = RewardHead::new(backbone.hidden_size(), device.clone())?;
+        Ok(Self {
+            backbone: Arc::new(Mutex::new(backbone)),
+            tokenizer,
+            head: Mutex::new(head),
+            device,
+            max_tokens: 512,
+        })
+    }
+
+    /// Download `repo` (a Qwen 0.5B checkpoint) and load it on the CPU
+    pub async fn from_hub(repo: &str) -> anyhow::Result<Self> {
+        let repo = repo.to_string();
+        tokio::task::spawn_blocking(move || {
+            use hf_hub::{api::sync::ApiBuilder, Repo};
+            let api = ApiBuilder::new().with_token(std::env::var("HF_TOKEN").ok()).build()?;
+            let repo = api.repo(Repo::new(repo, hf_hub::RepoType::Model));
+            let tokenizer = Tokenizer::from_file(repo.get("tokenizer.json")?).map_err(anyhow::Error::msg)?;
+            let weights = repo.get("model.safetensors")?;
+            let device = Device::Cpu;
+            let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[weights], DType::F32, &device)? };
+            let backbone = ReasonerModel::new(&ReasonerConfig::qwen_0_5b(), vb)?;
+            Self::new(backbone, tokenizer, device)
+        }).await?
+    }
+
+    /// Restore head weights saved by an earlier training run
+    pub async fn load_head(&self, path: &Path) -> anyhow::Result<()> {
+        self.head.lock().await.load(path)
+    }
+
+    pub async fn save_head(&self, path: &Path) -> anyhow::Result<()> {
+        self.head.lock().await.save(path)
+    }
+
+    async fn features(&self, query: &str, answer: &str) -> anyhow::Result<Tensor> {
+        let text = format!("Query: {}\nResponse: {}", query, answer);
+        let encoding = self.tokenizer.encode(text, true).map_err(anyhow::Error::msg)?;
+        let ids = encoding.get_ids();
+        let ids = &ids[ids.len().saturating_sub(self.max_tokens)..];
+        let input = Tensor::new(ids, &self.device)?.unsqueeze(0)?;
+
+        let mut backbone = self.backbone.lock().await;
+        backbone.clear_cache();
+        let hidden = backbone.hidden_states(&input, 0)?;
+        backbone.clear_cache();
+        let last = hidden.narrow(1, ids.len() - 1, 1)?.squeeze(1)?;
+        Ok(last.to_dtype(DType::F32)?.detach())
+    }
+
+    /// Fit the head to `samples`; returns the final loss
+    pub async fn fit(&self, samples: &[RewardSample], epochs: usize, learning_rate: f64) -> anyhow::Result<f32> {
+        let mut rows = Vec::with_capacity(samples.len());
+        for sample in samples {
+            rows.push(self.features(&sample.query, &sample.answer).await?);
+        }
+        let features = Tensor::cat(&rows, 0)?;
+        let labels: Vec<f32> = samples.iter().map(|s| s.label).collect();
+        self.head.lock().await.fit(&features, &labels, epochs, learning_rate)
+    }
+}
+
+#[async_trait]
+impl RewardModel for TrainedRewardModel {
+    async fn score(&self, query: &str, candidates: &[Candidate]) -> anyhow::Result<Vec<f32>> {
+        let mut scores = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            let features = self.features(query, &candidate.answer).await?;
+            let score = self.head.lock().await.score(&features)?;
+            scores.push(score.flatten_all()?.to_vec1::<f32>()?[0]);
+        }
+        Ok(scores)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardTrainingConfig {
+    /// Experiences needed before a fit runs
+    pub min_batch: usize,
+    pub epochs: usize,
+    pub learning_rate: f64,
+    pub interval: Duration,
+    /// Head weights are saved here after every fit and loaded on start
+    pub weights: PathBuf,
+}
+
+impl Default for RewardTrainingConfig {
+    fn default() -> Self {
+        Self {
+            min_batch: 8,
+            epochs: 20,
+            learning_rate: 1e-3,
+            interval: Duration::from_secs(600),
+            weights: PathBuf::from("data/reward_head.safetensors"),
+        }
+    }
+}
+
+impl RewardTrainingConfig {
+    /// `AGENCY_REWARD_MIN_BATCH`, `AGENCY_REWARD_TRAIN_INTERVAL` (seconds) and `AGENCY_REWARD_WEIGHTS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            min_batch: std::env::var("AGENCY_REWARD_MIN_BATCH").ok().and_then(|v| v.parse().ok()).unwrap_or(defaults.min_batch),
+            interval: std::env::var("AGENCY_REWARD_TRAIN_INTERVAL").ok().and_then(|v| v.parse().ok()).map(Duration::from_secs).unwrap_or(defaults.interval),
+            weights: std::env::var("AGENCY_REWARD_WEIGHTS").map(PathBuf::from).unwrap_or(defaults.weights),
+            ..defaults
+        }
+    }
+}
+
+/// Periodically fits a `TrainedRewardModel` from the supervisor's experience buffer. The
+/// model is shared with the supervisor, so each fit takes effect on the next turn.
+pub struct RewardTrainingLoop {
+    buffer: Arc<Mutex<ExperienceBuffer>>,
+    model: Arc<TrainedRewardModel>,
+    config: RewardTrainingConfig,
+    running: Arc<AtomicBool>,
+}
+
+impl RewardTrainingLoop {
+    pub fn new(buffer: Arc<Mutex<ExperienceBuffer>>, model: Arc<TrainedRewardModel>, config: RewardTrainingConfig) -> Self {
+        Self { buffer, model, config, running: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Fit on everything buffered once `min_batch` experiences are waiting; `None` when there were too few
+    pub async fn train_once(&self) -> anyhow::Result<Option<f32>> {
+        let batch = {
+            let mut buffer = self.buffer.lock().await;
+            if buffer.len() < self.config.min_batch {
+                return Ok(None);
+            }
+            let size = buffer.len();
+            buffer.pop_batch(size)
+        };
+        let samples: Vec<RewardSample> = batch.iter().map(RewardSample::from_experience).collect();
+        let loss = self.model.fit(&samples, self.config.epochs, self.config.learning_rate).await?;
+        self.model.save_head(&self.config.weights).await?;
+        info!("🎓 Reward model fitted on {} experiences (loss {:.4})", samples.len(), loss);
+        Ok(Some(loss))
+    }
+
+    pub async fn start(self: Arc<Self>) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            warn!("Reward training loop already running!");
+            return;
+        }
+        if self.config.weights.exists() {
+            if let Err(e) = self.model.load_head(&self.config.weights).await {
+                warn!("Could not load reward head from {:?}: {}", self.config.weights, e);
+            }
+        }
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.interval);
+            while self.running.load(Ordering::SeqCst) {
+                interval.tick().await;
+                if let Err(e) = self.train_once().await {
+                    warn!("Reward model training failed: {}", e);
+                }
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_head_fits_labels() {
+        let head = RewardHead::new(2, Device::Cpu).unwrap();
+        let features = Tensor::new(&[[1.0f32, 0.0], [0.0, 1.0]], &Device::Cpu).unwrap();
+        let before = head.fit(&features, &[1.0, 0.0], 1, 0.1).unwrap();
+        let after = head.fit(&features, &[1.0, 0.0], 200, 0.1).unwrap();
+        assert!(after < before);
+
+        let scores = head.score(&features).unwrap().flatten_all().unwrap().to_vec1::<f32>().unwrap();
+        assert!(scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn test_feedback_overrides_logged_reward() {
+        let mut buffer = ExperienceBuffer::new(10);
+        buffer.record(Experience {
+            query: "q".into(),
+            steps: Vec::new(),
+            answer: "a".into(),
+            total_reward: 0.9,
+            extrinsic_reward: 0.9,
+            intrinsic_reward: 0.0,
+            turn_id: Some("t1".into()),
+            feedback: None,
+        });
+        assert_eq!(RewardSample::from_experience(&buffer.experiences[0]).label, 0.9);
+
+        assert_eq!(buffer.apply_feedback("t1", -1.0), 1);
+        assert_eq!(buffer.apply_feedback("t2", 1.0), 0);
+        assert_eq!(RewardSample::from_experience(&buffer.experiences[0]).label, 0.0);
+    }
+}
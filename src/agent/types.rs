@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::orchestrator::profile::AgencyProfile;
+use super::provider::GenerationOptions;
 
 /// Types of specialized agents
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -26,6 +27,18 @@ impl AgentType {
         }
     }
 
+    /// Default sampling temperature: deterministic for code, creative for chat
+    pub fn default_temperature(&self) -> f32 {
+        match self {
+            AgentType::GeneralChat => 0.8,
+            AgentType::Reasoner => 0.3,
+            AgentType::Coder => 0.1,
+            AgentType::Researcher => 0.5,
+            AgentType::Planner => 0.2,
+            AgentType::Reviewer => 0.1,
+        }
+    }
+
     /// Generate a system prompt based on agent type and agency profile
     pub fn generate_system_prompt(&self, profile: &AgencyProfile) -> String {
         let base = match self {
@@ -43,16 +56,19 @@ impl AgentType {
             AgentType::Coder => 
                 "You are an expert programmer (CoderRole). \
                  Use 'codebase_explorer' to ground your claims in the physical source code. \
+                 Use 'file_system' (read/write/patch/list) for file changes; reserve 'code_exec' for running code. \
                  Adhere to the Strict Distinction: do not assume code state without explicit observation.".to_string(),
             
             AgentType::Researcher => 
                 "You are a research assistant (ResearcherRole). \
                  Formulate search queries to build an auditable Evidence Graph. \
+                 For current news, check 'rss_feed' before spending 'web_search' calls. \
                  Synthesize findings using the Multi-View Publication Kit (MVPK) principles.".to_string(),
             
             AgentType::Planner => 
                 "You are a task decomposition specialist (PlannerRole). \
-                 Break goals into discrete MethodDescriptions. Maintain the Design-Run separation in your execution plans.".to_string(),
+                 Break goals into discrete MethodDescriptions. Maintain the Design-Run separation in your execution plans. \
+                 Use 'calendar' to check availability and book events when a plan involves scheduling.".to_string(),
 
             AgentType::Reviewer =>
                 "You are a technical reviewer (ReviewerRole). \
@@ -60,8 +76,12 @@ impl AgentType {
                  Detect and penalize epistemic drift or hallucination.".to_string(),
         };
 
-        format!("{}\n\nAGENCY CONTEXT (U.BoundedContext):\n- Name: {}\n- Mission: {}\n- Traits: {}", 
-            base, profile.name, profile.mission, profile.traits.join(", "))
+        let prompt = format!("{}\n\nAGENCY CONTEXT (U.BoundedContext):\n- Name: {}\n- Mission: {}\n- Traits: {}", 
+            base, profile.name, profile.mission, profile.traits.join(", "));
+        match profile.verbosity.instruction() {
+            Some(instruction) => format!("{}\n- Verbosity: {}", prompt, instruction),
+            None => prompt,
+        }
     }
 }
 
@@ -85,7 +105,16 @@ pub struct AgentConfig {
     pub model: String,
     pub system_prompt: String,
     pub temperature: f32,
+    /// Nucleus sampling cutoff
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    /// Top-k sampling cutoff
+    #[serde(default)]
+    pub top_k: Option<u32>,
     pub max_tokens: Option<u32>,
+    /// Sequences that terminate generation
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
     /// Which tools this agent can use
     pub allowed_tools: Vec<String>,
     /// Which tools are in the 'laboratory' (not yet promoted)
@@ -96,6 +125,29 @@ pub struct AgentConfig {
     pub provider_url: Option<String>,
     /// Whether to enforce strict reasoning/planning tags
     pub reasoning_enabled: bool,
+    /// Retries per provider before failing over to the next one
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Base delay for exponential backoff between retries
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Attach a GBNF grammar built from the tool schemas so local models emit valid tool calls
+    #[serde(default)]
+    pub constrained_decoding: bool,
+    /// Name of a custom agent type, used instead of `agent_type` for tool permissions
+    #[serde(default)]
+    pub agent_name: Option<String>,
+    /// Profile this agent runs under, used for tool permissions
+    #[serde(default)]
+    pub profile_name: Option<String>,
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
 }
 
 impl AgentConfig {
@@ -110,12 +162,15 @@ impl AgentConfig {
                 "knowledge_graph_viewer".to_string(),
                 "system_monitor".to_string(),
                 "web_search".to_string(),
+                "http_request".to_string(),
+                "email".to_string(),
                 "code_exec".to_string(),
                 "sandbox".to_string(),
                 "model_manager".to_string(),
                 "visualization_tool".to_string(),
                 "science_tool".to_string(),
-                "forge_tool".to_string()
+                "forge_tool".to_string(),
+                "pipeline".to_string()
             ],
             AgentType::Reasoner => vec![
                 "agency_control".to_string(), 
@@ -126,15 +181,19 @@ impl AgentConfig {
                 "knowledge_graph_viewer".to_string(),
                 "system_monitor".to_string(),
                 "web_search".to_string(),
+                "http_request".to_string(),
                 "code_exec".to_string(),
                 "sandbox".to_string(),
                 "model_manager".to_string(),
                 "visualization_tool".to_string(),
                 "science_tool".to_string(),
-                "forge_tool".to_string()
+                "forge_tool".to_string(),
+                "sql_query".to_string()
             ],
             AgentType::Coder => vec![
                 "codebase_explorer".to_string(), 
+                "file_system".to_string(),
+                "git".to_string(),
                 "code_exec".to_string(), 
                 "sandbox".to_string(), 
                 "artifact_manager".to_string(), 
@@ -143,34 +202,89 @@ impl AgentConfig {
                 "agency_control".to_string(),
                 "speaker_rust".to_string(),
                 "web_search".to_string(),
+                "http_request".to_string(),
                 "model_manager".to_string(),
                 "visualization_tool".to_string()
             ],
             AgentType::Researcher => vec![
                 "web_search".to_string(), 
+                "rss_feed".to_string(),
+                "http_request".to_string(),
                 "memory_query".to_string(), 
                 "speaker_rust".to_string(),
                 "codebase_explorer".to_string(),
                 "artifact_manager".to_string(),
-                "knowledge_graph_viewer".to_string()
+                "knowledge_graph_viewer".to_string(),
+                "sql_query".to_string()
             ],
-            AgentType::Planner => vec!["speaker_rust".to_string()],
+            AgentType::Planner => vec!["speaker_rust".to_string(), "calendar".to_string()],
             AgentType::Reviewer => vec!["speaker_rust".to_string()],
         };
 
         Self {
             agent_type,
-            model: agent_type.default_model().to_string(),
+            model: profile.default_models.get(&agent_type).cloned().unwrap_or_else(|| agent_type.default_model().to_string()),
             system_prompt: agent_type.generate_system_prompt(profile),
-            temperature: 0.7,
+            temperature: agent_type.default_temperature(),
+            top_p: None,
+            top_k: None,
             max_tokens: None,
+            stop_sequences: Vec::new(),
             allowed_tools,
             laboratory_tools: Vec::new(),
             max_iterations: 5,
             provider_url: None,
             reasoning_enabled: true,
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            constrained_decoding: false,
+            agent_name: None,
+            profile_name: Some(profile.name.clone()),
+        }
+    }
+
+    /// Sampling parameters passed to every `LLMProvider` call made on behalf of this agent
+    pub fn generation_options(&self) -> GenerationOptions {
+        GenerationOptions {
+            temperature: Some(self.temperature),
+            top_p: self.top_p,
+            top_k: self.top_k,
+            max_tokens: self.max_tokens,
+            stop: self.stop_sequences.clone(),
+            ..Default::default()
         }
     }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_stop_sequences(mut self, stop: Vec<String>) -> Self {
+        self.stop_sequences = stop;
+        self
+    }
+
+    pub fn with_retry(mut self, max_retries: u32, backoff_ms: u64) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff_ms = backoff_ms;
+        self
+    }
 }
 
 impl Default for AgentConfig {
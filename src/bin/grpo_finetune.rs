@@ -0,0 +1,59 @@
+//! Fine-tune the local reasoner with GRPO on your own prompts.
+//!
+//! Usage: grpo_finetune <prompts.txt | experiences.json> [epochs]
+//!
+//! A `.json` file is read as logged experiences (`ExperienceBuffer::format_for_training`);
+//! anything else as one prompt per line. Completions are judged by `AGENCY_GRPO_JUDGE`
+//! (default `llama3.2:3b`) on the configured provider. Weights are written to
+//! `AGENCY_GRPO_CHECKPOINT` and training resumes from it when it exists.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::info;
+use rust_agency::agent::grpo::{prompts_from_experiences, GrpoConfig, GrpoFineTuner, REASONER_REPO};
+use rust_agency::agent::rl::{Experience, ExperienceBuffer};
+use rust_agency::orchestrator::aggregation::LLMRewardModel;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .init();
+
+    let mut args = std::env::args().skip(1);
+    let source = args.next().context("usage: grpo_finetune <prompts.txt | experiences.json> [epochs]")?;
+    let epochs: usize = args.next().and_then(|e| e.parse().ok()).unwrap_or(1);
+
+    let content = std::fs::read_to_string(&source).with_context(|| format!("Failed to read {}", source))?;
+    let prompts: Vec<String> = if source.ends_with(".json") {
+        let experiences: Vec<Experience> = serde_json::from_str(&content)?;
+        let mut buffer = ExperienceBuffer::new(experiences.len());
+        for experience in experiences {
+            buffer.record(experience);
+        }
+        prompts_from_experiences(&buffer, buffer.len())
+    } else {
+        content.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect()
+    };
+    anyhow::ensure!(!prompts.is_empty(), "No prompts in {}", source);
+
+    let repo = std::env::var("AGENCY_GRPO_MODEL").unwrap_or_else(|_| REASONER_REPO.to_string());
+    info!("Loading {} for fine-tuning...", repo);
+    let config = GrpoConfig::from_env();
+    let checkpoint = config.checkpoint.clone();
+    let mut tuner = GrpoFineTuner::from_hub(&repo, config).await?;
+    if checkpoint.exists() {
+        info!("Resuming from {:?}", checkpoint);
+        tuner.load_checkpoint(&checkpoint)?;
+    }
+
+    let judge = std::env::var("AGENCY_GRPO_JUDGE").unwrap_or_else(|_| "llama3.2:3b".to_string());
+    let reward = LLMRewardModel::new(rust_agency::agent::dynamic_provider() as Arc<dyn rust_agency::agent::LLMProvider>, judge);
+
+    for epoch in 0..epochs {
+        let report = tuner.train_on(&prompts, &reward).await?;
+        info!("Epoch {}/{}: {} updates, mean reward {:.3}, last loss {:?}", epoch + 1, epochs, report.steps, report.mean_reward, report.last_loss);
+    }
+    info!("Checkpoint saved to {:?}", checkpoint);
+    Ok(())
+}
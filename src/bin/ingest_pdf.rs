@@ -3,6 +3,7 @@ use std::path::Path;
 use std::sync::Arc;
 use tracing::info;
 use rust_agency::memory::{VectorMemory, Memory, MemoryEntry};
+use rust_agency::memory::documents::chunk_text;
 use rust_agency::memory::entry::MemorySource;
 use rust_agency::orchestrator::Kind;
 use pdf_extract::extract_text;
@@ -65,23 +66,3 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
-
-fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
-    let mut chunks = Vec::new();
-    let words: Vec<&str> = text.split_whitespace().collect();
-    
-    let mut i = 0;
-    while i < words.len() {
-        let end = std::cmp::min(i + chunk_size, words.len());
-        let chunk = words[i..end].join(" ");
-        chunks.push(chunk);
-        
-        if end == words.len() {
-            break;
-        }
-        
-        i += chunk_size - overlap;
-    }
-    
-    chunks
-}
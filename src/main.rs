@@ -8,7 +8,7 @@
 //! - Safety guardrails
 //! - Full session persistence
 
-use rust_agency::tools::McpServer;
+use rust_agency::tools::{McpServer, McpServerConfig};
 use anyhow::Result;
 use std::sync::Arc;
 use std::io::Write;
@@ -41,6 +41,8 @@ struct AgencyConfig {
     session_file: String,
     /// Path to agency profile file
     profile_file: String,
+    /// Path to custom agent type definitions
+    agent_types_file: String,
 }
 
 impl Default for AgencyConfig {
@@ -49,6 +51,7 @@ impl Default for AgencyConfig {
             memory_file: "memory.json".to_string(),
             session_file: "session.json".to_string(),
             profile_file: "config/agency_profile.json".to_string(),
+            agent_types_file: "config/agent_types.json".to_string(),
         }
     }
 }
@@ -62,11 +65,37 @@ async fn main() -> Result<()> {
     // SOTA: Apply Process Hardening (codex-inspired)
     rust_agency::safety::hardening::apply_hardening();
 
+    // MCP over stdio owns stdout, so logs go to stderr in that mode
+    let serve_mcp = std::env::args().any(|a| a == "--serve-mcp");
+
     // Initialize tracing (logs)
-    let _guard = rust_agency::utils::otel::init_telemetry("rust_agency")
+    let _guard = rust_agency::utils::otel::init_telemetry_with_console("rust_agency", serve_mcp)
         .expect("Failed to initialize OpenTelemetry");
     info!("🚀 Rust Agency Starting...");
 
+    // `--verify-audit [path]`: check the audit log's hash chain and exit
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 && args[1] == "--verify-audit" {
+        dotenv::dotenv().ok();
+        let path = args.get(2).cloned()
+            .or_else(|| rust_agency::safety::AUDIT_LOG.path().map(|p| p.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "agency_audit.jsonl".to_string());
+        match rust_agency::safety::audit::verify(&path) {
+            Ok(rust_agency::safety::AuditVerification::Intact { entries }) => {
+                println!("✅ Audit log {} intact ({} entries)", path, entries);
+                std::process::exit(0);
+            }
+            Ok(rust_agency::safety::AuditVerification::Broken { line, reason }) => {
+                eprintln!("❌ Audit log {} tampered at line {}: {}", path, line, reason);
+                std::process::exit(2);
+            }
+            Err(e) => {
+                eprintln!("Error verifying audit log: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     // ──────────────────────────────────────────────────────────────────────────
     // HARDENING: System Diagnostic Check
     // ──────────────────────────────────────────────────────────────────────────
@@ -78,6 +107,10 @@ async fn main() -> Result<()> {
     // Load environment variables IMMEDIATELY
     dotenv::dotenv().ok();
 
+    if serve_mcp {
+        return serve_tools_over_mcp(&AgencyConfig::default()).await;
+    }
+
     // ──────────────────────────────────────────────────────────────────────────
     // ORCHESTRATION: Integrated Microservices
     // ──────────────────────────────────────────────────────────────────────────
@@ -177,7 +210,6 @@ async fn main() -> Result<()> {
     }
 
     // Check for CLI arguments
-    let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 && (args[1] == "--visualize" || args[1] == "-v") {
         let tool = VisualizationTool::new();
         let params = serde_json::json!({
@@ -225,17 +257,27 @@ async fn main() -> Result<()> {
     // Initialize shared Speaker engine (Deduplicated SOTA)
     let shared_speaker = Arc::new(tokio::sync::Mutex::new(Speaker::new()?));
 
+    // The profile also selects how code-running tools are sandboxed
+    let profile_manager = Arc::new(ProfileManager::new(&config.profile_file));
+    let profile = profile_manager.load().await.unwrap_or_default();
+
     // Initialize tools
     let tools = Arc::new(ToolRegistry::default());
     
     // SOTA: Concurrent Tool Registration (FPF Principle: Rapid Capability Establishment)
     tokio::join!(
         tools.register_instance(WebSearchTool::new()),
-        tools.register_instance(CodeExecTool::new()),
+        tools.register_instance(CodeExecTool::new().with_sandbox_profile(profile.sandbox_profile)),
         tools.register_instance(MemoryQueryTool::new(memory.clone())),
         tools.register_instance(KnowledgeGraphTool::new(memory.clone())),
         tools.register_instance(ArtifactTool::default()),
-        tools.register_instance(SandboxTool::default()),
+        tools.register_instance(rust_agency::tools::FileSystemTool::from_env()),
+        tools.register_instance(rust_agency::tools::GitTool::default()),
+        tools.register_instance(rust_agency::tools::SqlTool::from_env()),
+        tools.register_instance(rust_agency::tools::HttpTool::default()),
+        tools.register_instance(rust_agency::tools::CalendarTool::from_env()),
+        tools.register_instance(rust_agency::tools::RssTool::from_env()),
+        tools.register_instance(SandboxTool::default().with_sandbox_profile(profile.sandbox_profile)),
         tools.register_instance(CodebaseTool::default()),
         tools.register_instance(ModelManager),
         tools.register_instance(SpeakerRsTool::new(shared_speaker.clone())),
@@ -243,12 +285,18 @@ async fn main() -> Result<()> {
         tools.register_instance(ScienceTool::new()),
         tools.register_instance(VisionTool::new()),
         tools.register_instance(ForgeTool::new("custom_tools", tools.clone())),
+        tools.register_instance(rust_agency::tools::PipelineTool::from_env(tools.clone())),
         tools.register_instance(SystemTool::new(manager.clone())),
         tools.register_instance(rust_agency::tools::ProviderTool::new(provider.clone())),
         tools.register_instance(rust_agency::tools::WasmCompilerTool::new()),
         tools.register_instance(rust_agency::tools::WasmExecutorTool::new())
     );
 
+    // Email is opt-in: only available when an IMAP/SMTP account is configured
+    if let Some(config) = rust_agency::tools::EmailConfig::from_env() {
+        tools.register_instance(rust_agency::tools::EmailTool::new(config)).await;
+    }
+
     // SOTA: Markdown-Based Skill Discovery (pi-mono-inspired)
     if let Ok(skills) = rust_agency::tools::SkillLoader::discover_skills("skills").await {
         for skill in skills {
@@ -265,19 +313,81 @@ async fn main() -> Result<()> {
             println!("🛠️  Loaded {} dynamic tools from laboratory ('custom_tools').", count);
         }
     }
-    let profile_manager = ProfileManager::new(&config.profile_file);
-    let profile = profile_manager.load().await.unwrap_or_default();
     println!("👤 Agency Profile loaded: {}", profile.name);
 
+    let agent_types = match rust_agency::agent::AgentTypeRegistry::load(&config.agent_types_file) {
+        Ok(registry) => registry,
+        Err(e) => {
+            tracing::warn!("Failed to load custom agent types from '{}': {}", config.agent_types_file, e);
+            rust_agency::agent::AgentTypeRegistry::new()
+        }
+    };
+    if !agent_types.is_empty() {
+        println!("🧬 Custom agent types: {}", agent_types.names().join(", "));
+    }
+
     // Initialize supervisor
     let mut supervisor = Supervisor::new_with_provider(provider.clone() as Arc<dyn rust_agency::agent::LLMProvider>, tools.clone())
         .await
         .with_memory(memory.clone())
         .with_session(session_manager)
         .with_episodic_memory(episodic_memory.clone())
-        .with_profile(profile)
+        .with_profile(profile.clone())
+        .with_agent_types(Arc::new(agent_types))
         .with_max_retries(2);
 
+    // Federated mesh: peer nodes from AGENCY_PEERS take work this node lacks the hardware for
+    if let Some(mesh) = rust_agency::orchestrator::mesh::PeerRegistry::from_env().await {
+        let mesh = Arc::new(mesh);
+        println!("🌐 Mesh peers: {}", mesh.peers().await.iter().map(|p| p.url.clone()).collect::<Vec<_>>().join(", "));
+        mesh.clone().spawn_refresh(std::time::Duration::from_secs(60));
+        supervisor = supervisor.with_mesh(mesh);
+    }
+
+    // Reward model trained from logged candidates and user feedback (AGENCY_REWARD_MODEL)
+    if let Some(repo) = std::env::var("AGENCY_REWARD_MODEL").ok().filter(|v| v != "off") {
+        let repo = if repo == "on" { rust_agency::agent::training::REWARD_BACKBONE_REPO.to_string() } else { repo };
+        match rust_agency::agent::training::TrainedRewardModel::from_hub(&repo).await {
+            Ok(model) => {
+                let model = Arc::new(model);
+                let training = Arc::new(rust_agency::agent::training::RewardTrainingLoop::new(
+                    supervisor.experience_buffer.clone(),
+                    model.clone(),
+                    rust_agency::agent::training::RewardTrainingConfig::from_env(),
+                ));
+                training.start().await;
+                println!("🎓 Reward model training enabled ({})", repo);
+                supervisor = supervisor.with_reward_model(model);
+            }
+            Err(e) => tracing::warn!("Failed to load reward model backbone '{}': {}", repo, e),
+        }
+    }
+
+    // GRPO fine-tuning of the local reasoner on handled queries (AGENCY_GRPO)
+    if std::env::var("AGENCY_GRPO").map(|v| v == "on").unwrap_or(false) {
+        let repo = std::env::var("AGENCY_GRPO_MODEL").unwrap_or_else(|_| rust_agency::agent::grpo::REASONER_REPO.to_string());
+        let config = rust_agency::agent::grpo::GrpoConfig::from_env();
+        let checkpoint = config.checkpoint.clone();
+        match rust_agency::agent::grpo::GrpoFineTuner::from_hub(&repo, config).await {
+            Ok(mut tuner) => {
+                if checkpoint.exists() {
+                    if let Err(e) = tuner.load_checkpoint(&checkpoint) {
+                        tracing::warn!("Failed to resume GRPO checkpoint {:?}: {}", checkpoint, e);
+                    }
+                }
+                // The trained reward model when there is one, otherwise an LLM judge
+                let reward = supervisor.reward_model.clone().unwrap_or_else(|| Arc::new(rust_agency::orchestrator::aggregation::LLMRewardModel::new(
+                    provider.clone() as Arc<dyn rust_agency::agent::LLMProvider>,
+                    std::env::var("AGENCY_GRPO_JUDGE").unwrap_or_else(|_| "llama3.2:3b".to_string()),
+                )));
+                let training = rust_agency::agent::training::TrainingLoop::new(supervisor.experience_buffer.clone(), tuner, reward);
+                training.start().await;
+                println!("🎓 GRPO fine-tuning enabled ({})", repo);
+            }
+            Err(e) => tracing::warn!("Failed to load reasoner '{}' for GRPO: {}", repo, e),
+        }
+    }
+
     // NOTE: Background thinking (CTM) is disabled by default to save resources on 16GB M2 Air.
     // To enable it, uncomment the following line or use the 'autonomous' command.
     // let _ = supervisor.activate_background_thinking().await;
@@ -292,16 +402,53 @@ async fn main() -> Result<()> {
     // Wrap Supervisor in Shared Mutex for Hybrid Access
     let shared_supervisor = Arc::new(Mutex::new(supervisor));
 
+    // Slack (socket mode): one conversation per thread, approvals as buttons
+    if let Some(slack_config) = rust_agency::services::slack::SlackConfig::from_env() {
+        let slack = Arc::new(rust_agency::services::slack::SlackService::new(slack_config, shared_supervisor.clone()));
+        println!("💬 Slack integration enabled");
+        tokio::spawn(async move { let _ = slack.run().await; });
+    }
+
+    // Discord gateway: per-channel memory plus /memory, /tools and /autonomous
+    if let Some(discord_config) = rust_agency::services::discord::DiscordConfig::from_env() {
+        let discord = Arc::new(rust_agency::services::discord::DiscordService::new(discord_config, shared_supervisor.clone()));
+        println!("🎮 Discord integration enabled");
+        tokio::spawn(async move { let _ = discord.run().await; });
+    }
+
+    // Telegram: text and voice notes on the shared supervisor, spoken replies to voice
+    if let Some(telegram_config) = rust_agency::services::telegram::TelegramConfig::from_env() {
+        let telegram = Arc::new(rust_agency::services::telegram::TelegramService::new(telegram_config, shared_supervisor.clone()));
+        println!("✈️ Telegram bot enabled");
+        tokio::spawn(async move { let _ = telegram.run().await; });
+    }
+
     // ──────────────────────────────────────────────────────────────────────────
     // HYBRID MODE: Spawn Server EARLY (FPF Principle: Parallel Availability)
     // ──────────────────────────────────────────────────────────────────────────
-    let (tx, _) = broadcast::channel(1024);
+    // `agency.toml` and env: listen address, TLS, auth, CORS, body limits, channel sizes
+    let server_config = rust_agency::services::server_config::ServerConfig::load()?;
+    let (tx, _) = broadcast::channel(server_config.broadcast_capacity);
     let server_shared_supervisor = shared_supervisor.clone();
     let server_provider = provider.clone();
     let server_speaker = shared_speaker.clone();
     let server_episodic = episodic_memory.clone();
     let server_tx = tx.clone();
     let server_start_local = start_local.clone();
+    let server_tools = tools.clone();
+    let server_safety = shared_supervisor.lock().await.safety.clone();
+    let server_memory = memory.clone();
+    let server_turn_feed = shared_supervisor.lock().await.turn_feed.clone();
+    let server_steering = shared_supervisor.lock().await.active_steer_txs.clone();
+    let server_profile = profile_manager.clone();
+    let public_url = std::env::var("AGENCY_PUBLIC_URL").unwrap_or_else(|_| "http://localhost:8002".to_string());
+    let server_card = Arc::new(rust_agency::orchestrator::a2a::AgentCard::new(&profile, &public_url)
+        .with_node_capabilities(rust_agency::orchestrator::a2a::node_capabilities_from_env()));
+    // Webhook deliveries are queued for the background worker rather than waiting on the supervisor
+    let server_hooks = Arc::new(rust_agency::orchestrator::webhooks::WebhookRegistry::from_env(
+        shared_supervisor.lock().await.task_queue.clone()));
+    let server_experiences = shared_supervisor.lock().await.experience_buffer.clone();
+    let server_conversations = Arc::new(rust_agency::orchestrator::ConversationRegistry::from_env(&*shared_supervisor.lock().await));
 
     tokio::spawn(async move {
         let server_state = AppState {
@@ -312,9 +459,22 @@ async fn main() -> Result<()> {
             episodic_memory: server_episodic,
             supervisor: server_shared_supervisor,
             current_task: Arc::new(Mutex::new(None)),
+            tools: server_tools,
+            safety: server_safety,
+            agent_card: server_card,
+            a2a_tasks: Arc::new(rust_agency::orchestrator::a2a::A2ATaskStore::new()),
+            assistants: Arc::new(rust_agency::services::assistants::AssistantStore::new()),
+            hooks: server_hooks,
+            conversations: server_conversations,
+            experiences: server_experiences,
+            memory: server_memory,
+            turn_feed: server_turn_feed,
+            web_sessions: Arc::new(rust_agency::services::web_sessions::WebSessions::with_capacity(server_config.session_channel_capacity)),
+            steering: server_steering,
+            profile: server_profile,
         };
         
-        if let Err(e) = run_server(server_state).await {
+        if let Err(e) = run_server(server_state, server_config).await {
             eprintln!("❌ Server crashed: {}", e);
         }
     });
@@ -330,29 +490,28 @@ async fn main() -> Result<()> {
             if let Ok(config) = serde_json::from_str::<serde_json::Value>(&content) {
                 if let Some(servers) = config["servers"].as_array() {
                     for server_cfg in servers {
-                        let name = server_cfg["name"].as_str().unwrap_or("unnamed");
-                        let command = server_cfg["command"].as_str().unwrap_or("");
-                        let args: Vec<String> = server_cfg["args"]
-                            .as_array()
-                            .unwrap_or(&vec![])
-                            .iter()
-                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                            .collect();
-
-                        if !command.is_empty() {
-                                                    match McpServer::spawn(name, command, &args).await {
-                                                        Ok(server) => {
-                                                            // SOTA: Automatic Root Registration (FPF Grounding)
-                                                            let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-                                                            let _ = server.add_root(&cwd.to_string_lossy()).await;
-                            
-                                                            match mcp_tools.register_mcp_server(server).await {
-                                                                Ok(count) => println!("🔌 Connected to MCP Server '{}' ({} tools loaded)", name, count),
-                                                                Err(e) => tracing::warn!("Failed to register tools from MCP server '{}': {}", name, e),
-                                                            }
-                                                        }
-                                                        Err(e) => tracing::warn!("Failed to spawn MCP server '{}': {}", name, e),
-                                                    }                        }
+                        let server_cfg: McpServerConfig = match serde_json::from_value(server_cfg.clone()) {
+                            Ok(cfg) => cfg,
+                            Err(e) => {
+                                tracing::warn!("Skipping invalid MCP server entry: {}", e);
+                                continue;
+                            }
+                        };
+                        let name = server_cfg.name.clone();
+
+                        match McpServer::connect(server_cfg).await {
+                            Ok(server) => {
+                                // SOTA: Automatic Root Registration (FPF Grounding)
+                                let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+                                let _ = server.add_root(&cwd.to_string_lossy()).await;
+
+                                match mcp_tools.register_mcp_server(server).await {
+                                    Ok(count) => println!("🔌 Connected to MCP Server '{}' ({} tools loaded)", name, count),
+                                    Err(e) => tracing::warn!("Failed to register tools from MCP server '{}': {}", name, e),
+                                }
+                            }
+                            Err(e) => tracing::warn!("Failed to spawn MCP server '{}': {}", name, e),
+                        }
                     }
                 }
             }
@@ -421,14 +580,17 @@ async fn main() -> Result<()> {
     {
         let supervisor_guard = shared_supervisor.lock().await;
         let queue = supervisor_guard.task_queue.clone();
+        let schedules = supervisor_guard.schedules.clone();
         drop(supervisor_guard);
 
-        let scheduler = rust_agency::orchestrator::scheduler::AgencyScheduler::new(queue)
+        let scheduler = Arc::new(rust_agency::orchestrator::scheduler::AgencyScheduler::new(queue)
             .await
-            .expect("Failed to init scheduler");
+            .expect("Failed to init scheduler"));
         
         scheduler.init_defaults().await.expect("Failed to init habits");
         scheduler.start().await.expect("Failed to start scheduler");
+        // User schedules created through the `scheduler` tool
+        scheduler.clone().watch_schedules(schedules, std::time::Duration::from_secs(30));
         println!("⏰ Circadian Rhythm active.");
     }
 
@@ -447,6 +609,24 @@ async fn main() -> Result<()> {
         println!("🌡️  Homeostasis active.");
     }
 
+    // ──────────────────────────────────────────────────────────────────────────
+    // HEALTH PROBES: Provider Circuit Breakers
+    // ──────────────────────────────────────────────────────────────────────────
+    // Opt-in: each probe is a one-token generation, which hosted providers bill for
+    let probe_secs: u64 = std::env::var("AGENCY_HEALTH_PROBE_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
+    if probe_secs > 0 {
+        use rust_agency::orchestrator::{ScaleClass, ScaleProfile};
+        let tier_models = [ScaleClass::Tiny, ScaleClass::Standard, ScaleClass::Heavy]
+            .into_iter()
+            .map(|class| ScaleProfile::new_with_class(class, 8.0).target_model)
+            .collect();
+        let prober = rust_agency::agent::HealthProber::new(provider.clone(), tier_models, std::time::Duration::from_secs(probe_secs));
+        tokio::spawn(async move {
+            prober.start().await;
+        });
+        println!("🩺 Provider health probes active.");
+    }
+
     // ──────────────────────────────────────────────────────────────────────────
     // HEALING: The Doctor
     // ──────────────────────────────────────────────────────────────────────────
@@ -491,3 +671,23 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// `--serve-mcp`: publish the agency's read-only tools to MCP clients over stdio
+async fn serve_tools_over_mcp(config: &AgencyConfig) -> Result<()> {
+    let memory: Arc<dyn Memory> = Arc::new(VectorMemory::new(&config.memory_file)?);
+    let manager = Arc::new(MemoryManager::new(memory.clone()));
+
+    let tools = Arc::new(ToolRegistry::default());
+    tokio::join!(
+        tools.register_instance(MemoryQueryTool::new(memory.clone())),
+        tools.register_instance(KnowledgeGraphTool::new(memory.clone())),
+        tools.register_instance(VisionTool::new()),
+        tools.register_instance(WebSearchTool::new()),
+        tools.register_instance(CodebaseTool::default()),
+        tools.register_instance(ScienceTool::new()),
+        tools.register_instance(SystemTool::new(manager)),
+        tools.register_instance(rust_agency::tools::RssTool::from_env())
+    );
+
+    rust_agency::services::mcp_server::McpToolServer::from_env(tools).run_stdio().await
+}
@@ -14,14 +14,22 @@ use crate::orchestrator::profile::AgencyProfile;
 pub struct ContextCompactor;
 
 impl ContextCompactor {
-    /// Compacts the episodic memory if it exceeds the specified token limit.
+    /// Token count of the history as it is injected into prompts, using `model`'s tokenizer.
+    pub async fn count_tokens(memory: &EpisodicMemory, provider: &dyn LLMProvider, model: &str) -> usize {
+        let history = memory.format_as_chatml();
+        provider.count_tokens(model, &history).await
+            .unwrap_or_else(|_| memory.estimate_total_tokens())
+    }
+
+    /// Compacts the episodic memory if it exceeds the specified token limit for `model`.
     pub async fn compact_if_needed(
         memory: &mut EpisodicMemory,
         provider: Arc<dyn LLMProvider>,
         profile: &AgencyProfile,
+        model: &str,
         max_tokens: usize,
     ) -> Result<bool> {
-        let current_tokens = memory.estimate_total_tokens();
+        let current_tokens = Self::count_tokens(memory, provider.as_ref(), model).await;
         
         if current_tokens < max_tokens {
             return Ok(false);
@@ -50,7 +58,7 @@ impl ContextCompactor {
         // 3. Perform summarization
         let mut config = AgentConfig::new(AgentType::GeneralChat, profile);
         config.model = "qwen2.5:3b-q4".to_string(); // Use a fast model for summary
-        let summarizer = SimpleAgent::new_with_provider(provider, config);
+        let summarizer = SimpleAgent::new_with_provider(provider.clone(), config);
 
         let prompt = format!(
             "Please provide a concise technical summary of the following conversation history. \nFocus on key decisions made, tools used, and the current progress toward the goal. \nKEEP IT UNDER 500 CHARACTERS.\n\n### History to Summarize:\n{}"
@@ -75,7 +83,7 @@ impl ContextCompactor {
         new_turns.extend(recent_turns);
 
         memory.replace_turns(new_turns);
-        info!("Compaction complete. New token count: {}", memory.estimate_total_tokens());
+        info!("Compaction complete. New token count: {}", Self::count_tokens(memory, provider.as_ref(), model).await);
 
         Ok(true)
     }
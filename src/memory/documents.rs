@@ -0,0 +1,85 @@
+//! Document Ingestion
+//!
+//! Extracts text from PDFs and plain-text documents and stores it in vector memory as
+//! overlapping chunks, so agents can recall it through `memory_query`.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use super::entry::MemorySource;
+use super::{Memory, MemoryEntry};
+use crate::orchestrator::Kind;
+
+/// Words per chunk and words shared between neighbouring chunks
+pub const CHUNK_WORDS: usize = 1500;
+pub const CHUNK_OVERLAP: usize = 200;
+
+/// Split `text` into chunks of `chunk_size` words, each repeating the last `overlap` of the previous
+pub fn chunk_text(text: &str, chunk_size: usize, overlap: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let step = chunk_size.saturating_sub(overlap).max(1);
+
+    let mut i = 0;
+    while i < words.len() {
+        let end = std::cmp::min(i + chunk_size, words.len());
+        chunks.push(words[i..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+        i += step;
+    }
+
+    chunks
+}
+
+/// Text of a PDF (by extension) or UTF-8 text file
+pub async fn extract_text(path: &Path) -> Result<String> {
+    let is_pdf = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        let owned = path.to_path_buf();
+        return tokio::task::spawn_blocking(move || pdf_extract::extract_text(&owned)).await?
+            .with_context(|| format!("Failed to extract text from PDF {}", path.display()));
+    }
+    let bytes = tokio::fs::read(path).await.with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Store `text` in `memory` as evidence chunks under `context`, grounded in `source`; returns the chunks' memory ids
+pub async fn ingest_text(memory: &dyn Memory, text: &str, context: &str, source: &str, tags: &[&str]) -> Result<Vec<String>> {
+    let chunks = chunk_text(text, CHUNK_WORDS, CHUNK_OVERLAP);
+    let mut ids = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut entry = MemoryEntry::new(chunk, "DocumentIngestor", MemorySource::Codebase);
+        entry.metadata.context = context.to_string();
+        entry.metadata.kind = Kind::Evidence;
+        entry.metadata.tags.extend(tags.iter().map(|t| t.to_string()));
+        entry.metadata.grounding_holon = Some(source.to_string());
+        ids.push(memory.store(entry).await?);
+
+        // Yield to prevent blocking the executor too long
+        if i % 10 == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+    memory.persist().await?;
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text_overlaps() {
+        let text = (0..25).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let chunks = chunk_text(&text, 10, 2);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].ends_with("8 9"));
+        assert!(chunks[1].starts_with("8 9"));
+        assert!(chunks[2].ends_with("24"));
+        assert!(chunk_text("   ", 10, 2).is_empty());
+        // An overlap as large as the chunk still advances
+        assert_eq!(chunk_text("a b c", 2, 2).len(), 2);
+    }
+}
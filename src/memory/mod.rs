@@ -10,6 +10,7 @@ pub mod manager;
 pub mod indexer;
 pub mod history;
 pub mod compactor;
+pub mod documents;
 
 pub use vector::{VectorMemory, LocalVectorMemory, RemoteVectorMemory};
 pub use episodic::EpisodicMemory;
@@ -59,4 +60,7 @@ pub trait Memory: Send + Sync {
 
     /// Wake the memory system (reload models/caches)
     async fn wake(&self) -> Result<()>;
+
+    /// Normalised embeddings of `texts` from the model memories are indexed with
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
 }
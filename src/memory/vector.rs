@@ -116,6 +116,13 @@ impl Memory for VectorMemory {
         }
     }
 
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Self::Local(m) => m.embed_texts(texts).await,
+            Self::Remote(m) => m.embed_texts(texts).await,
+        }
+    }
+
     async fn wake(&self) -> Result<()> {
         match self {
             Self::Local(m) => m.wake().await,
@@ -351,6 +358,23 @@ impl Memory for LocalVectorMemory {
     async fn prune(&self, ids: Vec<String>) -> Result<()> {
         let mut hot = self.hot_entries.write().await;
         hot.retain(|e| !ids.contains(&e.id));
+        drop(hot);
+
+        // Consolidated entries live in the COLD tier
+        self.ensure_cold_cache().await?;
+        let mut cold_guard = self.cold_cache.write().await;
+        let cold = cold_guard.as_mut().unwrap();
+        let before = cold.len();
+        cold.retain(|e| !ids.contains(&e.id));
+        if cold.len() != before {
+            let cold_clone = cold.clone();
+            let cold_path = self.cold_path.clone();
+            tokio::task::spawn_blocking(move || {
+                let file = OpenOptions::new().create(true).write(true).truncate(true).open(cold_path)?;
+                bincode::serialize_into(file, &cold_clone)?;
+                Ok::<(), anyhow::Error>(())
+            }).await??;
+        }
         Ok(())
     }
     
@@ -369,6 +393,10 @@ impl Memory for LocalVectorMemory {
         self.ensure_cold_cache().await?;
         Ok(())
     }
+
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed(texts).await
+    }
 }
 
 #[cfg(test)]
@@ -476,4 +504,13 @@ impl Memory for RemoteVectorMemory {
     async fn clear_cache(&self) -> Result<()> { Ok(()) }
     async fn hibernate(&self) -> Result<()> { Ok(()) }
     async fn wake(&self) -> Result<()> { Ok(()) }
+
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let resp = self.client.post(format!("{}/embed", self.url))
+            .json(&json!({ "texts": texts }))
+            .send().await?
+            .error_for_status()?;
+        let data: serde_json::Value = resp.json().await?;
+        Ok(serde_json::from_value(data["embeddings"].clone())?)
+    }
 }
@@ -271,7 +271,12 @@ impl ReasonerModel {
             layers.push(Block::new(cfg, vb_l.pp(i))?);
         }
         let norm = candle_nn::layer_norm(cfg.hidden_size, cfg.layer_norm_std, vb.pp("model.norm"))?;
-        let lm_head = linear(cfg.hidden_size, cfg.vocab_size, vb.pp("lm_head"))?;
+        // Small Qwen checkpoints (0.5B) tie the LM head to the embeddings
+        let lm_head = if vb.contains_tensor("lm_head.weight") {
+            linear(cfg.hidden_size, cfg.vocab_size, vb.pp("lm_head"))?
+        } else {
+            Linear::new(embed_tokens.embeddings().clone(), None)
+        };
         Ok(Self { embed_tokens, layers, norm, lm_head, cfg: cfg.clone() })
     }
 
@@ -300,6 +305,13 @@ impl ReasonerModel {
     }
 
     pub fn forward_full(&mut self, input_ids: &Tensor, pos: usize) -> Result<Tensor> {
+        let x = self.hidden_states(input_ids, pos)?;
+        self.lm_head.forward(&x)
+    }
+
+    /// Final normalized hidden states for every position, before the LM head
+    /// (used as features by the reward model)
+    pub fn hidden_states(&mut self, input_ids: &Tensor, pos: usize) -> Result<Tensor> {
         let (_b_sz, seq_len) = input_ids.dims2()?;
         let mut x = self.embed_tokens.forward(input_ids)?;
         
@@ -316,8 +328,11 @@ impl ReasonerModel {
             x = layer.forward(&x, pos, mask.as_ref())?;
         }
         
-        let x = self.norm.forward(&x)?;
-        self.lm_head.forward(&x)
+        self.norm.forward(&x)
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.cfg.hidden_size
     }
 
     pub fn clear_cache(&mut self) {
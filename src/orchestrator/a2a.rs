@@ -2,15 +2,30 @@
 //! 
 //! Implements the protocol for direct collaboration between agents.
 //! Aligns with FPF U.Interaction (A.1) and uses SNS for token efficiency.
+//! Also holds the standard A2A agent card and task lifecycle used to interoperate
+//! with agents that are not rust_agency.
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use chrono::Utc;
 use uuid::Uuid;
 
-use crate::agent::{AgentType, AgentResponse, AgentResult};
+use crate::agent::{AgentType, AgentResponse, AgentResult, AgentError};
 use crate::orchestrator::Supervisor;
+use crate::orchestrator::profile::AgencyProfile;
+
+/// Finished tasks kept for polling before the oldest are evicted
+const MAX_A2A_TASKS: usize = 1000;
+/// Finished tasks are forgotten after this long, even under the cap
+const FINISHED_TASK_TTL_SECS: i64 = 3600;
+/// Tasks that may be submitted or working at once; further submissions are refused
+const MAX_RUNNING_A2A_TASKS: usize = 64;
+/// Limit for a single JSON-RPC call to a remote agent
+const RPC_TIMEOUT_SECS: u64 = 30;
 
 /// FPF-aligned Agent Interaction (A.1)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,5 +114,491 @@ impl A2ABridge {
             }
         
         }
-        
-        
\ No newline at end of file
+
+/// Skill id advertised for each agent role
+pub fn skill_id(agent: AgentType) -> &'static str {
+    match agent {
+        AgentType::GeneralChat => "chat",
+        AgentType::Reasoner => "reasoner",
+        AgentType::Coder => "coder",
+        AgentType::Researcher => "researcher",
+        AgentType::Planner => "planner",
+        AgentType::Reviewer => "reviewer",
+    }
+}
+
+/// Agent role for a skill id; unknown skills go to general chat
+pub fn agent_for_skill(skill: &str) -> AgentType {
+    match skill {
+        "reasoner" => AgentType::Reasoner,
+        "coder" => AgentType::Coder,
+        "researcher" => AgentType::Researcher,
+        "planner" => AgentType::Planner,
+        "reviewer" => AgentType::Reviewer,
+        _ => AgentType::GeneralChat,
+    }
+}
+
+/// Standard A2A agent card, served at `/.well-known/agent.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCard {
+    pub name: String,
+    pub description: String,
+    /// JSON-RPC endpoint for `tasks/*` calls
+    pub url: String,
+    pub version: String,
+    #[serde(default)]
+    pub capabilities: AgentCardCapabilities,
+    #[serde(default)]
+    pub default_input_modes: Vec<String>,
+    #[serde(default)]
+    pub default_output_modes: Vec<String>,
+    #[serde(default)]
+    pub skills: Vec<AgentSkill>,
+    /// Hardware/feature tags of the node (e.g. `gpu`, `vision`), used for mesh delegation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_capabilities: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCardCapabilities {
+    #[serde(default)]
+    pub streaming: bool,
+    #[serde(default)]
+    pub push_notifications: bool,
+    #[serde(default)]
+    pub state_transition_history: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSkill {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl AgentCard {
+    /// Card for this agency; every agent role is a skill, `base_url` is the public server address
+    pub fn new(profile: &AgencyProfile, base_url: &str) -> Self {
+        let roles = [
+            (AgentType::GeneralChat, "General assistance and conversation"),
+            (AgentType::Researcher, "Web research with cited sources"),
+            (AgentType::Coder, "Writing, running and debugging code"),
+            (AgentType::Reasoner, "Step-by-step analysis of hard problems"),
+            (AgentType::Planner, "Breaking goals into executable plans"),
+            (AgentType::Reviewer, "Reviewing answers and code for errors"),
+        ];
+        Self {
+            name: profile.name.clone(),
+            description: profile.mission.clone(),
+            url: format!("{}/v1/a2a", base_url.trim_end_matches('/')),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: AgentCardCapabilities::default(),
+            default_input_modes: vec!["text".to_string()],
+            default_output_modes: vec!["text".to_string()],
+            skills: roles.into_iter().map(|(agent, description)| AgentSkill {
+                id: skill_id(agent).to_string(),
+                name: format!("{:?}", agent),
+                description: description.to_string(),
+                tags: profile.traits.clone(),
+            }).collect(),
+            node_capabilities: Vec::new(),
+        }
+    }
+
+    pub fn with_node_capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.node_capabilities = capabilities;
+        self
+    }
+
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.node_capabilities.iter().any(|c| c.eq_ignore_ascii_case(capability))
+    }
+}
+
+/// Capability tags this node advertises (`AGENCY_NODE_CAPABILITIES=gpu,vision`)
+pub fn node_capabilities_from_env() -> Vec<String> {
+    std::env::var("AGENCY_NODE_CAPABILITIES")
+        .map(|v| v.split(',').map(|c| c.trim().to_lowercase()).filter(|c| !c.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// A2A task lifecycle states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum A2ATaskState {
+    Submitted,
+    Working,
+    InputRequired,
+    Completed,
+    Canceled,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+impl A2ATaskState {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Canceled | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum A2APart {
+    Text { text: String },
+    Data { data: Value },
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A2AMessage {
+    pub role: String,
+    pub parts: Vec<A2APart>,
+}
+
+impl A2AMessage {
+    pub fn text(role: &str, text: impl Into<String>) -> Self {
+        Self { role: role.to_string(), parts: vec![A2APart::Text { text: text.into() }] }
+    }
+
+    /// All text parts joined by newlines
+    pub fn text_content(&self) -> String {
+        self.parts.iter()
+            .filter_map(|p| match p {
+                A2APart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A2ATaskStatus {
+    pub state: A2ATaskState,
+    #[serde(default)]
+    pub message: Option<A2AMessage>,
+    pub timestamp: chrono::DateTime<Utc>,
+}
+
+impl A2ATaskStatus {
+    fn new(state: A2ATaskState, message: Option<A2AMessage>) -> Self {
+        Self { state, message, timestamp: Utc::now() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A2AArtifact {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub parts: Vec<A2APart>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A2ATask {
+    pub id: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub status: A2ATaskStatus,
+    #[serde(default)]
+    pub artifacts: Vec<A2AArtifact>,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+/// Parameters of `tasks/send`; `metadata.skill` picks the agent role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSendParams {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    pub message: A2AMessage,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+struct TaskEntry {
+    task: A2ATask,
+    handle: Option<tokio::task::AbortHandle>,
+}
+
+/// In-memory store of A2A tasks submitted to this agency
+#[derive(Default)]
+pub struct A2ATaskStore {
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+}
+
+impl A2ATaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a task and run `run(agent, text)` in the background; returns the submitted task.
+    /// Refuses an id that is already known, and new work while too many tasks are running.
+    pub async fn submit<F, Fut>(self: &Arc<Self>, params: TaskSendParams, run: F) -> AgentResult<A2ATask>
+    where
+        F: FnOnce(AgentType, String) -> Fut + Send + 'static,
+        Fut: Future<Output = AgentResult<AgentResponse>> + Send + 'static,
+    {
+        let id = params.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        let agent = agent_for_skill(params.metadata["skill"].as_str().unwrap_or("chat"));
+        let text = params.message.text_content();
+        let task = A2ATask {
+            id: id.clone(),
+            session_id: params.session_id,
+            status: A2ATaskStatus::new(A2ATaskState::Submitted, None),
+            artifacts: Vec::new(),
+            metadata: params.metadata,
+        };
+
+        let mut tasks = self.tasks.lock().await;
+        Self::evict(&mut tasks);
+        if tasks.contains_key(&id) {
+            return Err(AgentError::Validation(format!("Task id '{}' is already in use", id)));
+        }
+        if tasks.values().filter(|e| !e.task.status.state.is_terminal()).count() >= MAX_RUNNING_A2A_TASKS {
+            return Err(AgentError::Execution(format!("Too many running tasks (limit {}); retry later", MAX_RUNNING_A2A_TASKS)));
+        }
+        tasks.insert(id.clone(), TaskEntry { task: task.clone(), handle: None });
+
+        let store = self.clone();
+        let task_id = id.clone();
+        let handle = tokio::spawn(async move {
+            store.set_status(&task_id, A2ATaskStatus::new(A2ATaskState::Working, None)).await;
+            let (status, artifact) = match run(agent, text).await {
+                Ok(response) if response.success => (
+                    A2ATaskStatus::new(A2ATaskState::Completed, None),
+                    Some(A2AArtifact { name: Some("answer".to_string()), parts: vec![A2APart::Text { text: response.answer }] }),
+                ),
+                Ok(response) => (
+                    A2ATaskStatus::new(A2ATaskState::Failed, Some(A2AMessage::text("agent", response.error.unwrap_or(response.answer)))),
+                    None,
+                ),
+                Err(e) => (A2ATaskStatus::new(A2ATaskState::Failed, Some(A2AMessage::text("agent", e.to_string()))), None),
+            };
+            let mut tasks = store.tasks.lock().await;
+            if let Some(entry) = tasks.get_mut(&task_id) {
+                if !entry.task.status.state.is_terminal() {
+                    entry.task.status = status;
+                    entry.task.artifacts.extend(artifact);
+                }
+                entry.handle = None;
+            }
+        });
+        if let Some(entry) = tasks.get_mut(&id) {
+            entry.handle = Some(handle.abort_handle());
+        }
+        Ok(task)
+    }
+
+    pub async fn get(&self, id: &str) -> Option<A2ATask> {
+        self.tasks.lock().await.get(id).map(|e| e.task.clone())
+    }
+
+    /// Cancel a running task; `Err` holds the task when it had already finished
+    pub async fn cancel(&self, id: &str) -> Option<Result<A2ATask, A2ATask>> {
+        let mut tasks = self.tasks.lock().await;
+        let entry = tasks.get_mut(id)?;
+        if entry.task.status.state.is_terminal() {
+            return Some(Err(entry.task.clone()));
+        }
+        if let Some(handle) = entry.handle.take() {
+            handle.abort();
+        }
+        entry.task.status = A2ATaskStatus::new(A2ATaskState::Canceled, None);
+        Some(Ok(entry.task.clone()))
+    }
+
+    async fn set_status(&self, id: &str, status: A2ATaskStatus) {
+        if let Some(entry) = self.tasks.lock().await.get_mut(id) {
+            if !entry.task.status.state.is_terminal() {
+                entry.task.status = status;
+            }
+        }
+    }
+
+    fn evict(tasks: &mut HashMap<String, TaskEntry>) {
+        let expired = Utc::now() - chrono::Duration::seconds(FINISHED_TASK_TTL_SECS);
+        tasks.retain(|_, e| !e.task.status.state.is_terminal() || e.task.status.timestamp > expired);
+        if tasks.len() < MAX_A2A_TASKS {
+            return;
+        }
+        let mut finished: Vec<(String, chrono::DateTime<Utc>)> = tasks.iter()
+            .filter(|(_, e)| e.task.status.state.is_terminal())
+            .map(|(id, e)| (id.clone(), e.task.status.timestamp))
+            .collect();
+        finished.sort_by_key(|(_, t)| *t);
+        for (id, _) in finished.into_iter().take(tasks.len() + 1 - MAX_A2A_TASKS) {
+            tasks.remove(&id);
+        }
+    }
+}
+
+/// HTTP client for remote A2A agents: card discovery plus `tasks/*` JSON-RPC
+#[derive(Clone, Default)]
+pub struct A2AClient {
+    client: reqwest::Client,
+}
+
+impl A2AClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch `/.well-known/agent.json` from `base_url`, if the remote publishes one
+    pub async fn fetch_card(&self, base_url: &str) -> Option<AgentCard> {
+        let endpoint = format!("{}/.well-known/agent.json", base_url.trim_end_matches('/'));
+        let response = self.client.get(&endpoint)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json().await.ok()
+    }
+
+    pub async fn rpc(&self, endpoint: &str, method: &str, params: Value) -> AgentResult<A2ATask> {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": Uuid::new_v4().to_string(), "method": method, "params": params });
+        let reply: Value = self.client.post(endpoint)
+            .timeout(std::time::Duration::from_secs(RPC_TIMEOUT_SECS))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AgentError::Tool(format!("Network error dialing remote: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AgentError::Tool(format!("Failed to parse remote response: {}", e)))?;
+        if let Some(error) = reply.get("error") {
+            return Err(AgentError::Tool(format!("Remote {} failed: {}", method, error["message"].as_str().unwrap_or("unknown error"))));
+        }
+        serde_json::from_value(reply["result"].clone())
+            .map_err(|e| AgentError::Tool(format!("Remote returned an invalid task: {}", e)))
+    }
+
+    /// JSON-RPC endpoint of the peer configured at `base_url`: the card's path on that origin,
+    /// so a card cannot send calls to another host
+    pub fn rpc_endpoint(base_url: &str, card: &AgentCard) -> AgentResult<String> {
+        let mut endpoint = reqwest::Url::parse(base_url)
+            .map_err(|e| AgentError::Validation(format!("Invalid peer URL '{}': {}", base_url, e)))?;
+        let path = reqwest::Url::parse(&card.url).map(|u| u.path().to_string()).unwrap_or_else(|_| "/v1/a2a".to_string());
+        endpoint.set_path(&path);
+        endpoint.set_query(None);
+        endpoint.set_fragment(None);
+        Ok(endpoint.to_string())
+    }
+
+    /// Submit `query` to `skill` on the peer at `base_url` and poll until the task finishes;
+    /// cancels it after `timeout`
+    pub async fn run_task(&self, base_url: &str, card: &AgentCard, skill: &str, query: &str, timeout: std::time::Duration) -> AgentResult<A2ATask> {
+        let endpoint = Self::rpc_endpoint(base_url, card)?;
+        let params = TaskSendParams {
+            id: Some(Uuid::new_v4().to_string()),
+            session_id: None,
+            message: A2AMessage::text("user", query),
+            metadata: serde_json::json!({ "skill": skill }),
+        };
+        let mut task = self.rpc(&endpoint, "tasks/send", serde_json::to_value(&params).unwrap_or_default()).await?;
+        let deadline = std::time::Instant::now() + timeout;
+
+        while !task.status.state.is_terminal() {
+            if std::time::Instant::now() > deadline {
+                let _ = self.rpc(&endpoint, "tasks/cancel", serde_json::json!({ "id": task.id })).await;
+                return Err(AgentError::Execution(format!("Remote task on '{}' did not finish within {}s", card.name, timeout.as_secs())));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            task = self.rpc(&endpoint, "tasks/get", serde_json::json!({ "id": task.id })).await?;
+        }
+        Ok(task)
+    }
+}
+
+impl A2ATask {
+    /// Text of every artifact, joined by newlines
+    pub fn answer(&self) -> String {
+        self.artifacts.iter()
+            .map(|a| A2AMessage { role: "agent".to_string(), parts: a.parts.clone() }.text_content())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn params(text: &str, skill: &str) -> TaskSendParams {
+        serde_json::from_value(serde_json::json!({
+            "message": { "role": "user", "parts": [{ "type": "text", "text": text }] },
+            "metadata": { "skill": skill }
+        })).unwrap()
+    }
+
+    async fn wait_terminal(store: &A2ATaskStore, id: &str) -> A2ATask {
+        for _ in 0..100 {
+            let task = store.get(id).await.unwrap();
+            if task.status.state.is_terminal() {
+                return task;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("task {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_task_lifecycle() {
+        let store = Arc::new(A2ATaskStore::new());
+
+        let task = store.submit(params("hello", "coder"), |agent, text| async move {
+            Ok(AgentResponse::success(format!("{:?}: {}", agent, text), Vec::new(), agent))
+        }).await.unwrap();
+        assert_eq!(task.status.state, A2ATaskState::Submitted);
+        let done = wait_terminal(&store, &task.id).await;
+        assert_eq!(done.status.state, A2ATaskState::Completed);
+        assert!(matches!(&done.artifacts[0].parts[0], A2APart::Text { text } if text == "Coder: hello"));
+        assert!(store.cancel(&task.id).await.unwrap().is_err());
+
+        let slow = store.submit(params("wait", "chat"), |agent, _| async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(AgentResponse::success("late", Vec::new(), agent))
+        }).await.unwrap();
+        let canceled = store.cancel(&slow.id).await.unwrap().unwrap();
+        assert_eq!(canceled.status.state, A2ATaskState::Canceled);
+        assert_eq!(store.get(&slow.id).await.unwrap().status.state, A2ATaskState::Canceled);
+        assert!(store.get("missing").await.is_none());
+
+        // A client cannot take over another task by reusing its id
+        let mut reused = params("again", "chat");
+        reused.id = Some(task.id.clone());
+        let duplicate = store.submit(reused, |agent, _| async move { Ok(AgentResponse::success("other", Vec::new(), agent)) }).await;
+        assert!(duplicate.is_err());
+        assert!(matches!(&store.get(&task.id).await.unwrap().artifacts[0].parts[0], A2APart::Text { text } if text == "Coder: hello"));
+    }
+
+    #[test]
+    fn test_agent_card_lists_roles() {
+        let card = AgentCard::new(&AgencyProfile::default(), "https://agency.example/");
+        assert_eq!(card.url, "https://agency.example/v1/a2a");
+        let json = serde_json::to_value(&card).unwrap();
+        assert!(json["defaultInputModes"].is_array());
+        assert!(card.skills.iter().any(|s| s.id == "coder"));
+        assert_eq!(agent_for_skill("reviewer"), AgentType::Reviewer);
+
+        // Calls go to the configured peer, whatever host its card names
+        let mut remote = card.clone();
+        remote.url = "http://169.254.169.254/v1/a2a".to_string();
+        assert_eq!(A2AClient::rpc_endpoint("https://peer.example:8443", &remote).unwrap(), "https://peer.example:8443/v1/a2a");
+    }
+}
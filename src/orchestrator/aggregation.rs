@@ -87,6 +87,48 @@ Provide ONLY the numeric score."###,
     }
 }
 
+/// How the Supervisor picks one answer out of a candidate portfolio; set per `AgencyProfile`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregationStrategy {
+    /// N-U-C-D-χ objective (FPF default)
+    #[default]
+    Pareto,
+    /// The answer most candidates agree on after normalization
+    MajorityVote,
+    /// Highest score from an LLM judge
+    LlmJudge,
+    /// Highest reward-model score
+    RewardArgmax,
+    /// Cheapest candidate whose quality is close to the best
+    CostWeighted,
+}
+
+impl std::fmt::Display for AggregationStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Pareto => "pareto",
+            Self::MajorityVote => "majority_vote",
+            Self::LlmJudge => "llm_judge",
+            Self::RewardArgmax => "reward_argmax",
+            Self::CostWeighted => "cost_weighted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The aggregation outcome recorded in the Publication
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    /// Strategy that actually decided; differs from the requested one after a fallback
+    pub strategy: AggregationStrategy,
+    pub index: usize,
+    pub rationale: String,
+}
+
+/// Quality within this margin of the best counts as "close" for `CostWeighted`
+const COST_WEIGHTED_MARGIN: f32 = 0.1;
+
 pub struct Gamma;
 
 impl Gamma {
@@ -99,23 +141,7 @@ impl Gamma {
         let mut max_score = -1.0;
 
         for (i, c) in portfolio.candidates.iter().enumerate() {
-            // Quality (U) x (1 - Risk (C)) x Novelty (N)
-            let raw_quality = c.reward_score.unwrap_or(c.quality_score);
-            let constraint_fit = 1.0 - c.risk_score;
-            
-            // SLL uplift: Rising elasticity increases the attractiveness of a candidate 
-            // for exploitation in future loops.
-            let sll_uplift = match c.scale_elasticity {
-                ScaleElasticity::Rising => 1.2,
-                ScaleElasticity::Knee => 1.0,
-                ScaleElasticity::Flat => 0.8, // Penalize stalled scaling
-                ScaleElasticity::Unknown => 1.0,
-            };
-
-            // Score = U * C_fit * (1 + N) * χ / Cost_norm
-            let cost_norm = 1.0 + (c.cost_tokens as f32 / 1000.0);
-            let score = (raw_quality * constraint_fit * (1.0 + c.novelty_score) * sll_uplift) / cost_norm;
-            
+            let score = Self::pareto_score(c);
             if score > max_score {
                 max_score = score;
                 winner_idx = i;
@@ -125,6 +151,102 @@ impl Gamma {
         Some(winner_idx)
     }
 
+    fn pareto_score(c: &Candidate) -> f32 {
+        // Quality (U) x (1 - Risk (C)) x Novelty (N)
+        let raw_quality = c.reward_score.unwrap_or(c.quality_score);
+        let constraint_fit = 1.0 - c.risk_score;
+
+        // SLL uplift: Rising elasticity increases the attractiveness of a candidate 
+        // for exploitation in future loops.
+        let sll_uplift = match c.scale_elasticity {
+            ScaleElasticity::Rising => 1.2,
+            ScaleElasticity::Knee => 1.0,
+            ScaleElasticity::Flat => 0.8, // Penalize stalled scaling
+            ScaleElasticity::Unknown => 1.0,
+        };
+
+        // Score = U * C_fit * (1 + N) * χ / Cost_norm
+        let cost_norm = 1.0 + (c.cost_tokens as f32 / 1000.0);
+        (raw_quality * constraint_fit * (1.0 + c.novelty_score) * sll_uplift) / cost_norm
+    }
+
+    /// Pick a winner with `strategy`. `judge_scores` are only read by `LlmJudge`; strategies
+    /// missing their input (no judge or reward scores) fall back to Pareto and say so.
+    pub fn select(strategy: AggregationStrategy, portfolio: &ResultPortfolio, judge_scores: Option<&[f32]>) -> Option<Selection> {
+        let candidates = &portfolio.candidates;
+        if candidates.is_empty() { return None; }
+
+        match strategy {
+            AggregationStrategy::Pareto => Self::pareto_selection(portfolio, None),
+            AggregationStrategy::MajorityVote => {
+                let keys: Vec<String> = candidates.iter().map(|c| normalize_answer(&c.answer)).collect();
+                let votes = |key: &str| keys.iter().filter(|k| k.as_str() == key).count();
+                let top = keys.iter().map(|k| votes(k)).max().unwrap_or(0);
+                if top < 2 {
+                    return Self::pareto_selection(portfolio, Some("no two candidates agreed"));
+                }
+                // Among the largest bloc(s), the Pareto-best member represents the vote
+                let index = (0..candidates.len())
+                    .filter(|&i| votes(&keys[i]) == top)
+                    .max_by(|&a, &b| Self::pareto_score(&candidates[a]).total_cmp(&Self::pareto_score(&candidates[b])).then(b.cmp(&a)))?;
+                Some(Selection {
+                    strategy,
+                    index,
+                    rationale: format!("Majority vote: {}/{} candidates agreed on {}'s answer", top, candidates.len(), candidates[index].agent_id),
+                })
+            }
+            AggregationStrategy::LlmJudge => match judge_scores.filter(|s| s.len() == candidates.len()) {
+                Some(scores) => {
+                    let index = argmax(scores)?;
+                    Some(Selection {
+                        strategy,
+                        index,
+                        rationale: format!("LLM judge scored {} highest ({:.2})", candidates[index].agent_id, scores[index]),
+                    })
+                }
+                None => Self::pareto_selection(portfolio, Some("no judge scores")),
+            },
+            AggregationStrategy::RewardArgmax => {
+                let scores: Option<Vec<f32>> = candidates.iter().map(|c| c.reward_score).collect();
+                match scores {
+                    Some(scores) => {
+                        let index = argmax(&scores)?;
+                        Some(Selection {
+                            strategy,
+                            index,
+                            rationale: format!("Reward model scored {} highest ({:.2})", candidates[index].agent_id, scores[index]),
+                        })
+                    }
+                    None => Self::pareto_selection(portfolio, Some("no reward-model scores")),
+                }
+            }
+            AggregationStrategy::CostWeighted => {
+                let quality = |c: &Candidate| c.reward_score.unwrap_or(c.quality_score);
+                let best = candidates.iter().map(quality).fold(f32::MIN, f32::max);
+                let index = (0..candidates.len())
+                    .filter(|&i| quality(&candidates[i]) >= best - COST_WEIGHTED_MARGIN)
+                    .min_by_key(|&i| candidates[i].cost_tokens)?;
+                Some(Selection {
+                    strategy,
+                    index,
+                    rationale: format!(
+                        "Cost-weighted: {} is the cheapest ({} tokens) within {:.1} of the best quality",
+                        candidates[index].agent_id, candidates[index].cost_tokens, COST_WEIGHTED_MARGIN
+                    ),
+                })
+            }
+        }
+    }
+
+    fn pareto_selection(portfolio: &ResultPortfolio, fallback_reason: Option<&str>) -> Option<Selection> {
+        let index = Self::select_pareto_winner(portfolio)?;
+        let mut rationale = format!("Selected candidate {} ({}) based on Pareto logic", index, portfolio.candidates[index].agent_id);
+        if let Some(reason) = fallback_reason {
+            rationale.push_str(&format!(" (fallback: {})", reason));
+        }
+        Some(Selection { strategy: AggregationStrategy::Pareto, index, rationale })
+    }
+
     /// Weakest-Link Roll-up for Assurance (B.3)
     pub fn roll_up_assurance(levels: &[AssuranceLevel]) -> AssuranceLevel {
         if levels.is_empty() { return AssuranceLevel::L0; }
@@ -139,3 +261,84 @@ impl Gamma {
         !results.is_empty() && results.iter().all(|&r| r)
     }
 }
+
+/// Lowercase, collapse whitespace and drop surrounding punctuation so trivially different
+/// phrasings of the same answer vote together
+pub fn normalize_answer(answer: &str) -> String {
+    answer
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
+/// First index of the highest score
+fn argmax(scores: &[f32]) -> Option<usize> {
+    scores.iter().enumerate()
+        .fold(None, |best: Option<(usize, f32)>, (i, &s)| match best {
+            Some((_, b)) if b >= s => best,
+            _ => Some((i, s)),
+        })
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(agent_id: &str, answer: &str, quality: f32, cost_tokens: u32) -> Candidate {
+        Candidate {
+            agent_id: agent_id.to_string(),
+            answer: answer.to_string(),
+            quality_score: quality,
+            risk_score: 0.1,
+            novelty_score: 0.0,
+            cost_tokens,
+            assurance: AssuranceLevel::L1,
+            reward_score: None,
+            scale_elasticity: ScaleElasticity::Unknown,
+        }
+    }
+
+    fn portfolio() -> ResultPortfolio {
+        ResultPortfolio {
+            candidates: vec![
+                candidate("Coder", "The answer is 42.", 0.9, 2000),
+                candidate("Researcher", "the answer  is 42", 0.85, 300),
+                candidate("GeneralChat", "It is 41", 0.95, 100),
+            ],
+            selected_index: None,
+        }
+    }
+
+    #[test]
+    fn test_majority_vote_and_cost_weighted() {
+        let portfolio = portfolio();
+        let vote = Gamma::select(AggregationStrategy::MajorityVote, &portfolio, None).unwrap();
+        assert_eq!(vote.strategy, AggregationStrategy::MajorityVote);
+        assert_eq!(vote.index, 1);
+        assert!(vote.rationale.contains("2/3"));
+
+        let cheap = Gamma::select(AggregationStrategy::CostWeighted, &portfolio, None).unwrap();
+        assert_eq!(cheap.index, 2);
+    }
+
+    #[test]
+    fn test_score_strategies_fall_back_to_pareto() {
+        let mut portfolio = portfolio();
+        let judged = Gamma::select(AggregationStrategy::LlmJudge, &portfolio, Some(&[0.2, 0.9, 0.4])).unwrap();
+        assert_eq!((judged.strategy, judged.index), (AggregationStrategy::LlmJudge, 1));
+
+        let fallback = Gamma::select(AggregationStrategy::RewardArgmax, &portfolio, None).unwrap();
+        assert_eq!(fallback.strategy, AggregationStrategy::Pareto);
+        assert_eq!(Some(fallback.index), Gamma::select_pareto_winner(&portfolio));
+        assert!(fallback.rationale.contains("fallback"));
+
+        for (c, score) in portfolio.candidates.iter_mut().zip([0.7, 0.3, 0.8]) {
+            c.reward_score = Some(score);
+        }
+        let reward = Gamma::select(AggregationStrategy::RewardArgmax, &portfolio, None).unwrap();
+        assert_eq!((reward.strategy, reward.index), (AggregationStrategy::RewardArgmax, 2));
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::orchestrator::event_bus::AgencyEvent;
 
 /// FPF-aligned Autonomy Ledger (E.16)
 /// 
@@ -71,3 +74,234 @@ INSTRUCTION: If resources are low, prioritize immediate completion or 'forge_too
         )
     }
 }
+
+/// Caps for one scope; `None` means unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    pub max_tokens: Option<u64>,
+    pub max_seconds: Option<u64>,
+    pub max_usd: Option<f64>,
+    pub max_tool_calls: Option<u64>,
+}
+
+impl BudgetLimits {
+    /// `AGENCY_<SCOPE>_MAX_TOKENS`, `_MAX_SECONDS`, `_MAX_USD` and `_MAX_TOOL_CALLS`
+    pub fn from_env(scope: &str) -> Self {
+        let var = |name: &str| std::env::var(format!("AGENCY_{}_MAX_{}", scope, name)).ok();
+        Self {
+            max_tokens: var("TOKENS").and_then(|v| v.parse().ok()),
+            max_seconds: var("SECONDS").and_then(|v| v.parse().ok()),
+            max_usd: var("USD").and_then(|v| v.parse().ok()),
+            max_tool_calls: var("TOOL_CALLS").and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// The first cap `usage` has reached, e.g. `"tokens (12000/10000)"`
+    pub fn exceeded(&self, usage: &BudgetUsage) -> Option<String> {
+        if let Some(max) = self.max_tokens.filter(|&max| usage.tokens >= max) {
+            return Some(format!("tokens ({}/{})", usage.tokens, max));
+        }
+        if let Some(max) = self.max_seconds.filter(|&max| usage.seconds >= max) {
+            return Some(format!("time ({}s/{}s)", usage.seconds, max));
+        }
+        if let Some(max) = self.max_usd.filter(|&max| usage.cost_usd >= max) {
+            return Some(format!("cost (${:.4}/${:.4})", usage.cost_usd, max));
+        }
+        if let Some(max) = self.max_tool_calls.filter(|&max| usage.tool_calls >= max) {
+            return Some(format!("tool calls ({}/{})", usage.tool_calls, max));
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub tokens: u64,
+    pub seconds: u64,
+    pub cost_usd: f64,
+    pub tool_calls: u64,
+}
+
+/// Running usage of one budget; fed by `CostTracker::record` and tool executions
+/// that happen inside `metered`
+#[derive(Debug, Default)]
+pub struct UsageMeter(Mutex<BudgetUsage>);
+
+impl UsageMeter {
+    pub fn usage(&self) -> BudgetUsage {
+        *self.0.lock().unwrap()
+    }
+}
+
+tokio::task_local! {
+    /// Meters of the budgets the current task is running under
+    static METERS: Vec<Arc<UsageMeter>>;
+}
+
+/// Run `fut` with its token, spend and tool-call usage counted against `meter`
+/// (in addition to any meter already active for the task)
+pub async fn metered<F: std::future::Future>(meter: Arc<UsageMeter>, fut: F) -> F::Output {
+    let mut meters = METERS.try_with(|m| m.clone()).unwrap_or_default();
+    if !meters.iter().any(|m| Arc::ptr_eq(m, &meter)) {
+        meters.push(meter);
+    }
+    METERS.scope(meters, fut).await
+}
+
+fn with_meters(f: impl Fn(&mut BudgetUsage)) {
+    let _ = METERS.try_with(|meters| {
+        for meter in meters {
+            f(&mut meter.0.lock().unwrap());
+        }
+    });
+}
+
+/// Count model usage against the active budgets
+pub fn meter_tokens(tokens: u64, cost_usd: f64) {
+    with_meters(|usage| {
+        usage.tokens += tokens;
+        usage.cost_usd += cost_usd;
+    });
+}
+
+/// Count a tool execution against the active budgets
+pub fn meter_tool_call() {
+    with_meters(|usage| usage.tool_calls += 1);
+}
+
+/// Where a turn or session started, in terms of its budget's meter
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetMark {
+    usage: BudgetUsage,
+    started: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Turn,
+    Session,
+}
+
+/// Per-turn and per-session caps on tokens, wall-clock time, spend and tool calls.
+/// The Supervisor checks them before routing, before each escalation attempt and on
+/// every autonomous iteration; usage is published as `AgencyEvent::BudgetStatus`.
+/// Only work run inside `metered` counts, so each conversation (see `fork`) is
+/// charged for its own turns rather than for everything the process does.
+pub struct BudgetManager {
+    pub turn: BudgetLimits,
+    pub session: BudgetLimits,
+    meter: Arc<UsageMeter>,
+    session_mark: Mutex<BudgetMark>,
+}
+
+impl BudgetManager {
+    pub fn new(turn: BudgetLimits, session: BudgetLimits) -> Self {
+        let meter = Arc::new(UsageMeter::default());
+        let session_mark = Mutex::new(BudgetMark { usage: meter.usage(), started: Instant::now() });
+        Self { turn, session, meter, session_mark }
+    }
+
+    /// Limits from `AGENCY_TURN_MAX_*` and `AGENCY_SESSION_MAX_*`
+    pub fn from_env() -> Self {
+        Self::new(BudgetLimits::from_env("TURN"), BudgetLimits::from_env("SESSION"))
+    }
+
+    /// Same limits with a fresh meter, for a separate conversation
+    pub fn fork(&self) -> Self {
+        Self::new(self.turn.clone(), self.session.clone())
+    }
+
+    /// Run `fut` with its usage counted against this budget
+    pub async fn metered<F: std::future::Future>(&self, fut: F) -> F::Output {
+        metered(self.meter.clone(), fut).await
+    }
+
+    /// Start of a turn (or autonomous run)
+    pub fn mark(&self) -> BudgetMark {
+        BudgetMark { usage: self.meter.usage(), started: Instant::now() }
+    }
+
+    /// Start a new session budget, e.g. when the conversation is cleared
+    pub fn reset_session(&self) {
+        *self.session_mark.lock().unwrap() = self.mark();
+    }
+
+    pub fn usage_since(&self, mark: &BudgetMark) -> BudgetUsage {
+        let now = self.meter.usage();
+        BudgetUsage {
+            tokens: now.tokens.saturating_sub(mark.usage.tokens),
+            seconds: mark.started.elapsed().as_secs(),
+            cost_usd: (now.cost_usd - mark.usage.cost_usd).max(0.0),
+            tool_calls: now.tool_calls.saturating_sub(mark.usage.tool_calls),
+        }
+    }
+
+    pub fn session_usage(&self) -> BudgetUsage {
+        let mark = *self.session_mark.lock().unwrap();
+        self.usage_since(&mark)
+    }
+
+    /// Check the turn started at `turn` and the session; on the first exhausted cap a
+    /// `BudgetStatus` event is emitted and a description of the cap is returned
+    pub fn check(&self, turn: &BudgetMark) -> Result<(), String> {
+        for (scope, limits, usage) in [
+            (BudgetScope::Session, &self.session, self.session_usage()),
+            (BudgetScope::Turn, &self.turn, self.usage_since(turn)),
+        ] {
+            if let Some(cap) = limits.exceeded(&usage) {
+                Self::publish(scope, usage, Some(cap.clone()));
+                let scope = match scope { BudgetScope::Turn => "Turn", BudgetScope::Session => "Session" };
+                return Err(format!("{} budget exhausted: {}", scope, cap));
+            }
+        }
+        Ok(())
+    }
+
+    /// Emit the usage of the turn started at `turn` and of the session
+    pub fn report(&self, turn: &BudgetMark) {
+        Self::publish(BudgetScope::Turn, self.usage_since(turn), None);
+        Self::publish(BudgetScope::Session, self.session_usage(), None);
+    }
+
+    fn publish(scope: BudgetScope, usage: BudgetUsage, exceeded: Option<String>) {
+        crate::emit_event!(AgencyEvent::BudgetStatus { scope, usage, exceeded });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_and_usage() {
+        let limits = BudgetLimits { max_tokens: Some(1000), max_usd: Some(0.05), ..Default::default() };
+        let mut usage = BudgetUsage { tokens: 400, seconds: 3, cost_usd: 0.01, tool_calls: 7 };
+        assert_eq!(limits.exceeded(&usage), None);
+        usage.cost_usd = 0.06;
+        assert!(limits.exceeded(&usage).unwrap().starts_with("cost"));
+        usage.tokens = 1000;
+        assert_eq!(limits.exceeded(&usage).unwrap(), "tokens (1000/1000)");
+
+        let budget = BudgetManager::new(BudgetLimits::default(), BudgetLimits { max_tokens: Some(500), ..Default::default() });
+        let turn = budget.mark();
+        meter_tokens(200, 0.0); // outside `metered`: not counted
+        budget.metered(async {
+            meter_tokens(300, 0.01);
+            meter_tool_call();
+        }).await;
+        let usage = budget.usage_since(&turn);
+        assert_eq!((usage.tokens, usage.tool_calls), (300, 1));
+        assert!(budget.check(&turn).is_ok());
+
+        // A forked budget does not see this conversation's usage
+        let other = budget.fork();
+        budget.metered(async { meter_tokens(300, 0.0) }).await;
+        assert_eq!(other.session_usage().tokens, 0);
+        let err = budget.check(&budget.mark()).unwrap_err();
+        assert!(err.starts_with("Session budget exhausted"), "{}", err);
+
+        budget.reset_session();
+        assert!(budget.check(&budget.mark()).is_ok());
+    }
+}
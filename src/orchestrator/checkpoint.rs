@@ -0,0 +1,83 @@
+//! Turn Checkpoints
+//!
+//! A snapshot of the supervisor state a turn can change — episodic memory, the follow-up
+//! queue and the position in the tool side-effect journal — taken before each escalation
+//! attempt. Restoring one puts memory back and undoes the file changes made since, so a
+//! failed attempt does not leave half-applied work behind for the next tier. Plans keep
+//! their own per-step checkpoints (see `PlanExecutor`) and are not rolled back.
+
+use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::EpisodicMemory;
+use crate::tools::{RevertReport, ToolRegistry};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnCheckpoint {
+    /// What the checkpoint guards, e.g. "attempt 2"
+    pub label: String,
+    pub turn_id: String,
+    episodic: EpisodicMemory,
+    followups: VecDeque<String>,
+    /// `SideEffectJournal::mark` for the turn when the checkpoint was taken
+    side_effects: usize,
+}
+
+impl TurnCheckpoint {
+    pub async fn take(label: impl Into<String>, turn_id: &str, episodic: &EpisodicMemory, followups: &VecDeque<String>, tools: &ToolRegistry) -> Self {
+        Self {
+            label: label.into(),
+            turn_id: turn_id.to_string(),
+            episodic: episodic.clone(),
+            followups: followups.clone(),
+            side_effects: tools.journal().mark(turn_id).await,
+        }
+    }
+
+    /// Put memory and follow-ups back and undo the tool side effects recorded since
+    pub async fn restore(&self, episodic: &mut EpisodicMemory, followups: &mut VecDeque<String>, tools: &ToolRegistry) -> RevertReport {
+        *episodic = self.episodic.clone();
+        *followups = self.followups.clone();
+        self.revert_side_effects(tools).await
+    }
+
+    /// Undo only the tool side effects, leaving conversation state as it is
+    pub async fn revert_side_effects(&self, tools: &ToolRegistry) -> RevertReport {
+        tools.journal().revert(&self.turn_id, self.side_effects).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{SideEffectRecord, ToolUndo};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_restore_reverts_memory_and_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        let tools = ToolRegistry::new(dir.path().join("custom"), dir.path().join("standard"));
+        let mut memory = EpisodicMemory::default();
+        memory.add_user("draft the notes");
+        let mut followups = VecDeque::new();
+
+        let checkpoint = TurnCheckpoint::take("attempt 1", "turn", &memory, &followups, &tools).await;
+        tools.journal().record(SideEffectRecord {
+            tool: "file_system".into(),
+            parameters: serde_json::json!({ "path": "notes.md" }),
+            turn_id: Some("turn".into()),
+            undo: Some(ToolUndo::RestoreFile { path: file.clone(), previous: None }),
+            timestamp: chrono::Utc::now(),
+        }).await;
+        std::fs::write(&file, "half-written").unwrap();
+        memory.add_system("partial result");
+        followups.push_back("check the notes".into());
+
+        let report = checkpoint.restore(&mut memory, &mut followups, &tools).await;
+        assert_eq!(report.reverted, vec!["file_system notes.md".to_string()]);
+        assert!(!file.exists());
+        assert_eq!(memory, checkpoint.episodic);
+        assert!(followups.is_empty());
+    }
+}
@@ -4,51 +4,153 @@ use std::time::{Duration, Instant};
 use std::sync::Arc;
 use tokio::sync::{Mutex, mpsc};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
 
-use crate::orchestrator::{Supervisor, AgencyEvent, AGENCY_EVENT_BUS};
+use crate::agent::AgentType;
+use crate::orchestrator::{Supervisor, AgencyEvent, ReplayQuery, AGENCY_EVENT_BUS, KILL_SWITCH};
 use crate::orchestrator::mvpk::Publication;
 
 /// Events sent from the background worker or event bus to the TUI
 enum AppEvent {
     Response(String, Option<Publication>),
     Error(String),
+    /// Output of a slash command; not spoken and does not end a turn
+    Info(String),
     SystemEvent(AgencyEvent),
 }
 
+/// Slash commands offered by Tab completion
+const SLASH_COMMANDS: &[&str] = &["/approve", "/clear", "/memory", "/model", "/profile", "/queue", "/rearm", "/resume", "/tools"];
+
+/// Messages kept in the history pane's scrollback
+const HISTORY_LIMIT: usize = 1000;
+
+/// Lines moved per mouse wheel step
+const WHEEL_LINES: u16 = 3;
+
+/// Results shown by `/memory`
+const MEMORY_RESULTS: usize = 5;
+
+const AGENT_TYPES: [AgentType; 6] = [
+    AgentType::GeneralChat,
+    AgentType::Reasoner,
+    AgentType::Coder,
+    AgentType::Researcher,
+    AgentType::Planner,
+    AgentType::Reviewer,
+];
+
+/// A `/command` typed into the input box
+#[derive(Debug, Clone, PartialEq)]
+enum SlashCommand {
+    Tools,
+    Memory(String),
+    /// Show the models, or use this one for every agent type
+    Model(Option<String>),
+    Profile,
+    Clear,
+    /// Approve this call, or the only pending one
+    Approve(Option<String>),
+    Rearm,
+    Resume,
+    Queue(String),
+    Unknown(String),
+}
+
+impl SlashCommand {
+    /// `None` for input that is a query rather than a command
+    fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if !input.starts_with('/') {
+            return None;
+        }
+        let (name, arg) = match input.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (input, ""),
+        };
+        let arg = (!arg.is_empty()).then(|| arg.to_string());
+        Some(match (name, arg) {
+            ("/tools", _) => Self::Tools,
+            ("/memory", Some(query)) => Self::Memory(query),
+            ("/model", model) => Self::Model(model),
+            ("/profile", _) => Self::Profile,
+            ("/clear", _) => Self::Clear,
+            ("/approve", id) => Self::Approve(id),
+            ("/rearm", _) => Self::Rearm,
+            ("/resume", _) => Self::Resume,
+            ("/queue", Some(goal)) => Self::Queue(goal),
+            _ => Self::Unknown(input.to_string()),
+        })
+    }
+
+    /// Whether the command runs a turn, so it must wait for the current one
+    fn runs_turn(&self) -> bool {
+        matches!(self, Self::Approve(Some(_)) | Self::Resume)
+    }
+}
+
+/// Complete a partly typed command name: the longest prefix shared by the commands it starts,
+/// with the candidates when more than one matches
+fn complete_command(input: &str) -> Option<(String, Vec<&'static str>)> {
+    if !input.starts_with('/') || input.contains(char::is_whitespace) {
+        return None;
+    }
+    let matches: Vec<&'static str> = SLASH_COMMANDS.iter().copied().filter(|c| c.starts_with(input)).collect();
+    let first = *matches.first()?;
+    if matches.len() == 1 {
+        return Some((format!("{} ", first), matches));
+    }
+    let shared = matches.iter().fold(first.len(), |len, c| {
+        first.bytes().zip(c.bytes()).take(len).take_while(|(a, b)| a == b).count()
+    });
+    Some((first[..shared].to_string(), matches))
+}
+
 /// TUI Application State
 struct App {
     input: String,
     history: Vec<String>,
+    /// Wrapped lines the history pane is scrolled up from the bottom; 0 follows new messages
+    scroll: u16,
+    /// History pane as last drawn, for paging and mouse hits
+    history_area: Rect,
     logs: Vec<String>,
     status: String,
     is_orchestrating: bool,
     supervisor: Arc<Mutex<Supervisor>>,
     last_publication: Option<Publication>,
     speaker: Arc<Mutex<crate::orchestrator::Speaker>>,
+    /// Read without the supervisor lock, which a running turn holds
+    approvals: Arc<crate::safety::ApprovalQueue>,
     event_rx: mpsc::Receiver<AppEvent>,
     event_tx: mpsc::Sender<AppEvent>,
 }
 
 impl App {
-    fn new(supervisor: Arc<Mutex<Supervisor>>, speaker: Arc<Mutex<crate::orchestrator::Speaker>>) -> Self {
+    fn new(supervisor: Arc<Mutex<Supervisor>>, speaker: Arc<Mutex<crate::orchestrator::Speaker>>, approvals: Arc<crate::safety::ApprovalQueue>) -> Self {
         let (tx, rx) = mpsc::channel(100);
         
         // Subscribe to global agency events
         let tx_clone = tx.clone();
         tokio::spawn(async move {
             let mut bus_rx = AGENCY_EVENT_BUS.subscribe();
+            // Show the last logged turn, e.g. after restarting the TUI
+            if let Some(turn) = AGENCY_EVENT_BUS.latest_turn().await {
+                for record in AGENCY_EVENT_BUS.replay(&ReplayQuery { turn: Some(turn), ..Default::default() }).await {
+                    let _ = tx_clone.send(AppEvent::SystemEvent(record.event)).await;
+                }
+            }
             while let Ok(event) = bus_rx.recv().await {
                 let _ = tx_clone.send(AppEvent::SystemEvent(event)).await;
             }
@@ -57,20 +159,39 @@ impl App {
         Self {
             input: String::new(),
             history: Vec::new(),
+            scroll: 0,
+            history_area: Rect::default(),
             logs: Vec::new(),
             status: "Idle".to_string(),
             is_orchestrating: false,
             supervisor,
             last_publication: None,
             speaker,
+            approvals,
             event_rx: rx,
             event_tx: tx,
         }
     }
 
     fn push_history(&mut self, msg: String) {
+        // Keep the lines being read in view while scrolled up
+        if self.scroll > 0 {
+            let lines = history_text(std::slice::from_ref(&msg)).line_count(self.history_area.width.saturating_sub(2));
+            self.scroll = self.scroll.saturating_add(u16::try_from(lines).unwrap_or(u16::MAX));
+        }
         self.history.push(msg);
-        if self.history.len() > 50 { self.history.remove(0); }
+        if self.history.len() > HISTORY_LIMIT { self.history.remove(0); }
+    }
+
+    /// Scroll the history pane; positive is up, towards older messages
+    fn scroll_history(&mut self, lines: i32) {
+        let scroll = (i32::from(self.scroll) + lines).clamp(0, i32::from(u16::MAX));
+        self.scroll = scroll as u16;
+    }
+
+    fn history_page(&self) -> i32 {
+        // A page less the borders and one line of overlap
+        i32::from(self.history_area.height.saturating_sub(3).max(1))
     }
 
     fn push_log(&mut self, msg: String) {
@@ -79,6 +200,10 @@ impl App {
     }
 
     async fn execute_query(&mut self, query: String) {
+        if let Some(command) = SlashCommand::parse(&query) {
+            self.run_command(command).await;
+            return;
+        }
         self.is_orchestrating = true;
         self.status = "Orchestrating...".to_string();
         self.push_history(format!("λ User: {}", query));
@@ -105,23 +230,6 @@ impl App {
             return;
         }
 
-        if query.starts_with("/queue ") {
-            let task_description = query.strip_prefix("/queue ").unwrap().trim().to_string();
-            tokio::spawn(async move {
-                let guard = supervisor.lock().await;
-                let payload_json = serde_json::json!(task_description);
-                match guard.schedule_task("autonomous_goal", payload_json).await {
-                    Ok(id) => {
-                        let _ = tx.send(AppEvent::Response(format!("Task Scheduled! ID: {}", id), None)).await;
-                    },
-                    Err(e) => {
-                        let _ = tx.send(AppEvent::Error(format!("Failed to schedule task: {}", e))).await;
-                    }
-                }
-            });
-            return;
-        }
-        
         tokio::spawn(async move {
             let mut guard = supervisor.lock().await;
             match guard.handle(&query).await {
@@ -140,12 +248,153 @@ impl App {
         });
     }
 
+    async fn run_command(&mut self, mut command: SlashCommand) {
+        if command == SlashCommand::Approve(None) {
+            // A lone pending call needs no id
+            if let [only] = self.approvals.pending().await.as_slice() {
+                command = SlashCommand::Approve(Some(only.request.id.clone()));
+            }
+        }
+        if command.runs_turn() && self.is_orchestrating {
+            self.push_history("⏳ Wait for the current turn to finish (or Ctrl+K) first.".to_string());
+            return;
+        }
+        if command == SlashCommand::Clear {
+            self.history.clear();
+            self.scroll = 0;
+            self.logs.clear();
+            self.last_publication = None;
+        }
+        let runs_turn = command.runs_turn();
+        if runs_turn {
+            self.is_orchestrating = true;
+            self.status = "Orchestrating...".to_string();
+        }
+        let supervisor = self.supervisor.clone();
+        let approvals = self.approvals.clone();
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let event = match run_slash_command(&supervisor, &approvals, command).await {
+                Ok(event) => event,
+                Err(e) if runs_turn => AppEvent::Error(e.to_string()),
+                // Leaves a running turn's status alone
+                Err(e) => AppEvent::Info(format!("❌ Error: {}", e)),
+            };
+            let _ = tx.send(event).await;
+        });
+    }
+
+    /// Tab: complete the command name being typed
+    fn complete_input(&mut self) {
+        let Some((completed, candidates)) = complete_command(&self.input) else { return };
+        if candidates.len() > 1 && completed == self.input {
+            self.push_log(format!("💡 {}", candidates.join("  ")));
+        }
+        self.input = completed;
+    }
+
+    /// Ctrl+K: halt everything until `/rearm`
+    fn panic_stop(&mut self) {
+        let reason = "Panic stop from TUI (Ctrl+K)";
+        // The running turn holds the supervisor lock; engaging first makes it return
+        let aborted = KILL_SWITCH.engage(reason);
+        let supervisor = self.supervisor.clone();
+        tokio::spawn(async move {
+            supervisor.lock().await.panic_stop(reason).await;
+        });
+        self.is_orchestrating = false;
+        self.status = "HALTED".to_string();
+        self.push_history(format!("🛑 Kill switch engaged ({} tasks aborted). Type /rearm to resume.", aborted));
+    }
+
     async fn steer(&self, msg: String) {
         let guard = self.supervisor.lock().await;
         let _ = guard.steer(msg).await;
     }
 }
 
+async fn run_slash_command(supervisor: &Mutex<Supervisor>, approvals: &crate::safety::ApprovalQueue, command: SlashCommand) -> Result<AppEvent> {
+    let info = |text: String| Ok(AppEvent::Info(text));
+    match command {
+        SlashCommand::Tools => {
+            let tools = supervisor.lock().await.tools.clone();
+            let names = tools.tool_names().await;
+            info(format!("🔧 {} tools: {}", names.len(), names.join(", ")))
+        }
+        SlashCommand::Memory(query) => {
+            let Some(memory) = supervisor.lock().await.memory.clone() else {
+                return info("🧠 No vector memory is configured.".to_string());
+            };
+            let entries = memory.search(&query, MEMORY_RESULTS, None, None).await?;
+            if entries.is_empty() {
+                return info(format!("🧠 Nothing remembered about '{}'.", query));
+            }
+            let lines: Vec<String> = entries.iter().map(|e| {
+                let content: String = e.content.chars().take(120).collect();
+                format!("  [{}] {}", e.metadata.agent, content.replace('\n', " "))
+            }).collect();
+            info(format!("🧠 {} memories for '{}':\n{}", entries.len(), query, lines.join("\n")))
+        }
+        SlashCommand::Model(None) => {
+            let guard = supervisor.lock().await;
+            let models: Vec<String> = AGENT_TYPES.iter().map(|agent| {
+                let model = guard.profile.default_models.get(agent).map(String::as_str).unwrap_or(agent.default_model());
+                format!("{:?}={}", agent, model)
+            }).collect();
+            info(format!("🤖 Models: {}", models.join(", ")))
+        }
+        SlashCommand::Model(Some(model)) => {
+            // Only for this run; the saved profile is unchanged
+            let mut guard = supervisor.lock().await;
+            for agent in AGENT_TYPES {
+                guard.profile.default_models.insert(agent, model.clone());
+            }
+            info(format!("🤖 Every agent now uses {} until restart.", model))
+        }
+        SlashCommand::Profile => {
+            let profile = supervisor.lock().await.profile.clone();
+            info(format!(
+                "🏛️ {}: {}\n  Traits: {}\n  Verbosity: {:?} | Aggregation: {:?} | Plan critique: {}",
+                profile.name, profile.mission, profile.traits.join(", "), profile.verbosity, profile.aggregation, profile.plan_critique
+            ))
+        }
+        SlashCommand::Clear => {
+            supervisor.lock().await.clear_history().await?;
+            info("🧹 Conversation cleared.".to_string())
+        }
+        SlashCommand::Approve(None) => {
+            let pending = approvals.pending().await;
+            if pending.is_empty() {
+                return info("✋ No tool calls are waiting for approval.".to_string());
+            }
+            let lines: Vec<String> = pending.iter()
+                .map(|p| format!("  {} {}: {}", p.request.id, p.request.tool_name, p.request.rationale))
+                .collect();
+            info(format!("✋ {} pending approvals; use /approve <id>:\n{}", pending.len(), lines.join("\n")))
+        }
+        SlashCommand::Approve(Some(id)) => {
+            let result = supervisor.lock().await.approve(&id).await?;
+            Ok(AppEvent::Response(result.answer, result.publication))
+        }
+        SlashCommand::Rearm => {
+            supervisor.lock().await.rearm();
+            Ok(AppEvent::Response("Kill switch re-armed.".to_string(), None))
+        }
+        SlashCommand::Resume => match supervisor.lock().await.resume_plan().await? {
+            Some(result) => Ok(AppEvent::Response(result.answer, result.publication)),
+            None => Ok(AppEvent::Response("No unfinished plan to resume.".to_string(), None)),
+        },
+        SlashCommand::Queue(goal) => {
+            let id = supervisor.lock().await.schedule_task("autonomous_goal", serde_json::json!(goal)).await
+                .map_err(|e| anyhow::anyhow!("Failed to schedule task: {}", e))?;
+            info(format!("Task Scheduled! ID: {}", id))
+        }
+        SlashCommand::Unknown(input) => {
+            info(format!("❓ Unknown command '{}'. Commands: {}", input, SLASH_COMMANDS.join(" ")))
+        }
+    }
+}
+
 pub struct AgencyCLI {
     supervisor: Arc<Mutex<Supervisor>>,
     speaker: Arc<Mutex<crate::orchestrator::Speaker>>,
@@ -159,16 +408,18 @@ impl AgencyCLI {
     pub async fn run(self) -> Result<()> {
         enable_raw_mode()?;
         stdout().execute(EnterAlternateScreen)?;
+        stdout().execute(EnableMouseCapture)?;
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
         // Consuming self allows moving supervisor safely
-        let mut app = App::new(self.supervisor, self.speaker.clone());
+        let approvals = self.supervisor.lock().await.approvals.clone();
+        let mut app = App::new(self.supervisor, self.speaker.clone(), approvals);
 
         let tick_rate = Duration::from_millis(50);
         let mut last_tick = Instant::now();
 
         loop {
-            terminal.draw(|f| ui(f, &app))?;
+            terminal.draw(|f| ui(f, &mut app))?;
 
             // Handle background events
             while let Ok(event) = app.event_rx.try_recv() {
@@ -190,6 +441,11 @@ impl AgencyCLI {
                         app.is_orchestrating = false;
                         app.status = "Error".to_string();
                     }
+                    AppEvent::Info(text) => {
+                        for line in text.lines() {
+                            app.push_history(line.to_string());
+                        }
+                    }
                     AppEvent::SystemEvent(e) => {
                         match e {
                             AgencyEvent::StatusUpdate(s) => app.status = s,
@@ -198,7 +454,13 @@ impl AgencyCLI {
                                 let icon = if success { "✅" } else { "❌" };
                                 app.push_log(format!("{} Tool End: {}", icon, tool));
                             }
+                            AgencyEvent::ToolProgress { tool, chunk } => app.push_log(format!("⏳ {}: {}", tool, chunk.trim_end())),
                             AgencyEvent::TurnStarted { agent, model } => app.push_log(format!("🤖 Turn Start: {} ({})", agent, model)),
+                            AgencyEvent::ScheduledRunFinished { name, answer, .. } => app.push_history(format!("⏰ {}: {}", name, answer)),
+                            AgencyEvent::WebhookHandled { hook_id, answer, .. } => app.push_history(format!("🪝 {}: {}", hook_id, answer)),
+                            AgencyEvent::BudgetStatus { scope, exceeded: Some(cap), .. } => app.push_history(format!("💸 {:?} budget exhausted: {}", scope, cap)),
+                            AgencyEvent::CheckpointRestored { label, reverted, irreversible } => app.push_log(format!("↩️ Rolled back {}: {} undone, {} irreversible", label, reverted.len(), irreversible.len())),
+                            AgencyEvent::SubAgentFinished { task_id, success, .. } => app.push_log(format!("🧬 Sub-agent {} {}", task_id, if success { "finished" } else { "failed" })),
                             _ => app.push_log(format!("📝 Event: {:?}", e)),
                         }
                     }
@@ -207,32 +469,57 @@ impl AgencyCLI {
 
             let timeout = tick_rate.saturating_sub(last_tick.elapsed());
             if event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
+                match event::read()? {
+                    Event::Mouse(mouse) if app.history_area.contains(Position::new(mouse.column, mouse.row)) => {
+                        match mouse.kind {
+                            MouseEventKind::ScrollUp => app.scroll_history(i32::from(WHEEL_LINES)),
+                            MouseEventKind::ScrollDown => app.scroll_history(-i32::from(WHEEL_LINES)),
+                            _ => {}
+                        }
+                    }
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
                         match key.code {
                             KeyCode::Enter => {
                                 let query = std::mem::take(&mut app.input);
                                 if query == "quit" || query == "exit" {
                                     break;
                                 }
-                                if app.is_orchestrating {
+                                if let Some(command) = SlashCommand::parse(&query).filter(|_| app.is_orchestrating) {
+                                    app.run_command(command).await;
+                                } else if app.is_orchestrating {
                                     app.steer(query).await;
                                 } else {
                                     app.execute_query(query).await;
                                 }
                             }
+                            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                app.panic_stop();
+                            }
+                            KeyCode::Tab => {
+                                app.complete_input();
+                            }
                             KeyCode::Char(c) => {
                                 app.input.push(c);
                             }
                             KeyCode::Backspace => {
                                 app.input.pop();
                             }
+                            KeyCode::PageUp => {
+                                app.scroll_history(app.history_page());
+                            }
+                            KeyCode::PageDown => {
+                                app.scroll_history(-app.history_page());
+                            }
+                            KeyCode::End if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                app.scroll = 0;
+                            }
                             KeyCode::Esc => {
                                 break;
                             }
                             _ => {}
                         }
                     }
+                    _ => {}
                 }
             }
 
@@ -242,12 +529,19 @@ impl AgencyCLI {
         }
 
         disable_raw_mode()?;
+        stdout().execute(DisableMouseCapture)?;
         stdout().execute(LeaveAlternateScreen)?;
         Ok(())
     }
 }
 
-fn ui(f: &mut Frame, app: &App) {
+/// History messages as wrapping text, one line per line of each message
+fn history_text(history: &[String]) -> Paragraph<'_> {
+    let lines: Vec<Line> = history.iter().flat_map(|msg| msg.lines().map(Line::from)).collect();
+    Paragraph::new(lines).wrap(Wrap { trim: false })
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -265,13 +559,22 @@ fn ui(f: &mut Frame, app: &App) {
         ])
         .split(chunks[0]);
 
-    // History Area
-    let history: Vec<ListItem> = app.history.iter().map(|msg| {
-        ListItem::new(msg.as_str())
-    }).collect();
-    let history_list = List::new(history)
-        .block(Block::default().borders(Borders::ALL).title(" 🏛️ FPF Interaction Trace "));
-    f.render_widget(history_list, main_chunks[0]);
+    // History Area: scrolled by wrapped lines, counted from the bottom
+    let area = main_chunks[0];
+    app.history_area = area;
+    let history = history_text(&app.history);
+    let total = u16::try_from(history.line_count(area.width.saturating_sub(2))).unwrap_or(u16::MAX);
+    let max_scroll = total.saturating_sub(area.height.saturating_sub(2));
+    app.scroll = app.scroll.min(max_scroll);
+    let title = if app.scroll > 0 {
+        format!(" 🏛️ FPF Interaction Trace (↑ {} lines, Ctrl+End: latest) ", app.scroll)
+    } else {
+        " 🏛️ FPF Interaction Trace ".to_string()
+    };
+    let history = history
+        .scroll((max_scroll - app.scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(history, area);
 
     // Sidebar: Telemetry & Logs
     let sidebar_chunks = Layout::default()
@@ -340,8 +643,45 @@ fn ui(f: &mut Frame, app: &App) {
     f.render_widget(input, chunks[1]);
 
     // Footer
-    let help_text = format!(" ESC: Quit | /queue <goal>: Schedule Task | PID: {} | SOTA v0.2.0 ", std::process::id());
+    let help_text = format!(" ESC: Quit | Ctrl+K: Kill Switch (/rearm) | PgUp/PgDn/Wheel: Scroll | Tab: Complete /commands (/tools /memory /model /profile /clear /approve /queue /resume) | PID: {} | SOTA v0.2.0 ", std::process::id());
     let footer = Paragraph::new(help_text)
         .style(Style::default().fg(Color::DarkGray));
     f.render_widget(footer, chunks[2]);
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slash_commands() {
+        assert_eq!(SlashCommand::parse("what is rust?"), None);
+        assert_eq!(SlashCommand::parse("/tools"), Some(SlashCommand::Tools));
+        assert_eq!(SlashCommand::parse("/memory  rust traits "), Some(SlashCommand::Memory("rust traits".to_string())));
+        assert_eq!(SlashCommand::parse("/model"), Some(SlashCommand::Model(None)));
+        assert_eq!(SlashCommand::parse("/model llama3"), Some(SlashCommand::Model(Some("llama3".to_string()))));
+        assert_eq!(SlashCommand::parse("/approve"), Some(SlashCommand::Approve(None)));
+        assert_eq!(SlashCommand::parse("/approve abc"), Some(SlashCommand::Approve(Some("abc".to_string()))));
+        assert_eq!(SlashCommand::parse("/queue"), Some(SlashCommand::Unknown("/queue".to_string())));
+        assert_eq!(SlashCommand::parse("/memory"), Some(SlashCommand::Unknown("/memory".to_string())));
+        assert!(SlashCommand::Resume.runs_turn());
+        assert!(!SlashCommand::Approve(None).runs_turn());
+    }
+
+    #[test]
+    fn test_complete_command() {
+        assert_eq!(complete_command("/to"), Some(("/tools ".to_string(), vec!["/tools"])));
+        assert_eq!(complete_command("/m"), Some(("/m".to_string(), vec!["/memory", "/model"])));
+        assert_eq!(complete_command("/mo"), Some(("/model ".to_string(), vec!["/model"])));
+        assert_eq!(complete_command("/re"), Some(("/re".to_string(), vec!["/rearm", "/resume"])));
+        assert_eq!(complete_command("/x"), None);
+        assert_eq!(complete_command("/model ll"), None);
+        assert_eq!(complete_command("hello"), None);
+    }
+
+    #[test]
+    fn test_history_text_counts_wrapped_lines() {
+        let history = vec!["λ User: hi\nthere".to_string(), "x".repeat(25)];
+        assert_eq!(history_text(&history).line_count(80), 3);
+        assert_eq!(history_text(&history).line_count(20), 4);
+    }
+}
@@ -0,0 +1,340 @@
+//! Conversation Registry
+//!
+//! Independent conversations keyed by session id. Each one gets its own `Supervisor`
+//! (see `Supervisor::for_conversation`) with its own episodic memory, follow-up queue and
+//! safety counters, persisted to `<dir>/<id>.json`, so the server and desktop app can run
+//! several conversations at once without one turn waiting on another. Conversations idle past
+//! `IDLE_TTL`, or the least recently used beyond `MAX_OPEN`, are closed; their session files
+//! stay, so they reopen on next use.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tracing::{info, warn};
+
+use crate::agent::{AgentError, AgentResult};
+use crate::memory::episodic::Role;
+use crate::orchestrator::profile::AgencyProfile;
+use crate::orchestrator::session::SessionState;
+use crate::orchestrator::{SessionManager, Supervisor, TurnEvent};
+
+/// Longest accepted session id
+const MAX_ID_LEN: usize = 64;
+/// Characters of the first message used as an untitled conversation's title
+const TITLE_LEN: usize = 60;
+/// Open conversations unused this long are closed
+const IDLE_TTL: Duration = Duration::from_secs(2 * 60 * 60);
+/// Open conversations kept in memory at once
+const MAX_OPEN: usize = 256;
+
+/// Conversations to close before opening another: those idle past `IDLE_TTL`, then the least
+/// recently used until there is room. Each entry is `(id, last used, busy)`; busy ones (a turn
+/// or request holds them) are never picked.
+fn to_evict(mut open: Vec<(String, Instant, bool)>) -> Vec<String> {
+    let mut count = open.len();
+    open.retain(|(_, _, busy)| !busy);
+    open.sort_by_key(|(_, used, _)| *used);
+    let mut evict = Vec::new();
+    for (id, used, _) in open {
+        if used.elapsed() < IDLE_TTL && count < MAX_OPEN {
+            break;
+        }
+        evict.push(id);
+        count -= 1;
+    }
+    evict
+}
+
+/// Turn events of conversation `id` only, from its entry in `feeds`
+fn subscribe_feed(feeds: &HashMap<String, broadcast::Sender<TurnEvent>>, id: &str) -> AgentResult<broadcast::Receiver<TurnEvent>> {
+    feeds.get(id)
+        .map(broadcast::Sender::subscribe)
+        .ok_or_else(|| AgentError::Validation(format!("Conversation '{}' was closed", id)))
+}
+
+/// Ids become file names, so only `[A-Za-z0-9_-]` is allowed
+pub fn valid_session_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A conversation as listed in a session sidebar
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary {
+    pub id: String,
+    /// Its given title, else the start of its first message
+    pub title: String,
+    pub messages: usize,
+    /// When its session file was last written
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Loaded in memory
+    pub open: bool,
+}
+
+pub struct ConversationRegistry {
+    /// Shares the main supervisor's providers, tools and stores; new conversations are forked from it
+    template: Mutex<Supervisor>,
+    /// Where conversation session files are kept; `None` keeps them in memory only
+    dir: Option<PathBuf>,
+    conversations: Mutex<HashMap<String, Arc<Mutex<Supervisor>>>>,
+    /// Each open conversation's turn feed, reachable while a turn holds its supervisor
+    feeds: Mutex<HashMap<String, broadcast::Sender<TurnEvent>>>,
+    /// Each open conversation's agent steering channels, likewise
+    steering: Mutex<HashMap<String, Arc<Mutex<Vec<mpsc::Sender<String>>>>>>,
+    /// When each open conversation was last fetched
+    last_used: Mutex<HashMap<String, Instant>>,
+}
+
+impl ConversationRegistry {
+    pub fn new(base: &Supervisor, dir: Option<PathBuf>) -> Self {
+        Self {
+            template: Mutex::new(base.for_conversation("template", None)),
+            dir,
+            conversations: Mutex::new(HashMap::new()),
+            feeds: Mutex::new(HashMap::new()),
+            steering: Mutex::new(HashMap::new()),
+            last_used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Session files under `AGENCY_SESSIONS_DIR` (default `sessions`; `off` disables persistence)
+    pub fn from_env(base: &Supervisor) -> Self {
+        let dir = std::env::var("AGENCY_SESSIONS_DIR").unwrap_or_else(|_| "sessions".to_string());
+        Self::new(base, (dir != "off" && !dir.is_empty()).then(|| PathBuf::from(dir)))
+    }
+
+    /// The supervisor for conversation `id`, created (from its saved session, if any) on first use
+    pub async fn get(&self, id: &str) -> AgentResult<Arc<Mutex<Supervisor>>> {
+        self.get_for(id, None).await
+    }
+
+    /// Like `get`, with a newly opened conversation owned by `user`
+    pub async fn get_for(&self, id: &str, user: Option<&str>) -> AgentResult<Arc<Mutex<Supervisor>>> {
+        if !valid_session_id(id) {
+            return Err(AgentError::Validation(format!("Invalid session id '{}'", id)));
+        }
+        if let Some(existing) = self.conversations.lock().await.get(id) {
+            self.last_used.lock().await.insert(id.to_string(), Instant::now());
+            return Ok(existing.clone());
+        }
+        self.evict().await;
+
+        let session = match self.dir {
+            Some(ref dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                self.session_path(id).map(SessionManager::new)
+            }
+            None => None,
+        };
+        let mut supervisor = self.template.lock().await.for_conversation(id, session);
+        supervisor.user = user.map(str::to_string);
+        if let Err(e) = supervisor.load_session().await {
+            warn!("Conversation {}: could not load saved session: {}", id, e);
+        }
+        info!("Conversation {} opened", id);
+
+        // Another request may have opened it while the session loaded; keep the first
+        let mut conversations = self.conversations.lock().await;
+        if let Some(existing) = conversations.get(id) {
+            return Ok(existing.clone());
+        }
+        self.feeds.lock().await.insert(id.to_string(), supervisor.turn_feed.clone());
+        self.steering.lock().await.insert(id.to_string(), supervisor.active_steer_txs.clone());
+        let supervisor = Arc::new(Mutex::new(supervisor));
+        conversations.insert(id.to_string(), supervisor.clone());
+        self.last_used.lock().await.insert(id.to_string(), Instant::now());
+        Ok(supervisor)
+    }
+
+    /// Close idle and least recently used conversations to make room for another
+    async fn evict(&self) {
+        let evicted = {
+            let conversations = self.conversations.lock().await;
+            let last_used = self.last_used.lock().await;
+            to_evict(conversations.iter().map(|(id, supervisor)| {
+                let busy = Arc::strong_count(supervisor) > 1 || supervisor.try_lock().is_err();
+                (id.clone(), last_used.get(id).copied().unwrap_or_else(Instant::now), busy)
+            }).collect())
+        };
+        for id in evicted {
+            info!("Conversation {} closed after going idle", id);
+            self.close(&id).await;
+        }
+    }
+
+    /// Events of every turn in conversation `id` from now on, opening it if needed
+    pub async fn subscribe_turns(&self, id: &str, user: Option<&str>) -> AgentResult<broadcast::Receiver<TurnEvent>> {
+        self.get_for(id, user).await?;
+        subscribe_feed(&self.feeds.lock().await, id)
+    }
+
+    /// Steer the agents running a turn in open conversation `id`; returns how many took it
+    pub async fn steer(&self, id: &str, message: &str) -> AgentResult<usize> {
+        let channels = self.steering.lock().await.get(id).cloned()
+            .ok_or_else(|| AgentError::Validation(format!("Conversation '{}' is not open", id)))?;
+        Ok(crate::orchestrator::supervisor::steer_agents(&channels, message).await)
+    }
+
+    /// Use `profile` in conversations opened from now on and, once their running turn ends,
+    /// in the open ones
+    pub async fn set_profile(&self, profile: &AgencyProfile) {
+        self.template.lock().await.profile = profile.clone();
+        for supervisor in self.conversations.lock().await.values().cloned() {
+            apply_profile(supervisor, profile.clone());
+        }
+    }
+
+    /// Open conversations, sorted
+    pub async fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.conversations.lock().await.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Drop a conversation from memory (its session file is kept); false if it was not open
+    pub async fn close(&self, id: &str) -> bool {
+        self.feeds.lock().await.remove(id);
+        self.steering.lock().await.remove(id);
+        self.last_used.lock().await.remove(id);
+        self.conversations.lock().await.remove(id).is_some()
+    }
+
+    fn session_path(&self, id: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.json", id)))
+    }
+
+    /// Open a new conversation with a generated id
+    pub async fn create(&self, title: Option<&str>) -> AgentResult<ConversationSummary> {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        self.get(&id).await?;
+        if let Some(title) = title {
+            self.rename(&id, Some(title)).await?;
+        }
+        Ok(self.summary(&id).await)
+    }
+
+    /// Open and saved conversations, most recently updated first
+    pub async fn summaries(&self) -> AgentResult<Vec<ConversationSummary>> {
+        let mut ids: BTreeSet<String> = self.ids().await.into_iter().collect();
+        if let Some(ref dir) = self.dir {
+            if dir.exists() {
+                let mut entries = tokio::fs::read_dir(dir).await?;
+                while let Some(entry) = entries.next_entry().await? {
+                    let path = entry.path();
+                    let stem = path.file_stem().and_then(|s| s.to_str()).filter(|id| valid_session_id(id));
+                    if let (Some(id), true) = (stem, path.extension().is_some_and(|e| e == "json")) {
+                        ids.insert(id.to_string());
+                    }
+                }
+            }
+        }
+        let mut summaries = Vec::new();
+        for id in ids {
+            summaries.push(self.summary(&id).await);
+        }
+        summaries.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(summaries)
+    }
+
+    pub async fn summary(&self, id: &str) -> ConversationSummary {
+        let open = self.conversations.lock().await.get(id).cloned();
+        let (state, updated_at) = match self.session_path(id) {
+            Some(path) => {
+                let modified = tokio::fs::metadata(&path).await.ok().and_then(|m| m.modified().ok());
+                (SessionManager::new(&path).load().await.unwrap_or_default(), modified.map(DateTime::<Utc>::from))
+            }
+            None => (SessionState::default(), None),
+        };
+        // An open conversation may be ahead of its file; unless a turn holds it
+        let turns = match open.as_ref().and_then(|supervisor| supervisor.try_lock().ok()) {
+            Some(supervisor) => supervisor.episodic_memory.lock().await.get_turns(),
+            None => state.episodic_memory.get_turns(),
+        };
+        let title = state.title.unwrap_or_else(|| {
+            let first = turns.iter()
+                .find(|t| matches!(t.role, Role::User))
+                .and_then(|t| t.content.lines().find(|l| !l.trim().is_empty()))
+                .map(str::trim)
+                .unwrap_or("New conversation");
+            match first.char_indices().nth(TITLE_LEN) {
+                Some((end, _)) => format!("{}…", &first[..end]),
+                None => first.to_string(),
+            }
+        });
+        ConversationSummary { id: id.to_string(), title, messages: turns.len(), updated_at, open: open.is_some() }
+    }
+
+    /// Name conversation `id` (`None` goes back to its first message); saved with its session
+    pub async fn rename(&self, id: &str, title: Option<&str>) -> AgentResult<()> {
+        if !valid_session_id(id) {
+            return Err(AgentError::Validation(format!("Invalid session id '{}'", id)));
+        }
+        let path = self.session_path(id)
+            .ok_or_else(|| AgentError::Validation("Conversations are not saved (AGENCY_SESSIONS_DIR=off)".to_string()))?;
+        let title = title.map(str::trim).filter(|t| !t.is_empty());
+        SessionManager::new(path).set_title(title).await.map_err(|e| AgentError::Execution(e.to_string()))
+    }
+
+    /// Close conversation `id` and delete its session file; false if there was neither
+    pub async fn delete(&self, id: &str) -> AgentResult<bool> {
+        if !valid_session_id(id) {
+            return Err(AgentError::Validation(format!("Invalid session id '{}'", id)));
+        }
+        let mut existed = self.close(id).await;
+        if let Some(path) = self.session_path(id).filter(|p| p.exists()) {
+            tokio::fs::remove_file(path).await?;
+            existed = true;
+        }
+        Ok(existed)
+    }
+}
+
+/// Set `supervisor`'s profile without waiting for a turn that holds it
+pub fn apply_profile(supervisor: Arc<Mutex<Supervisor>>, profile: AgencyProfile) {
+    tokio::spawn(async move {
+        supervisor.lock().await.profile = profile;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_session_id() {
+        assert!(valid_session_id("design-review_2"));
+        assert!(!valid_session_id(""));
+        assert!(!valid_session_id("../session"));
+        assert!(!valid_session_id("a b"));
+        assert!(!valid_session_id(&"x".repeat(MAX_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn test_to_evict() {
+        let now = Instant::now();
+        let stale = now - IDLE_TTL - Duration::from_secs(1);
+        let open = vec![("idle".to_string(), stale, false), ("running".to_string(), stale, true), ("fresh".to_string(), now, false)];
+        assert_eq!(to_evict(open), vec!["idle".to_string()]);
+
+        // At the cap the least recently used goes, so a new conversation fits
+        let full: Vec<_> = (0..MAX_OPEN).map(|i| (i.to_string(), now + Duration::from_millis(i as u64), false)).collect();
+        assert_eq!(to_evict(full), vec!["0".to_string()]);
+    }
+
+    #[test]
+    fn test_turn_feeds_are_per_conversation() {
+        let feeds: HashMap<String, broadcast::Sender<TurnEvent>> = ["alice-a", "bob-a"].iter()
+            .map(|id| (id.to_string(), broadcast::channel(8).0))
+            .collect();
+        let mut alice = subscribe_feed(&feeds, "alice-a").unwrap();
+        let mut bob = subscribe_feed(&feeds, "bob-a").unwrap();
+
+        feeds["alice-a"].send(TurnEvent::TokenChunk { text: "hi".into() }).unwrap();
+        assert!(matches!(alice.try_recv(), Ok(TurnEvent::TokenChunk { ref text }) if text == "hi"));
+        assert!(bob.try_recv().is_err());
+        assert!(subscribe_feed(&feeds, "closed").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Escalation Policy
+//!
+//! How far the Supervisor's escalation loop may go after a failed attempt: how many attempts,
+//! which model serves each scale class, how much a turn may spend before it stops escalating,
+//! how long to wait between tiers, and whether hosted models are off limits (privacy mode).
+//! Stored in `AgencyProfile` under `escalation`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::orchestrator::routing::ROUTING;
+use crate::orchestrator::{ScaleClass, ScaleProfile};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationPolicy {
+    /// Attempts per turn, the first one included
+    pub max_attempts: usize,
+    /// Keyed by scale class (`tiny`, `standard`, `heavy`, ...); wins over the routing matrix
+    pub class_models: HashMap<String, String>,
+    /// Spend (USD) after which a turn stops escalating
+    pub max_cost_usd: Option<f64>,
+    /// Wait before the first escalation, doubled for each one after it
+    pub backoff_ms: u64,
+    /// Privacy mode: never escalate to a model served by a hosted provider
+    pub local_only: bool,
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            class_models: HashMap::new(),
+            max_cost_usd: None,
+            backoff_ms: 0,
+            local_only: false,
+        }
+    }
+}
+
+impl EscalationPolicy {
+    pub fn model_for(&self, class: ScaleClass) -> Option<&str> {
+        self.class_models.get(class.key()).map(String::as_str)
+    }
+
+    /// Scale profile for `class`, with this policy's model override applied
+    pub fn scale_for(&self, class: ScaleClass, vram_available_gb: f32) -> ScaleProfile {
+        let mut scale = ScaleProfile::new_with_class(class, vram_available_gb);
+        self.apply_override(&mut scale);
+        scale
+    }
+
+    pub fn apply_override(&self, scale: &mut ScaleProfile) {
+        if let Some(model) = self.model_for(scale.class) {
+            scale.target_model = model.to_string();
+        }
+    }
+
+    /// Delay before escalation number `escalation` (1 for the second attempt)
+    pub fn backoff(&self, escalation: usize) -> Duration {
+        let factor = 1u64 << escalation.saturating_sub(1).min(16);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor))
+    }
+
+    /// Which ceiling `spent_usd` has reached, if any
+    pub fn cost_exceeded(&self, spent_usd: f64) -> Option<String> {
+        self.max_cost_usd
+            .filter(|&max| spent_usd >= max)
+            .map(|max| format!("escalation cost ceiling reached (${:.4}/${:.4})", spent_usd, max))
+    }
+
+    /// Whether escalating to `model` is permitted
+    pub fn allows(&self, model: &str) -> bool {
+        !self.local_only || is_local_model(model)
+    }
+}
+
+/// Served by a local provider: the model's route names one, else `AGENCY_PROVIDER` does.
+/// Unknown providers count as hosted.
+pub fn is_local_model(model: &str) -> bool {
+    let provider = ROUTING.current().route_for_model(model)
+        .and_then(|r| r.provider.clone())
+        .or_else(|| std::env::var("AGENCY_PROVIDER").ok())
+        .unwrap_or_default();
+    crate::agent::provider::is_local_provider(&provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_deserializes_partially() {
+        let policy: EscalationPolicy = serde_json::from_str(
+            r#"{"max_attempts": 2, "class_models": {"heavy": "qwen2.5:14b"}, "max_cost_usd": 0.05, "backoff_ms": 500}"#,
+        ).unwrap();
+        assert_eq!(policy.max_attempts, 2);
+        assert!(!policy.local_only);
+        assert_eq!(policy.model_for(ScaleClass::Heavy), Some("qwen2.5:14b"));
+        assert_eq!(policy.model_for(ScaleClass::Tiny), None);
+        assert_eq!(policy.scale_for(ScaleClass::Heavy, 8.0).target_model, "qwen2.5:14b");
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), Duration::from_millis(2000));
+        assert!(policy.cost_exceeded(0.01).is_none());
+        assert!(policy.cost_exceeded(0.06).unwrap().contains("ceiling"));
+    }
+
+    #[test]
+    fn test_default_policy_allows_any_model() {
+        let policy = EscalationPolicy::default();
+        assert_eq!(policy.max_attempts, 3);
+        assert_eq!(policy.backoff(2), Duration::ZERO);
+        assert!(policy.allows("gpt-4o"));
+    }
+}
@@ -1,12 +1,20 @@
 //! Internal Event Bus for Agency Coordination
 //! 
 //! Provides a centralized, asynchronous pub/sub system for cross-component 
-//! communication and telemetry tracing.
+//! communication and telemetry tracing. Every event is also appended to a JSONL log
+//! (`AGENCY_EVENT_LOG`) with a sequence number and the turn it belongs to, so a
+//! dashboard that reconnects can replay what it missed.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::broadcast;
 use std::sync::Arc;
 use crate::agent::{LadeQuadrant, PubCharacteristic};
+use crate::orchestrator::budget::{BudgetScope, BudgetUsage};
 
 /// FPF-Aligned Claim Entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,36 +46,231 @@ pub enum AgencyEvent {
     ToolCallStarted { tool: String },
     /// A tool call observation was received
     ToolCallFinished { tool: String, success: bool },
+    /// Partial output from a tool that is still running
+    ToolProgress { tool: String, chunk: String },
     /// HITL Approval was requested
     ApprovalRequested { id: String, tool: String },
+    /// A scheduled prompt finished running
+    ScheduledRunFinished { schedule_id: String, name: String, success: bool, answer: String },
+    /// Work triggered by an inbound webhook finished
+    WebhookHandled { hook_id: String, delivery_id: String, success: bool, answer: String },
+    /// Budget usage for a turn or the session; `exceeded` names the cap that stopped work
+    BudgetStatus { scope: BudgetScope, usage: BudgetUsage, exceeded: Option<String> },
+    /// A turn returned to a checkpoint after a failed attempt or a rejected call
+    CheckpointRestored { label: String, reverted: Vec<String>, irreversible: Vec<String> },
+    /// A sub-agent spawned with `spawn_task` finished or was refused its start
+    SubAgentFinished { task_id: String, parent_turn: Option<String>, success: bool, answer: String },
     /// Generic system status update
     StatusUpdate(String),
 }
 
+/// A published event with its position in the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Supervisor turn the event was emitted in
+    #[serde(default)]
+    pub turn_id: Option<String>,
+    /// Authenticated user the event was emitted for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub event: AgencyEvent,
+}
+
+impl EventRecord {
+    /// Whether a caller limited to `user`'s events (`None`: every event) may see this one
+    pub fn visible_to(&self, user: Option<&str>) -> bool {
+        user.is_none_or(|user| self.user.as_deref() == Some(user))
+    }
+}
+
+/// Which logged events to replay
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReplayQuery {
+    /// Only events with a greater sequence number
+    pub since: Option<u64>,
+    /// Only events of this turn
+    pub turn: Option<String>,
+    /// At most this many, keeping the newest
+    pub limit: Option<usize>,
+    /// Only events emitted for this user; set by the server, never by the client
+    #[serde(skip)]
+    pub user: Option<String>,
+}
+
+tokio::task_local! {
+    static CURRENT_TURN: Option<String>;
+    static CURRENT_USER: Option<String>;
+}
+
+/// Run `fut` with events it emits attributed to `turn_id`. Task-locals do not cross
+/// `tokio::spawn`, so spawned work needs wrapping too; the caller's user is carried along.
+pub fn in_turn<F: Future>(turn_id: Option<String>, fut: F) -> impl Future<Output = F::Output> {
+    CURRENT_USER.scope(current_user(), CURRENT_TURN.scope(turn_id, fut))
+}
+
+/// Turn of the task currently running, if any
+pub fn current_turn() -> Option<String> {
+    CURRENT_TURN.try_with(|turn| turn.clone()).ok().flatten()
+}
+
+/// Run `fut` on behalf of an authenticated `user`
+pub async fn as_user<F: Future>(user: Option<String>, fut: F) -> F::Output {
+    CURRENT_USER.scope(user, fut).await
+}
+
+/// Authenticated user the current task works for, if any
+pub fn current_user() -> Option<String> {
+    CURRENT_USER.try_with(|user| user.clone()).ok().flatten()
+}
+
 pub struct EventBus {
     tx: broadcast::Sender<AgencyEvent>,
+    records: broadcast::Sender<EventRecord>,
+    /// Append-only JSONL log; `None` keeps events in memory only
+    log: Option<PathBuf>,
+    /// Guards the log file and sequence numbers so lines are written in order
+    seq: std::sync::Mutex<u64>,
 }
 
 impl EventBus {
     pub fn new() -> Self {
+        Self::with_log(None)
+    }
+
+    /// Bus that also appends every event to `log`, continuing its sequence numbers
+    pub fn with_log(log: Option<PathBuf>) -> Self {
         let (tx, _) = broadcast::channel(1024);
-        Self { tx }
+        let (records, _) = broadcast::channel(1024);
+        let last_seq = log.as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| content.lines().rev().find_map(|line| serde_json::from_str::<EventRecord>(line).ok()))
+            .map(|record| record.seq)
+            .unwrap_or(0);
+        Self { tx, records, log, seq: std::sync::Mutex::new(last_seq) }
+    }
+
+    /// `AGENCY_EVENT_LOG` (default `agency_events.jsonl`; `off` disables)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_EVENT_LOG").unwrap_or_else(|_| "agency_events.jsonl".to_string());
+        Self::with_log((path != "off" && !path.is_empty()).then(|| PathBuf::from(path)))
     }
 
     /// Publish an event to all subscribers
     pub fn publish(&self, event: AgencyEvent) {
+        let record = {
+            let mut seq = self.seq.lock().unwrap_or_else(|e| e.into_inner());
+            *seq += 1;
+            let record = EventRecord { seq: *seq, timestamp: Utc::now(), turn_id: current_turn(), user: current_user(), event: event.clone() };
+            if let Some(ref path) = self.log {
+                if let Err(e) = Self::append(path, &record) {
+                    tracing::warn!("Failed to log event: {}", e);
+                }
+            }
+            record
+        };
+        let _ = self.records.send(record);
         let _ = self.tx.send(event);
     }
 
+    fn append(path: &PathBuf, record: &EventRecord) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        std::fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())
+    }
+
     /// Create a new subscriber
     pub fn subscribe(&self) -> broadcast::Receiver<AgencyEvent> {
         self.tx.subscribe()
     }
+
+    /// Subscribe to events with their sequence numbers, to follow on from a replay
+    pub fn subscribe_records(&self) -> broadcast::Receiver<EventRecord> {
+        self.records.subscribe()
+    }
+
+    /// Sequence number of the last published event
+    pub fn last_seq(&self) -> u64 {
+        *self.seq.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Logged events matching `query`, oldest first, skipping malformed lines
+    pub async fn replay(&self, query: &ReplayQuery) -> Vec<EventRecord> {
+        let Some(ref path) = self.log else { return Vec::new() };
+        let content = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        let mut records: Vec<EventRecord> = content.lines()
+            .filter_map(|line| serde_json::from_str::<EventRecord>(line).ok())
+            .filter(|r| query.since.is_none_or(|since| r.seq > since))
+            .filter(|r| query.turn.as_ref().is_none_or(|turn| r.turn_id.as_ref() == Some(turn)))
+            .filter(|r| r.visible_to(query.user.as_deref()))
+            .collect();
+        if let Some(limit) = query.limit {
+            records.drain(..records.len().saturating_sub(limit));
+        }
+        records
+    }
+
+    /// The most recent turn in the log
+    pub async fn latest_turn(&self) -> Option<String> {
+        let Some(ref path) = self.log else { return None };
+        let content = tokio::fs::read_to_string(path).await.ok()?;
+        content.lines().rev()
+            .filter_map(|line| serde_json::from_str::<EventRecord>(line).ok())
+            .find_map(|r| r.turn_id)
+    }
 }
 
 lazy_static::lazy_static! {
     /// Global singleton instance of the EventBus
-    pub static ref AGENCY_EVENT_BUS: Arc<EventBus> = Arc::new(EventBus::new());
+    pub static ref AGENCY_EVENT_BUS: Arc<EventBus> = Arc::new(EventBus::from_env());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_log_replays_by_turn_and_resumes_sequence() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+
+        let bus = EventBus::with_log(Some(path.clone()));
+        bus.publish(AgencyEvent::StatusUpdate("boot".into()));
+        in_turn(Some("t1".into()), async {
+            bus.publish(AgencyEvent::ToolCallStarted { tool: "web_search".into() });
+            bus.publish(AgencyEvent::ToolCallFinished { tool: "web_search".into(), success: true });
+        }).await;
+
+        let turn = bus.replay(&ReplayQuery { turn: Some("t1".into()), ..Default::default() }).await;
+        assert_eq!(turn.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(bus.latest_turn().await.as_deref(), Some("t1"));
+
+        // A new process continues the log's numbering
+        let reopened = EventBus::with_log(Some(path));
+        reopened.publish(AgencyEvent::StatusUpdate("again".into()));
+        let missed = reopened.replay(&ReplayQuery { since: Some(2), ..Default::default() }).await;
+        assert_eq!(missed.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![3, 4]);
+        let newest = reopened.replay(&ReplayQuery { limit: Some(1), ..Default::default() }).await;
+        assert_eq!(newest[0].seq, 4);
+    }
+
+    #[tokio::test]
+    async fn test_replay_scoped_to_user() {
+        let dir = tempdir().unwrap();
+        let bus = EventBus::with_log(Some(dir.path().join("events.jsonl")));
+        as_user(Some("alice".into()), async { bus.publish(AgencyEvent::StatusUpdate("alice's".into())) }).await;
+        as_user(Some("bob".into()), async { bus.publish(AgencyEvent::StatusUpdate("bob's".into())) }).await;
+        bus.publish(AgencyEvent::StatusUpdate("system".into()));
+
+        let alice = bus.replay(&ReplayQuery { user: Some("alice".into()), limit: Some(5), ..Default::default() }).await;
+        assert_eq!(alice.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(bus.replay(&ReplayQuery::default()).await.len(), 3);
+        // The scope cannot come from the query string
+        let forged: ReplayQuery = serde_json::from_value(serde_json::json!({ "user": "bob" })).unwrap();
+        assert!(forged.user.is_none());
+    }
 }
 
 /// Helper macro to publish events globally
@@ -0,0 +1,174 @@
+//! Emergency Kill Switch
+//!
+//! `Supervisor::panic_stop` engages the switch: every task spawned through `KILL_SWITCH.spawn`
+//! is aborted, turns running under `cancellable` return at their next await, and new turns,
+//! tool calls and queued work are refused until someone calls `rearm`. The engaged state is
+//! written to disk so a restart does not silently resume work.
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::watch;
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::agent::{AgentError, AgentResult};
+
+lazy_static! {
+    /// Process-wide kill switch checked by turns, ReAct loops, tools and the task worker
+    pub static ref KILL_SWITCH: KillSwitch = KillSwitch::from_env();
+}
+
+/// Why and when the switch was engaged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Halt {
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+pub struct KillSwitch {
+    state: watch::Sender<Option<Halt>>,
+    tasks: Mutex<HashMap<u64, AbortHandle>>,
+    next_id: AtomicU64,
+    /// Persisted halt; `None` keeps it in memory only
+    path: Option<PathBuf>,
+}
+
+impl KillSwitch {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let halt = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str::<Halt>(&content).ok());
+        if let Some(ref halt) = halt {
+            tracing::warn!("Kill switch engaged since {} ({}); re-arm to resume work", halt.at, halt.reason);
+        }
+        Self { state: watch::channel(halt).0, tasks: Mutex::new(HashMap::new()), next_id: AtomicU64::new(0), path }
+    }
+
+    /// `AGENCY_KILL_SWITCH_FILE` (default `agency_halt.json`; `off` keeps the state in memory)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_KILL_SWITCH_FILE").unwrap_or_else(|_| "agency_halt.json".to_string());
+        Self::new((path != "off" && !path.is_empty()).then(|| PathBuf::from(path)))
+    }
+
+    pub fn halt(&self) -> Option<Halt> {
+        self.state.borrow().clone()
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.state.borrow().is_some()
+    }
+
+    /// Refuse work while engaged
+    pub fn check(&self) -> AgentResult<()> {
+        match self.halt() {
+            Some(halt) => Err(AgentError::Execution(format!("Agency halted by kill switch ({}); re-arm to continue", halt.reason))),
+            None => Ok(()),
+        }
+    }
+
+    /// Engage the switch and abort every tracked task; returns how many were still running.
+    /// Engaging again keeps the first reason but still aborts tasks spawned since.
+    pub fn engage(&self, reason: impl Into<String>) -> usize {
+        let halt = Halt { reason: reason.into(), at: Utc::now() };
+        let newly_engaged = self.state.send_if_modified(|state| {
+            if state.is_none() {
+                *state = Some(halt.clone());
+                return true;
+            }
+            false
+        });
+        if newly_engaged {
+            tracing::error!("KILL SWITCH ENGAGED: {}", halt.reason);
+            if let Some(ref path) = self.path {
+                if let Err(e) = serde_json::to_string_pretty(&halt).map_err(std::io::Error::from).and_then(|json| std::fs::write(path, json)) {
+                    tracing::error!("Failed to persist kill switch state: {}", e);
+                }
+            }
+        }
+
+        let tasks: Vec<AbortHandle> = self.tasks.lock().unwrap_or_else(|e| e.into_inner()).drain().map(|(_, h)| h).collect();
+        let running = tasks.iter().filter(|h| !h.is_finished()).count();
+        tasks.iter().for_each(AbortHandle::abort);
+        running
+    }
+
+    /// Clear the halt so work can start again
+    pub fn rearm(&self) {
+        if let Some(ref path) = self.path {
+            let _ = std::fs::remove_file(path);
+        }
+        if self.state.send_replace(None).is_some() {
+            tracing::warn!("Kill switch re-armed; the agency may run again");
+        }
+    }
+
+    /// `tokio::spawn` a task that `engage` aborts
+    pub fn spawn<F>(&'static self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let handle = tokio::spawn(async move {
+            let output = future.await;
+            self.tasks.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+            output
+        });
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if self.is_engaged() {
+            handle.abort();
+        } else if !handle.is_finished() {
+            tasks.insert(id, handle.abort_handle());
+        }
+        handle
+    }
+
+    /// Run `future` until it finishes or the switch is engaged
+    pub async fn cancellable<T>(&self, future: impl Future<Output = AgentResult<T>>) -> AgentResult<T> {
+        self.check()?;
+        let mut state = self.state.subscribe();
+        tokio::select! {
+            result = future => result,
+            _ = state.wait_for(Option::is_some) => Err(self.check().err().unwrap_or_else(|| AgentError::Execution("Agency halted by kill switch".to_string()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_engage_aborts_tasks_and_persists_until_rearmed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("halt.json");
+        let switch: &'static KillSwitch = Box::leak(Box::new(KillSwitch::new(Some(path.clone()))));
+
+        let task = switch.spawn(std::future::pending::<()>());
+        let turn = switch.cancellable(async {
+            std::future::pending::<()>().await;
+            Ok(())
+        });
+        let engage = async {
+            tokio::task::yield_now().await;
+            switch.engage("operator panic")
+        };
+        let (result, aborted) = tokio::join!(turn, engage);
+        assert!(result.unwrap_err().to_string().contains("operator panic"));
+        assert_eq!(aborted, 1);
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        // New work is refused, even after a restart, until re-armed
+        assert!(switch.check().is_err());
+        assert!(KillSwitch::new(Some(path.clone())).is_engaged());
+        switch.rearm();
+        assert!(switch.check().is_ok());
+        assert!(!KillSwitch::new(Some(path)).is_engaged());
+    }
+}
@@ -0,0 +1,219 @@
+//! Agency Mesh
+//!
+//! Registry of peer rust_agency nodes (e.g. a GPU box on the LAN), the capabilities each
+//! advertises in its agent card, and the policy deciding which sub-tasks are delegated.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::agent::{AgentError, AgentResponse, AgentResult, AgentType};
+use crate::orchestrator::a2a::{skill_id, A2AClient, A2ATaskState, AgentCard};
+
+/// Longest wait for a delegated task
+const DELEGATION_TIMEOUT_SECS: u64 = 600;
+
+/// One remote node and what it last advertised
+#[derive(Debug, Clone, Serialize)]
+pub struct MeshPeer {
+    pub url: String,
+    pub card: Option<AgentCard>,
+    pub healthy: bool,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl MeshPeer {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), card: None, healthy: false, last_seen: None }
+    }
+
+    pub fn name(&self) -> &str {
+        self.card.as_ref().map(|c| c.name.as_str()).unwrap_or(&self.url)
+    }
+
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.card.as_ref().is_some_and(|c| c.has_capability(capability))
+    }
+}
+
+/// Send queries matching `keywords` (or routed to one of `agents`) to a peer with `capability`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationRule {
+    pub capability: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub agents: Vec<AgentType>,
+}
+
+impl DelegationRule {
+    pub fn matches(&self, query: &str, agent: AgentType) -> bool {
+        let query = query.to_lowercase();
+        self.agents.contains(&agent) || self.keywords.iter().any(|k| query.contains(&k.to_lowercase()))
+    }
+}
+
+/// Rules are only applied for capabilities this node lacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationPolicy {
+    pub rules: Vec<DelegationRule>,
+}
+
+impl Default for DelegationPolicy {
+    fn default() -> Self {
+        let keywords = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Self {
+            rules: vec![
+                DelegationRule { capability: "vision".into(), keywords: keywords(&["image", "photo", "screenshot", "picture", "ocr"]), agents: Vec::new() },
+                DelegationRule { capability: "gpu".into(), keywords: keywords(&["fine-tune", "finetune", "train a model", "transcribe"]), agents: Vec::new() },
+            ],
+        }
+    }
+}
+
+impl DelegationPolicy {
+    /// Load from a JSON file, falling back to the defaults
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring invalid delegation policy {:?}: {}", path.as_ref(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// Known peers plus this node's own capabilities
+pub struct PeerRegistry {
+    peers: RwLock<Vec<MeshPeer>>,
+    local_capabilities: Vec<String>,
+    policy: DelegationPolicy,
+    client: A2AClient,
+}
+
+impl PeerRegistry {
+    pub fn new(local_capabilities: Vec<String>, policy: DelegationPolicy) -> Self {
+        Self { peers: RwLock::new(Vec::new()), local_capabilities, policy, client: A2AClient::new() }
+    }
+
+    /// `AGENCY_PEERS` (comma-separated base URLs), `AGENCY_NODE_CAPABILITIES` and
+    /// `AGENCY_DELEGATION_POLICY` (default `config/delegation.json`). `None` without peers.
+    pub async fn from_env() -> Option<Self> {
+        let urls: Vec<String> = std::env::var("AGENCY_PEERS").ok()?
+            .split(',')
+            .map(|u| u.trim().trim_end_matches('/').to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+        if urls.is_empty() {
+            return None;
+        }
+        let policy_path = std::env::var("AGENCY_DELEGATION_POLICY").unwrap_or_else(|_| "config/delegation.json".to_string());
+        let registry = Self::new(crate::orchestrator::a2a::node_capabilities_from_env(), DelegationPolicy::load(policy_path));
+        for url in urls {
+            registry.add_peer(url).await;
+        }
+        Some(registry)
+    }
+
+    pub async fn add_peer(&self, url: impl Into<String>) {
+        let url = url.into();
+        let mut peers = self.peers.write().await;
+        if !peers.iter().any(|p| p.url == url) {
+            peers.push(MeshPeer::new(url));
+        }
+    }
+
+    pub async fn peers(&self) -> Vec<MeshPeer> {
+        self.peers.read().await.clone()
+    }
+
+    /// Re-fetch every peer's agent card; peers without one are marked unhealthy
+    pub async fn refresh(&self) {
+        let urls: Vec<String> = self.peers.read().await.iter().map(|p| p.url.clone()).collect();
+        for url in urls {
+            let card = self.client.fetch_card(&url).await;
+            let mut peers = self.peers.write().await;
+            if let Some(peer) = peers.iter_mut().find(|p| p.url == url) {
+                peer.healthy = card.is_some();
+                if card.is_some() {
+                    peer.card = card;
+                    peer.last_seen = Some(Utc::now());
+                }
+            }
+        }
+    }
+
+    /// Refresh peers on an interval for the lifetime of the process
+    pub fn spawn_refresh(self: std::sync::Arc<Self>, every: Duration) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                self.refresh().await;
+            }
+        });
+    }
+
+    /// Healthy peer that should take this query, if any rule applies that this node cannot serve
+    pub async fn delegation_target(&self, query: &str, agent: AgentType) -> Option<MeshPeer> {
+        let peers = self.peers.read().await;
+        self.policy.rules.iter()
+            .filter(|rule| rule.matches(query, agent))
+            .filter(|rule| !self.local_capabilities.iter().any(|c| c.eq_ignore_ascii_case(&rule.capability)))
+            .find_map(|rule| peers.iter().find(|p| p.healthy && p.has_capability(&rule.capability)).cloned())
+    }
+
+    /// Run `query` on `peer` as `agent`; a peer that fails to answer is marked unhealthy
+    pub async fn delegate(&self, peer: &MeshPeer, agent: AgentType, query: &str) -> AgentResult<AgentResponse> {
+        let card = peer.card.as_ref().ok_or_else(|| AgentError::Execution(format!("Peer {} has no agent card", peer.url)))?;
+        info!("Mesh: delegating to '{}' ({})", card.name, peer.url);
+
+        let task = match self.client.run_task(&peer.url, card, skill_id(agent), query, Duration::from_secs(DELEGATION_TIMEOUT_SECS)).await {
+            Ok(task) => task,
+            Err(e) => {
+                if let Some(p) = self.peers.write().await.iter_mut().find(|p| p.url == peer.url) {
+                    p.healthy = false;
+                }
+                return Err(e);
+            }
+        };
+        match task.status.state {
+            A2ATaskState::Completed => Ok(AgentResponse::success(task.answer(), Vec::new(), agent)),
+            state => {
+                let reason = task.status.message.map(|m| m.text_content()).unwrap_or_default();
+                Ok(AgentResponse::failure(format!("Peer '{}' ended {:?}: {}", card.name, state, reason), Vec::new(), agent))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::profile::AgencyProfile;
+
+    #[tokio::test]
+    async fn test_delegates_only_missing_capabilities() {
+        let registry = PeerRegistry::new(vec!["gpu".to_string()], DelegationPolicy::default());
+        registry.add_peer("http://gpu-box:8002").await;
+        {
+            let mut peers = registry.peers.write().await;
+            peers[0].card = Some(AgentCard::new(&AgencyProfile::default(), "http://gpu-box:8002")
+                .with_node_capabilities(vec!["gpu".into(), "vision".into()]));
+            peers[0].healthy = true;
+        }
+
+        let peer = registry.delegation_target("Describe this screenshot", AgentType::GeneralChat).await;
+        assert_eq!(peer.map(|p| p.url), Some("http://gpu-box:8002".to_string()));
+        // This node has a GPU itself, so GPU-only work stays local
+        assert!(registry.delegation_target("Transcribe the meeting audio", AgentType::GeneralChat).await.is_none());
+        assert!(registry.delegation_target("What is 2 + 2?", AgentType::GeneralChat).await.is_none());
+
+        registry.peers.write().await[0].healthy = false;
+        assert!(registry.delegation_target("Describe this screenshot", AgentType::GeneralChat).await.is_none());
+    }
+}
@@ -4,9 +4,19 @@
 //! planning, routing, and supervision.
 
 pub mod supervisor;
+pub mod turn_events;
+pub mod turn_history;
+pub mod checkpoint;
+pub mod subagents;
 pub mod planner;
+pub mod plan_review;
+pub mod plan_executor;
+pub mod team;
 pub mod router;
+pub mod route_learning;
+pub mod routing;
 pub mod session;
+pub mod conversations;
 pub mod profile;
 pub mod sns;
 pub mod drr;
@@ -17,6 +27,7 @@ pub mod mht;
 pub mod governance;
 pub mod scale;
 pub mod budget;
+pub mod escalation;
 pub mod mvpk;
 pub mod bridge;
 pub mod service;
@@ -33,9 +44,12 @@ pub mod optimal_info;
 pub mod crystallizer;
 pub mod curiosity;
 pub mod event_bus;
+pub mod kill_switch;
 
 pub use scheduler::AgencyScheduler;
 pub mod a2a;
+pub mod mesh;
+pub mod webhooks;
 pub mod arti_a2a;
 pub mod uap_grpc;
 pub mod queue;
@@ -50,10 +64,19 @@ pub mod vault;
 
 pub use crate::agent::speaker_rs::Speaker;
 pub use supervisor::{Supervisor, SupervisorResult};
+pub use turn_events::{AnswerSplitter, StreamChunk, TurnEvent};
+pub use checkpoint::TurnCheckpoint;
+pub use kill_switch::{Halt, KillSwitch, KILL_SWITCH};
+pub use subagents::{SpawnLimits, SubAgentRecord, SubAgentStatus, SubAgentTracker, SUBAGENTS};
 pub use planner::{Planner, Plan, PlanStep};
+pub use plan_executor::PlanExecutor;
+pub use team::{HandOff, HandOffKind, Team};
 pub use optimal_info::OptimalInfoSelector;
 pub use router::{Router, RoutingDecision};
+pub use route_learning::{RouteLearner, RouteRecord, ROUTE_LEARNER};
+pub use routing::{ModelRoute, RoutingMatrix, RoutingTable, RoutedProvider, ROUTING};
 pub use session::{SessionManager, SessionState};
+pub use conversations::ConversationRegistry;
 pub use drr::DesignRationaleRecord;
 pub use objective::{Objective, ResourceBudget};
 pub use alignment::{MethodDescription, MethodStep, WorkRecord, AssuranceLevel};
@@ -61,16 +84,17 @@ pub use role_algebra::RoleAlgebra;
 pub use mht::{MHTEngine, MHTEvent};
 pub use governance::{NormSquare, AdmissibilityGate, GateStatus, DeonticRule, DeonticModality, AdjudicationResult, AdjudicationVerdict};
 pub use scale::{ScaleClass, ScaleProfile};
-pub use budget::{AutonomyLedger, BudgetStatus};
+pub use escalation::EscalationPolicy;
+pub use budget::{AutonomyLedger, BudgetLimits, BudgetManager, BudgetMark, BudgetScope, BudgetStatus, BudgetUsage, UsageMeter};
 pub use mvpk::Publication;
 pub use bridge::Bridge;
 pub use service::{ServiceClause, ServiceStatus};
 pub use commitment::{Commitment, Modality, CommitmentStatus};
-pub use aggregation::{Gamma, ResultPortfolio};
+pub use aggregation::{AggregationStrategy, Gamma, ResultPortfolio, Selection};
 pub use provenance::EvidenceGraph;
 pub use cn_frame::CNFrame;
 pub use kind::{Kind, KindAlgebra};
 pub use evolution::{EvolutionEvent, EvolutionEngine};
 pub use debt::{HeuristicDebt, DebtRegistry};
-pub use event_bus::{AGENCY_EVENT_BUS, AgencyEvent};
+pub use event_bus::{AGENCY_EVENT_BUS, AgencyEvent, EventRecord, ReplayQuery};
 pub mod pai;
@@ -16,6 +16,15 @@ pub struct Publication {
     pub debt_register: Option<DebtRegistry>,
     /// HITL State: Present if awaiting human approval
     pub pending_approval: Option<crate::safety::ApprovalRequest>,
+    /// Which candidate won and why
+    #[serde(default)]
+    pub aggregation: Option<crate::orchestrator::aggregation::Selection>,
+    /// PII scrubbed from tool outputs during the turn
+    #[serde(default)]
+    pub redactions: Option<crate::safety::RedactionReport>,
+    /// Output moderation categories the answer was flagged for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moderation: Vec<crate::safety::ModerationCategory>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +36,9 @@ pub struct Telemetry {
     pub scale: ScaleClass,
     pub model: String,
     pub elasticity: ScaleElasticity,
+    /// Token usage and spend for the turn
+    #[serde(default)]
+    pub usage: Option<crate::agent::UsageSummary>,
 }
 
 impl Publication {
@@ -60,11 +72,15 @@ impl Publication {
                 scale: scale_profile.class,
                 model: scale_profile.target_model,
                 elasticity: scale_profile.elasticity,
+                usage: None,
             },
             rationale,
             governance: square,
             debt_register,
             pending_approval: None,
+            aggregation: None,
+            redactions: None,
+            moderation: Vec::new(),
         }
     }
 
@@ -74,11 +90,31 @@ impl Publication {
         self
     }
 
+    pub fn with_usage(mut self, usage: crate::agent::UsageSummary) -> Self {
+        self.telemetry.usage = Some(usage);
+        self
+    }
+
     pub fn with_approval(mut self, approval: Option<crate::safety::ApprovalRequest>) -> Self {
         self.pending_approval = approval;
         self
     }
 
+    pub fn with_aggregation(mut self, selection: Option<crate::orchestrator::aggregation::Selection>) -> Self {
+        self.aggregation = selection;
+        self
+    }
+
+    pub fn with_redactions(mut self, report: Option<crate::safety::RedactionReport>) -> Self {
+        self.redactions = report.filter(|r| !r.is_empty());
+        self
+    }
+
+    pub fn with_moderation(mut self, flagged: Vec<crate::safety::ModerationCategory>) -> Self {
+        self.moderation = flagged;
+        self
+    }
+
     pub fn format_full_audit(&self) -> String {
         let mut out = format!("✅ FINAL ANSWER (PlainView):\n{}\n\n", self.answer);
         
@@ -92,6 +128,19 @@ impl Publication {
         out.push_str(&format!("  - Evidence: {}\n", self.telemetry.evidence_count));
         out.push_str(&format!("  - Scale: {:?} (Elasticity: {:?})\n", self.telemetry.scale, self.telemetry.elasticity));
         out.push_str(&format!("  - Model: {}\n", self.telemetry.model));
+        if let Some(ref usage) = self.telemetry.usage {
+            out.push_str(&format!("  - Tokens: {} in / {} out (${:.4})\n", usage.input_tokens, usage.output_tokens, usage.cost_usd));
+        }
+        if let Some(ref selection) = self.aggregation {
+            out.push_str(&format!("  - Aggregation: {} ({})\n", selection.strategy, selection.rationale));
+        }
+        if let Some(ref redactions) = self.redactions {
+            out.push_str(&format!("  - Privacy: {}\n", redactions.summary()));
+        }
+        if !self.moderation.is_empty() {
+            let keys: Vec<&str> = self.moderation.iter().map(|c| c.key()).collect();
+            out.push_str(&format!("  - Moderation: flagged {}\n", keys.join(", ")));
+        }
         out.push_str(&format!("  - Reliability (R): {:.2}\n\n", self.reliability));
         
         if let Some(ref drr) = self.rationale {
@@ -19,7 +19,7 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use crate::agent::LLMProvider;
+use crate::agent::{GenerationOptions, JsonSchemaFormat, LLMProvider};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataQuery {
@@ -81,7 +81,9 @@ Output JSON format:
         );
 
         let system = Some("You are an expert in Optimal Experiment Design and Decision Theory.".to_string());
-        let response = self.provider.generate(&self.model, prompt, system).await?;
+        // Providers with structured output return `{"assumptions": [...]}`; `parse_needs` reads the array either way
+        let options = GenerationOptions { json_schema: Some(Self::needs_schema()), ..Default::default() };
+        let response = self.provider.generate_with_options(&self.model, prompt, system, &options).await?;
 
         // Parse relevant needs
         let needs: Vec<InformationNeed> = self.parse_needs(&response);
@@ -103,6 +105,28 @@ Output JSON format:
         Ok(selected_queries)
     }
 
+    /// Strict JSON schema for a list of `InformationNeed`s
+    fn needs_schema() -> JsonSchemaFormat {
+        let object = |properties: serde_json::Value| {
+            let required: Vec<String> = properties.as_object().map(|p| p.keys().cloned().collect()).unwrap_or_default();
+            serde_json::json!({ "type": "object", "properties": properties, "required": required, "additionalProperties": false })
+        };
+        let query = object(serde_json::json!({
+            "description": { "type": "string" },
+            "tool_call": { "type": "string" },
+            "cost_estimate": { "type": "integer" },
+        }));
+        let need = object(serde_json::json!({
+            "assumption": { "type": "string" },
+            "relevance_score": { "type": "number" },
+            "queries": { "type": "array", "items": query },
+        }));
+        JsonSchemaFormat {
+            name: "information_needs".to_string(),
+            schema: object(serde_json::json!({ "assumptions": { "type": "array", "items": need } })),
+        }
+    }
+
     fn parse_needs(&self, response: &str) -> Vec<InformationNeed> {
         // Attempt to parse JSON from the response
         if let Some(start) = response.find('[') {
@@ -0,0 +1,191 @@
+//! Plan Executor
+//!
+//! Runs a `Plan` as a DAG: every step whose dependencies are complete is started at once
+//! (bounded by the supervisor's `concurrency_limit`), and each finished step's output is
+//! handed to the steps that depend on it.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::agent::{AgentError, AgentResponse, AgentResult, AgentType};
+use crate::orchestrator::{Plan, PlanStep, SessionManager};
+
+pub struct PlanExecutor {
+    concurrency_limit: Arc<Semaphore>,
+    /// Checkpoints the plan after every completed step so it can be resumed
+    session: Option<SessionManager>,
+}
+
+impl PlanExecutor {
+    pub fn new(concurrency_limit: Arc<Semaphore>) -> Self {
+        Self { concurrency_limit, session: None }
+    }
+
+    pub fn with_session(mut self, session: SessionManager) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    async fn checkpoint(&self, plan: Option<&Plan>) {
+        if let Some(ref session) = self.session {
+            if let Err(e) = session.save_active_plan(plan).await {
+                warn!("PlanExecutor: failed to checkpoint plan: {}", e);
+            }
+        }
+    }
+
+    /// Outputs of `step`'s dependencies, passed to it as context
+    pub fn dependency_context(plan: &Plan, step: &PlanStep) -> String {
+        plan.steps.iter()
+            .filter(|s| step.depends_on.contains(&s.step_num))
+            .filter_map(|s| s.output.as_ref().map(|out| format!("Output of step {} ({}):\n{}", s.step_num, s.description, out)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Final answer: outputs of the steps nothing else depends on, in step order
+    pub fn merge_outputs(plan: &Plan) -> String {
+        let sinks: Vec<&PlanStep> = plan.steps.iter()
+            .filter(|s| !plan.steps.iter().any(|other| other.depends_on.contains(&s.step_num)))
+            .collect();
+        match sinks.as_slice() {
+            [only] => only.output.clone().unwrap_or_default(),
+            _ => sinks.iter()
+                .map(|s| format!("## {}\n{}", s.description, s.output.as_deref().unwrap_or("")))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        }
+    }
+
+    /// Run every incomplete step with `run_step(step, dependency_context)`. Steps completed
+    /// before the call are kept. Stops at the first step that fails or needs approval and
+    /// returns that step's response; otherwise returns the merged outputs.
+    pub async fn execute<F, Fut>(&self, plan: &mut Plan, run_step: F) -> AgentResult<AgentResponse>
+    where
+        F: Fn(PlanStep, String) -> Fut + Send,
+        Fut: Future<Output = AgentResult<AgentResponse>> + Send + 'static,
+    {
+        if plan.steps.is_empty() {
+            return Err(AgentError::Validation("Plan has no steps".to_string()));
+        }
+        self.checkpoint(Some(plan)).await;
+        let mut running: JoinSet<(usize, AgentResult<AgentResponse>)> = JoinSet::new();
+        let mut started: HashSet<usize> = HashSet::new();
+        let mut trace = Vec::new();
+
+        loop {
+            let ready: Vec<PlanStep> = plan.ready_steps().into_iter()
+                .filter(|s| !started.contains(&s.step_num))
+                .cloned()
+                .collect();
+            for step in ready {
+                let context = Self::dependency_context(plan, &step);
+                let semaphore = self.concurrency_limit.clone();
+                let step_num = step.step_num;
+                info!("PlanExecutor: starting step {} ({})", step_num, step.description);
+                started.insert(step_num);
+                let task = run_step(step, context);
+                running.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await.ok();
+                    (step_num, task.await)
+                });
+            }
+
+            let Some(joined) = running.join_next().await else { break };
+            let (step_num, result) = joined.map_err(|e| AgentError::Execution(format!("Plan step panicked: {}", e)))?;
+            match result {
+                Ok(response) if response.success => {
+                    trace.extend(response.steps);
+                    plan.complete_step(step_num, response.answer);
+                    self.checkpoint(Some(plan)).await;
+                }
+                Ok(response) => {
+                    warn!("PlanExecutor: step {} did not complete", step_num);
+                    return Ok(response);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if !plan.is_complete {
+            let stuck: Vec<String> = plan.steps.iter().filter(|s| !s.completed).map(|s| s.step_num.to_string()).collect();
+            // Resuming would hit the same dead end
+            self.checkpoint(None).await;
+            return Err(AgentError::Validation(format!("Plan steps {} have unsatisfiable dependencies", stuck.join(", "))));
+        }
+        self.checkpoint(None).await;
+        Ok(AgentResponse::success(Self::merge_outputs(plan), trace, AgentType::Planner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn step(step_num: usize, depends_on: Vec<usize>) -> PlanStep {
+        PlanStep {
+            step_num,
+            description: format!("Step {}", step_num),
+            agent_type: AgentType::Reasoner,
+            suggested_tools: vec![],
+            expected_output: String::new(),
+            depends_on,
+            completed: false,
+            output: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_independent_steps_concurrently() {
+        // 1 and 2 are independent; 3 needs both
+        let mut plan = Plan::new("goal");
+        plan.steps = vec![step(1, vec![]), step(2, vec![]), step(3, vec![1, 2])];
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let executor = PlanExecutor::new(Arc::new(Semaphore::new(4)));
+        let result = executor.execute(&mut plan, |step, context| {
+            let (active, peak) = (active.clone(), peak.clone());
+            async move {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                let answer = if context.is_empty() { format!("out{}", step.step_num) } else { format!("merged[{}]", context.len()) };
+                Ok(AgentResponse::success(answer, vec![], step.agent_type))
+            }
+        }).await.unwrap();
+
+        assert!(result.success);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert!(plan.is_complete);
+        assert_eq!(result.answer, plan.steps[2].output.clone().unwrap());
+        assert!(PlanExecutor::dependency_context(&plan, &plan.steps[2]).contains("out2"));
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_failure_and_detects_cycles() {
+        let executor = PlanExecutor::new(Arc::new(Semaphore::new(1)));
+
+        let mut plan = Plan::new("goal");
+        plan.steps = vec![step(1, vec![]), step(2, vec![1])];
+        let result = executor.execute(&mut plan, |step, _| async move {
+            Ok(AgentResponse::failure("boom", vec![], step.agent_type))
+        }).await.unwrap();
+        assert!(!result.success);
+        assert!(!plan.steps[1].completed);
+
+        let mut cyclic = Plan::new("goal");
+        cyclic.steps = vec![step(1, vec![2]), step(2, vec![1])];
+        let err = executor.execute(&mut cyclic, |step, _| async move {
+            Ok(AgentResponse::success("ok", vec![], step.agent_type))
+        }).await;
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Plan Review
+//!
+//! Before a fresh plan runs, a Reviewer checks it against the goal: missing steps, tools
+//! that do not exist or do not fit, and steps ordered before the work they depend on. A plan
+//! the Reviewer rejects goes back to the Planner through `Planner::refine` with the critique
+//! as feedback. Turned off with `plan_critique: false` in the agency profile.
+
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::agent::LLMProvider;
+use crate::orchestrator::Plan;
+
+/// The Reviewer's verdict on a plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanCritique {
+    pub approved: bool,
+    pub issues: Vec<String>,
+}
+
+impl PlanCritique {
+    /// Parse `VERDICT: APPROVE|REVISE` and `ISSUE:` lines; a reply without a verdict
+    /// approves unless it lists issues
+    pub fn parse(response: &str) -> Self {
+        let mut verdict = None;
+        let mut issues = Vec::new();
+        for line in response.lines().map(str::trim) {
+            let upper = line.to_uppercase();
+            if let Some(rest) = upper.strip_prefix("VERDICT:") {
+                verdict = Some(!rest.trim().starts_with("REVISE"));
+            } else if upper.starts_with("ISSUE:") {
+                let issue = line.get("ISSUE:".len()..).unwrap_or("").trim();
+                if !issue.is_empty() && !issue.eq_ignore_ascii_case("none") {
+                    issues.push(issue.to_string());
+                }
+            }
+        }
+        Self { approved: verdict.unwrap_or(issues.is_empty()), issues }
+    }
+
+    /// Feedback text for `Planner::refine`
+    pub fn feedback(&self) -> String {
+        format!("A reviewer rejected the plan before execution:\n- {}", self.issues.join("\n- "))
+    }
+}
+
+pub struct PlanReviewer {
+    provider: Arc<dyn LLMProvider>,
+    model: String,
+    system_prompt: Option<String>,
+}
+
+impl PlanReviewer {
+    pub fn new(provider: Arc<dyn LLMProvider>, model: impl Into<String>) -> Self {
+        Self { provider, model: model.into(), system_prompt: None }
+    }
+
+    /// The Reviewer agent's persona
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    pub async fn critique(&self, plan: &Plan, available_tools: &[String]) -> Result<PlanCritique> {
+        let prompt = format!(
+            r#"Review this plan before it is executed.
+
+Goal: {}
+
+Plan:
+{}
+
+Available tools: {}
+
+Check for:
+1. Missing steps: the goal cannot be reached with these steps alone
+2. Wrong tools: a step suggests a tool that is not available or does not fit the step
+3. Ordering issues: a step uses output from a step it does not depend on, or comes before it
+
+Reply with one line per problem and a verdict:
+ISSUE: [problem and the fix]
+VERDICT: APPROVE or REVISE"#,
+            plan.goal,
+            describe(plan),
+            if available_tools.is_empty() { "unknown".to_string() } else { available_tools.join(", ") }
+        );
+        let response = self.provider.generate(&self.model, prompt, self.system_prompt.clone()).await?;
+        Ok(PlanCritique::parse(&response))
+    }
+}
+
+/// Steps with the tools, dependencies and outputs the critique needs
+fn describe(plan: &Plan) -> String {
+    plan.steps.iter()
+        .map(|s| {
+            let tools = if s.suggested_tools.is_empty() { "none".to_string() } else { s.suggested_tools.join(", ") };
+            let deps = if s.depends_on.is_empty() {
+                "none".to_string()
+            } else {
+                s.depends_on.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+            };
+            format!("{}. {} (agent: {}; tools: {}; depends on: {}; expected: {})", s.step_num, s.description, s.agent_type, tools, deps, s.expected_output)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_critique() {
+        let critique = PlanCritique::parse("ISSUE: step 2 uses web_fetch, which is not available\nIssue: none\nVERDICT: REVISE");
+        assert!(!critique.approved);
+        assert_eq!(critique.issues, vec!["step 2 uses web_fetch, which is not available".to_string()]);
+        assert!(critique.feedback().contains("web_fetch"));
+
+        assert!(PlanCritique::parse("VERDICT: APPROVE").approved);
+        assert!(!PlanCritique::parse("ISSUE: no test step").approved);
+    }
+}
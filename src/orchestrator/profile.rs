@@ -1,13 +1,62 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
 use tokio::fs;
 
+use crate::agent::AgentType;
+use crate::orchestrator::aggregation::AggregationStrategy;
+use crate::orchestrator::escalation::EscalationPolicy;
+use crate::orchestrator::ScaleClass;
+use crate::utils::sandbox::SandboxProfile;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AgencyProfile {
     pub name: String,
     pub mission: String,
     pub traits: Vec<String>,
+    /// How competing candidate answers are reduced to one
+    #[serde(default)]
+    pub aggregation: AggregationStrategy,
+    /// Limits on the Supervisor's escalation loop
+    #[serde(default)]
+    pub escalation: EscalationPolicy,
+    /// Have a Reviewer critique new plans before they run; off for latency-sensitive use
+    #[serde(default = "default_plan_critique")]
+    pub plan_critique: bool,
+    /// Confinement for `code_exec` and `sandbox`; calls may only tighten it
+    #[serde(default)]
+    pub sandbox_profile: SandboxProfile,
+    /// Model per agent type, replacing `AgentType::default_model`
+    #[serde(default)]
+    pub default_models: HashMap<AgentType, String>,
+    #[serde(default)]
+    pub verbosity: Verbosity,
+}
+
+/// How much detail agents put in their answers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Concise,
+    #[default]
+    Normal,
+    Detailed,
+}
+
+impl Verbosity {
+    /// Instruction added to system prompts; none for `Normal`
+    pub fn instruction(self) -> Option<&'static str> {
+        match self {
+            Verbosity::Concise => Some("Keep answers as short as possible: no preamble, no recap."),
+            Verbosity::Normal => None,
+            Verbosity::Detailed => Some("Give thorough answers that explain your reasoning and cite the evidence used."),
+        }
+    }
+}
+
+fn default_plan_critique() -> bool {
+    true
 }
 
 impl Default for AgencyProfile {
@@ -16,10 +65,59 @@ impl Default for AgencyProfile {
             name: "The Agency".to_string(),
             mission: "To assist the user through specialized multi-agent coordination.".to_string(),
             traits: vec!["efficient".to_string(), "technical".to_string(), "autonomous".to_string()],
+            aggregation: AggregationStrategy::default(),
+            escalation: EscalationPolicy::default(),
+            plan_critique: true,
+            sandbox_profile: SandboxProfile::default(),
+            default_models: HashMap::new(),
+            verbosity: Verbosity::default(),
         }
     }
 }
 
+impl AgencyProfile {
+    /// Every problem that would keep this profile from being applied
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.name.trim().is_empty() {
+            errors.push("name must not be empty".to_string());
+        }
+        if self.mission.trim().is_empty() {
+            errors.push("mission must not be empty".to_string());
+        }
+        if self.traits.iter().any(|t| t.trim().is_empty()) {
+            errors.push("traits must not be empty strings".to_string());
+        }
+        for (agent, model) in &self.default_models {
+            if model.trim().is_empty() {
+                errors.push(format!("default_models.{}: model must not be empty", serde_json::to_value(agent).unwrap_or_default().as_str().unwrap_or_default()));
+            }
+        }
+        let escalation = &self.escalation;
+        if escalation.max_attempts == 0 {
+            errors.push("escalation.max_attempts must be at least 1".to_string());
+        }
+        if escalation.max_cost_usd.is_some_and(|max| !max.is_finite() || max < 0.0) {
+            errors.push("escalation.max_cost_usd must be a non-negative number".to_string());
+        }
+        for (class, model) in &escalation.class_models {
+            if ScaleClass::from_key(class).is_none() {
+                errors.push(format!("escalation.class_models: unknown scale class '{}'", class));
+            }
+            if model.trim().is_empty() {
+                errors.push(format!("escalation.class_models.{}: model must not be empty", class));
+            }
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Fields that differ from `other` but only take effect after a restart
+    pub fn restart_required(&self, other: &AgencyProfile) -> Vec<&'static str> {
+        // Code-running tools get their sandbox when they are registered
+        if self.sandbox_profile != other.sandbox_profile { vec!["sandbox_profile"] } else { Vec::new() }
+    }
+}
+
 pub struct ProfileManager {
     path: PathBuf,
 }
@@ -29,6 +127,10 @@ impl ProfileManager {
         Self { path: path.into() }
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     pub async fn load(&self) -> Result<AgencyProfile> {
         if !self.path.exists() {
             let default = AgencyProfile::default();
@@ -62,6 +164,12 @@ mod tests {
             name: "Test Agency".to_string(),
             mission: "Testing mission".to_string(),
             traits: vec!["test".to_string()],
+            aggregation: AggregationStrategy::MajorityVote,
+            escalation: EscalationPolicy { max_attempts: 1, local_only: true, ..Default::default() },
+            plan_critique: false,
+            sandbox_profile: SandboxProfile::NoNetwork,
+            default_models: HashMap::from([(AgentType::Coder, "qwen2.5-coder:7b".to_string())]),
+            verbosity: Verbosity::Concise,
         };
         
         manager.save(&profile).await.unwrap();
@@ -82,4 +190,21 @@ mod tests {
         assert_eq!(default.name, loaded.name);
         assert_eq!(default.mission, loaded.mission);
     }
+
+    #[test]
+    fn test_profile_validate() {
+        assert!(AgencyProfile::default().validate().is_ok());
+
+        let mut profile = AgencyProfile { name: " ".to_string(), ..Default::default() };
+        profile.escalation.max_attempts = 0;
+        profile.escalation.class_models.insert("huge".to_string(), "llama3".to_string());
+        profile.default_models.insert(AgentType::Planner, String::new());
+        let errors = profile.validate().unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors.contains(&"default_models.planner: model must not be empty".to_string()));
+
+        let loaded: AgencyProfile = serde_json::from_str(r#"{"name":"A","mission":"M","traits":[],"verbosity":"detailed","default_models":{"reasoner":"glm-4-plus"}}"#).unwrap();
+        assert_eq!(loaded.verbosity, Verbosity::Detailed);
+        assert_eq!(loaded.default_models[&AgentType::Reasoner], "glm-4-plus");
+    }
 }
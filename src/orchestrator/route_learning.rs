@@ -0,0 +1,308 @@
+//! Route Learning
+//!
+//! The Router's keyword heuristics pick an agent and scale class; this module learns from
+//! how those picks turned out. Every routed turn is logged with its outcome (success, or the
+//! reward model's score for the winning answer) and later user ratings. Outcomes update a
+//! per-context bandit: the context is the heuristic's own pick (e.g. `coder/heavy`), the arms
+//! are the agents and scale classes that could have served it. Once an arm has enough
+//! evidence and clearly beats the heuristic's pick, the Router uses it instead.
+
+use lazy_static::lazy_static;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::agent::AgentType;
+use crate::orchestrator::a2a::skill_id;
+use crate::orchestrator::ScaleClass;
+
+lazy_static! {
+    /// Process-wide learner shared by the Router, Supervisor and the feedback endpoint
+    pub static ref ROUTE_LEARNER: Arc<RouteLearner> = Arc::new(RouteLearner::from_env());
+}
+
+const AGENT_ARMS: [AgentType; 5] = [
+    AgentType::GeneralChat,
+    AgentType::Reasoner,
+    AgentType::Coder,
+    AgentType::Researcher,
+    AgentType::Planner,
+];
+const CLASS_ARMS: [ScaleClass; 4] = [ScaleClass::Logic, ScaleClass::Tiny, ScaleClass::Standard, ScaleClass::Heavy];
+
+/// Routed turns kept for late feedback
+const MAX_DECISIONS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningConfig {
+    /// Observations an arm needs before it can override the heuristic
+    pub min_samples: u32,
+    /// How much better (mean reward) an arm must be than the heuristic's pick
+    pub margin: f32,
+    /// Chance of trying the least-observed arm instead, once the heuristic's pick has `min_samples`
+    pub explore: f32,
+}
+
+impl Default for LearningConfig {
+    fn default() -> Self {
+        Self { min_samples: 5, margin: 0.1, explore: 0.05 }
+    }
+}
+
+impl LearningConfig {
+    /// `AGENCY_ROUTE_MIN_SAMPLES`, `AGENCY_ROUTE_MARGIN`, `AGENCY_ROUTE_EXPLORE`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok();
+        Self {
+            min_samples: var("AGENCY_ROUTE_MIN_SAMPLES").and_then(|v| v.parse().ok()).unwrap_or(defaults.min_samples),
+            margin: var("AGENCY_ROUTE_MARGIN").and_then(|v| v.parse().ok()).unwrap_or(defaults.margin),
+            explore: var("AGENCY_ROUTE_EXPLORE").and_then(|v| v.parse().ok()).unwrap_or(defaults.explore),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArmStats {
+    pub pulls: u32,
+    pub reward_sum: f32,
+}
+
+impl ArmStats {
+    /// Mean reward shrunk toward 0.5 by a two-observation prior
+    pub fn mean(&self) -> f32 {
+        (self.reward_sum + 1.0) / (self.pulls as f32 + 2.0)
+    }
+}
+
+/// One routed turn and the signals it received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteRecord {
+    pub turn_id: String,
+    pub context: String,
+    pub agent: AgentType,
+    pub class: ScaleClass,
+    /// The turn needed a stronger tier, so the routed class gets no credit
+    pub escalated: bool,
+    pub outcome: f32,
+    #[serde(default)]
+    pub feedback: Option<f32>,
+}
+
+/// What the learner changed about a heuristic decision
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adaptation {
+    pub agent: Option<AgentType>,
+    pub class: Option<ScaleClass>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LearnerState {
+    /// context -> arm key (`agent:coder`, `class:heavy`) -> stats
+    #[serde(default)]
+    arms: HashMap<String, HashMap<String, ArmStats>>,
+    #[serde(default)]
+    decisions: VecDeque<RouteRecord>,
+}
+
+pub struct RouteLearner {
+    path: Option<PathBuf>,
+    config: LearningConfig,
+    state: Mutex<LearnerState>,
+}
+
+impl RouteLearner {
+    /// Persist to `path`; `None` disables learning
+    pub fn new(path: Option<PathBuf>, config: LearningConfig) -> Self {
+        let state = path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str(&content)
+                .map_err(|e| warn!("Ignoring invalid route learning state: {}", e))
+                .ok())
+            .unwrap_or_default();
+        Self { path, config, state: Mutex::new(state) }
+    }
+
+    /// `AGENCY_ROUTE_LEARNING` (default `agency_route_learning.json`; `off` disables)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_ROUTE_LEARNING").unwrap_or_else(|_| "agency_route_learning.json".to_string());
+        Self::new((path != "off" && !path.is_empty()).then(|| PathBuf::from(path)), LearningConfig::from_env())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Bandit context for a heuristic pick
+    pub fn context(agent: AgentType, class: ScaleClass) -> String {
+        format!("{}/{}", skill_id(agent), class.key())
+    }
+
+    /// Better agent and class for `context` than the heuristic's, if the evidence says so
+    pub fn adapt(&self, context: &str, agent: AgentType, class: ScaleClass) -> Option<Adaptation> {
+        if !self.is_enabled() { return None; }
+        let state = self.state.lock().unwrap();
+        let arms = state.arms.get(context);
+        let stats = |key: &str| arms.and_then(|a| a.get(key)).copied().unwrap_or_default();
+        // Explore only once the heuristic's own pick has a track record to compare against
+        let explore = self.config.explore > 0.0
+            && stats(&agent_key(agent)).pulls >= self.config.min_samples
+            && rand::thread_rng().gen::<f32>() < self.config.explore;
+
+        let pick = |current: String, keys: Vec<String>| -> Option<String> {
+            if explore {
+                let least = keys.iter().min_by_key(|k| stats(k).pulls)?;
+                return (*least != current).then(|| least.clone());
+            }
+            let baseline = stats(&current).mean();
+            keys.into_iter()
+                .filter(|k| *k != current && stats(k).pulls >= self.config.min_samples)
+                .filter(|k| stats(k).mean() >= baseline + self.config.margin)
+                .max_by(|a, b| stats(a).mean().total_cmp(&stats(b).mean()))
+        };
+
+        let learned_agent = pick(agent_key(agent), AGENT_ARMS.iter().map(|a| agent_key(*a)).collect())
+            .and_then(|k| AGENT_ARMS.iter().copied().find(|a| agent_key(*a) == k));
+        let learned_class = pick(class_key(class), CLASS_ARMS.iter().map(|c| class_key(*c)).collect())
+            .and_then(|k| CLASS_ARMS.iter().copied().find(|c| class_key(*c) == k));
+        if learned_agent.is_none() && learned_class.is_none() {
+            return None;
+        }
+
+        let mut changes = Vec::new();
+        if let Some(a) = learned_agent {
+            changes.push(format!("agent {} (mean {:.2})", a, stats(&agent_key(a)).mean()));
+        }
+        if let Some(c) = learned_class {
+            changes.push(format!("class {:?} (mean {:.2})", c, stats(&class_key(c)).mean()));
+        }
+        let verb = if explore { "Exploring" } else { "Learned" };
+        Some(Adaptation {
+            agent: learned_agent,
+            class: learned_class,
+            reason: format!("{} route for {}: {}", verb, context, changes.join(", ")),
+        })
+    }
+
+    /// Log a finished turn and credit its arms with `outcome` (0.0 - 1.0)
+    pub fn record_outcome(&self, record: RouteRecord) {
+        if !self.is_enabled() { return; }
+        let mut state = self.state.lock().unwrap();
+        Self::credit(&mut state, &record, record.outcome, 1);
+        state.decisions.retain(|d| d.turn_id != record.turn_id);
+        state.decisions.push_back(record);
+        while state.decisions.len() > MAX_DECISIONS {
+            state.decisions.pop_front();
+        }
+        self.save(&state);
+    }
+
+    /// Apply a user rating to a logged turn; a second rating replaces the first.
+    /// Returns whether the turn was found.
+    pub fn apply_feedback(&self, turn_id: &str, score: f32) -> bool {
+        if !self.is_enabled() { return false; }
+        let score = score.clamp(0.0, 1.0);
+        let mut state = self.state.lock().unwrap();
+        let Some(index) = state.decisions.iter().position(|d| d.turn_id == turn_id) else { return false };
+        let record = state.decisions[index].clone();
+        match record.feedback {
+            Some(previous) => Self::credit(&mut state, &record, score - previous, 0),
+            None => Self::credit(&mut state, &record, score, 1),
+        }
+        state.decisions[index].feedback = Some(score);
+        self.save(&state);
+        true
+    }
+
+    pub fn arm_stats(&self, context: &str) -> HashMap<String, ArmStats> {
+        self.state.lock().unwrap().arms.get(context).cloned().unwrap_or_default()
+    }
+
+    fn credit(state: &mut LearnerState, record: &RouteRecord, reward: f32, pulls: u32) {
+        let arms = state.arms.entry(record.context.clone()).or_default();
+        let mut add = |key: String, reward: f32| {
+            let stats = arms.entry(key).or_default();
+            stats.pulls += pulls;
+            stats.reward_sum += reward;
+        };
+        add(agent_key(record.agent), reward);
+        add(class_key(record.class), if record.escalated { 0.0 } else { reward });
+    }
+
+    fn save(&self, state: &LearnerState) {
+        let Some(path) = &self.path else { return };
+        let result = serde_json::to_string(state)
+            .map_err(|e| e.to_string())
+            .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!("Route learning state saved ({} turns)", state.decisions.len()),
+            Err(e) => warn!("Failed to save route learning state: {}", e),
+        }
+    }
+}
+
+fn agent_key(agent: AgentType) -> String {
+    format!("agent:{}", skill_id(agent))
+}
+
+fn class_key(class: ScaleClass) -> String {
+    format!("class:{}", class.key())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(turn_id: &str, agent: AgentType, outcome: f32) -> RouteRecord {
+        RouteRecord {
+            turn_id: turn_id.to_string(),
+            context: RouteLearner::context(AgentType::Coder, ScaleClass::Standard),
+            agent,
+            class: ScaleClass::Standard,
+            escalated: false,
+            outcome,
+            feedback: None,
+        }
+    }
+
+    #[test]
+    fn test_learner_overrides_heuristic_with_evidence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("routes.json");
+        let config = LearningConfig { min_samples: 3, margin: 0.1, explore: 0.0 };
+        let learner = RouteLearner::new(Some(path.clone()), config.clone());
+        let context = RouteLearner::context(AgentType::Coder, ScaleClass::Standard);
+
+        for i in 0..3 {
+            learner.record_outcome(record(&format!("c{}", i), AgentType::Coder, 0.0));
+            assert!(learner.adapt(&context, AgentType::Coder, ScaleClass::Standard).is_none());
+        }
+        for i in 0..3 {
+            learner.record_outcome(record(&format!("r{}", i), AgentType::Reasoner, 1.0));
+        }
+        let adaptation = learner.adapt(&context, AgentType::Coder, ScaleClass::Standard).unwrap();
+        assert_eq!(adaptation.agent, Some(AgentType::Reasoner));
+        assert_eq!(adaptation.class, None);
+
+        // State survives a restart
+        let reloaded = RouteLearner::new(Some(path), config);
+        assert_eq!(reloaded.arm_stats(&context)["agent:reasoner"].pulls, 3);
+    }
+
+    #[test]
+    fn test_feedback_replaces_previous_rating() {
+        let dir = tempfile::tempdir().unwrap();
+        let learner = RouteLearner::new(Some(dir.path().join("routes.json")), LearningConfig::default());
+        learner.record_outcome(record("t1", AgentType::Coder, 1.0));
+        assert!(learner.apply_feedback("t1", 0.0));
+        assert!(learner.apply_feedback("t1", 1.0));
+        assert!(!learner.apply_feedback("unknown", 1.0));
+
+        let stats = learner.arm_stats(&RouteLearner::context(AgentType::Coder, ScaleClass::Standard));
+        assert_eq!(stats["agent:coder"], ArmStats { pulls: 2, reward_sum: 2.0 });
+    }
+}
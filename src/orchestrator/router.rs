@@ -9,8 +9,9 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::info;
 
-use crate::agent::{AgentType, LLMProvider, OllamaProvider, OpenAICompatibleProvider};
-use crate::orchestrator::ScaleProfile;
+use crate::agent::{AgentType, CircuitRegistry, LLMProvider, OllamaProvider, OpenAICompatibleProvider, CIRCUITS};
+use crate::orchestrator::route_learning::{RouteLearner, ROUTE_LEARNER};
+use crate::orchestrator::{RoutedProvider, ScaleProfile, ROUTING};
 
 /// Routing decision for a query
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,27 +28,37 @@ pub struct RoutingDecision {
     pub reason: String,
     /// FPF Integration: Scaling-Law Lens (C.18.1)
     pub scale: ScaleProfile,
+    /// Route-learning context (the heuristic's pick), used to credit the outcome
+    #[serde(default)]
+    pub context: Option<String>,
 }
 
 /// Router for directing queries to appropriate agents
 #[derive(Clone)]
 pub struct Router {
     provider: Arc<dyn LLMProvider>,
-    model: String,
+    /// Fixed classification model; the routing matrix's router model when unset
+    model: Option<String>,
+    circuits: Arc<CircuitRegistry>,
+    learner: Arc<RouteLearner>,
 }
 
 impl Router {
     pub fn new(ollama: Ollama) -> Self {
         Self {
             provider: Arc::new(OllamaProvider::new(ollama)),
-            model: "llama3.2:3b".to_string(),
+            model: None,
+            circuits: CIRCUITS.clone(),
+            learner: ROUTE_LEARNER.clone(),
         }
     }
 
     pub fn new_with_provider(provider: Arc<dyn LLMProvider>) -> Self {
         Self {
             provider,
-            model: "llama3.2:3b".to_string(),
+            model: None,
+            circuits: CIRCUITS.clone(),
+            learner: ROUTE_LEARNER.clone(),
         }
     }
 
@@ -56,9 +67,21 @@ impl Router {
         self
     }
 
+    /// Circuit breakers consulted to skip tiers whose model is down
+    pub fn with_circuits(mut self, circuits: Arc<CircuitRegistry>) -> Self {
+        self.circuits = circuits;
+        self
+    }
+
+    /// Outcome-trained overrides for the keyword heuristics
+    pub fn with_learner(mut self, learner: Arc<RouteLearner>) -> Self {
+        self.learner = learner;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
-        self.model = model.into();
+        self.model = Some(model.into());
         self
     }
 
@@ -70,8 +93,32 @@ impl Router {
         self
     }
 
-    /// Route a query to the appropriate agent
+    /// Route a query to the appropriate agent: keyword heuristics (or the LLM), then
+    /// whatever the route learner has found works better for that kind of pick
+    #[tracing::instrument(name = "route", skip(self, query), fields(query_len = query.len()))]
     pub async fn route(&self, query: &str, vram_available_gb: Option<f32>) -> Result<RoutingDecision> {
+        let mut decision = self.heuristic_route(query, vram_available_gb).await?;
+        let Some(&agent) = decision.candidate_agents.first() else { return Ok(decision) };
+        let context = RouteLearner::context(agent, decision.scale.class);
+        if let Some(adaptation) = self.learner.adapt(&context, agent, decision.scale.class) {
+            info!("{}", adaptation.reason);
+            if let Some(learned) = adaptation.agent {
+                decision.candidate_agents.retain(|a| *a != learned && *a != agent);
+                decision.candidate_agents.insert(0, learned);
+            }
+            if let Some(class) = adaptation.class {
+                let vram = vram_available_gb.unwrap_or(8.0);
+                let mut scale = ScaleProfile::new_with_class(class, vram).healthy_tier(&self.circuits, vram);
+                scale.predicted_complexity = decision.scale.predicted_complexity;
+                decision.scale = scale;
+            }
+            decision.reason = format!("{} ({})", decision.reason, adaptation.reason);
+        }
+        decision.context = Some(context);
+        Ok(decision)
+    }
+
+    async fn heuristic_route(&self, query: &str, vram_available_gb: Option<f32>) -> Result<RoutingDecision> {
         // FPF Integration: Scaling-Law Lens (SLL) - The Scale Probe
         // 1. Calculate complexity (Scale Variables S)
         let q_lower = query.to_lowercase();
@@ -91,7 +138,12 @@ impl Router {
 
         // 2. Evaluate Scale Probe against actual hardware state
         let vram = vram_available_gb.unwrap_or(8.0); // Fallback to 8GB if tool is missing
-        let scale = ScaleProfile::new(complexity, vram);
+        let planned = ScaleProfile::new(complexity, vram);
+        let planned_model = planned.target_model.clone();
+        let scale = planned.healthy_tier(&self.circuits, vram);
+        if scale.target_model != planned_model {
+            info!("SLL-Audit: {} is unhealthy (circuit open), routing to {:?} tier ({})", planned_model, scale.class, scale.target_model);
+        }
         
         // FPF Integration: Reasoning Requirement Probe
         // Determine if the task is complex enough to merit strict reasoning tags
@@ -109,6 +161,7 @@ impl Router {
                 confidence: 0.95,
                 reason: "Query explicitly mentions tool usage (FPF Tool Detection)".to_string(),
                 scale,
+                context: None,
             });
         }
         
@@ -128,6 +181,7 @@ impl Router {
                 confidence: 0.9,
                 reason: "Simple greeting or short message".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -140,6 +194,7 @@ impl Router {
                 confidence: 0.95,
                 reason: "Direct filesystem query (heuristics fast-path)".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -152,6 +207,7 @@ impl Router {
                 confidence: 0.9,
                 reason: "Knowledge graph or relationship query".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -164,6 +220,7 @@ impl Router {
                 confidence: 0.85,
                 reason: "Query contains code-related keywords".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -176,6 +233,7 @@ impl Router {
                 confidence: 0.8,
                 reason: "Query involves planning or task decomposition".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -188,6 +246,7 @@ impl Router {
                 confidence: 0.8,
                 reason: "Query requires information gathering".to_string(),
                 scale,
+                context: None,
             });
         }
 
@@ -287,7 +346,8 @@ q = "{}"
         );
 
         let system = Some(super::sns::get_sns_system_prompt());
-        let content = self.provider.generate(&self.model, prompt, system).await?;
+        let model = self.model.clone().unwrap_or_else(|| ROUTING.current().router_model().to_string());
+        let content = RoutedProvider::new(self.provider.clone()).generate(&model, prompt, system).await?;
 
         self.parse_routing_response(&content)
     }
@@ -330,6 +390,7 @@ q = "{}"
                         confidence: 0.7,
                         reason,
                         scale: ScaleProfile::new(0.5, 8.0), // Placeholder, will be updated by caller
+                        context: None,
                     });
                 }
             }
@@ -373,6 +434,7 @@ q = "{}"
             confidence: 0.7, // LLM routing is less certain
             reason,
             scale: ScaleProfile::new(0.5, 8.0), // Placeholder
+            context: None,
         })
     }
 }
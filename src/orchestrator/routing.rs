@@ -0,0 +1,280 @@
+//! Routing Matrix
+//!
+//! Which model (and provider) serves the router, each scale class and each agent type,
+//! plus the cost and context-window metadata used for metering and context compaction.
+//! Loaded from `AGENCY_ROUTING_CONFIG` (default `config/routing.json`); `ModelManager`'s
+//! `reload` action swaps in an edited file without a restart.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::agent::cost::ModelPrice;
+use crate::agent::{AgentError, AgentResult, AgentType, ChatMessage, GenerationOptions, LLMProvider, COST_TRACKER};
+use crate::orchestrator::ScaleClass;
+
+/// Classification model when the matrix names none
+pub const DEFAULT_ROUTER_MODEL: &str = "llama3.2:3b";
+
+lazy_static! {
+    /// Process-wide routing matrix shared by the Router, ScaleProfile and Supervisor
+    pub static ref ROUTING: RoutingTable = RoutingTable::from_env();
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub model: String,
+    /// Provider type as in `AGENCY_PROVIDER` (e.g. `ollama`, `openai`); the supervisor's provider when absent
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Registered with the cost tracker when the matrix loads
+    #[serde(default)]
+    pub price: Option<ModelPrice>,
+    /// Tokens; overrides the built-in context window table
+    #[serde(default)]
+    pub context_window: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutingMatrix {
+    /// Model the Router classifies queries with
+    #[serde(default)]
+    pub router: Option<ModelRoute>,
+    /// Keyed by scale class (`logic`, `tiny`, `standard`, `heavy`); `logic` falls back to `tiny`
+    #[serde(default)]
+    pub classes: HashMap<String, ModelRoute>,
+    /// Keyed by agent role (`coder`, `reasoner`, ...); overrides the class model for that agent
+    #[serde(default)]
+    pub agents: HashMap<String, ModelRoute>,
+    /// Escalation order, weakest class first; the built-in ladder when empty
+    #[serde(default)]
+    pub escalation: Vec<String>,
+}
+
+impl RoutingMatrix {
+    pub fn router_model(&self) -> &str {
+        self.router.as_ref().map(|r| r.model.as_str()).unwrap_or(DEFAULT_ROUTER_MODEL)
+    }
+
+    pub fn class_route(&self, class: ScaleClass) -> Option<&ModelRoute> {
+        self.classes.get(class.key()).or_else(|| match class {
+            ScaleClass::Logic => self.classes.get(ScaleClass::Tiny.key()),
+            _ => None,
+        })
+    }
+
+    pub fn agent_route(&self, agent: AgentType) -> Option<&ModelRoute> {
+        self.agents.get(crate::orchestrator::a2a::skill_id(agent))
+    }
+
+    /// First route that names `model`
+    pub fn route_for_model(&self, model: &str) -> Option<&ModelRoute> {
+        self.router.iter()
+            .chain(self.classes.values())
+            .chain(self.agents.values())
+            .find(|r| r.model == model)
+    }
+
+    /// Next class in the configured escalation order; the last class (or one missing from the order) stays put
+    pub fn escalate(&self, class: ScaleClass) -> ScaleClass {
+        let order: Vec<ScaleClass> = self.escalation.iter().filter_map(|k| ScaleClass::from_key(k)).collect();
+        if order.is_empty() {
+            return class.escalate();
+        }
+        match order.iter().position(|c| *c == class) {
+            Some(pos) => order.get(pos + 1).copied().unwrap_or(class),
+            None => class,
+        }
+    }
+
+    pub fn context_window(&self, model: &str) -> Option<usize> {
+        self.route_for_model(model).and_then(|r| r.context_window)
+    }
+
+    fn register_prices(&self) {
+        for route in self.router.iter().chain(self.classes.values()).chain(self.agents.values()) {
+            if let Some(price) = route.price {
+                COST_TRACKER.set_price(route.model.clone(), price);
+            }
+        }
+    }
+}
+
+/// The loaded matrix plus the providers created for routes that name one
+pub struct RoutingTable {
+    path: PathBuf,
+    matrix: RwLock<Arc<RoutingMatrix>>,
+    providers: std::sync::Mutex<HashMap<String, Arc<dyn LLMProvider>>>,
+}
+
+impl RoutingTable {
+    /// Load `path`; a missing or invalid file gives an empty matrix (built-in defaults)
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let matrix = match Self::read(&path) {
+            Ok(Some(matrix)) => matrix,
+            Ok(None) => RoutingMatrix::default(),
+            Err(e) => {
+                warn!("Ignoring invalid routing config {:?}: {}", path, e);
+                RoutingMatrix::default()
+            }
+        };
+        matrix.register_prices();
+        Self { path, matrix: RwLock::new(Arc::new(matrix)), providers: std::sync::Mutex::new(HashMap::new()) }
+    }
+
+    /// `AGENCY_ROUTING_CONFIG` (default `config/routing.json`)
+    pub fn from_env() -> Self {
+        Self::load(std::env::var("AGENCY_ROUTING_CONFIG").unwrap_or_else(|_| "config/routing.json".to_string()))
+    }
+
+    fn read(path: &Path) -> AgentResult<Option<RoutingMatrix>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AgentError::Io(e)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn current(&self) -> Arc<RoutingMatrix> {
+        self.matrix.read().unwrap().clone()
+    }
+
+    /// Re-read the config file. An invalid file is reported and the current matrix kept.
+    pub fn reload(&self) -> AgentResult<Arc<RoutingMatrix>> {
+        let matrix = Arc::new(Self::read(&self.path)?.unwrap_or_default());
+        matrix.register_prices();
+        *self.matrix.write().unwrap() = matrix.clone();
+        self.providers.lock().unwrap().clear();
+        info!("Routing matrix reloaded from {:?}", self.path);
+        Ok(matrix)
+    }
+
+    /// Point the class or agent route `key` at `model`, save the config and reload it
+    pub fn set_route_model(&self, key: &str, model: &str) -> AgentResult<Arc<RoutingMatrix>> {
+        let mut matrix = Self::read(&self.path)?.unwrap_or_default();
+        let key = key.to_lowercase();
+        let routes = if ScaleClass::from_key(&key).is_some() { &mut matrix.classes } else { &mut matrix.agents };
+        routes.entry(key).or_default().model = model.to_string();
+        std::fs::write(&self.path, serde_json::to_string_pretty(&matrix)?)?;
+        self.reload()
+    }
+
+    /// Provider for `model` when its route names one
+    pub fn provider_for(&self, model: &str) -> Option<Arc<dyn LLMProvider>> {
+        let provider_type = self.current().route_for_model(model)?.provider.clone()?;
+        let mut providers = self.providers.lock().unwrap();
+        Some(providers.entry(provider_type.clone())
+            .or_insert_with(|| crate::agent::provider::create_provider_by_type(&provider_type))
+            .clone())
+    }
+}
+
+/// Sends each request to the provider its model is routed to, or to `inner`
+pub struct RoutedProvider {
+    inner: Arc<dyn LLMProvider>,
+}
+
+impl RoutedProvider {
+    pub fn new(inner: Arc<dyn LLMProvider>) -> Self {
+        Self { inner }
+    }
+
+    fn target(&self, model: &str) -> Arc<dyn LLMProvider> {
+        ROUTING.provider_for(model).unwrap_or_else(|| self.inner.clone())
+    }
+}
+
+#[async_trait]
+impl LLMProvider for RoutedProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.target(model).generate(model, prompt, system).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.target(model).generate_stream(model, prompt, system).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        self.target(model).generate_with_options(model, prompt, system, options).await
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.target(model).generate_stream_with_options(model, prompt, system, options).await
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        self.target(model).generate_chat(model, messages, options).await
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        self.target(model).generate_chat_stream(model, messages, options).await
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        self.target(model).count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        self.target(model).health_check(model).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.inner.get_lock()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        self.inner.notify(message).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_matrix_routes_and_reloads() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("routing.json");
+        std::fs::write(&path, r#"{
+            "router": {"model": "qwen2.5:1.5b"},
+            "classes": {"tiny": {"model": "phi-3-mini", "context_window": 8192}, "heavy": {"model": "gpt-4.1", "provider": "openai"}},
+            "agents": {"coder": {"model": "qwen2.5-coder:7b"}},
+            "escalation": ["tiny", "heavy"]
+        }"#).unwrap();
+
+        let table = RoutingTable::load(&path);
+        let matrix = table.current();
+        assert_eq!(matrix.router_model(), "qwen2.5:1.5b");
+        assert_eq!(matrix.class_route(ScaleClass::Logic).unwrap().model, "phi-3-mini");
+        assert_eq!(matrix.agent_route(AgentType::Coder).unwrap().model, "qwen2.5-coder:7b");
+        assert_eq!(matrix.context_window("phi-3-mini"), Some(8192));
+        // Standard is skipped by the configured ladder
+        assert_eq!(matrix.escalate(ScaleClass::Tiny), ScaleClass::Heavy);
+        assert_eq!(matrix.escalate(ScaleClass::Heavy), ScaleClass::Heavy);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(table.reload().is_err());
+        assert_eq!(table.current().router_model(), "qwen2.5:1.5b");
+
+        std::fs::write(&path, r#"{"escalation": []}"#).unwrap();
+        let reloaded = table.reload().unwrap();
+        assert_eq!(reloaded.router_model(), DEFAULT_ROUTER_MODEL);
+        assert_eq!(reloaded.escalate(ScaleClass::Tiny), ScaleClass::Standard);
+
+        let updated = table.set_route_model("Coder", "deepseek-coder-v2").unwrap();
+        assert_eq!(updated.agent_route(AgentType::Coder).unwrap().model, "deepseek-coder-v2");
+    }
+}
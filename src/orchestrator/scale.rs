@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::collections::HashMap;
 use crate::orchestrator::aggregation::ScaleElasticity;
+use crate::orchestrator::routing::ROUTING;
 
 /// FPF-aligned Scale Classes (C.18.1 SLL)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, PartialOrd)]
@@ -27,6 +28,26 @@ impl ScaleClass {
             ScaleClass::Heavy => ScaleClass::Heavy,
         }
     }
+
+    /// Lowercase name used in `config/routing.json` and the model registry defaults
+    pub fn key(&self) -> &'static str {
+        match self {
+            ScaleClass::Logic => "logic",
+            ScaleClass::Tiny => "tiny",
+            ScaleClass::Standard => "standard",
+            ScaleClass::Heavy => "heavy",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key.to_lowercase().as_str() {
+            "logic" => Some(ScaleClass::Logic),
+            "tiny" => Some(ScaleClass::Tiny),
+            "standard" => Some(ScaleClass::Standard),
+            "heavy" => Some(ScaleClass::Heavy),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,79 +73,87 @@ pub struct ScaleProfile {
 
 impl ScaleProfile {
     pub fn new(complexity: f32, vram_available_gb: f32) -> Self {
-        // Load defaults from registry
-        let defaults = if let Ok(file) = File::open("config/agency_models.json") {
-            if let Ok(registry) = serde_json::from_reader::<_, Registry>(file) {
-                registry.defaults
-            } else {
-                HashMap::new()
-            }
-        } else {
-            HashMap::new()
-        };
-
         // FPF Integration: Scaling-Law Lens (SLL)
         // Complexity mapped to scale classes
-        let (class, model_key, elasticity) = if complexity < 0.15 {
-            (ScaleClass::Logic, "tiny", ScaleElasticity::Flat)
+        let (class, elasticity) = if complexity < 0.15 {
+            (ScaleClass::Logic, ScaleElasticity::Flat)
         } else if complexity < 0.3 {
-            (ScaleClass::Tiny, "tiny", ScaleElasticity::Flat)
+            (ScaleClass::Tiny, ScaleElasticity::Flat)
         } else if complexity < 0.7 {
-            (ScaleClass::Standard, "standard", ScaleElasticity::Rising)
+            (ScaleClass::Standard, ScaleElasticity::Rising)
         } else {
-            (ScaleClass::Heavy, "heavy", ScaleElasticity::Knee)
+            (ScaleClass::Heavy, ScaleElasticity::Knee)
         };
 
-        let target_model = defaults.get(model_key).cloned().unwrap_or_else(|| {
-            match class {
-                ScaleClass::Logic => "qwen2.5-coder:0.5b".to_string(),
-                ScaleClass::Tiny => "qwen2.5-coder:0.5b".to_string(),
-                ScaleClass::Standard => "qwen2.5:3b-q4".to_string(),
-                ScaleClass::Heavy => if vram_available_gb >= 8.0 { "qwen2.5:7b-q4".to_string() } else { "qwen2.5:3b-q4".to_string() },
-            }
-        });
-
         Self {
             class,
             predicted_complexity: complexity,
             elasticity,
-            target_model,
+            target_model: Self::model_for_class(class, vram_available_gb),
         }
     }
 
     pub fn new_with_class(class: ScaleClass, vram_available_gb: f32) -> Self {
-        let defaults = if let Ok(file) = File::open("config/agency_models.json") {
-            if let Ok(registry) = serde_json::from_reader::<_, Registry>(file) {
-                registry.defaults
-            } else {
-                HashMap::new()
-            }
-        } else {
-            HashMap::new()
-        };
+        Self {
+            class,
+            predicted_complexity: 1.0,
+            elasticity: ScaleElasticity::Flat,
+            target_model: Self::model_for_class(class, vram_available_gb),
+        }
+    }
+
+    /// Routing matrix first, then the model registry defaults, then the built-in tiers
+    pub fn model_for_class(class: ScaleClass, vram_available_gb: f32) -> String {
+        if let Some(route) = ROUTING.current().class_route(class) {
+            return route.model.clone();
+        }
 
+        let defaults = File::open("config/agency_models.json").ok()
+            .and_then(|file| serde_json::from_reader::<_, Registry>(file).ok())
+            .map(|registry| registry.defaults)
+            .unwrap_or_default();
+        // The registry has no separate logic tier
         let model_key = match class {
-            ScaleClass::Logic => "tiny",
-            ScaleClass::Tiny => "tiny",
-            ScaleClass::Standard => "standard",
-            ScaleClass::Heavy => "heavy",
+            ScaleClass::Logic => ScaleClass::Tiny.key(),
+            _ => class.key(),
         };
+        defaults.get(model_key).cloned().unwrap_or_else(|| Self::fallback_model(class, vram_available_gb))
+    }
 
-        let target_model = defaults.get(model_key).cloned().unwrap_or_else(|| {
-            match class {
-                ScaleClass::Logic => "qwen2.5-coder:0.5b".to_string(),
-                ScaleClass::Tiny => "qwen2.5-coder:0.5b".to_string(),
-                ScaleClass::Standard => "qwen2.5:3b-q4".to_string(),
-                ScaleClass::Heavy => if vram_available_gb >= 8.0 { "qwen2.5:7b-q4".to_string() } else { "qwen2.5:3b-q4".to_string() },
-            }
-        });
+    /// Tier default when the registry has no entry. Hosted Gemini tiers replace the local
+    /// models when `AGENCY_PROVIDER` selects Gemini.
+    fn fallback_model(class: ScaleClass, vram_available_gb: f32) -> String {
+        let provider = std::env::var("AGENCY_PROVIDER").unwrap_or_default().to_lowercase();
+        if provider == "gemini" || provider == "google" {
+            return crate::agent::GeminiProvider::model_for_scale(class).to_string();
+        }
 
-        Self {
-            class,
-            predicted_complexity: 1.0,
-            elasticity: ScaleElasticity::Flat,
-            target_model,
+        match class {
+            ScaleClass::Logic => "qwen2.5-coder:0.5b".to_string(),
+            ScaleClass::Tiny => "qwen2.5-coder:0.5b".to_string(),
+            ScaleClass::Standard => "qwen2.5:3b-q4".to_string(),
+            ScaleClass::Heavy => if vram_available_gb >= 8.0 { "qwen2.5:7b-q4".to_string() } else { "qwen2.5:3b-q4".to_string() },
+        }
+    }
+
+    /// Nearest tier whose model is not behind an open circuit, preferring stronger tiers.
+    /// Returns `self` unchanged when it is healthy or no tier is.
+    pub fn healthy_tier(self, circuits: &crate::agent::CircuitRegistry, vram_available_gb: f32) -> Self {
+        if circuits.is_available(&self.target_model) {
+            return self;
+        }
+        let order = [ScaleClass::Logic, ScaleClass::Tiny, ScaleClass::Standard, ScaleClass::Heavy];
+        let pos = order.iter().position(|c| *c == self.class).unwrap_or(0);
+        let candidates = order[pos + 1..].iter().chain(order[..pos].iter().rev());
+        for &class in candidates {
+            let mut candidate = Self::new_with_class(class, vram_available_gb);
+            if circuits.is_available(&candidate.target_model) {
+                candidate.predicted_complexity = self.predicted_complexity;
+                candidate.elasticity = self.elasticity;
+                return candidate;
+            }
         }
+        self
     }
 
     pub fn format_for_audit(&self) -> String {
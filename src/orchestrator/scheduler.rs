@@ -4,20 +4,219 @@
 //! maintenance tasks (habits) and future intentions.
 
 use tokio_cron_scheduler::{Job, JobScheduler};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
 use tracing::{info, error};
+use uuid::Uuid;
 use crate::orchestrator::queue::TaskQueue;
+use crate::orchestrator::ResourceBudget;
 use serde_json::json;
 
+/// Task kind enqueued when a user schedule fires
+pub const SCHEDULED_PROMPT_TASK: &str = "scheduled_prompt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleMode {
+    /// One Supervisor turn
+    #[default]
+    Prompt,
+    /// An autonomous run of the background thought machine, bounded by the schedule's budget
+    Goal,
+}
+
+impl ScheduleMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Prompt => "prompt",
+            Self::Goal => "goal",
+        }
+    }
+}
+
+/// A user-defined recurring prompt ("every morning at 8, summarize HN") or autonomous goal
+/// ("every night at 2, reindex the codebase")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPrompt {
+    pub id: String,
+    pub name: String,
+    /// Six-field cron expression (sec min hour day month weekday), evaluated in local time
+    pub cron: String,
+    /// The prompt, or the goal in `Goal` mode
+    pub prompt: String,
+    #[serde(default)]
+    pub mode: ScheduleMode,
+    /// Limits for `Goal` runs (default `ResourceBudget::default()`)
+    #[serde(default)]
+    pub budget: Option<ResourceBudget>,
+    pub created_at: DateTime<Utc>,
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// Accept standard five-field cron by prepending a seconds field, and reject invalid expressions
+pub fn normalize_cron(expr: &str) -> anyhow::Result<String> {
+    let expr = expr.split_whitespace().collect::<Vec<_>>().join(" ");
+    let normalized = if expr.split(' ').count() == 5 { format!("0 {}", expr) } else { expr };
+    Job::new_tz(normalized.as_str(), chrono::Local, |_, _| {})
+        .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", normalized, e))?;
+    Ok(normalized)
+}
+
+/// SQLite persistence for user schedules so they survive restarts
+pub struct ScheduleStore {
+    db_path: PathBuf,
+}
+
+impl ScheduleStore {
+    pub async fn new(db_path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = db_path.as_ref().to_path_buf();
+        let path_clone = path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path_clone)?;
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS schedules (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    cron TEXT NOT NULL,
+                    prompt TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    last_run TEXT
+                )",
+                [],
+            )?;
+            // Goal schedules were added later; older databases lack their columns
+            let columns: Vec<String> = conn.prepare("SELECT name FROM pragma_table_info('schedules')")?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            if !columns.iter().any(|c| c == "mode") {
+                conn.execute("ALTER TABLE schedules ADD COLUMN mode TEXT NOT NULL DEFAULT 'prompt'", [])?;
+                conn.execute("ALTER TABLE schedules ADD COLUMN budget TEXT", [])?;
+            }
+            Ok::<_, anyhow::Error>(())
+        }).await??;
+        Ok(Self { db_path: path })
+    }
+
+    /// Store at `AGENCY_SCHEDULE_DB` (default `agency_schedules.db` in the data directory)
+    pub async fn from_env() -> anyhow::Result<Self> {
+        let path = std::env::var("AGENCY_SCHEDULE_DB").map(PathBuf::from).unwrap_or_else(|_| crate::utils::data_path("agency_schedules.db"));
+        Self::new(path).await
+    }
+
+    /// Throwaway store in the temp directory, for when the configured one cannot be opened;
+    /// schedules then last until the process exits
+    pub async fn ephemeral() -> Self {
+        let path = std::env::temp_dir().join(format!("agency_schedules_{}.db", Uuid::new_v4()));
+        match Self::new(&path).await {
+            Ok(store) => store,
+            Err(e) => {
+                error!("Temporary schedule store failed too; schedule actions will error: {:#}", e);
+                Self { db_path: path }
+            }
+        }
+    }
+
+    pub async fn add(&self, name: &str, cron: &str, prompt: &str) -> anyhow::Result<ScheduledPrompt> {
+        self.insert(name, cron, prompt, ScheduleMode::Prompt, None).await
+    }
+
+    /// Schedule a recurring autonomous goal, each run bounded by `budget`
+    pub async fn add_goal(&self, name: &str, cron: &str, goal: &str, budget: ResourceBudget) -> anyhow::Result<ScheduledPrompt> {
+        self.insert(name, cron, goal, ScheduleMode::Goal, Some(budget)).await
+    }
+
+    async fn insert(&self, name: &str, cron: &str, prompt: &str, mode: ScheduleMode, budget: Option<ResourceBudget>) -> anyhow::Result<ScheduledPrompt> {
+        let schedule = ScheduledPrompt {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            cron: normalize_cron(cron)?,
+            prompt: prompt.to_string(),
+            mode,
+            budget,
+            created_at: Utc::now(),
+            last_run: None,
+        };
+        let path = self.db_path.clone();
+        let record = schedule.clone();
+        let budget = record.budget.as_ref().map(serde_json::to_string).transpose()?;
+        tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path)?;
+            conn.execute(
+                "INSERT INTO schedules (id, name, cron, prompt, mode, budget, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![record.id, record.name, record.cron, record.prompt, record.mode.as_str(), budget, record.created_at.to_rfc3339()],
+            )?;
+            Ok::<_, anyhow::Error>(())
+        }).await??;
+        Ok(schedule)
+    }
+
+    pub async fn list(&self) -> anyhow::Result<Vec<ScheduledPrompt>> {
+        let path = self.db_path.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path)?;
+            let mut stmt = conn.prepare("SELECT id, name, cron, prompt, created_at, last_run, mode, budget FROM schedules ORDER BY created_at ASC")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?, row.get::<_, String>(4)?, row.get::<_, Option<String>>(5)?,
+                    row.get::<_, String>(6)?, row.get::<_, Option<String>>(7)?))
+            })?;
+            let parse = |s: &str| DateTime::parse_from_rfc3339(s).map(|d| d.with_timezone(&Utc));
+            let mut schedules = Vec::new();
+            for row in rows {
+                let (id, name, cron, prompt, created_at, last_run, mode, budget) = row?;
+                schedules.push(ScheduledPrompt {
+                    id,
+                    name,
+                    cron,
+                    prompt,
+                    mode: if mode == "goal" { ScheduleMode::Goal } else { ScheduleMode::Prompt },
+                    budget: budget.as_deref().map(serde_json::from_str).transpose()?,
+                    created_at: parse(&created_at)?,
+                    last_run: last_run.as_deref().map(parse).transpose()?,
+                });
+            }
+            Ok::<_, anyhow::Error>(schedules)
+        }).await?
+    }
+
+    /// Delete a schedule; returns false if it did not exist
+    pub async fn remove(&self, id: &str) -> anyhow::Result<bool> {
+        let path = self.db_path.clone();
+        let id = id.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path)?;
+            Ok::<_, anyhow::Error>(conn.execute("DELETE FROM schedules WHERE id = ?1", params![id])? > 0)
+        }).await?
+    }
+
+    pub async fn mark_run(&self, id: &str) -> anyhow::Result<()> {
+        let path = self.db_path.clone();
+        let id = id.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&path)?;
+            conn.execute("UPDATE schedules SET last_run = ?1 WHERE id = ?2", params![Utc::now().to_rfc3339(), id])?;
+            Ok::<_, anyhow::Error>(())
+        }).await?
+    }
+}
+
 pub struct AgencyScheduler {
     scheduler: JobScheduler,
     queue: Arc<dyn TaskQueue>,
+    /// Schedule id -> registered cron job
+    user_jobs: Mutex<HashMap<String, Uuid>>,
 }
 
 impl AgencyScheduler {
     pub async fn new(queue: Arc<dyn TaskQueue>) -> anyhow::Result<Self> {
         let scheduler = JobScheduler::new().await?;
-        Ok(Self { scheduler, queue })
+        Ok(Self { scheduler, queue, user_jobs: Mutex::new(HashMap::new()) })
     }
 
     /// Start the biological clock
@@ -54,6 +253,60 @@ impl AgencyScheduler {
         Ok(())
     }
 
+    /// Register jobs for new user schedules and drop jobs whose schedule was deleted.
+    /// Each firing enqueues a `scheduled_prompt` task for the Supervisor worker.
+    pub async fn sync_schedules(&self, store: &ScheduleStore) -> anyhow::Result<()> {
+        let schedules = store.list().await?;
+        let mut jobs = self.user_jobs.lock().await;
+
+        let stale: Vec<String> = jobs.keys().filter(|id| !schedules.iter().any(|s| &s.id == *id)).cloned().collect();
+        for id in stale {
+            if let Some(job_id) = jobs.remove(&id) {
+                self.scheduler.remove(&job_id).await?;
+                info!("📅 Schedule removed: {}", id);
+            }
+        }
+
+        for schedule in schedules.into_iter().filter(|s| !jobs.contains_key(&s.id)) {
+            let queue = self.queue.clone();
+            let payload = json!({
+                "schedule_id": schedule.id,
+                "name": schedule.name,
+                "prompt": schedule.prompt,
+                "mode": schedule.mode,
+                "budget": schedule.budget,
+            });
+            let name = schedule.name.clone();
+            let job = Job::new_async_tz(schedule.cron.as_str(), chrono::Local, move |_uuid, _l| {
+                let q = queue.clone();
+                let p = payload.clone();
+                let n = name.clone();
+                Box::pin(async move {
+                    info!("⏰ Schedule '{}' fired", n);
+                    if let Err(e) = q.enqueue(SCHEDULED_PROMPT_TASK, p).await {
+                        error!("Failed to enqueue schedule '{}': {}", n, e);
+                    }
+                })
+            })?;
+            let job_id = self.scheduler.add(job).await?;
+            info!("📅 Schedule active: '{}' ({})", schedule.name, schedule.cron);
+            jobs.insert(schedule.id, job_id);
+        }
+        Ok(())
+    }
+
+    /// Keep cron jobs in sync with the store (schedules are created by the `scheduler` tool)
+    pub fn watch_schedules(self: Arc<Self>, store: Arc<ScheduleStore>, interval: Duration) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = self.sync_schedules(&store).await {
+                    error!("Failed to sync schedules: {}", e);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
     /// Initialize default "Health" habits
     pub async fn init_defaults(&self) -> anyhow::Result<()> {
         // Hourly: System Health Check
@@ -109,4 +362,46 @@ mod tests {
 
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn test_normalize_cron() {
+        assert_eq!(normalize_cron("0 8 * * *").unwrap(), "0 0 8 * * *");
+        assert_eq!(normalize_cron("0 30 9 * * Mon-Fri").unwrap(), "0 30 9 * * Mon-Fri");
+        assert!(normalize_cron("every morning").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_store_roundtrip() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = ScheduleStore::new(tmp.path()).await.unwrap();
+
+        let schedule = store.add("HN digest", "0 8 * * *", "Summarize the top Hacker News stories").await.unwrap();
+        store.mark_run(&schedule.id).await.unwrap();
+
+        let all = store.list().await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].cron, "0 0 8 * * *");
+        assert!(all[0].last_run.is_some());
+
+        assert!(store.remove(&schedule.id).await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_goal_schedule_keeps_budget() {
+        let tmp = NamedTempFile::new().unwrap();
+        let store = ScheduleStore::new(tmp.path()).await.unwrap();
+
+        let budget = ResourceBudget { max_cycles: 3, max_tokens: Some(20_000), max_time_seconds: 900 };
+        store.add_goal("Nightly reindex", "0 2 * * *", "Reindex the codebase", budget).await.unwrap();
+        store.add("HN digest", "0 8 * * *", "Summarize Hacker News").await.unwrap();
+
+        // Reopening runs the migration check against an up-to-date table
+        let all = ScheduleStore::new(tmp.path()).await.unwrap().list().await.unwrap();
+        assert_eq!(all[0].mode, ScheduleMode::Goal);
+        let budget = all[0].budget.as_ref().unwrap();
+        assert_eq!((budget.max_cycles, budget.max_tokens, budget.max_time_seconds), (3, Some(20_000), 900));
+        assert_eq!(all[1].mode, ScheduleMode::Prompt);
+        assert!(all[1].budget.is_none());
+    }
 }
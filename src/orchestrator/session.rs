@@ -18,8 +18,15 @@ pub struct SessionState {
     pub episodic_memory: EpisodicMemory,
     /// The last executed plan (if any)
     pub last_plan: Option<Plan>,
+    /// A multi-step plan that has not finished; resumed instead of replanning
+    #[serde(default)]
+    pub active_plan: Option<Plan>,
+    /// Name given to the conversation, e.g. in the desktop sidebar
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
+#[derive(Clone)]
 pub struct SessionManager {
     path: PathBuf,
 }
@@ -29,17 +36,43 @@ impl SessionManager {
         Self { path: path.into() }
     }
 
-    /// Save the current state to disk
+    /// Save the current state to disk; the active plan and title on disk are kept
     pub async fn save(&self, memory: &EpisodicMemory, plan: Option<&Plan>) -> Result<()> {
+        let saved = self.load().await.ok();
         let state = SessionState {
             episodic_memory: memory.clone(),
             last_plan: plan.cloned(),
+            active_plan: saved.as_ref().and_then(|s| s.active_plan.clone()),
+            title: saved.and_then(|s| s.title),
         };
-        
-        let json = serde_json::to_string_pretty(&state)
+        self.write(&state).await
+    }
+
+    /// Name the conversation (`None` clears the name)
+    pub async fn set_title(&self, title: Option<&str>) -> Result<()> {
+        let mut state = self.load().await.unwrap_or_default();
+        state.title = title.map(str::to_string);
+        self.write(&state).await
+    }
+
+    /// Checkpoint the plan in progress (`None` once it has finished)
+    pub async fn save_active_plan(&self, plan: Option<&Plan>) -> Result<()> {
+        let mut state = self.load().await.unwrap_or_default();
+        state.active_plan = plan.cloned();
+        self.write(&state).await
+    }
+
+    /// The unfinished plan from a previous (possibly crashed) run
+    pub async fn active_plan(&self) -> Result<Option<Plan>> {
+        Ok(self.load().await?.active_plan)
+    }
+
+    async fn write(&self, state: &SessionState) -> Result<()> {
+        let json = serde_json::to_string_pretty(state)
             .context("Failed to serialize session state")?;
         
-        fs::write(&self.path, json).await
+        // A crash mid-write must not take the checkpointed plan and memory with it
+        crate::utils::write_atomic(&self.path, json).await
             .context("Failed to write session file")?;
         
         Ok(())
@@ -93,6 +126,37 @@ mod tests {
         assert_eq!(loaded.last_plan.unwrap().goal, "Test goal");
     }
 
+    #[tokio::test]
+    async fn test_active_plan_survives_saves() {
+        let temp_dir = tempdir().unwrap();
+        let manager = SessionManager::new(temp_dir.path().join("session.json"));
+
+        let mut plan = Plan::new("Build the report");
+        plan.steps.push(crate::orchestrator::PlanStep {
+            step_num: 1,
+            description: "Gather data".to_string(),
+            agent_type: crate::agent::AgentType::Researcher,
+            suggested_tools: vec![],
+            expected_output: String::new(),
+            depends_on: vec![],
+            completed: false,
+            output: None,
+        });
+        plan.complete_step(1, "data");
+        manager.save_active_plan(Some(&plan)).await.unwrap();
+        manager.set_title(Some("Quarterly report")).await.unwrap();
+
+        // Saving the conversation must not drop the checkpoint or the title
+        manager.save(&EpisodicMemory::default(), None).await.unwrap();
+        let resumed = manager.active_plan().await.unwrap().unwrap();
+        assert_eq!(resumed, plan);
+        assert_eq!(manager.load().await.unwrap().title.as_deref(), Some("Quarterly report"));
+        assert_eq!(resumed.steps[0].output.as_deref(), Some("data"));
+
+        manager.save_active_plan(None).await.unwrap();
+        assert!(manager.active_plan().await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_session_clear() {
         let temp_dir = tempdir().unwrap();
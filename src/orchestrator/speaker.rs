@@ -97,7 +97,7 @@ impl Speaker {
         let mut lock = self.inner.lock().await;
         if let Some(ref mut inner) = *lock {
             // Sanitize text for pipe
-            let clean_text = text.replace('\n', " ").replace('\r', "");
+            let clean_text = crate::safety::OUTPUT_MODERATOR.moderate(text).text.replace('\n', " ").replace('\r', "");
             if let Err(e) = writeln!(inner.stdin, "{}", clean_text) {
                 error!("Speaker: Pipe write failed: {}", e);
             }
@@ -0,0 +1,303 @@
+//! Sub-Agent Lineage
+//!
+//! Sub-agents spawned with `spawn_task` run as queued autonomous goals under their own turn
+//! id. The tracker records which turn spawned each one, refuses spawns past the depth and
+//! fan-out limits or once the spawn tree has used its aggregate budget, and hands finished
+//! results back to the parent turn as observations.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::orchestrator::{BudgetLimits, BudgetUsage, ResourceBudget};
+
+lazy_static! {
+    /// Process-wide sub-agent lineage shared by `TaskSpawnerTool`, the Supervisor worker and ReAct agents
+    pub static ref SUBAGENTS: SubAgentTracker = SubAgentTracker::new(SpawnLimits::from_env());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnLimits {
+    /// Deepest allowed sub-agent; children of a top-level turn are depth 1
+    pub max_depth: usize,
+    /// Children one turn may spawn
+    pub max_children: usize,
+    /// Shared by every sub-agent descending from the same top-level turn
+    pub tree: BudgetLimits,
+}
+
+impl Default for SpawnLimits {
+    fn default() -> Self {
+        Self { max_depth: 2, max_children: 4, tree: BudgetLimits::default() }
+    }
+}
+
+impl SpawnLimits {
+    /// `AGENCY_SPAWN_MAX_DEPTH`, `AGENCY_SPAWN_MAX_CHILDREN` and the `AGENCY_SPAWN_MAX_*` budget caps
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        Self {
+            max_depth: var("AGENCY_SPAWN_MAX_DEPTH").unwrap_or(defaults.max_depth),
+            max_children: var("AGENCY_SPAWN_MAX_CHILDREN").unwrap_or(defaults.max_children),
+            tree: BudgetLimits::from_env("SPAWN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubAgentStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAgentRecord {
+    pub task_id: String,
+    pub goal: String,
+    /// Turn that called `spawn_task`; `None` outside a turn
+    pub parent_turn: Option<String>,
+    /// Top-level turn of the spawn tree, or the task id when spawned outside a turn
+    pub root: String,
+    pub depth: usize,
+    /// Turn id the sub-agent runs under once started
+    pub turn_id: Option<String>,
+    pub status: SubAgentStatus,
+    pub answer: Option<String>,
+    pub usage: BudgetUsage,
+    /// Already handed to the parent as an observation
+    #[serde(default)]
+    pub delivered: bool,
+}
+
+impl SubAgentRecord {
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, SubAgentStatus::Completed | SubAgentStatus::Failed)
+    }
+
+    /// Observation text for the parent's ReAct loop
+    pub fn observation(&self) -> String {
+        format!(
+            "Sub-agent {} ({:?}) for '{}': {}",
+            self.task_id,
+            self.status,
+            self.goal,
+            self.answer.as_deref().unwrap_or("no answer")
+        )
+    }
+}
+
+/// Where a new sub-agent sits in the tree; pass it to `register` once the task is queued
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnTicket {
+    pub parent_turn: Option<String>,
+    pub root: Option<String>,
+    pub depth: usize,
+}
+
+/// A queued sub-agent the worker is about to run
+#[derive(Debug, Clone)]
+pub struct SubAgentRun {
+    pub turn_id: String,
+    /// What is left of the spawn tree's token and time budget
+    pub budget: ResourceBudget,
+}
+
+pub struct SubAgentTracker {
+    limits: SpawnLimits,
+    records: Mutex<HashMap<String, SubAgentRecord>>,
+}
+
+impl SubAgentTracker {
+    pub fn new(limits: SpawnLimits) -> Self {
+        Self { limits, records: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn limits(&self) -> &SpawnLimits {
+        &self.limits
+    }
+
+    /// Check the limits for a spawn from `parent_turn`; the error says which one was hit
+    pub fn admit(&self, parent_turn: Option<&str>) -> Result<SpawnTicket, String> {
+        let records = self.records.lock().unwrap();
+        let parent = parent_turn.and_then(|turn| records.values().find(|r| r.turn_id.as_deref() == Some(turn)));
+        let depth = parent.map(|p| p.depth + 1).unwrap_or(1);
+        if depth > self.limits.max_depth {
+            return Err(format!("depth limit reached ({}/{})", depth - 1, self.limits.max_depth));
+        }
+
+        if let Some(turn) = parent_turn {
+            let children = records.values().filter(|r| r.parent_turn.as_deref() == Some(turn)).count();
+            if children >= self.limits.max_children {
+                return Err(format!("fan-out limit reached ({}/{} children)", children, self.limits.max_children));
+            }
+        }
+
+        let root = parent.map(|p| p.root.clone()).or_else(|| parent_turn.map(str::to_string));
+        if let Some(ref root) = root {
+            if let Some(cap) = self.limits.tree.exceeded(&Self::tree_usage(&records, root)) {
+                return Err(format!("spawn tree budget exhausted: {}", cap));
+            }
+        }
+
+        Ok(SpawnTicket { parent_turn: parent_turn.map(str::to_string), root, depth })
+    }
+
+    pub fn register(&self, task_id: &str, goal: &str, ticket: SpawnTicket) {
+        let record = SubAgentRecord {
+            task_id: task_id.to_string(),
+            goal: goal.to_string(),
+            parent_turn: ticket.parent_turn,
+            root: ticket.root.unwrap_or_else(|| task_id.to_string()),
+            depth: ticket.depth,
+            turn_id: None,
+            status: SubAgentStatus::Queued,
+            answer: None,
+            usage: BudgetUsage::default(),
+            delivered: false,
+        };
+        self.records.lock().unwrap().insert(task_id.to_string(), record);
+    }
+
+    /// Mark a queued sub-agent running; `None` for tasks the tracker does not know
+    /// (spawned before a restart) or whose tree has no budget left
+    pub fn start(&self, task_id: &str) -> Option<SubAgentRun> {
+        let mut records = self.records.lock().unwrap();
+        let root = records.get(task_id)?.root.clone();
+        let used = Self::tree_usage(&records, &root);
+        if let Some(cap) = self.limits.tree.exceeded(&used) {
+            let record = records.get_mut(task_id)?;
+            record.status = SubAgentStatus::Failed;
+            record.answer = Some(format!("Not started: spawn tree budget exhausted: {}", cap));
+            return None;
+        }
+
+        let defaults = ResourceBudget::default();
+        let budget = ResourceBudget {
+            max_tokens: self.limits.tree.max_tokens
+                .map(|max| max.saturating_sub(used.tokens).min(u32::MAX as u64) as u32),
+            max_time_seconds: self.limits.tree.max_seconds
+                .map(|max| max.saturating_sub(used.seconds).min(defaults.max_time_seconds))
+                .unwrap_or(defaults.max_time_seconds),
+            ..defaults
+        };
+        let record = records.get_mut(task_id)?;
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        record.turn_id = Some(turn_id.clone());
+        record.status = SubAgentStatus::Running;
+        Some(SubAgentRun { turn_id, budget })
+    }
+
+    pub fn finish(&self, task_id: &str, success: bool, answer: String, usage: BudgetUsage) -> Option<SubAgentRecord> {
+        let mut records = self.records.lock().unwrap();
+        let record = records.get_mut(task_id)?;
+        record.status = if success { SubAgentStatus::Completed } else { SubAgentStatus::Failed };
+        record.answer = Some(answer);
+        record.usage = usage;
+        Some(record.clone())
+    }
+
+    pub fn get(&self, task_id: &str) -> Option<SubAgentRecord> {
+        self.records.lock().unwrap().get(task_id).cloned()
+    }
+
+    /// Sub-agents spawned by `parent_turn`
+    pub fn children(&self, parent_turn: &str) -> Vec<SubAgentRecord> {
+        let mut children: Vec<SubAgentRecord> = self.records.lock().unwrap().values()
+            .filter(|r| r.parent_turn.as_deref() == Some(parent_turn))
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+        children
+    }
+
+    /// Finished children of `parent_turn` not yet handed to it; each is returned once
+    pub fn take_results(&self, parent_turn: &str) -> Vec<SubAgentRecord> {
+        let mut records = self.records.lock().unwrap();
+        let mut results: Vec<SubAgentRecord> = records.values_mut()
+            .filter(|r| r.parent_turn.as_deref() == Some(parent_turn) && r.is_finished() && !r.delivered)
+            .map(|r| {
+                r.delivered = true;
+                r.clone()
+            })
+            .collect();
+        results.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+        results
+    }
+
+    /// Fail every queued or running sub-agent (kill switch); returns their task ids
+    pub fn halt_all(&self, reason: &str) -> Vec<String> {
+        let mut records = self.records.lock().unwrap();
+        let mut halted: Vec<String> = records.values_mut()
+            .filter(|r| !r.is_finished())
+            .map(|r| {
+                r.status = SubAgentStatus::Failed;
+                r.answer = Some(format!("Halted: {}", reason));
+                r.task_id.clone()
+            })
+            .collect();
+        halted.sort();
+        halted
+    }
+
+    fn tree_usage(records: &HashMap<String, SubAgentRecord>, root: &str) -> BudgetUsage {
+        records.values().filter(|r| r.root == root).fold(BudgetUsage::default(), |mut total, r| {
+            total.tokens += r.usage.tokens;
+            total.seconds += r.usage.seconds;
+            total.cost_usd += r.usage.cost_usd;
+            total.tool_calls += r.usage.tool_calls;
+            total
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> SubAgentTracker {
+        SubAgentTracker::new(SpawnLimits {
+            max_depth: 2,
+            max_children: 2,
+            tree: BudgetLimits { max_tokens: Some(1000), ..Default::default() },
+        })
+    }
+
+    #[test]
+    fn test_depth_and_fan_out_limits() {
+        let tracker = tracker();
+        let ticket = tracker.admit(Some("root")).unwrap();
+        assert_eq!(ticket.depth, 1);
+        tracker.register("a", "child a", ticket);
+        tracker.register("b", "child b", tracker.admit(Some("root")).unwrap());
+        assert!(tracker.admit(Some("root")).unwrap_err().contains("fan-out"));
+
+        let child_turn = tracker.start("a").unwrap().turn_id;
+        let grandchild = tracker.admit(Some(&child_turn)).unwrap();
+        assert_eq!(grandchild.depth, 2);
+        assert_eq!(grandchild.root.as_deref(), Some("root"));
+        tracker.register("c", "grandchild", grandchild);
+
+        let grandchild_turn = tracker.start("c").unwrap().turn_id;
+        assert!(tracker.admit(Some(&grandchild_turn)).unwrap_err().contains("depth"));
+    }
+
+    #[test]
+    fn test_tree_budget_and_results() {
+        let tracker = tracker();
+        tracker.register("a", "child a", tracker.admit(Some("root")).unwrap());
+        let run = tracker.start("a").unwrap();
+        assert_eq!(run.budget.max_tokens, Some(1000));
+        tracker.finish("a", true, "done".into(), BudgetUsage { tokens: 1200, ..Default::default() });
+
+        assert!(tracker.admit(Some("root")).unwrap_err().contains("budget"));
+        let results = tracker.take_results("root");
+        assert_eq!(results.len(), 1);
+        assert!(results[0].observation().contains("done"));
+        assert!(tracker.take_results("root").is_empty());
+    }
+}
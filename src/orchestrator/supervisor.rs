@@ -5,30 +5,43 @@
 use anyhow::Result;
 use ollama_rs::Ollama;
 use std::sync::Arc;
-use tokio::sync::{Semaphore, Mutex, mpsc};
-use std::collections::VecDeque;
-use tracing::{info, warn, error};
+use tokio::sync::{Semaphore, Mutex, broadcast, mpsc};
+use std::collections::{HashMap, VecDeque};
+use tracing::{info, warn, error, Instrument};
 use futures_util::future::join_all;
+use futures_util::{Stream, StreamExt};
 
 use crate::agent::{
-    ReActAgent, AgentType, AgentConfig, LLMCache, LLMProvider, Agent,
+    ReActAgent, AgentType, AgentConfig, LLMCache, LLMProvider, Agent, PausedReAct,
     AutonomousMachine, AgentResponse, OllamaProvider, AgentResult, AgentError,
     PubCharacteristic
 };
-use crate::agent::rl::ExperienceBuffer;
+use crate::agent::rl::{Experience, ExperienceBuffer};
 use crate::memory::{Memory, EpisodicMemory};
 use crate::emit_event;
+use crate::orchestrator::turn_history::{TurnRecord, TURN_HISTORY};
 use crate::orchestrator::{
-    Plan, Router, SessionManager, 
+    Plan, PlanExecutor, Planner, Router, SessionManager, 
     DesignRationaleRecord, Publication,
-    Objective, profile::AgencyProfile,
-    aggregation::{Candidate, Gamma, RewardModel},
-    ResultPortfolio, ScaleProfile, AgencyEvent,
+    Objective, ResourceBudget, profile::AgencyProfile,
+    aggregation::{AggregationStrategy, Candidate, Gamma, LLMRewardModel, RewardModel, Selection},
+    ResultPortfolio, ScaleProfile, ScaleClass, AgencyEvent, RouteRecord, ROUTE_LEARNER,
     queue::{TaskQueue, SqliteTaskQueue},
-    governance::NormSquare
+    governance::NormSquare,
+    turn_events::{TurnEvent, TurnEventProvider},
+    routing::{RoutedProvider, ROUTING},
+    checkpoint::TurnCheckpoint,
+    plan_review::PlanReviewer,
+    event_bus::{as_user, current_user, in_turn},
+    kill_switch::KILL_SWITCH,
+    subagents::SUBAGENTS,
+    team::Team,
 };
 use pai_core::{HookManager, HookEvent, HookEventType};
 
+/// Turn events buffered for slow watchers of a conversation before they lag
+const TURN_FEED_CAPACITY: usize = 1024;
+
 pub struct SupervisorResult {
     pub answer: String,
     pub success: bool,
@@ -75,6 +88,47 @@ pub struct Supervisor {
     pub metabolism: Arc<crate::orchestrator::metabolism::EconomicMetabolism>,
     /// Cryptographic Identity (Sovereignty)
     pub identity: Arc<crate::orchestrator::sovereignty::SovereignIdentity>,
+    /// Runtime-registered custom agent types
+    pub agent_types: Arc<crate::agent::AgentTypeRegistry>,
+    /// Ordered failover chain used when the primary provider keeps erroring
+    pub fallback_providers: Vec<Arc<dyn LLMProvider>>,
+    /// Token and spend accounting for every cached provider this supervisor creates
+    pub cost_tracker: Arc<crate::agent::CostTracker>,
+    /// Per-model circuit breakers; tiers whose model is open are skipped on escalation
+    pub circuits: Arc<crate::agent::CircuitRegistry>,
+    /// User-defined recurring prompts, fired by the cron runner
+    pub schedules: Arc<crate::orchestrator::scheduler::ScheduleStore>,
+    /// Peer nodes that heavy sub-tasks can be delegated to
+    pub mesh: Option<Arc<crate::orchestrator::mesh::PeerRegistry>>,
+    /// Tool calls waiting for a human decision
+    pub approvals: Arc<crate::safety::ApprovalQueue>,
+    /// Per-turn and per-session caps on tokens, time, spend and tool calls
+    pub budget: Arc<crate::orchestrator::BudgetManager>,
+    /// Set on supervisors created for one conversation by `ConversationRegistry`
+    pub conversation_id: Option<String>,
+    /// Authenticated owner of the conversation; their turns and audit entries carry it
+    pub user: Option<String>,
+    /// Receives typed progress while a turn runs under `handle_stream`
+    turn_events: Option<mpsc::UnboundedSender<TurnEvent>>,
+    /// Every turn's events, for clients watching the conversation rather than running a turn
+    pub turn_feed: broadcast::Sender<TurnEvent>,
+    /// Id of the turn in progress; tool side effects are journaled under it
+    turn_id: Option<String>,
+    /// Checkpoint of each attempt paused for approval, reverted if the call is rejected
+    paused_checkpoints: Arc<Mutex<HashMap<String, TurnCheckpoint>>>,
+}
+
+/// Send `message` to every agent steering channel in `channels`; returns how many took it.
+/// Works on a clone of `active_steer_txs` while a turn holds the supervisor.
+pub async fn steer_agents(channels: &Mutex<Vec<mpsc::Sender<String>>>, message: &str) -> usize {
+    let txs = channels.lock().await;
+    let mut delivered = 0;
+    for tx in txs.iter() {
+        if tx.send(message.to_string()).await.is_ok() {
+            delivered += 1;
+        }
+    }
+    delivered
 }
 
 impl Supervisor {
@@ -90,9 +144,25 @@ impl Supervisor {
         let vocal_cords = Arc::new(crate::orchestrator::vocal_cords::VocalCords::new());
         let metabolism = Arc::new(crate::orchestrator::metabolism::EconomicMetabolism::new()); // Default initial balance handled inside
         let identity = Arc::new(crate::orchestrator::sovereignty::SovereignIdentity::new().expect("Failed to initialize Sovereign Identity"));
+        let schedules = Arc::new(match crate::orchestrator::scheduler::ScheduleStore::from_env().await {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::error!("Schedule store unavailable, schedules will not survive a restart: {:#}", e);
+                crate::orchestrator::scheduler::ScheduleStore::ephemeral().await
+            }
+        });
+        let approvals = Arc::new(match crate::safety::ApprovalQueue::from_env().await {
+            Ok(queue) => queue,
+            Err(e) => {
+                tracing::error!("Approval queue unreadable, starting empty: {:#}", e);
+                crate::safety::ApprovalQueue::reset(crate::safety::ApprovalQueue::path_from_env()).await
+            }
+        });
 
         // Register the TaskSpawnerTool to enable Cellular Division
         tools.register_instance(crate::tools::TaskSpawnerTool::new(task_queue.clone())).await;
+        // Register the SchedulerTool for recurring prompts
+        tools.register_instance(crate::tools::SchedulerTool::new(schedules.clone())).await;
         // Register the WatchdogTool to enable Sensory Expansion
         tools.register_instance(crate::tools::WatchdogTool::new(sensory.clone())).await;
         // Register the NotifyTool to enable Vocal Cords
@@ -120,7 +190,7 @@ impl Supervisor {
             session: None,
             history_manager: Arc::new(crate::memory::HistoryManager::new(crate::memory::HistoryManager::default_path(), Some(10 * 1024 * 1024))),
             max_retries: 2,
-            cache: Arc::new(LLMCache::new()),
+            cache: Arc::new(LLMCache::from_env().await),
             safety: Arc::new(Mutex::new(crate::safety::SafetyGuard::new())),
             role_algebra: crate::orchestrator::RoleAlgebra::new(),
             concurrency_limit: Arc::new(Semaphore::new(4)),
@@ -155,6 +225,67 @@ impl Supervisor {
             vocal_cords,
             metabolism,
             identity,
+            agent_types: Arc::new(crate::agent::AgentTypeRegistry::new()),
+            fallback_providers: crate::agent::fallback_providers_from_env(),
+            cost_tracker: crate::agent::COST_TRACKER.clone(),
+            circuits: crate::agent::CIRCUITS.clone(),
+            schedules,
+            mesh: None,
+            approvals,
+            budget: Arc::new(crate::orchestrator::BudgetManager::from_env()),
+            conversation_id: None,
+            user: None,
+            turn_events: None,
+            turn_feed: broadcast::channel(TURN_FEED_CAPACITY).0,
+            turn_id: None,
+            paused_checkpoints: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// A supervisor for an independent conversation: providers, tools, queues and stores are
+    /// shared with `self`, while episodic memory, follow-ups, steering and safety counters are
+    /// its own, so conversations can run turns concurrently
+    pub fn for_conversation(&self, id: impl Into<String>, session: Option<SessionManager>) -> Self {
+        Self {
+            provider: self.provider.clone(),
+            tools: self.tools.clone(),
+            memory: self.memory.clone(),
+            session,
+            history_manager: self.history_manager.clone(),
+            max_retries: self.max_retries,
+            cache: self.cache.clone(),
+            hw_lock: self.hw_lock.clone(),
+            safety: Arc::new(Mutex::new(crate::safety::SafetyGuard::new())),
+            role_algebra: self.role_algebra.clone(),
+            concurrency_limit: self.concurrency_limit.clone(),
+            episodic_memory: Arc::new(tokio::sync::Mutex::new(EpisodicMemory::default())),
+            profile: self.profile.clone(),
+            reward_model: self.reward_model.clone(),
+            experience_buffer: self.experience_buffer.clone(),
+            active_steer_txs: Arc::new(Mutex::new(Vec::new())),
+            followup_queue: Arc::new(Mutex::new(VecDeque::new())),
+            pai_hooks: self.pai_hooks.clone(),
+            pai_memory: self.pai_memory.clone(),
+            recovery: self.recovery.clone(),
+            task_queue: self.task_queue.clone(),
+            sensory: self.sensory.clone(),
+            vocal_cords: self.vocal_cords.clone(),
+            metabolism: self.metabolism.clone(),
+            identity: self.identity.clone(),
+            agent_types: self.agent_types.clone(),
+            fallback_providers: self.fallback_providers.clone(),
+            cost_tracker: self.cost_tracker.clone(),
+            circuits: self.circuits.clone(),
+            schedules: self.schedules.clone(),
+            mesh: self.mesh.clone(),
+            approvals: self.approvals.clone(),
+            budget: Arc::new(self.budget.fork()),
+            conversation_id: Some(id.into()),
+            user: self.user.clone(),
+            turn_events: None,
+            turn_feed: broadcast::channel(TURN_FEED_CAPACITY).0,
+            turn_id: None,
+            paused_checkpoints: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -165,14 +296,25 @@ impl Supervisor {
 
     /// Process the next pending task from the queue (Single Step)
     pub async fn process_next_task(&mut self) -> Result<bool> {
+        // Queued work stays queued while halted
+        if KILL_SWITCH.is_engaged() {
+            return Ok(false);
+        }
         match self.task_queue.dequeue().await {
             Ok(Some(task)) => {
                 info!("Supervisor Worker: Processing task {} ({})", task.id, task.kind);
                 
                 if task.kind == "autonomous_goal" {
-                    if let Ok(goal) = serde_json::from_str::<String>(&task.payload) {
-                        info!("Supervisor Worker: Running autonomous goal: {}", goal);
-                        if let Err(e) = self.run_autonomous(&goal).await {
+                    let payload: serde_json::Value = serde_json::from_str(&task.payload).unwrap_or_default();
+                    // Plain string payloads predate sub-agent lineage
+                    if let Some(goal) = payload.as_str().or_else(|| payload["goal"].as_str()).map(str::to_string) {
+                        let result = if SUBAGENTS.get(&task.id).is_some() {
+                            self.run_subagent(&task.id, &goal).await
+                        } else {
+                            info!("Supervisor Worker: Running autonomous goal: {}", goal);
+                            self.run_autonomous(&goal).await.map(|_| ())
+                        };
+                        if let Err(e) = result {
                             error!("Supervisor Worker: Autonomous task failed: {}", e);
                             let _ = self.task_queue.fail(&task.id, &e.to_string(), true).await;
                             return Ok(true);
@@ -180,6 +322,43 @@ impl Supervisor {
                     }
                 }
 
+                if task.kind == crate::orchestrator::scheduler::SCHEDULED_PROMPT_TASK {
+                    let payload: serde_json::Value = serde_json::from_str(&task.payload).unwrap_or_default();
+                    let schedule_id = payload["schedule_id"].as_str().unwrap_or_default().to_string();
+                    let name = payload["name"].as_str().unwrap_or_default().to_string();
+                    if let Some(prompt) = payload["prompt"].as_str() {
+                        let result = if payload["mode"] == "goal" {
+                            info!("Supervisor Worker: Running scheduled goal '{}'", name);
+                            let budget = serde_json::from_value(payload["budget"].clone()).unwrap_or_default();
+                            self.run_autonomous_with_budget(prompt, budget).await
+                        } else {
+                            info!("Supervisor Worker: Running scheduled prompt '{}'", name);
+                            self.handle(prompt).await
+                        };
+                        let (success, answer) = match result {
+                            Ok(result) => (result.success, result.answer),
+                            Err(e) => (false, e.to_string()),
+                        };
+                        let _ = self.schedules.mark_run(&schedule_id).await;
+                        emit_event!(AgencyEvent::ScheduledRunFinished { schedule_id, name, success, answer });
+                    }
+                }
+
+                if task.kind == crate::orchestrator::webhooks::WEBHOOK_TASK {
+                    let payload: serde_json::Value = serde_json::from_str(&task.payload).unwrap_or_default();
+                    let hook_id = payload["hook_id"].as_str().unwrap_or_default().to_string();
+                    let delivery_id = payload["delivery_id"].as_str().unwrap_or_default().to_string();
+                    if let Some(prompt) = payload["prompt"].as_str() {
+                        info!("Supervisor Worker: Handling webhook '{}' ({})", hook_id, delivery_id);
+                        let result = if payload["mode"] == "goal" { self.run_autonomous(prompt).await } else { self.handle(prompt).await };
+                        let (success, answer) = match result {
+                            Ok(result) => (result.success, result.answer),
+                            Err(e) => (false, e.to_string()),
+                        };
+                        emit_event!(AgencyEvent::WebhookHandled { hook_id, delivery_id, success, answer });
+                    }
+                }
+
                 if task.kind == "memory_consolidation" {
                     info!("Supervisor Worker: Performing memory consolidation (Dreaming)...");
                     if let Some(ref memory) = self.memory {
@@ -318,11 +497,7 @@ impl Supervisor {
 
     /// Interrupt all active agents with a steering message
     pub async fn steer(&self, message: impl Into<String>) -> Result<()> {
-        let msg = message.into();
-        let txs = self.active_steer_txs.lock().await;
-        for tx in txs.iter() {
-            let _ = tx.send(msg.clone()).await;
-        }
+        steer_agents(&self.active_steer_txs, &message.into()).await;
         Ok(())
     }
 
@@ -341,6 +516,14 @@ impl Supervisor {
         self
     }
 
+    /// Rate the answers of a finished turn in [0, 1]; the rating replaces the logged reward
+    /// when the reward model is next trained, and the route learner credits the turn's routing
+    /// decision with it. Returns how many logged candidates were rated.
+    pub async fn record_feedback(&self, turn_id: &str, score: f32) -> usize {
+        ROUTE_LEARNER.apply_feedback(turn_id, score);
+        self.experience_buffer.lock().await.apply_feedback(turn_id, score)
+    }
+
     pub fn with_episodic_memory(mut self, memory: Arc<tokio::sync::Mutex<EpisodicMemory>>) -> Self {
         self.episodic_memory = memory;
         self
@@ -361,6 +544,11 @@ impl Supervisor {
         self
     }
 
+    pub fn with_budget(mut self, budget: crate::orchestrator::BudgetManager) -> Self {
+        self.budget = Arc::new(budget);
+        self
+    }
+
     pub fn with_max_retries(mut self, retries: usize) -> Self {
         self.max_retries = retries;
         self
@@ -371,6 +559,21 @@ impl Supervisor {
         self
     }
 
+    pub fn with_agent_types(mut self, agent_types: Arc<crate::agent::AgentTypeRegistry>) -> Self {
+        self.agent_types = agent_types;
+        self
+    }
+
+    pub fn with_mesh(mut self, mesh: Arc<crate::orchestrator::mesh::PeerRegistry>) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+
+    pub fn with_fallback_providers(mut self, providers: Vec<Arc<dyn LLMProvider>>) -> Self {
+        self.fallback_providers = providers;
+        self
+    }
+
     pub async fn load_session(&mut self) -> Result<()> {
         if let Some(ref mut sm) = self.session {
             let state = sm.load().await?;
@@ -390,21 +593,194 @@ impl Supervisor {
         if let Some(ref mut sm) = self.session {
             sm.clear().await?;
         }
+        self.budget.reset_session();
         Ok(())
     }
 
     fn create_cached_provider(&self) -> Arc<dyn LLMProvider> {
-        Arc::new(crate::agent::CachedProvider::new(
-            self.provider.clone(),
+        // Cache hits are served even while the model's circuit is open
+        let routed = Arc::new(RoutedProvider::new(self.provider.clone()));
+        let guarded = Arc::new(crate::agent::CircuitBreakerProvider::new(routed)
+            .with_circuits(self.circuits.clone()));
+        let cached: Arc<dyn LLMProvider> = Arc::new(crate::agent::CachedProvider::new(
+            guarded,
             self.cache.clone()
-        ))
+        ).with_cost_tracker(self.cost_tracker.clone()));
+        match self.turn_events() {
+            Some(tx) => Arc::new(TurnEventProvider::new(cached, tx)),
+            None => cached,
+        }
+    }
+
+    /// Sender for the streamed turn in progress, if any
+    fn turn_events(&self) -> Option<mpsc::UnboundedSender<TurnEvent>> {
+        self.turn_events.clone().filter(|tx| !tx.is_closed())
+    }
+
+    fn emit_turn_event(&self, event: TurnEvent) {
+        if let Some(tx) = self.turn_events() {
+            let _ = tx.send(event);
+        }
     }
 
-    #[tracing::instrument(skip(self, query), fields(query_len = query.len()))]
+    /// Progress message for the UI and, when streaming, the turn's event stream
+    async fn notify(&self, message: &str) {
+        self.emit_turn_event(TurnEvent::Status { message: message.trim().to_string() });
+        let _ = self.provider.notify(message).await;
+    }
+
+    /// Snapshot of what the current turn can change, taken before a risky step
+    async fn checkpoint(&self, label: impl Into<String>) -> Option<TurnCheckpoint> {
+        let turn_id = self.turn_id.as_ref()?;
+        let memory = self.episodic_memory.lock().await;
+        let followups = self.followup_queue.lock().await;
+        Some(TurnCheckpoint::take(label, turn_id, &memory, &followups, &self.tools).await)
+    }
+
+    /// Return to `checkpoint`, undoing the memory changes and file writes made since
+    async fn rollback(&self, checkpoint: &TurnCheckpoint) {
+        let report = {
+            let mut memory = self.episodic_memory.lock().await;
+            let mut followups = self.followup_queue.lock().await;
+            checkpoint.restore(&mut memory, &mut followups, &self.tools).await
+        };
+        self.report_rollback(&checkpoint.label, report).await;
+    }
+
+    async fn report_rollback(&self, label: &str, report: crate::tools::RevertReport) {
+        if report.is_empty() {
+            return;
+        }
+        if report.irreversible.is_empty() {
+            self.notify(&format!("\n↩️ Rolled back {} ({} change(s) undone)\n", label, report.reverted.len())).await;
+        } else {
+            self.notify(&format!("\n↩️ Rolled back {}; could not undo: {}\n", label, report.irreversible.join(", "))).await;
+        }
+        emit_event!(AgencyEvent::CheckpointRestored {
+            label: label.to_string(),
+            reverted: report.reverted,
+            irreversible: report.irreversible,
+        });
+    }
+
+    /// Run `handle(query)` and yield its progress as typed events, ending with `FinalAnswer`
+    /// (or `Error`)
+    pub fn handle_stream<'a>(&'a mut self, query: &'a str) -> impl Stream<Item = TurnEvent> + Send + 'a {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let feed = self.turn_feed.clone();
+        let turn = Box::pin(async move {
+            self.turn_events = Some(tx.clone());
+            let result = self.handle(query).await;
+            self.turn_events = None;
+            let _ = tx.send(match result {
+                Ok(ref result) => TurnEvent::final_answer(result),
+                Err(e) => TurnEvent::Error { message: e.to_string() },
+            });
+        });
+
+        futures_util::stream::unfold((Some(turn), rx), |(mut turn, mut rx)| async move {
+            if let Some(running) = turn.as_mut() {
+                let event = tokio::select! {
+                    biased;
+                    Some(event) = rx.recv() => Some(event),
+                    _ = running => None,
+                };
+                match event {
+                    Some(event) => return Some((event, (turn, rx))),
+                    None => turn = None,
+                }
+            }
+            // The turn is over; drain what it sent, ending with its final event
+            rx.try_recv().ok().map(|event| (event, (turn, rx)))
+        })
+        .inspect(move |event| {
+            let _ = feed.send(event.clone());
+        })
+    }
+
+    /// Events of every turn in this conversation from now on, however it was started
+    pub fn subscribe_turns(&self) -> broadcast::Receiver<TurnEvent> {
+        self.turn_feed.subscribe()
+    }
+
+    #[tracing::instrument(name = "turn", skip(self, query), fields(query_len = query.len(), turn_id = tracing::field::Empty))]
     pub async fn handle(&mut self, query: &str) -> AgentResult<SupervisorResult> {
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        tracing::Span::current().record("turn_id", turn_id.as_str());
+        // Events emitted during the turn are logged under its id for replay
+        let user = self.user.clone().or_else(current_user);
+        // Watchers of the conversation see turns that are not streamed too
+        let feed = (self.turn_events.is_none() && self.turn_feed.receiver_count() > 0).then(|| {
+            let (tx, mut rx) = mpsc::unbounded_channel::<TurnEvent>();
+            let feed = self.turn_feed.clone();
+            tokio::spawn(async move {
+                while let Some(event) = rx.recv().await {
+                    let _ = feed.send(event);
+                }
+            });
+            self.turn_events = Some(tx.clone());
+            tx
+        });
+        let budget = self.budget.clone();
+        let result = in_turn(Some(turn_id.clone()), as_user(user.clone(), budget.metered(KILL_SWITCH.cancellable(self.handle_turn(query, turn_id.clone()))))).await;
+        TURN_HISTORY.record(TurnRecord::new(turn_id, self.conversation_id.clone(), user, query, &result));
+        if let Some(tx) = feed {
+            self.turn_events = None;
+            let _ = tx.send(match result {
+                Ok(ref result) => TurnEvent::final_answer(result),
+                Err(ref e) => TurnEvent::Error { message: e.to_string() },
+            });
+        }
+        result
+    }
+
+    /// Emergency stop: abort running turns, sub-agents and tool subprocesses, save the session,
+    /// and refuse new work until `rearm`. When the supervisor sits behind a lock held by the
+    /// running turn, engage `KILL_SWITCH` first; the turn then returns and releases it.
+    pub async fn panic_stop(&self, reason: &str) -> usize {
+        let aborted = KILL_SWITCH.engage(reason);
+        self.tools.cancel_running();
+        self.active_steer_txs.lock().await.clear();
+        self.followup_queue.lock().await.clear();
+
+        let halted = SUBAGENTS.halt_all(reason);
+        for task_id in &halted {
+            let _ = self.task_queue.fail(task_id, reason, false).await;
+        }
+
+        if let Some(ref session) = self.session {
+            let last_plan = session.load().await.ok().and_then(|s| s.last_plan);
+            let memory = self.episodic_memory.lock().await;
+            if let Err(e) = session.save(&memory, last_plan.as_ref()).await {
+                error!("Failed to save session during panic stop: {}", e);
+            }
+        }
+
+        crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::KillSwitch, "operator", serde_json::json!({
+            "action": "engage",
+            "reason": reason,
+            "aborted_tasks": aborted,
+            "halted_subagents": halted,
+        }));
+        emit_event!(AgencyEvent::StatusUpdate(format!("Halted by kill switch: {}", reason)));
+        aborted
+    }
+
+    /// Lift a `panic_stop` so turns, tools and queued work run again
+    pub fn rearm(&self) {
+        KILL_SWITCH.rearm();
+        crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::KillSwitch, "operator", serde_json::json!({ "action": "rearm" }));
+        emit_event!(AgencyEvent::StatusUpdate("Kill switch re-armed".to_string()));
+    }
+
+    async fn handle_turn(&mut self, query: &str, session_id: String) -> AgentResult<SupervisorResult> {
         let _work_start_time = std::time::Instant::now();
+        let usage_mark = self.cost_tracker.mark();
+        let budget_mark = self.budget.mark();
+        // An exhausted session budget stops work before any model is called
+        self.budget.check(&budget_mark).map_err(AgentError::Validation)?;
         
-        let session_id = uuid::Uuid::new_v4().to_string();
+        self.turn_id = Some(session_id.clone());
 
         // PAI: Trigger and LOG SessionStart Event
         let mut start_event = HookEvent {
@@ -425,12 +801,16 @@ impl Supervisor {
 
         // SOTA: High-Fidelity Context Compaction (pi-mono-inspired)
         {
+            // Count against the standard-tier model, which serves most turns
+            let model = ScaleProfile::new_with_class(ScaleClass::Standard, 8.0).target_model;
+            let threshold = crate::agent::tokenizer::context_window(&model) * 4 / 5; // 80% of the window
             let mut memory = self.episodic_memory.lock().await;
             let _ = crate::memory::compactor::ContextCompactor::compact_if_needed(
                 &mut memory,
                 self.provider.clone(),
                 &self.profile,
-                3200 // Threshold: 80% of 4k window
+                &model,
+                threshold
             ).await;
         }
 
@@ -460,7 +840,8 @@ impl Supervisor {
         };
 
         let router_task = async {
-            let router = Router::new_with_provider(self.provider.clone());
+            let router = Router::new_with_provider(self.provider.clone())
+                .with_circuits(self.circuits.clone());
             router.route(query, Some(8.0)).await
         };
 
@@ -477,7 +858,7 @@ impl Supervisor {
         };
 
         let (memory_ctx, routing_result, project_ctx) = tokio::join!(memory_search_task, router_task, project_context_task);
-        let routing_decision = routing_result.map_err(|e| AgentError::Execution(e.to_string()))?;
+        let mut routing_decision = routing_result.map_err(|e| AgentError::Execution(e.to_string()))?;
 
         if let Some(ctx) = project_ctx {
             full_context.push_str(&ctx);
@@ -500,7 +881,7 @@ impl Supervisor {
             
             if let Ok(queries) = selector.select_minimal_queries(query, "Direct Execution Plan").await {
                 for q in queries {
-                    let _ = self.provider.notify(&format!("🔍 Resolving Uncertainty: {}", q.description)).await;
+                    self.notify(&format!("🔍 Resolving Uncertainty: {}", q.description)).await;
                     // Execute query as a lightweight system prompt injection
                     // In a real implementation, we would run a one-off tool call here.
                     full_context.push_str(&format!("\n<|im_start|>system\nVerified Assumption ({}): {}\n<|im_end|>\n", q.description, q.tool_call));
@@ -508,30 +889,156 @@ impl Supervisor {
             }
         }
 
+        // Custom agent types take over the candidate slot of their base type
+        let custom_agent = self.agent_types.match_query(query);
+        if let Some(ref custom) = custom_agent {
+            info!("Custom agent type selected: {} (base {})", custom.name, custom.base);
+            routing_decision.candidate_agents.retain(|a| *a != custom.base);
+            routing_decision.candidate_agents.insert(0, custom.base);
+        }
+        self.emit_turn_event(TurnEvent::RoutingDecided {
+            agents: routing_decision.candidate_agents.clone(),
+            model: routing_decision.scale.target_model.clone(),
+            reasoning_required: routing_decision.reasoning_required,
+            reason: routing_decision.reason.clone(),
+        });
+
+        let escalation = self.profile.escalation.clone();
         let mut current_scale = routing_decision.scale.clone();
+        escalation.apply_override(&mut current_scale);
         let mut final_res: Option<AgentResponse> = None;
         let mut final_performer = String::new();
         let final_routing = routing_decision.clone();
-        let mut final_winner_idx = 0;
+        let mut final_selection: Option<Selection> = None;
+        let mut final_reward: Option<f32> = None;
+
+        // Mesh delegation: work needing a capability this node lacks goes to a peer that has it
+        if let Some(ref mesh) = self.mesh {
+            let lead = final_routing.candidate_agents.first().copied().unwrap_or(AgentType::GeneralChat);
+            if let Some(peer) = mesh.delegation_target(query, lead).await {
+                self.notify(&format!("🌐 Delegating to peer '{}'...", peer.name())).await;
+                // Only the query is sent; local history and memory stay on this node
+                match mesh.delegate(&peer, lead, query).await {
+                    Ok(res) if res.success => {
+                        final_performer = format!("Peer:{}", peer.name());
+                        final_res = Some(res);
+                    }
+                    Ok(res) => warn!("Peer '{}' could not complete the task, running locally: {}", peer.name(), res.answer),
+                    Err(e) => warn!("Delegation to '{}' failed, running locally: {}", peer.name(), e),
+                }
+            }
+        }
+
+        // Multi-step work routed to the planner runs as a DAG; if a step fails, the
+        // escalation loop below retries the whole query. An unfinished plan for the same
+        // goal (e.g. after a crash) is resumed from its last completed step.
+        let mut executed_plan = None;
+        if final_res.is_none() {
+            let plan = match self.resumable_plan(query).await {
+                Some(plan) => {
+                    let done = plan.steps.iter().filter(|s| s.completed).count();
+                    self.notify(&format!("🗺️ Resuming plan ({}/{} steps done)...", done, plan.steps.len())).await;
+                    Some(plan)
+                }
+                None if final_routing.candidate_agents.first() == Some(&AgentType::Planner) => {
+                    let planner = Planner::new(Ollama::default())
+                        .with_provider(self.create_cached_provider())
+                        .with_model(current_scale.target_model.clone());
+                    if planner.should_skip_planning(query) {
+                        None
+                    } else {
+                        match planner.decompose(query).await {
+                            Ok(mut plan) => {
+                                if self.profile.plan_critique {
+                                    plan = self.review_plan(&planner, plan, &current_scale.target_model).await;
+                                }
+                                self.notify(&format!("🗺️ Executing {}-step plan...", plan.steps.len())).await;
+                                Some(plan)
+                            }
+                            Err(e) => {
+                                warn!("Planning failed, executing directly: {}", e);
+                                None
+                            }
+                        }
+                    }
+                }
+                None => None,
+            };
+            if let Some(mut plan) = plan {
+                match self.execute_plan(&mut plan, &full_context, &current_scale.target_model).await {
+                    Ok(res) => {
+                        final_performer = "PlanExecutor".to_string();
+                        final_res = Some(res);
+                    }
+                    Err(e) => warn!("Plan execution failed, falling back to direct execution: {}", e),
+                }
+                executed_plan = Some(plan);
+            }
+        }
 
         // SOTA: Escalation Loop (FPF Principle C.18.2)
         // If execution fails, escalate to a stronger model and retry.
-        for attempt in 0..3 {
+        let mut paused_checkpoint = None;
+        for attempt in 0..escalation.max_attempts.max(1) {
+            if final_res.as_ref().is_some_and(|r| r.success || r.pending_approval.is_some()) {
+                break;
+            }
+            let over_budget = self.budget.check(&budget_mark).err()
+                .or_else(|| escalation.cost_exceeded(self.cost_tracker.summary_since(usage_mark).cost_usd));
+            if let Some(cap) = over_budget {
+                self.notify(&format!("\n💸 {}; not escalating further.\n", cap)).await;
+                if final_res.is_none() {
+                    final_res = Some(AgentResponse::failure(cap, Vec::new(), AgentType::GeneralChat));
+                }
+                break;
+            }
             if attempt > 0 {
-                let _ = self.provider.notify(&format!("\n⚠️ Task failed with {}. Escalating to next intelligence tier...\n", current_scale.target_model)).await;
-                let next_class = current_scale.class.escalate();
-                if next_class == current_scale.class && attempt > 0 { break; } // Already at intelligence ceiling
-                current_scale = ScaleProfile::new_with_class(next_class, 8.0); // Use class override
+                let routing = ROUTING.current();
+                let next_class = routing.escalate(current_scale.class);
+                if next_class == current_scale.class { break; } // Already at intelligence ceiling
+                let mut next_scale = escalation.scale_for(next_class, 8.0); // Use class override
+                // Don't burn an attempt timing out on a tier whose model is known to be down
+                while !self.circuits.is_available(&next_scale.target_model) {
+                    let skip_to = routing.escalate(next_scale.class);
+                    if skip_to == next_scale.class { break; }
+                    info!("Skipping tier {:?}: circuit open for {}", next_scale.class, next_scale.target_model);
+                    next_scale = escalation.scale_for(skip_to, 8.0);
+                }
+                if !escalation.allows(&next_scale.target_model) {
+                    self.notify(&format!("\n🔒 Privacy mode: not escalating to hosted model {}.\n", next_scale.target_model)).await;
+                    break;
+                }
+                self.notify(&format!("\n⚠️ Task failed with {}. Escalating to next intelligence tier...\n", current_scale.target_model)).await;
+                crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::Escalation, "supervisor", serde_json::json!({
+                    "attempt": attempt + 1,
+                    "from_model": current_scale.target_model,
+                    "to_model": next_scale.target_model,
+                    "to_class": next_scale.class.key(),
+                }));
+                let backoff = escalation.backoff(attempt);
+                if !backoff.is_zero() {
+                    tokio::time::sleep(backoff).await;
+                }
+                current_scale = next_scale;
             }
+            let checkpoint = self.checkpoint(format!("attempt {}", attempt + 1)).await;
 
             let mut portfolio = ResultPortfolio::default();
             let mut execution_tasks = Vec::new();
             
             for &agent_type in &final_routing.candidate_agents {
-                let mut config = AgentConfig::new(agent_type, &self.profile);
+                let custom = custom_agent.as_ref().filter(|c| c.base == agent_type);
+                let mut config = match custom {
+                    Some(c) => c.to_config(&self.profile),
+                    None => AgentConfig::new(agent_type, &self.profile),
+                };
                 
                 // SOTA: Agent-specific model overrides
-                config.model = if agent_type == AgentType::Coder {
+                config.model = if let Some(model) = custom.and_then(|c| c.model.clone()) {
+                    model
+                } else if let Some(route) = ROUTING.current().agent_route(agent_type) {
+                    route.model.clone()
+                } else if agent_type == AgentType::Coder {
                     let registry_file = std::fs::File::open("config/agency_models.json").ok();
                     let coder_model = registry_file.and_then(|f| {
                         let v: serde_json::Value = serde_json::from_reader(f).ok()?;
@@ -543,9 +1050,21 @@ impl Supervisor {
                 };
 
                 config.reasoning_enabled = final_routing.reasoning_required;
+                config.constrained_decoding = matches!(
+                    std::env::var("AGENCY_PROVIDER").unwrap_or_default().to_lowercase().as_str(),
+                    "llamacpp" | "llama.cpp" | "llama-server"
+                );
                 let _ = self.provider.notify(&format!("STATE:MODEL:{}", config.model)).await;
+                self.emit_turn_event(TurnEvent::StepStarted {
+                    agent: agent_type,
+                    model: config.model.clone(),
+                    description: format!("Attempt {}", attempt + 1),
+                });
                 
                 let provider = self.create_cached_provider();
+                let turn_events = self.turn_events();
+                let turn_id = self.turn_id.clone();
+                let event_turn = turn_id.clone();
                 let query_owned = query.to_string();
                 let context_owned = full_context.clone();
                 let semaphore = self.concurrency_limit.clone();
@@ -555,20 +1074,24 @@ impl Supervisor {
                 let hooks = self.pai_hooks.clone();
                 let pai_mem = self.pai_memory.clone();
                 let recovery = self.recovery.clone();
+                let fallbacks = self.fallback_providers.clone();
                 
                 let (steer_tx, steer_rx) = mpsc::channel(10);
                 self.active_steer_txs.lock().await.push(steer_tx);
 
-                execution_tasks.push(tokio::spawn(async move {
+                // Spawned tasks do not inherit the current span; attach the step explicitly
+                let step_span = tracing::info_span!("step", agent = %agent_type, model = %config.model, attempt = attempt + 1);
+                execution_tasks.push(KILL_SWITCH.spawn(in_turn(event_turn, async move {
                     let _permit = semaphore.acquire().await.ok();
                     let mut agent = ReActAgent::new_with_provider(provider, config, tools)
+                        .with_fallback_providers(fallbacks)
                         .with_hooks(hooks)
                         .with_memory_manager(pai_mem)
                         .with_recovery(recovery);
                     if let Some(ref memory) = memory { agent = agent.with_memory(memory.clone()); }
-                    agent = agent.with_safety(safety);
+                    agent = agent.with_safety(safety).with_turn_events(turn_events).with_turn_id(turn_id);
                     agent.execute_with_steering(&query_owned, Some(&context_owned), Some(steer_rx)).await
-                }));
+                }.instrument(step_span))));
             }
 
             let task_results = join_all(execution_tasks).await;
@@ -609,10 +1132,40 @@ impl Supervisor {
                 }
             }
 
+            // Every scored candidate is training data for the reward model
+            {
+                let mut buffer = self.experience_buffer.lock().await;
+                for (candidate, response) in portfolio.candidates.iter().zip(&responses) {
+                    let reward = candidate.reward_score.unwrap_or(candidate.quality_score);
+                    buffer.record(Experience {
+                        query: query.to_string(),
+                        steps: response.steps.clone(),
+                        answer: response.answer.clone(),
+                        total_reward: reward,
+                        extrinsic_reward: reward,
+                        intrinsic_reward: 0.0,
+                        turn_id: self.turn_id.clone(),
+                        feedback: None,
+                    });
+                }
+            }
+
             if !responses.is_empty() {
-                let winner_idx = Gamma::select_pareto_winner(&portfolio).unwrap_or(0);
+                let strategy = self.profile.aggregation;
+                let judge_scores = if strategy == AggregationStrategy::LlmJudge {
+                    let _ = self.provider.notify("STATE:AGGREGATION:JUDGING").await;
+                    let judge_model = ScaleProfile::new_with_class(ScaleClass::Standard, 8.0).target_model;
+                    LLMRewardModel::new(self.provider.clone(), judge_model).score(query, &portfolio.candidates).await
+                        .map_err(|e| warn!("LLM judge failed: {}", e))
+                        .ok()
+                } else {
+                    None
+                };
+                let selection = Gamma::select(strategy, &portfolio, judge_scores.as_deref());
+                let winner_idx = selection.as_ref().map(|s| s.index).unwrap_or(0);
+                final_selection = selection;
+                final_reward = portfolio.candidates.get(winner_idx).and_then(|c| c.reward_score);
                 let winner_res = responses[winner_idx].clone();
-                final_winner_idx = winner_idx;
                 
                 if winner_res.success {
                     final_res = Some(winner_res);
@@ -622,6 +1175,7 @@ impl Supervisor {
                     // HITL Pause
                     final_res = Some(winner_res);
                     final_performer = format!("{:?}", final_routing.candidate_agents[winner_idx]);
+                    paused_checkpoint = checkpoint;
                     break; 
                 } else { 
                     // All candidates in this tier failed, continue loop to escalate
@@ -629,9 +1183,36 @@ impl Supervisor {
                     final_performer = format!("{:?}", final_routing.candidate_agents[winner_idx]);
                 }
             }
+            // The next tier starts from the state before this failed attempt
+            if let Some(ref checkpoint) = checkpoint {
+                self.rollback(checkpoint).await;
+            }
         }
 
-        let final_res = final_res.ok_or_else(|| AgentError::Execution("All execution attempts and escalations failed".to_string()))?;
+        let mut final_res = final_res.ok_or_else(|| AgentError::Execution("All execution attempts and escalations failed".to_string()))?;
+        // Screened before it reaches memory, the UI or the speaker
+        let moderation = crate::safety::OUTPUT_MODERATOR.moderate(&final_res.answer);
+        final_res.answer = moderation.text;
+        // Credit the routing decision; later user ratings arrive through `record_feedback`
+        if let (Some(turn_id), Some(context), Some(&agent)) = (&self.turn_id, &final_routing.context, final_routing.candidate_agents.first()) {
+            if !final_performer.starts_with("Peer:") && final_res.pending_approval.is_none() {
+                ROUTE_LEARNER.record_outcome(RouteRecord {
+                    turn_id: turn_id.clone(),
+                    context: context.clone(),
+                    agent,
+                    class: final_routing.scale.class,
+                    escalated: current_scale.class != final_routing.scale.class,
+                    outcome: final_reward.unwrap_or(if final_res.success { 1.0 } else { 0.0 }),
+                    feedback: None,
+                });
+            }
+        }
+        // The query was answered without finishing the plan, so there is nothing left to resume
+        if final_res.success && executed_plan.as_ref().is_some_and(|p| !p.is_complete) {
+            if let Some(ref sm) = self.session {
+                let _ = sm.save_active_plan(None).await;
+            }
+        }
         let latency_ms = _work_start_time.elapsed().as_millis();
 
         // Emit FPF-Aligned Publication Characteristics (E.17.5.5)
@@ -669,13 +1250,17 @@ impl Supervisor {
             Some(square), 
             None, 
             None
-        ).with_mvpk(final_res.thought.clone(), final_res.reliability);
+        ).with_mvpk(final_res.thought.clone(), final_res.reliability)
+        .with_usage(self.cost_tracker.summary_since(usage_mark));
         
         publication.rationale = Some(DesignRationaleRecord::new(
             "Supervisor", 
             "Routed", 
-            format!("Selected candidate {} based on Pareto logic", final_winner_idx)
+            final_selection.as_ref().map(|s| s.rationale.clone()).unwrap_or_else(|| "Selected candidate 0 based on Pareto logic".to_string())
         ));
+        publication = publication.with_aggregation(final_selection)
+            .with_redactions(self.turn_id.as_deref().and_then(|turn| crate::safety::PII_SCRUBBER.take_report(turn)))
+            .with_moderation(moderation.flagged);
 
         // Only add to memory if it's NOT a pending approval
         if final_res.pending_approval.is_none() {
@@ -686,14 +1271,27 @@ impl Supervisor {
 
             if let Some(ref sm) = self.session {
                 let mem = self.episodic_memory.lock().await;
-                sm.save(&mem, None).await.map_err(|e| AgentError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+                sm.save(&mem, executed_plan.as_ref()).await.map_err(|e| AgentError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
             }
+        } else if let Some(ref approval) = final_res.pending_approval {
+            // Queued so the dashboard, desktop app or API can decide and resume the turn
+            self.approvals.add_paused(approval.clone(), query, self.conversation_id.clone(), final_res.paused.clone()).await.map_err(|e| AgentError::Execution(e.to_string()))?;
+            emit_event!(AgencyEvent::ApprovalRequested { id: approval.id.clone(), tool: approval.tool_name.clone() });
         }
+        // The journal is kept for a paused attempt so a rejection can still revert it
+        match (final_res.pending_approval.as_ref(), paused_checkpoint) {
+            (Some(approval), Some(checkpoint)) => {
+                self.paused_checkpoints.lock().await.insert(approval.id.clone(), checkpoint);
+            }
+            _ => self.tools.journal().forget(&session_id).await,
+        }
+
+        self.budget.report(&budget_mark);
 
         Ok(SupervisorResult {
             answer: final_res.answer,
             success: final_res.success,
-            plan: None,
+            plan: executed_plan,
             reflections: vec![format!("Classified as {:?}", routing_decision.scale.class)],
             publication: Some(publication),
             pending_approval: final_res.pending_approval,
@@ -701,6 +1299,205 @@ impl Supervisor {
         })
     }
 
+    /// Run a plan's steps as a DAG, each on its own agent with its dependencies' outputs as context.
+    /// The steps are staffed as a team whose hand-off contracts come from the role algebra.
+    /// One Reviewer critique of a fresh plan; a rejected plan is refined once by the Planner.
+    /// Review or refinement failures keep the original plan.
+    async fn review_plan(&self, planner: &Planner, plan: Plan, model: &str) -> Plan {
+        let reviewer = PlanReviewer::new(self.create_cached_provider(), model)
+            .with_system_prompt(AgentConfig::new(AgentType::Reviewer, &self.profile).system_prompt);
+        let tools = self.tools.tool_names().await;
+        let critique = match reviewer.critique(&plan, &tools).await {
+            Ok(critique) => critique,
+            Err(e) => {
+                warn!("Plan review failed, executing unreviewed plan: {}", e);
+                return plan;
+            }
+        };
+        if critique.approved {
+            return plan;
+        }
+
+        self.notify(&format!("🧐 Plan review found {} issue(s); revising...", critique.issues.len())).await;
+        match planner.refine(&plan, &critique.feedback()).await {
+            Ok(refined) if !refined.steps.is_empty() => refined,
+            Ok(_) => plan,
+            Err(e) => {
+                warn!("Plan refinement failed, executing original plan: {}", e);
+                plan
+            }
+        }
+    }
+
+    pub async fn execute_plan(&self, plan: &mut Plan, context: &str, model: &str) -> AgentResult<AgentResponse> {
+        let mut executor = PlanExecutor::new(self.concurrency_limit.clone());
+        if let Some(ref sm) = self.session {
+            executor = executor.with_session(sm.clone());
+        }
+        let team = Team::assemble(plan, &self.role_algebra);
+        if team.members.len() > 1 {
+            self.notify(&format!("👥 Team: {} ({} hand-offs)", team.roster(), team.handoffs.len())).await;
+        }
+        let goal = plan.goal.clone();
+        executor.execute(plan, |step, dependency_outputs| {
+            let mut config = AgentConfig::new(step.agent_type, &self.profile);
+            config.model = model.to_string();
+            let step_span = tracing::info_span!("step", agent = %step.agent_type, model = %config.model, step = step.step_num);
+            self.emit_turn_event(TurnEvent::StepStarted {
+                agent: step.agent_type,
+                model: config.model.clone(),
+                description: format!("Step {}: {}", step.step_num, step.description),
+            });
+            let agent = ReActAgent::new_with_provider(self.create_cached_provider(), config, self.tools.clone())
+                .with_fallback_providers(self.fallback_providers.clone())
+                .with_hooks(self.pai_hooks.clone())
+                .with_memory_manager(self.pai_memory.clone())
+                .with_recovery(self.recovery.clone())
+                .with_safety(self.safety.clone())
+                .with_turn_events(self.turn_events())
+                .with_turn_id(self.turn_id.clone());
+            let agent = match self.memory {
+                Some(ref memory) => agent.with_memory(memory.clone()),
+                None => agent,
+            };
+            let mut task = format!("Overall goal: {}\n\nYour step: {}\nExpected output: {}", goal, step.description, step.expected_output);
+            let briefing = team.briefing(&step);
+            if !briefing.is_empty() {
+                task.push_str(&format!("\n\nTeam: {}\n{}", team.roster(), briefing));
+            }
+            let step_context = format!("{}\n{}", context, dependency_outputs);
+            // Steps run on spawned tasks, outside the turn's task-local
+            in_turn(self.turn_id.clone(), async move { agent.execute(&task, Some(&step_context)).await }.instrument(step_span))
+        }).await
+    }
+
+    /// Allow the queued tool call and continue the turn that stopped at it. A turn whose
+    /// loop state was saved resumes at the approved call; otherwise the query is re-run.
+    pub async fn approve(&mut self, id: &str) -> AgentResult<SupervisorResult> {
+        // The approval stays queued while halted
+        KILL_SWITCH.check()?;
+        let entry = self.approvals.resolve(id, true).await
+            .map_err(|e| AgentError::Execution(e.to_string()))?
+            .ok_or_else(|| AgentError::Validation(format!("No pending approval with id {}", id)))?;
+        info!("Approval {} granted for {}", id, entry.request.tool_name);
+        // The approved call runs in a new turn; the paused one is final
+        if let Some(checkpoint) = self.paused_checkpoints.lock().await.remove(id) {
+            self.tools.journal().forget(&checkpoint.turn_id).await;
+        }
+        self.safety.lock().await.approve_call(&entry.request.tool_name, &entry.request.parameters);
+        match entry.paused {
+            Some(paused) => {
+                let turn_id = uuid::Uuid::new_v4().to_string();
+                let budget = self.budget.clone();
+                in_turn(Some(turn_id.clone()), budget.metered(self.resume_paused(&entry.query, paused, turn_id))).await
+            }
+            None => self.handle(&entry.query).await,
+        }
+    }
+
+    /// Run a paused ReAct loop on from its held-back calls, as a turn of its own
+    async fn resume_paused(&mut self, query: &str, paused: PausedReAct, turn_id: String) -> AgentResult<SupervisorResult> {
+        let usage_mark = self.cost_tracker.mark();
+        self.turn_id = Some(turn_id.clone());
+        info!("Resuming paused {} loop for \"{}\"", paused.config.agent_type, query);
+        let _ = self.provider.notify(&format!("STATE:MODEL:{}", paused.config.model)).await;
+
+        let mut work = crate::orchestrator::WorkRecord::new("Supervisor".to_string(), paused.config.agent_type.to_string());
+        let mut scale = ScaleProfile::new_with_class(ScaleClass::Standard, 8.0);
+        scale.target_model = paused.config.model.clone();
+        let checkpoint = self.checkpoint("resumed after approval").await;
+
+        let agent = ReActAgent::new_with_provider(self.create_cached_provider(), paused.config.clone(), self.tools.clone())
+            .with_fallback_providers(self.fallback_providers.clone())
+            .with_hooks(self.pai_hooks.clone())
+            .with_memory_manager(self.pai_memory.clone())
+            .with_recovery(self.recovery.clone())
+            .with_safety(self.safety.clone())
+            .with_turn_events(self.turn_events())
+            .with_turn_id(self.turn_id.clone());
+        let agent = match self.memory {
+            Some(ref memory) => agent.with_memory(memory.clone()),
+            None => agent,
+        };
+        let mut res = agent.resume(paused).await?;
+        let moderation = crate::safety::OUTPUT_MODERATOR.moderate(&res.answer);
+        res.answer = moderation.text;
+
+        work.trace = res.steps.clone();
+        work.complete(res.success, crate::orchestrator::AssuranceLevel::L1);
+        let publication = Publication::project(res.answer.clone(), &work, scale, None, None, None)
+            .with_mvpk(res.thought.clone(), res.reliability)
+            .with_usage(self.cost_tracker.summary_since(usage_mark))
+            .with_approval(res.pending_approval.clone())
+            .with_redactions(crate::safety::PII_SCRUBBER.take_report(&turn_id))
+            .with_moderation(moderation.flagged);
+
+        match (res.pending_approval.as_ref(), checkpoint) {
+            // Stopped at another call that needs a decision
+            (Some(approval), checkpoint) => {
+                self.approvals.add_paused(approval.clone(), query, self.conversation_id.clone(), res.paused.clone()).await
+                    .map_err(|e| AgentError::Execution(e.to_string()))?;
+                emit_event!(AgencyEvent::ApprovalRequested { id: approval.id.clone(), tool: approval.tool_name.clone() });
+                if let Some(checkpoint) = checkpoint {
+                    self.paused_checkpoints.lock().await.insert(approval.id.clone(), checkpoint);
+                }
+            }
+            (None, _) => {
+                let performer = res.agent_type.to_string();
+                self.episodic_memory.lock().await.add_assistant(&res.answer, Some(performer.clone()));
+                let _ = self.history_manager.append(&turn_id, "assistant", Some(&performer), &res.answer).await;
+                self.tools.journal().forget(&turn_id).await;
+            }
+        }
+
+        Ok(SupervisorResult {
+            answer: res.answer,
+            success: res.success,
+            plan: None,
+            reflections: vec!["Resumed after approval".to_string()],
+            publication: Some(publication),
+            pending_approval: res.pending_approval,
+            has_followup: !self.followup_queue.lock().await.is_empty(),
+        })
+    }
+
+    /// Refuse the queued tool call; the refusal is noted in the conversation
+    pub async fn reject(&mut self, id: &str) -> AgentResult<()> {
+        let entry = self.approvals.resolve(id, false).await
+            .map_err(|e| AgentError::Execution(e.to_string()))?
+            .ok_or_else(|| AgentError::Validation(format!("No pending approval with id {}", id)))?;
+        info!("Approval {} rejected for {}", id, entry.request.tool_name);
+        let paused = self.paused_checkpoints.lock().await.remove(id);
+        if let Some(checkpoint) = paused {
+            let report = checkpoint.revert_side_effects(&self.tools).await;
+            self.tools.journal().forget(&checkpoint.turn_id).await;
+            self.report_rollback(&checkpoint.label, report).await;
+        }
+        self.episodic_memory.lock().await.add_system(format!(
+            "The user rejected the {} call requested for \"{}\". Do not retry it without asking.",
+            entry.request.tool_name, entry.query
+        ));
+        Ok(())
+    }
+
+    /// The checkpointed plan for `goal`, if a previous run stopped before finishing it
+    async fn resumable_plan(&self, goal: &str) -> Option<Plan> {
+        let sm = self.session.as_ref()?;
+        sm.active_plan().await.ok().flatten().filter(|p| p.goal == goal && !p.is_complete)
+    }
+
+    /// Continue the checkpointed plan from its last completed step; `None` if there is none
+    pub async fn resume_plan(&mut self) -> AgentResult<Option<SupervisorResult>> {
+        let plan = match self.session {
+            Some(ref sm) => sm.active_plan().await.map_err(|e| AgentError::Execution(e.to_string()))?,
+            None => None,
+        };
+        match plan {
+            Some(plan) => self.handle(&plan.goal).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// Internal logic for A2A (Agent-to-Agent) direct requests
     pub async fn handle_peer_request(
         &mut self, 
@@ -727,15 +1524,73 @@ impl Supervisor {
     }
 
     pub async fn run_autonomous(&mut self, goal: &str) -> AgentResult<SupervisorResult> {
+        self.run_autonomous_with_budget(goal, ResourceBudget::default()).await
+    }
+
+    /// Run a spawned sub-agent under its own turn id, within what is left of its spawn tree's
+    /// budget, and record the result for the parent turn
+    async fn run_subagent(&mut self, task_id: &str, goal: &str) -> AgentResult<()> {
+        let Some(run) = SUBAGENTS.start(task_id) else {
+            let record = SUBAGENTS.get(task_id);
+            let answer = record.as_ref().and_then(|r| r.answer.clone()).unwrap_or_default();
+            warn!("Supervisor Worker: Sub-agent {} not started: {}", task_id, answer);
+            emit_event!(AgencyEvent::SubAgentFinished {
+                task_id: task_id.to_string(),
+                parent_turn: record.and_then(|r| r.parent_turn),
+                success: false,
+                answer,
+            });
+            return Ok(());
+        };
+        info!("Supervisor Worker: Running sub-agent {} ({}): {}", task_id, run.turn_id, goal);
+
+        let mark = self.budget.mark();
+        let result = in_turn(Some(run.turn_id.clone()), self.run_autonomous_with_budget(goal, run.budget)).await;
+        let usage = self.budget.usage_since(&mark);
+        let (success, answer) = match &result {
+            Ok(result) => (result.success, result.answer.clone()),
+            Err(e) => (false, e.to_string()),
+        };
+        let record = SUBAGENTS.finish(task_id, success, answer.clone(), usage);
+        emit_event!(AgencyEvent::SubAgentFinished {
+            task_id: task_id.to_string(),
+            parent_turn: record.and_then(|r| r.parent_turn),
+            success,
+            answer,
+        });
+        result.map(|_| ())
+    }
+
+    /// Autonomous run limited to `budget.max_cycles` iterations and the budget's token and time
+    /// caps, on top of the supervisor's own turn and session budgets
+    pub async fn run_autonomous_with_budget(&mut self, goal: &str, budget: ResourceBudget) -> AgentResult<SupervisorResult> {
+        let usage_mark = self.cost_tracker.mark();
+        let budget_mark = self.budget.mark();
+        let run_limits = crate::orchestrator::BudgetLimits {
+            max_tokens: budget.max_tokens.map(u64::from),
+            max_seconds: Some(budget.max_time_seconds),
+            ..Default::default()
+        };
+        let cycles = budget.max_cycles;
         let provider = self.create_cached_provider();
-        let objective = Objective::new(goal);
+        let objective = Objective::new(goal).with_budget(budget);
         let mut machine = AutonomousMachine::new_with_provider(provider.clone(), self.tools.clone(), &self.profile, objective);
         machine = machine.with_provider(provider);
         
         let mut last_res = AgentResponse::failure("Autonomous loop failed to start", Vec::new(), AgentType::Coder);
-        for i in 0..5 {
-            info!("Autonomous iteration {}/5", i + 1);
-            match machine.run_iteration().await {
+        for i in 0..cycles {
+            let exhausted = self.budget.check(&budget_mark).err().or_else(|| {
+                run_limits.exceeded(&self.budget.usage_since(&budget_mark)).map(|cap| format!("Goal budget exhausted: {}", cap))
+            });
+            if let Some(cap) = exhausted {
+                warn!("Autonomous run stopped: {}", cap);
+                if i == 0 {
+                    last_res = AgentResponse::failure(cap, Vec::new(), AgentType::Coder);
+                }
+                break;
+            }
+            info!("Autonomous iteration {}/{}", i + 1, cycles);
+            match self.budget.metered(machine.run_iteration()).await {
                 Ok(res) => {
                     let success = res.success;
                     last_res = res;
@@ -759,6 +1614,9 @@ impl Supervisor {
             }
         }
 
+        let moderation = crate::safety::OUTPUT_MODERATOR.moderate(&last_res.answer);
+        last_res.answer = moderation.text;
+
         let heavy_profile = crate::orchestrator::ScaleProfile::new(0.9, 8.0);
         let publication = Publication::project(
             last_res.answer.clone(), 
@@ -767,7 +1625,11 @@ impl Supervisor {
             Some(square), 
             None, 
             None
-        ).with_mvpk(last_res.thought.clone(), last_res.reliability);
+        ).with_mvpk(last_res.thought.clone(), last_res.reliability)
+        .with_usage(self.cost_tracker.summary_since(usage_mark))
+        .with_redactions(crate::orchestrator::event_bus::current_turn().and_then(|turn| crate::safety::PII_SCRUBBER.take_report(&turn)))
+        .with_moderation(moderation.flagged);
+        self.budget.report(&budget_mark);
         
         Ok(SupervisorResult {
             answer: last_res.answer,
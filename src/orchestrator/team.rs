@@ -0,0 +1,179 @@
+//! Plan Teams
+//!
+//! A plan's steps are staffed by a temporary team, one member per agent role. Every
+//! dependency between steps becomes a hand-off contract: what the producing role must
+//! deliver and what the receiving role does with it. `RoleAlgebra` decides the kind of
+//! hand-off — output passed between incompatible roles (Coder ⊥ Reviewer) must be checked
+//! independently rather than built on — and whether the plan needs a Reviewer at all.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::AgentType;
+use crate::orchestrator::{Plan, PlanStep, RoleAlgebra};
+
+const REVIEWER_ROLE: &str = "Reviewer";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandOffKind {
+    /// The receiver continues from the delivered output
+    Build,
+    /// The receiver holds a role incompatible with the producer's and must verify the output
+    Review,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandOff {
+    pub from_step: usize,
+    pub to_step: usize,
+    pub from: AgentType,
+    pub to: AgentType,
+    pub kind: HandOffKind,
+    /// What the producing step promised (its expected output)
+    pub deliverable: String,
+}
+
+impl HandOff {
+    pub fn contract(&self) -> String {
+        match self.kind {
+            HandOffKind::Build => format!(
+                "{} (step {}) hands {} (step {}): {}. Build on it; do not redo it.",
+                self.from, self.from_step, self.to, self.to_step, self.deliverable
+            ),
+            HandOffKind::Review => format!(
+                "{} (step {}) hands {} (step {}): {}. Verify it independently; report every defect and do not trust its claims unchecked.",
+                self.from, self.from_step, self.to, self.to_step, self.deliverable
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    /// One member per role, in order of first step
+    pub members: Vec<AgentType>,
+    pub handoffs: Vec<HandOff>,
+}
+
+impl Team {
+    /// Staff `plan`. When a member's work requires independent review and no step is
+    /// assigned to a Reviewer, a final review step depending on the plan's last steps is
+    /// appended, so its output becomes the plan's answer.
+    pub fn assemble(plan: &mut Plan, algebra: &RoleAlgebra) -> Self {
+        let needs_review = plan.steps.iter()
+            .any(|s| algebra.is_incompatible(&s.agent_type.to_string(), REVIEWER_ROLE));
+        let has_reviewer = plan.steps.iter().any(|s| s.agent_type == AgentType::Reviewer);
+        if needs_review && !has_reviewer {
+            let sinks: Vec<usize> = plan.steps.iter()
+                .filter(|s| !plan.steps.iter().any(|other| other.depends_on.contains(&s.step_num)))
+                .map(|s| s.step_num)
+                .collect();
+            let step_num = plan.steps.iter().map(|s| s.step_num).max().unwrap_or(0) + 1;
+            plan.steps.push(PlanStep {
+                step_num,
+                description: "Review the team's work against its hand-off contracts".to_string(),
+                agent_type: AgentType::Reviewer,
+                suggested_tools: Vec::new(),
+                expected_output: "The final answer, corrected where the review found defects".to_string(),
+                depends_on: sinks,
+                completed: false,
+                output: None,
+            });
+            plan.is_complete = false;
+        }
+
+        let mut members = Vec::new();
+        for step in &plan.steps {
+            if !members.contains(&step.agent_type) {
+                members.push(step.agent_type);
+            }
+        }
+
+        let handoffs = plan.steps.iter()
+            .flat_map(|step| step.depends_on.iter().filter_map(move |dep| {
+                let producer = plan.steps.iter().find(|s| s.step_num == *dep)?;
+                let kind = if algebra.is_incompatible(&producer.agent_type.to_string(), &step.agent_type.to_string()) {
+                    HandOffKind::Review
+                } else {
+                    HandOffKind::Build
+                };
+                Some(HandOff {
+                    from_step: producer.step_num,
+                    to_step: step.step_num,
+                    from: producer.agent_type,
+                    to: step.agent_type,
+                    kind,
+                    deliverable: producer.expected_output.clone(),
+                })
+            }))
+            .collect();
+
+        Self { members, handoffs }
+    }
+
+    /// e.g. "Researcher + Coder + Reviewer"
+    pub fn roster(&self) -> String {
+        self.members.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" + ")
+    }
+
+    /// Contracts `step` receives and owes, for its task prompt; empty for a step with no hand-offs
+    pub fn briefing(&self, step: &PlanStep) -> String {
+        let incoming: Vec<String> = self.handoffs.iter().filter(|h| h.to_step == step.step_num).map(|h| h.contract()).collect();
+        let outgoing: Vec<String> = self.handoffs.iter().filter(|h| h.from_step == step.step_num).map(|h| h.contract()).collect();
+        let mut briefing = String::new();
+        if !incoming.is_empty() {
+            briefing.push_str(&format!("Hand-offs you receive:\n- {}\n", incoming.join("\n- ")));
+        }
+        if !outgoing.is_empty() {
+            briefing.push_str(&format!("Hand-offs you owe:\n- {}\n", outgoing.join("\n- ")));
+        }
+        briefing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(step_num: usize, agent_type: AgentType, depends_on: Vec<usize>) -> PlanStep {
+        PlanStep {
+            step_num,
+            description: format!("step {}", step_num),
+            agent_type,
+            suggested_tools: Vec::new(),
+            expected_output: format!("output {}", step_num),
+            depends_on,
+            completed: false,
+            output: None,
+        }
+    }
+
+    #[test]
+    fn test_assemble_adds_reviewer_and_contracts() {
+        let algebra = RoleAlgebra::new();
+        let mut plan = Plan::new("ship the parser");
+        plan.steps = vec![step(1, AgentType::Researcher, vec![]), step(2, AgentType::Coder, vec![1])];
+
+        let team = Team::assemble(&mut plan, &algebra);
+        assert_eq!(team.roster(), "Researcher + Coder + Reviewer");
+        assert_eq!(plan.steps[2].depends_on, vec![2]);
+        assert_eq!(team.handoffs.len(), 2);
+        assert_eq!(team.handoffs[0].kind, HandOffKind::Build);
+        assert_eq!(team.handoffs[1].kind, HandOffKind::Review);
+        assert!(team.briefing(&plan.steps[1]).contains("Hand-offs you owe"));
+
+        // Assembling a resumed plan does not add a second reviewer
+        let again = Team::assemble(&mut plan, &algebra);
+        assert_eq!(plan.steps.len(), 3);
+        assert_eq!(again, team);
+    }
+
+    #[test]
+    fn test_no_review_without_separation_of_duties() {
+        let mut plan = Plan::new("summarize the news");
+        plan.steps = vec![step(1, AgentType::Researcher, vec![]), step(2, AgentType::GeneralChat, vec![1])];
+        let team = Team::assemble(&mut plan, &RoleAlgebra::new());
+        assert_eq!(team.members, vec![AgentType::Researcher, AgentType::GeneralChat]);
+        assert_eq!(plan.steps.len(), 2);
+    }
+}
@@ -0,0 +1,190 @@
+//! Turn Events
+//!
+//! Typed progress of one Supervisor turn, produced by `Supervisor::handle_stream`. Front-ends
+//! consume these instead of the string prefixes (`TOKEN:`, `STATE:`, `FINAL_ANSWER:`) that the
+//! broadcast channel carries for the legacy dashboard.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::agent::{AgentType, ChatMessage, GenerationOptions, LLMProvider};
+use crate::orchestrator::{Publication, SupervisorResult};
+use crate::safety::ApprovalRequest;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "payload")]
+pub enum TurnEvent {
+    /// The router picked the candidate agents and model tier for the turn
+    RoutingDecided { agents: Vec<AgentType>, model: String, reasoning_required: bool, reason: String },
+    /// An agent started on the query (one per candidate and escalation attempt) or on a plan step
+    StepStarted { agent: AgentType, model: String, description: String },
+    /// A tool call returned; `observation` is what the agent sees (truncated)
+    ToolObservation { tool: String, success: bool, observation: String },
+    /// Generated text as it streams from the model
+    TokenChunk { text: String },
+    /// Human-readable progress (escalation, delegation, plan execution)
+    Status { message: String },
+    /// The turn finished; always the last event unless the turn failed with `Error`
+    FinalAnswer {
+        answer: String,
+        success: bool,
+        pending_approval: Option<ApprovalRequest>,
+        publication: Option<Publication>,
+    },
+    Error { message: String },
+}
+
+impl TurnEvent {
+    pub fn final_answer(result: &SupervisorResult) -> Self {
+        Self::FinalAnswer {
+            answer: result.answer.clone(),
+            success: result.success,
+            pending_approval: result.pending_approval.clone(),
+            publication: result.publication.clone(),
+        }
+    }
+}
+
+/// Sends streamed tokens and notifications of the wrapped provider as `TurnEvent`s
+pub struct TurnEventProvider {
+    inner: Arc<dyn LLMProvider>,
+    tx: mpsc::UnboundedSender<TurnEvent>,
+}
+
+impl TurnEventProvider {
+    pub fn new(inner: Arc<dyn LLMProvider>, tx: mpsc::UnboundedSender<TurnEvent>) -> Self {
+        Self { inner, tx }
+    }
+
+    fn publish(&self, stream: BoxStream<'static, Result<String>>) -> BoxStream<'static, Result<String>> {
+        let tx = self.tx.clone();
+        Box::pin(stream.inspect(move |chunk| {
+            if let Ok(text) = chunk {
+                let _ = tx.send(TurnEvent::TokenChunk { text: text.clone() });
+            }
+        }))
+    }
+}
+
+#[async_trait]
+impl LLMProvider for TurnEventProvider {
+    async fn generate(&self, model: &str, prompt: String, system: Option<String>) -> Result<String> {
+        self.generate_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<BoxStream<'static, Result<String>>> {
+        self.generate_stream_with_options(model, prompt, system, &GenerationOptions::default()).await
+    }
+
+    async fn generate_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_stream_with_options(model, prompt, system, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
+        }
+        Ok(full_text)
+    }
+
+    async fn generate_stream_with_options(&self, model: &str, prompt: String, system: Option<String>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let stream = self.inner.generate_stream_with_options(model, prompt, system, options).await?;
+        Ok(self.publish(stream))
+    }
+
+    async fn generate_chat(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<String> {
+        let mut stream = self.generate_chat_stream(model, messages, options).await?;
+        let mut full_text = String::new();
+        while let Some(chunk) = stream.next().await {
+            full_text.push_str(&chunk?);
+        }
+        Ok(full_text)
+    }
+
+    async fn generate_chat_stream(&self, model: &str, messages: Vec<ChatMessage>, options: &GenerationOptions) -> Result<BoxStream<'static, Result<String>>> {
+        let stream = self.inner.generate_chat_stream(model, messages, options).await?;
+        Ok(self.publish(stream))
+    }
+
+    async fn count_tokens(&self, model: &str, text: &str) -> Result<usize> {
+        self.inner.count_tokens(model, text).await
+    }
+
+    async fn health_check(&self, model: &str) -> Result<()> {
+        self.inner.health_check(model).await
+    }
+
+    fn get_lock(&self) -> Arc<Mutex<()>> {
+        self.inner.get_lock()
+    }
+
+    async fn notify(&self, message: &str) -> Result<()> {
+        // `STATE:` markers only drive the legacy dashboard
+        if !message.starts_with("STATE:") {
+            let _ = self.tx.send(TurnEvent::Status { message: message.trim().to_string() });
+        }
+        self.inner.notify(message).await
+    }
+}
+
+/// Streamed model text sorted into reasoning and answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamChunk {
+    Thought(String),
+    /// The answer marker was seen; later text is answer
+    AnswerStart,
+    Answer(String),
+}
+
+/// Splits `TokenChunk` text at the first `[ANSWER]` or `ANSWER:` marker, dropping the marker
+#[derive(Debug, Default)]
+pub struct AnswerSplitter {
+    text: String,
+    answer_started: bool,
+}
+
+impl AnswerSplitter {
+    pub fn push(&mut self, chunk: &str) -> Vec<StreamChunk> {
+        self.text.push_str(chunk);
+        let mut out = Vec::new();
+        if !self.answer_started && (self.text.contains("[ANSWER]") || self.text.to_uppercase().contains("ANSWER:")) {
+            self.answer_started = true;
+            out.push(StreamChunk::AnswerStart);
+        }
+        out.push(match self.answer_started {
+            true => StreamChunk::Answer(chunk.replace("[ANSWER]", "").replace("ANSWER:", "")),
+            false => StreamChunk::Thought(chunk.to_string()),
+        });
+        out
+    }
+
+    /// Everything pushed so far
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_splitter() {
+        let mut splitter = AnswerSplitter::default();
+        assert_eq!(splitter.push("Checking the docs."), vec![StreamChunk::Thought("Checking the docs.".into())]);
+        assert_eq!(splitter.push(" [ANSWER]It is"), vec![StreamChunk::AnswerStart, StreamChunk::Answer(" It is".into())]);
+        assert_eq!(splitter.push(" 42."), vec![StreamChunk::Answer(" 42.".into())]);
+        assert_eq!(splitter.text(), "Checking the docs. [ANSWER]It is 42.");
+    }
+
+    #[test]
+    fn test_serializes_tagged() {
+        let event = TurnEvent::ToolObservation { tool: "web_search".into(), success: true, observation: "3 results".into() };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "ToolObservation");
+        assert_eq!(json["payload"]["tool"], "web_search");
+    }
+}
@@ -0,0 +1,88 @@
+//! Turn History
+//!
+//! The most recent finished turns of every conversation, with their publications, kept in
+//! memory for dashboards (the GraphQL API) to browse without replaying the event log.
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::agent::AgentResult;
+use crate::orchestrator::{Publication, SupervisorResult};
+
+/// Turns kept before the oldest are dropped
+pub const MAX_TURN_HISTORY: usize = 1000;
+
+lazy_static! {
+    /// Process-wide history written by every supervisor
+    pub static ref TURN_HISTORY: TurnHistory = TurnHistory::new(MAX_TURN_HISTORY);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnRecord {
+    pub turn_id: String,
+    /// Registry id of the conversation; `None` for the main one
+    pub conversation: Option<String>,
+    pub user: Option<String>,
+    pub query: String,
+    pub answer: String,
+    pub success: bool,
+    /// Error of a turn that failed outright
+    pub error: Option<String>,
+    pub finished_at: DateTime<Utc>,
+    pub publication: Option<Publication>,
+}
+
+impl TurnRecord {
+    pub fn new(turn_id: String, conversation: Option<String>, user: Option<String>, query: &str, result: &AgentResult<SupervisorResult>) -> Self {
+        let (answer, success, error, publication) = match result {
+            Ok(result) => (result.answer.clone(), result.success, None, result.publication.clone()),
+            Err(e) => (String::new(), false, Some(e.to_string()), None),
+        };
+        Self { turn_id, conversation, user, query: query.to_string(), answer, success, error, finished_at: Utc::now(), publication }
+    }
+}
+
+pub struct TurnHistory {
+    turns: Mutex<VecDeque<TurnRecord>>,
+    capacity: usize,
+}
+
+impl TurnHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { turns: Mutex::new(VecDeque::new()), capacity }
+    }
+
+    pub fn record(&self, turn: TurnRecord) {
+        let mut turns = self.turns.lock().unwrap_or_else(|e| e.into_inner());
+        if turns.len() == self.capacity {
+            turns.pop_front();
+        }
+        turns.push_back(turn);
+    }
+
+    /// Turns matching `filter`, newest first
+    pub fn list(&self, filter: impl Fn(&TurnRecord) -> bool) -> Vec<TurnRecord> {
+        let turns = self.turns.lock().unwrap_or_else(|e| e.into_inner());
+        turns.iter().rev().filter(|t| filter(t)).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded_and_newest_first() {
+        let history = TurnHistory::new(2);
+        for i in 0..3 {
+            let result: AgentResult<SupervisorResult> = Err(crate::agent::AgentError::Validation(format!("turn {}", i)));
+            history.record(TurnRecord::new(i.to_string(), None, None, "q", &result));
+        }
+        let ids: Vec<String> = history.list(|_| true).into_iter().map(|t| t.turn_id).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(history.list(|t| t.turn_id == "1")[0].error.as_deref(), Some("Validation error: turn 1"));
+    }
+}
@@ -73,8 +73,9 @@ impl VocalCords {
     pub async fn start_listening(&self, queue: Arc<dyn TaskQueue>) {
         info!("👂 Vocal Cords: Opening ears...");
 
-        // 1. Listen to Telegram
-        if let (Some(bot), Some(allowed_chat_id)) = (self.tg_bot.clone(), self.tg_chat_id) {
+        // 1. Listen to Telegram (unless the bot front-end owns the updates stream)
+        let tg_frontend = std::env::var("AGENCY_TELEGRAM_BOT").unwrap_or_default() == "1";
+        if let (Some(bot), Some(allowed_chat_id), false) = (self.tg_bot.clone(), self.tg_chat_id, tg_frontend) {
             let q = queue.clone();
             tokio::spawn(async move {
                 let handler = Update::filter_message().endpoint(move |bot: Bot, msg: Message, q: Arc<dyn TaskQueue>| async move {
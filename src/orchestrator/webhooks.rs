@@ -0,0 +1,334 @@
+//! Inbound Webhooks
+//!
+//! External systems (GitHub, CI, monitoring) POST events to `/v1/hooks/{hook_id}`. Each hook
+//! in `config/webhooks.json` renders the payload into a Supervisor query or an autonomous goal,
+//! which is queued for the Supervisor worker; the result is published on the event bus.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+use crate::orchestrator::queue::TaskQueue;
+
+/// Task kind enqueued for each accepted delivery
+pub const WEBHOOK_TASK: &str = "webhook_event";
+/// Longest rendered payload placed into a prompt
+const MAX_PAYLOAD_CHARS: usize = 8000;
+/// How long a delivery is remembered to drop replays and sender retries
+const REPLAY_WINDOW: Duration = Duration::from_secs(24 * 3600);
+/// Most deliveries remembered at once
+const MAX_SEEN_DELIVERIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HookMode {
+    /// One Supervisor turn
+    #[default]
+    Query,
+    /// A full autonomous run
+    Goal,
+}
+
+/// One endpoint and how its payloads become work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub id: String,
+    #[serde(default)]
+    pub mode: HookMode,
+    /// Prompt with `{{dotted.path}}` placeholders into the JSON payload; `{{payload}}` is the whole body
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Environment variable holding the shared secret (never stored in the config file)
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// Accept deliveries without a secret; only for trusted networks
+    #[serde(default)]
+    pub allow_unsigned: bool,
+    /// Only these event types (`X-GitHub-Event`, `X-Gitlab-Event` or `X-Event-Type`) are accepted
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Dotted payload paths that must equal the given values, e.g. `{"workflow_run.conclusion": "failure"}`
+    #[serde(default)]
+    pub filter: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WebhookFile {
+    hooks: Vec<WebhookConfig>,
+}
+
+/// Why a delivery was not queued
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookRejection {
+    UnknownHook,
+    Unauthorized,
+    /// Authenticated, but the event or filter did not match; not an error for the sender
+    Ignored(String),
+}
+
+/// Look up a dotted path (`a.b.0.c`) in a JSON value
+pub fn lookup<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(payload, |value, key| match value {
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => value.get(key),
+    })
+}
+
+/// Replace `{{path}}` placeholders; strings are inserted raw, missing paths become empty
+pub fn render_template(template: &str, payload: &Value) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        let path = rest[start + 2..start + end].trim();
+        let value = if path == "payload" { Some(payload) } else { lookup(payload, path) };
+        match value {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(Value::Null) | None => {}
+            Some(other) => out.push_str(&truncate(&serde_json::to_string_pretty(other).unwrap_or_default())),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_PAYLOAD_CHARS {
+        return text.to_string();
+    }
+    format!("{}\n... (truncated)", text.chars().take(MAX_PAYLOAD_CHARS).collect::<String>())
+}
+
+/// `sha256=<hex>` HMAC of the raw body, as sent by GitHub in `X-Hub-Signature-256`
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(expected) = signature.strip_prefix("sha256=").and_then(|h| hex::decode(h).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else { return false };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn header<'a>(headers: &'a axum::http::HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+impl WebhookConfig {
+    fn authorized(&self, headers: &axum::http::HeaderMap, body: &[u8]) -> bool {
+        let Some(secret) = self.secret_env.as_ref().and_then(|name| std::env::var(name).ok()) else {
+            return self.allow_unsigned;
+        };
+        if let Some(signature) = header(headers, "x-hub-signature-256") {
+            return verify_signature(&secret, body, signature);
+        }
+        // GitLab and simple senders pass the secret itself
+        let token = header(headers, "x-gitlab-token")
+            .or_else(|| header(headers, "x-agency-token"))
+            .or_else(|| header(headers, "authorization").and_then(|v| v.strip_prefix("Bearer ")));
+        token.is_some_and(|token| bool::from(token.as_bytes().ct_eq(secret.as_bytes())))
+    }
+
+    /// Check the event type and filters; `Err` explains why the delivery is ignored
+    pub fn accepts(&self, event: Option<&str>, payload: &Value) -> Result<(), String> {
+        if !self.events.is_empty() && !event.is_some_and(|e| self.events.iter().any(|allowed| allowed == e)) {
+            return Err(format!("event {:?} is not handled", event.unwrap_or("unknown")));
+        }
+        for (path, expected) in &self.filter {
+            let actual = lookup(payload, path);
+            let matches = match (actual, expected) {
+                (Some(actual), Value::String(s)) if !actual.is_string() => actual.to_string() == *s,
+                (Some(actual), expected) => actual == expected,
+                (None, _) => false,
+            };
+            if !matches {
+                return Err(format!("filter '{}' did not match", path));
+            }
+        }
+        Ok(())
+    }
+
+    /// The query or goal for a delivery
+    pub fn render(&self, event: Option<&str>, payload: &Value) -> String {
+        match &self.template {
+            Some(template) => render_template(template, payload),
+            None => format!(
+                "Webhook '{}' received{}:\n```json\n{}\n```",
+                self.id,
+                event.map(|e| format!(" a '{}' event", e)).unwrap_or_default(),
+                truncate(&serde_json::to_string_pretty(payload).unwrap_or_default())
+            ),
+        }
+    }
+}
+
+/// Configured hooks plus the queue deliveries are handed to
+pub struct WebhookRegistry {
+    hooks: HashMap<String, WebhookConfig>,
+    queue: Arc<dyn TaskQueue>,
+    /// Replay keys of recently queued deliveries
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl WebhookRegistry {
+    pub fn new(hooks: Vec<WebhookConfig>, queue: Arc<dyn TaskQueue>) -> Self {
+        for hook in &hooks {
+            if hook.secret_env.is_none() && !hook.allow_unsigned {
+                warn!("Webhook '{}' has no secret_env and will reject every delivery", hook.id);
+            }
+        }
+        Self { hooks: hooks.into_iter().map(|h| (h.id.clone(), h)).collect(), queue, seen: Mutex::new(HashMap::new()) }
+    }
+
+    /// Hooks from `AGENCY_WEBHOOKS` (default `config/webhooks.json`); none if the file is missing
+    pub fn from_env(queue: Arc<dyn TaskQueue>) -> Self {
+        let path = std::env::var("AGENCY_WEBHOOKS").unwrap_or_else(|_| "config/webhooks.json".to_string());
+        Self::new(Self::load(&path), queue)
+    }
+
+    fn load(path: impl AsRef<Path>) -> Vec<WebhookConfig> {
+        let Ok(content) = std::fs::read_to_string(path.as_ref()) else { return Vec::new() };
+        match serde_json::from_str::<WebhookFile>(&content) {
+            Ok(file) => file.hooks,
+            Err(e) => {
+                warn!("Ignoring invalid webhook config {:?}: {}", path.as_ref(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn hooks(&self) -> Vec<&WebhookConfig> {
+        let mut hooks: Vec<_> = self.hooks.values().collect();
+        hooks.sort_by(|a, b| a.id.cmp(&b.id));
+        hooks
+    }
+
+    /// Authenticate, filter and queue one delivery; returns the delivery id
+    pub async fn deliver(&self, hook_id: &str, headers: &axum::http::HeaderMap, body: &[u8]) -> anyhow::Result<Result<String, HookRejection>> {
+        let Some(hook) = self.hooks.get(hook_id) else { return Ok(Err(HookRejection::UnknownHook)) };
+        if !hook.authorized(headers, body) {
+            return Ok(Err(HookRejection::Unauthorized));
+        }
+
+        let payload: Value = serde_json::from_slice(body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()));
+        let event = header(headers, "x-github-event")
+            .or_else(|| header(headers, "x-gitlab-event"))
+            .or_else(|| header(headers, "x-event-type"));
+        if let Err(reason) = hook.accepts(event, &payload) {
+            return Ok(Err(HookRejection::Ignored(reason)));
+        }
+
+        let sender_id = header(headers, "x-github-delivery")
+            .or_else(|| header(headers, "x-gitlab-event-uuid"))
+            .or_else(|| header(headers, "x-delivery-id"));
+        // Without a delivery id, an identical body within the window counts as a replay
+        let replay_key = format!("{}/{}", hook.id, sender_id.map(str::to_string)
+            .unwrap_or_else(|| hex::encode(Sha256::digest(body))));
+        if !self.first_delivery(replay_key.clone()) {
+            return Ok(Err(HookRejection::Ignored("duplicate delivery".to_string())));
+        }
+
+        let delivery_id = sender_id.map(str::to_string).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let queued = self.queue.enqueue(WEBHOOK_TASK, json!({
+            "hook_id": hook.id,
+            "delivery_id": delivery_id,
+            "mode": hook.mode,
+            "prompt": hook.render(event, &payload),
+        })).await;
+        if let Err(e) = queued {
+            // Forget the claim so the sender's retry is not rejected as a replay
+            self.seen.lock().unwrap().remove(&replay_key);
+            return Err(e);
+        }
+        Ok(Ok(delivery_id))
+    }
+
+    /// Remember `key`; false if it was already seen within `REPLAY_WINDOW`
+    fn first_delivery(&self, key: String) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, at| at.elapsed() < REPLAY_WINDOW);
+        if seen.contains_key(&key) {
+            return false;
+        }
+        if seen.len() >= MAX_SEEN_DELIVERIES {
+            if let Some(oldest) = seen.iter().min_by_key(|(_, at)| **at).map(|(k, _)| k.clone()) {
+                seen.remove(&oldest);
+            }
+        }
+        seen.insert(key, Instant::now());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_and_filter() {
+        let payload = json!({
+            "action": "completed",
+            "workflow_run": { "conclusion": "failure", "html_url": "https://ci/run/1", "run_attempt": 2 },
+            "repository": { "full_name": "acme/app" },
+            "commits": [{ "id": "abc123" }]
+        });
+        let hook: WebhookConfig = serde_json::from_value(json!({
+            "id": "github",
+            "mode": "goal",
+            "template": "CI failed on {{repository.full_name}} (attempt {{workflow_run.run_attempt}}, {{commits.0.id}}): {{workflow_run.html_url}}{{missing}}",
+            "events": ["workflow_run"],
+            "filter": { "workflow_run.conclusion": "failure", "workflow_run.run_attempt": "2" }
+        })).unwrap();
+
+        assert_eq!(hook.mode, HookMode::Goal);
+        assert!(hook.accepts(Some("workflow_run"), &payload).is_ok());
+        assert!(hook.accepts(Some("push"), &payload).is_err());
+        assert!(hook.accepts(Some("workflow_run"), &json!({ "workflow_run": { "conclusion": "success" } })).is_err());
+        assert_eq!(
+            hook.render(Some("workflow_run"), &payload),
+            "CI failed on acme/app (attempt 2, abc123): https://ci/run/1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_auth_and_replays() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = Arc::new(crate::orchestrator::queue::SqliteTaskQueue::new(dir.path().join("queue.db")).await.unwrap());
+        std::env::set_var("TEST_REPLAY_HOOK_SECRET", "s3cret");
+        let hook: WebhookConfig = serde_json::from_value(json!({ "id": "ci", "secret_env": "TEST_REPLAY_HOOK_SECRET" })).unwrap();
+        let registry = WebhookRegistry::new(vec![hook], queue);
+
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("x-agency-token", "wrong".parse().unwrap());
+        assert_eq!(registry.deliver("ci", &headers, b"{}").await.unwrap(), Err(HookRejection::Unauthorized));
+
+        headers.insert("x-agency-token", "s3cret".parse().unwrap());
+        headers.insert("x-delivery-id", "d-1".parse().unwrap());
+        assert_eq!(registry.deliver("ci", &headers, b"{}").await.unwrap(), Ok("d-1".to_string()));
+        assert!(matches!(registry.deliver("ci", &headers, b"{}").await.unwrap(), Err(HookRejection::Ignored(_))));
+
+        // Without a delivery id the body itself is the replay key
+        headers.remove("x-delivery-id");
+        assert!(registry.deliver("ci", &headers, b"{\"n\": 1}").await.unwrap().is_ok());
+        assert!(registry.deliver("ci", &headers, b"{\"n\": 1}").await.unwrap().is_err());
+        assert!(registry.deliver("ci", &headers, b"{\"n\": 2}").await.unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // Example from GitHub's webhook documentation
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert!(verify_signature("It's a Secret to Everybody", b"Hello, World!", signature));
+        assert!(!verify_signature("wrong", b"Hello, World!", signature));
+        assert!(!verify_signature("It's a Secret to Everybody", b"Hello, World!", "garbage"));
+    }
+}
@@ -1 +1,2 @@
 pub mod wasm;
+pub mod wasi;
@@ -0,0 +1,152 @@
+//! WASI Runtime
+//!
+//! Runs WASI command modules under wasmtime with fuel and memory limits. Modules get
+//! no filesystem, network or environment access; input arrives via argv and stdin.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+
+/// Resource limits for one WASI run
+#[derive(Debug, Clone, Copy)]
+pub struct WasiLimits {
+    /// Instructions budget; execution traps once it is spent
+    pub fuel: u64,
+    pub max_memory_bytes: usize,
+    /// Captured stdout/stderr are truncated beyond this size
+    pub max_output_bytes: usize,
+}
+
+impl Default for WasiLimits {
+    fn default() -> Self {
+        Self { fuel: 1_000_000_000, max_memory_bytes: 64 * 1024 * 1024, max_output_bytes: 1024 * 1024 }
+    }
+}
+
+impl WasiLimits {
+    /// Defaults overridden by `AGENCY_WASM_FUEL` and `AGENCY_WASM_MAX_MEMORY_MB`
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        if let Some(fuel) = std::env::var("AGENCY_WASM_FUEL").ok().and_then(|v| v.parse().ok()) {
+            limits.fuel = fuel;
+        }
+        if let Some(mb) = std::env::var("AGENCY_WASM_MAX_MEMORY_MB").ok().and_then(|v| v.parse::<usize>().ok()) {
+            limits.max_memory_bytes = mb * 1024 * 1024;
+        }
+        limits
+    }
+
+    /// These limits lowered by per-tool settings; a tool can never raise them
+    pub fn narrowed(mut self, fuel: Option<u64>, max_memory_mb: Option<usize>) -> Self {
+        if let Some(fuel) = fuel {
+            self.fuel = self.fuel.min(fuel);
+        }
+        if let Some(mb) = max_memory_mb {
+            self.max_memory_bytes = self.max_memory_bytes.min(mb.saturating_mul(1024 * 1024));
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WasiOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i32,
+    pub fuel_consumed: u64,
+}
+
+struct WasiState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// Run the `_start` export of a WASI module (`.wasm` or `.wat`) to completion
+pub fn run_wasi(module_path: &Path, args: &[String], stdin: &[u8], limits: WasiLimits) -> Result<WasiOutput> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::from_file(&engine, module_path)
+        .with_context(|| format!("Failed to load WASM module {:?}", module_path))?;
+
+    let mut linker: Linker<WasiState> = Linker::new(&engine);
+    preview1::add_to_linker_sync(&mut linker, |s| &mut s.wasi)?;
+
+    let stdout = MemoryOutputPipe::new(limits.max_output_bytes);
+    let stderr = MemoryOutputPipe::new(limits.max_output_bytes);
+    let wasi = WasiCtxBuilder::new()
+        .args(args)
+        .stdin(MemoryInputPipe::new(stdin.to_vec()))
+        .stdout(stdout.clone())
+        .stderr(stderr.clone())
+        .build_p1();
+    let store_limits = StoreLimitsBuilder::new()
+        .memory_size(limits.max_memory_bytes)
+        .instances(1)
+        .build();
+
+    let mut store = Store::new(&engine, WasiState { wasi, limits: store_limits });
+    store.limiter(|s| &mut s.limits);
+    store.set_fuel(limits.fuel)?;
+
+    let instance = linker.instantiate(&mut store, &module)
+        .context("Failed to instantiate WASM module (memory limit exceeded or missing imports)")?;
+    let start = instance.get_typed_func::<(), ()>(&mut store, "_start")
+        .context("WASM module has no `_start` export")?;
+
+    let exit_code = match start.call(&mut store, ()) {
+        Ok(()) => 0,
+        Err(e) => {
+            if let Some(exit) = e.downcast_ref::<I32Exit>() {
+                exit.0
+            } else if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+                anyhow::bail!("WASM module exhausted its fuel budget ({} units)", limits.fuel);
+            } else {
+                return Err(e.context("WASM module trapped"));
+            }
+        }
+    };
+
+    let fuel_consumed = limits.fuel.saturating_sub(store.get_fuel().unwrap_or(0));
+    drop(store);
+    Ok(WasiOutput {
+        stdout: String::from_utf8_lossy(&stdout.contents()).to_string(),
+        stderr: String::from_utf8_lossy(&stderr.contents()).to_string(),
+        exit_code,
+        fuel_consumed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_module(dir: &Path, wat: &str) -> std::path::PathBuf {
+        let path = dir.join("module.wat");
+        std::fs::write(&path, wat).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_fuel_and_memory_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let limits = WasiLimits { fuel: 10_000, ..WasiLimits::default() };
+
+        let spin = write_module(dir.path(), r#"(module (memory (export "memory") 1) (func (export "_start") (loop (br 0))))"#);
+        let err = run_wasi(&spin, &[], b"", limits).unwrap_err();
+        assert!(err.to_string().contains("fuel"));
+
+        let hungry = write_module(dir.path(), r#"(module (memory (export "memory") 2000) (func (export "_start")))"#);
+        assert!(run_wasi(&hungry, &[], b"", limits).is_err());
+
+        let ok = write_module(dir.path(), r#"(module (memory (export "memory") 1) (func (export "_start")))"#);
+        assert_eq!(run_wasi(&ok, &[], b"", limits).unwrap().exit_code, 0);
+
+        // Per-tool settings only lower the limits
+        let narrowed = limits.narrowed(Some(u64::MAX), Some(1));
+        assert_eq!((narrowed.fuel, narrowed.max_memory_bytes), (10_000, 1024 * 1024));
+    }
+}
@@ -0,0 +1,226 @@
+//! Approval Queue
+//!
+//! Tool calls waiting for a human decision, persisted to disk so a restart does not lose
+//! them. Each entry remembers the query that triggered it and, when the agent's loop state
+//! was captured, where that loop stopped, so approving even after a restart continues the
+//! turn from the held-back call rather than re-running it from scratch. Decided entries are
+//! dropped once they are older than `RESOLVED_RETENTION_DAYS`.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use super::audit::{AuditKind, AUDIT_LOG};
+use super::ApprovalRequest;
+use crate::agent::PausedReAct;
+use crate::utils::write_atomic;
+
+/// Approved and rejected entries are kept this long, for listing and audit, then pruned
+const RESOLVED_RETENTION_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub request: ApprovalRequest,
+    /// The user query whose turn stopped at this call
+    pub query: String,
+    /// Conversation the turn belongs to; `None` for the main one
+    #[serde(default)]
+    pub conversation: Option<String>,
+    pub status: ApprovalStatus,
+    /// The paused ReAct loop to resume on approval
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<PausedReAct>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+pub struct ApprovalQueue {
+    path: PathBuf,
+    entries: Mutex<Vec<PendingApproval>>,
+}
+
+impl ApprovalQueue {
+    /// Open the queue at `path`, loading any saved entries
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut entries = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).context("Failed to parse approval queue")?,
+            Err(_) => Vec::new(),
+        };
+        Self::prune(&mut entries);
+        Ok(Self { path, entries: Mutex::new(entries) })
+    }
+
+    /// An empty queue saving to `path`; an unreadable file already there is kept as `<path>.corrupt`
+    pub async fn reset(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            let mut aside = path.clone().into_os_string();
+            aside.push(".corrupt");
+            let _ = tokio::fs::rename(&path, aside).await;
+        }
+        Self { path, entries: Mutex::new(Vec::new()) }
+    }
+
+    /// `AGENCY_APPROVALS_FILE` (default `agency_approvals.json`)
+    pub fn path_from_env() -> PathBuf {
+        std::env::var("AGENCY_APPROVALS_FILE").unwrap_or_else(|_| "agency_approvals.json".to_string()).into()
+    }
+
+    /// Queue at `path_from_env`
+    pub async fn from_env() -> Result<Self> {
+        Self::open(Self::path_from_env()).await
+    }
+
+    /// Drop decided entries past the retention period; pending ones are kept however old
+    fn prune(entries: &mut Vec<PendingApproval>) {
+        let cutoff = Utc::now() - chrono::Duration::days(RESOLVED_RETENTION_DAYS);
+        entries.retain(|e| e.status == ApprovalStatus::Pending || e.resolved_at.is_none_or(|at| at > cutoff));
+    }
+
+    async fn persist(&self, entries: &mut Vec<PendingApproval>) -> Result<()> {
+        Self::prune(entries);
+        let json = serde_json::to_string_pretty(entries)?;
+        write_atomic(&self.path, json).await.context("Failed to write approval queue")
+    }
+
+    pub async fn add(&self, request: ApprovalRequest, query: impl Into<String>, conversation: Option<String>) -> Result<()> {
+        self.add_paused(request, query, conversation, None).await
+    }
+
+    /// Queue a call together with the loop state to resume once it is approved
+    pub async fn add_paused(&self, request: ApprovalRequest, query: impl Into<String>, conversation: Option<String>, paused: Option<PausedReAct>) -> Result<()> {
+        AUDIT_LOG.record(AuditKind::ApprovalRequested, "safety_guard", serde_json::json!({
+            "id": request.id,
+            "tool": request.tool_name,
+            "rationale": request.rationale,
+        }));
+        let mut entries = self.entries.lock().await;
+        entries.push(PendingApproval {
+            request,
+            query: query.into(),
+            conversation,
+            status: ApprovalStatus::Pending,
+            paused,
+            created_at: Utc::now(),
+            resolved_at: None,
+        });
+        self.persist(&mut entries).await
+    }
+
+    pub async fn pending(&self) -> Vec<PendingApproval> {
+        self.entries.lock().await.iter().filter(|e| e.status == ApprovalStatus::Pending).cloned().collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<PendingApproval> {
+        self.entries.lock().await.iter().find(|e| e.request.id == id).cloned()
+    }
+
+    /// Record the decision for a pending entry; `None` if it is unknown or already decided
+    pub async fn resolve(&self, id: &str, approved: bool) -> Result<Option<PendingApproval>> {
+        let mut entries = self.entries.lock().await;
+        let Some(entry) = entries.iter_mut().find(|e| e.request.id == id && e.status == ApprovalStatus::Pending) else {
+            return Ok(None);
+        };
+        entry.status = if approved { ApprovalStatus::Approved } else { ApprovalStatus::Rejected };
+        entry.resolved_at = Some(Utc::now());
+        let resolved = entry.clone();
+        AUDIT_LOG.record(AuditKind::ApprovalDecision, "user", serde_json::json!({
+            "id": id,
+            "tool": resolved.request.tool_name,
+            "approved": approved,
+        }));
+        self.persist(&mut entries).await?;
+        Ok(Some(resolved))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::safety::AssuranceScore;
+    use tempfile::tempdir;
+
+    fn request(id: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            id: id.to_string(),
+            tool_name: "sandbox".to_string(),
+            parameters: serde_json::json!({ "code": "rm -rf build" }),
+            assurance: AssuranceScore { f: 0.5, g: 0.5, r: 0.5 },
+            rationale: "High-risk tool call.".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decisions_persist() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        let queue = ApprovalQueue::open(&path).await.unwrap();
+        queue.add(request("a"), "clean the build", None).await.unwrap();
+        queue.add(request("b"), "clean again", Some("design-review".to_string())).await.unwrap();
+        assert_eq!(queue.resolve("a", true).await.unwrap().unwrap().query, "clean the build");
+        // Decisions are final
+        assert!(queue.resolve("a", false).await.unwrap().is_none());
+
+        let reopened = ApprovalQueue::open(&path).await.unwrap();
+        let pending = reopened.pending().await;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].request.id, "b");
+        assert_eq!(pending[0].conversation.as_deref(), Some("design-review"));
+        assert_eq!(reopened.get("a").await.unwrap().status, ApprovalStatus::Approved);
+    }
+
+    #[tokio::test]
+    async fn test_old_decisions_are_pruned_and_corrupt_files_kept() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+        let queue = ApprovalQueue::open(&path).await.unwrap();
+        queue.add(request("old"), "q", None).await.unwrap();
+        queue.add(request("waiting"), "q", None).await.unwrap();
+        queue.resolve("old", false).await.unwrap();
+        queue.entries.lock().await[0].resolved_at = Some(Utc::now() - chrono::Duration::days(RESOLVED_RETENTION_DAYS + 1));
+        queue.add(request("new"), "q", None).await.unwrap();
+        assert!(queue.get("old").await.is_none());
+        assert_eq!(queue.pending().await.len(), 2);
+
+        tokio::fs::write(&path, "{ not json").await.unwrap();
+        assert!(ApprovalQueue::open(&path).await.is_err());
+        let fresh = ApprovalQueue::reset(&path).await;
+        assert!(fresh.pending().await.is_empty());
+        assert!(dir.path().join("approvals.json.corrupt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_paused_loop_survives_restart() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+        let held = crate::agent::ReActStep::thought("Clean the build dir")
+            .with_action(crate::tools::ToolCall { name: "sandbox".to_string(), parameters: serde_json::json!({ "code": "rm -rf build" }) });
+        let paused = PausedReAct {
+            config: crate::agent::AgentConfig::default(),
+            query: "clean the build".to_string(),
+            context: None,
+            steps: vec![held],
+        };
+
+        let queue = ApprovalQueue::open(&path).await.unwrap();
+        queue.add_paused(request("a"), "clean the build", None, Some(paused)).await.unwrap();
+
+        let entry = ApprovalQueue::open(&path).await.unwrap().resolve("a", true).await.unwrap().unwrap();
+        let resumed = entry.paused.unwrap();
+        assert_eq!(resumed.steps.len(), 1);
+        assert_eq!(resumed.steps[0].actions[0].name, "sandbox");
+        assert!(resumed.steps[0].observations.is_empty());
+    }
+}
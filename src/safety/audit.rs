@@ -0,0 +1,223 @@
+//! Audit Log
+//!
+//! Append-only record of every tool execution, approval decision, escalation and provider
+//! call, for deployments that must show what the agency did. Each JSONL entry carries the
+//! SHA-256 of the entry before it, so editing, removing or reordering a line breaks the
+//! chain; `verify` (and `rust_agency --verify-audit`) walks the file and reports the first
+//! break. Parameters are stored with secrets masked.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Process-wide audit log
+    pub static ref AUDIT_LOG: AuditLog = AuditLog::from_env();
+}
+
+/// `prev_hash` of the first entry
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    ToolExecution,
+    ApprovalRequested,
+    ApprovalDecision,
+    Escalation,
+    ProviderCall,
+    KillSwitch,
+    /// A tool was enabled, disabled or promoted at runtime
+    ToolManagement,
+    /// The agency profile was replaced through the API
+    ProfileUpdate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub kind: AuditKind,
+    #[serde(default)]
+    pub turn_id: Option<String>,
+    /// Authenticated user the turn ran for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Agent, user or model responsible
+    pub actor: String,
+    pub details: Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash over every field except `hash` itself
+    fn compute_hash(&self) -> String {
+        let mut body = serde_json::json!({
+            "seq": self.seq,
+            "timestamp": self.timestamp,
+            "kind": self.kind,
+            "turn_id": self.turn_id,
+            "actor": self.actor,
+            "details": self.details,
+            "prev_hash": self.prev_hash,
+        });
+        // Only hashed when present, so entries written before users existed still verify
+        if let Some(ref user) = self.user {
+            body["user"] = Value::String(user.clone());
+        }
+        hex::encode(Sha256::digest(body.to_string().as_bytes()))
+    }
+}
+
+/// Result of walking the chain
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditVerification {
+    Intact { entries: u64 },
+    /// The first entry (1-based line) that does not follow from the one before
+    Broken { line: usize, reason: String },
+}
+
+struct ChainHead {
+    seq: u64,
+    hash: String,
+}
+
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    /// Guards the file as well as the head so entries are chained in write order
+    head: Mutex<Option<ChainHead>>,
+}
+
+impl AuditLog {
+    /// Log appending to `path`; `None` records nothing
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, head: Mutex::new(None) }
+    }
+
+    /// `AGENCY_AUDIT_LOG` (default `agency_audit.jsonl`; `off` disables)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_AUDIT_LOG").unwrap_or_else(|_| "agency_audit.jsonl".to_string());
+        Self::new((path != "off" && !path.is_empty()).then(|| PathBuf::from(path)))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Append an entry chained to the last one; failures are logged, never raised
+    pub fn record(&self, kind: AuditKind, actor: impl Into<String>, details: Value) {
+        let Some(ref path) = self.path else { return };
+        let mut head = self.head.lock().unwrap_or_else(|e| e.into_inner());
+        if head.is_none() {
+            *head = Some(Self::read_head(path));
+        }
+        let current = head.as_ref().expect("head loaded above");
+        let mut entry = AuditEntry {
+            seq: current.seq + 1,
+            timestamp: Utc::now(),
+            kind,
+            turn_id: crate::orchestrator::event_bus::current_turn(),
+            user: crate::orchestrator::event_bus::current_user(),
+            actor: actor.into(),
+            details,
+            prev_hash: current.hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        match Self::append(path, &entry) {
+            Ok(()) => *head = Some(ChainHead { seq: entry.seq, hash: entry.hash }),
+            Err(e) => tracing::warn!("Failed to write audit entry: {}", e),
+        }
+    }
+
+    /// Record a tool call with its parameters (secrets masked)
+    pub fn record_tool(&self, tool: &str, agent: &str, parameters: &Value, success: bool, latency_ms: u64) {
+        let mut parameters = parameters.clone();
+        crate::safety::SECRET_SCANNER.mask_value(&mut parameters, &mut Vec::new());
+        self.record(AuditKind::ToolExecution, agent, serde_json::json!({
+            "tool": tool,
+            "parameters": parameters,
+            "success": success,
+            "latency_ms": latency_ms,
+        }));
+    }
+
+    fn read_head(path: &Path) -> ChainHead {
+        std::fs::read_to_string(path).ok()
+            .and_then(|content| content.lines().rev().find_map(|line| serde_json::from_str::<AuditEntry>(line).ok()))
+            .map(|last| ChainHead { seq: last.seq, hash: last.hash })
+            .unwrap_or_else(|| ChainHead { seq: 0, hash: GENESIS.to_string() })
+    }
+
+    fn append(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        std::fs::OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())
+    }
+}
+
+/// Check every entry's hash, its link to the previous entry and its sequence number
+pub fn verify(path: impl AsRef<Path>) -> Result<AuditVerification> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read audit log {:?}", path))?;
+    let mut prev_hash = GENESIS.to_string();
+    let mut prev_seq = 0;
+    for (i, line) in content.lines().enumerate() {
+        let line_no = i + 1;
+        let broken = |reason: String| Ok(AuditVerification::Broken { line: line_no, reason });
+        let entry: AuditEntry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => return broken(format!("not a valid entry: {}", e)),
+        };
+        if entry.seq != prev_seq + 1 {
+            return broken(format!("sequence {} follows {}", entry.seq, prev_seq));
+        }
+        if entry.prev_hash != prev_hash {
+            return broken("previous-hash link does not match the entry before it".to_string());
+        }
+        if entry.compute_hash() != entry.hash {
+            return broken("contents do not match the recorded hash".to_string());
+        }
+        prev_hash = entry.hash;
+        prev_seq = entry.seq;
+    }
+    Ok(AuditVerification::Intact { entries: prev_seq })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_chain_survives_reopen_and_detects_tampering() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::new(Some(path.clone()));
+        log.record_tool("http_request", "coder", &json!({"url": "https://example.com", "token": "sk-abcdefghijklmnopqrstuvwx"}), true, 12);
+        log.record(AuditKind::ApprovalDecision, "user", json!({"id": "a1", "approved": false}));
+        // A new process continues the chain
+        AuditLog::new(Some(path.clone())).record(AuditKind::Escalation, "supervisor", json!({"from": "tiny", "to": "standard"}));
+        assert_eq!(verify(&path).unwrap(), AuditVerification::Intact { entries: 3 });
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("sk-abcdef"));
+
+        let tampered = content.replacen("\"approved\":false", "\"approved\":true", 1);
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(matches!(verify(&path).unwrap(), AuditVerification::Broken { line: 2, .. }));
+
+        let dropped: Vec<&str> = content.lines().enumerate().filter(|(i, _)| *i != 1).map(|(_, l)| l).collect();
+        std::fs::write(&path, dropped.join("\n")).unwrap();
+        assert!(matches!(verify(&path).unwrap(), AuditVerification::Broken { line: 2, .. }));
+    }
+}
@@ -0,0 +1,203 @@
+//! Domain Policy
+//!
+//! Allow/deny lists for outbound HTTP. Loopback, link-local and private addresses are denied
+//! unless explicitly allowlisted, so agents cannot reach cloud metadata endpoints or services
+//! on the local network by accident. Host names are checked by what they resolve to:
+//! `PolicyResolver` is the DNS resolver of HTTP clients that fetch agent-chosen URLs, so every
+//! connection, redirects included, goes to an address the policy was checked against.
+
+use anyhow::Result;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DomainPolicy {
+    /// When non-empty, only these domains (and their subdomains) are reachable
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Domains that are never reachable; takes precedence over `allow`
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl DomainPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comma-separated lists from `AGENCY_HTTP_ALLOW` and `AGENCY_HTTP_DENY`
+    pub fn from_env() -> Self {
+        let list = |var: &str| -> Vec<String> {
+            std::env::var(var).unwrap_or_default()
+                .split(',')
+                .map(|d| d.trim().to_lowercase())
+                .filter(|d| !d.is_empty())
+                .collect()
+        };
+        Self { allow: list("AGENCY_HTTP_ALLOW"), deny: list("AGENCY_HTTP_DENY") }
+    }
+
+    pub fn allow(mut self, domain: impl Into<String>) -> Self {
+        self.allow.push(domain.into().to_lowercase());
+        self
+    }
+
+    pub fn deny(mut self, domain: impl Into<String>) -> Self {
+        self.deny.push(domain.into().to_lowercase());
+        self
+    }
+
+    /// `example.com` and `*.example.com` both match the domain and all of its subdomains
+    pub(crate) fn matches(pattern: &str, host: &str) -> bool {
+        let pattern = pattern.trim_start_matches("*.");
+        host == pattern || host.ends_with(&format!(".{}", pattern))
+    }
+
+    fn is_internal(host: &str) -> bool {
+        if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal") {
+            return true;
+        }
+        host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().is_ok_and(Self::is_internal_ip)
+    }
+
+    /// Loopback, private, link-local, shared (CGNAT), unspecified, broadcast and multicast
+    /// addresses, including IPv4 addresses embedded in IPv6 (`::ffff:127.0.0.1`, NAT64)
+    pub fn is_internal_ip(ip: IpAddr) -> bool {
+        let v4_internal = |ip: Ipv4Addr| {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast()
+                || ip.is_multicast() || a == 0 || (a == 100 && (b & 0xc0) == 64) || (a == 198 && (b & 0xfe) == 18)
+        };
+        match ip {
+            IpAddr::V4(ip) => v4_internal(ip),
+            IpAddr::V6(ip) => {
+                let segments = ip.segments();
+                if let Some(v4) = ip.to_ipv4() {
+                    return v4_internal(v4);
+                }
+                if segments[0] == 0x64 && segments[1] == 0xff9b {
+                    let [.., a, b, c, d] = ip.octets();
+                    return v4_internal(Ipv4Addr::new(a, b, c, d));
+                }
+                ip.is_loopback() || ip.is_unspecified() || ip.is_multicast()
+                    || (segments[0] & 0xfe00) == 0xfc00 || (segments[0] & 0xffc0) == 0xfe80
+            }
+        }
+    }
+
+    fn is_allowlisted(&self, host: &str) -> bool {
+        self.allow.iter().any(|d| Self::matches(d, host))
+    }
+
+    /// `check_url`, then resolve the host and check every address it resolves to, so internal
+    /// DNS names are refused too. Clients using `PolicyResolver` get the same check on redirects.
+    pub async fn check_url_resolved(&self, url: &str) -> Result<()> {
+        self.check_url(url)?;
+        let parsed = Url::parse(url)?;
+        // IP literals were checked above; only names need resolving
+        if let Some(host) = parsed.domain() {
+            let port = parsed.port_or_known_default().unwrap_or(80);
+            self.resolve(host, port).await?;
+        }
+        Ok(())
+    }
+
+    /// Addresses of `host`; fails if any is internal and the host is not allowlisted
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port)).await
+            .map_err(|e| anyhow::anyhow!("Could not resolve '{}': {}", host, e))?
+            .collect();
+        if !self.is_allowlisted(&host) {
+            if let Some(addr) = addrs.iter().find(|a| Self::is_internal_ip(a.ip())) {
+                anyhow::bail!("'{}' resolves to internal address {}, which is blocked", host, addr.ip());
+            }
+        }
+        Ok(addrs)
+    }
+
+    /// Check that `url` is an http(s) URL whose host the policy permits
+    pub fn check_url(&self, url: &str) -> Result<()> {
+        let parsed = Url::parse(url).map_err(|e| anyhow::anyhow!("Invalid URL '{}': {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            anyhow::bail!("Scheme '{}' is not allowed (http/https only)", parsed.scheme());
+        }
+        // `localhost.` is `localhost`
+        let host = parsed.host_str().map(|h| h.trim_end_matches('.').to_lowercase())
+            .ok_or_else(|| anyhow::anyhow!("URL '{}' has no host", url))?;
+
+        if self.deny.iter().any(|d| Self::matches(d, &host)) {
+            anyhow::bail!("Domain '{}' is on the deny list", host);
+        }
+        let allowlisted = self.is_allowlisted(&host);
+        if !self.allow.is_empty() && !allowlisted {
+            anyhow::bail!("Domain '{}' is not on the allow list", host);
+        }
+        if Self::is_internal(&host) && !allowlisted {
+            anyhow::bail!("Requests to internal address '{}' are blocked", host);
+        }
+        Ok(())
+    }
+}
+
+/// DNS resolver for `reqwest` clients that refuses hosts resolving to internal addresses.
+/// Because the checked addresses are the ones connected to, neither redirects nor DNS
+/// rebinding can reach the local network.
+pub struct PolicyResolver {
+    policy: Arc<DomainPolicy>,
+}
+
+impl PolicyResolver {
+    pub fn new(policy: Arc<DomainPolicy>) -> Arc<Self> {
+        Arc::new(Self { policy })
+    }
+}
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let addrs = policy.resolve(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_deny_and_internal_hosts() {
+        let open = DomainPolicy::new().deny("evil.com");
+        assert!(open.check_url("https://api.github.com/repos").is_ok());
+        assert!(open.check_url("https://cdn.evil.com/x").is_err());
+        assert!(open.check_url("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(open.check_url("http://localhost:8080").is_err());
+        assert!(open.check_url("file:///etc/passwd").is_err());
+        // Alternative spellings of loopback and metadata addresses
+        assert!(open.check_url("http://[::ffff:127.0.0.1]/").is_err());
+        assert!(open.check_url("http://[::ffff:a9fe:a9fe]/").is_err());
+        assert!(open.check_url("http://2130706433/").is_err());
+        assert!(open.check_url("http://0177.0.0.1/").is_err());
+        assert!(open.check_url("http://0x7f.1/").is_err());
+        assert!(open.check_url("http://100.64.0.1/").is_err());
+        assert!(open.check_url("http://[64:ff9b::a9fe:a9fe]/").is_err());
+
+        let strict = DomainPolicy::new().allow("*.github.com").allow("localhost");
+        assert!(strict.check_url("https://api.github.com").is_ok());
+        assert!(strict.check_url("https://example.org").is_err());
+        assert!(strict.check_url("http://localhost:3000/health").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_names_resolving_to_internal_addresses() {
+        let open = DomainPolicy::new();
+        assert!(open.check_url("http://localhost./").is_err());
+        assert!(open.resolve("localhost", 80).await.is_err());
+        assert!(DomainPolicy::new().allow("localhost").resolve("localhost", 80).await.is_ok());
+    }
+}
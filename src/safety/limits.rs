@@ -0,0 +1,139 @@
+//! Tool Limits
+//!
+//! Enforces the `[[limit]]` entries of the safety policy inside `ToolRegistry`: at most
+//! `per_minute` calls of a tool start in any 60-second window, and at most `concurrent`
+//! run at once. A refused call gets a message telling the agent how long to back off.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::policy::SafetyPolicy;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Why a call was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitExceeded {
+    Rate { tool: String, per_minute: u32, retry_after: Duration },
+    Concurrency { tool: String, concurrent: usize },
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitExceeded::Rate { tool, per_minute, retry_after } => write!(
+                f,
+                "Rate limit for '{}' reached ({} calls per minute). Retry in {}s, or continue with other tools in the meantime.",
+                tool, per_minute, retry_after.as_secs().max(1)
+            ),
+            LimitExceeded::Concurrency { tool, concurrent } => write!(
+                f,
+                "Concurrency limit for '{}' reached ({} calls already running). Wait for one to finish before calling it again.",
+                tool, concurrent
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    started: VecDeque<Instant>,
+    running: usize,
+}
+
+/// Held while a call runs; releases its concurrency slot when dropped
+pub struct LimitPermit {
+    tool: String,
+    usage: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl Drop for LimitPermit {
+    fn drop(&mut self) {
+        if let Some(usage) = self.usage.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&self.tool) {
+            usage.running = usage.running.saturating_sub(1);
+        }
+    }
+}
+
+pub struct ToolLimiter {
+    policy: SafetyPolicy,
+    usage: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl ToolLimiter {
+    pub fn new(policy: SafetyPolicy) -> Self {
+        Self { policy, usage: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Limits from `AGENCY_SAFETY_POLICY`
+    pub fn from_env() -> Self {
+        Self::new(SafetyPolicy::from_env())
+    }
+
+    /// Reserve a slot for a call of `tool`, or say why it must wait
+    pub fn acquire(&self, tool: &str) -> Result<LimitPermit, LimitExceeded> {
+        let limit = self.policy.limit_for(tool);
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = usage.entry(tool.to_string()).or_default();
+        let now = Instant::now();
+        while entry.started.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+            entry.started.pop_front();
+        }
+
+        if let Some(ref limit) = limit {
+            if let Some(concurrent) = limit.concurrent {
+                if entry.running >= concurrent {
+                    return Err(LimitExceeded::Concurrency { tool: tool.to_string(), concurrent });
+                }
+            }
+            if let Some(per_minute) = limit.per_minute {
+                if entry.started.len() >= per_minute as usize {
+                    let oldest = entry.started.front().copied().unwrap_or(now);
+                    let retry_after = WINDOW.saturating_sub(now.duration_since(oldest));
+                    return Err(LimitExceeded::Rate { tool: tool.to_string(), per_minute, retry_after });
+                }
+            }
+        }
+
+        entry.started.push_back(now);
+        entry.running += 1;
+        Ok(LimitPermit { tool: tool.to_string(), usage: self.usage.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_and_concurrency_limits() {
+        let limiter = ToolLimiter::new(SafetyPolicy::parse(r#"
+            [[limit]]
+            tool = "web_*"
+            per_minute = 2
+
+            [[limit]]
+            tool = "sandbox"
+            concurrent = 1
+        "#).unwrap());
+
+        assert!(limiter.acquire("web_search").is_ok());
+        assert!(limiter.acquire("web_search").is_ok());
+        let refused = limiter.acquire("web_search").err().unwrap();
+        assert!(matches!(refused, LimitExceeded::Rate { per_minute: 2, .. }));
+        assert!(refused.to_string().contains("Retry in"));
+        // Counted per tool, not per pattern
+        assert!(limiter.acquire("web_fetch").is_ok());
+
+        let running = limiter.acquire("sandbox").unwrap();
+        assert!(matches!(limiter.acquire("sandbox"), Err(LimitExceeded::Concurrency { concurrent: 1, .. })));
+        drop(running);
+        assert!(limiter.acquire("sandbox").is_ok());
+
+        // Unlimited tools are never refused
+        for _ in 0..100 {
+            assert!(limiter.acquire("memory_query").is_ok());
+        }
+    }
+}
@@ -7,11 +7,29 @@ mod content_filter;
 pub mod assurance;
 mod command;
 pub mod hardening;
+mod domain;
+pub mod approvals;
+pub mod policy;
+pub mod limits;
+pub mod moderation;
+pub mod pii;
+pub mod secrets;
+pub mod audit;
+pub mod injection;
 
 pub use rate_limiter::RateLimiter;
 pub use content_filter::ContentFilter;
 pub use assurance::AssuranceScore;
 pub use command::is_dangerous_command;
+pub use domain::{DomainPolicy, PolicyResolver};
+pub use approvals::{ApprovalQueue, ApprovalStatus, PendingApproval};
+pub use pii::{PiiKind, PiiScrubber, RedactionReport, PII_SCRUBBER};
+pub use moderation::{ModerationCategory, ModerationResult, OutputModerator, OUTPUT_MODERATOR};
+pub use limits::{LimitExceeded, LimitPermit, ToolLimiter};
+pub use policy::{PolicyAction, PolicyDecision, PolicyRule, SafetyPolicy, ToolLimit};
+pub use audit::{AuditEntry, AuditKind, AuditLog, AuditVerification, AUDIT_LOG};
+pub use injection::{InjectionDetector, InjectionScore, INJECTION_DETECTOR};
+pub use secrets::{SecretFinding, SecretKind, SecretMode, SecretScanner, SECRET_SCANNER};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -37,6 +55,8 @@ pub struct SafetyGuard {
     rate_limiter: RateLimiter,
     content_filter: ContentFilter,
     approved_hashes: HashSet<String>,
+    domain_policy: DomainPolicy,
+    policy: SafetyPolicy,
 }
 
 impl SafetyGuard {
@@ -45,9 +65,41 @@ impl SafetyGuard {
             rate_limiter: RateLimiter::new(),
             content_filter: ContentFilter::new(),
             approved_hashes: HashSet::new(),
+            domain_policy: DomainPolicy::from_env(),
+            policy: SafetyPolicy::from_env(),
         }
     }
 
+    /// Replace the outbound HTTP allow/deny lists
+    pub fn with_domain_policy(mut self, policy: DomainPolicy) -> Self {
+        self.domain_policy = policy;
+        self
+    }
+
+    pub fn domain_policy(&self) -> &DomainPolicy {
+        &self.domain_policy
+    }
+
+    /// Replace the allow/deny/confirm rules for tool calls
+    pub fn with_policy(mut self, policy: SafetyPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &SafetyPolicy {
+        &self.policy
+    }
+
+    /// Why the policy refuses this call, if it does; human-approved calls are never refused
+    pub fn policy_denial(&self, tool_name: &str, params: &Value) -> Option<String> {
+        if self.is_approved(tool_name, params) {
+            return None;
+        }
+        self.policy.evaluate(tool_name, params)
+            .filter(|d| d.action == PolicyAction::Deny)
+            .map(|d| d.reason)
+    }
+
     /// Calculate a deterministic hash for a tool call to track approvals
     pub fn hash_tool_call(&self, tool_name: &str, params: &Value) -> String {
         let mut hasher = Sha256::new();
@@ -71,6 +123,12 @@ impl SafetyGuard {
         self.approved_hashes.contains(&hash)
     }
 
+    /// Use up the approval for a call that is about to run; an approval covers one execution
+    pub fn consume_approval(&mut self, tool_name: &str, params: &Value) -> bool {
+        let hash = self.hash_tool_call(tool_name, params);
+        self.approved_hashes.remove(&hash)
+    }
+
     /// Validate user input before processing
     pub fn validate_input(&self, input: &str) -> Result<()> {
         // Check for prompt injection attempts
@@ -90,8 +148,16 @@ impl SafetyGuard {
 
     /// Check if a tool call is safe to execute
     pub async fn check_tool_safety(&mut self, tool_name: &str, params: &Value, registry: Arc<ToolRegistry>) -> Result<()> {
+        // The domain policy (and its block on internal hosts) is not up to the policy file or a human
+        if let Some(url) = params.get("url").and_then(|u| u.as_str()) {
+            if let Err(e) = self.domain_policy.check_url_resolved(url).await {
+                warn!("HTTP request blocked by domain policy: {}", e);
+                anyhow::bail!("HTTP request blocked: {}", e);
+            }
+        }
+
         // BYPASS: If human already approved this exact call, we skip further safety hurdles
-        if self.is_approved(tool_name, params) {
+        if self.consume_approval(tool_name, params) {
             info!("Bypassing safety checks for human-approved tool call: {}", tool_name);
             return Ok(());
         }
@@ -112,38 +178,24 @@ impl SafetyGuard {
             info!("Tool call evaluated with assurance R={:.2}", score.r);
         }
 
-        // Check dangerous operations
-        match tool_name {
-            "code_exec" | "sandbox" => {
-                // Check for dangerous code patterns
-                if let Some(code) = params.get("code").and_then(|c| c.as_str()) {
-                    let filter_result = self.content_filter.check_code(code);
-                    if !filter_result.is_safe {
-                        warn!("Code blocked by safety filter: {:?}", filter_result.reasons);
-                        anyhow::bail!("Code blocked: {}", filter_result.reasons.join(", "));
-                    }
-                    
-                    // Also check for shell commands in sandbox
-                    if let Some(lang) = params.get("language").and_then(|l| l.as_str()) {
-                        if lang == "shell" {
-                            let cmd_parts: Vec<String> = code.split_whitespace().map(|s| s.to_string()).collect();
-                            if is_dangerous_command(&cmd_parts) {
-                                warn!("Dangerous shell command detected in {}: {}", tool_name, code);
-                                anyhow::bail!("Dangerous shell command blocked: {}", code);
-                            }
-                        }
-                    }
-                }
-            }
-            "web_search" => {
-                // Rate limit web searches more aggressively
-                if !self.rate_limiter.check_web_search() {
-                    anyhow::bail!("Web search rate limit exceeded");
-                }
-            }
-            _ => {}
+        let decision = self.policy.evaluate(tool_name, params);
+        if let Some(denied) = decision.as_ref().filter(|d| d.action == PolicyAction::Deny) {
+            warn!("Tool call {} blocked by safety policy: {}", tool_name, denied.reason);
+            anyhow::bail!("Blocked by safety policy: {}", denied.reason);
+        }
+
+        // Explicitly allowed calls skip the content checks below
+        if decision.is_some_and(|d| d.action == PolicyAction::Allow) {
+            return Ok(());
         }
 
+        if let Some(code) = params.get("code").and_then(|c| c.as_str()) {
+            let filter_result = self.content_filter.check_code(code);
+            if !filter_result.is_safe {
+                warn!("Code blocked by safety filter: {:?}", filter_result.reasons);
+                anyhow::bail!("Code blocked: {}", filter_result.reasons.join(", "));
+            }
+        }
         Ok(())
     }
 
@@ -154,35 +206,31 @@ impl SafetyGuard {
             return None;
         }
 
+        let decision = self.policy.evaluate(tool_name, params);
+        if decision.as_ref().is_some_and(|d| d.action == PolicyAction::Allow) {
+            return None;
+        }
+        let policy_confirm = decision.filter(|d| d.action == PolicyAction::Confirm);
+
         if let Some(tool) = registry.get_tool(tool_name).await {
-            let score = AssuranceScore::calculate(tool, params);
-            
-            let is_risky_tool = matches!(tool_name, "code_exec" | "sandbox" | "system_monitor");
+            let score = AssuranceScore::calculate(tool.clone(), params);
             let is_caution_zone = score.r < 0.6 && score.r >= 0.3;
-            
-            let mut dangerous_cmd = false;
-            if tool_name == "sandbox" {
-                if let Some(code) = params.get("code").and_then(|c| c.as_str()) {
-                    let cmd_parts: Vec<String> = code.split_whitespace().map(|s| s.to_string()).collect();
-                    dangerous_cmd = is_dangerous_command(&cmd_parts);
-                }
-            }
-
-            if is_risky_tool || is_caution_zone || dangerous_cmd {
-                return Some(ApprovalRequest {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    tool_name: tool_name.to_string(),
-                    parameters: params.clone(),
-                    assurance: score,
-                    rationale: if dangerous_cmd { 
-                        "Dangerous shell command detected.".to_string() 
-                    } else if is_caution_zone {
-                        "Assurance score is below trust threshold.".to_string()
-                    } else {
-                        "High-risk tool call.".to_string()
-                    },
-                });
-            }
+            let rationale = if let Some(decision) = policy_confirm {
+                decision.reason
+            } else if tool.requires_confirmation_for(params) {
+                "High-risk tool call.".to_string()
+            } else if is_caution_zone {
+                "Assurance score is below trust threshold.".to_string()
+            } else {
+                return None;
+            };
+            return Some(ApprovalRequest {
+                id: uuid::Uuid::new_v4().to_string(),
+                tool_name: tool_name.to_string(),
+                parameters: params.clone(),
+                assurance: score,
+                rationale,
+            });
         }
         None
     }
@@ -214,4 +262,32 @@ impl Default for SafetyGuard {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_approval_covers_one_execution() {
+        let mut guard = SafetyGuard::new();
+        let registry = Arc::new(ToolRegistry::default());
+        let params = serde_json::json!({ "code": "echo hi" });
+
+        guard.approve_call("code_exec", &params);
+        assert!(guard.is_approved("code_exec", &params));
+        assert!(guard.check_tool_safety("code_exec", &params, registry.clone()).await.is_ok());
+        assert!(!guard.is_approved("code_exec", &params));
+        assert!(!guard.consume_approval("code_exec", &params));
+    }
+
+    #[tokio::test]
+    async fn test_approval_does_not_bypass_domain_policy() {
+        let mut guard = SafetyGuard::new();
+        let registry = Arc::new(ToolRegistry::default());
+        let params = serde_json::json!({ "url": "http://127.0.0.1:8002/v1/kill_switch" });
+
+        guard.approve_call("http", &params);
+        assert!(guard.check_tool_safety("http", &params, registry).await.is_err());
+    }
+}
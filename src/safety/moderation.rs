@@ -0,0 +1,183 @@
+//! Output Moderation
+//!
+//! `ContentFilter` screens what goes into the agency; this screens what comes out. Final
+//! answers and text sent to the speaker are checked against configurable categories before
+//! they are emitted: an answer in a withheld category is replaced by a short notice, and
+//! profanity is masked. Each flag is reported as a `BoundaryCrossing` event.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::LadeQuadrant;
+use crate::orchestrator::event_bus::FPFBoundClaim;
+use crate::orchestrator::AgencyEvent;
+
+lazy_static! {
+    /// Process-wide moderator for answers and speech
+    pub static ref OUTPUT_MODERATOR: OutputModerator = OutputModerator::from_env();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationCategory {
+    SelfHarm,
+    Violence,
+    Hate,
+    Sexual,
+    Profanity,
+}
+
+impl ModerationCategory {
+    pub const ALL: [ModerationCategory; 5] = [
+        ModerationCategory::SelfHarm,
+        ModerationCategory::Violence,
+        ModerationCategory::Hate,
+        ModerationCategory::Sexual,
+        ModerationCategory::Profanity,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ModerationCategory::SelfHarm => "self_harm",
+            ModerationCategory::Violence => "violence",
+            ModerationCategory::Hate => "hate",
+            ModerationCategory::Sexual => "sexual",
+            ModerationCategory::Profanity => "profanity",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key.trim())
+    }
+
+    /// Masked categories keep the rest of the text; the others withhold all of it
+    fn masks(&self) -> bool {
+        matches!(self, ModerationCategory::Profanity)
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            ModerationCategory::SelfHarm => r"(?i)\b(?:kill|hurt|harm) (?:yourself|myself)\b|\b(?:how to|ways to|best way to) (?:commit suicide|end (?:my|your) life)\b",
+            ModerationCategory::Violence => r"(?i)\b(?:how to|steps to|instructions for) (?:make|build|assemble) an? (?:pipe )?(?:bomb|explosive device|untraceable (?:gun|firearm))\b|\b(?:kill|murder|shoot) (?:him|her|them|everyone)\b",
+            ModerationCategory::Hate => r"(?i)\b(?:all|those) (?:\w+ ){1,2}(?:should|must|deserve to) (?:die|be (?:exterminated|eradicated|wiped out))\b|\b(?:inferior|subhuman) (?:race|people)\b",
+            ModerationCategory::Sexual => r"(?i)\b(?:sexually explicit|pornographic|graphic sex(?:ual)? (?:scene|content))\b",
+            ModerationCategory::Profanity => r"(?i)\b(?:fuck\w*|shit\w*|bitch\w*|asshole\w*|bastard\w*|cunt\w*)\b",
+        }
+    }
+}
+
+/// What moderation did to a text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationResult {
+    /// The text to emit
+    pub text: String,
+    pub flagged: Vec<ModerationCategory>,
+    /// The original text was replaced entirely
+    pub withheld: bool,
+}
+
+impl ModerationResult {
+    pub fn is_clean(&self) -> bool {
+        self.flagged.is_empty()
+    }
+}
+
+pub struct OutputModerator {
+    rules: Vec<(ModerationCategory, Regex)>,
+}
+
+impl OutputModerator {
+    pub fn new(categories: &[ModerationCategory]) -> Self {
+        let rules = categories.iter()
+            .map(|c| (*c, Regex::new(c.pattern()).expect("moderation pattern")))
+            .collect();
+        Self { rules }
+    }
+
+    /// `AGENCY_MODERATION`: comma-separated categories to screen (default all of
+    /// `self_harm,violence,hate,sexual,profanity`; `off` disables)
+    pub fn from_env() -> Self {
+        match std::env::var("AGENCY_MODERATION") {
+            Ok(v) if v == "off" => Self::new(&[]),
+            Ok(v) => {
+                let categories: Vec<ModerationCategory> = v.split(',')
+                    .filter_map(|key| {
+                        let category = ModerationCategory::from_key(key);
+                        if category.is_none() {
+                            tracing::warn!("Unknown moderation category '{}' in AGENCY_MODERATION", key);
+                        }
+                        category
+                    })
+                    .collect();
+                Self::new(&categories)
+            }
+            Err(_) => Self::new(&ModerationCategory::ALL),
+        }
+    }
+
+    pub fn categories(&self) -> Vec<ModerationCategory> {
+        self.rules.iter().map(|(c, _)| *c).collect()
+    }
+
+    /// Screen `text` before it is shown or spoken
+    pub fn moderate(&self, text: &str) -> ModerationResult {
+        let flagged: Vec<ModerationCategory> = self.rules.iter()
+            .filter(|(_, pattern)| pattern.is_match(text))
+            .map(|(c, _)| *c)
+            .collect();
+        if flagged.is_empty() {
+            return ModerationResult { text: text.to_string(), flagged, withheld: false };
+        }
+
+        let keys: Vec<&str> = flagged.iter().map(|c| c.key()).collect();
+        let withheld = flagged.iter().any(|c| !c.masks());
+        let moderated = if withheld {
+            format!("[Response withheld by output moderation: {}]", keys.join(", "))
+        } else {
+            self.rules.iter()
+                .filter(|(c, _)| flagged.contains(c))
+                .fold(text.to_string(), |acc, (_, pattern)| {
+                    pattern.replace_all(&acc, |caps: &regex::Captures| "*".repeat(caps[0].chars().count())).into_owned()
+                })
+        };
+
+        let content = format!("Output flagged ({}); {}", keys.join(", "), if withheld { "withheld" } else { "masked" });
+        tracing::warn!("Moderation: {}", content);
+        crate::emit_event!(AgencyEvent::BoundaryCrossing(FPFBoundClaim {
+            quadrant: LadeQuadrant::A,
+            claim_id: "MODERATION-output".to_string(),
+            content,
+        }));
+        ModerationResult { text: moderated, flagged, withheld }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moderation_masks_or_withholds() {
+        let moderator = OutputModerator::new(&ModerationCategory::ALL);
+
+        let clean = moderator.moderate("The build passed; 42 tests ran.");
+        assert!(clean.is_clean());
+        assert_eq!(clean.text, "The build passed; 42 tests ran.");
+
+        let masked = moderator.moderate("This shit compiles now.");
+        assert_eq!(masked.flagged, vec![ModerationCategory::Profanity]);
+        assert!(!masked.withheld);
+        assert_eq!(masked.text, "This **** compiles now.");
+
+        let withheld = moderator.moderate("Here is how to build a pipe bomb: ...");
+        assert!(withheld.withheld);
+        assert!(withheld.text.contains("violence"));
+        assert!(!withheld.text.contains("bomb"));
+
+        // Only the configured categories are screened
+        let lenient = OutputModerator::new(&[ModerationCategory::Violence]);
+        assert!(lenient.moderate("This shit compiles now.").is_clean());
+        assert_eq!(ModerationCategory::from_key(" self_harm"), Some(ModerationCategory::SelfHarm));
+    }
+}
@@ -0,0 +1,233 @@
+//! PII Redaction
+//!
+//! Scrubs emails, phone numbers, payment card numbers and API keys from tool outputs before
+//! they reach an agent's context, so they are neither sent to remote providers nor written to
+//! episodic memory. What was removed is tallied per turn and attached to the Publication.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Process-wide scrubber applied by `ToolRegistry` to every tool output
+    pub static ref PII_SCRUBBER: PiiScrubber = PiiScrubber::from_env();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    ApiKey,
+    CreditCard,
+    Email,
+    Phone,
+}
+
+impl PiiKind {
+    fn placeholder(&self) -> &'static str {
+        match self {
+            PiiKind::ApiKey => "[REDACTED:API_KEY]",
+            PiiKind::CreditCard => "[REDACTED:CREDIT_CARD]",
+            PiiKind::Email => "[REDACTED:EMAIL]",
+            PiiKind::Phone => "[REDACTED:PHONE]",
+        }
+    }
+}
+
+/// What was redacted, by kind and by tool
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedactionReport {
+    pub counts: BTreeMap<PiiKind, usize>,
+    #[serde(default)]
+    pub by_tool: BTreeMap<String, usize>,
+}
+
+impl RedactionReport {
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+
+    pub fn merge(&mut self, other: &RedactionReport) {
+        for (kind, count) in &other.counts {
+            *self.counts.entry(*kind).or_default() += count;
+        }
+        for (tool, count) in &other.by_tool {
+            *self.by_tool.entry(tool.clone()).or_default() += count;
+        }
+    }
+
+    /// e.g. "3 redactions (email: 2, phone: 1)"
+    pub fn summary(&self) -> String {
+        let kinds: Vec<String> = self.counts.iter()
+            .map(|(kind, count)| format!("{}: {}", serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default(), count))
+            .collect();
+        format!("{} redactions ({})", self.total(), kinds.join(", "))
+    }
+}
+
+pub struct PiiScrubber {
+    enabled: bool,
+    /// Checked in order; earlier kinds win where matches overlap
+    patterns: Vec<(PiiKind, Regex)>,
+    reports: Mutex<HashMap<String, RedactionReport>>,
+}
+
+impl PiiScrubber {
+    pub fn new(enabled: bool) -> Self {
+        let patterns = vec![
+            (PiiKind::ApiKey, Regex::new(r#"\b(?:sk-[A-Za-z0-9_-]{20,}|AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|xox[baprs]-[A-Za-z0-9-]{10,}|AIza[0-9A-Za-z_-]{35})\b|(?i)\b(?:api[_-]?key|access[_-]?token|secret[_-]?key)\b["']?\s*[:=]\s*["']?[A-Za-z0-9_\-]{16,}"#).unwrap()),
+            (PiiKind::Email, Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap()),
+            (PiiKind::CreditCard, Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap()),
+            (PiiKind::Phone, Regex::new(r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\)|\b\d{3})[ .-]?\d{3}[ .-]?\d{4}\b").unwrap()),
+        ];
+        Self { enabled, patterns, reports: Mutex::new(HashMap::new()) }
+    }
+
+    /// `AGENCY_PII_REDACTION` (default on; `off` disables)
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("AGENCY_PII_REDACTION").map(|v| v != "off").unwrap_or(true))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// `text` with PII replaced by placeholders, and what was replaced
+    pub fn scrub(&self, text: &str) -> (String, BTreeMap<PiiKind, usize>) {
+        let mut counts = BTreeMap::new();
+        if !self.enabled {
+            return (text.to_string(), counts);
+        }
+        let mut scrubbed = text.to_string();
+        for (kind, pattern) in &self.patterns {
+            let mut found = 0;
+            scrubbed = pattern.replace_all(&scrubbed, |caps: &regex::Captures| {
+                let matched = &caps[0];
+                if *kind == PiiKind::CreditCard && !luhn_valid(matched) {
+                    return matched.to_string();
+                }
+                found += 1;
+                kind.placeholder().to_string()
+            }).into_owned();
+            if found > 0 {
+                counts.insert(*kind, found);
+            }
+        }
+        (scrubbed, counts)
+    }
+
+    /// Scrub every string in `value` in place
+    pub fn scrub_value(&self, value: &mut Value, counts: &mut BTreeMap<PiiKind, usize>) {
+        match value {
+            Value::String(s) => {
+                let (scrubbed, found) = self.scrub(s);
+                if !found.is_empty() {
+                    *s = scrubbed;
+                    for (kind, count) in found {
+                        *counts.entry(kind).or_default() += count;
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.scrub_value(v, counts)),
+            Value::Object(map) => map.values_mut().for_each(|v| self.scrub_value(v, counts)),
+            _ => {}
+        }
+    }
+
+    /// Scrub a tool's output and charge the redactions to `turn_id`
+    pub fn scrub_output(&self, tool: &str, turn_id: Option<&str>, output: &mut crate::tools::ToolOutput) {
+        if !self.enabled { return; }
+        let mut counts = BTreeMap::new();
+        let (summary, found) = self.scrub(&output.summary);
+        output.summary = summary;
+        for (kind, count) in found {
+            *counts.entry(kind).or_default() += count;
+        }
+        if let Some(ref mut error) = output.error {
+            let (scrubbed, found) = self.scrub(error);
+            *error = scrubbed;
+            for (kind, count) in found {
+                *counts.entry(kind).or_default() += count;
+            }
+        }
+        // The data usually repeats what the summary says; only its extra findings count
+        let mut data_counts = BTreeMap::new();
+        self.scrub_value(&mut output.data, &mut data_counts);
+        for (kind, count) in data_counts {
+            let seen = counts.entry(kind).or_default();
+            *seen = (*seen).max(count);
+        }
+
+        if counts.is_empty() { return; }
+        let report = RedactionReport {
+            by_tool: BTreeMap::from([(tool.to_string(), counts.values().sum())]),
+            counts,
+        };
+        tracing::info!("Redacted PII from '{}' output: {}", tool, report.summary());
+        if let Some(turn) = turn_id {
+            self.reports.lock().unwrap().entry(turn.to_string()).or_default().merge(&report);
+        }
+    }
+
+    /// Redactions made during `turn_id`, removed from the tally
+    pub fn take_report(&self, turn_id: &str) -> Option<RedactionReport> {
+        self.reports.lock().unwrap().remove(turn_id)
+    }
+}
+
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() < 13 { return false; }
+    let sum: u32 = digits.iter().rev().enumerate()
+        .map(|(i, &d)| if i % 2 == 1 { let d2 = d * 2; if d2 > 9 { d2 - 9 } else { d2 } } else { d })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolOutput;
+
+    #[test]
+    fn test_scrub_kinds() {
+        let scrubber = PiiScrubber::new(true);
+        let (text, counts) = scrubber.scrub(
+            "Mail jane.doe@example.com or call (555) 123-4567. Card 4111 1111 1111 1111, order 1234567890123. key sk-abcdefghijklmnopqrstuvwx",
+        );
+        assert!(text.contains("[REDACTED:EMAIL]"));
+        assert!(text.contains("[REDACTED:PHONE]"));
+        assert!(text.contains("[REDACTED:CREDIT_CARD]"));
+        assert!(text.contains("[REDACTED:API_KEY]"));
+        // Not a valid card number, so left alone
+        assert!(text.contains("1234567890123"));
+        assert_eq!(counts.values().sum::<usize>(), 4);
+
+        let (untouched, none) = PiiScrubber::new(false).scrub("jane.doe@example.com");
+        assert_eq!(untouched, "jane.doe@example.com");
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_scrub_output_reports_per_turn() {
+        let scrubber = PiiScrubber::new(true);
+        let mut output = ToolOutput::success(
+            serde_json::json!({ "from": "jane.doe@example.com", "body": "hi" }),
+            "Email from jane.doe@example.com",
+        );
+        scrubber.scrub_output("email", Some("turn-1"), &mut output);
+        assert!(!output.summary.contains("jane"));
+        assert_eq!(output.data["from"], "[REDACTED:EMAIL]");
+
+        let report = scrubber.take_report("turn-1").unwrap();
+        assert_eq!(report.counts[&PiiKind::Email], 1);
+        assert_eq!(report.by_tool["email"], 1);
+        assert!(scrubber.take_report("turn-1").is_none());
+    }
+}
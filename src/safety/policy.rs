@@ -0,0 +1,354 @@
+//! Safety Policy
+//!
+//! Declarative allow/deny/confirm rules for tool calls, read from a TOML file so operators
+//! can tune safety without recompiling. Rules match on the tool name, path-like parameters,
+//! the host of a `url` parameter and regexes over parameter values; the first matching rule
+//! decides. `[[limit]]` entries cap how often and how many at once a tool may run. The file
+//! is re-read whenever it changes on disk. See `config/safety_policy.toml`.
+//!
+//! Code execution always needs confirmation: no rule, custom file or `off` can allow it
+//! outright, though a deny rule still refuses it.
+
+use anyhow::Result;
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+use super::DomainPolicy;
+
+/// Used when the policy file does not exist
+const DEFAULT_POLICY: &str = include_str!("../../config/safety_policy.toml");
+
+/// Parameters whose values are treated as filesystem paths
+const PATH_PARAMS: [&str; 9] = ["path", "file_path", "file", "filename", "directory", "dir", "cwd", "target", "database"];
+
+/// Tools that pause for approval whatever the policy says, unless a rule denies them
+const ALWAYS_CONFIRM: [&str; 2] = ["code_exec", "sandbox"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
+    Confirm,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Glob over the tool name
+    #[serde(default)]
+    pub tool: Option<String>,
+    /// Glob over path-like parameters
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Host of the `url` parameter, subdomains included
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Regex over `param`, or over every string parameter
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub param: Option<String>,
+    pub action: PolicyAction,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Rate and concurrency caps for the tools matching `tool`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolLimit {
+    /// Glob over the tool name
+    pub tool: String,
+    /// Calls started in any 60-second window
+    #[serde(default)]
+    pub per_minute: Option<u32>,
+    /// Calls running at the same time
+    #[serde(default)]
+    pub concurrent: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PolicyFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<PolicyRule>,
+    #[serde(default, rename = "limit")]
+    limits: Vec<ToolLimit>,
+}
+
+/// The rule that matched a tool call
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyDecision {
+    pub action: PolicyAction,
+    pub reason: String,
+    /// Position of the rule in the file; `None` for the built-in confirmation of `ALWAYS_CONFIRM` tools
+    pub rule: Option<usize>,
+}
+
+struct CompiledRule {
+    rule: PolicyRule,
+    tool: Option<glob::Pattern>,
+    path: Option<glob::Pattern>,
+    pattern: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn compile(rule: PolicyRule) -> Result<Self> {
+        let glob = |p: &Option<String>| p.as_deref().map(glob::Pattern::new).transpose();
+        Ok(Self {
+            tool: glob(&rule.tool)?,
+            path: glob(&rule.path)?,
+            pattern: rule.pattern.as_deref().map(Regex::new).transpose()?,
+            rule,
+        })
+    }
+
+    fn matches(&self, tool_name: &str, params: &Value) -> bool {
+        if self.tool.as_ref().is_some_and(|t| !t.matches(tool_name)) {
+            return false;
+        }
+        if let Some(ref path) = self.path {
+            let paths: Vec<&str> = PATH_PARAMS.iter().filter_map(|k| params.get(*k).and_then(|v| v.as_str())).collect();
+            if !paths.iter().any(|p| path.matches(p)) {
+                return false;
+            }
+        }
+        if let Some(ref domain) = self.rule.domain {
+            let host = params.get("url").and_then(|u| u.as_str())
+                .and_then(|u| Url::parse(u).ok())
+                .and_then(|u| u.host_str().map(|h| h.to_lowercase()));
+            if !host.is_some_and(|h| DomainPolicy::matches(&domain.to_lowercase(), &h)) {
+                return false;
+            }
+        }
+        if let Some(ref pattern) = self.pattern {
+            let texts = match self.rule.param {
+                Some(ref param) => params.get(param).map(value_text).into_iter().collect(),
+                None => string_values(params),
+            };
+            if !texts.iter().any(|t| pattern.is_match(t)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string())
+}
+
+fn string_values(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().flat_map(string_values).collect(),
+        Value::Object(map) => map.values().flat_map(string_values).collect(),
+        _ => Vec::new(),
+    }
+}
+
+struct Loaded {
+    rules: Vec<CompiledRule>,
+    limits: Vec<(glob::Pattern, ToolLimit)>,
+    modified: Option<SystemTime>,
+}
+
+impl Loaded {
+    fn empty() -> Self {
+        Self { rules: Vec::new(), limits: Vec::new(), modified: None }
+    }
+}
+
+type Compiled = (Vec<CompiledRule>, Vec<(glob::Pattern, ToolLimit)>);
+
+pub struct SafetyPolicy {
+    path: Option<PathBuf>,
+    loaded: RwLock<Loaded>,
+}
+
+impl SafetyPolicy {
+    /// Rules from `toml`; invalid rules are an error
+    pub fn parse(toml: &str) -> Result<Self> {
+        let (rules, limits) = Self::compile(toml)?;
+        Ok(Self { path: None, loaded: RwLock::new(Loaded { rules, limits, modified: None }) })
+    }
+
+    /// Rules from `path`, or the built-in defaults while it does not exist. An invalid file
+    /// is reported and the defaults used until it is fixed.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let policy = Self {
+            path: Some(path.into()),
+            loaded: RwLock::new(Loaded::empty()),
+        };
+        if !policy.reload() {
+            let mut loaded = policy.loaded.write().unwrap();
+            (loaded.rules, loaded.limits) = Self::compile(DEFAULT_POLICY).unwrap_or_default();
+        }
+        policy
+    }
+
+    /// `AGENCY_SAFETY_POLICY` (default `config/safety_policy.toml`; `off` disables every rule)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_SAFETY_POLICY").unwrap_or_else(|_| "config/safety_policy.toml".to_string());
+        if path == "off" {
+            return Self { path: None, loaded: RwLock::new(Loaded::empty()) };
+        }
+        Self::load(path)
+    }
+
+    fn compile(toml: &str) -> Result<Compiled> {
+        let file: PolicyFile = toml::from_str(toml)?;
+        let rules = file.rules.into_iter().map(CompiledRule::compile).collect::<Result<_>>()?;
+        let limits = file.limits.into_iter()
+            .map(|limit| Ok((glob::Pattern::new(&limit.tool)?, limit)))
+            .collect::<Result<_>>()?;
+        Ok((rules, limits))
+    }
+
+    /// Re-read the file if it changed since the last load; returns whether rules from the
+    /// file are in effect
+    fn reload(&self) -> bool {
+        let Some(ref path) = self.path else { return false };
+        let Ok(modified) = std::fs::metadata(path).and_then(|m| m.modified()) else {
+            return false;
+        };
+        if self.loaded.read().unwrap().modified == Some(modified) {
+            return true;
+        }
+        let result = std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Self::compile(&content));
+        let mut loaded = self.loaded.write().unwrap();
+        // Remember the bad edit too, so it is reported once rather than on every call
+        loaded.modified = Some(modified);
+        match result {
+            Ok((rules, limits)) => {
+                info!("Safety policy loaded from {:?} ({} rules, {} limits)", path, rules.len(), limits.len());
+                loaded.rules = rules;
+                loaded.limits = limits;
+                true
+            }
+            Err(e) => {
+                warn!("Ignoring invalid safety policy {:?}, keeping previous rules: {}", path, e);
+                false
+            }
+        }
+    }
+
+    pub fn rules(&self) -> Vec<PolicyRule> {
+        self.reload();
+        self.loaded.read().unwrap().rules.iter().map(|r| r.rule.clone()).collect()
+    }
+
+    /// The first limit whose glob matches `tool_name`, if any
+    pub fn limit_for(&self, tool_name: &str) -> Option<ToolLimit> {
+        self.reload();
+        self.loaded.read().unwrap().limits.iter()
+            .find(|(pattern, _)| pattern.matches(tool_name))
+            .map(|(_, limit)| limit.clone())
+    }
+
+    /// The first rule matching this call, if any. Code execution tools get `Confirm` when
+    /// no rule denies or confirms them.
+    pub fn evaluate(&self, tool_name: &str, params: &Value) -> Option<PolicyDecision> {
+        self.reload();
+        let loaded = self.loaded.read().unwrap();
+        let decision = loaded.rules.iter().enumerate()
+            .find(|(_, r)| r.matches(tool_name, params))
+            .map(|(i, r)| PolicyDecision {
+                action: r.rule.action,
+                reason: r.rule.reason.clone().unwrap_or_else(|| format!("Safety policy rule {}", i + 1)),
+                rule: Some(i),
+            });
+        if ALWAYS_CONFIRM.contains(&tool_name) && decision.as_ref().is_none_or(|d| d.action == PolicyAction::Allow) {
+            return Some(PolicyDecision {
+                action: PolicyAction::Confirm,
+                reason: "High-risk tool call.".to_string(),
+                rule: None,
+            });
+        }
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_default_policy_matches_previous_checks() {
+        let policy = SafetyPolicy::parse(DEFAULT_POLICY).unwrap();
+        let action = |tool: &str, params: Value| policy.evaluate(tool, &params).map(|d| d.action);
+
+        assert_eq!(action("sandbox", json!({"code": "rm -rf /tmp/x", "language": "shell"})), Some(PolicyAction::Confirm));
+        assert_eq!(action("http_request", json!({"url": "https://api.github.com", "method": "POST"})), Some(PolicyAction::Confirm));
+        assert_eq!(action("http_request", json!({"url": "https://api.github.com"})), None);
+        assert_eq!(action("web_search", json!({"query": "rust"})), None);
+        assert_eq!(action("file_system", json!({"action": "write", "path": "config/safety_policy.toml"})), Some(PolicyAction::Deny));
+        assert_eq!(action("file_system", json!({"action": "read", "path": "config/safety_policy.toml"})), None);
+        assert_eq!(policy.limit_for("web_search").and_then(|l| l.per_minute), Some(10));
+        assert!(policy.limit_for("memory_query").is_none());
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let policy = SafetyPolicy::parse(r#"
+            [[rule]]
+            tool = "file_*"
+            path = "/tmp/**"
+            action = "allow"
+
+            [[rule]]
+            tool = "file_*"
+            pattern = "(?i)password"
+            action = "deny"
+            reason = "No secrets"
+
+            [[rule]]
+            domain = "evil.com"
+            action = "deny"
+        "#).unwrap();
+
+        let allowed = policy.evaluate("file_system", &json!({"path": "/tmp/password.txt"})).unwrap();
+        assert_eq!(allowed.action, PolicyAction::Allow);
+        let denied = policy.evaluate("file_system", &json!({"path": "/home/me/password.txt"})).unwrap();
+        assert_eq!((denied.action, denied.reason.as_str(), denied.rule), (PolicyAction::Deny, "No secrets", Some(1)));
+        assert_eq!(policy.evaluate("http_request", &json!({"url": "https://cdn.evil.com/x"})).unwrap().rule, Some(2));
+        assert!(policy.evaluate("http_request", &json!({"url": "https://example.org"})).is_none());
+        assert!(SafetyPolicy::parse("[[rule]]\npattern = \"(\"\naction = \"deny\"").is_err());
+    }
+
+    #[test]
+    fn test_code_execution_always_confirms() {
+        let code = json!({"code": "print(1)"});
+        for toml in ["", "[[rule]]\naction = \"allow\"\n"] {
+            let policy = SafetyPolicy::parse(toml).unwrap();
+            let decision = policy.evaluate("code_exec", &code).unwrap();
+            assert_eq!((decision.action, decision.rule), (PolicyAction::Confirm, None));
+            assert_eq!(policy.evaluate("sandbox", &code).unwrap().action, PolicyAction::Confirm);
+            assert!(policy.evaluate("web_search", &json!({})).is_none_or(|d| d.action == PolicyAction::Allow));
+        }
+
+        let policy = SafetyPolicy::parse("[[rule]]\ntool = \"sandbox\"\naction = \"deny\"\n").unwrap();
+        assert_eq!(policy.evaluate("sandbox", &code).unwrap().action, PolicyAction::Deny);
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.toml");
+        std::fs::write(&path, "[[rule]]\ntool = \"shell\"\naction = \"deny\"\n").unwrap();
+        let policy = SafetyPolicy::load(&path);
+        assert_eq!(policy.evaluate("shell", &json!({})).unwrap().action, PolicyAction::Deny);
+
+        std::fs::write(&path, "[[rule]]\ntool = \"shell\"\naction = \"confirm\"\n").unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(policy.evaluate("shell", &json!({})).unwrap().action, PolicyAction::Confirm);
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{
-    extract::{Json, State, ws::{WebSocketUpgrade, Message as WsMessage}},
-    response::{IntoResponse, Html, Response, sse::{Event, Sse}},
+    extract::{DefaultBodyLimit, Extension, Json, Multipart, Path, Query, State, ws::{WebSocketUpgrade, Message as WsMessage}},
+    response::{IntoResponse, Response, sse::{Event, Sse}},
     routing::{get, post},
     Router,
 };
@@ -14,8 +14,19 @@ use axum::http::StatusCode;
 use tower_http::trace::TraceLayer;
 
 use crate::agent::{Speaker, LLMProvider};
-use crate::memory::EpisodicMemory;
-use crate::orchestrator::Supervisor;
+use crate::memory::{EpisodicMemory, Memory};
+use crate::orchestrator::{AnswerSplitter, ConversationRegistry, ReplayQuery, StreamChunk, Supervisor, TurnEvent, AGENCY_EVENT_BUS, KILL_SWITCH};
+use crate::orchestrator::a2a::{A2ATaskStore, AgentCard, TaskSendParams};
+use crate::orchestrator::profile::{AgencyProfile, ProfileManager};
+use crate::orchestrator::webhooks::{HookRejection, WebhookRegistry};
+use crate::services::assistants;
+use crate::services::auth::{require_auth, OidcValidator, UserIdentity};
+use crate::services::chat_tools::{self, ChatCompletionMessage as Message};
+use crate::services::rate_limit::ClientRateLimiter;
+use crate::services::server_config::ServerConfig;
+use crate::services::web_sessions::{ClientSession, WebSession, WebSessions};
+use crate::services::ws_protocol::{ClientMessage, ServerMessage, TurnState, PROTOCOL_VERSION};
+use crate::tools::{ToolRegistry, UploadKind, UploadStore, UploadedFile};
 
 // --- SOTA: Robust Error Handling ---
 pub struct ServerError(anyhow::Error);
@@ -39,10 +50,36 @@ pub struct AppState {
     pub provider: Arc<dyn LLMProvider>,
     pub start_local: String,
     pub speaker: Arc<Mutex<Speaker>>,
-    pub tx: broadcast::Sender<String>,
+    /// Dashboard messages of clients without a web session
+    pub tx: broadcast::Sender<ServerMessage>,
     pub episodic_memory: Arc<Mutex<EpisodicMemory>>,
     pub supervisor: Arc<Mutex<Supervisor>>,
-    pub current_task: Arc<Mutex<Option<tokio::task::AbortHandle>>>
+    pub current_task: Arc<Mutex<Option<tokio::task::AbortHandle>>>,
+    /// Shared with the supervisor
+    pub tools: Arc<ToolRegistry>,
+    /// The supervisor's guard, checking tool calls the server runs for API clients
+    pub safety: Arc<Mutex<crate::safety::SafetyGuard>>,
+    /// Published at `/.well-known/agent.json`
+    pub agent_card: Arc<AgentCard>,
+    pub a2a_tasks: Arc<A2ATaskStore>,
+    /// Assistants API objects (assistants, threads, runs)
+    pub assistants: Arc<crate::services::assistants::AssistantStore>,
+    /// Inbound webhook endpoints (`/v1/hooks/{hook_id}`)
+    pub hooks: Arc<WebhookRegistry>,
+    /// Independent conversations under `/v1/sessions/{id}`
+    pub conversations: Arc<ConversationRegistry>,
+    /// The supervisor's experience buffer, rated through `/v1/turns/{id}/feedback`
+    pub experiences: Arc<Mutex<crate::agent::rl::ExperienceBuffer>>,
+    /// Turn events of the main conversation, streamed by `/v1/turns/events`
+    pub turn_feed: broadcast::Sender<TurnEvent>,
+    /// Vector memory, whose embedding model `/v1/embeddings` serves
+    pub memory: Arc<dyn Memory>,
+    /// Dashboard channels and running turns of cookie-identified web clients
+    pub web_sessions: Arc<WebSessions>,
+    /// Steering channels of the main conversation's running agents, reachable during a turn
+    pub steering: Arc<Mutex<Vec<tokio::sync::mpsc::Sender<String>>>>,
+    /// `agency_profile.json`, edited through `/v1/profile`
+    pub profile: Arc<ProfileManager>,
 }
 
 #[derive(Deserialize)]
@@ -50,22 +87,26 @@ struct ChatRequest {
     messages: Vec<Message>,
     #[serde(default)]
     stream: bool,
-}
-
-#[derive(Deserialize, Serialize)]
-struct Message {
-    role: String,
-    content: String,
+    /// OpenAI function definitions; read-only registry tools run server-side
+    #[serde(default)]
+    tools: Vec<serde_json::Value>,
+    #[serde(default)]
+    tool_choice: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    /// Tool calls the agency ran to produce the answer, with their results
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_messages: Vec<Message>,
 }
 
 #[derive(Serialize)]
 struct Choice {
     message: Message,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finish_reason: Option<&'static str>,
 }
 
 #[derive(Serialize)]
@@ -135,8 +176,9 @@ impl SentenceBuffer {
     }
 }
 
-pub async fn run_server(state: AppState) -> Result<()> {
+pub async fn run_server(state: AppState, config: ServerConfig) -> Result<()> {
     println!("🏛️  Initializing Nexus SOTA Server...\n");
+    let grpc_state = state.clone();
     
     let tx_metrics = state.tx.clone();
     let mem_metrics = state.episodic_memory.clone();
@@ -147,36 +189,598 @@ pub async fn run_server(state: AppState) -> Result<()> {
         loop {
             interval.tick().await;
             let count = mem_metrics.lock().await.len();
-            let msg = format!("METRICS:{{ \"since\": \"{}\", \"memory\": {}}}", start_local_metrics, count);
-            let _ = tx_metrics.send(msg);
+            let _ = tx_metrics.send(ServerMessage::Metrics { since: start_local_metrics.clone(), memory: count });
         }
     });
 
     let app = Router::new()
-        .route("/", get(dashboard))
+        .route("/", get(crate::services::dashboard::index))
+        .nest_service("/assets", get::<_, _, ()>(crate::services::dashboard::asset))
+        .route("/v1/bootstrap", get(crate::services::dashboard::bootstrap))
+        .route("/healthz", get(crate::services::health::healthz))
+        .route("/readyz", get(crate::services::health::readyz))
         .route("/ws", get(ws_handler))
         .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/tools", get(list_tools))
+        .route("/v1/models", get(list_models))
+        .route("/v1/embeddings", post(embeddings))
+        .route("/v1/models/{*id}", get(get_model))
+        .route("/v1/turns", post(stream_turn))
+        .route("/v1/turns/events", get(watch_turns))
+        .route("/v1/turns/{id}/feedback", post(turn_feedback))
         .route("/v1/responses", post(crate::services::responses::responses_handler))
+        .route("/v1/assistants", post(assistants::create_assistant).get(assistants::list_assistants))
+        .route("/v1/assistants/{id}", get(assistants::get_assistant).delete(assistants::delete_assistant))
+        .route("/v1/threads", post(assistants::create_thread))
+        .route("/v1/threads/runs", post(assistants::create_thread_and_run))
+        .route("/v1/threads/{id}", get(assistants::get_thread).delete(assistants::delete_thread))
+        .route("/v1/threads/{id}/messages", post(assistants::create_message).get(assistants::list_messages))
+        .route("/v1/threads/{id}/runs", post(assistants::create_run).get(assistants::list_runs))
+        .route("/v1/threads/{id}/runs/{run_id}", get(assistants::get_run))
+        .route("/v1/threads/{id}/runs/{run_id}/cancel", post(assistants::cancel_run))
         .route("/v1/a2a/interact", post(a2a_interact_handler))
+        .route("/.well-known/agent.json", get(agent_card))
+        .route("/v1/a2a", post(a2a_rpc_handler))
+        .route("/v1/a2a/capabilities", get(a2a_capabilities))
+        .route("/v1/a2a/tasks", post(a2a_submit_task))
+        .route("/v1/a2a/tasks/{id}", get(a2a_get_task))
+        .route("/v1/a2a/tasks/{id}/cancel", post(a2a_cancel_task))
+        .route("/v1/approvals", get(list_approvals))
+        .route("/v1/approvals/{id}/approve", post(approve_tool_call))
+        .route("/v1/approvals/{id}/reject", post(reject_tool_call))
+        .route("/v1/hooks", get(list_hooks))
+        .route("/v1/hooks/{hook_id}", post(receive_hook))
         .route("/v1/memory/clear", post(clear_memory))
+        .route("/v1/files", post(upload_file).layer(DefaultBodyLimit::max(config.max_upload_bytes)))
+        .route("/v1/files/{id}", get(get_file))
+        .route("/v1/graphql", get(crate::services::graphql::graphiql).post(crate::services::graphql::graphql)
+            .layer(Extension(crate::services::graphql::schema(state.clone()))))
+        .route("/v1/sessions", get(list_sessions))
+        .route("/v1/sessions/{id}", axum::routing::delete(close_session))
+        .route("/v1/sessions/{id}/query", post(session_query))
+        .route("/v1/sessions/{id}/clear", post(clear_session))
+        .route("/v1/sessions/{id}/export", get(crate::services::transcript::export_session))
+        .route("/v1/events", get(replay_events))
+        .route("/v1/events/stream", get(stream_events))
+        .route("/v1/usage", get(usage))
+        .route("/v1/tools/stats", get(tool_stats))
+        .route("/v1/tools/dry_run", get(get_dry_run).post(set_dry_run))
+        .route("/v1/tools/{name}/enable", post(enable_tool))
+        .route("/v1/tools/{name}/disable", post(disable_tool))
+        .route("/v1/tools/{name}/promote", post(promote_tool))
+        .route("/v1/profile", get(get_profile).put(update_profile))
+        .route("/v1/profile/validate", post(validate_profile))
+        .route("/v1/kill_switch", get(kill_switch_status).post(panic_stop))
+        .route("/v1/kill_switch/rearm", post(rearm_kill_switch))
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
+    // Inside the auth layer, so authenticated callers are limited per user
+    let app = match ClientRateLimiter::new(&config.rate_limits) {
+        Some(limiter) => app.layer(axum::middleware::from_fn_with_state(Arc::new(limiter), crate::services::rate_limit::limit)),
+        None => app,
+    };
+    let app = match config.cors()? {
+        Some(cors) => app.layer(cors),
+        None => app,
+    };
+    // With OIDC configured every route except the public ones needs a bearer token
+    let validator = config.auth.oidc()?.map(|oidc| Arc::new(OidcValidator::new(oidc)));
+    let app = match validator {
+        Some(ref validator) => {
+            println!("🔐 OIDC authentication enabled");
+            app.layer(axum::middleware::from_fn_with_state(validator.clone(), require_auth))
+        }
+        None => app,
+    };
 
-    let addr = "0.0.0.0:8002";
+    if let Some(grpc_addr) = config.grpc_addr()? {
+        let service = crate::services::grpc::AgencyGrpc::new(grpc_state, validator);
+        println!("🛰️  gRPC API at {}", grpc_addr);
+        tokio::spawn(async move {
+            if let Err(e) = tonic::transport::Server::builder()
+                .add_service(crate::services::grpc::AgencyServiceServer::new(service))
+                .serve(grpc_addr)
+                .await
+            {
+                eprintln!("❌ gRPC API crashed: {}", e);
+            }
+        });
+    }
+
+    let addr = config.listen_addr()?;
+    if let Some(tls) = config.tls.mode()? {
+        println!("🚀 SOTA Backend Ready: https://{}", addr);
+        return tls.serve(addr, app).await;
+    }
     println!("🚀 SOTA Backend Ready: http://{}", addr);
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    let _ = axum::serve(listener, app).await;
+    let _ = axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await;
 
     Ok(())
 }
 
-async fn clear_memory(State(state): State<AppState>) -> impl IntoResponse {
-    if let Ok(mut supervisor) = state.supervisor.try_lock() {
-        let _ = supervisor.clear_history().await;
+/// Set by `require_auth` when OIDC is enabled
+pub type User = Option<Extension<UserIdentity>>;
+
+/// Supervisor a request runs in. Authenticated users get their own main conversation, and
+/// their session ids are namespaced so one user cannot reach another's.
+pub(crate) async fn conversation_for(state: &AppState, user: &User, session_id: Option<&str>) -> crate::agent::AgentResult<Arc<Mutex<Supervisor>>> {
+    match conversation_key(user, session_id)? {
+        Some(key) => state.conversations.get_for(&key, owner(user)).await,
+        None => Ok(state.supervisor.clone()),
+    }
+}
+
+/// Registry id of the conversation; `None` for the main one
+pub(crate) fn conversation_key(user: &User, session_id: Option<&str>) -> crate::agent::AgentResult<Option<String>> {
+    match (user, session_id) {
+        (Some(Extension(user)), Some(id)) => {
+            if !crate::orchestrator::conversations::valid_session_id(id) {
+                return Err(crate::agent::AgentError::Validation(format!("Invalid session id '{}'", id)));
+            }
+            Ok(Some(user.session_id(id)))
+        }
+        (Some(Extension(user)), None) => Ok(Some(user.conversation_id())),
+        (None, id) => Ok(id.map(str::to_string)),
+    }
+}
+
+/// Dashboard channel and task slot of a web client; clients without a session share the main ones
+async fn web_channel(state: &AppState, session: &ClientSession) -> WebSession {
+    match session.0 {
+        Some(ref id) => state.web_sessions.get(id).await,
+        None => WebSession { tx: state.tx.clone(), current_task: state.current_task.clone() },
+    }
+}
+
+pub(crate) fn owner(user: &User) -> Option<&str> {
+    user.as_ref().map(|Extension(user)| user.user.as_str())
+}
+
+async fn clear_memory(State(state): State<AppState>, user: User, session: ClientSession) -> impl IntoResponse {
+    if let Ok(supervisor) = conversation_for(&state, &user, session.0.as_deref()).await {
+        if let Ok(mut supervisor) = supervisor.try_lock() {
+            let _ = supervisor.clear_history().await;
+        }
     }
     (StatusCode::OK, Json(serde_json::json!({ "status": "cleared" })))
 }
 
+#[derive(Deserialize)]
+struct SessionQuery {
+    query: String,
+}
+
+#[derive(Deserialize)]
+struct TurnRequest {
+    query: String,
+    /// Run in this conversation instead of the main one
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+/// One Supervisor turn as server-sent events, one JSON `TurnEvent` per event
+async fn stream_turn(State(state): State<AppState>, user: User, Json(req): Json<TurnRequest>) -> Response {
+    let supervisor = match conversation_for(&state, &user, req.session_id.as_deref()).await {
+        Ok(supervisor) => supervisor,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    };
+
+    let (sse_tx, sse_rx) = tokio::sync::mpsc::unbounded_channel::<Result<Event, Infallible>>();
+    tokio::spawn(async move {
+        let mut supervisor = supervisor.lock().await;
+        let events = supervisor.handle_stream(&req.query);
+        tokio::pin!(events);
+        while let Some(event) = events.next().await {
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            if sse_tx.send(Ok(Event::default().data(data))).is_err() {
+                break;
+            }
+        }
+    });
+    Sse::new(tokio_stream::wrappers::UnboundedReceiverStream::new(sse_rx)).into_response()
+}
+
+#[derive(Deserialize)]
+struct WatchQuery {
+    /// Watch this conversation instead of the main one
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+/// Every turn of a conversation as server-sent events, one JSON `TurnEvent` per event, whoever
+/// started the turn (a session query, the dashboard or another stream)
+async fn watch_turns(State(state): State<AppState>, user: User, Query(query): Query<WatchQuery>) -> Response {
+    let subscribed = match conversation_key(&user, query.session_id.as_deref()) {
+        Ok(Some(key)) => state.conversations.subscribe_turns(&key, owner(&user)).await,
+        Ok(None) => Ok(state.turn_feed.subscribe()),
+        Err(e) => Err(e),
+    };
+    let mut rx = match subscribed {
+        Ok(rx) => rx,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    };
+
+    let (sse_tx, sse_rx) = tokio::sync::mpsc::unbounded_channel::<Result<Event, Infallible>>();
+    tokio::spawn(async move {
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            if sse_tx.send(Ok(Event::default().data(data))).is_err() {
+                break;
+            }
+        }
+    });
+    Sse::new(tokio_stream::wrappers::UnboundedReceiverStream::new(sse_rx))
+        .keep_alive(axum::response::sse::KeepAlive::default())
+        .into_response()
+}
+
+async fn list_sessions(State(state): State<AppState>, user: User, session: ClientSession) -> impl IntoResponse {
+    let ids = state.conversations.ids().await;
+    let sessions: Vec<String> = match user {
+        Some(Extension(user)) => {
+            let prefix = format!("{}-", user.conversation_id());
+            ids.iter().filter_map(|id| id.strip_prefix(&prefix).map(str::to_string)).collect()
+        }
+        // Other browsers' cookie ids would let a caller join their conversations
+        None => ids.into_iter().filter(|id| !id.starts_with("web-") || Some(id) == session.0.as_ref()).collect(),
+    };
+    Json(serde_json::json!({ "sessions": sessions, "current": session.0 }))
+}
+
+/// One turn in conversation `id`; turns in different conversations run concurrently
+async fn session_query(State(state): State<AppState>, user: User, Path(id): Path<String>, Json(req): Json<SessionQuery>) -> Response {
+    let supervisor = match conversation_for(&state, &user, Some(&id)).await {
+        Ok(supervisor) => supervisor,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    };
+    let _ = state.tx.send(ServerMessage::TurnStarted { message: format!("🚀 Request (session {}): {}", id, req.query) });
+    let result = supervisor.lock().await.handle(&req.query).await;
+    match result {
+        Ok(result) => Json(serde_json::json!({
+            "session_id": id,
+            "answer": result.answer,
+            "success": result.success,
+            "pending_approval": result.pending_approval,
+        })).into_response(),
+        Err(e) => ServerError::from(e).into_response(),
+    }
+}
+
+async fn clear_session(State(state): State<AppState>, user: User, Path(id): Path<String>) -> Result<Response, ServerError> {
+    let supervisor = match conversation_for(&state, &user, Some(&id)).await {
+        Ok(supervisor) => supervisor,
+        Err(e) => return Ok((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response()),
+    };
+    supervisor.lock().await.clear_history().await?;
+    Ok(Json(serde_json::json!({ "status": "cleared" })).into_response())
+}
+
+async fn close_session(State(state): State<AppState>, user: User, Path(id): Path<String>) -> Response {
+    let id = match user {
+        Some(Extension(user)) => user.session_id(&id),
+        None => id,
+    };
+    if state.conversations.close(&id).await {
+        Json(serde_json::json!({ "status": "closed" })).into_response()
+    } else {
+        (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "Unknown session" }))).into_response()
+    }
+}
+
+/// OpenAI-compatible model list, with capability metadata
+async fn list_models() -> impl IntoResponse {
+    let models = crate::tools::ModelManager.catalog().await;
+    Json(serde_json::json!({ "object": "list", "data": models }))
+}
+
+async fn get_model(Path(id): Path<String>) -> Response {
+    match crate::tools::ModelManager.catalog().await.into_iter().find(|m| m.id == id) {
+        Some(model) => Json(model).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": format!("Unknown model '{}'", id) }))).into_response(),
+    }
+}
+
+/// Model behind the vector memory's embeddings
+const EMBEDDING_MODEL: &str = "all-MiniLM-L6-v2";
+/// Most inputs accepted per request, as in OpenAI's API
+const MAX_EMBEDDING_INPUTS: usize = 2048;
+
+#[derive(Deserialize)]
+struct EmbeddingRequest {
+    /// A string or an array of strings
+    input: serde_json::Value,
+    #[serde(default)]
+    encoding_format: Option<String>,
+    #[serde(default)]
+    dimensions: Option<usize>,
+}
+
+impl EmbeddingRequest {
+    /// The texts to embed and whether to return base64 instead of floats
+    fn texts(self) -> Result<(Vec<String>, bool), String> {
+        let texts: Vec<String> = match self.input {
+            serde_json::Value::String(text) => vec![text],
+            serde_json::Value::Array(items) if items.iter().all(|i| i.is_string()) => {
+                items.into_iter().filter_map(|i| i.as_str().map(str::to_string)).collect()
+            }
+            _ => return Err("'input' must be a string or an array of strings".to_string()),
+        };
+        if texts.is_empty() || texts.len() > MAX_EMBEDDING_INPUTS {
+            return Err(format!("'input' must hold 1 to {} strings", MAX_EMBEDDING_INPUTS));
+        }
+        let base64 = match self.encoding_format.as_deref() {
+            None | Some("float") => false,
+            Some("base64") => true,
+            Some(other) => return Err(format!("Unsupported encoding_format '{}'", other)),
+        };
+        Ok((texts, base64))
+    }
+}
+
+/// One embedding as a float array, or as base64 of its little-endian `f32`s
+fn encode_embedding(vector: &[f32], base64: bool) -> serde_json::Value {
+    if base64 {
+        use base64::Engine as _;
+        let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+        serde_json::json!(base64::engine::general_purpose::STANDARD.encode(bytes))
+    } else {
+        serde_json::json!(vector)
+    }
+}
+
+/// OpenAI-compatible embeddings from the vector memory's model, so other local apps need
+/// not load their own copy
+async fn embeddings(State(state): State<AppState>, Json(req): Json<EmbeddingRequest>) -> Response {
+    let bad_request = |message: String| (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": { "message": message, "type": "invalid_request_error" } }))).into_response();
+    let dimensions = req.dimensions;
+    let (texts, base64) = match req.texts() {
+        Ok(parsed) => parsed,
+        Err(message) => return bad_request(message),
+    };
+
+    let vectors = match state.memory.embed_texts(&texts).await {
+        Ok(vectors) => vectors,
+        Err(e) => return ServerError(e).into_response(),
+    };
+    if let Some(dimensions) = dimensions {
+        if vectors.first().is_some_and(|v| v.len() != dimensions) {
+            return bad_request(format!("{} produces {}-dimensional embeddings", EMBEDDING_MODEL, vectors[0].len()));
+        }
+    }
+
+    let data: Vec<serde_json::Value> = vectors.iter().enumerate().map(|(index, vector)| {
+        serde_json::json!({ "object": "embedding", "index": index, "embedding": encode_embedding(vector, base64) })
+    }).collect();
+    let tokens: usize = texts.iter().map(|t| crate::agent::tokenizer::estimate(t)).sum();
+    Json(serde_json::json!({
+        "object": "list",
+        "data": data,
+        "model": EMBEDDING_MODEL,
+        "usage": { "prompt_tokens": tokens, "total_tokens": tokens },
+    })).into_response()
+}
+
+async fn usage() -> impl IntoResponse {
+    let tracker = crate::agent::COST_TRACKER.clone();
+    Json(serde_json::json!({
+        "total": tracker.summary(),
+        "recent": tracker.recent(50),
+    }))
+}
+
+/// User whose events a caller may see: non-operators only get events emitted for them,
+/// operators and unauthenticated servers get every event
+fn event_scope(user: &User) -> Option<String> {
+    user.as_ref().filter(|Extension(user)| !user.operator).map(|Extension(user)| user.user.clone())
+}
+
+/// Logged agency events, filtered by `since` (sequence number), `turn` and `limit`
+async fn replay_events(user: User, Query(mut query): Query<ReplayQuery>) -> impl IntoResponse {
+    query.user = event_scope(&user);
+    Json(serde_json::json!({ "events": AGENCY_EVENT_BUS.replay(&query).await, "last_seq": AGENCY_EVENT_BUS.last_seq() }))
+}
+
+/// Replay missed events, then follow live ones, as server-sent events whose id is the
+/// sequence number; a reconnecting `EventSource` resumes from its `Last-Event-ID`
+async fn stream_events(user: User, headers: axum::http::HeaderMap, Query(mut query): Query<ReplayQuery>) -> Response {
+    query.user = event_scope(&user);
+    if let Some(last) = headers.get("last-event-id").and_then(|v| v.to_str().ok()).and_then(|v| v.parse().ok()) {
+        query.since = Some(last);
+    }
+    // Subscribe before replaying so nothing published in between is lost
+    let mut live = AGENCY_EVENT_BUS.subscribe_records();
+    if query.since.is_none() && query.turn.is_none() && query.limit.is_none() {
+        // Nothing to catch up on; only new events
+        query.since = Some(AGENCY_EVENT_BUS.last_seq());
+    }
+    let (sse_tx, sse_rx) = tokio::sync::mpsc::unbounded_channel::<Result<Event, Infallible>>();
+    tokio::spawn(async move {
+        let to_sse = |record: &crate::orchestrator::EventRecord| {
+            Event::default().id(record.seq.to_string()).data(serde_json::to_string(record).unwrap_or_default())
+        };
+        let mut last_sent = query.since.unwrap_or(0);
+        let mut backlog = AGENCY_EVENT_BUS.replay(&query).await;
+        loop {
+            for record in backlog.drain(..) {
+                if record.seq <= last_sent {
+                    continue;
+                }
+                last_sent = record.seq;
+                if sse_tx.send(Ok(to_sse(&record))).is_err() {
+                    return;
+                }
+            }
+            match live.recv().await {
+                Ok(record) if record.seq > last_sent => {
+                    if (query.turn.is_none() || record.turn_id == query.turn) && record.visible_to(query.user.as_deref()) {
+                        backlog.push(record);
+                    }
+                }
+                Ok(_) => {}
+                // Fell behind the live channel; the log has what was dropped
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    backlog = AGENCY_EVENT_BUS.replay(&ReplayQuery { since: Some(last_sent), turn: query.turn.clone(), limit: None, user: query.user.clone() }).await;
+                }
+                Err(_) => break,
+            }
+        }
+    });
+    Sse::new(tokio_stream::wrappers::UnboundedReceiverStream::new(sse_rx)).into_response()
+}
+
+async fn tool_stats() -> impl IntoResponse {
+    let stats = crate::tools::TOOL_ANALYTICS.stats(None).await;
+    Json(serde_json::json!({ "tools": stats }))
+}
+
+#[derive(Deserialize)]
+struct DryRunRequest {
+    enabled: bool,
+}
+
+async fn get_dry_run(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "enabled": state.tools.is_dry_run() }))
+}
+
+async fn set_dry_run(State(state): State<AppState>, Json(req): Json<DryRunRequest>) -> impl IntoResponse {
+    state.tools.set_dry_run(req.enabled);
+    Json(serde_json::json!({ "enabled": req.enabled }))
+}
+
+#[derive(Deserialize)]
+struct PanicRequest {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+async fn kill_switch_status() -> impl IntoResponse {
+    Json(serde_json::json!({ "halted": KILL_SWITCH.is_engaged(), "halt": KILL_SWITCH.halt() }))
+}
+
+/// Abort every running turn, sub-agent and tool call; work is refused until re-armed
+async fn panic_stop(State(state): State<AppState>, body: Option<Json<PanicRequest>>) -> impl IntoResponse {
+    let reason = body.and_then(|Json(req)| req.reason).unwrap_or_else(|| "Panic stop from API".to_string());
+    // Running turns hold the supervisor lock; engaging first makes them return
+    let mut aborted = KILL_SWITCH.engage(reason.as_str());
+    if let Some(handle) = state.current_task.lock().await.take() {
+        handle.abort();
+    }
+    aborted += state.web_sessions.abort_all().await;
+    aborted += state.supervisor.lock().await.panic_stop(&reason).await;
+    let _ = state.tx.send(ServerMessage::State { state: TurnState::Halted });
+    Json(serde_json::json!({ "halted": true, "reason": reason, "aborted_tasks": aborted }))
+}
+
+async fn rearm_kill_switch(State(state): State<AppState>) -> impl IntoResponse {
+    state.supervisor.lock().await.rearm();
+    let _ = state.tx.send(ServerMessage::State { state: TurnState::Rearmed });
+    Json(serde_json::json!({ "halted": false }))
+}
+
+/// Whether `user` may see and decide an approval queued by `conversation`
+fn may_decide(user: &User, conversation: Option<&str>) -> bool {
+    match user {
+        Some(Extension(user)) => conversation.is_some_and(|c| user.owns(c)),
+        None => true,
+    }
+}
+
+async fn list_approvals(State(state): State<AppState>, user: User) -> impl IntoResponse {
+    let approvals = state.supervisor.lock().await.approvals.clone();
+    let pending: Vec<_> = approvals.pending().await.into_iter()
+        .filter(|a| may_decide(&user, a.conversation.as_deref()))
+        .collect();
+    Json(serde_json::json!({ "approvals": pending }))
+}
+
+/// The supervisor of the conversation a queued approval belongs to
+pub(crate) async fn approval_owner(state: &AppState, user: &User, id: &str) -> crate::agent::AgentResult<Arc<Mutex<Supervisor>>> {
+    let entry = state.supervisor.lock().await.approvals.get(id).await;
+    let conversation = entry.as_ref().and_then(|e| e.conversation.clone());
+    if entry.is_some() && !may_decide(user, conversation.as_deref()) {
+        return Err(crate::agent::AgentError::Validation(format!("Unknown approval '{}'", id)));
+    }
+    match conversation {
+        Some(conversation) => state.conversations.get(&conversation).await,
+        None => Ok(state.supervisor.clone()),
+    }
+}
+
+/// Approve a queued tool call and resume the turn; responds once the turn finishes
+async fn approve_tool_call(State(state): State<AppState>, user: User, Path(id): Path<String>) -> Response {
+    let result = match approval_owner(&state, &user, &id).await {
+        Ok(supervisor) => supervisor.lock().await.approve(&id).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(result) => Json(serde_json::json!({
+            "answer": result.answer,
+            "success": result.success,
+            "pending_approval": result.pending_approval,
+        })).into_response(),
+        Err(crate::agent::AgentError::Validation(e)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": e }))).into_response(),
+        Err(e) => ServerError::from(e).into_response(),
+    }
+}
+
+async fn reject_tool_call(State(state): State<AppState>, user: User, Path(id): Path<String>) -> Response {
+    let result = match approval_owner(&state, &user, &id).await {
+        Ok(supervisor) => supervisor.lock().await.reject(&id).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => Json(serde_json::json!({ "status": "rejected" })).into_response(),
+        Err(crate::agent::AgentError::Validation(e)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": e }))).into_response(),
+        Err(e) => ServerError::from(e).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct FeedbackRequest {
+    /// 0.0 (bad) to 1.0 (good)
+    score: f32,
+}
+
+/// Rate a finished turn; the rating trains the reward model and the route learner
+async fn turn_feedback(State(state): State<AppState>, Path(id): Path<String>, Json(req): Json<FeedbackRequest>) -> Response {
+    let routed = crate::orchestrator::ROUTE_LEARNER.apply_feedback(&id, req.score);
+    match state.experiences.lock().await.apply_feedback(&id, req.score) {
+        0 if !routed => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": format!("No logged answers for turn {}", id) }))).into_response(),
+        rated => Json(serde_json::json!({ "rated": rated, "routed": routed })).into_response(),
+    }
+}
+
+async fn list_hooks(State(state): State<AppState>) -> impl IntoResponse {
+    let hooks: Vec<_> = state.hooks.hooks().into_iter().map(|h| serde_json::json!({
+        "id": h.id,
+        "mode": h.mode,
+        "events": h.events,
+    })).collect();
+    Json(serde_json::json!({ "hooks": hooks }))
+}
+
+/// Queue a webhook delivery; the signature is checked against the raw body
+async fn receive_hook(
+    State(state): State<AppState>,
+    Path(hook_id): Path<String>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Response, ServerError> {
+    Ok(match state.hooks.deliver(&hook_id, &headers, &body).await? {
+        Ok(delivery_id) => (StatusCode::ACCEPTED, Json(serde_json::json!({ "status": "queued", "delivery_id": delivery_id }))).into_response(),
+        Err(HookRejection::Ignored(reason)) => Json(serde_json::json!({ "status": "ignored", "reason": reason })).into_response(),
+        Err(HookRejection::Unauthorized) => (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "Invalid webhook signature" }))).into_response(),
+        Err(HookRejection::UnknownHook) => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "Unknown hook" }))).into_response(),
+    })
+}
+
 async fn a2a_interact_handler(
     State(state): State<AppState>,
     Json(interaction): Json<crate::orchestrator::a2a::AgentInteraction>,
@@ -193,212 +797,123 @@ async fn a2a_interact_handler(
     Ok(Json(response))
 }
 
-async fn dashboard(State(state): State<AppState>) -> impl IntoResponse {
-    let start_local = state.start_local.clone();
-    let initial_model = "-".to_string(); 
-    let memory_count = { let memory = state.episodic_memory.lock().await; memory.len() };
-    
-    Html(format!(r####"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>NEXUS | First Principles Interface</title>
-    <script src="https://cdn.jsdelivr.net/npm/marked/marked.min.js"></script>
-    <link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/styles/github-dark.min.css">
-    <script src="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/highlight.min.js"></script>
-    <style>
-        :root {{ --bg-color: #050505; --panel-bg: #0f0f0f; --border-color: #222; --accent-tech: #00ff41; --accent-plain: #ffffff; --accent-warn: #ff9500; --accent-danger: #ff3b30; --accent-assurance: #00e5ff; --font-ui: -apple-system, BlinkMacSystemFont, \"SF Pro Display\", sans-serif; --font-mono: \"SF Mono\", monospace; }}
-
-        body {{ background-color: var(--bg-color); color: #e0e0e0; font-family: var(--font-ui); margin: 0; height: 100vh; display: grid; grid-template-rows: 50px 1fr 60px; overflow: hidden; }}
-        .message-nexus pre {{ background: #111; padding: 15px; border-radius: 6px; border: 1px solid #333; overflow-x: auto; }}
-        .message-nexus code {{ font-family: var(--font-mono); font-size: 13px; color: var(--accent-assurance); }}
-        .message-nexus p {{ margin-top: 0; }}
-        .message-nexus table {{ border-collapse: collapse; width: 100%; margin-bottom: 15px; }}
-        .message-nexus th, .message-nexus td {{ border: 1px solid #333; padding: 8px; text-align: left; }}
-        .message-nexus th {{ background: #1a1a1a; font-size: 12px; text-transform: uppercase; color: #888; }}
-        header {{ background: rgba(10, 10, 10, 0.95); border-bottom: 1px solid var(--border-color); display: flex; align-items: center; justify-content: space-between; padding: 0 20px; backdrop-filter: blur(10px); z-index: 100; }}
-        .brand {{ font-weight: 700; font-size: 14px; letter-spacing: 1px; }}
-        .stats-bar {{ display: flex; gap: 15px; font-size: 11px; font-family: var(--font-mono); color: #666; }}
-        .stat-item {{ display: flex; align-items: center; gap: 6px; background: #111; padding: 4px 10px; border-radius: 4px; border: 1px solid #222; }}
-        .stat-value {{ color: #ccc; }}
-        .mvpk-grid {{ display: grid; grid-template-columns: 35% 45% 20%; gap: 1px; background: var(--border-color); height: 100%; overflow: hidden; }}
-        .view-panel {{ background: var(--bg-color); display: flex; flex-direction: column; overflow: hidden; }}
-        .panel-header {{ padding: 10px 15px; font-size: 10px; text-transform: uppercase; letter-spacing: 1.5px; color: #555; border-bottom: 1px solid var(--border-color); background: rgba(20, 20, 20, 0.5); display: flex; justify-content: space-between; }}
-        .scroll-area {{ flex: 1; overflow-y: auto; padding: 20px; }}
-        #tech-content {{ font-family: var(--font-mono); font-size: 12px; line-height: 1.5; color: var(--accent-tech); white-space: pre-wrap; opacity: 0.8; }}
-        #plain-content {{ font-family: var(--font-ui); font-size: 15px; line-height: 1.6; color: #eee; white-space: pre-wrap; }}
-        .message-nexus {{ color: #fff; margin-bottom: 20px; padding: 15px; background: rgba(255,255,255,0.03); border-radius: 6px; border-left: 2px solid var(--accent-plain); }}
-        .message-user {{ color: #888; font-style: italic; border-left: 2px solid #444; padding-left: 10px; margin-bottom: 15px; }}
-        .r-value-display {{ font-size: 42px; font-weight: 200; font-family: var(--font-mono); color: var(--accent-assurance); text-align: center; }}
-        .assurance-log {{ flex: 1; font-family: var(--font-mono); font-size: 10px; color: #555; overflow-y: auto; padding: 10px; }}
-        .input-area {{ background: #0a0a0a; border-top: 1px solid var(--border-color); display: flex; align-items: center; padding: 0 20px; gap: 15px; }}
-        #chat-input {{ flex: 1; background: transparent; border: none; color: #fff; outline: none; font-size: 14px; font-family: var(--font-ui); }}
-        .btn {{ background: #222; border: 1px solid #333; color: #ccc; padding: 6px 12px; font-size: 11px; border-radius: 4px; cursor: pointer; }}
-    </style>
-</head>
-<body>
-    <header>
-        <div style="font-weight:700;">❖ NEXUS <span style="font-weight:200; opacity:0.5;">SoTA</span></div>
-        <div class="stats-bar">
-            <div class="stat-item"><span>MODEL</span><span class="stat-value" id="model-val">{}</span></div>
-            <div class="stat-item"><span>SINCE</span><span class="stat-value" id="uptime-val">{}</span></div>
-            <div class="stat-item"><span>MEMORY</span><span class="stat-value" id="memory-val">{} Turns</span></div>
-        </div>
-    </header>
-
-    <div class="mvpk-grid">
-        <div class="view-panel">
-            <div class="panel-header"><span>TechView</span><span>Internal_Projection</span></div>
-            <div class="scroll-area" id="tech-scroll"><div id="tech-content"></div></div>
-        </div>
-        <div class="view-panel">
-            <div class="panel-header"><span>PlainView</span><span>Publication_Surface</span></div>
-            <div class="scroll-area" id="plain-scroll"><div id="plain-content"></div></div>
-        </div>
-        <div class="view-panel">
-            <div class="panel-header"><span>Assurance</span><span>Reliability_Metrics</span></div>
-            <div style="background:#080808; flex:1; display:flex; flex-direction:column; padding:20px;">
-                <div class="r-value-display" id="r-value">1.00</div>
-                <div style="font-size:9px; color:#444; text-align:center; margin-bottom:20px;">CONFIDENCE SCORE</div>
-                <div class="assurance-log" id="assurance-log"></div>
-            </div>
-        </div>
-    </div>
-
-    <div class="input-area">
-        <input type="text" id="chat-input" placeholder="Type a message for Nexus..." autocomplete="off">
-        <button class="btn" id="send-btn" onclick="sendQuery()">Send</button>
-        <button class="btn" id="stop-btn" style="display:none; background:var(--accent-danger); border-color:#500;" onclick="stopInference()">Stop</button>
-        <button class="btn" onclick="clearMemory()">Wipe</button>
-    </div>
-
-    <script>
-        const ws = new WebSocket('ws://' + location.host + '/ws');
-        const techContent = document.getElementById('tech-content');
-        const plainContent = document.getElementById('plain-content');
-        const assuranceLog = document.getElementById('assurance-log');
-        const rValue = document.getElementById('r-value');
-        const chatInput = document.getElementById('chat-input');
-        const sendBtn = document.getElementById('send-btn');
-        const stopBtn = document.getElementById('stop-btn');
-        
-        let currentTechBlock = null;
-        let currentPlainBlock = null;
-        let currentPlainRaw = '';
-        let isAnswerMode = false;
-
-        marked.setOptions({{
-            highlight: function(code, lang) {{
-                if (lang && hljs.getLanguage(lang)) {{ try {{ return hljs.highlight(code, {{ language: lang }}).value; }} catch (err) {{}} }} 
-                try {{ return hljs.highlightAuto(code).value; }} catch (err) {{}} 
-                return '';
-            }},
-            breaks: true,
-            gfm: true
-        }});
-
-        ws.onmessage = (e) => {{ 
-            const data = e.data;
-            if (data.startsWith('METRICS:')) {{ try {{ const m = JSON.parse(data.substring(8)); document.getElementById('uptime-val').textContent = m.since; document.getElementById('memory-val').textContent = m.memory + ' Turns'; }} catch (err) {{}} }} 
-            else if (data.startsWith('THOUGHT:') || (!isAnswerMode && data.startsWith('TOKEN:'))) {{
-                const token = data.startsWith('TOKEN:') ? data.substring(6) : data.substring(8);
-                if (!currentTechBlock) {{ currentTechBlock = document.createElement('span'); techContent.appendChild(currentTechBlock); }}
-                currentTechBlock.textContent += token;
-                document.getElementById('tech-scroll').scrollTop = techContent.scrollHeight;
-                if (data.startsWith('THOUGHT:')) isAnswerMode = false;
-            }} else if (data.startsWith('ANSWER:') || (isAnswerMode && data.startsWith('TOKEN:'))) {{
-                isAnswerMode = true;
-                const token = data.startsWith('TOKEN:') ? data.substring(6) : data.substring(7);
-                if (!currentPlainBlock) {{ currentPlainBlock = document.createElement('div'); currentPlainBlock.className = 'message-nexus'; plainContent.appendChild(currentPlainBlock); currentPlainRaw = ''; }} 
-                let clean = token.replace(/[[A-Z]ANSWER]|ANSWER:/gi, '');
-                if (currentPlainRaw === '') clean = clean.replace(/^]\s*/, '');
-                currentPlainRaw += clean;
-                currentPlainBlock.innerHTML = marked.parse(currentPlainRaw);
-                currentPlainBlock.querySelectorAll('pre code').forEach((block) => hljs.highlightElement(block));
-                document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
-            }} else if (data.startsWith('FINAL_ANSWER:')) {{
-                const answer = data.substring(13);
-                if (!currentPlainBlock || currentPlainRaw.trim() === '') {{
-                    isAnswerMode = true;
-                    if (!currentPlainBlock) {{ currentPlainBlock = document.createElement('div'); currentPlainBlock.className = 'message-nexus'; plainContent.appendChild(currentPlainBlock); }}
-                    currentPlainRaw = answer;
-                    currentPlainBlock.innerHTML = marked.parse(currentPlainRaw);
-                    currentPlainBlock.querySelectorAll('pre code').forEach((block) => hljs.highlightElement(block));
-                    document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
-                }}
-            }} else if (data.startsWith('RELIABILITY:')) {{
-                const val = parseFloat(data.substring(12));
-                rValue.textContent = val.toFixed(2);
-                logAssurance('Audit', 'R-Score: ' + val.toFixed(2));
-            }} else if (data.startsWith('PUBLICATION_UPDATE:')) {{ try {{ const pc = JSON.parse(data.substring(19)); logAssurance('PC-Update', `${{pc.pc_type}}: ${{JSON.stringify(pc.value)}} ${{pc.unit || ''}} (Ed: ${{pc.edition}})`); }} catch (err) {{}} }}
-            else if (data.startsWith('BOUNDARY_CROSSING:')) {{ try {{ const claim = JSON.parse(data.substring(18)); logAssurance('Security', `🚨 [Quadrant ${{claim.quadrant}}] ${{claim.claim_id}}: ${{claim.content}}`, 'var(--accent-warn)'); }} catch (err) {{}} }}
-            else if (data.startsWith('ASSURANCE:')) {{ try {{ const a = JSON.parse(data.substring(10)); logAssurance('Telemetry', `Latency: ${{a.latency}}ms`); logAssurance('Telemetry', `Tool Calls: ${{a.tools}}`); logAssurance('Telemetry', `Evidence Nodes: ${{a.evidence}}`); logAssurance('Telemetry', `Scale Class: ${{a.scale}}`); logAssurance('Telemetry', `Model: ${{a.model}}`); document.getElementById('model-val').textContent = a.model; }} catch (err) {{}} }}
-            else if (data.startsWith('STATE:MODEL:')) {{ document.getElementById('model-val').textContent = data.substring(12); }}
-            else if (data.startsWith('STATE:')) {{
-                if (data.startsWith('STATE:ANSWER_START')) {{ isAnswerMode = true; currentPlainBlock = null; currentPlainRaw = ''; if (currentTechBlock) {{ const full = currentTechBlock.textContent; const match = full.match(/[[A-Z]ANSWER]*|ANSWER:?$/i); if (match) currentTechBlock.textContent = full.substring(0, match.index).trim(); }} }} 
-                else if (data.startsWith('STATE:THOUGHT_START')) {{ isAnswerMode = false; currentTechBlock = null; }} 
-                else if (data.startsWith('STATE:TURN_COMPLETE') || data.startsWith('STATE:STOPPED')) {{ isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; sendBtn.style.display = 'inline-block'; stopBtn.style.display = 'none'; }} 
-                else if (data.startsWith('STATE:ABORTED')) {{ isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; }}
-                logAssurance('System', data);
-            }} else if (data.startsWith('🚀 Request')) {{
-                isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; 
-                techContent.innerHTML += '<div style="color:#444; margin:15px 0; border-top:1px solid #222; padding-top:10px;">--- NEW TURN ---</div>'; 
-                assuranceLog.innerHTML = ''; rValue.textContent = '1.00'; sendBtn.style.display = 'none'; stopBtn.style.display = 'inline-block';
-                logAssurance('System', data);
-            }}
-        }};
-
-        function logAssurance(source, msg, color) {{ 
-            const div = document.createElement('div');
-            div.style.marginBottom = '5px';
-            if (color) div.style.color = color;
-            div.innerHTML = `<span style="color:#333;">[${{new Date().toLocaleTimeString()}}]</span> <b>${{source}}:</b> ${{msg}}`;
-            assuranceLog.appendChild(div);
-            assuranceLog.scrollTop = assuranceLog.scrollHeight;
-        }}
-
-        function sendQuery() {{ 
-            const val = chatInput.value.trim();
-            if (!val) return;
-            const div = document.createElement('div');
-            div.className = 'message-user';
-            div.textContent = '> ' + val;
-            plainContent.appendChild(div);
-            ws.send(JSON.stringify({{ type: 'query', content: val }}));
-            chatInput.value = '';
-            document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
-        }}
-
-        function stopInference() {{ 
-            ws.send(JSON.stringify({{ type: 'stop' }}));
-        }}
-
-        chatInput.addEventListener('keypress', (e) => {{ if (e.key === 'Enter') sendQuery(); }});
-
-        async function clearMemory() {{ 
-            if (!confirm('Wipe episodic memory?')) return;
-            await fetch('/v1/memory/clear', {{ method: 'POST' }});
-            location.reload();
-        }}
-    </script>
-</body>
-</html>"####, initial_model, start_local, memory_count))
-}
-
-async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl IntoResponse {
-    ws.on_upgrade(|socket| async move {
+async fn agent_card(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.agent_card.as_ref().clone())
+}
+
+async fn a2a_capabilities(State(state): State<AppState>) -> impl IntoResponse {
+    let mut tools = state.tools.tool_names().await;
+    tools.sort();
+    Json(serde_json::json!({
+        "skills": state.agent_card.skills,
+        "tools": tools,
+        "protocols": ["a2a/jsonrpc", "a2a/interact"],
+    }))
+}
+
+/// Runs an A2A task through the supervisor's peer handling
+async fn submit_a2a_task(state: &AppState, params: TaskSendParams) -> crate::agent::AgentResult<crate::orchestrator::a2a::A2ATask> {
+    let supervisor = state.supervisor.clone();
+    state.a2a_tasks.submit(params, move |agent, text| async move {
+        supervisor.lock().await.handle_peer_request(agent, &text, None).await
+    }).await
+}
+
+async fn a2a_submit_task(State(state): State<AppState>, Json(params): Json<TaskSendParams>) -> Response {
+    match submit_a2a_task(&state, params).await {
+        Ok(task) => (StatusCode::ACCEPTED, Json(task)).into_response(),
+        Err(e @ crate::agent::AgentError::Validation(_)) => (StatusCode::CONFLICT, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+async fn a2a_get_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.a2a_tasks.get(&id).await {
+        Some(task) => Json(task).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "Task not found" }))).into_response(),
+    }
+}
+
+async fn a2a_cancel_task(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.a2a_tasks.cancel(&id).await {
+        Some(Ok(task)) => Json(task).into_response(),
+        Some(Err(task)) => (StatusCode::CONFLICT, Json(task)).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "Task not found" }))).into_response(),
+    }
+}
+
+/// A2A JSON-RPC endpoint (`tasks/send`, `tasks/get`, `tasks/cancel`) named by the agent card
+async fn a2a_rpc_handler(State(state): State<AppState>, Json(request): Json<serde_json::Value>) -> impl IntoResponse {
+    let id = request.get("id").cloned().unwrap_or(serde_json::Value::Null);
+    let params = request.get("params").cloned().unwrap_or_default();
+    let task_id = params["id"].as_str().unwrap_or_default().to_string();
+
+    let result = match request["method"].as_str().unwrap_or("") {
+        "tasks/send" => match serde_json::from_value::<TaskSendParams>(params) {
+            Ok(params) => submit_a2a_task(&state, params).await
+                .map(|task| serde_json::to_value(task).unwrap_or_default())
+                .map_err(|e| (-32003, e.to_string())),
+            Err(e) => Err((-32602, format!("Invalid params: {}", e))),
+        },
+        "tasks/get" => state.a2a_tasks.get(&task_id).await
+            .map(|t| serde_json::to_value(t).unwrap_or_default())
+            .ok_or((-32001, "Task not found".to_string())),
+        "tasks/cancel" => match state.a2a_tasks.cancel(&task_id).await {
+            Some(Ok(task)) => Ok(serde_json::to_value(task).unwrap_or_default()),
+            Some(Err(_)) => Err((-32002, "Task cannot be canceled".to_string())),
+            None => Err((-32001, "Task not found".to_string())),
+        },
+        other => Err((-32601, format!("Method not found: {}", other))),
+    };
+
+    Json(match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    })
+}
+
+#[derive(Deserialize)]
+struct WsParams {
+    /// Protocol version the client speaks; without it the dashboard's legacy strings are sent
+    v: Option<u32>,
+}
+
+async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>, Query(params): Query<WsParams>, user: User, session: ClientSession) -> Response {
+    let version = params.v;
+    if version.is_some_and(|v| v == 0 || v > PROTOCOL_VERSION) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Unsupported protocol version; this server speaks up to {}", PROTOCOL_VERSION),
+        }))).into_response();
+    }
+    ws.on_upgrade(move |socket| async move {
         let (mut sender, mut receiver) = socket.split();
-        let mut rx = state.tx.subscribe();
+        let web = web_channel(&state, &session).await;
+        let mut rx = web.tx.subscribe();
+        // Metrics and kill switch states still arrive on the shared channel
+        let mut shared_rx = session.0.as_ref().map(|_| state.tx.subscribe());
         let mut global_rx = crate::orchestrator::event_bus::AGENCY_EVENT_BUS.subscribe();
+
+        if version.is_some() {
+            let _ = web.tx.send(ServerMessage::Hello { version: PROTOCOL_VERSION, session: session.0.clone() });
+        }
         
         let state_c = state.clone();
         
         // Forward turn-specific messages
         tokio::spawn(async move {
             loop {
-                match rx.recv().await {
-                    Ok(msg) => { if sender.send(WsMessage::Text(msg.into())).await.is_err() { break; } },
+                let received = match shared_rx {
+                    Some(ref mut shared_rx) => tokio::select! {
+                        msg = rx.recv() => msg,
+                        msg = shared_rx.recv() => msg,
+                    },
+                    None => rx.recv().await,
+                };
+                match received {
+                    Ok(msg) => {
+                        let Some(frame) = msg.encode(version) else { continue };
+                        if sender.send(WsMessage::Text(frame.into())).await.is_err() { break; }
+                    },
                     Err(broadcast::error::RecvError::Lagged(_)) => continue,
                     Err(_) => break,
                 }
@@ -406,18 +921,15 @@ async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl
         });
 
         // Forward global agency events
-        let sender_c = state.tx.clone();
+        let sender_c = web.tx.clone();
         tokio::spawn(async move {
             loop {
                 match global_rx.recv().await {
                     Ok(event) => {
                         let msg = match event {
-                            crate::orchestrator::event_bus::AgencyEvent::BoundaryCrossing(claim) => {
-                                format!("BOUNDARY_CROSSING:{}", serde_json::to_string(&claim).unwrap_or_default())
-                            },
-                            crate::orchestrator::event_bus::AgencyEvent::PublicationUpdate { pc } => {
-                                format!("PUBLICATION_UPDATE:{}", serde_json::to_string(&pc).unwrap_or_default())
-                            },
+                            crate::orchestrator::event_bus::AgencyEvent::BoundaryCrossing(claim) => ServerMessage::BoundaryCrossing { claim },
+                            crate::orchestrator::event_bus::AgencyEvent::PublicationUpdate { pc } => ServerMessage::PublicationUpdate { pc },
+                            crate::orchestrator::event_bus::AgencyEvent::ToolProgress { tool, chunk } => ServerMessage::ToolProgress { tool, chunk },
                             _ => continue,
                         };
                         if sender_c.send(msg).is_err() { break; }
@@ -428,58 +940,86 @@ async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl
         });
 
         while let Some(Ok(msg)) = receiver.next().await {
-            if let WsMessage::Text(text) = msg {
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                    if json["type"] == "query" {
-                        let query = json["content"].as_str().unwrap_or_default().to_string();
-                        let supervisor = state_c.supervisor.clone();
-                        let tx = state_c.tx.clone();
-                        let current_task = state_c.current_task.clone();
-                        
-                        // Abort existing task
-                        { let mut task_guard = current_task.lock().await; if let Some(handle) = task_guard.take() { handle.abort(); let _ = tx.send("STATE:ABORTED".to_string()); } } 
-
-                        let handle = tokio::spawn(async move { 
-                            let mut supervisor = supervisor.lock().await;
-                            let _ = tx.send(format!("🚀 Request: Orchestrating Agency..."));
-                            let result = supervisor.handle(&query).await;
-                            
-                            match result {
-                                Ok(res) => {
+            let WsMessage::Text(text) = msg else { continue };
+            let message = match serde_json::from_str::<ClientMessage>(&text) {
+                Ok(message) => message,
+                Err(e) => {
+                    // Legacy clients never sent anything else; tell versioned ones what was wrong
+                    if version.is_some() {
+                        let _ = web.tx.send(ServerMessage::Error { message: format!("Invalid message: {}", e) });
+                    }
+                    continue;
+                }
+            };
+            match message {
+                ClientMessage::Query { content: query } => {
+                    let supervisor = match conversation_for(&state_c, &user, session.0.as_deref()).await {
+                        Ok(supervisor) => supervisor,
+                        Err(e) => {
+                            let _ = web.tx.send(ServerMessage::Error { message: e.to_string() });
+                            continue;
+                        }
+                    };
+                    let tx = web.tx.clone();
+                    let current_task = web.current_task.clone();
+                    
+                    // Abort existing task
+                    { let mut task_guard = current_task.lock().await; if let Some(handle) = task_guard.take() { handle.abort(); let _ = tx.send(ServerMessage::State { state: TurnState::Aborted }); } } 
+
+                    let handle = KILL_SWITCH.spawn(crate::tools::cancel_scope(async move { 
+                        let mut supervisor = supervisor.lock().await;
+                        let _ = tx.send(ServerMessage::TurnStarted { message: "🚀 Request: Orchestrating Agency...".to_string() });
+                        let events = supervisor.handle_stream(&query);
+                        tokio::pin!(events);
+
+                        while let Some(event) = events.next().await {
+                            match event {
+                                // Tokens already reach the dashboard as `token` messages through the publishing provider
+                                TurnEvent::TokenChunk { .. } => continue,
+                                TurnEvent::FinalAnswer { ref answer, ref publication, .. } => {
                                     // SOTA: Final Answer Fallback
                                     // If the model was tagless, the tokens went to TechView. 
                                     // We send the final projected answer to ensure it appears in PlainView.
-                                    let _ = tx.send(format!("FINAL_ANSWER:{}", res.answer));
-
-                                    if let Some(pub_obj) = res.publication {
-                                        let _ = tx.send(format!("RELIABILITY:{}", pub_obj.reliability));
-                                        let assurance_json = serde_json::json!({
-                                            "latency": pub_obj.telemetry.latency_ms,
-                                            "tools": pub_obj.telemetry.tool_calls,
-                                            "evidence": pub_obj.telemetry.evidence_count,
-                                            "scale": format!("{:?}", pub_obj.telemetry.scale),
-                                            "model": pub_obj.telemetry.model
-                                        });
-                                        let _ = tx.send(format!("ASSURANCE:{}", assurance_json));
+                                    let _ = tx.send(ServerMessage::FinalAnswer { answer: answer.clone() });
+
+                                    if let Some(pub_obj) = publication {
+                                        let _ = tx.send(ServerMessage::Reliability { score: pub_obj.reliability });
+                                        let _ = tx.send(ServerMessage::assurance(pub_obj));
                                     }
-                                },
-                                Err(e) => {
-                                    let _ = tx.send(format!("THOUGHT:\n🛑 **Error during execution:**\n{}\n", e));
-                                    let _ = tx.send(format!("STATE:ABORTED"));
                                 }
+                                TurnEvent::Error { ref message } => {
+                                    let _ = tx.send(ServerMessage::Error { message: message.clone() });
+                                    let _ = tx.send(ServerMessage::State { state: TurnState::Aborted });
+                                }
+                                _ => {}
                             }
-                            
-                            let _ = tx.send(format!("STATE:TURN_COMPLETE"));
-                        });
-                        
-                        *current_task.lock().await = Some(handle.abort_handle());
-                    } else if json["type"] == "stop" {
-                        let mut task_guard = state_c.current_task.lock().await;
-                        if let Some(handle) = task_guard.take() {
-                            handle.abort();
-                            let _ = state_c.tx.send("STATE:STOPPED".to_string());
-                            let _ = state_c.tx.send("THOUGHT:\n🛑 Inference manually stopped by user.\n".to_string());
+                            let _ = tx.send(ServerMessage::TurnEvent { event });
                         }
+                        
+                        let _ = tx.send(ServerMessage::State { state: TurnState::TurnComplete });
+                    }));
+                    
+                    *current_task.lock().await = Some(handle.abort_handle());
+                }
+                ClientMessage::Stop => {
+                    let mut task_guard = web.current_task.lock().await;
+                    if let Some(handle) = task_guard.take() {
+                        // Aborting drops the turn, cancelling its tool calls only; other sessions keep theirs
+                        handle.abort();
+                        let _ = web.tx.send(ServerMessage::State { state: TurnState::Stopped });
+                        let _ = web.tx.send(ServerMessage::Thought { text: "\n🛑 Inference manually stopped by user.\n".to_string() });
+                    }
+                }
+                ClientMessage::Steer { message } => {
+                    let delivered = match conversation_key(&user, session.0.as_deref()) {
+                        Ok(Some(key)) => state_c.conversations.steer(&key, &message).await,
+                        Ok(None) => Ok(crate::orchestrator::supervisor::steer_agents(&state_c.steering, &message).await),
+                        Err(e) => Err(e),
+                    };
+                    match delivered {
+                        Ok(0) => { let _ = web.tx.send(ServerMessage::Error { message: "No running agents to steer".to_string() }); }
+                        Ok(_) => {}
+                        Err(e) => { let _ = web.tx.send(ServerMessage::Error { message: e.to_string() }); }
                     }
                 }
             }
@@ -487,12 +1027,98 @@ async fn ws_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> impl
     })
 }
 
+/// Store a multipart `file` field under `artifacts/uploads/`; documents are also ingested into
+/// vector memory under their file id
+async fn upload_file(State(state): State<AppState>, user: User, mut multipart: Multipart) -> Result<Response, ServerError> {
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("file") {
+            let filename = field.file_name().unwrap_or("upload").to_string();
+            upload = Some((filename, field.bytes().await?));
+        }
+    }
+    let Some((filename, bytes)) = upload else {
+        return Ok((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": { "message": "Missing multipart field 'file'", "type": "invalid_request_error" } }))).into_response());
+    };
+
+    let uploads = UploadStore::default();
+    let mut file = uploads.store(&filename, &bytes, owner(&user)).await?;
+    if file.kind.is_document() {
+        let source = format!("file://{}", file.path.display());
+        let ingested = match crate::memory::documents::extract_text(&file.path).await {
+            Ok(text) => crate::memory::documents::ingest_text(state.memory.as_ref(), &text, &file.id, &source, &["upload"]).await,
+            Err(e) => Err(e),
+        };
+        match ingested {
+            Ok(ids) => { file.chunks = ids.len(); uploads.save(&file).await?; }
+            Err(e) => tracing::warn!("Upload {}: ingestion failed: {}", file.id, e),
+        }
+    }
+    tracing::info!("Upload {}: {} ({:?}, {} bytes)", file.id, file.filename, file.kind, file.bytes);
+    Ok(Json(file.to_openai()).into_response())
+}
+
+/// An upload `user` may read
+async fn visible_file(user: &User, id: &str) -> Option<UploadedFile> {
+    UploadStore::default().get(id).await.ok().filter(|file| file.owner.is_none() || file.owner.as_deref() == owner(user))
+}
+
+async fn get_file(user: User, Path(id): Path<String>) -> Response {
+    match visible_file(&user, &id).await {
+        Some(file) => Json(file.to_openai()).into_response(),
+        None => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": { "message": format!("No such file '{}'", id), "type": "invalid_request_error" } }))).into_response(),
+    }
+}
+
+/// `content` with a note for each upload id it mentions: how to look at images, and the
+/// document passages most relevant to the message
+async fn attach_files(state: &AppState, user: &User, content: &str) -> String {
+    let mut ids: Vec<&str> = content.split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .filter(|word| crate::tools::is_file_id(word))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut attached = content.to_string();
+    for id in ids {
+        let Some(file) = visible_file(user, id).await else { continue };
+        match file.kind {
+            UploadKind::Image => attached.push_str(&format!(
+                "\n\n[{}: image '{}'. Use the vision tool's describe action with image_source \"{}\" to see it.]", id, file.filename, id)),
+            kind if kind.is_document() => {
+                let passages = state.memory.search(content, 2, Some(id), None).await.unwrap_or_default();
+                attached.push_str(&format!("\n\n[{}: document '{}']", id, file.filename));
+                for passage in passages {
+                    attached.push_str(&format!("\n{}", passage.content));
+                }
+            }
+            _ => attached.push_str(&format!("\n\n[{}: binary file '{}' at {}]", id, file.filename, file.path.display())),
+        }
+    }
+    attached
+}
+
 async fn chat_completions(
     State(state): State<AppState>,
-    Json(req): Json<ChatRequest>,
+    user: User,
+    session: ClientSession,
+    Json(mut req): Json<ChatRequest>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let last_msg = req.messages.last().map(|m| m.content.clone()).unwrap_or_default();
-    let history = { let mut memory = state.episodic_memory.lock().await; if !last_msg.is_empty() { memory.add_user(&last_msg); } memory.format_as_chatml() };
+    if let Some(last) = req.messages.last_mut() {
+        if let Some(ref content) = last.content {
+            last.content = Some(attach_files(&state, &user, content).await);
+        }
+    }
+    // Authenticated users and web sessions chat against their own episodic memory
+    let episodic_memory = match (&user, &session.0) {
+        (None, None) => state.episodic_memory.clone(),
+        _ => conversation_for(&state, &user, session.0.as_deref()).await?.lock().await.episodic_memory.clone(),
+    };
+    let tx = web_channel(&state, &session).await.tx;
+    let last_msg = req.messages.last().map(|m| m.text().to_string()).unwrap_or_default();
+    if !req.tools.is_empty() {
+        return chat_completions_with_tools(state, tx, episodic_memory, last_msg, req).await;
+    }
+    let history = { let mut memory = episodic_memory.lock().await; if !last_msg.is_empty() { memory.add_user(&last_msg); } memory.format_as_chatml() };
 
     let prompt = format!(
         "<|im_start|>system\nYou are a high-fidelity intelligence layer. 
@@ -501,8 +1127,7 @@ Use [THOUGHT] for your internal reasoning and [ANSWER] for the final user surfac
         history
     );
 
-    let tx = state.tx.clone();
-    let _ = tx.send(format!("🚀 Request (Streaming Inference)"));
+    let _ = tx.send(ServerMessage::TurnStarted { message: "🚀 Request (Streaming Inference)".to_string() });
 
     let mut stream = state.provider.generate_stream("standard", prompt, None).await
         .map_err(|e| ServerError(e))?;
@@ -513,26 +1138,24 @@ Use [THOUGHT] for your internal reasoning and [ANSWER] for the final user surfac
         
         tokio::task::spawn(async move {
             let mut tts = SentenceBuffer::new(state_c.speaker.clone());
-            let mut full_response = String::new();
-            let mut answer_started = false;
+            let mut splitter = AnswerSplitter::default();
 
             while let Some(chunk_res) = stream.next().await {
                 if let Ok(text) = chunk_res {
-                    full_response.push_str(&text);
+                    let chunks = splitter.push(&text);
 
                     // SOTA: Proactive Stop Detection (Direct Endpoint)
-                    if full_response.ends_with("<|im_end|>") || full_response.ends_with("<|eot_id|>") { break; }
+                    if splitter.text().ends_with("<|im_end|>") || splitter.text().ends_with("<|eot_id|>") { break; }
 
-                    if !answer_started && (full_response.contains("[ANSWER]") || full_response.to_uppercase().contains("ANSWER:")) {
-                        answer_started = true;
-                        let _ = state_c.tx.send("STATE:ANSWER_START".to_string());
-                    }
-                    if answer_started {
-                        let clean = text.replace("[ANSWER]", "").replace("ANSWER:", "");
-                        let _ = state_c.tx.send(format!("ANSWER:{}", clean));
-                        let _ = tts.push(&clean).await;
-                    } else {
-                        let _ = state_c.tx.send(format!("THOUGHT:{}", text));
+                    for chunk in chunks {
+                        match chunk {
+                            StreamChunk::AnswerStart => { let _ = tx.send(ServerMessage::State { state: TurnState::AnswerStart }); }
+                            StreamChunk::Answer(clean) => {
+                                let _ = tx.send(ServerMessage::Answer { text: clean.clone() });
+                                let _ = tts.push(&clean).await;
+                            }
+                            StreamChunk::Thought(text) => { let _ = tx.send(ServerMessage::Thought { text }); }
+                        }
                     }
                     
                     let resp = StreamResponse { choices: vec![StreamChoice { delta: StreamDelta { content: text } } ] };
@@ -540,8 +1163,8 @@ Use [THOUGHT] for your internal reasoning and [ANSWER] for the final user surfac
                 }
             }
             tts.flush().await;
-            let mut memory = state_c.episodic_memory.lock().await;
-            memory.add_assistant(full_response, Some("Nexus".to_string()));
+            let mut memory = episodic_memory.lock().await;
+            memory.add_assistant(splitter.text(), Some("Nexus".to_string()));
             let _ = sse_tx.send(Ok(Event::default().data("[DONE]")));
         });
         Ok(Sse::new(tokio_stream::wrappers::UnboundedReceiverStream::new(sse_rx)).into_response())
@@ -553,8 +1176,193 @@ Use [THOUGHT] for your internal reasoning and [ANSWER] for the final user surfac
                 full_response.push_str(&chunk);
             }
         }
-        let mut memory = state.episodic_memory.lock().await;
+        let mut memory = episodic_memory.lock().await;
         memory.add_assistant(full_response.clone(), Some("Nexus".to_string()));
-        Ok(Json(ChatResponse { choices: vec![Choice { message: Message { role: "assistant".to_string(), content: full_response } } ] }).into_response())
+        Ok(Json(ChatResponse { choices: vec![Choice { message: Message::assistant(full_response), finish_reason: None }], tool_messages: Vec::new() }).into_response())
+    }
+}
+/// The enabled tools in OpenAI function format, ready to pass as `tools`, plus every registered
+/// tool with its schema, work scope, capabilities and state
+async fn list_tools(State(state): State<AppState>) -> impl IntoResponse {
+    let mut names = state.tools.all_tool_names().await;
+    names.sort();
+    let mut tools = Vec::new();
+    for name in names {
+        let Some(tool) = state.tools.get_registered_tool(&name).await else { continue };
+        tools.push(serde_json::json!({
+            "name": name,
+            "description": tool.description(),
+            "parameters": tool.parameters(),
+            "work_scope": tool.work_scope(),
+            "capabilities": tool.capabilities(),
+            "requires_confirmation": tool.requires_confirmation(),
+            "enabled": state.tools.is_enabled(&name).await,
+            "source": state.tools.tool_source(&name),
+        }));
+    }
+    Json(serde_json::json!({ "object": "list", "data": chat_tools::registry_functions(&state.tools).await, "tools": tools }))
+}
+
+async fn enable_tool(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    set_tool_enabled(&state, &name, true).await
+}
+
+async fn disable_tool(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    set_tool_enabled(&state, &name, false).await
+}
+
+async fn set_tool_enabled(state: &AppState, name: &str, enabled: bool) -> Response {
+    match state.tools.set_enabled(name, enabled).await {
+        Ok(changed) => {
+            if changed {
+                crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::ToolManagement, "operator", serde_json::json!({
+                    "tool": name,
+                    "action": if enabled { "enable" } else { "disable" },
+                }));
+            }
+            Json(serde_json::json!({ "name": name, "enabled": enabled, "changed": changed })).into_response()
+        }
+        Err(e) => (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": e.to_string() }))).into_response(),
+    }
+}
+
+/// Move a forged tool from the custom set to the standard set
+async fn promote_tool(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let source = state.tools.tool_source(&name);
+    if let Err(e) = state.tools.promote_tool(&name).await {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": e.to_string() }))).into_response();
+    }
+    let promoted = source == "custom";
+    if promoted {
+        crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::ToolManagement, "operator", serde_json::json!({ "tool": name, "action": "promote" }));
+    }
+    Json(serde_json::json!({ "name": name, "promoted": promoted, "source": state.tools.tool_source(&name) })).into_response()
+}
+
+async fn get_profile(State(state): State<AppState>) -> Result<Response, ServerError> {
+    Ok(Json(state.profile.load().await?).into_response())
+}
+
+/// Parse and check a profile without applying it
+async fn validate_profile(Json(body): Json<serde_json::Value>) -> Response {
+    let errors = match serde_json::from_value::<AgencyProfile>(body) {
+        Ok(profile) => profile.validate().err().unwrap_or_default(),
+        Err(e) => vec![e.to_string()],
+    };
+    Json(serde_json::json!({ "valid": errors.is_empty(), "errors": errors })).into_response()
+}
+
+/// Save the profile and apply it to every supervisor from its next turn on
+async fn update_profile(State(state): State<AppState>, Json(profile): Json<AgencyProfile>) -> Result<Response, ServerError> {
+    if let Err(errors) = profile.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({ "valid": false, "errors": errors }))).into_response());
+    }
+    let previous = state.profile.load().await?;
+    state.profile.save(&profile).await?;
+    crate::orchestrator::conversations::apply_profile(state.supervisor.clone(), profile.clone());
+    state.conversations.set_profile(&profile).await;
+    crate::safety::AUDIT_LOG.record(crate::safety::AuditKind::ProfileUpdate, "operator", serde_json::json!({ "name": profile.name }));
+    Ok(Json(serde_json::json!({ "profile": profile, "restart_required": profile.restart_required(&previous) })).into_response())
+}
+
+/// `chat_completions` for requests with `tools`: read-only registry tools run here, other
+/// calls come back as `tool_calls`. Streaming requests get the result as a single chunk.
+async fn chat_completions_with_tools(
+    state: AppState,
+    tx: broadcast::Sender<ServerMessage>,
+    episodic_memory: Arc<Mutex<EpisodicMemory>>,
+    last_msg: String,
+    req: ChatRequest,
+) -> Result<Response, ServerError> {
+    let _ = tx.send(ServerMessage::TurnStarted { message: "🚀 Request (Tool Calling)".to_string() });
+    let outcome = match chat_tools::run_tool_loop(state.provider.as_ref(), &state.tools, &state.safety, &req.messages, &req.tools, req.tool_choice.as_ref()).await {
+        Ok(outcome) => outcome,
+        Err(e) => return Ok((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": { "message": e.to_string(), "type": "invalid_request_error" } }))).into_response()),
+    };
+
+    if outcome.finish_reason == "stop" {
+        let mut memory = episodic_memory.lock().await;
+        if !last_msg.is_empty() {
+            memory.add_user(&last_msg);
+        }
+        memory.add_assistant(outcome.message.text().to_string(), Some("Nexus".to_string()));
     }
-}
\ No newline at end of file
+
+    if !req.stream {
+        return Ok(Json(ChatResponse {
+            choices: vec![Choice { message: outcome.message, finish_reason: Some(outcome.finish_reason) }],
+            tool_messages: outcome.executed,
+        }).into_response());
+    }
+    let delta = match outcome.message.tool_calls {
+        Some(ref calls) => {
+            let calls: Vec<serde_json::Value> = calls.iter().enumerate()
+                .map(|(index, call)| { let mut call = call.clone(); call["index"] = serde_json::json!(index); call })
+                .collect();
+            serde_json::json!({ "role": "assistant", "tool_calls": calls })
+        }
+        None => serde_json::json!({ "role": "assistant", "content": outcome.message.text() }),
+    };
+    let chunk = serde_json::json!({ "choices": [{ "index": 0, "delta": delta, "finish_reason": outcome.finish_reason }] });
+    let events = vec![
+        Ok::<_, Infallible>(Event::default().data(chunk.to_string())),
+        Ok(Event::default().data("[DONE]")),
+    ];
+    Ok(Sse::new(futures_util::stream::iter(events)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: serde_json::Value) -> EmbeddingRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_embedding_request_validation() {
+        let (texts, base64) = request(serde_json::json!({ "input": "hello" })).texts().unwrap();
+        assert_eq!((texts, base64), (vec!["hello".to_string()], false));
+        let (texts, base64) = request(serde_json::json!({ "input": ["a", "b"], "encoding_format": "base64" })).texts().unwrap();
+        assert_eq!((texts, base64), (vec!["a".to_string(), "b".to_string()], true));
+
+        assert!(request(serde_json::json!({ "input": [] })).texts().is_err());
+        assert!(request(serde_json::json!({ "input": ["a", 1] })).texts().is_err());
+        assert!(request(serde_json::json!({ "input": [[1, 2]] })).texts().is_err());
+        assert!(request(serde_json::json!({ "input": "a", "encoding_format": "int8" })).texts().is_err());
+        let too_many = vec!["x"; MAX_EMBEDDING_INPUTS + 1];
+        assert!(request(serde_json::json!({ "input": too_many })).texts().is_err());
+    }
+
+    #[test]
+    fn test_watched_conversation_is_scoped_to_the_user() {
+        let identity = |user: &str| Some(Extension(UserIdentity {
+            subject: user.to_string(),
+            user: user.to_string(),
+            email: None,
+            name: None,
+            operator: false,
+        }));
+        let (alice, bob) = (identity("alice"), identity("bob"));
+
+        let alice_key = conversation_key(&alice, Some("design")).unwrap().unwrap();
+        assert_ne!(alice_key, conversation_key(&bob, Some("design")).unwrap().unwrap());
+        assert_ne!(alice_key, conversation_key(&alice, None).unwrap().unwrap());
+        assert!(conversation_key(&alice, Some("../design")).is_err());
+        // Without auth the session id is the conversation, and no id is the main one
+        assert_eq!(conversation_key(&None, Some("design")).unwrap(), Some("design".to_string()));
+        assert_eq!(conversation_key(&None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_base64_embeddings_are_little_endian_f32() {
+        use base64::Engine as _;
+        let vector = [1.0f32, -0.5, 0.25];
+        assert_eq!(encode_embedding(&vector, false), serde_json::json!([1.0, -0.5, 0.25]));
+
+        let encoded = encode_embedding(&vector, true);
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.as_str().unwrap()).unwrap();
+        let decoded: Vec<f32> = bytes.chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(decoded, vector);
+    }
+}
@@ -0,0 +1,516 @@
+//! OpenAI Assistants API Compatibility
+//!
+//! Implements `/v1/assistants`, `/v1/threads` and thread runs on top of the Supervisor.
+//! Each thread keeps its own conversation; a run swaps it into the Supervisor for the
+//! duration of the turn so threads never leak into each other or the main session.
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::memory::EpisodicMemory;
+use crate::orchestrator::{SessionManager, Supervisor};
+use crate::server::AppState;
+use crate::services::ws_protocol::ServerMessage;
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, uuid::Uuid::new_v4().simple())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Assistant {
+    pub id: String,
+    pub object: &'static str,
+    pub created_at: i64,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub model: String,
+    pub instructions: Option<String>,
+    pub tools: Vec<Value>,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Thread {
+    pub id: String,
+    pub object: &'static str,
+    pub created_at: i64,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadMessage {
+    pub id: String,
+    pub object: &'static str,
+    pub created_at: i64,
+    pub thread_id: String,
+    pub role: String,
+    pub content: Vec<Value>,
+    pub assistant_id: Option<String>,
+    pub run_id: Option<String>,
+    pub metadata: Value,
+}
+
+impl ThreadMessage {
+    fn new(thread_id: &str, role: &str, text: &str) -> Self {
+        Self {
+            id: new_id("msg"),
+            object: "thread.message",
+            created_at: now(),
+            thread_id: thread_id.to_string(),
+            role: role.to_string(),
+            content: vec![json!({ "type": "text", "text": { "value": text, "annotations": [] } })],
+            assistant_id: None,
+            run_id: None,
+            metadata: json!({}),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.content.iter()
+            .filter_map(|c| c["text"]["value"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Queued,
+    InProgress,
+    Cancelled,
+    Failed,
+    Completed,
+}
+
+impl RunStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Cancelled | Self::Failed | Self::Completed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    pub id: String,
+    pub object: &'static str,
+    pub created_at: i64,
+    pub thread_id: String,
+    pub assistant_id: String,
+    pub status: RunStatus,
+    pub model: String,
+    pub instructions: Option<String>,
+    pub last_error: Option<Value>,
+    pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub cancelled_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct ThreadState {
+    thread: Option<Thread>,
+    messages: Vec<ThreadMessage>,
+    runs: Vec<Run>,
+}
+
+/// In-memory assistants, threads, messages and runs
+#[derive(Default)]
+pub struct AssistantStore {
+    assistants: Mutex<HashMap<String, Assistant>>,
+    threads: Mutex<HashMap<String, ThreadState>>,
+    handles: Mutex<HashMap<String, tokio::task::AbortHandle>>,
+}
+
+impl AssistantStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn create_thread(&self, metadata: Value, messages: &[NewMessage]) -> Thread {
+        let thread = Thread { id: new_id("thread"), object: "thread", created_at: now(), metadata };
+        let messages = messages.iter().map(|m| ThreadMessage::new(&thread.id, &m.role, &m.text())).collect();
+        self.threads.lock().await.insert(thread.id.clone(), ThreadState { thread: Some(thread.clone()), messages, runs: Vec::new() });
+        thread
+    }
+
+    pub async fn add_message(&self, thread_id: &str, role: &str, text: &str) -> Option<ThreadMessage> {
+        let mut threads = self.threads.lock().await;
+        let state = threads.get_mut(thread_id)?;
+        let message = ThreadMessage::new(thread_id, role, text);
+        state.messages.push(message.clone());
+        Some(message)
+    }
+
+    pub async fn messages(&self, thread_id: &str) -> Option<Vec<ThreadMessage>> {
+        self.threads.lock().await.get(thread_id).map(|s| s.messages.clone())
+    }
+
+    pub async fn run(&self, thread_id: &str, run_id: &str) -> Option<Run> {
+        self.threads.lock().await.get(thread_id)?.runs.iter().find(|r| r.id == run_id).cloned()
+    }
+
+    /// Apply `update` unless the run already finished (e.g. it was cancelled meanwhile)
+    async fn update_run(&self, thread_id: &str, run_id: &str, update: impl FnOnce(&mut Run)) -> Option<Run> {
+        let mut threads = self.threads.lock().await;
+        let run = threads.get_mut(thread_id)?.runs.iter_mut().find(|r| r.id == run_id)?;
+        if !run.status.is_terminal() {
+            update(run);
+        }
+        Some(run.clone())
+    }
+
+    async fn add_reply(&self, thread_id: &str, run: &Run, text: &str) {
+        if let Some(state) = self.threads.lock().await.get_mut(thread_id) {
+            let mut message = ThreadMessage::new(thread_id, "assistant", text);
+            message.assistant_id = Some(run.assistant_id.clone());
+            message.run_id = Some(run.id.clone());
+            state.messages.push(message);
+        }
+    }
+}
+
+/// Prior conversation for the Supervisor plus the query to answer: every user message
+/// since the last assistant reply
+pub fn thread_turn(messages: &[ThreadMessage], instructions: Option<&str>) -> (EpisodicMemory, String) {
+    let mut memory = EpisodicMemory::default();
+    if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+        memory.add_system(instructions);
+    }
+    let pending_from = messages.iter().rposition(|m| m.role == "assistant").map(|i| i + 1).unwrap_or(0);
+    for message in &messages[..pending_from] {
+        match message.role.as_str() {
+            "assistant" => memory.add_assistant(message.text(), None),
+            _ => memory.add_user(message.text()),
+        }
+    }
+    let query = messages[pending_from..].iter().map(|m| m.text()).collect::<Vec<_>>().join("\n\n");
+    (memory, query)
+}
+
+/// Swaps a thread's conversation into the Supervisor and restores the original on drop,
+/// including when the run is cancelled mid-turn
+pub(crate) struct ThreadSession {
+    pub(crate) supervisor: tokio::sync::OwnedMutexGuard<Supervisor>,
+    memory: Option<Arc<Mutex<EpisodicMemory>>>,
+    session: Option<SessionManager>,
+}
+
+impl ThreadSession {
+    pub(crate) async fn enter(supervisor: Arc<Mutex<Supervisor>>, memory: EpisodicMemory) -> Self {
+        let mut supervisor = supervisor.lock_owned().await;
+        let original = std::mem::replace(&mut supervisor.episodic_memory, Arc::new(Mutex::new(memory)));
+        // Threads are not persisted to the interactive session file
+        let session = supervisor.session.take();
+        Self { supervisor, memory: Some(original), session }
+    }
+}
+
+impl Drop for ThreadSession {
+    fn drop(&mut self) {
+        if let Some(memory) = self.memory.take() {
+            self.supervisor.episodic_memory = memory;
+        }
+        self.supervisor.session = self.session.take();
+    }
+}
+
+async fn execute_run(state: AppState, thread_id: String, run_id: String, instructions: Option<String>) {
+    let store = state.assistants.clone();
+    store.update_run(&thread_id, &run_id, |r| { r.status = RunStatus::InProgress; r.started_at = Some(now()); }).await;
+    let messages = store.messages(&thread_id).await.unwrap_or_default();
+    let (memory, query) = thread_turn(&messages, instructions.as_deref());
+    let _ = state.tx.send(ServerMessage::TurnStarted { message: format!("🚀 Request (Assistants run {}): {}", run_id, query) });
+
+    let result = {
+        let mut session = ThreadSession::enter(state.supervisor.clone(), memory).await;
+        session.supervisor.handle(&query).await
+    };
+
+    match result {
+        Ok(result) => {
+            let run = store.update_run(&thread_id, &run_id, |r| { r.status = RunStatus::Completed; r.completed_at = Some(now()); }).await;
+            if let Some(run) = run.filter(|r| r.status == RunStatus::Completed) {
+                store.add_reply(&thread_id, &run, &result.answer).await;
+            }
+        }
+        Err(e) => {
+            store.update_run(&thread_id, &run_id, |r| {
+                r.status = RunStatus::Failed;
+                r.last_error = Some(json!({ "code": "server_error", "message": e.to_string() }));
+            }).await;
+        }
+    }
+    store.handles.lock().await.remove(&run_id);
+}
+
+fn not_found(what: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(json!({ "error": { "message": format!("No {} found", what), "type": "invalid_request_error" } }))).into_response()
+}
+
+fn list<T: Serialize>(items: Vec<T>, ids: Vec<String>) -> Value {
+    json!({
+        "object": "list",
+        "data": items,
+        "first_id": ids.first(),
+        "last_id": ids.last(),
+        "has_more": false,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct CreateAssistantRequest {
+    pub model: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub instructions: Option<String>,
+    #[serde(default)]
+    pub tools: Vec<Value>,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+/// A message as sent by clients: plain string content or text parts
+#[derive(Deserialize)]
+pub struct NewMessage {
+    #[serde(default = "default_role")]
+    pub role: String,
+    pub content: Value,
+}
+
+fn default_role() -> String {
+    "user".to_string()
+}
+
+impl NewMessage {
+    fn text(&self) -> String {
+        match &self.content {
+            Value::String(s) => s.clone(),
+            Value::Array(parts) => parts.iter()
+                .filter_map(|p| p["text"].as_str().or_else(|| p["text"]["value"].as_str()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            other => other.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct CreateThreadRequest {
+    #[serde(default)]
+    pub messages: Vec<NewMessage>,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+#[derive(Deserialize)]
+pub struct CreateRunRequest {
+    pub assistant_id: String,
+    pub instructions: Option<String>,
+    pub additional_instructions: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateThreadAndRunRequest {
+    pub assistant_id: String,
+    #[serde(default)]
+    pub thread: Option<CreateThreadRequest>,
+    pub instructions: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ListQuery {
+    pub order: Option<String>,
+}
+
+pub async fn create_assistant(State(state): State<AppState>, Json(req): Json<CreateAssistantRequest>) -> impl IntoResponse {
+    // Without explicit tools the assistant advertises the whole registry
+    let tools = if req.tools.is_empty() {
+        let mut names = state.tools.tool_names().await;
+        names.sort();
+        let mut tools = Vec::new();
+        for name in names {
+            if let Some(tool) = state.tools.get_tool(&name).await {
+                tools.push(json!({ "type": "function", "function": { "name": tool.name(), "description": tool.description(), "parameters": tool.parameters() } }));
+            }
+        }
+        tools
+    } else {
+        req.tools
+    };
+    let assistant = Assistant {
+        id: new_id("asst"),
+        object: "assistant",
+        created_at: now(),
+        name: req.name,
+        description: req.description,
+        model: req.model.unwrap_or_else(|| "rust_agency_sovereign".to_string()),
+        instructions: req.instructions,
+        tools,
+        metadata: if req.metadata.is_null() { json!({}) } else { req.metadata },
+    };
+    state.assistants.assistants.lock().await.insert(assistant.id.clone(), assistant.clone());
+    Json(assistant)
+}
+
+pub async fn list_assistants(State(state): State<AppState>) -> impl IntoResponse {
+    let mut assistants: Vec<Assistant> = state.assistants.assistants.lock().await.values().cloned().collect();
+    assistants.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+    let ids = assistants.iter().map(|a| a.id.clone()).collect();
+    Json(list(assistants, ids))
+}
+
+pub async fn get_assistant(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.assistants.assistants.lock().await.get(&id) {
+        Some(assistant) => Json(assistant.clone()).into_response(),
+        None => not_found("assistant"),
+    }
+}
+
+pub async fn delete_assistant(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let deleted = state.assistants.assistants.lock().await.remove(&id).is_some();
+    Json(json!({ "id": id, "object": "assistant.deleted", "deleted": deleted }))
+}
+
+pub async fn create_thread(State(state): State<AppState>, body: Option<Json<CreateThreadRequest>>) -> impl IntoResponse {
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let metadata = if req.metadata.is_null() { json!({}) } else { req.metadata };
+    Json(state.assistants.create_thread(metadata, &req.messages).await)
+}
+
+pub async fn get_thread(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.assistants.threads.lock().await.get(&id).and_then(|s| s.thread.clone()) {
+        Some(thread) => Json(thread).into_response(),
+        None => not_found("thread"),
+    }
+}
+
+pub async fn delete_thread(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let deleted = state.assistants.threads.lock().await.remove(&id).is_some();
+    Json(json!({ "id": id, "object": "thread.deleted", "deleted": deleted }))
+}
+
+pub async fn create_message(State(state): State<AppState>, Path(thread_id): Path<String>, Json(req): Json<NewMessage>) -> Response {
+    match state.assistants.add_message(&thread_id, &req.role, &req.text()).await {
+        Some(message) => Json(message).into_response(),
+        None => not_found("thread"),
+    }
+}
+
+pub async fn list_messages(State(state): State<AppState>, Path(thread_id): Path<String>, Query(query): Query<ListQuery>) -> Response {
+    let Some(mut messages) = state.assistants.messages(&thread_id).await else { return not_found("thread") };
+    // Newest first unless `order=asc`, as in the OpenAI API
+    if query.order.as_deref() != Some("asc") {
+        messages.reverse();
+    }
+    let ids = messages.iter().map(|m| m.id.clone()).collect();
+    Json(list(messages, ids)).into_response()
+}
+
+async fn start_run(state: &AppState, thread_id: &str, assistant_id: &str, instructions: Option<String>, additional: Option<String>) -> Response {
+    let Some(assistant) = state.assistants.assistants.lock().await.get(assistant_id).cloned() else { return not_found("assistant") };
+    let instructions = match (instructions.or(assistant.instructions.clone()), additional) {
+        (Some(base), Some(extra)) => Some(format!("{}\n\n{}", base, extra)),
+        (base, extra) => base.or(extra),
+    };
+    let run = Run {
+        id: new_id("run"),
+        object: "thread.run",
+        created_at: now(),
+        thread_id: thread_id.to_string(),
+        assistant_id: assistant.id.clone(),
+        status: RunStatus::Queued,
+        model: assistant.model.clone(),
+        instructions: instructions.clone(),
+        last_error: None,
+        started_at: None,
+        completed_at: None,
+        cancelled_at: None,
+    };
+    {
+        let mut threads = state.assistants.threads.lock().await;
+        let Some(thread) = threads.get_mut(thread_id) else { return not_found("thread") };
+        if thread.runs.iter().any(|r| !r.status.is_terminal()) {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": { "message": format!("Thread {} already has an active run", thread_id), "type": "invalid_request_error" } }))).into_response();
+        }
+        thread.runs.push(run.clone());
+    }
+
+    let handle = tokio::spawn(execute_run(state.clone(), thread_id.to_string(), run.id.clone(), instructions));
+    state.assistants.handles.lock().await.insert(run.id.clone(), handle.abort_handle());
+    Json(run).into_response()
+}
+
+pub async fn create_run(State(state): State<AppState>, Path(thread_id): Path<String>, Json(req): Json<CreateRunRequest>) -> Response {
+    start_run(&state, &thread_id, &req.assistant_id, req.instructions, req.additional_instructions).await
+}
+
+pub async fn create_thread_and_run(State(state): State<AppState>, Json(req): Json<CreateThreadAndRunRequest>) -> Response {
+    let thread_req = req.thread.unwrap_or_default();
+    let metadata = if thread_req.metadata.is_null() { json!({}) } else { thread_req.metadata };
+    let thread = state.assistants.create_thread(metadata, &thread_req.messages).await;
+    start_run(&state, &thread.id, &req.assistant_id, req.instructions, None).await
+}
+
+pub async fn list_runs(State(state): State<AppState>, Path(thread_id): Path<String>) -> Response {
+    let Some(mut runs) = state.assistants.threads.lock().await.get(&thread_id).map(|s| s.runs.clone()) else { return not_found("thread") };
+    runs.reverse();
+    let ids = runs.iter().map(|r| r.id.clone()).collect();
+    Json(list(runs, ids)).into_response()
+}
+
+pub async fn get_run(State(state): State<AppState>, Path((thread_id, run_id)): Path<(String, String)>) -> Response {
+    match state.assistants.run(&thread_id, &run_id).await {
+        Some(run) => Json(run).into_response(),
+        None => not_found("run"),
+    }
+}
+
+pub async fn cancel_run(State(state): State<AppState>, Path((thread_id, run_id)): Path<(String, String)>) -> Response {
+    let Some(run) = state.assistants.run(&thread_id, &run_id).await else { return not_found("run") };
+    if run.status.is_terminal() {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": { "message": format!("Cannot cancel run with status '{:?}'", run.status), "type": "invalid_request_error" } }))).into_response();
+    }
+    if let Some(handle) = state.assistants.handles.lock().await.remove(&run_id) {
+        handle.abort();
+    }
+    let run = state.assistants.update_run(&thread_id, &run_id, |r| {
+        r.status = RunStatus::Cancelled;
+        r.cancelled_at = Some(now());
+    }).await;
+    Json(run).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_thread_turn_answers_pending_messages() {
+        let store = AssistantStore::new();
+        let thread = store.create_thread(json!({}), &[]).await;
+        store.add_message(&thread.id, "user", "Hi").await.unwrap();
+        store.add_message(&thread.id, "assistant", "Hello!").await.unwrap();
+        store.add_message(&thread.id, "user", "Summarize this:").await.unwrap();
+        store.add_message(&thread.id, "user", "Rust is fast.").await.unwrap();
+        assert!(store.add_message("thread_missing", "user", "x").await.is_none());
+
+        let messages = store.messages(&thread.id).await.unwrap();
+        let (memory, query) = thread_turn(&messages, Some("Be brief."));
+        assert_eq!(query, "Summarize this:\n\nRust is fast.");
+        assert_eq!(memory.len(), 3);
+
+        let parts = NewMessage { role: "user".into(), content: json!([{ "type": "text", "text": "a" }, { "type": "text", "text": "b" }]) };
+        assert_eq!(parts.text(), "a\nb");
+    }
+}
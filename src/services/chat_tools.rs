@@ -0,0 +1,381 @@
+//! Tool Calling for Chat Completions
+//!
+//! OpenAI-style `tools` and `tool_choice` on `/v1/chat/completions`. The model sees the
+//! requested functions and answers either in text or with a JSON `tool_calls` object. Calls to
+//! read-only registry tools run server-side (as the `api` caller, so the permission policy
+//! applies, and through the `SafetyGuard`) and their results are fed back until the model
+//! answers. Every other call, including registry tools that write, run code or need
+//! confirmation, is returned as `tool_calls` for the client to run.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::agent::{ChatMessage, GenerationOptions, LLMProvider};
+use crate::safety::SafetyGuard;
+use crate::tools::{Tool, ToolCall, ToolCaller, ToolCapability, ToolRegistry};
+
+/// Model calls executed server-side before the loop gives up
+pub const MAX_TOOL_ROUNDS: usize = 5;
+
+/// A message in OpenAI's chat format
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionMessage {
+    pub role: String,
+    /// `null` on assistant messages that only call tools
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<Value>>,
+    /// Set on `tool` messages: the call this is the result of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl ChatCompletionMessage {
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self { role: "assistant".to_string(), content: Some(content.into()), ..Default::default() }
+    }
+
+    pub fn text(&self) -> &str {
+        self.content.as_deref().unwrap_or_default()
+    }
+}
+
+/// A registry tool in OpenAI function format
+pub fn function_definition(tool: &dyn Tool) -> Value {
+    json!({
+        "type": "function",
+        "function": { "name": tool.name(), "description": tool.description(), "parameters": tool.parameters() },
+    })
+}
+
+/// Every registry tool in OpenAI function format, sorted by name
+pub async fn registry_functions(registry: &ToolRegistry) -> Vec<Value> {
+    let mut names = registry.tool_names().await;
+    names.sort();
+    let mut functions = Vec::new();
+    for name in names {
+        if let Some(tool) = registry.get_tool(&name).await {
+            functions.push(function_definition(tool.as_ref()));
+        }
+    }
+    functions
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolChoice {
+    None,
+    Auto,
+    Required,
+    Function(String),
+}
+
+impl ToolChoice {
+    /// `"none"`, `"auto"` (the default), `"required"` or `{"type": "function", "function": {"name": ...}}`
+    pub fn parse(value: Option<&Value>) -> Result<Self> {
+        match value {
+            None | Some(Value::Null) => Ok(ToolChoice::Auto),
+            Some(Value::String(s)) => match s.as_str() {
+                "none" => Ok(ToolChoice::None),
+                "auto" => Ok(ToolChoice::Auto),
+                "required" => Ok(ToolChoice::Required),
+                other => anyhow::bail!("Unknown tool_choice '{}'", other),
+            },
+            Some(v) => v["function"]["name"].as_str()
+                .map(|name| ToolChoice::Function(name.to_string()))
+                .ok_or_else(|| anyhow::anyhow!("tool_choice object needs function.name")),
+        }
+    }
+}
+
+/// A function offered to the model
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    /// A registry tool the agency may run itself (see `runs_on_server`)
+    pub server_side: bool,
+}
+
+/// Whether API clients may have the server run this registry tool: only read-only tools that
+/// never ask for confirmation. Clients get calls to any other tool back as `tool_calls`.
+pub fn runs_on_server(tool: &dyn Tool) -> bool {
+    tool.capabilities().iter().all(|c| *c == ToolCapability::ReadOnly) && !tool.requires_confirmation()
+}
+
+/// Resolve the request's `tools`. Registry tools may be named without a definition and always
+/// use the registry's schema; other functions are the client's to run.
+pub async fn resolve_tools(registry: &ToolRegistry, tools: &[Value]) -> Result<Vec<FunctionSpec>> {
+    let mut specs = Vec::new();
+    for tool in tools {
+        if tool["type"].as_str().unwrap_or("function") != "function" {
+            anyhow::bail!("Only tools of type 'function' are supported");
+        }
+        let function = &tool["function"];
+        let name = function["name"].as_str().ok_or_else(|| anyhow::anyhow!("Tool definition without function.name"))?;
+        let spec = match registry.get_tool(name).await {
+            Some(tool) => FunctionSpec { name: name.to_string(), description: tool.description(), parameters: tool.parameters(), server_side: runs_on_server(tool.as_ref()) },
+            None => FunctionSpec {
+                name: name.to_string(),
+                description: function["description"].as_str().unwrap_or_default().to_string(),
+                parameters: if function["parameters"].is_null() { json!({ "type": "object" }) } else { function["parameters"].clone() },
+                server_side: false,
+            },
+        };
+        specs.push(spec);
+    }
+    Ok(specs)
+}
+
+/// System prompt describing the functions and how to call them
+pub fn tools_prompt(specs: &[FunctionSpec], choice: &ToolChoice) -> String {
+    let mut prompt = String::from("You can call these functions:\n");
+    for spec in specs {
+        prompt.push_str(&format!("- {}: {} (parameters: {})\n", spec.name, spec.description, spec.parameters));
+    }
+    prompt.push_str("\nTo call functions, reply with only this JSON and nothing else:\n{\"tool_calls\": [{\"name\": \"<function>\", \"arguments\": {...}}]}\n");
+    match choice {
+        ToolChoice::Required => prompt.push_str("You must call at least one function now."),
+        ToolChoice::Function(name) => prompt.push_str(&format!("You must call '{}' now.", name)),
+        _ => prompt.push_str("Otherwise answer the user directly."),
+    }
+    prompt
+}
+
+/// A call the model asked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestedCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+impl RequestedCall {
+    /// OpenAI `tool_calls` entry; `arguments` is a JSON string per the spec
+    pub fn to_openai(&self) -> Value {
+        json!({ "id": self.id, "type": "function", "function": { "name": self.name, "arguments": self.arguments.to_string() } })
+    }
+}
+
+/// Tool calls in a model reply: `{"tool_calls": [...]}` or a single `{"name": ..., "arguments": ...}`,
+/// possibly surrounded by other text. Empty if the reply is a plain answer.
+pub fn parse_tool_calls(text: &str) -> Vec<RequestedCall> {
+    let call = |v: &Value| -> Option<RequestedCall> {
+        let function = if v["function"].is_object() { &v["function"] } else { v };
+        let name = function["name"].as_str()?.to_string();
+        let arguments = match &function["arguments"] {
+            Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| json!({})),
+            Value::Null => function.get("parameters").cloned().unwrap_or_else(|| json!({})),
+            other => other.clone(),
+        };
+        Some(RequestedCall { id: format!("call_{}", uuid::Uuid::new_v4().simple()), name, arguments })
+    };
+
+    for (start, _) in text.match_indices('{') {
+        let Some(Ok(value)) = serde_json::Deserializer::from_str(&text[start..]).into_iter::<Value>().next() else { continue };
+        if let Some(calls) = value["tool_calls"].as_array() {
+            return calls.iter().filter_map(call).collect();
+        }
+        if value.get("name").is_some() && (value.get("arguments").is_some() || value.get("parameters").is_some()) {
+            return call(&value).into_iter().collect();
+        }
+    }
+    Vec::new()
+}
+
+/// The request's messages as provider chat messages
+fn to_chat(messages: &[ChatCompletionMessage]) -> Vec<ChatMessage> {
+    messages.iter().map(|m| match m.role.as_str() {
+        "system" | "developer" => ChatMessage::system(m.text()),
+        "assistant" => match m.tool_calls {
+            Some(ref calls) => ChatMessage::assistant(json!({ "tool_calls": calls }).to_string()),
+            None => ChatMessage::assistant(m.text()),
+        },
+        "tool" => ChatMessage::tool(format!("Result of {}: {}", m.tool_call_id.as_deref().unwrap_or("call"), m.text())),
+        _ => ChatMessage::user(m.text()),
+    }).collect()
+}
+
+/// What the tool loop ended with
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolLoopOutcome {
+    /// The final assistant message: an answer, or calls for the client to run
+    pub message: ChatCompletionMessage,
+    /// `stop` or `tool_calls`
+    pub finish_reason: &'static str,
+    /// Assistant `tool_calls` and `tool` result messages for the calls run server-side
+    pub executed: Vec<ChatCompletionMessage>,
+}
+
+/// Whether every call may run here: a read-only registry tool, not confirmed for these
+/// arguments and not held for human approval by the safety policy
+async fn all_server_side(calls: &[RequestedCall], specs: &[FunctionSpec], registry: &Arc<ToolRegistry>, safety: &Mutex<SafetyGuard>) -> bool {
+    for call in calls {
+        if !specs.iter().any(|s| s.name == call.name && s.server_side) {
+            return false;
+        }
+        let Some(tool) = registry.get_tool(&call.name).await else { return false };
+        if tool.requires_confirmation_for(&call.arguments) {
+            return false;
+        }
+        if safety.lock().await.needs_human_approval(&call.name, &call.arguments, registry.clone()).await.is_some() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Run the conversation, executing read-only registry tool calls until the model answers or
+/// calls anything else
+pub async fn run_tool_loop(
+    provider: &dyn LLMProvider,
+    registry: &Arc<ToolRegistry>,
+    safety: &Mutex<SafetyGuard>,
+    messages: &[ChatCompletionMessage],
+    tools: &[Value],
+    tool_choice: Option<&Value>,
+) -> Result<ToolLoopOutcome> {
+    let specs = resolve_tools(registry, tools).await?;
+    let mut choice = ToolChoice::parse(tool_choice)?;
+    if let ToolChoice::Function(ref name) = choice {
+        if !specs.iter().any(|s| &s.name == name) {
+            anyhow::bail!("tool_choice names '{}', which is not in tools", name);
+        }
+    }
+
+    let mut chat = Vec::new();
+    if choice != ToolChoice::None {
+        chat.push(ChatMessage::system(tools_prompt(&specs, &choice)));
+    }
+    chat.extend(to_chat(messages));
+    let caller = ToolCaller::new("api", None);
+    let mut executed = Vec::new();
+
+    for _ in 0..MAX_TOOL_ROUNDS {
+        let reply = provider.generate_chat("standard", chat.clone(), &GenerationOptions::default()).await?;
+        let calls: Vec<RequestedCall> = match choice {
+            ToolChoice::None => Vec::new(),
+            _ => parse_tool_calls(&reply).into_iter()
+                .filter(|c| specs.iter().any(|s| s.name == c.name))
+                .filter(|c| !matches!(choice, ToolChoice::Function(ref name) if &c.name != name))
+                .collect(),
+        };
+
+        if calls.is_empty() {
+            if matches!(choice, ToolChoice::Required | ToolChoice::Function(_)) {
+                chat.push(ChatMessage::assistant(reply));
+                chat.push(ChatMessage::user("Call a function now, replying with only the tool_calls JSON."));
+                continue;
+            }
+            return Ok(ToolLoopOutcome { message: ChatCompletionMessage::assistant(reply), finish_reason: "stop", executed });
+        }
+
+        let openai_calls: Vec<Value> = calls.iter().map(RequestedCall::to_openai).collect();
+        let assistant = ChatCompletionMessage { role: "assistant".to_string(), content: None, tool_calls: Some(openai_calls), tool_call_id: None };
+        if !all_server_side(&calls, &specs, registry, safety).await {
+            return Ok(ToolLoopOutcome { message: assistant, finish_reason: "tool_calls", executed });
+        }
+
+        chat.push(ChatMessage::assistant(reply));
+        executed.push(assistant);
+        for call in calls {
+            let checked = safety.lock().await.check_tool_safety(&call.name, &call.arguments, registry.clone()).await;
+            let content = match checked {
+                Ok(()) => match registry.execute_as(&ToolCall { name: call.name.clone(), parameters: call.arguments.clone() }, &caller).await {
+                    Ok(output) if output.success => output.summary,
+                    Ok(output) => format!("Error: {}", output.error.unwrap_or(output.summary)),
+                    Err(e) => format!("Error: {}", e),
+                },
+                Err(e) => format!("Error: {}", e),
+            };
+            chat.push(ChatMessage::tool(format!("Result of {} ({}): {}", call.name, call.id, content)));
+            executed.push(ChatCompletionMessage { role: "tool".to_string(), content: Some(content), tool_calls: None, tool_call_id: Some(call.id) });
+        }
+        // A forced call has been made; the model may answer now
+        choice = ToolChoice::Auto;
+    }
+
+    Ok(ToolLoopOutcome {
+        message: ChatCompletionMessage::assistant(format!("Stopped after {} rounds of tool calls without a final answer.", MAX_TOOL_ROUNDS)),
+        finish_reason: "stop",
+        executed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_calls() {
+        let calls = parse_tool_calls("Sure.\n{\"tool_calls\": [{\"name\": \"web_search\", \"arguments\": {\"query\": \"rust {braces}\"}}]}");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "web_search");
+        assert_eq!(calls[0].arguments["query"], "rust {braces}");
+        assert!(calls[0].id.starts_with("call_"));
+        assert_eq!(calls[0].to_openai()["function"]["arguments"], "{\"query\":\"rust {braces}\"}");
+
+        // OpenAI-shaped calls with string arguments, and the ReAct shape, are accepted too
+        let openai = parse_tool_calls(r#"{"tool_calls": [{"type": "function", "function": {"name": "get_weather", "arguments": "{\"city\": \"Oslo\"}"}}]}"#);
+        assert_eq!(openai[0].arguments, json!({ "city": "Oslo" }));
+        let react = parse_tool_calls(r#"{"name": "memory_query", "parameters": {"query": "x"}}"#);
+        assert_eq!(react[0].name, "memory_query");
+
+        assert!(parse_tool_calls("The answer is {42}.").is_empty());
+        assert!(parse_tool_calls("Plain answer.").is_empty());
+    }
+
+    #[test]
+    fn test_tool_choice() {
+        assert_eq!(ToolChoice::parse(None).unwrap(), ToolChoice::Auto);
+        assert_eq!(ToolChoice::parse(Some(&json!("none"))).unwrap(), ToolChoice::None);
+        assert_eq!(
+            ToolChoice::parse(Some(&json!({ "type": "function", "function": { "name": "f" } }))).unwrap(),
+            ToolChoice::Function("f".to_string())
+        );
+        assert!(ToolChoice::parse(Some(&json!("sometimes"))).is_err());
+    }
+
+    /// Replies with the same tool call every time
+    struct CallingProvider(&'static str);
+
+    #[async_trait::async_trait]
+    impl LLMProvider for CallingProvider {
+        async fn generate(&self, _model: &str, _prompt: String, _system: Option<String>) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+        async fn generate_stream(&self, model: &str, prompt: String, system: Option<String>) -> Result<futures::stream::BoxStream<'static, Result<String>>> {
+            let reply = self.generate(model, prompt, system).await?;
+            Ok(Box::pin(futures::stream::once(async move { Ok(reply) })))
+        }
+        fn get_lock(&self) -> Arc<Mutex<()>> {
+            Arc::new(Mutex::new(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_code_running_tools_are_returned_to_the_client() {
+        let registry = Arc::new(ToolRegistry::default());
+        registry.register_instance(crate::tools::CodeExecTool::new()).await;
+        registry.register_instance(crate::tools::SandboxTool::default()).await;
+        let safety = Mutex::new(SafetyGuard::new());
+        let messages = vec![ChatCompletionMessage { role: "user".to_string(), content: Some("run it".to_string()), ..Default::default() }];
+        let tools = vec![json!({ "type": "function", "function": { "name": "code_exec" } }), json!({ "type": "function", "function": { "name": "sandbox" } })];
+
+        let specs = resolve_tools(&registry, &tools).await.unwrap();
+        assert!(specs.iter().all(|s| !s.server_side));
+
+        for reply in [
+            r#"{"tool_calls": [{"name": "code_exec", "arguments": {"language": "python", "code": "open('/tmp/pwned', 'w')"}}]}"#,
+            r#"{"tool_calls": [{"name": "sandbox", "arguments": {"code": "touch /tmp/pwned"}}]}"#,
+        ] {
+            let outcome = run_tool_loop(&CallingProvider(reply), &registry, &safety, &messages, &tools, None).await.unwrap();
+            assert_eq!(outcome.finish_reason, "tool_calls");
+            assert!(outcome.executed.is_empty(), "nothing runs server-side");
+        }
+    }
+}
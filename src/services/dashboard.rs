@@ -0,0 +1,94 @@
+//! Dashboard
+//!
+//! The web UI in `web/dashboard/`: `index.html` at `/` and the rest under `/assets/`. Files are
+//! served from `AGENCY_WEB_DIR` (default `web/dashboard`) when it exists, so the UI can be
+//! edited without rebuilding, and otherwise from the copies embedded in the binary.
+//! `GET /v1/bootstrap` gives the page its initial state.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use rust_embed::RustEmbed;
+use std::path::PathBuf;
+use tower::ServiceExt;
+use tower_http::services::ServeDir;
+
+use crate::server::{AppState, ServerError};
+use crate::services::web_sessions::ClientSession;
+use crate::services::ws_protocol::PROTOCOL_VERSION;
+
+#[derive(RustEmbed)]
+#[folder = "web/dashboard/"]
+struct Assets;
+
+pub fn web_dir() -> PathBuf {
+    PathBuf::from(std::env::var("AGENCY_WEB_DIR").unwrap_or_else(|_| "web/dashboard".to_string()))
+}
+
+/// Embedded copy of `path` with its content type
+fn embedded(path: &str) -> Option<Response> {
+    let file = Assets::get(path)?;
+    Some(([(header::CONTENT_TYPE, file.metadata.mimetype().to_string())], file.data).into_response())
+}
+
+/// `/assets/*`, nested so the request path is relative to the web directory
+pub async fn asset(request: Request) -> Response {
+    let path = request.uri().path().trim_start_matches('/').to_string();
+    let dir = web_dir();
+    if dir.is_dir() {
+        let Ok(response) = ServeDir::new(dir).oneshot(request).await;
+        if response.status() != StatusCode::NOT_FOUND {
+            return response.map(Body::new);
+        }
+    }
+    embedded(&path).unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// `index.html`, handing new browsers their session cookie
+pub async fn index(session: ClientSession) -> Response {
+    let set_cookie = session.0.is_none().then(|| [(header::SET_COOKIE, ClientSession::cookie(&ClientSession::generate()))]);
+    let page = match tokio::fs::read(web_dir().join("index.html")).await {
+        Ok(page) => ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], page).into_response(),
+        Err(_) => embedded("index.html").unwrap_or_else(|| StatusCode::NOT_FOUND.into_response()),
+    };
+    (set_cookie, page).into_response()
+}
+
+/// Header values and session of the calling browser
+pub async fn bootstrap(State(state): State<AppState>, session: ClientSession) -> Result<Response, ServerError> {
+    // Only an already open conversation has memory to count
+    let memory = match session.0 {
+        Some(ref id) if state.conversations.ids().await.contains(id) => match state.conversations.get(id).await?.try_lock() {
+            Ok(supervisor) => supervisor.episodic_memory.lock().await.len(),
+            Err(_) => 0,
+        },
+        _ => 0,
+    };
+    Ok(Json(serde_json::json!({
+        "model": "-",
+        "since": state.start_local,
+        "memory": memory,
+        "session": session.0,
+        "agency": state.agent_card.name,
+        "protocol_version": PROTOCOL_VERSION,
+    })).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_assets() {
+        let page = Assets::get("index.html").unwrap();
+        let page = String::from_utf8_lossy(&page.data);
+        assert!(page.contains("/assets/app.js") && page.contains("/assets/style.css"));
+        for asset in ["app.js", "style.css"] {
+            assert!(Assets::get(asset).is_some(), "{} is not embedded", asset);
+        }
+        assert_eq!(embedded("style.css").unwrap().headers()[header::CONTENT_TYPE], "text/css");
+        assert!(embedded("../Cargo.toml").is_none());
+    }
+}
@@ -0,0 +1,411 @@
+//! Discord Integration (Gateway)
+//!
+//! Exposes the agency in servers and DMs. Each channel has its own episodic memory, and
+//! the `/memory`, `/tools` and `/autonomous` slash commands are registered on startup.
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{info, warn};
+
+use crate::memory::EpisodicMemory;
+use crate::orchestrator::Supervisor;
+use crate::services::assistants::ThreadSession;
+
+const DISCORD_API: &str = "https://discord.com/api/v10";
+const GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+/// GUILD_MESSAGES | DIRECT_MESSAGES | MESSAGE_CONTENT
+const INTENTS: u64 = (1 << 9) | (1 << 12) | (1 << 15);
+/// Discord rejects messages longer than this
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct DiscordConfig {
+    pub token: String,
+    /// Only these guild ids are served (all when empty)
+    pub guilds: Vec<String>,
+    /// Only these user ids may message the bot or run its commands, in servers and DMs;
+    /// empty serves nobody
+    pub users: Vec<String>,
+}
+
+impl DiscordConfig {
+    /// `DISCORD_BOT_TOKEN` and the comma-separated `DISCORD_ALLOWED_USERS` (required),
+    /// plus optional `DISCORD_GUILDS`
+    pub fn from_env() -> Option<Self> {
+        let list = |key: &str| std::env::var(key)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let config = Self {
+            token: std::env::var("DISCORD_BOT_TOKEN").ok()?,
+            guilds: list("DISCORD_GUILDS"),
+            users: list("DISCORD_ALLOWED_USERS"),
+        };
+        if config.users.is_empty() {
+            warn!("Discord: DISCORD_ALLOWED_USERS is not set; staying off rather than answering everyone");
+            return None;
+        }
+        Some(config)
+    }
+}
+
+/// Slash commands registered for the application
+pub fn slash_commands() -> Value {
+    json!([
+        {
+            "name": "memory",
+            "description": "Show or clear this channel's conversation memory",
+            "options": [{
+                "type": 3, "name": "action", "description": "show (default) or clear", "required": false,
+                "choices": [{ "name": "show", "value": "show" }, { "name": "clear", "value": "clear" }]
+            }]
+        },
+        { "name": "tools", "description": "List the tools the agency can use" },
+        {
+            "name": "autonomous",
+            "description": "Pursue a goal autonomously",
+            "options": [{ "type": 3, "name": "goal", "description": "What to achieve", "required": true }]
+        }
+    ])
+}
+
+/// Split text into Discord-sized chunks, preferring line breaks
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.split_inclusive('\n') {
+        if current.chars().count() + line.chars().count() > limit && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if line.chars().count() > limit {
+            let chars: Vec<char> = line.chars().collect();
+            for piece in chars.chunks(limit) {
+                chunks.push(piece.iter().collect());
+            }
+        } else {
+            current.push_str(line);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    if chunks.is_empty() {
+        chunks.push(String::new());
+    }
+    chunks
+}
+
+/// A message or command the bot should act on
+#[derive(Debug, PartialEq)]
+pub enum DiscordIncoming {
+    Message { channel_id: String, message_id: String, text: String },
+    Command { interaction_id: String, token: String, channel_id: String, name: String, options: HashMap<String, String> },
+}
+
+/// Interpret a gateway dispatch (`op` 0) for a bot with user id `bot_id`, dropping
+/// anything from a guild or user the config does not allow
+pub fn parse_dispatch(event: &str, data: &Value, bot_id: &str, config: &DiscordConfig) -> Option<DiscordIncoming> {
+    let guild = data["guild_id"].as_str();
+    if guild.is_some_and(|g| !config.guilds.is_empty() && !config.guilds.iter().any(|allowed| allowed == g)) {
+        return None;
+    }
+    // Messages carry `author`; interactions carry `member.user` in servers and `user` in DMs
+    let user = data["author"]["id"].as_str()
+        .or_else(|| data["member"]["user"]["id"].as_str())
+        .or_else(|| data["user"]["id"].as_str())?;
+    if !config.users.iter().any(|allowed| allowed == user) {
+        return None;
+    }
+    match event {
+        "MESSAGE_CREATE" => {
+            if data["author"]["bot"].as_bool() == Some(true) {
+                return None;
+            }
+            let content = data["content"].as_str().unwrap_or_default();
+            let mentions = [format!("<@{}>", bot_id), format!("<@!{}>", bot_id)];
+            // In servers only mentions are answered; every DM is
+            if guild.is_some() && !mentions.iter().any(|m| content.contains(m.as_str())) {
+                return None;
+            }
+            let text = mentions.iter().fold(content.to_string(), |t, m| t.replace(m.as_str(), "")).trim().to_string();
+            (!text.is_empty()).then(|| DiscordIncoming::Message {
+                channel_id: data["channel_id"].as_str().unwrap_or_default().to_string(),
+                message_id: data["id"].as_str().unwrap_or_default().to_string(),
+                text,
+            })
+        }
+        // Type 2 is an application (slash) command
+        "INTERACTION_CREATE" if data["type"].as_u64() == Some(2) => Some(DiscordIncoming::Command {
+            interaction_id: data["id"].as_str().unwrap_or_default().to_string(),
+            token: data["token"].as_str().unwrap_or_default().to_string(),
+            channel_id: data["channel_id"].as_str().unwrap_or_default().to_string(),
+            name: data["data"]["name"].as_str().unwrap_or_default().to_string(),
+            options: data["data"]["options"].as_array().into_iter().flatten()
+                .filter_map(|o| Some((o["name"].as_str()?.to_string(), o["value"].as_str()?.to_string())))
+                .collect(),
+        }),
+        _ => None,
+    }
+}
+
+pub struct DiscordService {
+    config: DiscordConfig,
+    supervisor: Arc<Mutex<Supervisor>>,
+    client: reqwest::Client,
+    /// Conversation per channel id
+    channels: Mutex<HashMap<String, EpisodicMemory>>,
+    application_id: Mutex<Option<String>>,
+}
+
+impl DiscordService {
+    pub fn new(config: DiscordConfig, supervisor: Arc<Mutex<Supervisor>>) -> Self {
+        Self {
+            config,
+            supervisor,
+            client: reqwest::Client::new(),
+            channels: Mutex::new(HashMap::new()),
+            application_id: Mutex::new(None),
+        }
+    }
+
+    async fn api(&self, method: reqwest::Method, path: &str, body: Value) -> Result<Value> {
+        let response = self.client.request(method, format!("{}{}", DISCORD_API, path))
+            .header("Authorization", format!("Bot {}", self.config.token))
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            anyhow::bail!("Discord {} returned {}: {}", path, status, text);
+        }
+        Ok(serde_json::from_str(&text).unwrap_or(Value::Null))
+    }
+
+    async fn send(&self, channel_id: &str, reply_to: Option<&str>, text: &str) -> Result<()> {
+        for (i, chunk) in split_message(text, MAX_MESSAGE_CHARS).into_iter().enumerate() {
+            let mut body = json!({ "content": chunk });
+            if let (0, Some(message_id)) = (i, reply_to) {
+                body["message_reference"] = json!({ "message_id": message_id });
+            }
+            self.api(reqwest::Method::POST, &format!("/channels/{}/messages", channel_id), body).await?;
+        }
+        Ok(())
+    }
+
+    /// Connect to the gateway and serve until the process exits, reconnecting as needed
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        loop {
+            if let Err(e) = self.clone().serve_connection().await {
+                warn!("Discord gateway connection lost: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn serve_connection(self: Arc<Self>) -> Result<()> {
+        let (socket, _) = tokio_tungstenite::connect_async(GATEWAY_URL).await.context("Failed to open Discord gateway")?;
+        let (mut write, mut read) = socket.split();
+
+        // Heartbeats and identify go through one writer task
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<Value>();
+        let writer = tokio::spawn(async move {
+            while let Some(payload) = out_rx.recv().await {
+                if write.send(WsMessage::Text(payload.to_string().into())).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let sequence = Arc::new(Mutex::new(None::<u64>));
+        let mut heartbeat: Option<tokio::task::JoinHandle<()>> = None;
+        let mut bot_id = String::new();
+
+        let result = async {
+            while let Some(frame) = read.next().await {
+                let text = match frame? {
+                    WsMessage::Text(text) => text.to_string(),
+                    WsMessage::Close(frame) => anyhow::bail!("Gateway closed: {:?}", frame),
+                    _ => continue,
+                };
+                let Ok(payload) = serde_json::from_str::<Value>(&text) else { continue };
+                if let Some(s) = payload["s"].as_u64() {
+                    *sequence.lock().await = Some(s);
+                }
+
+                match payload["op"].as_u64() {
+                    // Hello: start heartbeating, then identify
+                    Some(10) => {
+                        let interval = Duration::from_millis(payload["d"]["heartbeat_interval"].as_u64().unwrap_or(41_250));
+                        let (tx, seq) = (out_tx.clone(), sequence.clone());
+                        heartbeat = Some(tokio::spawn(async move {
+                            let mut ticker = tokio::time::interval(interval);
+                            ticker.tick().await;
+                            loop {
+                                ticker.tick().await;
+                                let s = *seq.lock().await;
+                                if tx.send(json!({ "op": 1, "d": s })).is_err() {
+                                    break;
+                                }
+                            }
+                        }));
+                        let _ = out_tx.send(json!({
+                            "op": 2,
+                            "d": {
+                                "token": self.config.token,
+                                "intents": INTENTS,
+                                "properties": { "os": std::env::consts::OS, "browser": "rust_agency", "device": "rust_agency" }
+                            }
+                        }));
+                    }
+                    // Heartbeat request
+                    Some(1) => { let _ = out_tx.send(json!({ "op": 1, "d": *sequence.lock().await })); }
+                    // Reconnect / invalid session
+                    Some(7) | Some(9) => anyhow::bail!("Gateway asked to reconnect"),
+                    Some(0) => {
+                        let event = payload["t"].as_str().unwrap_or_default();
+                        if event == "READY" {
+                            bot_id = payload["d"]["user"]["id"].as_str().unwrap_or_default().to_string();
+                            let app_id = payload["d"]["application"]["id"].as_str().unwrap_or_default().to_string();
+                            info!("🎮 Discord: connected as {}", payload["d"]["user"]["username"].as_str().unwrap_or("bot"));
+                            *self.application_id.lock().await = Some(app_id.clone());
+                            if let Err(e) = self.api(reqwest::Method::PUT, &format!("/applications/{}/commands", app_id), slash_commands()).await {
+                                warn!("Discord: failed to register slash commands: {}", e);
+                            }
+                            continue;
+                        }
+                        if let Some(incoming) = parse_dispatch(event, &payload["d"], &bot_id, &self.config) {
+                            let service = self.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = service.dispatch(incoming).await {
+                                    warn!("Discord: failed to handle event: {}", e);
+                                }
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        }.await;
+
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.abort();
+        }
+        writer.abort();
+        result
+    }
+
+    async fn dispatch(&self, incoming: DiscordIncoming) -> Result<()> {
+        match incoming {
+            DiscordIncoming::Message { channel_id, message_id, text } => {
+                let _ = self.api(reqwest::Method::POST, &format!("/channels/{}/typing", channel_id), json!({})).await;
+                let answer = match self.run_turn(&channel_id, &text).await {
+                    Ok(answer) => answer,
+                    Err(e) => format!("⚠️ {}", e),
+                };
+                self.send(&channel_id, Some(&message_id), &answer).await
+            }
+            DiscordIncoming::Command { interaction_id, token, channel_id, name, options } => {
+                // Defer first: commands must be acknowledged within 3 seconds
+                self.api(reqwest::Method::POST, &format!("/interactions/{}/{}/callback", interaction_id, token), json!({ "type": 5 })).await?;
+                let reply = self.run_command(&channel_id, &name, &options).await;
+                let app_id = self.application_id.lock().await.clone().unwrap_or_default();
+                let content = split_message(&reply, MAX_MESSAGE_CHARS).into_iter().next().unwrap_or_default();
+                self.api(reqwest::Method::PATCH, &format!("/webhooks/{}/{}/messages/@original", app_id, token), json!({ "content": content })).await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// One Supervisor turn against the channel's own memory
+    async fn run_turn(&self, channel_id: &str, text: &str) -> Result<String> {
+        let memory = self.channels.lock().await.get(channel_id).cloned().unwrap_or_default();
+        let result = {
+            let mut session = ThreadSession::enter(self.supervisor.clone(), memory).await;
+            session.supervisor.handle(text).await?
+        };
+        let mut channels = self.channels.lock().await;
+        let memory = channels.entry(channel_id.to_string()).or_default();
+        memory.add_user(text);
+        memory.add_assistant(&result.answer, Some("Agency".to_string()));
+        Ok(match result.pending_approval {
+            Some(approval) => format!("{}\n\n🛡️ `{}` needs approval; approve it from the dashboard or CLI.", result.answer, approval.tool_name),
+            None => result.answer,
+        })
+    }
+
+    async fn run_command(&self, channel_id: &str, name: &str, options: &HashMap<String, String>) -> String {
+        match name {
+            "memory" => {
+                if options.get("action").map(String::as_str) == Some("clear") {
+                    self.channels.lock().await.remove(channel_id);
+                    return "🧹 Memory for this channel cleared.".to_string();
+                }
+                match self.channels.lock().await.get(channel_id) {
+                    Some(memory) if !memory.is_empty() => format!("🧠 {} turns in this channel:\n{}", memory.len(), memory.format_for_prompt()),
+                    _ => "🧠 No conversation in this channel yet.".to_string(),
+                }
+            }
+            "tools" => {
+                let tools = self.supervisor.lock().await.tools.clone();
+                let mut names = tools.tool_names().await;
+                names.sort();
+                format!("🛠️ {} tools: {}", names.len(), names.join(", "))
+            }
+            "autonomous" => {
+                let Some(goal) = options.get("goal") else { return "Provide a goal.".to_string() };
+                let memory = self.channels.lock().await.get(channel_id).cloned().unwrap_or_default();
+                let mut session = ThreadSession::enter(self.supervisor.clone(), memory).await;
+                match session.supervisor.run_autonomous(goal).await {
+                    Ok(result) => format!("🎯 {}", result.answer),
+                    Err(e) => format!("⚠️ {}", e),
+                }
+            }
+            other => format!("Unknown command: /{}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dispatch() {
+        let config = DiscordConfig { token: String::new(), guilds: vec![], users: vec!["u1".into()] };
+        let mention = json!({ "id": "m1", "channel_id": "c1", "guild_id": "g1", "author": { "id": "u1" }, "content": "<@42> hello there" });
+        assert_eq!(parse_dispatch("MESSAGE_CREATE", &mention, "42", &config), Some(DiscordIncoming::Message {
+            channel_id: "c1".into(), message_id: "m1".into(), text: "hello there".into(),
+        }));
+        // Unmentioned server chatter and other guilds are ignored
+        let chatter = json!({ "id": "m2", "channel_id": "c1", "guild_id": "g1", "author": { "id": "u1" }, "content": "hello" });
+        assert_eq!(parse_dispatch("MESSAGE_CREATE", &chatter, "42", &config), None);
+        let other_guild = DiscordConfig { guilds: vec!["g2".into()], ..config.clone() };
+        assert_eq!(parse_dispatch("MESSAGE_CREATE", &mention, "42", &other_guild), None);
+
+        let command = json!({ "type": 2, "id": "i1", "token": "t", "channel_id": "c1", "member": { "user": { "id": "u1" } }, "data": { "name": "autonomous", "options": [{ "name": "goal", "value": "ship it" }] } });
+        let Some(DiscordIncoming::Command { name, options, .. }) = parse_dispatch("INTERACTION_CREATE", &command, "42", &config) else { panic!("expected command") };
+        assert_eq!(name, "autonomous");
+        assert_eq!(options["goal"], "ship it");
+
+        // Users outside the allowlist get nothing, in DMs or through commands
+        let dm = json!({ "id": "m3", "channel_id": "d1", "author": { "id": "u2" }, "content": "hi" });
+        assert_eq!(parse_dispatch("MESSAGE_CREATE", &dm, "42", &config), None);
+        let dm_command = json!({ "type": 2, "id": "i2", "token": "t", "channel_id": "d1", "user": { "id": "u2" }, "data": { "name": "autonomous", "options": [{ "name": "goal", "value": "x" }] } });
+        assert_eq!(parse_dispatch("INTERACTION_CREATE", &dm_command, "42", &config), None);
+    }
+
+    #[test]
+    fn test_split_message() {
+        let text = format!("{}\n{}", "a".repeat(1500), "b".repeat(1500));
+        let chunks = split_message(&text, MAX_MESSAGE_CHARS);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| c.chars().count() <= MAX_MESSAGE_CHARS));
+        assert_eq!(split_message(&"x".repeat(4500), MAX_MESSAGE_CHARS).len(), 3);
+    }
+}
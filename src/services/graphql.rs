@@ -0,0 +1,333 @@
+//! GraphQL API
+//!
+//! `POST /v1/graphql` (GraphiQL at `GET /v1/graphql`) exposes sessions, finished turns,
+//! memory entries, tool stats, publications and spend in one schema, so a dashboard fetches
+//! exactly the fields it renders. Lists take `limit`/`offset` and return a page with the
+//! total match count. Callers see the same conversations the REST API would give them.
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Json, Object, OutputType, Schema, SimpleObject};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::response::{Html, IntoResponse};
+use axum::Extension;
+use chrono::{DateTime, Utc};
+
+use crate::memory::MemoryEntry;
+use crate::orchestrator::turn_history::{TurnRecord, TURN_HISTORY};
+use crate::orchestrator::Publication;
+use crate::server::{owner, AppState, User};
+use crate::services::web_sessions::ClientSession;
+
+pub type AgencySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Largest accepted `limit`
+const MAX_PAGE: usize = 200;
+const DEFAULT_PAGE: usize = 50;
+
+pub fn schema(state: AppState) -> AgencySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .limit_depth(8)
+        .limit_complexity(500)
+        .finish()
+}
+
+pub async fn graphql(
+    Extension(schema): Extension<AgencySchema>,
+    user: User,
+    session: ClientSession,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    schema.execute(request.into_inner().data(Viewer { user, session: session.0 })).await.into()
+}
+
+pub async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/v1/graphql").finish())
+}
+
+/// Who is asking, deciding which conversations they can see
+struct Viewer {
+    user: User,
+    /// Web session of an unauthenticated browser
+    session: Option<String>,
+}
+
+impl Viewer {
+    /// The caller's name for registry conversation `id`, or `None` if it is not theirs
+    fn session_name(&self, id: &str) -> Option<String> {
+        match self.user {
+            Some(Extension(ref user)) => id.strip_prefix(&format!("{}-", user.conversation_id())).map(str::to_string),
+            // Other browsers' ids are their cookies
+            None => (!id.starts_with("web-") || self.session.as_deref() == Some(id)).then(|| id.to_string()),
+        }
+    }
+
+    fn sees(&self, turn: &TurnRecord) -> bool {
+        match self.user {
+            Some(_) => turn.user.as_deref() == owner(&self.user),
+            None => turn.conversation.as_deref().is_none_or(|c| self.session_name(c).is_some()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(concrete(name = "SessionPage", params(SessionNode)))]
+#[graphql(concrete(name = "TurnPage", params(TurnNode)))]
+#[graphql(concrete(name = "PublicationPage", params(PublicationNode)))]
+#[graphql(concrete(name = "MemoryPage", params(MemoryNode)))]
+pub struct Page<T: OutputType> {
+    /// Matches before paging
+    total: usize,
+    items: Vec<T>,
+}
+
+fn page<T: OutputType>(items: Vec<T>, limit: Option<usize>, offset: Option<usize>) -> Page<T> {
+    let total = items.len();
+    let items = items.into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(DEFAULT_PAGE).min(MAX_PAGE))
+        .collect();
+    Page { total, items }
+}
+
+#[derive(SimpleObject)]
+pub struct SessionNode {
+    id: String,
+    /// Turns of this session still in the turn history
+    turns: usize,
+    /// Episodic memory entries; null while a turn is running
+    memory_entries: Option<usize>,
+}
+
+#[derive(SimpleObject)]
+pub struct TurnNode {
+    id: String,
+    /// `null` for the main conversation
+    session: Option<String>,
+    query: String,
+    answer: String,
+    success: bool,
+    error: Option<String>,
+    finished_at: DateTime<Utc>,
+    publication: Option<PublicationNode>,
+}
+
+#[derive(SimpleObject)]
+pub struct PublicationNode {
+    turn_id: String,
+    answer: String,
+    reliability: f32,
+    model: String,
+    latency_ms: u64,
+    tool_calls: usize,
+    evidence_count: usize,
+    scale: String,
+    cost_usd: Option<f64>,
+    pending_approval: Option<String>,
+}
+
+impl PublicationNode {
+    fn new(turn_id: &str, publication: &Publication) -> Self {
+        let telemetry = &publication.telemetry;
+        Self {
+            turn_id: turn_id.to_string(),
+            answer: publication.answer.clone(),
+            reliability: publication.reliability,
+            model: telemetry.model.clone(),
+            latency_ms: telemetry.latency_ms as u64,
+            tool_calls: telemetry.tool_calls,
+            evidence_count: telemetry.evidence_count,
+            scale: format!("{:?}", telemetry.scale),
+            cost_usd: telemetry.usage.as_ref().map(|u| u.cost_usd),
+            pending_approval: publication.pending_approval.as_ref().map(|a| a.id.clone()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct MemoryNode {
+    id: String,
+    content: String,
+    context: String,
+    agent: String,
+    kind: String,
+    tags: Vec<String>,
+    timestamp: DateTime<Utc>,
+    /// Set when searching
+    similarity: Option<f32>,
+}
+
+impl From<MemoryEntry> for MemoryNode {
+    fn from(entry: MemoryEntry) -> Self {
+        Self {
+            id: entry.id,
+            content: entry.content,
+            context: entry.metadata.context,
+            agent: entry.metadata.agent,
+            kind: format!("{:?}", entry.metadata.kind),
+            tags: entry.metadata.tags,
+            timestamp: entry.timestamp,
+            similarity: entry.similarity,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ToolStatNode {
+    tool: String,
+    calls: usize,
+    failures: usize,
+    failure_rate: f64,
+    avg_latency_ms: f64,
+    p95_latency_ms: u64,
+    last_error: Option<String>,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Open conversations the caller can use
+    async fn sessions(&self, ctx: &Context<'_>, limit: Option<usize>, offset: Option<usize>) -> Page<SessionNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        let viewer = ctx.data_unchecked::<Viewer>();
+        let mut sessions = Vec::new();
+        for id in state.conversations.ids().await {
+            let Some(name) = viewer.session_name(&id) else { continue };
+            let turns = TURN_HISTORY.list(|t| t.conversation.as_deref() == Some(id.as_str())).len();
+            let memory_entries = match state.conversations.get(&id).await.ok() {
+                Some(supervisor) => match supervisor.try_lock() {
+                    Ok(supervisor) => Some(supervisor.episodic_memory.lock().await.len()),
+                    Err(_) => None,
+                },
+                None => None,
+            };
+            sessions.push(SessionNode { id: name, turns, memory_entries });
+        }
+        page(sessions, limit, offset)
+    }
+
+    /// Finished turns, newest first. `session: ""` selects the main conversation.
+    #[allow(clippy::too_many_arguments)]
+    async fn turns(
+        &self,
+        ctx: &Context<'_>,
+        session: Option<String>,
+        success: Option<bool>,
+        #[graphql(desc = "Case-insensitive match on the query or answer")] search: Option<String>,
+        since: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Page<TurnNode> {
+        let viewer = ctx.data_unchecked::<Viewer>();
+        let search = search.map(|s| s.to_lowercase());
+        let turns = TURN_HISTORY.list(|t| {
+            viewer.sees(t)
+                && session.as_ref().is_none_or(|s| turn_session(viewer, t).as_deref().unwrap_or_default() == s)
+                && success.is_none_or(|ok| t.success == ok)
+                && since.is_none_or(|since| t.finished_at >= since)
+                && search.as_ref().is_none_or(|s| t.query.to_lowercase().contains(s) || t.answer.to_lowercase().contains(s))
+        });
+        let nodes = turns.iter().map(|t| TurnNode {
+            id: t.turn_id.clone(),
+            session: turn_session(viewer, t),
+            query: t.query.clone(),
+            answer: t.answer.clone(),
+            success: t.success,
+            error: t.error.clone(),
+            finished_at: t.finished_at,
+            publication: t.publication.as_ref().map(|p| PublicationNode::new(&t.turn_id, p)),
+        }).collect();
+        page(nodes, limit, offset)
+    }
+
+    /// Publications of finished turns, newest first
+    async fn publications(
+        &self,
+        ctx: &Context<'_>,
+        min_reliability: Option<f32>,
+        model: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Page<PublicationNode> {
+        let viewer = ctx.data_unchecked::<Viewer>();
+        let nodes = TURN_HISTORY.list(|t| viewer.sees(t)).iter()
+            .filter_map(|t| t.publication.as_ref().map(|p| PublicationNode::new(&t.turn_id, p)))
+            .filter(|p| min_reliability.is_none_or(|min| p.reliability >= min))
+            .filter(|p| model.as_ref().is_none_or(|m| &p.model == m))
+            .collect();
+        page(nodes, limit, offset)
+    }
+
+    /// Vector memory: the closest entries to `query`, or the most recent without one
+    async fn memories(
+        &self,
+        ctx: &Context<'_>,
+        query: Option<String>,
+        #[graphql(desc = "Restrict to one memory context (e.g. an uploaded file id)")] context: Option<String>,
+        kind: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> async_graphql::Result<Page<MemoryNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        let wanted = offset.unwrap_or(0) + limit.unwrap_or(DEFAULT_PAGE).min(MAX_PAGE);
+        let entries = match query {
+            Some(ref query) => state.memory.search(query, wanted, context.as_deref(), None).await?,
+            None => state.memory.get_recent(wanted).await?
+                .into_iter()
+                .filter(|e| context.as_ref().is_none_or(|c| &e.metadata.context == c))
+                .collect(),
+        };
+        let nodes: Vec<MemoryNode> = entries.into_iter()
+            .map(MemoryNode::from)
+            .filter(|m| kind.as_ref().is_none_or(|k| m.kind.eq_ignore_ascii_case(k)))
+            .collect();
+        Ok(page(nodes, limit, offset))
+    }
+
+    /// Per-tool call counts, failure rates and latency from the analytics log
+    async fn tool_stats(&self, tool: Option<String>, since: Option<DateTime<Utc>>) -> Vec<ToolStatNode> {
+        crate::tools::TOOL_ANALYTICS.stats(since).await.into_iter()
+            .filter(|s| tool.as_ref().is_none_or(|t| &s.tool == t))
+            .map(|s| ToolStatNode {
+                tool: s.tool,
+                calls: s.calls,
+                failures: s.failures,
+                failure_rate: s.failure_rate,
+                avg_latency_ms: s.avg_latency_ms,
+                p95_latency_ms: s.p95_latency_ms,
+                last_error: s.last_error,
+            })
+            .collect()
+    }
+
+    /// Token usage and spend since startup, as in `GET /v1/usage` (operators only when
+    /// authentication is on)
+    async fn usage(&self, ctx: &Context<'_>) -> async_graphql::Result<Json<crate::agent::UsageSummary>> {
+        if ctx.data_unchecked::<Viewer>().user.as_ref().is_some_and(|Extension(user)| !user.operator) {
+            return Err("Usage across all users needs the operator role".into());
+        }
+        Ok(Json(crate::agent::COST_TRACKER.summary()))
+    }
+}
+
+fn turn_session(viewer: &Viewer, turn: &TurnRecord) -> Option<String> {
+    turn.conversation.as_deref().and_then(|c| viewer.session_name(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_and_visibility() {
+        let paged = page((0..10).map(|i| MemoryNode::from(MemoryEntry::new(i.to_string(), "test", crate::memory::entry::MemorySource::System))).collect(), Some(3), Some(8));
+        assert_eq!(paged.total, 10);
+        assert_eq!(paged.items.len(), 2);
+
+        let viewer = Viewer { user: None, session: Some("web-mine".to_string()) };
+        assert_eq!(viewer.session_name("research").as_deref(), Some("research"));
+        assert_eq!(viewer.session_name("web-mine").as_deref(), Some("web-mine"));
+        assert!(viewer.session_name("web-theirs").is_none());
+    }
+}
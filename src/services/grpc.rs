@@ -0,0 +1,233 @@
+//! Agency gRPC Service
+//!
+//! `agency.v1.AgencyService` (see `proto/agency.proto`) beside the Axum server, for typed
+//! clients in other languages and integrations that want to skip JSON and SSE. It runs in the
+//! same conversations as the HTTP API, and with OIDC configured takes the same bearer tokens in
+//! the `authorization` metadata.
+
+use axum::Extension;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+
+use crate::agent::{AgentError, AgentResult};
+use crate::orchestrator::{SupervisorResult, TurnEvent};
+use crate::safety::ApprovalRequest;
+use crate::server::{self, AppState, User};
+use crate::services::auth::OidcValidator;
+
+pub mod proto {
+    tonic::include_proto!("agency.v1");
+}
+
+use proto::agency_service_server::AgencyService;
+pub use proto::agency_service_server::AgencyServiceServer;
+use proto::turn_event::Event;
+
+/// Largest `top_k` accepted by `MemorySearch`
+const MAX_TOP_K: u32 = 100;
+
+pub struct AgencyGrpc {
+    state: AppState,
+    validator: Option<Arc<OidcValidator>>,
+}
+
+impl AgencyGrpc {
+    pub fn new(state: AppState, validator: Option<Arc<OidcValidator>>) -> Self {
+        Self { state, validator }
+    }
+
+    /// The caller, when OIDC is enabled; requests without a valid token are refused
+    async fn authenticate<T>(&self, request: &Request<T>) -> Result<User, Status> {
+        let Some(ref validator) = self.validator else { return Ok(None) };
+        let token = request.metadata().get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))?;
+        let user = validator.validate(token).await.map_err(|e| Status::unauthenticated(e.to_string()))?;
+        Ok(Some(Extension(user)))
+    }
+}
+
+fn session(id: &str) -> Option<&str> {
+    (!id.is_empty()).then_some(id)
+}
+
+fn status(e: AgentError) -> Status {
+    match e {
+        AgentError::Validation(message) => Status::invalid_argument(message),
+        e => Status::internal(e.to_string()),
+    }
+}
+
+fn pending(approval: &ApprovalRequest) -> proto::PendingApproval {
+    proto::PendingApproval {
+        id: approval.id.clone(),
+        tool_name: approval.tool_name.clone(),
+        parameters_json: approval.parameters.to_string(),
+        rationale: approval.rationale.clone(),
+    }
+}
+
+fn query_response(result: SupervisorResult) -> proto::QueryResponse {
+    proto::QueryResponse {
+        pending_approval: result.pending_approval.as_ref().map(pending),
+        answer: result.answer,
+        success: result.success,
+    }
+}
+
+impl From<TurnEvent> for proto::TurnEvent {
+    fn from(event: TurnEvent) -> Self {
+        let event = match event {
+            TurnEvent::RoutingDecided { agents, model, reasoning_required, reason } => Event::RoutingDecided(proto::RoutingDecided {
+                agents: agents.iter().map(ToString::to_string).collect(),
+                model,
+                reasoning_required,
+                reason,
+            }),
+            TurnEvent::StepStarted { agent, model, description } => Event::StepStarted(proto::StepStarted { agent: agent.to_string(), model, description }),
+            TurnEvent::ToolObservation { tool, success, observation } => Event::ToolObservation(proto::ToolObservation { tool, success, observation }),
+            TurnEvent::TokenChunk { text } => Event::TokenChunk(proto::TokenChunk { text }),
+            TurnEvent::Status { message } => Event::Status(proto::Status { message }),
+            TurnEvent::FinalAnswer { answer, success, pending_approval, publication } => Event::FinalAnswer(proto::FinalAnswer {
+                answer,
+                success,
+                pending_approval: pending_approval.as_ref().map(pending),
+                reliability: publication.map(|p| p.reliability),
+            }),
+            TurnEvent::Error { message } => Event::Error(proto::Error { message }),
+        };
+        proto::TurnEvent { event: Some(event) }
+    }
+}
+
+type TurnEventStream = Pin<Box<dyn Stream<Item = Result<proto::TurnEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl AgencyService for AgencyGrpc {
+    async fn query(&self, request: Request<proto::QueryRequest>) -> Result<Response<proto::QueryResponse>, Status> {
+        let user = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let supervisor = server::conversation_for(&self.state, &user, session(&req.session_id)).await.map_err(status)?;
+        let result = supervisor.lock().await.handle(&req.query).await.map_err(status)?;
+        Ok(Response::new(query_response(result)))
+    }
+
+    type StreamTurnEventsStream = TurnEventStream;
+
+    async fn stream_turn_events(&self, request: Request<proto::QueryRequest>) -> Result<Response<TurnEventStream>, Status> {
+        let user = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        if req.query.is_empty() {
+            // Follow the conversation, like `GET /v1/turns/events`
+            let mut feed = match server::conversation_key(&user, session(&req.session_id)).map_err(status)? {
+                Some(key) => self.state.conversations.subscribe_turns(&key, server::owner(&user)).await.map_err(status)?,
+                None => self.state.turn_feed.subscribe(),
+            };
+            tokio::spawn(async move {
+                loop {
+                    let event = match feed.recv().await {
+                        Ok(event) => event,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if tx.send(Ok(event.into())).is_err() {
+                        break;
+                    }
+                }
+            });
+        } else {
+            let supervisor = server::conversation_for(&self.state, &user, session(&req.session_id)).await.map_err(status)?;
+            tokio::spawn(async move {
+                use futures_util::StreamExt;
+                let mut supervisor = supervisor.lock().await;
+                let events = supervisor.handle_stream(&req.query);
+                tokio::pin!(events);
+                while let Some(event) = events.next().await {
+                    if tx.send(Ok(event.into())).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(Response::new(Box::pin(tokio_stream::wrappers::UnboundedReceiverStream::new(rx))))
+    }
+
+    async fn steer(&self, request: Request<proto::SteerRequest>) -> Result<Response<proto::SteerResponse>, Status> {
+        let user = self.authenticate(&request).await?;
+        let req = request.into_inner();
+        if req.message.is_empty() {
+            return Err(Status::invalid_argument("Empty steering message"));
+        }
+        let delivered = match server::conversation_key(&user, session(&req.session_id)).map_err(status)? {
+            Some(key) => self.state.conversations.steer(&key, &req.message).await.map_err(status)?,
+            None => crate::orchestrator::supervisor::steer_agents(&self.state.steering, &req.message).await,
+        };
+        Ok(Response::new(proto::SteerResponse { delivered: delivered as u32 }))
+    }
+
+    async fn approve(&self, request: Request<proto::ApproveRequest>) -> Result<Response<proto::QueryResponse>, Status> {
+        let user = self.authenticate(&request).await?;
+        if user.as_ref().is_some_and(|Extension(identity)| !identity.operator) {
+            return Err(Status::permission_denied("Answering approvals needs the operator role"));
+        }
+        let req = request.into_inner();
+        let supervisor = server::approval_owner(&self.state, &user, &req.approval_id).await.map_err(status)?;
+        let mut supervisor = supervisor.lock().await;
+        let result: AgentResult<proto::QueryResponse> = if req.reject {
+            supervisor.reject(&req.approval_id).await
+                .map(|()| proto::QueryResponse { answer: "Tool call rejected.".to_string(), success: false, pending_approval: None })
+        } else {
+            supervisor.approve(&req.approval_id).await.map(query_response)
+        };
+        result.map(Response::new).map_err(|e| match e {
+            AgentError::Validation(message) => Status::not_found(message),
+            e => status(e),
+        })
+    }
+
+    async fn memory_search(&self, request: Request<proto::MemorySearchRequest>) -> Result<Response<proto::MemorySearchResponse>, Status> {
+        self.authenticate(&request).await?;
+        let req = request.into_inner();
+        let top_k = match req.top_k {
+            0 => 5,
+            k => k.min(MAX_TOP_K),
+        };
+        let entries = self.state.memory.search(&req.query, top_k as usize, session(&req.context), None).await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let hits = entries.into_iter().map(|entry| proto::MemoryHit {
+            id: entry.id,
+            content: entry.content,
+            similarity: entry.similarity.unwrap_or_default(),
+            context: entry.metadata.context,
+            agent: entry.metadata.agent,
+            timestamp: entry.timestamp.to_rfc3339(),
+        }).collect();
+        Ok(Response::new(proto::MemorySearchResponse { hits }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_event_conversion() {
+        let event: proto::TurnEvent = TurnEvent::ToolObservation { tool: "git".to_string(), success: true, observation: "clean".to_string() }.into();
+        assert!(matches!(event.event, Some(Event::ToolObservation(ref o)) if o.tool == "git" && o.success));
+
+        let event: proto::TurnEvent = TurnEvent::FinalAnswer { answer: "42".to_string(), success: true, pending_approval: None, publication: None }.into();
+        match event.event {
+            Some(Event::FinalAnswer(answer)) => {
+                assert_eq!(answer.answer, "42");
+                assert!(answer.pending_approval.is_none() && answer.reliability.is_none());
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
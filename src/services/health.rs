@@ -0,0 +1,136 @@
+//! Health Probes
+//!
+//! `GET /healthz` answers while the process is serving requests. `GET /readyz` answers `200`
+//! only when the provider responds, memory is loaded and the tool registry has tools, and `503`
+//! with the failing checks otherwise, so an orchestrator keeps traffic away until the agency can
+//! serve it. Both are public. The provider probe is a one-token generation, so its result is
+//! reused for `AGENCY_READY_PROBE_SECS` (default 30).
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::agent::{AgentType, LLMProvider};
+use crate::server::AppState;
+
+/// Longest wait for the provider before it counts as unreachable
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+lazy_static! {
+    /// Last provider probe: when it ran and its outcome
+    static ref PROVIDER_PROBE: Mutex<Option<(Instant, Result<(), String>)>> = Mutex::new(None);
+}
+
+fn probe_ttl() -> Duration {
+    Duration::from_secs(std::env::var("AGENCY_READY_PROBE_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(30))
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn from_result(result: Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Self { ok: true, detail },
+            Err(detail) => Self { ok: false, detail },
+        }
+    }
+}
+
+pub async fn healthz(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok", "since": state.start_local }))
+}
+
+pub async fn readyz(State(state): State<AppState>) -> Response {
+    let model = state.profile.load().await.ok()
+        .and_then(|p| p.default_models.get(&AgentType::GeneralChat).cloned())
+        .unwrap_or_else(|| AgentType::GeneralChat.default_model().to_string());
+    let provider = Check::from_result(probe_provider(state.provider.as_ref(), &model).await.map(|()| format!("{} responded", model)));
+    let memory = Check::from_result(match state.memory.count().await {
+        Ok(count) => Ok(format!("{} entries", count)),
+        Err(e) => Err(e.to_string()),
+    });
+    let tools = match state.tools.tool_names().await.len() {
+        0 => Check { ok: false, detail: "no tools registered".to_string() },
+        count => Check { ok: true, detail: format!("{} tools", count) },
+    };
+
+    let (status, body) = readiness(provider, memory, tools);
+    (status, Json(body)).into_response()
+}
+
+/// `200` when every check passed, else `503`, with each check's outcome
+fn readiness(provider: Check, memory: Check, tools: Check) -> (StatusCode, serde_json::Value) {
+    let ready = provider.ok && memory.ok && tools.ok;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, serde_json::json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": { "provider": provider, "memory": memory, "tools": tools },
+    }))
+}
+
+/// Health check of `model`, reusing a recent result
+async fn probe_provider(provider: &dyn LLMProvider, model: &str) -> Result<(), String> {
+    // Held across the probe so concurrent callers wait for one result
+    let mut last = PROVIDER_PROBE.lock().await;
+    if let Some((at, ref result)) = *last {
+        if at.elapsed() < probe_ttl() {
+            return result.clone();
+        }
+    }
+    let result = match tokio::time::timeout(PROVIDER_TIMEOUT, provider.health_check(model)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no response within {}s", PROVIDER_TIMEOUT.as_secs())),
+    };
+    *last = Some((Instant::now(), result.clone()));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct UnreachableProvider;
+
+    #[async_trait]
+    impl LLMProvider for UnreachableProvider {
+        async fn generate(&self, _model: &str, _prompt: String, _system: Option<String>) -> anyhow::Result<String> {
+            anyhow::bail!("connection refused")
+        }
+
+        async fn generate_stream(&self, _model: &str, _prompt: String, _system: Option<String>) -> anyhow::Result<futures::stream::BoxStream<'static, anyhow::Result<String>>> {
+            anyhow::bail!("connection refused")
+        }
+
+        fn get_lock(&self) -> std::sync::Arc<tokio::sync::Mutex<()>> {
+            std::sync::Arc::new(tokio::sync::Mutex::new(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readyz_reports_the_failing_check() {
+        let ok = |detail: &str| Check { ok: true, detail: detail.to_string() };
+        let (status, body) = readiness(ok("llama responded"), ok("3 entries"), ok("12 tools"));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ready");
+
+        let provider = Check::from_result(probe_provider(&UnreachableProvider, "llama").await.map(|()| "llama responded".to_string()));
+        let (status, body) = readiness(provider, ok("3 entries"), Check { ok: false, detail: "no tools registered".to_string() });
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "not_ready");
+        assert_eq!(body["checks"]["provider"]["ok"], false);
+        assert!(body["checks"]["provider"]["detail"].as_str().unwrap().contains("connection refused"));
+        assert_eq!(body["checks"]["memory"]["ok"], true);
+        assert_eq!(body["checks"]["tools"]["detail"], "no tools registered");
+    }
+}
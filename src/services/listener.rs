@@ -16,54 +16,138 @@ const NEXUS_URL: &str = "http://localhost:8002/v1/chat/completions";
 const SAMPLE_RATE: usize = 16000;
 const VAD_THRESHOLD: f32 = 0.015;
 const SILENCE_DURATION_MS: u64 = 800;
+/// Whisper's context window
+const MAX_SEGMENT_SECS: usize = 30;
+/// Longest push-to-talk recording; later audio is dropped
+const MAX_RECORDING_SECS: usize = 120;
 
 pub enum WhisperModel {
     Quantized(m::quantized_model::Whisper),
 }
 
+/// Whisper tiny-en, shared by the microphone loop and other front-ends (e.g. Telegram voice notes)
+pub struct WhisperTranscriber {
+    model: Mutex<WhisperModel>,
+    tokenizer: Tokenizer,
+    config: Config,
+    mel_filters: Vec<f32>,
+    device: Device,
+}
+
+impl WhisperTranscriber {
+    /// Fetch the quantized model from the Hugging Face hub (cached after the first run)
+    pub fn load() -> Result<Self> {
+        let device = Device::Cpu;
+        let api = hf_hub::api::sync::Api::new()?;
+        let repo = api.repo(hf_hub::Repo::with_revision(
+            WHISPER_MODEL_ID.to_string(),
+            hf_hub::RepoType::Model,
+            WHISPER_REVISION.to_string(),
+        ));
+
+        let config_filename = repo.get("config-tiny-en.json")?;
+        let tokenizer_filename = repo.get("tokenizer-tiny-en.json")?;
+        let weights_filename = repo.get("model-tiny-en-q80.gguf")?;
+
+        let config: Config = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
+        let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(anyhow::Error::msg)?;
+
+        let vb = candle_transformers::quantized_var_builder::VarBuilder::from_gguf(&weights_filename, &device)?;
+        let model = WhisperModel::Quantized(m::quantized_model::Whisper::load(&vb, config.clone())?);
+
+        let mel_bytes = include_bytes!("../../crates/candle/candle-examples/examples/whisper/melfilters.bytes").as_slice();
+        let mut mel_filters = vec![0f32; mel_bytes.len() / 4];
+        <byteorder::LittleEndian as byteorder::ByteOrder>::read_f32_into(mel_bytes, &mut mel_filters);
+
+        Ok(Self { model: Mutex::new(model), tokenizer, config, mel_filters, device })
+    }
+
+    /// Transcribe mono PCM at any sample rate; long recordings are split into 30s segments
+    pub async fn transcribe(self: &Arc<Self>, pcm: Vec<f32>, sample_rate: usize) -> Result<String> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let pcm_16k = if sample_rate != SAMPLE_RATE { resample(&pcm, sample_rate, SAMPLE_RATE)? } else { pcm };
+            let mut model = this.model.blocking_lock();
+            let mut segments = Vec::new();
+            for segment in pcm_16k.chunks(SAMPLE_RATE * MAX_SEGMENT_SECS) {
+                segments.push(transcribe_sync(&mut model, &this.tokenizer, &this.config, &this.mel_filters, segment, &this.device)?);
+            }
+            Ok(segments.iter().map(|s| s.trim()).filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" "))
+        }).await?
+    }
+}
+
+/// Push-to-talk capture from the default microphone, between `start` and `stop`. The audio
+/// stream is not `Send` on macOS, so it lives on its own thread.
+pub struct Recording {
+    stop: std::sync::mpsc::Sender<()>,
+    thread: std::thread::JoinHandle<(Vec<f32>, usize)>,
+}
+
+impl Recording {
+    pub fn start() -> Result<Self> {
+        let (stop, stop_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<()>>();
+        let thread = std::thread::spawn(move || {
+            let pcm = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let stream = (|| -> Result<(cpal::Stream, usize)> {
+                let audio_device = cpal::default_host().default_input_device().context("No audio input device found")?;
+                let audio_config = audio_device.default_input_config()?;
+                let channels = audio_config.channels() as usize;
+                let in_sample_rate = audio_config.sample_rate().0 as usize;
+                let max_samples = in_sample_rate * MAX_RECORDING_SECS;
+                let buffer = pcm.clone();
+                let stream = audio_device.build_input_stream(
+                    &audio_config.config(),
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        let mut buffer = buffer.lock().unwrap();
+                        if buffer.len() < max_samples {
+                            buffer.extend(data.iter().step_by(channels).copied());
+                        }
+                    },
+                    |err| error!("Audio stream error: {}", err),
+                    None,
+                )?;
+                stream.play()?;
+                Ok((stream, in_sample_rate))
+            })();
+            let (stream, in_sample_rate) = match stream {
+                Ok(started) => started,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return (Vec::new(), SAMPLE_RATE);
+                }
+            };
+            let _ = ready_tx.send(Ok(()));
+            // Until `stop`, or the recording is dropped
+            let _ = stop_rx.recv();
+            drop(stream);
+            let pcm = std::mem::take(&mut *pcm.lock().unwrap());
+            (pcm, in_sample_rate)
+        });
+        ready_rx.recv().context("Recording thread exited")??;
+        Ok(Self { stop, thread })
+    }
+
+    /// End the capture; returns mono PCM and its sample rate
+    pub fn stop(self) -> Result<(Vec<f32>, usize)> {
+        let _ = self.stop.send(());
+        self.thread.join().map_err(|_| anyhow::anyhow!("Recording thread panicked"))
+    }
+}
+
 pub struct ListenerState {
-    model: Arc<Mutex<WhisperModel>>,
-    tokenizer: Arc<Tokenizer>,
-    config: Arc<Config>,
-    mel_filters: Arc<Vec<f32>>,
+    transcriber: Arc<WhisperTranscriber>,
     client: Client,
-    device: Device,
 }
 
 pub async fn run_listener_server() -> Result<()> {
     info!("👂 Starting Integrated Listener Server...");
 
-    let device = Device::Cpu; 
-
     // 1. Load Whisper Model
-    let api = hf_hub::api::sync::Api::new()?;
-    let repo = api.repo(hf_hub::Repo::with_revision(
-        WHISPER_MODEL_ID.to_string(),
-        hf_hub::RepoType::Model,
-        WHISPER_REVISION.to_string(),
-    ));
-
-    let config_filename = repo.get("config-tiny-en.json")?;
-    let tokenizer_filename = repo.get("tokenizer-tiny-en.json")?;
-    let weights_filename = repo.get("model-tiny-en-q80.gguf")?;
-
-    let config: Config = serde_json::from_str(&std::fs::read_to_string(config_filename)?)?;
-    let tokenizer = Tokenizer::from_file(tokenizer_filename).map_err(anyhow::Error::msg)?;
-    
-    let vb = candle_transformers::quantized_var_builder::VarBuilder::from_gguf(&weights_filename, &device)?;
-    let model = WhisperModel::Quantized(m::quantized_model::Whisper::load(&vb, config.clone())?);
-    
-    let mel_bytes = include_bytes!("../../crates/candle/candle-examples/examples/whisper/melfilters.bytes").as_slice();
-    let mut mel_filters = vec![0f32; mel_bytes.len() / 4];
-    <byteorder::LittleEndian as byteorder::ByteOrder>::read_f32_into(mel_bytes, &mut mel_filters);
-
     let state = Arc::new(ListenerState {
-        model: Arc::new(Mutex::new(model)),
-        tokenizer: Arc::new(tokenizer),
-        config: Arc::new(config),
-        mel_filters: Arc::new(mel_filters),
+        transcriber: Arc::new(WhisperTranscriber::load()?),
         client: Client::new(),
-        device,
     });
 
     // 2. Setup Audio Input
@@ -126,24 +210,7 @@ pub async fn run_listener_server() -> Result<()> {
 }
 
 async fn process_speech(pcm: Vec<f32>, in_sample_rate: usize, state: Arc<ListenerState>) -> Result<()> {
-    let pcm_16k = if in_sample_rate != SAMPLE_RATE {
-        tokio::task::spawn_blocking(move || resample(&pcm, in_sample_rate, SAMPLE_RATE)).await??
-    } else {
-        pcm
-    };
-
-    let value = state.clone();
-    let text = tokio::task::spawn_blocking(move || {
-        let mut model_lock = value.model.blocking_lock();
-        transcribe_sync(
-            &mut model_lock,
-            &value.tokenizer,
-            &value.config,
-            &value.mel_filters,
-            &pcm_16k,
-            &value.device
-        )
-    }).await??;
+    let text = state.transcriber.transcribe(pcm, in_sample_rate).await?;
 
     let text = text.trim();
     if text.is_empty() || text.len() < 2 {
@@ -0,0 +1,182 @@
+//! MCP Server Mode
+//!
+//! Publishes the agency's `ToolRegistry` over the Model Context Protocol (stdio transport)
+//! so external MCP clients can call its tools. Only read-only tools are exposed: nothing that
+//! runs code, writes files, spends, reaches the network (HTTP, email, notifications) or needs
+//! human approval, since MCP clients have no way to approve a call.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::{debug, info, warn};
+
+use crate::tools::{Tool, ToolCall, ToolCaller, ToolCapability, ToolRegistry};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Answers MCP requests from a single client using the registry's tools
+pub struct McpToolServer {
+    registry: Arc<ToolRegistry>,
+    /// Optional allowlist (`AGENCY_MCP_TOOLS`); safe tools are still required
+    allowlist: Option<Vec<String>>,
+}
+
+impl McpToolServer {
+    pub fn new(registry: Arc<ToolRegistry>) -> Self {
+        Self { registry, allowlist: None }
+    }
+
+    pub fn with_allowlist(mut self, tools: Vec<String>) -> Self {
+        self.allowlist = Some(tools);
+        self
+    }
+
+    pub fn from_env(registry: Arc<ToolRegistry>) -> Self {
+        let server = Self::new(registry);
+        match std::env::var("AGENCY_MCP_TOOLS") {
+            Ok(list) => server.with_allowlist(list.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()),
+            Err(_) => server,
+        }
+    }
+
+    fn is_exposed(&self, tool: &dyn Tool) -> bool {
+        let safe = !tool.requires_confirmation()
+            && tool.capabilities().iter().all(|c| *c == ToolCapability::ReadOnly);
+        let allowed = self.allowlist.as_ref().is_none_or(|list| list.contains(&tool.name()));
+        safe && allowed
+    }
+
+    async fn exposed_tools(&self) -> Vec<Arc<dyn Tool>> {
+        let mut tools = Vec::new();
+        let mut names = self.registry.tool_names().await;
+        names.sort();
+        for name in names {
+            if let Some(tool) = self.registry.get_tool(&name).await {
+                if self.is_exposed(tool.as_ref()) {
+                    tools.push(tool);
+                }
+            }
+        }
+        tools
+    }
+
+    /// Handle one JSON-RPC message; notifications produce no reply
+    pub async fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?.clone();
+        let method = message["method"].as_str().unwrap_or("");
+        debug!("MCP server request: {}", method);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": "rust_agency", "version": env!("CARGO_PKG_VERSION") }
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => {
+                let tools: Vec<Value> = self.exposed_tools().await.iter().map(|t| json!({
+                    "name": t.name(),
+                    "description": t.description(),
+                    "inputSchema": t.parameters(),
+                })).collect();
+                Ok(json!({ "tools": tools }))
+            }
+            "tools/call" => self.call_tool(&message["params"]).await,
+            _ => Err((-32601, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+        })
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params["name"].as_str().ok_or((-32602, "Missing tool name".to_string()))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let exposed = match self.registry.get_tool(name).await {
+            Some(tool) => self.is_exposed(tool.as_ref()),
+            None => false,
+        };
+        if !exposed {
+            return Err((-32602, format!("Unknown tool: {}", name)));
+        }
+        // There is nobody to approve consequential actions (e.g. sending email) on this path
+        if self.registry.get_tool(name).await.is_some_and(|t| t.requires_confirmation_for(&arguments)) {
+            return Err((-32602, format!("'{}' with these arguments requires human approval and is not available over MCP", name)));
+        }
+
+        let call = ToolCall { name: name.to_string(), parameters: arguments };
+        // Calls are attributed to the client so the tool policy and analytics can tell them apart
+        let output = match self.registry.execute_as(&call, &ToolCaller::new("mcp_client", None)).await {
+            Ok(output) => output,
+            Err(e) => crate::tools::ToolOutput::failure(e.to_string()),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": output.summary }],
+            "structuredContent": output.data,
+            "isError": !output.success,
+        }))
+    }
+
+    /// Serve on stdin/stdout until the client disconnects. Nothing else may write to stdout.
+    pub async fn run_stdio(&self) -> Result<()> {
+        info!("Serving {} tools over MCP (stdio)", self.exposed_tools().await.len());
+        let mut stdin = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+
+        while let Some(line) = stdin.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle(&message).await,
+                Err(e) => {
+                    warn!("Invalid MCP message: {}", e);
+                    Some(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": "Parse error" } }))
+                }
+            };
+            if let Some(reply) = reply {
+                stdout.write_all((serde_json::to_string(&reply)? + "\n").as_bytes()).await?;
+                stdout.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{CodebaseTool, FileSystemTool, HttpTool};
+
+    #[tokio::test]
+    async fn test_dangerous_tools_are_hidden() {
+        let registry = Arc::new(ToolRegistry::default());
+        registry.register_instance(FileSystemTool::new(".")).await;
+        registry.register_instance(HttpTool::default()).await;
+        registry.register_instance(CodebaseTool::default()).await;
+        let server = McpToolServer::new(registry);
+
+        let list = server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })).await.unwrap();
+        let names: Vec<&str> = list["result"]["tools"].as_array().unwrap().iter().filter_map(|t| t["name"].as_str()).collect();
+        assert_eq!(names, vec!["codebase_explorer"]);
+
+        // Mutating HTTP is refused rather than sent
+        let post = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 3, "method": "tools/call",
+            "params": { "name": "http_request", "arguments": { "method": "POST", "url": "https://example.com/", "body": "x" } }
+        })).await.unwrap();
+        assert_eq!(post["error"]["code"], -32602);
+        assert!(post.get("result").is_none());
+
+        let call = server.handle(&json!({
+            "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": { "name": "file_system", "arguments": { "action": "write", "path": "x", "content": "y" } }
+        })).await.unwrap();
+        assert_eq!(call["error"]["code"], -32602);
+
+        assert!(server.handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await.is_none());
+    }
+}
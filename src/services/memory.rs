@@ -29,6 +29,11 @@ struct SearchRequest {
     kind: Option<crate::orchestrator::Kind>,
 }
 
+#[derive(Deserialize)]
+struct EmbedRequest {
+    texts: Vec<String>,
+}
+
 #[derive(Serialize)]
 struct StoreResponse {
     id: String,
@@ -72,6 +77,7 @@ pub async fn run_memory_server() -> Result<()> {
         .route("/hibernate", post(hibernate_handler))
         .route("/wake", post(wake_handler))
         .route("/count", get(count_handler))
+        .route("/embed", post(embed_handler))
         .with_state(state);
 
     let port = env::var("AGENCY_MEMORY_PORT").unwrap_or_else(|_| "3001".to_string());
@@ -105,6 +111,14 @@ async fn search_handler(
     Ok(Json(SearchResponse { entries }))
 }
 
+async fn embed_handler(
+    State(state): State<Arc<MemoryServerState>>,
+    Json(payload): Json<EmbedRequest>,
+) -> Result<Json<serde_json::Value>, ServerError> {
+    let embeddings = state.memory.embed_texts(&payload.texts).await?;
+    Ok(Json(serde_json::json!({ "embeddings": embeddings })))
+}
+
 async fn persist_handler(State(state): State<Arc<MemoryServerState>>) -> Result<Json<serde_json::Value>, ServerError> {
     state.memory.persist().await?;
     Ok(Json(serde_json::json!({ "status": "ok" })))
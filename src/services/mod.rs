@@ -2,3 +2,20 @@ pub mod memory;
 pub mod speaker;
 pub mod listener;
 pub mod responses;
+pub mod assistants;
+pub mod slack;
+pub mod discord;
+pub mod telegram;
+pub mod mcp_server;
+pub mod auth;
+pub mod tls;
+pub mod chat_tools;
+pub mod web_sessions;
+pub mod grpc;
+pub mod graphql;
+pub mod ws_protocol;
+pub mod server_config;
+pub mod dashboard;
+pub mod rate_limit;
+pub mod health;
+pub mod transcript;
@@ -0,0 +1,168 @@
+//! Client Rate Limiting
+//!
+//! Per-client request quotas on the chat, A2A and memory endpoints, so a publicly exposed agency
+//! cannot be flooded with turns. A client is its authenticated user, else its IP address; once
+//! its quota is spent it gets `429 Too Many Requests` with `Retry-After`. Tool calls inside a turn are limited separately by
+//! `safety::RateLimiter`.
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::services::auth::UserIdentity;
+
+/// Clients remembered per group before idle ones are forgotten
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Requests per minute per client on the chat endpoints; 0 disables
+    pub chat_per_minute: u32,
+    /// Requests per minute per client on the A2A endpoints; 0 disables
+    pub a2a_per_minute: u32,
+    /// Requests per minute per client on the memory, embedding and file endpoints; 0 disables
+    pub memory_per_minute: u32,
+    /// Take the client IP from `X-Forwarded-For`; only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self { chat_per_minute: 60, a2a_per_minute: 120, memory_per_minute: 120, trust_forwarded_for: false }
+    }
+}
+
+/// Endpoints sharing a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    Chat,
+    A2a,
+    Memory,
+}
+
+impl RouteGroup {
+    /// The group limiting `path`; `None` for unlimited routes
+    pub fn for_path(path: &str) -> Option<Self> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["v1", "chat", "completions"] | ["v1", "responses"] | ["v1", "turns"] => Some(RouteGroup::Chat),
+            ["v1", "threads", "runs"] | ["v1", "threads", _, "runs"] => Some(RouteGroup::Chat),
+            ["v1", "sessions", _, "query"] => Some(RouteGroup::Chat),
+            ["v1", "a2a", ..] => Some(RouteGroup::A2a),
+            ["v1", "memory", ..] | ["v1", "embeddings"] | ["v1", "files", ..] => Some(RouteGroup::Memory),
+            _ => None,
+        }
+    }
+}
+
+pub struct ClientRateLimiter {
+    limiters: HashMap<RouteGroup, DefaultKeyedRateLimiter<String>>,
+    trust_forwarded_for: bool,
+}
+
+impl ClientRateLimiter {
+    /// `None` when every quota is disabled
+    pub fn new(config: &RateLimitConfig) -> Option<Self> {
+        let limiters: HashMap<_, _> = [
+            (RouteGroup::Chat, config.chat_per_minute),
+            (RouteGroup::A2a, config.a2a_per_minute),
+            (RouteGroup::Memory, config.memory_per_minute),
+        ]
+        .into_iter()
+        .filter_map(|(group, per_minute)| Some((group, RateLimiter::keyed(Quota::per_minute(NonZeroU32::new(per_minute)?)))))
+        .collect();
+        (!limiters.is_empty()).then_some(Self { limiters, trust_forwarded_for: config.trust_forwarded_for })
+    }
+
+    /// Spends one request of `client`'s quota for `group`, or says how long until one is free
+    pub fn check(&self, group: RouteGroup, client: &str) -> Result<(), Duration> {
+        let Some(limiter) = self.limiters.get(&group) else { return Ok(()) };
+        if limiter.len() > MAX_TRACKED_CLIENTS {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+        limiter.check_key(&client.to_string()).map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+
+    /// Who is calling: the user auth verified, else the IP address. Unverified credentials
+    /// are ignored, since a caller could rotate them freely to dodge its quota.
+    pub fn client_key(&self, req: &Request) -> String {
+        if let Some(identity) = req.extensions().get::<UserIdentity>() {
+            return format!("user:{}", identity.user);
+        }
+        let forwarded = self.trust_forwarded_for
+            .then(|| req.headers().get("x-forwarded-for")?.to_str().ok()?.split(',').next().map(|ip| ip.trim().to_string()))
+            .flatten()
+            .filter(|ip| !ip.is_empty());
+        let ip = forwarded.or_else(|| req.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0.ip().to_string()));
+        format!("ip:{}", ip.unwrap_or_else(|| "unknown".to_string()))
+    }
+}
+
+pub async fn limit(State(limiter): State<Arc<ClientRateLimiter>>, req: Request, next: Next) -> Response {
+    let Some(group) = RouteGroup::for_path(req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let client = limiter.client_key(&req);
+    match limiter.check(group, &client) {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs() + 1;
+            tracing::warn!("Rate limited {} on {}", client, req.uri().path());
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(serde_json::json!({ "error": format!("Rate limit exceeded; retry in {}s", retry_after) })),
+            ).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    #[test]
+    fn test_route_groups() {
+        assert_eq!(RouteGroup::for_path("/v1/chat/completions"), Some(RouteGroup::Chat));
+        assert_eq!(RouteGroup::for_path("/v1/sessions/research/query"), Some(RouteGroup::Chat));
+        assert_eq!(RouteGroup::for_path("/v1/threads/t1/runs"), Some(RouteGroup::Chat));
+        assert_eq!(RouteGroup::for_path("/v1/a2a/tasks/42"), Some(RouteGroup::A2a));
+        assert_eq!(RouteGroup::for_path("/v1/memory/clear"), Some(RouteGroup::Memory));
+        assert_eq!(RouteGroup::for_path("/v1/turns/events"), None);
+        assert_eq!(RouteGroup::for_path("/v1/tools"), None);
+    }
+
+    #[test]
+    fn test_quota_is_per_client() {
+        let config = RateLimitConfig { chat_per_minute: 2, a2a_per_minute: 0, ..Default::default() };
+        let limiter = ClientRateLimiter::new(&config).unwrap();
+        assert!(limiter.check(RouteGroup::Chat, "ip:10.0.0.1").is_ok());
+        assert!(limiter.check(RouteGroup::Chat, "ip:10.0.0.1").is_ok());
+        assert!(limiter.check(RouteGroup::Chat, "ip:10.0.0.1").is_err());
+        assert!(limiter.check(RouteGroup::Chat, "ip:10.0.0.2").is_ok());
+        // A disabled group is never limited
+        for _ in 0..5 {
+            assert!(limiter.check(RouteGroup::A2a, "ip:10.0.0.1").is_ok());
+        }
+        assert!(ClientRateLimiter::new(&RateLimitConfig { chat_per_minute: 0, a2a_per_minute: 0, memory_per_minute: 0, trust_forwarded_for: false }).is_none());
+
+        // Made-up credentials do not buy a fresh quota
+        let req = Request::builder().uri("/v1/chat/completions").header("x-api-key", "secret").header("authorization", "Bearer other").body(Body::empty()).unwrap();
+        assert_eq!(limiter.client_key(&req), "ip:unknown");
+        let req = Request::builder().uri("/v1/chat/completions").header("x-forwarded-for", "203.0.113.7, 10.0.0.1").body(Body::empty()).unwrap();
+        assert_eq!(limiter.client_key(&req), "ip:unknown");
+    }
+}
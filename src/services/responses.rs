@@ -10,6 +10,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use crate::server::{AppState, ServerError};
+use crate::services::ws_protocol::ServerMessage;
 
 #[derive(Deserialize)]
 pub struct CreateResponseRequest {
@@ -66,7 +67,7 @@ pub async fn responses_handler(
     let mut supervisor = state.supervisor.lock().await;
     
     // Notify dashboard via WebSocket
-    let _ = state.tx.send(format!("🚀 Request (API): {}", query));
+    let _ = state.tx.send(ServerMessage::TurnStarted { message: format!("🚀 Request (API): {}", query) });
 
     // Execute Agentic Loop
     let result = supervisor.handle(&query).await
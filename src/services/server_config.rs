@@ -0,0 +1,377 @@
+//! Server Configuration
+//!
+//! Listen addresses, TLS, authentication, CORS origins, body size limits, client rate limits and
+//! dashboard channel sizes for `run_server`. Read from the `[server]` table of `agency.toml` (or the file named by
+//! `AGENCY_CONFIG`); environment variables override individual fields, and anything unset keeps
+//! its default. Without authentication the server only listens on loopback addresses unless
+//! `allow_unauthenticated_network` is set.
+//!
+//! ```toml
+//! [server]
+//! listen = "127.0.0.1:8002"
+//! allowed_origins = ["https://console.example.com"]
+//! max_upload_bytes = 67108864
+//!
+//! [server.tls]
+//! acme_domains = ["agency.example.com"]
+//!
+//! [server.auth]
+//! mode = "oidc"
+//! issuer = "https://login.example.com"
+//!
+//! [server.rate_limits]
+//! chat_per_minute = 20
+//! ```
+
+use anyhow::{Context, Result};
+use axum::http::{HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::services::auth::{default_operator_role, default_roles_claim, default_user_claim, OidcConfig};
+use crate::services::rate_limit::RateLimitConfig;
+use crate::services::tls::TlsMode;
+
+pub const DEFAULT_CONFIG_FILE: &str = "agency.toml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address of the HTTP API, dashboard and A2A endpoints
+    pub listen: String,
+    /// Address of the gRPC API; `None` disables it
+    pub grpc_listen: Option<String>,
+    pub tls: TlsConfig,
+    pub auth: AuthConfig,
+    /// Origins allowed to call the API from a browser; empty disables CORS, `*` allows any
+    pub allowed_origins: Vec<String>,
+    /// Largest request body, except uploads
+    pub max_body_bytes: usize,
+    /// Largest `/v1/files` upload
+    pub max_upload_bytes: usize,
+    /// Messages buffered for dashboard clients without a web session
+    pub broadcast_capacity: usize,
+    /// Messages buffered per web session
+    pub session_channel_capacity: usize,
+    /// Per-client quotas on the chat, A2A and memory endpoints
+    pub rate_limits: RateLimitConfig,
+    /// Serve a non-loopback address with `auth` set to `none`
+    pub allow_unauthenticated_network: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:8002".to_string(),
+            grpc_listen: None,
+            tls: TlsConfig::default(),
+            auth: AuthConfig::None,
+            allowed_origins: Vec::new(),
+            max_body_bytes: 2 * 1024 * 1024,
+            max_upload_bytes: 32 * 1024 * 1024,
+            broadcast_capacity: 1024,
+            session_channel_capacity: 100,
+            rate_limits: RateLimitConfig::default(),
+            allow_unauthenticated_network: false,
+        }
+    }
+}
+
+/// Either a certificate and key on disk or ACME domains; neither serves plain HTTP
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub cert: Option<PathBuf>,
+    pub key: Option<PathBuf>,
+    pub acme_domains: Vec<String>,
+    pub acme_contact: Option<String>,
+    /// Where issued certificates are kept (default `acme_cache`; `off` requests new ones each start)
+    pub acme_cache: Option<PathBuf>,
+    pub acme_staging: bool,
+}
+
+impl TlsConfig {
+    pub fn mode(&self) -> Result<Option<TlsMode>> {
+        match (&self.cert, &self.key) {
+            (Some(cert), Some(key)) => return Ok(Some(TlsMode::Pem { cert: cert.clone(), key: key.clone() })),
+            (Some(_), None) | (None, Some(_)) => anyhow::bail!("server.tls.cert and server.tls.key must be set together"),
+            (None, None) => {}
+        }
+        if self.acme_domains.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(TlsMode::Acme {
+            domains: self.acme_domains.clone(),
+            contact: self.acme_contact.clone(),
+            cache: Some(self.acme_cache.clone().unwrap_or_else(|| PathBuf::from("acme_cache"))).filter(|c| c != Path::new("off")),
+            staging: self.acme_staging,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum AuthConfig {
+    /// Every route is open
+    #[default]
+    None,
+    /// Bearer tokens from an OIDC issuer, as with `AGENCY_OIDC_ISSUER`
+    Oidc {
+        issuer: String,
+        /// Required; kept optional here so a missing value gets a clear error
+        #[serde(default)]
+        audience: Option<String>,
+        #[serde(default)]
+        jwks_url: Option<String>,
+        #[serde(default = "default_user_claim")]
+        user_claim: String,
+        #[serde(default = "default_roles_claim")]
+        roles_claim: String,
+        #[serde(default = "default_operator_role")]
+        operator_role: String,
+    },
+}
+
+impl AuthConfig {
+    pub fn oidc(&self) -> Result<Option<OidcConfig>> {
+        match self {
+            AuthConfig::None => Ok(None),
+            AuthConfig::Oidc { issuer, audience, jwks_url, user_claim, roles_claim, operator_role } => Ok(Some(OidcConfig {
+                issuer: issuer.trim_end_matches('/').to_string(),
+                audience: audience.clone().filter(|a| !a.is_empty()).context("server.auth.audience is required with mode = \"oidc\"")?,
+                jwks_url: jwks_url.clone(),
+                user_claim: user_claim.clone(),
+                roles_claim: roles_claim.clone(),
+                operator_role: operator_role.clone(),
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    server: ServerConfig,
+}
+
+impl ServerConfig {
+    /// `agency.toml` (or `AGENCY_CONFIG`) if present, then environment overrides
+    pub fn load() -> Result<Self> {
+        let path = std::env::var("AGENCY_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_FILE.to_string());
+        let mut config = Self::from_file(Path::new(&path))?;
+        config.apply_env()?;
+        config.check_exposure()?;
+        Ok(config)
+    }
+
+    /// Refuse to serve every route, approvals and kill switch included, to the network without
+    /// credentials, unless `allow_unauthenticated_network` says so
+    pub fn check_exposure(&self) -> Result<()> {
+        if self.auth != AuthConfig::None {
+            return Ok(());
+        }
+        let exposed: Vec<SocketAddr> = [Some(self.listen_addr()?), self.grpc_addr()?].into_iter()
+            .flatten()
+            .filter(|addr| !addr.ip().is_loopback())
+            .collect();
+        let Some(addr) = exposed.first() else { return Ok(()) };
+        if !self.allow_unauthenticated_network {
+            anyhow::bail!(
+                "Refusing to listen on {} without authentication: configure [server.auth] (or AGENCY_OIDC_ISSUER), \
+                 listen on 127.0.0.1, or set allow_unauthenticated_network (AGENCY_ALLOW_UNAUTHENTICATED_NETWORK=1)",
+                addr
+            );
+        }
+        tracing::warn!("⚠️  Serving {} WITHOUT AUTHENTICATION: anyone who can reach it can run tools, approve calls and change the agency's configuration", addr);
+        Ok(())
+    }
+
+    /// The `[server]` table of `path`; defaults when the file does not exist
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let file: ConfigFile = toml::from_str(&content).with_context(|| format!("Invalid server configuration in {}", path.display()))?;
+        Ok(file.server)
+    }
+
+    /// `AGENCY_LISTEN_ADDR`, `AGENCY_GRPC_ADDR` (`off` disables), `AGENCY_ALLOWED_ORIGINS`
+    /// (comma-separated), `AGENCY_MAX_BODY_BYTES`, `AGENCY_MAX_UPLOAD_BYTES`,
+    /// `AGENCY_BROADCAST_CAPACITY`, `AGENCY_RATE_LIMIT_CHAT`, `AGENCY_RATE_LIMIT_A2A`,
+    /// `AGENCY_RATE_LIMIT_MEMORY` (per minute; `off` disables), `AGENCY_TRUST_FORWARDED_FOR`,
+    /// `AGENCY_ALLOW_UNAUTHENTICATED_NETWORK`, and the TLS and OIDC variables
+    pub fn apply_env(&mut self) -> Result<()> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let size = |name: &str| -> Result<Option<usize>> {
+            var(name).map(|v| v.parse().with_context(|| format!("{} must be a whole number", name))).transpose()
+        };
+        if let Some(listen) = var("AGENCY_LISTEN_ADDR") {
+            self.listen = listen;
+        }
+        if let Some(grpc) = var("AGENCY_GRPC_ADDR") {
+            self.grpc_listen = (grpc != "off").then_some(grpc);
+        }
+        if let Some(origins) = var("AGENCY_ALLOWED_ORIGINS") {
+            self.allowed_origins = origins.split(',').map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect();
+        }
+        if let Some(bytes) = size("AGENCY_MAX_BODY_BYTES")? {
+            self.max_body_bytes = bytes;
+        }
+        if let Some(bytes) = size("AGENCY_MAX_UPLOAD_BYTES")? {
+            self.max_upload_bytes = bytes;
+        }
+        if let Some(capacity) = size("AGENCY_BROADCAST_CAPACITY")? {
+            self.broadcast_capacity = capacity;
+        }
+        let quota = |name: &str| -> Result<Option<u32>> {
+            var(name).map(|v| if v == "off" { Ok(0) } else { v.parse().with_context(|| format!("{} must be a whole number or 'off'", name)) }).transpose()
+        };
+        if let Some(per_minute) = quota("AGENCY_RATE_LIMIT_CHAT")? {
+            self.rate_limits.chat_per_minute = per_minute;
+        }
+        if let Some(per_minute) = quota("AGENCY_RATE_LIMIT_A2A")? {
+            self.rate_limits.a2a_per_minute = per_minute;
+        }
+        if let Some(per_minute) = quota("AGENCY_RATE_LIMIT_MEMORY")? {
+            self.rate_limits.memory_per_minute = per_minute;
+        }
+        if let Some(trust) = var("AGENCY_TRUST_FORWARDED_FOR") {
+            self.rate_limits.trust_forwarded_for = trust == "1" || trust == "true";
+        }
+        if let Some(open) = var("AGENCY_ALLOW_UNAUTHENTICATED_NETWORK") {
+            self.allow_unauthenticated_network = open == "1" || open == "true";
+        }
+        if let Some(tls) = TlsMode::from_env()? {
+            self.tls = TlsConfig::from(tls);
+        }
+        if let Some(oidc) = OidcConfig::from_env()? {
+            self.auth = AuthConfig::Oidc {
+                issuer: oidc.issuer,
+                audience: Some(oidc.audience),
+                jwks_url: oidc.jwks_url,
+                user_claim: oidc.user_claim,
+                roles_claim: oidc.roles_claim,
+                operator_role: oidc.operator_role,
+            };
+        }
+        Ok(())
+    }
+
+    pub fn listen_addr(&self) -> Result<SocketAddr> {
+        self.listen.parse().with_context(|| format!("Invalid listen address '{}'", self.listen))
+    }
+
+    pub fn grpc_addr(&self) -> Result<Option<SocketAddr>> {
+        self.grpc_listen.as_deref()
+            .map(|addr| addr.parse().with_context(|| format!("Invalid gRPC listen address '{}'", addr)))
+            .transpose()
+    }
+
+    /// CORS for `allowed_origins`; `None` when no origin is allowed
+    pub fn cors(&self) -> Result<Option<CorsLayer>> {
+        if self.allowed_origins.is_empty() {
+            return Ok(None);
+        }
+        let origin = if self.allowed_origins.iter().any(|o| o == "*") {
+            AllowOrigin::from(Any)
+        } else {
+            let origins = self.allowed_origins.iter()
+                .map(|o| HeaderValue::from_str(o).with_context(|| format!("Invalid allowed origin '{}'", o)))
+                .collect::<Result<Vec<_>>>()?;
+            AllowOrigin::list(origins)
+        };
+        Ok(Some(CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers(Any)))
+    }
+}
+
+impl From<TlsMode> for TlsConfig {
+    fn from(mode: TlsMode) -> Self {
+        match mode {
+            TlsMode::Pem { cert, key } => TlsConfig { cert: Some(cert), key: Some(key), ..Default::default() },
+            TlsMode::Acme { domains, contact, cache, staging } => TlsConfig {
+                acme_domains: domains,
+                acme_contact: contact,
+                acme_cache: Some(cache.unwrap_or_else(|| PathBuf::from("off"))),
+                acme_staging: staging,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_config_file_with_defaults() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, r#"
+[server]
+listen = "127.0.0.1:9000"
+allowed_origins = ["https://console.example.com"]
+
+[server.tls]
+cert = "cert.pem"
+
+[server.auth]
+mode = "oidc"
+issuer = "https://login.example.com/"
+audience = "agency"
+
+[server.rate_limits]
+chat_per_minute = 0
+"#).unwrap();
+        let config = ServerConfig::from_file(file.path()).unwrap();
+        assert_eq!(config.listen_addr().unwrap().port(), 9000);
+        assert_eq!(config.max_upload_bytes, ServerConfig::default().max_upload_bytes);
+        let oidc = config.auth.oidc().unwrap().unwrap();
+        assert_eq!(oidc.issuer, "https://login.example.com");
+        assert_eq!(oidc.operator_role, "agency-operator");
+        // OIDC without an audience would accept tokens the issuer minted for any other app
+        let no_audience = AuthConfig::Oidc { audience: None, ..config.auth.clone() };
+        assert!(no_audience.oidc().is_err());
+        assert!(config.cors().unwrap().is_some());
+        assert_eq!(config.rate_limits.chat_per_minute, 0);
+        assert_eq!(config.rate_limits.a2a_per_minute, RateLimitConfig::default().a2a_per_minute);
+        // A certificate without its key is refused
+        assert!(config.tls.mode().is_err());
+
+        let missing = ServerConfig::from_file(Path::new("/nonexistent/agency.toml")).unwrap();
+        assert_eq!(missing, ServerConfig::default());
+        assert!(missing.cors().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unauthenticated_server_stays_on_loopback() {
+        let mut config = ServerConfig::default();
+        assert!(config.listen_addr().unwrap().ip().is_loopback());
+        assert!(config.check_exposure().is_ok());
+
+        config.grpc_listen = Some("0.0.0.0:50052".to_string());
+        assert!(config.check_exposure().unwrap_err().to_string().contains("0.0.0.0:50052"));
+        config.grpc_listen = None;
+        config.listen = "[::]:8002".to_string();
+        assert!(config.check_exposure().is_err());
+
+        config.allow_unauthenticated_network = true;
+        assert!(config.check_exposure().is_ok());
+        config.allow_unauthenticated_network = false;
+        config.auth = AuthConfig::Oidc {
+            issuer: "https://login.example.com".to_string(),
+            audience: Some("agency".to_string()),
+            jwks_url: None,
+            user_claim: default_user_claim(),
+            roles_claim: default_roles_claim(),
+            operator_role: default_operator_role(),
+        };
+        assert!(config.check_exposure().is_ok());
+    }
+}
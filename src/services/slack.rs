@@ -0,0 +1,426 @@
+//! Slack Integration (Socket Mode)
+//!
+//! Maps Slack messages onto Supervisor turns with one conversation per thread. Replies are
+//! threaded and updated in place while the turn runs; tool approvals are posted as
+//! Approve/Deny buttons and resume the turn once approved.
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{info, warn};
+
+use crate::memory::EpisodicMemory;
+use crate::orchestrator::{AgencyEvent, Supervisor, AGENCY_EVENT_BUS};
+use crate::safety::ApprovalRequest;
+use crate::services::assistants::ThreadSession;
+
+const SLACK_API: &str = "https://slack.com/api";
+const APPROVE_ACTION: &str = "agency_approve";
+const DENY_ACTION: &str = "agency_deny";
+/// Minimum gap between in-place progress edits (Slack rate-limits `chat.update`)
+const UPDATE_INTERVAL: Duration = Duration::from_millis(1500);
+/// Thread conversations idle this long are forgotten
+const THREAD_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Thread conversations kept at once; the least recently used go first
+const MAX_THREADS: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct SlackConfig {
+    /// App-level token (`xapp-...`) with `connections:write`
+    pub app_token: String,
+    /// Bot token (`xoxb-...`) with `chat:write`
+    pub bot_token: String,
+    /// Only these channel ids are answered (all when empty)
+    pub channels: Vec<String>,
+    /// Only these user ids may talk to the agency; empty serves nobody
+    pub users: Vec<String>,
+    /// Only these user ids may approve tool calls; empty lets nobody approve
+    pub approvers: Vec<String>,
+}
+
+impl SlackConfig {
+    /// `SLACK_APP_TOKEN` and `SLACK_BOT_TOKEN`, the comma-separated `SLACK_ALLOWED_USERS`
+    /// (required) and `SLACK_APPROVERS`, and optionally `SLACK_CHANNELS`
+    pub fn from_env() -> Option<Self> {
+        let list = |key: &str| std::env::var(key)
+            .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let config = Self {
+            app_token: std::env::var("SLACK_APP_TOKEN").ok()?,
+            bot_token: std::env::var("SLACK_BOT_TOKEN").ok()?,
+            channels: list("SLACK_CHANNELS"),
+            users: list("SLACK_ALLOWED_USERS"),
+            approvers: list("SLACK_APPROVERS"),
+        };
+        if config.users.is_empty() {
+            warn!("Slack: SLACK_ALLOWED_USERS is not set; staying off rather than serving the whole workspace");
+            return None;
+        }
+        if config.approvers.is_empty() {
+            warn!("Slack: SLACK_APPROVERS is not set; tool calls needing approval cannot be approved from Slack");
+        }
+        Some(config)
+    }
+}
+
+/// What a socket-mode envelope asks of us
+#[derive(Debug, PartialEq)]
+pub enum SlackIncoming {
+    Message { channel: String, thread_ts: String, user: String, text: String, direct: bool },
+    Action { approval_id: String, approved: bool, user: String, channel: String, message_ts: String },
+    Disconnect,
+    Ignore,
+}
+
+/// Classify the payload of one envelope (already acknowledged)
+pub fn parse_envelope(envelope: &Value) -> SlackIncoming {
+    match envelope["type"].as_str() {
+        Some("disconnect") => SlackIncoming::Disconnect,
+        Some("events_api") => {
+            let event = &envelope["payload"]["event"];
+            // Skip our own posts, edits and other bots
+            if event.get("bot_id").is_some() || event.get("subtype").is_some() {
+                return SlackIncoming::Ignore;
+            }
+            let kind = event["type"].as_str().unwrap_or("");
+            if kind != "message" && kind != "app_mention" {
+                return SlackIncoming::Ignore;
+            }
+            let ts = event["ts"].as_str().unwrap_or_default();
+            SlackIncoming::Message {
+                channel: event["channel"].as_str().unwrap_or_default().to_string(),
+                thread_ts: event["thread_ts"].as_str().unwrap_or(ts).to_string(),
+                user: event["user"].as_str().unwrap_or_default().to_string(),
+                text: strip_mentions(event["text"].as_str().unwrap_or_default()),
+                direct: kind == "app_mention" || event["channel_type"].as_str() == Some("im"),
+            }
+        }
+        Some("interactive") => {
+            let payload = &envelope["payload"];
+            let Some(action) = payload["actions"].as_array().and_then(|a| a.first()) else { return SlackIncoming::Ignore };
+            let approved = match action["action_id"].as_str() {
+                Some(APPROVE_ACTION) => true,
+                Some(DENY_ACTION) => false,
+                _ => return SlackIncoming::Ignore,
+            };
+            SlackIncoming::Action {
+                approval_id: action["value"].as_str().unwrap_or_default().to_string(),
+                approved,
+                user: payload["user"]["id"].as_str().unwrap_or_default().to_string(),
+                channel: payload["channel"]["id"].as_str().unwrap_or_default().to_string(),
+                message_ts: payload["container"]["message_ts"].as_str().unwrap_or_default().to_string(),
+            }
+        }
+        _ => SlackIncoming::Ignore,
+    }
+}
+
+/// Remove `<@U123>` mentions of the bot from message text
+fn strip_mentions(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("<@") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find('>') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Block Kit message asking a human to approve a tool call
+pub fn approval_blocks(approval: &ApprovalRequest) -> Value {
+    let params = serde_json::to_string_pretty(&approval.parameters).unwrap_or_default();
+    json!([
+        {
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("🛡️ *Approval needed* for `{}`\n{}\n```{}```", approval.tool_name, approval.rationale, params) }
+        },
+        {
+            "type": "actions",
+            "elements": [
+                { "type": "button", "style": "primary", "text": { "type": "plain_text", "text": "Approve" }, "action_id": APPROVE_ACTION, "value": approval.id },
+                { "type": "button", "style": "danger", "text": { "type": "plain_text", "text": "Deny" }, "action_id": DENY_ACTION, "value": approval.id }
+            ]
+        }
+    ])
+}
+
+/// A thread's conversation and when it was last used
+struct ThreadState {
+    memory: EpisodicMemory,
+    used: Instant,
+}
+
+/// Drop threads idle past `THREAD_TTL`, then the least recently used beyond `MAX_THREADS`
+fn evict_threads(threads: &mut HashMap<String, ThreadState>) {
+    threads.retain(|_, thread| thread.used.elapsed() < THREAD_TTL);
+    while threads.len() > MAX_THREADS {
+        let Some(oldest) = threads.iter().min_by_key(|(_, t)| t.used).map(|(k, _)| k.clone()) else { break };
+        threads.remove(&oldest);
+    }
+}
+
+struct PendingApproval {
+    approval: ApprovalRequest,
+    channel: String,
+    thread_ts: String,
+    query: String,
+}
+
+pub struct SlackService {
+    config: SlackConfig,
+    supervisor: Arc<Mutex<Supervisor>>,
+    client: reqwest::Client,
+    /// Conversation per `channel:thread_ts`
+    threads: Mutex<HashMap<String, ThreadState>>,
+    approvals: Mutex<HashMap<String, PendingApproval>>,
+}
+
+impl SlackService {
+    pub fn new(config: SlackConfig, supervisor: Arc<Mutex<Supervisor>>) -> Self {
+        Self {
+            config,
+            supervisor,
+            client: reqwest::Client::new(),
+            threads: Mutex::new(HashMap::new()),
+            approvals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn api(&self, method: &str, token: &str, body: Value) -> Result<Value> {
+        let reply: Value = self.client.post(format!("{}/{}", SLACK_API, method))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if reply["ok"].as_bool() != Some(true) {
+            anyhow::bail!("Slack {} failed: {}", method, reply["error"].as_str().unwrap_or("unknown error"));
+        }
+        Ok(reply)
+    }
+
+    async fn post(&self, channel: &str, thread_ts: &str, text: &str, blocks: Option<Value>) -> Result<String> {
+        let mut body = json!({ "channel": channel, "thread_ts": thread_ts, "text": text });
+        if let Some(blocks) = blocks {
+            body["blocks"] = blocks;
+        }
+        let reply = self.api("chat.postMessage", &self.config.bot_token, body).await?;
+        Ok(reply["ts"].as_str().unwrap_or_default().to_string())
+    }
+
+    async fn update(&self, channel: &str, ts: &str, text: &str) -> Result<()> {
+        self.api("chat.update", &self.config.bot_token, json!({ "channel": channel, "ts": ts, "text": text, "blocks": [] })).await.map(|_| ())
+    }
+
+    fn user_allowed(&self, user: &str) -> bool {
+        self.config.users.iter().any(|u| u == user)
+    }
+
+    fn approver_allowed(&self, user: &str) -> bool {
+        self.config.approvers.iter().any(|u| u == user)
+    }
+
+    /// Connect over socket mode and serve until the process exits, reconnecting as needed
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        loop {
+            if let Err(e) = self.clone().serve_connection().await {
+                warn!("Slack connection lost: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn serve_connection(self: Arc<Self>) -> Result<()> {
+        let open = self.api("apps.connections.open", &self.config.app_token, json!({})).await?;
+        let url = open["url"].as_str().context("Slack returned no socket URL")?;
+        let (socket, _) = tokio_tungstenite::connect_async(url).await.context("Failed to open Slack socket")?;
+        let (mut write, mut read) = socket.split();
+        info!("💬 Slack: connected (socket mode)");
+
+        while let Some(frame) = read.next().await {
+            let text = match frame? {
+                WsMessage::Text(text) => text.to_string(),
+                WsMessage::Ping(data) => {
+                    write.send(WsMessage::Pong(data)).await?;
+                    continue;
+                }
+                WsMessage::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<Value>(&text) else { continue };
+            // Slack redelivers anything not acknowledged within 3 seconds
+            if let Some(id) = envelope["envelope_id"].as_str() {
+                write.send(WsMessage::Text(json!({ "envelope_id": id }).to_string().into())).await?;
+            }
+
+            match parse_envelope(&envelope) {
+                SlackIncoming::Disconnect => break,
+                SlackIncoming::Ignore => {}
+                incoming => {
+                    let service = self.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = service.dispatch(incoming).await {
+                            warn!("Slack: failed to handle event: {}", e);
+                        }
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn dispatch(&self, incoming: SlackIncoming) -> Result<()> {
+        match incoming {
+            SlackIncoming::Message { channel, thread_ts, user, text, direct } => {
+                let key = format!("{}:{}", channel, thread_ts);
+                // In channels, only mentions start a conversation; replies in its thread continue it
+                let known_thread = self.threads.lock().await.contains_key(&key);
+                let channel_allowed = self.config.channels.is_empty() || self.config.channels.contains(&channel);
+                if text.is_empty() || !(direct || known_thread) || !channel_allowed || !self.user_allowed(&user) {
+                    return Ok(());
+                }
+                self.run_turn(&channel, &thread_ts, &text).await
+            }
+            SlackIncoming::Action { approval_id, approved, user, channel, message_ts } => {
+                if !self.approver_allowed(&user) {
+                    let thread = self.approvals.lock().await.get(&approval_id).map(|p| p.thread_ts.clone());
+                    if let Some(thread_ts) = thread {
+                        self.post(&channel, &thread_ts, &format!("⛔ <@{}> is not allowed to answer approvals.", user), None).await?;
+                    }
+                    return Ok(());
+                }
+                let Some(pending) = self.approvals.lock().await.remove(&approval_id) else {
+                    return self.update(&channel, &message_ts, "This approval request has expired.").await;
+                };
+                // Keep the shared approval queue in step so the dashboard does not offer it again
+                let approvals = self.supervisor.lock().await.approvals.clone();
+                let _ = approvals.resolve(&approval_id, approved).await;
+                if !approved {
+                    return self.update(&channel, &message_ts, &format!("❌ `{}` denied by <@{}>", pending.approval.tool_name, user)).await;
+                }
+                self.update(&channel, &message_ts, &format!("✅ `{}` approved by <@{}>", pending.approval.tool_name, user)).await?;
+                {
+                    let supervisor = self.supervisor.lock().await;
+                    supervisor.safety.lock().await.approve_call(&pending.approval.tool_name, &pending.approval.parameters);
+                }
+                self.run_turn(&pending.channel, &pending.thread_ts, &pending.query).await
+            }
+            SlackIncoming::Disconnect | SlackIncoming::Ignore => Ok(()),
+        }
+    }
+
+    /// One Supervisor turn in the thread's conversation, streamed into a single reply
+    async fn run_turn(&self, channel: &str, thread_ts: &str, query: &str) -> Result<()> {
+        let key = format!("{}:{}", channel, thread_ts);
+        let reply_ts = self.post(channel, thread_ts, "⏳ Thinking...", None).await?;
+        let memory = {
+            let mut threads = self.threads.lock().await;
+            evict_threads(&mut threads);
+            threads.get(&key).map(|t| t.memory.clone()).unwrap_or_default()
+        };
+
+        let mut events = AGENCY_EVENT_BUS.subscribe();
+        let mut last_update = Instant::now();
+        let result = {
+            let mut session = ThreadSession::enter(self.supervisor.clone(), memory).await;
+            let turn = session.supervisor.handle(query);
+            tokio::pin!(turn);
+            loop {
+                tokio::select! {
+                    result = &mut turn => break result,
+                    Ok(event) = events.recv() => {
+                        let status = match event {
+                            AgencyEvent::ToolCallStarted { tool } => format!("⏳ Using `{}`...", tool),
+                            AgencyEvent::ToolProgress { tool, chunk } => format!("⏳ `{}`: {}", tool, chunk.chars().take(200).collect::<String>()),
+                            _ => continue,
+                        };
+                        if last_update.elapsed() >= UPDATE_INTERVAL {
+                            last_update = Instant::now();
+                            let _ = self.update(channel, &reply_ts, &status).await;
+                        }
+                    }
+                }
+            }
+        };
+
+        match result {
+            Ok(result) => {
+                {
+                    let mut threads = self.threads.lock().await;
+                    let thread = threads.entry(key).or_insert_with(|| ThreadState { memory: EpisodicMemory::default(), used: Instant::now() });
+                    thread.used = Instant::now();
+                    thread.memory.add_user(query);
+                    thread.memory.add_assistant(&result.answer, Some("Agency".to_string()));
+                }
+                self.update(channel, &reply_ts, &result.answer).await?;
+                if let Some(approval) = result.pending_approval {
+                    let text = format!("Approval needed for {}", approval.tool_name);
+                    self.post(channel, thread_ts, &text, Some(approval_blocks(&approval))).await?;
+                    self.approvals.lock().await.insert(approval.id.clone(), PendingApproval {
+                        approval,
+                        channel: channel.to_string(),
+                        thread_ts: thread_ts.to_string(),
+                        query: query.to_string(),
+                    });
+                }
+                Ok(())
+            }
+            Err(e) => self.update(channel, &reply_ts, &format!("⚠️ {}", e)).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_envelopes() {
+        let mention = json!({
+            "type": "events_api", "envelope_id": "e1",
+            "payload": { "event": { "type": "app_mention", "channel": "C1", "user": "U1", "ts": "100.1", "text": "<@UBOT> summarize the repo" } }
+        });
+        assert_eq!(parse_envelope(&mention), SlackIncoming::Message {
+            channel: "C1".into(), thread_ts: "100.1".into(), user: "U1".into(), text: "summarize the repo".into(), direct: true,
+        });
+
+        let own_post = json!({ "type": "events_api", "payload": { "event": { "type": "message", "bot_id": "B1", "text": "hi" } } });
+        assert_eq!(parse_envelope(&own_post), SlackIncoming::Ignore);
+
+        let click = json!({
+            "type": "interactive",
+            "payload": {
+                "user": { "id": "U2" }, "channel": { "id": "C1" }, "container": { "message_ts": "101.0" },
+                "actions": [{ "action_id": DENY_ACTION, "value": "appr-1" }]
+            }
+        });
+        assert_eq!(parse_envelope(&click), SlackIncoming::Action {
+            approval_id: "appr-1".into(), approved: false, user: "U2".into(), channel: "C1".into(), message_ts: "101.0".into(),
+        });
+        assert_eq!(parse_envelope(&json!({ "type": "disconnect" })), SlackIncoming::Disconnect);
+    }
+
+    #[test]
+    fn test_evict_threads() {
+        let now = Instant::now();
+        let mut threads: HashMap<String, ThreadState> = (0..MAX_THREADS + 2)
+            .map(|i| (i.to_string(), ThreadState { memory: EpisodicMemory::default(), used: now + Duration::from_millis(i as u64) }))
+            .collect();
+        evict_threads(&mut threads);
+        assert_eq!(threads.len(), MAX_THREADS);
+        assert!(!threads.contains_key("0") && !threads.contains_key("1"));
+    }
+}
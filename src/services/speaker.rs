@@ -115,16 +115,17 @@ impl AudioEngine {
         })
     }
 
-    pub async fn synthesize(&self, text: String) -> Result<()> {
-        let (audio_tx, mut audio_rx) = mpsc::unbounded_channel::<(usize, Vec<f32>)>();
+    /// Generate every sentence of `text` on the model pool; chunks arrive tagged with their index
+    fn spawn_chunks(&self, text: &str) -> Result<Option<mpsc::UnboundedReceiver<(usize, Vec<f32>)>>> {
+        let (audio_tx, audio_rx) = mpsc::unbounded_channel::<(usize, Vec<f32>)>();
         
         let re = regex::Regex::new(r"(?s)[^.!?\n\r,;:]+[.!?\n\r,;:]*")?;
-        let sentences: Vec<String> = re.find_iter(&text) 
+        let sentences: Vec<String> = re.find_iter(text) 
             .map(|m| m.as_str().trim().to_string())
             .filter(|s| !s.is_empty() && s.len() > 1)
             .collect();
 
-        if sentences.is_empty() { return Ok(()); }
+        if sentences.is_empty() { return Ok(None); }
         info!("AudioEngine: Synthesizing {} chunks...", sentences.len());
 
         for (idx, sentence) in sentences.into_iter().enumerate() {
@@ -167,6 +168,11 @@ impl AudioEngine {
             });
         }
         drop(audio_tx);
+        Ok(Some(audio_rx))
+    }
+
+    pub async fn synthesize(&self, text: String) -> Result<()> {
+        let Some(mut audio_rx) = self.spawn_chunks(&text)? else { return Ok(()); };
 
         let sink = self.sink.clone();
         tokio::spawn(async move {
@@ -191,6 +197,30 @@ impl AudioEngine {
         Ok(())
     }
 
+    /// Synthesize `text` without playing it, as a 24kHz mono WAV file
+    pub async fn render_wav(&self, text: &str) -> Result<Vec<u8>> {
+        let mut chunks = Vec::new();
+        if let Some(mut audio_rx) = self.spawn_chunks(text)? {
+            while let Some(chunk) = audio_rx.recv().await {
+                chunks.push(chunk);
+            }
+        }
+        chunks.sort_by_key(|(idx, _)| *idx);
+
+        let spec = hound::WavSpec { channels: 1, sample_rate: 24000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut cursor, spec)?;
+            for (_, audio) in chunks.iter().filter(|(_, a)| !a.is_empty()) {
+                for sample in audio.iter().chain(std::iter::repeat_n(&0.0f32, 1200)) {
+                    writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+                }
+            }
+            writer.finalize()?;
+        }
+        Ok(cursor.into_inner())
+    }
+
     fn decode_audio_native_static(
         decoder_model: &candle_onnx::onnx::ModelProto, 
         tokens: &[i64], 
@@ -219,6 +249,20 @@ impl AudioEngine {
     }
 }
 
+async fn synthesize_handler(
+    State(engine): State<Arc<AudioEngine>>,
+    Json(payload): Json<SayRequest>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    match engine.render_wav(&payload.text).await {
+        Ok(wav) => ([(axum::http::header::CONTENT_TYPE, "audio/wav")], wav).into_response(),
+        Err(e) => {
+            error!("Synthesis failed: {}", e);
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SayRequest {
     pub text: String,
@@ -241,6 +285,7 @@ pub async fn run_speaker_server() -> Result<()> {
 
     let app = Router::new()
         .route("/say", post(say_handler))
+        .route("/synthesize", post(synthesize_handler))
         .route("/health", get(|| async { "OK" }))
         .with_state(engine);
 
@@ -0,0 +1,281 @@
+//! Telegram Bot Front-end
+//!
+//! Chat with the agency from Telegram. Text and voice notes go to the shared Supervisor (and
+//! its session); voice notes are transcribed with the Whisper listener and answered with a
+//! voice note rendered by the Speaker Server. Audio conversion uses `ffmpeg` from `PATH`.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, InputFile};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{Mutex, OnceCell};
+use tracing::{info, warn};
+
+use crate::agent::Speaker;
+use crate::orchestrator::Supervisor;
+use crate::safety::ApprovalRequest;
+use crate::services::discord::split_message;
+use crate::services::listener::WhisperTranscriber;
+
+/// Telegram rejects messages longer than this
+const MAX_MESSAGE_CHARS: usize = 4096;
+const VOICE_SAMPLE_RATE: usize = 16000;
+
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub token: String,
+    /// Only these chats are served, and only these users may approve tool calls (a private
+    /// chat's id is its user's id). Empty serves nobody.
+    pub allowed_chats: Vec<i64>,
+}
+
+impl TelegramConfig {
+    /// Enabled by `AGENCY_TELEGRAM_BOT=1`; uses `TELEGRAM_BOT_TOKEN` and the comma-separated
+    /// `TELEGRAM_CHAT_ID` allowlist, without which the bot does not start
+    pub fn from_env() -> Option<Self> {
+        if std::env::var("AGENCY_TELEGRAM_BOT").ok()? != "1" {
+            return None;
+        }
+        let allowed_chats: Vec<i64> = std::env::var("TELEGRAM_CHAT_ID")
+            .map(|v| v.split(',').filter_map(|s| s.trim().parse().ok()).collect())
+            .unwrap_or_default();
+        if allowed_chats.is_empty() {
+            warn!("Telegram: TELEGRAM_CHAT_ID is not set; the bot stays off rather than serving every chat");
+            return None;
+        }
+        Some(Self { token: std::env::var("TELEGRAM_BOT_TOKEN").ok()?, allowed_chats })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TelegramCommand {
+    Start,
+    Approve,
+    Deny,
+    Query(String),
+}
+
+impl TelegramCommand {
+    /// Commands may carry a `@botname` suffix in group chats
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let command = text.split_whitespace().next().unwrap_or("").split('@').next().unwrap_or("");
+        match command {
+            "/start" | "/help" => Self::Start,
+            "/approve" => Self::Approve,
+            "/deny" => Self::Deny,
+            _ => Self::Query(text.to_string()),
+        }
+    }
+}
+
+/// Little-endian signed 16-bit PCM (ffmpeg `s16le`) to samples in [-1, 1]
+pub fn pcm_from_s16le(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32)
+        .collect()
+}
+
+/// Pipe `input` through ffmpeg with the given arguments and return its stdout
+async fn ffmpeg(args: &[&str], input: Vec<u8>) -> Result<Vec<u8>> {
+    let mut child = tokio::process::Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-i", "pipe:0"])
+        .args(args)
+        .arg("pipe:1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("ffmpeg is required for Telegram voice notes")?;
+
+    let mut stdin = child.stdin.take().context("ffmpeg stdin")?;
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(&input).await;
+    });
+    let mut output = Vec::new();
+    child.stdout.take().context("ffmpeg stdout")?.read_to_end(&mut output).await?;
+    let _ = writer.await;
+
+    let status = child.wait().await?;
+    anyhow::ensure!(status.success(), "ffmpeg exited with {}", status);
+    Ok(output)
+}
+
+pub struct TelegramService {
+    config: TelegramConfig,
+    supervisor: Arc<Mutex<Supervisor>>,
+    speaker: Speaker,
+    /// Whisper is loaded on the first voice note
+    transcriber: OnceCell<Arc<WhisperTranscriber>>,
+    /// Last unanswered approval (and the query that triggered it) per chat
+    approvals: Mutex<HashMap<i64, (ApprovalRequest, String)>>,
+}
+
+impl TelegramService {
+    pub fn new(config: TelegramConfig, supervisor: Arc<Mutex<Supervisor>>) -> Self {
+        Self {
+            config,
+            supervisor,
+            speaker: Speaker::default(),
+            transcriber: OnceCell::new(),
+            approvals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn chat_allowed(&self, chat: ChatId) -> bool {
+        self.config.allowed_chats.contains(&chat.0)
+    }
+
+    /// Approvals need a listed user, not just any member of a listed group chat
+    fn approver_allowed(&self, msg: &Message) -> bool {
+        msg.from.as_ref().is_some_and(|user| {
+            i64::try_from(user.id.0).is_ok_and(|id| self.config.allowed_chats.contains(&id))
+        })
+    }
+
+    /// Long-poll for updates until the process exits
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        let bot = Bot::new(&self.config.token);
+        info!("Telegram: bot front-end listening");
+
+        let handler = Update::filter_message().endpoint(|bot: Bot, msg: Message, service: Arc<TelegramService>| async move {
+            if service.chat_allowed(msg.chat.id) {
+                if let Err(e) = service.handle_message(&bot, &msg).await {
+                    warn!("Telegram: failed to handle message: {}", e);
+                    let _ = bot.send_message(msg.chat.id, format!("⚠️ {}", e)).await;
+                }
+            }
+            respond(())
+        });
+
+        Dispatcher::builder(bot, handler)
+            .dependencies(dptree::deps![self])
+            .build()
+            .dispatch()
+            .await;
+        Ok(())
+    }
+
+    async fn handle_message(&self, bot: &Bot, msg: &Message) -> Result<()> {
+        let chat = msg.chat.id;
+        if let Some(voice) = msg.voice() {
+            bot.send_chat_action(chat, ChatAction::Typing).await?;
+            let file = bot.get_file(voice.file.id.clone()).await?;
+            let mut audio = Vec::new();
+            bot.download_file(&file.path, &mut audio).await?;
+
+            let query = self.transcribe(audio).await?;
+            if query.is_empty() {
+                bot.send_message(chat, "🤷 I couldn't make out that voice note.").await?;
+                return Ok(());
+            }
+            info!("Telegram: voice note transcribed: {}", query);
+            bot.send_message(chat, format!("🎙️ {}", query)).await?;
+            return self.run_turn(bot, chat, &query, true).await;
+        }
+
+        let Some(text) = msg.text() else { return Ok(()) };
+        match TelegramCommand::parse(text) {
+            TelegramCommand::Start => {
+                let name = self.supervisor.lock().await.profile.name.clone();
+                bot.send_message(chat, format!("👋 {} here. Send a message or a voice note.", name)).await?;
+                Ok(())
+            }
+            TelegramCommand::Approve | TelegramCommand::Deny if !self.approver_allowed(msg) => {
+                bot.send_message(chat, "⛔ Only allowlisted users can answer approvals.").await?;
+                Ok(())
+            }
+            TelegramCommand::Approve => {
+                let Some((approval, query)) = self.approvals.lock().await.remove(&chat.0) else {
+                    bot.send_message(chat, "Nothing is waiting for approval.").await?;
+                    return Ok(());
+                };
+                {
+                    let supervisor = self.supervisor.lock().await;
+                    let _ = supervisor.approvals.resolve(&approval.id, true).await;
+                    supervisor.safety.lock().await.approve_call(&approval.tool_name, &approval.parameters);
+                }
+                bot.send_message(chat, format!("✅ {} approved", approval.tool_name)).await?;
+                self.run_turn(bot, chat, &query, false).await
+            }
+            TelegramCommand::Deny => {
+                if let Some((approval, _)) = self.approvals.lock().await.remove(&chat.0) {
+                    let _ = self.supervisor.lock().await.reject(&approval.id).await;
+                    bot.send_message(chat, format!("🚫 {} denied", approval.tool_name)).await?;
+                }
+                Ok(())
+            }
+            TelegramCommand::Query(query) => self.run_turn(bot, chat, &query, false).await,
+        }
+    }
+
+    async fn transcribe(&self, ogg: Vec<u8>) -> Result<String> {
+        let transcriber = self.transcriber.get_or_try_init(|| async {
+            info!("Telegram: loading Whisper for voice notes...");
+            let transcriber = tokio::task::spawn_blocking(WhisperTranscriber::load).await??;
+            Ok::<_, anyhow::Error>(Arc::new(transcriber))
+        }).await?;
+
+        let rate = VOICE_SAMPLE_RATE.to_string();
+        let pcm = ffmpeg(&["-f", "s16le", "-ac", "1", "-ar", &rate], ogg).await?;
+        Ok(transcriber.transcribe(pcm_from_s16le(&pcm), VOICE_SAMPLE_RATE).await?.trim().to_string())
+    }
+
+    /// One turn on the shared Supervisor; voice questions also get a spoken answer
+    async fn run_turn(&self, bot: &Bot, chat: ChatId, query: &str, speak: bool) -> Result<()> {
+        bot.send_chat_action(chat, ChatAction::Typing).await?;
+        let result = self.supervisor.lock().await.handle(query).await?;
+
+        for chunk in split_message(&result.answer, MAX_MESSAGE_CHARS) {
+            bot.send_message(chat, chunk).await?;
+        }
+        if speak && !result.answer.trim().is_empty() {
+            if let Err(e) = self.send_voice(bot, chat, &result.answer).await {
+                warn!("Telegram: voice reply failed: {}", e);
+            }
+        }
+        if let Some(approval) = result.pending_approval {
+            bot.send_message(chat, format!(
+                "🛡️ Approval needed for {}\n{}\n{}\n\nReply /approve or /deny.",
+                approval.tool_name,
+                approval.rationale,
+                serde_json::to_string_pretty(&approval.parameters).unwrap_or_default()
+            )).await?;
+            self.approvals.lock().await.insert(chat.0, (approval, query.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Voice notes must be OGG/Opus; without an Opus encoder the WAV is sent as a plain audio file
+    async fn send_voice(&self, bot: &Bot, chat: ChatId, text: &str) -> Result<()> {
+        bot.send_chat_action(chat, ChatAction::RecordVoice).await?;
+        let wav = self.speaker.synthesize_wav(text).await?;
+        match ffmpeg(&["-c:a", "libopus", "-b:a", "32k", "-f", "ogg"], wav.clone()).await {
+            Ok(ogg) => bot.send_voice(chat, InputFile::memory(ogg).file_name("reply.ogg")).await?,
+            Err(e) => {
+                warn!("Telegram: Opus encoding failed ({}), sending WAV", e);
+                bot.send_audio(chat, InputFile::memory(wav).file_name("reply.wav")).await?
+            }
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands_and_pcm() {
+        assert_eq!(TelegramCommand::parse("/start"), TelegramCommand::Start);
+        assert_eq!(TelegramCommand::parse("/approve@agency_bot"), TelegramCommand::Approve);
+        assert_eq!(TelegramCommand::parse("  what's the weather? "), TelegramCommand::Query("what's the weather?".into()));
+
+        let pcm = pcm_from_s16le(&[0xff, 0x7f, 0x00, 0x00, 0x01, 0x80, 0x42]);
+        assert_eq!(pcm, vec![1.0, 0.0, -1.0]);
+    }
+}
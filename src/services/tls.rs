@@ -0,0 +1,118 @@
+//! TLS Termination
+//!
+//! Serves the HTTP API, dashboard WebSocket and A2A endpoints over HTTPS without a reverse
+//! proxy, either from a certificate and key on disk or with certificates obtained and
+//! renewed automatically over ACME (Let's Encrypt, TLS-ALPN-01 on the same port).
+
+use anyhow::{Context, Result};
+use axum::Router;
+use futures_util::StreamExt;
+use rustls_acme::{caches::DirCache, AcmeConfig};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TlsMode {
+    /// PEM certificate chain and private key, reloaded on restart
+    Pem { cert: PathBuf, key: PathBuf },
+    /// Certificates for `domains` from an ACME directory
+    Acme {
+        domains: Vec<String>,
+        contact: Option<String>,
+        /// Where issued certificates and the account key are kept; `None` requests new ones each start
+        cache: Option<PathBuf>,
+        /// Use the Let's Encrypt staging directory
+        staging: bool,
+    },
+}
+
+impl TlsMode {
+    /// `AGENCY_TLS_CERT` and `AGENCY_TLS_KEY`, or `AGENCY_TLS_ACME_DOMAINS` (comma-separated) with
+    /// `AGENCY_TLS_ACME_CONTACT`, `AGENCY_TLS_ACME_CACHE` (default `acme_cache`; `off` disables)
+    /// and `AGENCY_TLS_ACME_STAGING`. `None` serves plain HTTP.
+    pub fn from_env() -> Result<Option<Self>> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// `from_env` over any variable lookup
+    fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Option<Self>> {
+        let var = |name: &str| lookup(name).filter(|v| !v.is_empty());
+        match (var("AGENCY_TLS_CERT"), var("AGENCY_TLS_KEY")) {
+            (Some(cert), Some(key)) => return Ok(Some(TlsMode::Pem { cert: cert.into(), key: key.into() })),
+            (Some(_), None) | (None, Some(_)) => anyhow::bail!("AGENCY_TLS_CERT and AGENCY_TLS_KEY must be set together"),
+            (None, None) => {}
+        }
+
+        let Some(domains) = var("AGENCY_TLS_ACME_DOMAINS") else { return Ok(None) };
+        let domains: Vec<String> = domains.split(',').map(|d| d.trim().to_string()).filter(|d| !d.is_empty()).collect();
+        if domains.is_empty() {
+            anyhow::bail!("AGENCY_TLS_ACME_DOMAINS names no domain");
+        }
+        let cache = var("AGENCY_TLS_ACME_CACHE").unwrap_or_else(|| "acme_cache".to_string());
+        Ok(Some(TlsMode::Acme {
+            domains,
+            contact: var("AGENCY_TLS_ACME_CONTACT"),
+            cache: (cache != "off").then(|| PathBuf::from(cache)),
+            staging: var("AGENCY_TLS_ACME_STAGING").is_some_and(|v| v == "1" || v == "true"),
+        }))
+    }
+
+    pub async fn serve(self, addr: SocketAddr, app: Router) -> Result<()> {
+        match self {
+            TlsMode::Pem { cert, key } => {
+                let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&cert, &key).await
+                    .with_context(|| format!("Failed to load TLS certificate {} / key {}", cert.display(), key.display()))?;
+                axum_server::bind_rustls(addr, config).serve(app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+            }
+            TlsMode::Acme { domains, contact, cache, staging } => {
+                let mut state = AcmeConfig::new(domains)
+                    .contact(contact.iter().map(|c| if c.contains(':') { c.clone() } else { format!("mailto:{}", c) }))
+                    .cache_option(cache.map(DirCache::new))
+                    .directory_lets_encrypt(!staging)
+                    .state();
+                let acceptor = state.axum_acceptor(state.default_rustls_config());
+                tokio::spawn(async move {
+                    while let Some(event) = state.next().await {
+                        match event {
+                            Ok(event) => tracing::info!("ACME: {:?}", event),
+                            Err(e) => tracing::error!("ACME: {:?}", e),
+                        }
+                    }
+                });
+                axum_server::bind(addr).acceptor(acceptor).serve(app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn mode(vars: &[(&str, &str)]) -> Result<Option<TlsMode>> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        TlsMode::from_vars(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_tls_config_validation() {
+        assert_eq!(mode(&[]).unwrap(), None);
+        assert_eq!(
+            mode(&[("AGENCY_TLS_CERT", "cert.pem"), ("AGENCY_TLS_KEY", "key.pem")]).unwrap(),
+            Some(TlsMode::Pem { cert: "cert.pem".into(), key: "key.pem".into() })
+        );
+        assert!(mode(&[("AGENCY_TLS_CERT", "cert.pem")]).is_err());
+        assert!(mode(&[("AGENCY_TLS_KEY", "key.pem")]).is_err());
+        assert!(mode(&[("AGENCY_TLS_ACME_DOMAINS", " , ")]).is_err());
+
+        let acme = mode(&[("AGENCY_TLS_ACME_DOMAINS", "a.example, b.example"), ("AGENCY_TLS_ACME_CACHE", "off")]).unwrap();
+        assert_eq!(acme, Some(TlsMode::Acme {
+            domains: vec!["a.example".to_string(), "b.example".to_string()],
+            contact: None,
+            cache: None,
+            staging: false,
+        }));
+    }
+}
@@ -0,0 +1,254 @@
+//! Session Transcripts
+//!
+//! `GET /v1/sessions/{id}/export?format=md|json` renders a conversation for sharing: its
+//! episodic memory, then each finished turn still in the turn history with the agents that
+//! answered it (from the `HistoryManager` log), the tools it called (from the event log) and
+//! its publication. `json` (the default) returns the `Transcript` itself, `md` a Markdown
+//! document.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::memory::episodic::{ConversationTurn, Role};
+use crate::memory::HistoryEntry;
+use crate::orchestrator::event_bus::AgencyEvent;
+use crate::orchestrator::turn_history::{TurnRecord, TURN_HISTORY};
+use crate::orchestrator::{EventRecord, Publication, ReplayQuery, AGENCY_EVENT_BUS};
+use crate::server::{conversation_key, AppState, ServerError, User};
+use crate::services::web_sessions::ClientSession;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Md,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub session: String,
+    pub exported_at: DateTime<Utc>,
+    /// Episodic memory, oldest first
+    pub messages: Vec<ConversationTurn>,
+    /// Finished turns, oldest first
+    pub turns: Vec<TranscriptTurn>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptTurn {
+    pub turn_id: String,
+    pub query: String,
+    pub answer: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub finished_at: DateTime<Utc>,
+    /// Agents that answered, in order
+    pub agents: Vec<String>,
+    pub tools: Vec<ToolTrace>,
+    pub publication: Option<Publication>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolTrace {
+    pub tool: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` when the call never finished
+    pub success: Option<bool>,
+    pub duration_ms: Option<i64>,
+}
+
+impl TranscriptTurn {
+    pub fn new(record: TurnRecord, history: &[HistoryEntry], events: &[EventRecord]) -> Self {
+        let agents = history.iter()
+            .filter(|e| e.session_id == record.turn_id && e.role == "assistant")
+            .filter_map(|e| e.agent.clone())
+            .collect();
+        Self {
+            agents,
+            tools: tool_traces(events),
+            turn_id: record.turn_id,
+            query: record.query,
+            answer: record.answer,
+            success: record.success,
+            error: record.error,
+            finished_at: record.finished_at,
+            publication: record.publication,
+        }
+    }
+}
+
+/// Tool calls of one turn's events, each finish matched to the oldest open call of its tool
+fn tool_traces(events: &[EventRecord]) -> Vec<ToolTrace> {
+    let mut traces: Vec<ToolTrace> = Vec::new();
+    for record in events {
+        match record.event {
+            AgencyEvent::ToolCallStarted { ref tool } => traces.push(ToolTrace {
+                tool: tool.clone(),
+                started_at: record.timestamp,
+                success: None,
+                duration_ms: None,
+            }),
+            AgencyEvent::ToolCallFinished { ref tool, success } => {
+                if let Some(trace) = traces.iter_mut().find(|t| &t.tool == tool && t.success.is_none()) {
+                    trace.success = Some(success);
+                    trace.duration_ms = Some((record.timestamp - trace.started_at).num_milliseconds());
+                }
+            }
+            _ => {}
+        }
+    }
+    traces
+}
+
+impl Transcript {
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Session {}\n\n_Exported {}_\n", self.session, self.exported_at.format("%Y-%m-%d %H:%M UTC"));
+
+        if !self.messages.is_empty() {
+            md.push_str("## Conversation\n\n");
+            for message in &self.messages {
+                let speaker = match (&message.role, &message.agent) {
+                    (Role::User, _) => "User".to_string(),
+                    (Role::Assistant, Some(agent)) => format!("Assistant ({})", agent),
+                    (Role::Assistant, None) => "Assistant".to_string(),
+                    (Role::System, _) => "System".to_string(),
+                    (Role::Tool, Some(tool)) => format!("Tool ({})", tool),
+                    (Role::Tool, None) => "Tool".to_string(),
+                };
+                let _ = writeln!(md, "**{}** · {}\n\n{}\n", speaker, message.timestamp.format("%H:%M:%S"), message.content.trim());
+            }
+        }
+
+        if !self.turns.is_empty() {
+            md.push_str("## Turns\n\n");
+        }
+        for turn in &self.turns {
+            let _ = writeln!(md, "### {} · `{}`\n", turn.finished_at.format("%Y-%m-%d %H:%M:%S"), turn.turn_id);
+            let _ = writeln!(md, "**Query:** {}\n", turn.query.trim());
+            match turn.error {
+                Some(ref error) => { let _ = writeln!(md, "**Failed:** {}\n", error); }
+                None => { let _ = writeln!(md, "**Answer:**\n\n{}\n", turn.answer.trim()); }
+            }
+            if !turn.agents.is_empty() {
+                let _ = writeln!(md, "- Answered by: {}", turn.agents.join(", "));
+            }
+            for tool in &turn.tools {
+                let outcome = match tool.success {
+                    Some(true) => "ok",
+                    Some(false) => "failed",
+                    None => "unfinished",
+                };
+                let duration = tool.duration_ms.map(|ms| format!(", {} ms", ms)).unwrap_or_default();
+                let _ = writeln!(md, "- Tool `{}`: {}{}", tool.tool, outcome, duration);
+            }
+            if let Some(ref publication) = turn.publication {
+                let telemetry = &publication.telemetry;
+                let cost = telemetry.usage.as_ref().map(|u| format!(", ${:.4}", u.cost_usd)).unwrap_or_default();
+                let _ = writeln!(md, "- Reliability {:.2} · {} · {} ms · {} tool calls{}",
+                    publication.reliability, telemetry.model, telemetry.latency_ms, telemetry.tool_calls, cost);
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+pub async fn export_session(
+    State(state): State<AppState>,
+    user: User,
+    session: ClientSession,
+    Path(id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ServerError> {
+    let not_found = || (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "Unknown session" }))).into_response();
+    let key = match conversation_key(&user, Some(&id)) {
+        Ok(Some(key)) => key,
+        Ok(None) => return Ok(not_found()),
+        Err(e) => return Ok((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))).into_response()),
+    };
+    // Other browsers' cookie ids are not exportable, as in the session list
+    if user.is_none() && key.starts_with("web-") && session.0.as_deref() != Some(key.as_str()) {
+        return Ok(not_found());
+    }
+    if !state.conversations.ids().await.contains(&key) {
+        return Ok(not_found());
+    }
+    let supervisor = state.conversations.get(&key).await?;
+    let (messages, history_manager) = match supervisor.try_lock() {
+        Ok(supervisor) => (supervisor.episodic_memory.lock().await.get_turns(), supervisor.history_manager.clone()),
+        Err(_) => return Ok((StatusCode::CONFLICT, Json(serde_json::json!({ "error": "A turn is running; export once it finishes" }))).into_response()),
+    };
+
+    let history = history_manager.load_recent(usize::MAX).await?;
+    let mut turns = Vec::new();
+    // Newest first in the history
+    for record in TURN_HISTORY.list(|t| t.conversation.as_deref() == Some(key.as_str())).into_iter().rev() {
+        let events = AGENCY_EVENT_BUS.replay(&ReplayQuery { turn: Some(record.turn_id.clone()), ..Default::default() }).await;
+        turns.push(TranscriptTurn::new(record, &history, &events));
+    }
+    let transcript = Transcript { session: id.clone(), exported_at: Utc::now(), messages, turns };
+
+    Ok(match query.format {
+        ExportFormat::Json => Json(transcript).into_response(),
+        ExportFormat::Md => (
+            [
+                (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.md\"", id)),
+            ],
+            transcript.to_markdown(),
+        ).into_response(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(seq: u64, ms: i64, event: AgencyEvent) -> EventRecord {
+        EventRecord { seq, timestamp: Utc.timestamp_millis_opt(ms).unwrap(), turn_id: Some("t1".to_string()), user: None, event }
+    }
+
+    #[test]
+    fn test_tool_traces_and_markdown() {
+        let events = vec![
+            event(1, 1_000, AgencyEvent::ToolCallStarted { tool: "web_search".to_string() }),
+            event(2, 1_100, AgencyEvent::ToolCallStarted { tool: "code_exec".to_string() }),
+            event(3, 1_250, AgencyEvent::ToolCallFinished { tool: "web_search".to_string(), success: true }),
+        ];
+        let traces = tool_traces(&events);
+        assert_eq!(traces.len(), 2);
+        assert_eq!((traces[0].success, traces[0].duration_ms), (Some(true), Some(250)));
+        assert_eq!(traces[1].success, None);
+
+        let result = Err(crate::agent::AgentError::Validation("no budget".to_string()));
+        let record = TurnRecord::new("t1".to_string(), Some("research".to_string()), None, "What changed?", &result);
+        let history = vec![HistoryEntry { session_id: "t1".to_string(), role: "assistant".to_string(), agent: Some("Researcher".to_string()), ts: 0, text: String::new() }];
+        let transcript = Transcript {
+            session: "research".to_string(),
+            exported_at: Utc::now(),
+            messages: Vec::new(),
+            turns: vec![TranscriptTurn::new(record, &history, &events)],
+        };
+        let md = transcript.to_markdown();
+        assert!(md.starts_with("# Session research"));
+        assert!(md.contains("**Query:** What changed?"));
+        assert!(md.contains("**Failed:** Validation error: no budget"));
+        assert!(md.contains("- Answered by: Researcher"));
+        assert!(md.contains("- Tool `web_search`: ok, 250 ms"));
+        assert!(md.contains("- Tool `code_exec`: unfinished"));
+    }
+}
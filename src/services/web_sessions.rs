@@ -0,0 +1,136 @@
+//! Web Client Sessions
+//!
+//! Keeps browsers apart on the server. The dashboard hands each browser an `agency_session`
+//! cookie (API clients may send an `X-Agency-Session` header instead); requests carrying one run
+//! in that session's own conversation, with its own episodic memory, and the dashboard
+//! WebSocket gets a per-session channel and running task, so one browser neither sees nor stops
+//! another's turns.
+
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderValue};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+
+use crate::orchestrator::conversations::valid_session_id;
+use crate::services::ws_protocol::ServerMessage;
+
+pub const SESSION_COOKIE: &str = "agency_session";
+pub const SESSION_HEADER: &str = "x-agency-session";
+
+/// Messages buffered per session channel, unless configured
+const CHANNEL_CAPACITY: usize = 100;
+
+/// The caller's web session id, if it sent a valid one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSession(pub Option<String>);
+
+impl ClientSession {
+    /// A new random session id; the id is the client's only credential, so it is never listed
+    pub fn generate() -> String {
+        format!("web-{}", &uuid::Uuid::new_v4().simple().to_string()[..16])
+    }
+
+    /// `Set-Cookie` value handing `id` to a browser
+    pub fn cookie(id: &str) -> HeaderValue {
+        HeaderValue::from_str(&format!("{}={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=31536000", SESSION_COOKIE, id))
+            .expect("session ids are header-safe")
+    }
+
+    fn from_parts(parts: &Parts) -> Option<String> {
+        let from_header = parts.headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let from_cookie = || {
+            parts.headers.get_all(header::COOKIE).iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(';'))
+                .find_map(|pair| pair.trim().strip_prefix(SESSION_COOKIE)?.strip_prefix('=').map(str::to_string))
+        };
+        from_header.or_else(from_cookie).filter(|id| valid_session_id(id))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientSession {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientSession(Self::from_parts(parts)))
+    }
+}
+
+/// Dashboard channel and running turn of one web session
+#[derive(Clone)]
+pub struct WebSession {
+    pub tx: broadcast::Sender<ServerMessage>,
+    pub current_task: Arc<Mutex<Option<tokio::task::AbortHandle>>>,
+}
+
+pub struct WebSessions {
+    sessions: Mutex<HashMap<String, WebSession>>,
+    capacity: usize,
+}
+
+impl Default for WebSessions {
+    fn default() -> Self {
+        Self::with_capacity(CHANNEL_CAPACITY)
+    }
+}
+
+impl WebSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Session channels buffering `capacity` messages each
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { sessions: Mutex::new(HashMap::new()), capacity }
+    }
+
+    /// The session's channel and task slot, created on first use
+    pub async fn get(&self, id: &str) -> WebSession {
+        self.sessions.lock().await.entry(id.to_string()).or_insert_with(|| WebSession {
+            tx: broadcast::channel(self.capacity).0,
+            current_task: Arc::new(Mutex::new(None)),
+        }).clone()
+    }
+
+    /// Abort every session's running turn; returns how many were running
+    pub async fn abort_all(&self) -> usize {
+        let mut aborted = 0;
+        for session in self.sessions.lock().await.values() {
+            if let Some(handle) = session.current_task.lock().await.take() {
+                handle.abort();
+                aborted += 1;
+            }
+        }
+        aborted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn session(headers: &[(&str, &str)]) -> Option<String> {
+        let mut request = Request::builder();
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let (parts, _) = request.body(()).unwrap().into_parts();
+        ClientSession::from_parts(&parts)
+    }
+
+    #[test]
+    fn test_session_from_cookie_or_header() {
+        let id = ClientSession::generate();
+        assert!(valid_session_id(&id));
+        assert_eq!(session(&[("cookie", &format!("theme=dark; {}={}", SESSION_COOKIE, id))]), Some(id.clone()));
+        assert_eq!(session(&[(SESSION_HEADER, "ci-runner"), ("cookie", &format!("{}={}", SESSION_COOKIE, id))]), Some("ci-runner".to_string()));
+        // Ids become file names, so anything else is ignored
+        assert_eq!(session(&[("cookie", "agency_session=../../etc")]), None);
+        assert_eq!(session(&[("cookie", "agency_session_old=x")]), None);
+        assert_eq!(session(&[]), None);
+        assert!(ClientSession::cookie(&id).to_str().unwrap().contains("HttpOnly"));
+    }
+}
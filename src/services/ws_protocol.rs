@@ -0,0 +1,186 @@
+//! Dashboard WebSocket Protocol
+//!
+//! Messages on `/ws`. Clients that connect with `?v=1` exchange JSON objects tagged by `type`
+//! with the protocol version in `v`, e.g. `{"v":1,"type":"token","text":"..."}`. Clients
+//! without `v` (the built-in dashboard) get the older prefixed strings (`TOKEN:`, `STATE:`,
+//! `ASSURANCE:`, ...) rendered from the same messages by `ServerMessage::to_legacy`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{PubCharacteristic, UsageSummary};
+use crate::orchestrator::event_bus::FPFBoundClaim;
+use crate::orchestrator::{Publication, TurnEvent};
+
+/// Newest protocol version the server speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A message with the protocol version it was written for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<M> {
+    pub v: u32,
+    #[serde(flatten)]
+    pub message: M,
+}
+
+/// Turn lifecycle states shown by the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnState {
+    /// The model moved from reasoning to its answer
+    AnswerStart,
+    ThoughtStart,
+    TurnComplete,
+    /// Stopped by the client
+    Stopped,
+    /// Replaced by a newer query, or failed
+    Aborted,
+    /// The kill switch was engaged
+    Halted,
+    Rearmed,
+}
+
+impl TurnState {
+    fn legacy_name(self) -> &'static str {
+        match self {
+            TurnState::AnswerStart => "ANSWER_START",
+            TurnState::ThoughtStart => "THOUGHT_START",
+            TurnState::TurnComplete => "TURN_COMPLETE",
+            TurnState::Stopped => "STOPPED",
+            TurnState::Aborted => "ABORTED",
+            TurnState::Halted => "HALTED",
+            TurnState::Rearmed => "REARMED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// First message of a versioned connection
+    Hello { version: u32, session: Option<String> },
+    Metrics { since: String, memory: usize },
+    /// A turn was accepted; `message` describes where it came from
+    TurnStarted { message: String },
+    State { state: TurnState },
+    /// The model now serving the turn
+    Model { model: String },
+    /// Streamed model output, before the answer starts
+    Thought { text: String },
+    /// Streamed answer text
+    Answer { text: String },
+    /// Raw token from a publishing provider; the client decides where it belongs
+    Token { text: String },
+    FinalAnswer { answer: String },
+    Reliability { score: f32 },
+    Assurance {
+        latency_ms: u64,
+        tool_calls: usize,
+        evidence_count: usize,
+        scale: String,
+        model: String,
+        usage: Option<UsageSummary>,
+    },
+    TurnEvent { event: TurnEvent },
+    BoundaryCrossing { claim: FPFBoundClaim },
+    PublicationUpdate { pc: PubCharacteristic },
+    ToolProgress { tool: String, chunk: String },
+    Error { message: String },
+}
+
+impl ServerMessage {
+    pub fn assurance(publication: &Publication) -> Self {
+        let telemetry = &publication.telemetry;
+        ServerMessage::Assurance {
+            latency_ms: telemetry.latency_ms as u64,
+            tool_calls: telemetry.tool_calls,
+            evidence_count: telemetry.evidence_count,
+            scale: format!("{:?}", telemetry.scale),
+            model: telemetry.model.clone(),
+            usage: telemetry.usage.clone(),
+        }
+    }
+
+    /// The text frame for a client speaking `version`, or the legacy string without one
+    pub fn encode(&self, version: Option<u32>) -> Option<String> {
+        match version {
+            Some(v) => serde_json::to_string(&Envelope { v, message: self }).ok(),
+            None => self.to_legacy(),
+        }
+    }
+
+    /// The prefixed string the unversioned dashboard expects; `None` for messages it has no form for
+    pub fn to_legacy(&self) -> Option<String> {
+        Some(match self {
+            ServerMessage::Hello { .. } => return None,
+            ServerMessage::Metrics { since, memory } => format!("METRICS:{}", serde_json::json!({ "since": since, "memory": memory })),
+            ServerMessage::TurnStarted { message } => message.clone(),
+            ServerMessage::State { state } => format!("STATE:{}", state.legacy_name()),
+            ServerMessage::Model { model } => format!("STATE:MODEL:{}", model),
+            ServerMessage::Thought { text } => format!("THOUGHT:{}", text),
+            ServerMessage::Answer { text } => format!("ANSWER:{}", text),
+            ServerMessage::Token { text } => format!("TOKEN:{}", text),
+            ServerMessage::FinalAnswer { answer } => format!("FINAL_ANSWER:{}", answer),
+            ServerMessage::Reliability { score } => format!("RELIABILITY:{}", score),
+            ServerMessage::Assurance { latency_ms, tool_calls, evidence_count, scale, model, usage } => format!("ASSURANCE:{}", serde_json::json!({
+                "latency": latency_ms,
+                "tools": tool_calls,
+                "evidence": evidence_count,
+                "scale": scale,
+                "model": model,
+                "usage": usage,
+            })),
+            ServerMessage::TurnEvent { event } => format!("TURN_EVENT:{}", json(event)),
+            ServerMessage::BoundaryCrossing { claim } => format!("BOUNDARY_CROSSING:{}", json(claim)),
+            ServerMessage::PublicationUpdate { pc } => format!("PUBLICATION_UPDATE:{}", json(pc)),
+            ServerMessage::ToolProgress { tool, chunk } => format!("TOOL_PROGRESS:{}", serde_json::json!({ "tool": tool, "chunk": chunk })),
+            ServerMessage::Error { message } => format!("THOUGHT:\n🛑 **Error during execution:**\n{}\n", message),
+        })
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Messages from clients. Unversioned clients send the same objects without `v`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Run a turn, replacing the client's running one
+    Query { content: String },
+    /// Stop the client's running turn
+    Stop,
+    /// Redirect the agents of the running turn
+    Steer { message: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_and_legacy_encoding() {
+        let state = ServerMessage::State { state: TurnState::TurnComplete };
+        assert_eq!(state.encode(None).as_deref(), Some("STATE:TURN_COMPLETE"));
+        assert_eq!(state.encode(Some(1)).as_deref(), Some(r#"{"v":1,"type":"state","state":"turn_complete"}"#));
+
+        let model = ServerMessage::Model { model: "qwen2.5:3b".to_string() };
+        assert_eq!(model.to_legacy().as_deref(), Some("STATE:MODEL:qwen2.5:3b"));
+        assert!(ServerMessage::Hello { version: 1, session: None }.to_legacy().is_none());
+
+        let frame = ServerMessage::FinalAnswer { answer: "42".to_string() }.encode(Some(PROTOCOL_VERSION)).unwrap();
+        let parsed: Envelope<ServerMessage> = serde_json::from_str(&frame).unwrap();
+        assert_eq!(parsed.v, PROTOCOL_VERSION);
+        assert!(matches!(parsed.message, ServerMessage::FinalAnswer { ref answer } if answer == "42"));
+    }
+
+    #[test]
+    fn test_client_messages_with_or_without_version() {
+        // What the built-in dashboard sends
+        let legacy: ClientMessage = serde_json::from_str(r#"{"type":"query","content":"hi"}"#).unwrap();
+        assert_eq!(legacy, ClientMessage::Query { content: "hi".to_string() });
+        let typed: ClientMessage = serde_json::from_str(r#"{"v":1,"type":"stop"}"#).unwrap();
+        assert_eq!(typed, ClientMessage::Stop);
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type":"reboot"}"#).is_err());
+    }
+}
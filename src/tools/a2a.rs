@@ -9,9 +9,9 @@ use tokio::sync::Mutex;
 use tracing::info;
 
 use crate::agent::{AgentResult, AgentError, AgentType, AgentResponse};
-use crate::orchestrator::a2a::{AgentInteraction, A2ABridge};
+use crate::orchestrator::a2a::{AgentInteraction, A2ABridge, A2AClient, A2ATaskState, AgentCard};
 use crate::orchestrator::Supervisor;
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
 
 pub struct PeerAgentTool {
     target_agent: AgentType,
@@ -74,6 +74,14 @@ impl Tool for PeerAgentTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let query = params["query"].as_str().ok_or_else(|| AgentError::Validation("Missing query".to_string()))?;
         let context = params["context"].as_str();
@@ -102,14 +110,38 @@ impl Tool for PeerAgentTool {
     }
 }
 
+/// Longest wait for a remote A2A task before giving up
+const REMOTE_TASK_TIMEOUT_SECS: u64 = 300;
+
 pub struct RemoteAgencyTool {
     client: reqwest::Client,
+    a2a: A2AClient,
 }
 
 impl RemoteAgencyTool {
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            a2a: A2AClient::new(),
+        }
+    }
+
+    /// Submit a task over A2A JSON-RPC and wait for the result
+    async fn run_task(&self, url: &str, card: &AgentCard, skill: &str, query: &str) -> AgentResult<ToolOutput> {
+        let task = match self.a2a.run_task(url, card, skill, query, std::time::Duration::from_secs(REMOTE_TASK_TIMEOUT_SECS)).await {
+            Ok(task) => task,
+            Err(e) => return Ok(ToolOutput::failure(e.to_string())),
+        };
+        let answer = task.answer();
+        match task.status.state {
+            A2ATaskState::Completed => Ok(ToolOutput::success(
+                json!({ "answer": answer, "agent": card.name, "task_id": task.id }),
+                format!("Remote Response from {}:\n{}", card.name, answer)
+            )),
+            state => {
+                let reason = task.status.message.map(|m| m.text_content()).unwrap_or_default();
+                Ok(ToolOutput::failure(format!("Remote task on '{}' ended {:?}: {}", card.name, state, reason)))
+            }
         }
     }
 }
@@ -121,7 +153,7 @@ impl Tool for RemoteAgencyTool {
     }
 
     fn description(&self) -> String {
-        "Dial a remote Agency server over the internet. \n            Use this to collaborate with external agent swarms. \n            Requires the URL of the remote Nexus and the target agent role (e.g. 'coder', 'researcher').".to_string()
+        "Dial a remote Agency server or any A2A-compatible agent over the internet. \n            Use this to collaborate with external agent swarms. \n            Requires the URL of the remote and the target role or skill id (e.g. 'coder', 'researcher'). Use action 'card' to list a remote's skills first.".to_string()
     }
 
     fn parameters(&self) -> Value {
@@ -129,10 +161,11 @@ impl Tool for RemoteAgencyTool {
             "type": "object",
             "properties": {
                 "url": { "type": "string", "description": "The base URL of the remote agency (e.g. https://api.nexus.io)" },
-                "target_agent": { "type": "string", "enum": ["coder", "researcher", "reasoner", "chat"], "description": "The remote role to consult." },
+                "action": { "type": "string", "enum": ["ask", "card"], "description": "'ask' (default) sends the query; 'card' returns the remote's agent card." },
+                "target_agent": { "type": "string", "description": "The remote role or skill id to consult (e.g. 'coder', 'researcher', 'chat')." },
                 "query": { "type": "string", "description": "The task or query for the remote agency." }
             },
-            "required": ["url", "target_agent", "query"]
+            "required": ["url"]
         })
     }
 
@@ -140,15 +173,48 @@ impl Tool for RemoteAgencyTool {
         json!({
             "status": "external",
             "network": "required",
-            "protocol": "A2A/JSON-over-HTTP"
+            "protocol": "A2A/JSON-RPC, A2A/JSON-over-HTTP"
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_secs(REMOTE_TASK_TIMEOUT_SECS + 30))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let url = params["url"].as_str().ok_or_else(|| AgentError::Validation("Missing URL".to_string()))?;
+        let card = self.a2a.fetch_card(url).await;
+
+        if params["action"].as_str() == Some("card") {
+            return Ok(match card {
+                Some(card) => {
+                    let skills: Vec<String> = card.skills.iter().map(|s| format!("- {} ({}): {}", s.id, s.name, s.description)).collect();
+                    ToolOutput::success(
+                        serde_json::to_value(&card).unwrap_or_default(),
+                        format!("{}: {}\nSkills:\n{}", card.name, card.description, skills.join("\n"))
+                    )
+                }
+                None => ToolOutput::failure(format!("{} does not publish an A2A agent card", url)),
+            });
+        }
+
         let target_str = params["target_agent"].as_str().unwrap_or("chat");
         let query = params["query"].as_str().ok_or_else(|| AgentError::Validation("Missing query".to_string()))?;
 
+        // Anything with an agent card speaks the standard task protocol
+        if let Some(card) = card {
+            info!("A2A: Submitting task to '{}' at {}...", card.name, url);
+            return self.run_task(url, &card, target_str, query).await;
+        }
+
         let target_agent = match target_str {
             "coder" => AgentType::Coder,
             "researcher" => AgentType::Researcher,
@@ -181,7 +247,6 @@ impl Tool for RemoteAgencyTool {
     }
 }
 
-                    
                     pub struct AnonymousAgencyTool {
                         dialer: Arc<Mutex<Option<crate::orchestrator::arti_a2a::AnonymousDialer>>>,
                     }
@@ -226,6 +291,14 @@ impl Tool for RemoteAgencyTool {
                             })
                         }
                     
+                        fn capabilities(&self) -> Vec<ToolCapability> {
+                            vec![ToolCapability::Network]
+                        }
+
+                        fn cache_policy(&self) -> ToolCachePolicy {
+                            ToolCachePolicy::no_cache()
+                        }
+
                         async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
                             let url = params["url"].as_str().ok_or_else(|| AgentError::Validation("Missing URL".to_string()))?;
                             let target_str = params["target_agent"].as_str().unwrap_or("chat");
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCachePolicy};
 use crate::orchestrator::profile::{AgencyProfile, ProfileManager};
 
 pub struct AgencyControlTool {
@@ -51,6 +51,10 @@ impl Tool for AgencyControlTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let mut profile = self.current_profile.lock().await;
         
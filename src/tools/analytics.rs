@@ -0,0 +1,212 @@
+//! Tool Usage Analytics
+//!
+//! Append-only JSONL log of every tool call made through `ToolRegistry`, with per-tool
+//! aggregates (call count, failure rate, latency) so users can see which tools fail most.
+//! The log is rotated to `<path>.1` once it grows past `MAX_LOG_BYTES`.
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+lazy_static! {
+    /// Process-wide analytics log shared by every registry, `SystemTool` and the server.
+    /// Tests that need a log inject their own with `ToolRegistry::with_analytics`.
+    pub static ref TOOL_ANALYTICS: Arc<ToolAnalytics> = Arc::new(if cfg!(test) { ToolAnalytics::new(None) } else { ToolAnalytics::from_env() });
+}
+
+/// Default size at which the log is rotated; at most two files' worth of records is kept
+const MAX_LOG_BYTES: u64 = 32 * 1024 * 1024;
+
+/// One tool invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    /// Hash of the serialized parameters; raw params are not stored
+    pub params_hash: String,
+    pub latency_ms: u64,
+    pub success: bool,
+    pub agent: String,
+    #[serde(default)]
+    pub turn_id: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl ToolCallRecord {
+    pub fn hash_params(params: &serde_json::Value) -> String {
+        let digest = Sha256::digest(params.to_string().as_bytes());
+        digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Aggregate statistics for one tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: usize,
+    pub failures: usize,
+    pub failure_rate: f64,
+    pub avg_latency_ms: f64,
+    pub p95_latency_ms: u64,
+    pub last_error: Option<String>,
+}
+
+pub struct ToolAnalytics {
+    path: Option<PathBuf>,
+    max_bytes: u64,
+    write_lock: Mutex<()>,
+}
+
+impl ToolAnalytics {
+    /// Log to `path`; `None` disables recording
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, max_bytes: MAX_LOG_BYTES, write_lock: Mutex::new(()) }
+    }
+
+    /// Rotate the log at `max_bytes` instead of `MAX_LOG_BYTES`
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// `AGENCY_TOOL_ANALYTICS` (default `agency_tool_calls.jsonl`; `off` disables)
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_TOOL_ANALYTICS").unwrap_or_else(|_| "agency_tool_calls.jsonl".to_string());
+        Self::new((path != "off" && !path.is_empty()).then(|| PathBuf::from(path)))
+    }
+
+    pub async fn record(&self, record: &ToolCallRecord) {
+        let Some(path) = &self.path else { return };
+        let Ok(mut line) = serde_json::to_string(record) else { return };
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        let result = async {
+            if tokio::fs::metadata(path).await.is_ok_and(|m| m.len() >= self.max_bytes) {
+                tokio::fs::rename(path, Self::rotated(path)).await?;
+            }
+            let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+            file.write_all(line.as_bytes()).await
+        }.await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to record tool call analytics: {}", e);
+        }
+    }
+
+    fn rotated(path: &Path) -> PathBuf {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        PathBuf::from(rotated)
+    }
+
+    /// All records since `since` (or all), oldest first, skipping malformed lines
+    pub async fn load(&self, since: Option<DateTime<Utc>>) -> Vec<ToolCallRecord> {
+        let Some(path) = &self.path else { return Vec::new() };
+        let mut records = Vec::new();
+        for file in [Self::rotated(path), path.clone()] {
+            let Ok(file) = tokio::fs::File::open(&file).await else { continue };
+            let mut lines = tokio::io::BufReader::new(file).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Ok(record) = serde_json::from_str::<ToolCallRecord>(&line) {
+                    if since.is_none_or(|s| record.timestamp >= s) {
+                        records.push(record);
+                    }
+                }
+            }
+        }
+        records
+    }
+
+    /// Per-tool aggregates, most failures first
+    pub async fn stats(&self, since: Option<DateTime<Utc>>) -> Vec<ToolStats> {
+        Self::aggregate(&self.load(since).await)
+    }
+
+    pub fn aggregate(records: &[ToolCallRecord]) -> Vec<ToolStats> {
+        let mut by_tool: HashMap<&str, Vec<&ToolCallRecord>> = HashMap::new();
+        for record in records {
+            by_tool.entry(record.tool.as_str()).or_default().push(record);
+        }
+
+        let mut stats: Vec<ToolStats> = by_tool.into_iter().map(|(tool, calls)| {
+            let failures = calls.iter().filter(|r| !r.success).count();
+            let mut latencies: Vec<u64> = calls.iter().map(|r| r.latency_ms).collect();
+            latencies.sort_unstable();
+            let p95_index = ((latencies.len() as f64 * 0.95).ceil() as usize).saturating_sub(1);
+            ToolStats {
+                tool: tool.to_string(),
+                calls: calls.len(),
+                failures,
+                failure_rate: failures as f64 / calls.len() as f64,
+                avg_latency_ms: latencies.iter().sum::<u64>() as f64 / calls.len() as f64,
+                p95_latency_ms: latencies[p95_index.min(latencies.len() - 1)],
+                last_error: calls.iter().rev().find_map(|r| r.error.clone()),
+            }
+        }).collect();
+        stats.sort_by(|a, b| b.failures.cmp(&a.failures).then(b.calls.cmp(&a.calls)));
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_aggregate() {
+        let dir = tempfile::tempdir().unwrap();
+        let analytics = ToolAnalytics::new(Some(dir.path().join("calls.jsonl")));
+
+        for (tool, success, latency) in [("web_search", true, 100), ("web_search", false, 300), ("git", true, 10)] {
+            analytics.record(&ToolCallRecord {
+                timestamp: Utc::now(),
+                tool: tool.to_string(),
+                params_hash: ToolCallRecord::hash_params(&serde_json::json!({})),
+                latency_ms: latency,
+                success,
+                agent: "Coder".to_string(),
+                turn_id: None,
+                error: (!success).then(|| "timeout".to_string()),
+            }).await;
+        }
+
+        let stats = analytics.stats(None).await;
+        assert_eq!(stats[0].tool, "web_search");
+        assert_eq!(stats[0].calls, 2);
+        assert_eq!(stats[0].failure_rate, 0.5);
+        assert_eq!(stats[0].p95_latency_ms, 300);
+        assert_eq!(stats[0].last_error.as_deref(), Some("timeout"));
+        assert_eq!(stats[1].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_log_is_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calls.jsonl");
+        let analytics = ToolAnalytics::new(Some(path.clone())).with_max_bytes(1);
+        for tool in ["a", "b", "c"] {
+            analytics.record(&ToolCallRecord {
+                timestamp: Utc::now(),
+                tool: tool.to_string(),
+                params_hash: String::new(),
+                latency_ms: 1,
+                success: true,
+                agent: "Coder".to_string(),
+                turn_id: None,
+                error: None,
+            }).await;
+        }
+
+        // "a" was rotated out twice over; the newest two records remain, oldest first
+        let tools: Vec<String> = analytics.load(None).await.into_iter().map(|r| r.tool).collect();
+        assert_eq!(tools, vec!["b".to_string(), "c".to_string()]);
+        assert!(dir.path().join("calls.jsonl.1").exists());
+    }
+}
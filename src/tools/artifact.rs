@@ -10,7 +10,7 @@ use tokio::fs;
 use tracing::info;
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability};
 
 /// Tool for managing persistent artifacts
 pub struct ArtifactTool {
@@ -94,6 +94,10 @@ impl Tool for ArtifactTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::FilesystemWrite]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         self.ensure_dir().await?;
 
@@ -110,8 +114,23 @@ impl Tool for ArtifactTool {
                     .as_str()
                     .ok_or_else(|| AgentError::Validation("Missing required parameter: content".to_string()))?;
                 
+                let scanner = &crate::safety::SECRET_SCANNER;
+                let (masked, findings) = scanner.mask(content);
+                let content = if findings.is_empty() {
+                    content.to_string()
+                } else {
+                    scanner.report(&format!("artifact '{}'", filename), &format!("SECRET-artifact-{}", filename), &findings);
+                    if scanner.mode() == crate::safety::SecretMode::Block {
+                        return Ok(ToolOutput::failure(format!(
+                            "Refusing to save artifact '{}': it contains {}",
+                            filename, crate::safety::secrets::describe(&findings)
+                        )));
+                    }
+                    masked
+                };
+
                 let path = self.resolve_path(filename)?;
-                fs::write(&path, content).await
+                fs::write(&path, &content).await
                     .map_err(|e| AgentError::Io(e))?;
                 
                 info!("Artifact written: {}", filename);
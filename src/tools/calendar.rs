@@ -0,0 +1,434 @@
+//! Calendar Tool
+//!
+//! Lists and creates events in a local `.ics` file or on a CalDAV server, so scheduling
+//! requests routed to the Planner can be acted on instead of only being planned.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use reqwest::{Client, Method};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use super::{Tool, ToolOutput, ToolCapability};
+
+/// Longest `duration_minutes` accepted (a year)
+const MAX_DURATION_MINUTES: i64 = 60 * 24 * 366;
+
+/// Where events are stored
+#[derive(Debug, Clone)]
+pub enum CalendarSource {
+    /// A local iCalendar file, created on first write
+    Ics(PathBuf),
+    /// A CalDAV calendar collection URL
+    CalDav { url: String, username: Option<String>, password: Option<String> },
+}
+
+/// A single VEVENT
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+impl CalendarEvent {
+    fn overlaps(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        let end = self.end.unwrap_or(self.start);
+        self.start < to && end >= from
+    }
+
+    /// Serialize as a VEVENT block (CRLF line endings)
+    pub fn to_ics(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", self.uid),
+            format!("DTSTAMP:{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        ];
+        if self.all_day {
+            // Dates are floating: the local day the event was created for, not its UTC instant
+            let date = |t: DateTime<Utc>| t.with_timezone(&Local).format("%Y%m%d").to_string();
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date(self.start)));
+            if let Some(end) = self.end {
+                lines.push(format!("DTEND;VALUE=DATE:{}", date(end)));
+            }
+        } else {
+            lines.push(format!("DTSTART:{}", self.start.format("%Y%m%dT%H%M%SZ")));
+            if let Some(end) = self.end {
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&self.summary)));
+        if let Some(location) = &self.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(description) = &self.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push("END:VEVENT".to_string());
+        lines.join("\r\n") + "\r\n"
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    text.replace("\\n", "\n").replace("\\N", "\n").replace("\\,", ",").replace("\\;", ";").replace("\\\\", "\\")
+}
+
+/// Parse an iCalendar DATE or DATE-TIME. Floating and TZID times are read as local time.
+fn parse_ics_datetime(value: &str) -> Option<(DateTime<Utc>, bool)> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive), false));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some((Local.from_local_datetime(&naive).earliest()?.with_timezone(&Utc), false));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some((Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?.with_timezone(&Utc), true))
+}
+
+/// Extract all VEVENTs from iCalendar text (also works on CalDAV multistatus bodies)
+pub fn parse_ics(text: &str) -> Vec<CalendarEvent> {
+    // Unfold continuation lines (RFC 5545 §3.1)
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        let raw = raw.trim_end_matches('\r');
+        if let Some(rest) = raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        lines.push(raw.to_string());
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Vec<(String, String)>> = None;
+    for line in lines {
+        let line = line.trim_start();
+        if line == "BEGIN:VEVENT" {
+            current = Some(Vec::new());
+        } else if line == "END:VEVENT" {
+            if let Some(props) = current.take() {
+                let get = |name: &str| props.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+                let Some((start, all_day)) = get("DTSTART").and_then(|v| parse_ics_datetime(&v)) else { continue };
+                let end = get("DTEND").and_then(|v| parse_ics_datetime(&v)).map(|(e, _)| e)
+                    .or_else(|| all_day.then(|| start + Duration::days(1)));
+                events.push(CalendarEvent {
+                    uid: get("UID").unwrap_or_default(),
+                    summary: get("SUMMARY").map(|s| unescape_text(&s)).unwrap_or_default(),
+                    start,
+                    end,
+                    all_day,
+                    location: get("LOCATION").map(|s| unescape_text(&s)),
+                    description: get("DESCRIPTION").map(|s| unescape_text(&s)),
+                });
+            }
+        } else if let Some(props) = current.as_mut() {
+            if let Some((key, value)) = line.split_once(':') {
+                // Drop parameters such as `;TZID=...` or `;VALUE=DATE`
+                let name = key.split(';').next().unwrap_or(key).to_uppercase();
+                props.push((name, value.to_string()));
+            }
+        }
+    }
+    events
+}
+
+/// Parse user-supplied times: RFC 3339, `YYYY-MM-DD HH:MM` (local) or `YYYY-MM-DD`
+fn parse_user_datetime(value: &str) -> Option<(DateTime<Utc>, bool)> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some((dt.with_timezone(&Utc), false));
+    }
+    for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return Some((Local.from_local_datetime(&naive).earliest()?.with_timezone(&Utc), false));
+        }
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some((Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?.with_timezone(&Utc), true))
+}
+
+fn wrap_calendar(events: &str) -> String {
+    format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//rust_agency//calendar//EN\r\n{}END:VCALENDAR\r\n", events)
+}
+
+/// Tool for reading and creating calendar events
+pub struct CalendarTool {
+    source: CalendarSource,
+    client: Client,
+}
+
+impl CalendarTool {
+    pub fn new(source: CalendarSource) -> Self {
+        Self { source, client: Client::new() }
+    }
+
+    /// `AGENCY_CALDAV_URL` (with `AGENCY_CALDAV_USER`/`AGENCY_CALDAV_PASSWORD`) when set,
+    /// otherwise the file at `AGENCY_CALENDAR_ICS` (default `agency_calendar.ics`)
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let source = match var("AGENCY_CALDAV_URL") {
+            Some(url) => CalendarSource::CalDav { url, username: var("AGENCY_CALDAV_USER"), password: var("AGENCY_CALDAV_PASSWORD") },
+            None => CalendarSource::Ics(PathBuf::from(var("AGENCY_CALENDAR_ICS").unwrap_or_else(|| "agency_calendar.ics".to_string()))),
+        };
+        Self::new(source)
+    }
+
+    fn caldav_request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.source {
+            CalendarSource::CalDav { username: Some(user), password, .. } => request.basic_auth(user, password.clone()),
+            _ => request,
+        }
+    }
+
+    async fn fetch_events(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> anyhow::Result<Vec<CalendarEvent>> {
+        let events = match &self.source {
+            CalendarSource::Ics(path) => match tokio::fs::read_to_string(path).await {
+                Ok(text) => parse_ics(&text),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e.into()),
+            },
+            CalendarSource::CalDav { url, .. } => {
+                let body = format!(
+                    r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">
+    <c:time-range start="{}" end="{}"/>
+  </c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#,
+                    from.format("%Y%m%dT%H%M%SZ"), to.format("%Y%m%dT%H%M%SZ"));
+                let response = self.caldav_request(Method::from_bytes(b"REPORT")?, url)
+                    .header("Depth", "1")
+                    .header("Content-Type", "application/xml; charset=utf-8")
+                    .body(body)
+                    .send().await?
+                    .error_for_status()?;
+                let text = response.text().await?
+                    .replace("&#13;", "\r").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+                parse_ics(&text)
+            }
+        };
+        let mut events: Vec<CalendarEvent> = events.into_iter().filter(|e| e.overlaps(from, to)).collect();
+        events.sort_by_key(|e| e.start);
+        Ok(events)
+    }
+
+    async fn store_event(&self, event: &CalendarEvent) -> anyhow::Result<()> {
+        match &self.source {
+            CalendarSource::Ics(path) => {
+                let existing = match tokio::fs::read_to_string(path).await {
+                    Ok(text) => text,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(e) => return Err(e.into()),
+                };
+                let updated = match existing.rfind("END:VCALENDAR") {
+                    Some(pos) => format!("{}{}{}", &existing[..pos], event.to_ics(), &existing[pos..]),
+                    None => wrap_calendar(&event.to_ics()),
+                };
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, updated).await?;
+            }
+            CalendarSource::CalDav { url, .. } => {
+                let target = format!("{}/{}.ics", url.trim_end_matches('/'), event.uid);
+                self.caldav_request(Method::PUT, &target)
+                    .header("Content-Type", "text/calendar; charset=utf-8")
+                    .header("If-None-Match", "*")
+                    .body(wrap_calendar(&event.to_ics()))
+                    .send().await?
+                    .error_for_status()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn format_event(event: &CalendarEvent) -> String {
+        let start = event.start.with_timezone(&Local);
+        let when = if event.all_day {
+            format!("{} (all day)", start.format("%a %Y-%m-%d"))
+        } else {
+            match event.end {
+                Some(end) => format!("{} – {}", start.format("%a %Y-%m-%d %H:%M"), end.with_timezone(&Local).format("%H:%M")),
+                None => start.format("%a %Y-%m-%d %H:%M").to_string(),
+            }
+        };
+        match &event.location {
+            Some(location) => format!("{}: {} @ {}", when, event.summary, location),
+            None => format!("{}: {}", when, event.summary),
+        }
+    }
+}
+
+impl Default for CalendarTool {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarTool {
+    fn name(&self) -> String {
+        "calendar".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Read and create calendar events. Actions: 'list' (from, to; defaults to the next 7 days) \
+         and 'create' (summary, start, end or duration_minutes, location, description). Times are \
+         RFC 3339 or 'YYYY-MM-DD HH:MM' in local time; a bare date creates an all-day event.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "create"],
+                    "description": "The calendar operation to perform"
+                },
+                "from": { "type": "string", "description": "Start of the range to list (default now)" },
+                "to": { "type": "string", "description": "End of the range to list (default from + 7 days)" },
+                "summary": { "type": "string", "description": "Event title" },
+                "start": { "type": "string", "description": "Event start time" },
+                "end": { "type": "string", "description": "Event end time" },
+                "duration_minutes": { "type": "integer", "description": "Event length when 'end' is omitted (default 60)" },
+                "location": { "type": "string" },
+                "description": { "type": "string" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        match &self.source {
+            CalendarSource::Ics(path) => json!({ "status": "constrained", "backend": "ics", "file": path.display().to_string() }),
+            CalendarSource::CalDav { url, .. } => json!({ "status": "constrained", "backend": "caldav", "url": url }),
+        }
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network, ToolCapability::FilesystemWrite]
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let action = params["action"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: action".to_string()))?;
+        let time_param = |name: &str| -> AgentResult<Option<(DateTime<Utc>, bool)>> {
+            match params[name].as_str() {
+                Some(value) => parse_user_datetime(value).map(Some)
+                    .ok_or_else(|| AgentError::Validation(format!("Could not parse '{}' time: {}", name, value))),
+                None => Ok(None),
+            }
+        };
+
+        match action {
+            "list" => {
+                let from = time_param("from")?.map(|(t, _)| t).unwrap_or_else(Utc::now);
+                let to = time_param("to")?.map(|(t, all_day)| if all_day { t + Duration::days(1) } else { t })
+                    .unwrap_or(from + Duration::days(7));
+                match self.fetch_events(from, to).await {
+                    Ok(events) => {
+                        let lines: Vec<String> = events.iter().map(Self::format_event).collect();
+                        let summary = if events.is_empty() {
+                            "No events in range".to_string()
+                        } else {
+                            format!("{} event(s):\n{}", events.len(), lines.join("\n"))
+                        };
+                        Ok(ToolOutput::success(json!({ "events": events }), summary))
+                    }
+                    Err(e) => Ok(ToolOutput::failure(format!("Failed to read calendar: {}", e))),
+                }
+            }
+            "create" => {
+                let summary = params["summary"].as_str()
+                    .ok_or_else(|| AgentError::Validation("Missing required parameter: summary".to_string()))?;
+                let (start, all_day) = time_param("start")?
+                    .ok_or_else(|| AgentError::Validation("Missing required parameter: start".to_string()))?;
+                let end = match time_param("end")? {
+                    // An end date is inclusive; DTEND is not
+                    Some((end, true)) => Some(end + Duration::days(1)),
+                    Some((end, false)) => Some(end),
+                    None if all_day => None,
+                    None => {
+                        let minutes = params["duration_minutes"].as_i64().unwrap_or(60).clamp(0, MAX_DURATION_MINUTES);
+                        Some(start + Duration::minutes(minutes))
+                    }
+                };
+                if end.is_some_and(|end| end < start) {
+                    return Err(AgentError::Validation("Event end is before its start".to_string()));
+                }
+
+                let event = CalendarEvent {
+                    uid: format!("{}@rust_agency", uuid::Uuid::new_v4()),
+                    summary: summary.to_string(),
+                    start,
+                    end,
+                    all_day,
+                    location: params["location"].as_str().map(|s| s.to_string()),
+                    description: params["description"].as_str().map(|s| s.to_string()),
+                };
+                info!("Creating calendar event: {}", event.summary);
+                match self.store_event(&event).await {
+                    Ok(()) => Ok(ToolOutput::success(json!({ "event": event }), format!("Created {}", Self::format_event(&event)))),
+                    Err(e) => Ok(ToolOutput::failure(format!("Failed to create event: {}", e))),
+                }
+            }
+            other => Ok(ToolOutput::failure(format!("Unknown action: {}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ics_unfolds_and_unescapes() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\nDTSTART:20250301T090000Z\r\nDTEND:20250301T100000Z\r\n\
+                   SUMMARY:Standup\\, daily\r\nDESCRIPTION:long\r\n  line\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:2\r\n\
+                   DTSTART;VALUE=DATE:20250302\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let events = parse_ics(ics);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].summary, "Standup, daily");
+        assert_eq!(events[0].description.as_deref(), Some("long line"));
+        assert_eq!(events[0].end.unwrap() - events[0].start, Duration::hours(1));
+        assert!(events[1].all_day);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_local_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = CalendarTool::new(CalendarSource::Ics(dir.path().join("cal.ics")));
+
+        let out = tool.execute(json!({ "action": "create", "summary": "Dentist", "start": "2030-05-01T14:00:00Z", "duration_minutes": 30 })).await.unwrap();
+        assert!(out.success, "{}", out.summary);
+
+        let out = tool.execute(json!({ "action": "list", "from": "2030-05-01T00:00:00Z", "to": "2030-05-02T00:00:00Z" })).await.unwrap();
+        assert_eq!(out.data["events"].as_array().unwrap().len(), 1);
+        assert_eq!(out.data["events"][0]["summary"], json!("Dentist"));
+
+        let out = tool.execute(json!({ "action": "list", "from": "2030-06-01T00:00:00Z" })).await.unwrap();
+        assert!(out.data["events"].as_array().unwrap().is_empty());
+
+        let out = tool.execute(json!({ "action": "create", "summary": "Holiday", "start": "2030-07-04" })).await.unwrap();
+        assert!(out.success, "{}", out.summary);
+        let ics = std::fs::read_to_string(dir.path().join("cal.ics")).unwrap();
+        assert!(ics.contains("DTSTART;VALUE=DATE:20300704\r\n"), "{}", ics);
+        let events = parse_ics(&ics);
+        assert!(events.iter().any(|e| e.all_day && e.start.with_timezone(&Local).format("%Y-%m-%d").to_string() == "2030-07-04"));
+    }
+}
@@ -0,0 +1,436 @@
+//! Skill & Tool Catalogs
+//!
+//! Curated Markdown skills and dynamic tools published as a catalog: an `index.json` at the
+//! root of a git repository, or at a URL or path ending in `.json`, listing each item's files
+//! relative to it. Syncing reports what installing each item would change, file by file with
+//! a unified diff, and installs the chosen ones into `custom_tools` and `skills`. Files are
+//! capped at `MAX_FILE_BYTES` while downloading, and symlinks in a checkout are refused.
+//!
+//! Each item is signed by its publisher: a hex Ed25519 signature of `item_digest`. Only keys
+//! in `AGENCY_CATALOG_KEYS` (comma-separated hex) are trusted; unsigned items are refused
+//! unless `AGENCY_CATALOG_ALLOW_UNSIGNED=1`, and items with a bad signature always are.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use tracing::info;
+
+use super::dynamic::DynamicToolMetadata;
+use super::MarkdownSkill;
+use crate::orchestrator::sovereignty::SovereignIdentity;
+
+/// Largest accepted catalog file
+const MAX_FILE_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Skill,
+    Tool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogIndex {
+    #[serde(default)]
+    pub name: String,
+    pub items: Vec<CatalogEntry>,
+}
+
+/// An item as listed in `index.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub kind: ItemKind,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub version: String,
+    /// Paths relative to the index; installed under their file names
+    pub files: Vec<String>,
+    /// Hex Ed25519 signature of `item_digest`
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Hex key that made `signature`; any trusted key is tried when absent
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Trust {
+    Signed { public_key: String },
+    Unsigned,
+    Invalid { reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChange {
+    /// Where the file is installed
+    pub path: PathBuf,
+    pub status: FileStatus,
+    /// Unified diff against the installed file, when modified
+    pub diff: Option<String>,
+}
+
+/// A fetched item with what installing it would change
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogItem {
+    pub kind: ItemKind,
+    pub name: String,
+    pub description: String,
+    pub version: String,
+    pub trust: Trust,
+    pub changes: Vec<FileChange>,
+    /// Install path and content of each file
+    #[serde(skip)]
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl CatalogItem {
+    pub fn up_to_date(&self) -> bool {
+        self.changes.iter().all(|c| c.status == FileStatus::Unchanged)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogConfig {
+    pub tools_dir: PathBuf,
+    pub skills_dir: PathBuf,
+    /// Hex Ed25519 keys of trusted publishers
+    pub trusted_keys: Vec<String>,
+    pub allow_unsigned: bool,
+}
+
+impl CatalogConfig {
+    pub fn from_env() -> Self {
+        Self {
+            tools_dir: PathBuf::from("custom_tools"),
+            skills_dir: PathBuf::from("skills"),
+            trusted_keys: std::env::var("AGENCY_CATALOG_KEYS").unwrap_or_default()
+                .split(',')
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            allow_unsigned: std::env::var("AGENCY_CATALOG_ALLOW_UNSIGNED").unwrap_or_default() == "1",
+        }
+    }
+
+    fn may_install(&self, trust: &Trust) -> Result<()> {
+        match trust {
+            Trust::Signed { .. } => Ok(()),
+            Trust::Unsigned if self.allow_unsigned => Ok(()),
+            Trust::Unsigned => bail!("unsigned (set AGENCY_CATALOG_ALLOW_UNSIGNED=1 to allow)"),
+            Trust::Invalid { reason } => bail!("{}", reason),
+        }
+    }
+}
+
+/// What publishers sign: the item's kind and name, then each file's name and SHA-256, in index order
+pub fn item_digest(kind: ItemKind, name: &str, files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let kind = match kind {
+        ItemKind::Skill => "skill",
+        ItemKind::Tool => "tool",
+    };
+    let mut digest = format!("{}:{}\n", kind, name);
+    for (file_name, content) in files {
+        digest.push_str(&format!("{}\n{}\n", file_name, hex::encode(Sha256::digest(content))));
+    }
+    Sha256::digest(digest.as_bytes()).to_vec()
+}
+
+/// Where a catalog's files are read from
+enum Source {
+    Dir(PathBuf),
+    Url(reqwest::Url),
+}
+
+impl Source {
+    async fn read(&self, relative: &str) -> Result<Vec<u8>> {
+        let relative = safe_relative(relative)?;
+        match self {
+            Source::Dir(dir) => {
+                // A checkout may contain symlinks pointing anywhere on this machine
+                let mut path = dir.clone();
+                for part in relative.components() {
+                    path.push(part);
+                    let meta = tokio::fs::symlink_metadata(&path).await
+                        .with_context(|| format!("Failed to read {:?}", relative))?;
+                    if meta.file_type().is_symlink() {
+                        bail!("{:?} is a symlink", relative);
+                    }
+                    if meta.is_file() && meta.len() > MAX_FILE_BYTES as u64 {
+                        bail!("{:?} is larger than {} bytes", relative, MAX_FILE_BYTES);
+                    }
+                }
+                tokio::fs::read(&path).await.with_context(|| format!("Failed to read {:?}", relative))
+            }
+            Source::Url(index) => fetch_capped(index.join(&relative.to_string_lossy())?).await,
+        }
+    }
+}
+
+/// Download `url`, giving up as soon as it exceeds `MAX_FILE_BYTES`
+async fn fetch_capped(url: reqwest::Url) -> Result<Vec<u8>> {
+    let mut response = reqwest::get(url.clone()).await?.error_for_status()?;
+    if response.content_length().is_some_and(|len| len > MAX_FILE_BYTES as u64) {
+        bail!("{} is larger than {} bytes", url, MAX_FILE_BYTES);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.with_context(|| format!("Failed to fetch {}", url))? {
+        if bytes.len() + chunk.len() > MAX_FILE_BYTES {
+            bail!("{} is larger than {} bytes", url, MAX_FILE_BYTES);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// `path` if it stays inside the catalog
+fn safe_relative(path: &str) -> Result<&Path> {
+    let path = Path::new(path);
+    if path.as_os_str().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!("Catalog path '{}' leaves the catalog", path.display());
+    }
+    Ok(path)
+}
+
+/// Fetch the catalog at `source` (a git URL, or a URL or path to an `index.json`) and compare
+/// each item with what is installed
+pub async fn fetch_catalog(source: &str, config: &CatalogConfig) -> Result<Vec<CatalogItem>> {
+    // A git catalog is cloned into a directory kept until its items are read
+    let checkout = if source.ends_with(".json") { None } else { Some(tempfile::tempdir()?) };
+    let (root, index_bytes) = match checkout {
+        Some(ref dir) => {
+            let (url, path) = (source.to_string(), dir.path().to_path_buf());
+            tokio::task::spawn_blocking(move || git2::Repository::clone(&url, &path)).await?
+                .with_context(|| format!("Failed to clone {}", source))?;
+            let root = Source::Dir(dir.path().to_path_buf());
+            let index_bytes = root.read("index.json").await?;
+            (root, index_bytes)
+        }
+        None => match reqwest::Url::parse(source) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                let index_bytes = fetch_capped(url.clone()).await?;
+                (Source::Url(url), index_bytes)
+            }
+            _ => {
+                let path = Path::new(source);
+                let index_bytes = tokio::fs::read(path).await.with_context(|| format!("Failed to read {}", source))?;
+                (Source::Dir(path.parent().map(Path::to_path_buf).unwrap_or_default()), index_bytes)
+            }
+        },
+    };
+    let index: CatalogIndex = serde_json::from_slice(&index_bytes).context("Invalid catalog index")?;
+    info!("Catalog '{}': {} items", index.name, index.items.len());
+
+    let mut items = Vec::new();
+    for entry in index.items {
+        items.push(load_item(&root, entry, config).await?);
+    }
+    Ok(items)
+}
+
+async fn load_item(root: &Source, entry: CatalogEntry, config: &CatalogConfig) -> Result<CatalogItem> {
+    let mut named = Vec::new();
+    for relative in &entry.files {
+        let file_name = Path::new(relative).file_name().and_then(|n| n.to_str())
+            .with_context(|| format!("Catalog path '{}' has no file name", relative))?
+            .to_string();
+        named.push((file_name, root.read(relative).await?));
+    }
+    validate_item(&entry, &named).with_context(|| format!("Catalog item '{}'", entry.name))?;
+
+    let trust = verify(&entry, &named, &config.trusted_keys);
+    let dir = match entry.kind {
+        ItemKind::Skill => &config.skills_dir,
+        ItemKind::Tool => &config.tools_dir,
+    };
+    let mut changes = Vec::new();
+    let mut files = Vec::new();
+    for (file_name, content) in named {
+        let path = dir.join(&file_name);
+        let (status, diff) = match tokio::fs::read(&path).await {
+            Ok(installed) if installed == content => (FileStatus::Unchanged, None),
+            Ok(installed) => (FileStatus::Modified, unified_diff(&installed, &content, &file_name)),
+            Err(_) => (FileStatus::Added, None),
+        };
+        changes.push(FileChange { path: path.clone(), status, diff });
+        files.push((path, content));
+    }
+    Ok(CatalogItem {
+        kind: entry.kind,
+        name: entry.name,
+        description: entry.description,
+        version: entry.version,
+        trust,
+        changes,
+        files,
+    })
+}
+
+/// A tool is its `<name>.json` definition plus the script it names; a skill is one Markdown file
+fn validate_item(entry: &CatalogEntry, files: &[(String, Vec<u8>)]) -> Result<()> {
+    match entry.kind {
+        ItemKind::Skill => {
+            let [(file_name, content)] = files else { bail!("a skill is exactly one .md file") };
+            if !file_name.ends_with(".md") {
+                bail!("a skill is exactly one .md file");
+            }
+            MarkdownSkill::parse(std::str::from_utf8(content)?, Path::new(file_name))?;
+        }
+        ItemKind::Tool => {
+            let definition = format!("{}.json", entry.name);
+            let (_, content) = files.iter().find(|(n, _)| *n == definition)
+                .with_context(|| format!("missing {}", definition))?;
+            let metadata: DynamicToolMetadata = serde_json::from_slice(content).context("invalid tool definition")?;
+            if metadata.name != entry.name {
+                bail!("definition names tool '{}'", metadata.name);
+            }
+            if !files.iter().any(|(n, _)| *n == metadata.script_path) {
+                bail!("script '{}' is not among its files", metadata.script_path);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn verify(entry: &CatalogEntry, files: &[(String, Vec<u8>)], trusted_keys: &[String]) -> Trust {
+    let Some(ref signature) = entry.signature else { return Trust::Unsigned };
+    let Ok(signature) = hex::decode(signature) else {
+        return Trust::Invalid { reason: "signature is not hex".to_string() };
+    };
+    let candidates: Vec<String> = match entry.public_key {
+        Some(ref key) if trusted_keys.contains(&key.to_lowercase()) => vec![key.to_lowercase()],
+        Some(ref key) => return Trust::Invalid { reason: format!("signed by untrusted key {}", key) },
+        None => trusted_keys.to_vec(),
+    };
+    let digest = item_digest(entry.kind, &entry.name, files);
+    candidates.into_iter()
+        .find(|key| SovereignIdentity::verify(key, &digest, &signature).unwrap_or(false))
+        .map(|public_key| Trust::Signed { public_key })
+        .unwrap_or_else(|| Trust::Invalid { reason: "signature does not match a trusted key".to_string() })
+}
+
+fn unified_diff(old: &[u8], new: &[u8], file_name: &str) -> Option<String> {
+    let patch = git2::Patch::from_buffers(old, Some(Path::new(file_name)), new, Some(Path::new(file_name)), None).ok()?;
+    let buf = patch.to_buf().ok()?;
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Install the fetched items named in `names`; returns the names installed
+pub async fn install_catalog_items(items: &[CatalogItem], names: &[String], config: &CatalogConfig) -> Result<Vec<String>> {
+    let chosen: Vec<&CatalogItem> = items.iter().filter(|i| names.contains(&i.name)).collect();
+    if let Some(missing) = names.iter().find(|n| !chosen.iter().any(|i| &i.name == *n)) {
+        bail!("'{}' is not in the catalog", missing);
+    }
+    // Nothing is written unless every chosen item may be
+    for item in &chosen {
+        config.may_install(&item.trust).with_context(|| format!("Refusing '{}'", item.name))?;
+    }
+    for item in &chosen {
+        for (path, content) in &item.files {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, content).await.with_context(|| format!("Failed to write {:?}", path))?;
+        }
+        info!("Installed {:?} '{}' {}", item.kind, item.name, item.version);
+    }
+    Ok(chosen.iter().map(|i| i.name.clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_fetch_verify_and_install() {
+        let catalog = tempdir().unwrap();
+        let target = tempdir().unwrap();
+        let definition = serde_json::json!({
+            "name": "word_count", "description": "Count words", "parameters": {},
+            "language": "python", "script_path": "word_count.py",
+        }).to_string();
+        let script = "print(len(input().split()))\n";
+        std::fs::create_dir_all(catalog.path().join("tools")).unwrap();
+        std::fs::write(catalog.path().join("tools/word_count.json"), &definition).unwrap();
+        std::fs::write(catalog.path().join("tools/word_count.py"), script).unwrap();
+        std::fs::write(catalog.path().join("summarize.md"), "---\nname: Summarize\ndescription: Short summaries\n---\nBe brief.").unwrap();
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let files = vec![
+            ("word_count.json".to_string(), definition.into_bytes()),
+            ("word_count.py".to_string(), script.as_bytes().to_vec()),
+        ];
+        let signature = hex::encode(key.sign(&item_digest(ItemKind::Tool, "word_count", &files)).to_bytes());
+        let index = serde_json::json!({ "name": "test", "items": [
+            { "kind": "tool", "name": "word_count", "files": ["tools/word_count.json", "tools/word_count.py"], "signature": signature },
+            { "kind": "skill", "name": "summarize", "files": ["summarize.md"] },
+        ]});
+        let index_path = catalog.path().join("index.json");
+        std::fs::write(&index_path, index.to_string()).unwrap();
+
+        let config = CatalogConfig {
+            tools_dir: target.path().join("custom_tools"),
+            skills_dir: target.path().join("skills"),
+            trusted_keys: vec![hex::encode(key.verifying_key().as_bytes())],
+            allow_unsigned: false,
+        };
+        let items = fetch_catalog(index_path.to_str().unwrap(), &config).await.unwrap();
+        assert!(matches!(items[0].trust, Trust::Signed { .. }));
+        assert_eq!(items[1].trust, Trust::Unsigned);
+        assert!(items.iter().all(|i| i.changes.iter().all(|c| c.status == FileStatus::Added)));
+
+        // The unsigned skill blocks the whole install
+        let both = vec!["word_count".to_string(), "summarize".to_string()];
+        assert!(install_catalog_items(&items, &both, &config).await.is_err());
+        assert!(!config.tools_dir.exists());
+
+        install_catalog_items(&items, &both[..1], &config).await.unwrap();
+        assert!(config.tools_dir.join("word_count.py").exists());
+        std::fs::write(config.tools_dir.join("word_count.py"), "print(0)\n").unwrap();
+        let items = fetch_catalog(index_path.to_str().unwrap(), &config).await.unwrap();
+        let script_change = &items[0].changes[1];
+        assert_eq!(script_change.status, FileStatus::Modified);
+        assert!(script_change.diff.as_deref().unwrap().contains("+print(len(input().split()))"));
+
+        // A tampered file no longer matches its signature
+        std::fs::write(catalog.path().join("tools/word_count.py"), "import os\n").unwrap();
+        let items = fetch_catalog(index_path.to_str().unwrap(), &config).await.unwrap();
+        assert!(matches!(items[0].trust, Trust::Invalid { .. }));
+    }
+
+    #[test]
+    fn test_paths_stay_inside_the_catalog() {
+        assert!(safe_relative("tools/a.json").is_ok());
+        assert!(safe_relative("../secrets.json").is_err());
+        assert!(safe_relative("/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinks_are_refused() {
+        let catalog = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "token").unwrap();
+        std::fs::write(catalog.path().join("plain.md"), "# ok").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), catalog.path().join("link.md")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), catalog.path().join("skills")).unwrap();
+
+        let root = Source::Dir(catalog.path().to_path_buf());
+        assert_eq!(root.read("plain.md").await.unwrap(), b"# ok");
+        assert!(root.read("link.md").await.is_err());
+        assert!(root.read("skills/secret.txt").await.is_err());
+    }
+}
@@ -12,8 +12,9 @@ use tokio::time::{timeout, Duration};
 use tracing::{debug, warn, info};
 
 use crate::agent::{AgentResult, AgentError};
-use crate::utils::sandbox::TOOL_SANDBOX_POLICY;
-use super::{Tool, ToolOutput};
+use crate::utils::container::ContainerConfig;
+use crate::utils::sandbox::SandboxProfile;
+use super::{Tool, ToolOutput, ToolCapability};
 
 /// Sandboxed code execution tool
 pub struct CodeExecTool {
@@ -21,6 +22,12 @@ pub struct CodeExecTool {
     timeout_secs: u64,
     /// Maximum output length
     max_output_len: usize,
+    /// Run inside a container instead of on the host
+    container: Option<ContainerConfig>,
+    /// Loosest confinement a call may run under
+    sandbox_profile: SandboxProfile,
+    /// Directory code runs in and may write to; `None` uses the agency workspace
+    workspace: Option<std::path::PathBuf>,
 }
 
 impl CodeExecTool {
@@ -28,20 +35,49 @@ impl CodeExecTool {
         Self {
             timeout_secs: 30,
             max_output_len: 10000,
+            container: ContainerConfig::from_env(),
+            sandbox_profile: SandboxProfile::default(),
+            workspace: None,
         }
     }
 
+    pub fn with_workspace(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.workspace = Some(dir.into());
+        self
+    }
+
+    /// Never the agency's own directory, so executed code cannot rewrite its config or tools
+    fn workspace_dir(&self) -> std::path::PathBuf {
+        let dir = self.workspace.clone().unwrap_or_else(crate::utils::workspace_dir);
+        dir.canonicalize().unwrap_or(dir)
+    }
+
+    pub fn with_sandbox_profile(mut self, profile: SandboxProfile) -> Self {
+        self.sandbox_profile = profile;
+        self
+    }
+
+    pub fn with_container(mut self, config: ContainerConfig) -> Self {
+        self.container = Some(config);
+        self
+    }
+
+    async fn execute_in_container(&self, container: &ContainerConfig, language: &str, code: &str) -> anyhow::Result<(String, String, i32)> {
+        let (stdout, stderr, code) = container.run(language, code, Duration::from_secs(self.timeout_secs)).await?;
+        Ok((self.truncate(&stdout), self.truncate(&stderr), code))
+    }
+
     #[allow(dead_code)]
     pub fn with_timeout(mut self, secs: u64) -> Self {
         self.timeout_secs = secs;
         self
     }
 
-    async fn execute_python(&self, code: &str) -> anyhow::Result<(String, String, i32)> {
-        self.run_command("python3", &["-c", code]).await
+    async fn execute_python(&self, code: &str, profile: SandboxProfile) -> anyhow::Result<(String, String, i32)> {
+        self.run_command("python3", &["-c", code], profile).await
     }
 
-    async fn execute_rust(&self, code: &str) -> anyhow::Result<(String, String, i32)> {
+    async fn execute_rust(&self, code: &str, profile: SandboxProfile) -> anyhow::Result<(String, String, i32)> {
         let temp_dir = std::env::temp_dir();
         let file_path = temp_dir.join(format!("agent_code_{}.rs", uuid::Uuid::new_v4()));
         let binary_path = temp_dir.join(format!("agent_code_{}", uuid::Uuid::new_v4()));
@@ -58,7 +94,7 @@ impl CodeExecTool {
                 file_path_str,
                 "-o",
                 binary_path_str,
-            ])
+            ], profile)
             .await?;
 
         if code_result != 0 {
@@ -67,7 +103,7 @@ impl CodeExecTool {
         }
 
         // Run the compiled binary
-        let result = self.run_command(binary_path_str, &[]).await;
+        let result = self.run_command(binary_path_str, &[], profile).await;
 
         // Clean up
         let _ = tokio::fs::remove_file(&file_path).await;
@@ -76,23 +112,22 @@ impl CodeExecTool {
         result
     }
 
-    async fn execute_javascript(&self, code: &str) -> anyhow::Result<(String, String, i32)> {
-        self.run_command("node", &["-e", code]).await
+    async fn execute_javascript(&self, code: &str, profile: SandboxProfile) -> anyhow::Result<(String, String, i32)> {
+        self.run_command("node", &["-e", code], profile).await
     }
 
-    async fn execute_shell(&self, code: &str) -> anyhow::Result<(String, String, i32)> {
-        self.run_command("sh", &["-c", code]).await
+    async fn execute_shell(&self, code: &str, profile: SandboxProfile) -> anyhow::Result<(String, String, i32)> {
+        self.run_command("sh", &["-c", code], profile).await
     }
 
-    async fn run_command(&self, program: &str, args: &[&str]) -> anyhow::Result<(String, String, i32)> {
-        debug!("Running sandboxed command: {} {:?}", program, args);
+    async fn run_command(&self, program: &str, args: &[&str], profile: SandboxProfile) -> anyhow::Result<(String, String, i32)> {
+        debug!("Running sandboxed command ({}): {} {:?}", profile.key(), program, args);
+        let workspace_dir = self.workspace_dir();
 
         #[cfg(target_os = "macos")]
         {
-            let workspace_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-            
             let mut sb_args = vec![
-                "-p".to_string(), TOOL_SANDBOX_POLICY.to_string(),
+                "-p".to_string(), profile.seatbelt_policy(),
                 "-D".to_string(), format!("WORKSPACE_DIR={}", workspace_dir.to_string_lossy()),
                 "--".to_string(),
                 program.to_string()
@@ -105,6 +140,8 @@ impl CodeExecTool {
             let result = timeout(
                 Duration::from_secs(self.timeout_secs),
                 Command::new("/usr/bin/sandbox-exec")
+                    .kill_on_drop(true)
+                    .current_dir(&workspace_dir)
                     .args(&sb_args)
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
@@ -126,16 +163,16 @@ impl CodeExecTool {
 
         #[cfg(not(target_os = "macos"))]
         {
-            warn!("Mandatory Seatbelt sandboxing only available on macOS. Running unconfined.");
-            let result = timeout(
-                Duration::from_secs(self.timeout_secs),
-                Command::new(program)
-                    .args(args)
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .stdin(Stdio::null())
-                    .output()
-            ).await;
+            let mut command = Command::new(program);
+            command
+                .kill_on_drop(true)
+                .current_dir(&workspace_dir)
+                .args(args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .stdin(Stdio::null());
+            profile.confine(&mut command, &workspace_dir);
+            let result = timeout(Duration::from_secs(self.timeout_secs), command.output()).await;
 
             match result {
                 Ok(Ok(output)) => {
@@ -173,7 +210,7 @@ impl Tool for CodeExecTool {
 
     fn description(&self) -> String {
         "Execute code in a MANDATORY sandboxed environment. Supports Python, JavaScript, Rust, and shell commands.\n 
-         Use this to run calculations, test code snippets, or perform automated tasks. Access restricted to project directory and /tmp.".to_string()
+         Use this to run calculations, test code snippets, or perform automated tasks. Code runs in, and can only write to, the agency workspace directory and /tmp.".to_string()
     }
 
     fn parameters(&self) -> Value {
@@ -188,6 +225,11 @@ impl Tool for CodeExecTool {
                     "type": "string",
                     "description": "Programming language",
                     "enum": ["python", "javascript", "rust", "shell"]
+                },
+                "sandbox_profile": {
+                    "type": "string",
+                    "description": "Optional stricter confinement for this call (cannot loosen the configured profile)",
+                    "enum": ["no-network", "workspace-only", "full"]
                 }
             },
             "required": ["code", "language"]
@@ -195,9 +237,14 @@ impl Tool for CodeExecTool {
     }
 
     fn work_scope(&self) -> Value {
+        let environment = match &self.container {
+            Some(c) => format!("{} container (network: {}, memory: {} MB)", c.runtime.binary(), if c.network { "on" } else { "off" }, c.memory_mb),
+            None => format!("Host sandbox ({})", self.sandbox_profile.key()),
+        };
         json!({
             "status": "constrained",
-            "environment": "MANDATORY macOS Seatbelt Sandbox",
+            "environment": environment,
+            "sandbox_profile": self.sandbox_profile.key(),
             "safety": "ULTRA-HIGH (Kernel-enforced isolation)",
             "resource_limits": {
                 "timeout": format!("{}s", self.timeout_secs),
@@ -210,6 +257,15 @@ impl Tool for CodeExecTool {
         true // Still require confirmation for auditing
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        // The process has its own limit; leave room to collect its output
+        Some(Duration::from_secs(self.timeout_secs + 5))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let code = params["code"]
             .as_str()
@@ -221,12 +277,25 @@ impl Tool for CodeExecTool {
 
         info!("MANDATORY SANDBOX EXECUTION: {} code ({} chars)", language, code.len());
 
-        let result = match language {
-            "python" => self.execute_python(code).await,
-            "javascript" => self.execute_javascript(code).await,
-            "rust" => self.execute_rust(code).await,
-            "shell" => self.execute_shell(code).await,
-            _ => return Ok(ToolOutput::failure(format!("Unsupported language: {}", language))),
+        if !["python", "javascript", "rust", "shell"].contains(&language) {
+            return Ok(ToolOutput::failure(format!("Unsupported language: {}", language)));
+        }
+
+        let profile = match self.sandbox_profile.narrow(params["sandbox_profile"].as_str()) {
+            Ok(profile) => profile,
+            Err(e) => return Ok(ToolOutput::failure(e)),
+        };
+
+        let result = match (&self.container, language) {
+            (Some(container), _) => {
+                let mut container = container.clone();
+                container.network &= profile.allows_network();
+                self.execute_in_container(&container, language, code).await
+            }
+            (None, "python") => self.execute_python(code, profile).await,
+            (None, "javascript") => self.execute_javascript(code, profile).await,
+            (None, "rust") => self.execute_rust(code, profile).await,
+            (None, _) => self.execute_shell(code, profile).await,
         };
 
         match result {
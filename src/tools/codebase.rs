@@ -9,7 +9,7 @@ use std::path::{Path, PathBuf};
 use tokio::fs;
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCachePolicy};
 
 /// Tool for exploring the agency's own codebase
 pub struct CodebaseTool {
@@ -88,6 +88,11 @@ impl Tool for CodebaseTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        // Files may change between turns
+        ToolCachePolicy::default().with_ttl(std::time::Duration::from_secs(30))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("list_files");
 
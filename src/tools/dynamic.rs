@@ -14,7 +14,8 @@ use tokio::time::{timeout, Duration};
 use tracing::{debug, warn};
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput, ToolRegistry};
+use crate::runtime::wasi::{run_wasi, WasiLimits};
+use super::{Tool, ToolOutput, ToolCapability, ToolRegistry};
 
 /// Metadata for a dynamic tool
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,8 +23,14 @@ pub struct DynamicToolMetadata {
     pub name: String,
     pub description: String,
     pub parameters: Value,
-    pub language: String, // "python", "shell", "node"
+    pub language: String, // "python", "shell", "node", "rust", "wasm"
     pub script_path: String,
+    /// WASM only: fuel budget, at most `AGENCY_WASM_FUEL`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    /// WASM only: linear memory cap in MB, at most `AGENCY_WASM_MAX_MEMORY_MB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<usize>,
 }
 
 /// A tool that executes an external script
@@ -46,6 +53,65 @@ impl DynamicTool {
         let base_path = path.parent().map(|p| p.to_path_buf()).unwrap_or_else(|| PathBuf::from("."));
         Ok(Self { metadata, base_path })
     }
+
+    /// Run a WASI module under wasmtime. Rust sources are compiled to `wasm32-wasip1` first
+    /// (and recompiled when the source is newer than the module).
+    async fn execute_wasm(&self, script_abs_path: &Path, params_json: String) -> AgentResult<ToolOutput> {
+        let module_path = match script_abs_path.extension().and_then(|e| e.to_str()) {
+            Some("wasm") | Some("wat") => script_abs_path.to_path_buf(),
+            Some("rs") => {
+                let wasm_path = script_abs_path.with_extension("wasm");
+                if is_stale(script_abs_path, &wasm_path) {
+                    let output = Command::new("rustc")
+                        .kill_on_drop(true)
+                        .args(["--target", "wasm32-wasip1", "-O", "-o"])
+                        .arg(&wasm_path)
+                        .arg(script_abs_path)
+                        .output()
+                        .await
+                        .map_err(|e| AgentError::Tool(format!("Failed to spawn rustc: {}", e)))?;
+                    if !output.status.success() {
+                        return Ok(ToolOutput::failure(format!(
+                            "Failed to compile dynamic WASM tool:\n{}",
+                            String::from_utf8_lossy(&output.stderr)
+                        )));
+                    }
+                }
+                wasm_path
+            }
+            _ => return Ok(ToolOutput::failure(format!("WASM tools need a .rs, .wasm or .wat script, got {:?}", self.metadata.script_path))),
+        };
+
+        let limits = WasiLimits::from_env().narrowed(self.metadata.fuel, self.metadata.max_memory_mb);
+
+        debug!("Executing WASM tool {} ({:?})", self.metadata.name, module_path);
+        let args = vec![self.metadata.name.clone(), params_json.clone()];
+        let run = tokio::task::spawn_blocking(move || run_wasi(&module_path, &args, params_json.as_bytes(), limits))
+            .await
+            .map_err(|e| AgentError::Execution(e.to_string()))?;
+
+        match run {
+            Ok(out) if out.exit_code == 0 => Ok(ToolOutput::success(
+                json!({ "stdout": out.stdout, "stderr": out.stderr, "fuel_consumed": out.fuel_consumed }),
+                out.stdout
+            )),
+            Ok(out) => Ok(ToolOutput {
+                success: false,
+                data: json!({ "stdout": out.stdout, "stderr": out.stderr, "exit_code": out.exit_code }),
+                summary: format!("Tool failed with exit code {}.\nError: {}", out.exit_code, out.stderr),
+                error: Some(out.stderr),
+            }),
+            Err(e) => Ok(ToolOutput::failure(format!("WASM execution failed: {:#}", e))),
+        }
+    }
+}
+
+fn is_stale(source: &Path, artifact: &Path) -> bool {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (modified(source), modified(artifact)) {
+        (Some(src), Some(art)) => src > art,
+        _ => true,
+    }
 }
 
 #[async_trait]
@@ -69,6 +135,17 @@ impl Tool for DynamicTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        // WASI modules get no filesystem, network or process access, but a Rust source is
+        // first compiled by a host `rustc` that can read local files and writes the module
+        let precompiled = matches!(Path::new(&self.metadata.script_path).extension().and_then(|e| e.to_str()), Some("wasm") | Some("wat"));
+        if self.metadata.language == "wasm" && precompiled {
+            vec![ToolCapability::ReadOnly]
+        } else {
+            vec![ToolCapability::Shell]
+        }
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let script_abs_path = self.base_path.join(&self.metadata.script_path);
         
@@ -77,6 +154,10 @@ impl Tool for DynamicTool {
         }
 
         let params_json = serde_json::to_string(&params)?;
+
+        if self.metadata.language == "wasm" {
+            return self.execute_wasm(&script_abs_path, params_json).await;
+        }
         
         let script_str = script_abs_path.to_str().ok_or_else(|| AgentError::Validation("Invalid script path".to_string()))?;
 
@@ -90,6 +171,7 @@ impl Tool for DynamicTool {
                 let binary_str = binary_path.to_str().ok_or_else(|| AgentError::Validation("Invalid binary path".to_string()))?;
                 
                 let compile_status = Command::new("rustc")
+                    .kill_on_drop(true)
                     .arg(script_str)
                     .arg("-o")
                     .arg(binary_str)
@@ -122,6 +204,7 @@ impl Tool for DynamicTool {
         let result = timeout(
             Duration::from_secs(60),
             Command::new(&cmd)
+                .kill_on_drop(true)
                 .args(&args)
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
@@ -180,7 +263,8 @@ impl Tool for ForgeTool {
         "Forge a new specialized tool by providing metadata and a script.\n
          The new tool will be permanently available to the agency and CAN BE USED IMMEDIATELY in the next step.\n 
          BY DEFAULT, tools should be forged in 'rust' unless specifically requested otherwise by the human or necessitated by complex logic.\n 
-         Use this when you need a specialized functionality that doesn't exist yet (e.g. specialized file parsing, data transformation, or API interaction).".to_string()
+         Use this when you need a specialized functionality that doesn't exist yet (e.g. specialized file parsing, data transformation, or API interaction).\n
+         Use 'wasm' for pure computation: the Rust code runs as an isolated WASI module with no filesystem or network access, under fuel and memory limits.".to_string()
     }
 
     fn parameters(&self) -> Value {
@@ -190,8 +274,10 @@ impl Tool for ForgeTool {
                 "name": { "type": "string", "description": "Unique name for the tool (snake_case)" },
                 "description": { "type": "string", "description": "What the tool does" },
                 "parameters": { "type": "object", "description": "JSON schema for tool parameters" },
-                "language": { "type": "string", "enum": ["python", "shell", "node", "rust"], "default": "rust" },
-                "code": { "type": "string", "description": "The actual script code" }
+                "language": { "type": "string", "enum": ["python", "shell", "node", "rust", "wasm"], "default": "rust" },
+                "code": { "type": "string", "description": "The actual script code. For 'wasm', a Rust program compiled to WASI; it reads the JSON params from stdin (or argv[1]) and prints its result" },
+                "fuel": { "type": "integer", "description": "WASM only: instruction budget" },
+                "max_memory_mb": { "type": "integer", "description": "WASM only: memory limit in MB" }
             },
             "required": ["name", "description", "parameters", "language", "code"]
         })
@@ -206,6 +292,11 @@ impl Tool for ForgeTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        // Forged scripts are registered as runnable tools straight away
+        vec![ToolCapability::FilesystemWrite, ToolCapability::Shell]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let name = params["name"].as_str().ok_or_else(|| AgentError::Validation("Missing name".to_string()))?;
         let description = params["description"].as_str().ok_or_else(|| AgentError::Validation("Missing description".to_string()))?;
@@ -217,6 +308,7 @@ impl Tool for ForgeTool {
             "node" => ("js", true),
             "shell" => ("sh", false), // Shell is higher risk
             "rust" => ("rs", true),
+            "wasm" => ("rs", true), // Compiled to wasm32-wasip1 and run isolated
             _ => ("script", false),
         };
 
@@ -224,6 +316,17 @@ impl Tool for ForgeTool {
             warn!("Forging high-risk tool: {} in {}", name, language);
         }
 
+        // Masking would break the script, so hardcoded credentials are always refused
+        let scanner = &crate::safety::SECRET_SCANNER;
+        let findings = scanner.scan(code);
+        if !findings.is_empty() {
+            scanner.report(&format!("code for forged tool '{}'", name), &format!("SECRET-forge-{}", name), &findings);
+            return Ok(ToolOutput::failure(format!(
+                "Refusing to forge '{}': the code contains {}. Read credentials from environment variables instead.",
+                name, crate::safety::secrets::describe(&findings)
+            )));
+        }
+
         let script_filename = format!("{}.{}", name, ext);
         let metadata_filename = format!("{}.json", name);
         
@@ -238,13 +341,17 @@ impl Tool for ForgeTool {
         // Write script
         std::fs::write(&script_path, code)?;
         
-        // Write metadata
+        // Write metadata; WASM limits can only be lowered below the configured ones
+        let wasm_limits = WasiLimits::from_env();
         let metadata = DynamicToolMetadata {
             name: name.to_string(),
             description: description.to_string(),
             parameters: params["parameters"].clone(),
             language: language.to_string(),
             script_path: script_filename,
+            fuel: params["fuel"].as_u64().map(|fuel| fuel.min(wasm_limits.fuel)),
+            max_memory_mb: params["max_memory_mb"].as_u64()
+                .map(|mb| (mb as usize).min(wasm_limits.max_memory_bytes / (1024 * 1024))),
         };
         
         std::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
@@ -259,6 +366,16 @@ impl Tool for ForgeTool {
         ))
     }
 
+    fn dry_run(&self, params: &Value) -> ToolOutput {
+        let name = params["name"].as_str().unwrap_or("?");
+        let language = params["language"].as_str().unwrap_or("?");
+        let lines = params["code"].as_str().map(|c| c.lines().count()).unwrap_or(0);
+        ToolOutput::success(
+            json!({ "dry_run": true, "tool": name, "language": language, "directory": self.custom_tools_dir }),
+            format!("[DRY RUN] Would forge {} tool '{}' ({} lines) into {:?} and register it", language, name, lines, self.custom_tools_dir)
+        )
+    }
+
     fn requires_confirmation(&self) -> bool {
         true
     }
@@ -294,4 +411,24 @@ mod tests {
         let tool_names = registry.tool_names().await;
         assert!(tool_names.contains(&"test_tool".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_wasm_tool_runs_under_fuel_limit() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        std::fs::write(temp_dir.path().join("spin.wat"), r#"(module (memory (export "memory") 1) (func (export "_start") (loop (br 0))))"#).unwrap();
+        let tool = DynamicTool::new(DynamicToolMetadata {
+            name: "spin".to_string(),
+            description: "Never returns".to_string(),
+            parameters: json!({"type": "object"}),
+            language: "wasm".to_string(),
+            script_path: "spin.wat".to_string(),
+            fuel: Some(10_000),
+            max_memory_mb: None,
+        }, temp_dir.path().to_path_buf());
+
+        assert_eq!(tool.capabilities(), vec![ToolCapability::ReadOnly]);
+        let res = tool.execute(json!({})).await.expect("Tool execution failed");
+        assert!(!res.success);
+        assert!(res.summary.contains("fuel"));
+    }
 }
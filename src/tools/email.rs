@@ -0,0 +1,355 @@
+//! Email Tool
+//!
+//! Mailbox access over IMAP (list, search, read) and composition over SMTP (draft, send) for
+//! assistant-style workflows such as "summarize unread emails". Sending is consequential, so
+//! `send` calls require human confirmation through the SafetyGuard approval flow.
+
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
+
+/// Message bodies longer than this are truncated when read
+const MAX_BODY_CHARS: usize = 8_000;
+
+/// IMAP/SMTP account settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    pub imap_host: String,
+    #[serde(default = "default_imap_port")]
+    pub imap_port: u16,
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    pub username: String,
+    #[serde(skip_serializing)]
+    pub password: String,
+    /// Sender address (defaults to `username`)
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+fn default_imap_port() -> u16 {
+    993
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+impl EmailConfig {
+    /// Read `AGENCY_IMAP_HOST`, `AGENCY_SMTP_HOST`, `AGENCY_EMAIL_USER` and `AGENCY_EMAIL_PASSWORD`
+    /// (plus optional `AGENCY_IMAP_PORT`, `AGENCY_SMTP_PORT`, `AGENCY_EMAIL_FROM`).
+    /// Returns `None` when the account is not configured.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Some(Self {
+            imap_host: var("AGENCY_IMAP_HOST")?,
+            imap_port: var("AGENCY_IMAP_PORT").and_then(|p| p.parse().ok()).unwrap_or_else(default_imap_port),
+            smtp_host: var("AGENCY_SMTP_HOST")?,
+            smtp_port: var("AGENCY_SMTP_PORT").and_then(|p| p.parse().ok()).unwrap_or_else(default_smtp_port),
+            username: var("AGENCY_EMAIL_USER")?,
+            password: var("AGENCY_EMAIL_PASSWORD")?,
+            from: var("AGENCY_EMAIL_FROM"),
+        })
+    }
+}
+
+/// Summary of a message in a mailbox listing
+#[derive(Debug, Clone, Serialize)]
+struct MessageSummary {
+    uid: u32,
+    from: String,
+    subject: String,
+    date: String,
+    seen: bool,
+}
+
+/// Tool for reading and sending email
+pub struct EmailTool {
+    config: EmailConfig,
+}
+
+impl EmailTool {
+    pub fn new(config: EmailConfig) -> Self {
+        Self { config }
+    }
+
+    fn session(config: &EmailConfig) -> anyhow::Result<imap::Session<native_tls::TlsStream<std::net::TcpStream>>> {
+        let tls = native_tls::TlsConnector::builder().build()?;
+        let client = imap::connect((config.imap_host.as_str(), config.imap_port), &config.imap_host, &tls)?;
+        client.login(&config.username, &config.password).map_err(|(e, _)| anyhow::anyhow!("IMAP login failed: {}", e))
+    }
+
+    fn decode(bytes: Option<&[u8]>) -> String {
+        bytes.map(|b| String::from_utf8_lossy(b).to_string()).unwrap_or_default()
+    }
+
+    fn format_address(addresses: Option<&Vec<imap_proto::types::Address>>) -> String {
+        addresses.and_then(|a| a.first()).map(|a| {
+            let name = Self::decode(a.name);
+            let email = format!("{}@{}", Self::decode(a.mailbox), Self::decode(a.host));
+            if name.is_empty() { email } else { format!("{} <{}>", name, email) }
+        }).unwrap_or_default()
+    }
+
+    /// `s` as an IMAP quoted string: `\` and `"` are escaped, and line breaks (which would
+    /// end the command and start another) become spaces
+    fn quoted(s: &str) -> String {
+        let mut out = String::with_capacity(s.len() + 2);
+        out.push('"');
+        for c in s.chars() {
+            match c {
+                '\\' | '"' => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                '\r' | '\n' => out.push(' '),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+        out
+    }
+
+    /// Build an IMAP SEARCH criterion from the user's filters
+    fn search_criteria(params: &Value) -> String {
+        let mut criteria = Vec::new();
+        if params["unread_only"].as_bool().unwrap_or(false) {
+            criteria.push("UNSEEN".to_string());
+        }
+        if let Some(from) = params["from"].as_str() {
+            criteria.push(format!("FROM {}", Self::quoted(from)));
+        }
+        if let Some(subject) = params["subject"].as_str() {
+            criteria.push(format!("SUBJECT {}", Self::quoted(subject)));
+        }
+        if let Some(text) = params["query"].as_str() {
+            criteria.push(format!("TEXT {}", Self::quoted(text)));
+        }
+        if criteria.is_empty() { "ALL".to_string() } else { criteria.join(" ") }
+    }
+
+    fn list(config: &EmailConfig, params: &Value) -> anyhow::Result<ToolOutput> {
+        let mut session = Self::session(config)?;
+        let folder = params["folder"].as_str().unwrap_or("INBOX");
+        session.select(folder)?;
+
+        let limit = params["limit"].as_u64().unwrap_or(10).min(50) as usize;
+        let mut uids: Vec<u32> = session.uid_search(Self::search_criteria(params))?.into_iter().collect();
+        uids.sort_unstable_by(|a, b| b.cmp(a));
+        uids.truncate(limit);
+        if uids.is_empty() {
+            session.logout().ok();
+            return Ok(ToolOutput::success(json!({ "folder": folder, "messages": [] }), format!("No matching messages in {}", folder)));
+        }
+
+        let set = uids.iter().map(|u| u.to_string()).collect::<Vec<_>>().join(",");
+        let fetches = session.uid_fetch(&set, "(UID FLAGS ENVELOPE)")?;
+        let mut messages: Vec<MessageSummary> = fetches.iter().filter_map(|f| {
+            let envelope = f.envelope()?;
+            Some(MessageSummary {
+                uid: f.uid?,
+                from: Self::format_address(envelope.from.as_ref()),
+                subject: Self::decode(envelope.subject),
+                date: Self::decode(envelope.date),
+                seen: f.flags().iter().any(|flag| matches!(flag, imap::types::Flag::Seen)),
+            })
+        }).collect();
+        messages.sort_by(|a, b| b.uid.cmp(&a.uid));
+        session.logout().ok();
+
+        let lines: Vec<String> = messages.iter()
+            .map(|m| format!("[{}]{} {} — {} ({})", m.uid, if m.seen { "" } else { " *" }, m.from, m.subject, m.date))
+            .collect();
+        Ok(ToolOutput::success(
+            json!({ "folder": folder, "messages": messages }),
+            format!("{} message(s) in {}:\n{}", messages.len(), folder, lines.join("\n"))
+        ))
+    }
+
+    fn read(config: &EmailConfig, params: &Value) -> anyhow::Result<ToolOutput> {
+        let uid = params["uid"].as_u64().ok_or_else(|| anyhow::anyhow!("Missing required parameter: uid"))?;
+        let mut session = Self::session(config)?;
+        let folder = params["folder"].as_str().unwrap_or("INBOX");
+        session.select(folder)?;
+
+        // PEEK keeps the message unread unless explicitly asked to mark it
+        let query = if params["mark_read"].as_bool().unwrap_or(false) { "(UID ENVELOPE BODY[TEXT])" } else { "(UID ENVELOPE BODY.PEEK[TEXT])" };
+        let fetches = session.uid_fetch(uid.to_string(), query)?;
+        let fetch = fetches.iter().next().ok_or_else(|| anyhow::anyhow!("No message with UID {} in {}", uid, folder))?;
+        let envelope = fetch.envelope();
+        let from = Self::format_address(envelope.and_then(|e| e.from.as_ref()));
+        let subject = Self::decode(envelope.and_then(|e| e.subject));
+        let mut body = Self::decode(fetch.text());
+        if body.chars().count() > MAX_BODY_CHARS {
+            body = format!("{}\n... (truncated)", body.chars().take(MAX_BODY_CHARS).collect::<String>());
+        }
+        session.logout().ok();
+
+        Ok(ToolOutput::success(
+            json!({ "uid": uid, "from": from, "subject": subject, "body": body }),
+            format!("From: {}\nSubject: {}\n\n{}", from, subject, body)
+        ))
+    }
+
+    fn build_message(&self, params: &Value) -> anyhow::Result<Message> {
+        let from = self.config.from.clone().unwrap_or_else(|| self.config.username.clone());
+        let mut builder = Message::builder()
+            .from(from.parse::<Mailbox>()?)
+            .subject(params["subject"].as_str().unwrap_or("(no subject)"));
+        let recipients: Vec<String> = match &params["to"] {
+            Value::String(to) => to.split(',').map(|s| s.trim().to_string()).collect(),
+            Value::Array(to) => to.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect(),
+            _ => Vec::new(),
+        };
+        if recipients.is_empty() {
+            anyhow::bail!("Missing required parameter: to");
+        }
+        for to in recipients {
+            builder = builder.to(to.parse::<Mailbox>()?);
+        }
+        Ok(builder.body(params["body"].as_str().unwrap_or_default().to_string())?)
+    }
+
+    async fn send(&self, params: &Value) -> anyhow::Result<ToolOutput> {
+        let message = self.build_message(params)?;
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.config.smtp_host)?
+            .port(self.config.smtp_port)
+            .credentials(Credentials::new(self.config.username.clone(), self.config.password.clone()))
+            .build();
+        transport.send(message).await?;
+        info!("Email sent: {}", params["subject"].as_str().unwrap_or_default());
+        Ok(ToolOutput::success(json!({ "sent": true, "to": params["to"] }), "Email sent"))
+    }
+}
+
+#[async_trait]
+impl Tool for EmailTool {
+    fn name(&self) -> String {
+        "email".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Work with the user's mailbox. Actions: 'list' (folder, unread_only, limit), \
+         'search' (query/from/subject), 'read' (uid), 'draft' (to, subject, body: returns a preview \
+         without sending) and 'send' (requires user confirmation).".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "search", "read", "draft", "send"],
+                    "description": "The email operation to perform"
+                },
+                "folder": { "type": "string", "description": "Mailbox folder (default INBOX)" },
+                "unread_only": { "type": "boolean", "description": "Only unread messages" },
+                "limit": { "type": "integer", "description": "Maximum messages to list (default 10)" },
+                "query": { "type": "string", "description": "Full-text search" },
+                "from": { "type": "string", "description": "Filter by sender" },
+                "subject": { "type": "string", "description": "Subject filter, or subject of a new message" },
+                "uid": { "type": "integer", "description": "Message UID for 'read'" },
+                "mark_read": { "type": "boolean", "description": "Mark the message as read when reading" },
+                "to": { "description": "Recipient address(es), comma-separated string or array" },
+                "body": { "type": "string", "description": "Plain-text body of a new message" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "account": self.config.username,
+            "imap": format!("{}:{}", self.config.imap_host, self.config.imap_port),
+            "smtp": format!("{}:{}", self.config.smtp_host, self.config.smtp_port),
+            "send": "requires confirmation"
+        })
+    }
+
+    fn requires_confirmation_for(&self, params: &Value) -> bool {
+        params["action"].as_str() == Some("send")
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn has_side_effects(&self, params: &Value) -> bool {
+        params["action"].as_str() == Some("send")
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let action = params["action"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: action".to_string()))?
+            .to_string();
+
+        let result = match action.as_str() {
+            "list" | "search" | "read" => {
+                let config = self.config.clone();
+                tokio::task::spawn_blocking(move || {
+                    if action == "read" { Self::read(&config, &params) } else { Self::list(&config, &params) }
+                }).await.map_err(|e| AgentError::Execution(e.to_string()))?
+            }
+            "draft" => self.build_message(&params).map(|message| {
+                let preview = String::from_utf8_lossy(&message.formatted()).to_string();
+                ToolOutput::success(json!({ "draft": preview }), format!("Draft (not sent):\n\n{}", preview))
+            }),
+            "send" => self.send(&params).await,
+            other => return Ok(ToolOutput::failure(format!("Unknown action: {}", other))),
+        };
+
+        result.or_else(|e| Ok(ToolOutput::failure(e.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool() -> EmailTool {
+        EmailTool::new(EmailConfig {
+            imap_host: "imap.example.com".to_string(),
+            imap_port: 993,
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_port: 587,
+            username: "agent@example.com".to_string(),
+            password: "secret".to_string(),
+            from: None,
+        })
+    }
+
+    #[test]
+    fn test_search_criteria() {
+        let criteria = EmailTool::search_criteria(&json!({ "unread_only": true, "from": "bob", "query": "invoice" }));
+        assert_eq!(criteria, "UNSEEN FROM \"bob\" TEXT \"invoice\"");
+        // Quotes, backslashes and line breaks cannot close the string or inject a command
+        let hostile = EmailTool::search_criteria(&json!({ "subject": "a\\\" ALL\r\nA1 DELETE INBOX" }));
+        assert_eq!(hostile, r#"SUBJECT "a\\\" ALL  A1 DELETE INBOX""#);
+        assert_eq!(EmailTool::search_criteria(&json!({})), "ALL");
+    }
+
+    #[tokio::test]
+    async fn test_draft_does_not_send_and_send_needs_confirmation() {
+        let tool = tool();
+        let out = tool.execute(json!({ "action": "draft", "to": "bob@example.com", "subject": "Hi", "body": "Hello" })).await.unwrap();
+        assert!(out.success);
+        assert!(out.summary.contains("Subject: Hi"));
+
+        assert!(tool.requires_confirmation_for(&json!({ "action": "send" })));
+        assert!(!tool.requires_confirmation_for(&json!({ "action": "list" })));
+    }
+}
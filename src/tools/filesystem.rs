@@ -0,0 +1,454 @@
+//! File System Tool
+//!
+//! First-class file manipulation for agents: read, write, append, patch with unified diffs
+//! and glob directory listings. Every path is confined to an allowlist of root directories so
+//! the Coder agent no longer needs `code_exec` shell commands to touch files. The agency's own
+//! configuration, tools and skills are never writable, even when a root contains them.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use super::{Tool, ToolOutput, ToolCapability, ToolUndo};
+
+/// Largest file `read` returns in full; bigger files must be read by line range
+const MAX_READ_BYTES: usize = 256 * 1024;
+
+/// Paths (relative to the working directory) that are hot-reloaded into the agency's safety
+/// policy, profile, tools or skills, so writing them would bypass human review
+const PROTECTED_PATHS: [&str; 5] = ["config", "custom_tools", "standard_tools", "skills", ".env"];
+
+/// Tool for reading and editing files inside allowlisted roots
+pub struct FileSystemTool {
+    allowed_roots: Vec<PathBuf>,
+}
+
+impl FileSystemTool {
+    /// Create a tool confined to `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { allowed_roots: vec![root.into()] }
+    }
+
+    /// Additionally allow access below `root`
+    pub fn with_allowed_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.allowed_roots.push(root.into());
+        self
+    }
+
+    /// Roots from `AGENCY_FS_ALLOW` (`:`-separated), defaulting to the workspace directory
+    pub fn from_env() -> Self {
+        let roots: Vec<PathBuf> = std::env::var("AGENCY_FS_ALLOW").ok()
+            .map(|v| std::env::split_paths(&v).collect())
+            .unwrap_or_default();
+        if roots.is_empty() {
+            return Self::default();
+        }
+        Self { allowed_roots: roots }
+    }
+
+    fn canonical_roots(&self) -> Vec<PathBuf> {
+        self.allowed_roots.iter().filter_map(|r| r.canonicalize().ok()).collect()
+    }
+
+    /// Resolve `path` (relative paths are taken from the first root) and make sure it stays
+    /// inside an allowed root, following symlinks for the parts that already exist
+    pub(crate) fn resolve_path(&self, path: &str) -> AgentResult<PathBuf> {
+        let roots = self.canonical_roots();
+        let base = roots.first()
+            .ok_or_else(|| AgentError::Validation("No accessible root directory configured".to_string()))?;
+        let requested = Path::new(path);
+        let joined = if requested.is_absolute() { requested.to_path_buf() } else { base.join(requested) };
+
+        // Canonicalize the deepest existing ancestor, then re-append the missing tail
+        let mut existing = joined.as_path();
+        let mut tail = Vec::new();
+        while !existing.exists() {
+            match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    tail.push(name.to_owned());
+                    existing = parent;
+                }
+                _ => break,
+            }
+        }
+        let mut resolved = existing.canonicalize().map_err(AgentError::Io)?;
+        for part in tail.into_iter().rev() {
+            resolved.push(part);
+        }
+        if resolved.components().any(|c| c == Component::ParentDir) {
+            return Err(AgentError::Validation(format!("Access denied: '{}' escapes the allowed roots", path)));
+        }
+
+        if roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(AgentError::Validation(format!("Access denied: '{}' is outside the allowed roots", path)))
+        }
+    }
+
+    /// Resolve a path that is about to be written, refusing the protected paths
+    pub(crate) fn resolve_writable(&self, path: &str) -> AgentResult<PathBuf> {
+        let resolved = self.resolve_path(path)?;
+        if is_protected(&resolved) {
+            return Err(AgentError::Validation(format!("Access denied: '{}' is agency configuration and cannot be modified", path)));
+        }
+        Ok(resolved)
+    }
+
+    fn is_allowed(&self, path: &Path) -> bool {
+        let roots = self.canonical_roots();
+        path.canonicalize().map(|p| roots.iter().any(|root| p.starts_with(root))).unwrap_or(false)
+    }
+
+    fn required_str<'a>(params: &'a Value, key: &str) -> AgentResult<&'a str> {
+        params[key].as_str()
+            .ok_or_else(|| AgentError::Validation(format!("Missing required parameter: {}", key)))
+    }
+
+    async fn read(&self, params: &Value) -> AgentResult<ToolOutput> {
+        let path_str = Self::required_str(params, "path")?;
+        let path = self.resolve_path(path_str)?;
+        let content = fs::read_to_string(&path).await.map_err(AgentError::Io)?;
+
+        let start = params["start_line"].as_u64().map(|n| n.max(1) as usize);
+        let end = params["end_line"].as_u64().map(|n| n as usize);
+        let total_lines = content.lines().count();
+        let content = if start.is_some() || end.is_some() {
+            let start = start.unwrap_or(1);
+            let end = end.unwrap_or(total_lines).min(total_lines);
+            content.lines().skip(start - 1).take(end.saturating_sub(start - 1)).collect::<Vec<_>>().join("\n")
+        } else if content.len() > MAX_READ_BYTES {
+            return Ok(ToolOutput::failure(format!(
+                "{} is {} bytes ({} lines); read it in parts with start_line/end_line",
+                path_str, content.len(), total_lines
+            )));
+        } else {
+            content
+        };
+
+        Ok(ToolOutput::success(
+            json!({ "path": path_str, "content": content, "total_lines": total_lines }),
+            format!("Content of {}:\n\n{}", path_str, content)
+        ))
+    }
+
+    async fn write(&self, params: &Value, append: bool) -> AgentResult<ToolOutput> {
+        let path_str = Self::required_str(params, "path")?;
+        let content = Self::required_str(params, "content")?;
+        let path = self.resolve_writable(path_str)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(AgentError::Io)?;
+        }
+
+        if append {
+            use tokio::io::AsyncWriteExt;
+            let mut file = fs::OpenOptions::new().create(true).append(true).open(&path).await.map_err(AgentError::Io)?;
+            file.write_all(content.as_bytes()).await.map_err(AgentError::Io)?;
+        } else {
+            fs::write(&path, content).await.map_err(AgentError::Io)?;
+        }
+
+        let verb = if append { "Appended" } else { "Wrote" };
+        info!("{} {} bytes to {:?}", verb, content.len(), path);
+        Ok(ToolOutput::success(
+            json!({ "path": path_str, "bytes": content.len() }),
+            format!("{} {} bytes to {}", verb, content.len(), path_str)
+        ))
+    }
+
+    async fn patch(&self, params: &Value) -> AgentResult<ToolOutput> {
+        let path_str = Self::required_str(params, "path")?;
+        let diff = Self::required_str(params, "diff")?;
+        let path = self.resolve_writable(path_str)?;
+        let original = if path.exists() {
+            fs::read_to_string(&path).await.map_err(AgentError::Io)?
+        } else {
+            String::new()
+        };
+
+        match apply_unified_diff(&original, diff) {
+            Ok((patched, hunks)) => {
+                fs::write(&path, &patched).await.map_err(AgentError::Io)?;
+                info!("Patched {:?} ({} hunks)", path, hunks);
+                Ok(ToolOutput::success(
+                    json!({ "path": path_str, "hunks_applied": hunks }),
+                    format!("Applied {} hunk(s) to {}", hunks, path_str)
+                ))
+            }
+            Err(e) => Ok(ToolOutput::failure(format!("Patch did not apply to {}: {}", path_str, e))),
+        }
+    }
+
+    async fn list(&self, params: &Value) -> AgentResult<ToolOutput> {
+        let dir_str = params["path"].as_str().unwrap_or(".");
+        let dir = self.resolve_path(dir_str)?;
+        let pattern = params["pattern"].as_str().unwrap_or("*");
+        let full_pattern = dir.join(pattern);
+
+        let entries = glob::glob(&full_pattern.to_string_lossy())
+            .map_err(|e| AgentError::Validation(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+        let mut files = Vec::new();
+        for entry in entries.flatten() {
+            if !self.is_allowed(&entry) {
+                continue;
+            }
+            let relative = entry.strip_prefix(&dir).unwrap_or(&entry).to_string_lossy().to_string();
+            if entry.is_dir() {
+                files.push(format!("{}/", relative));
+            } else {
+                files.push(relative);
+            }
+        }
+        files.sort();
+
+        let summary = if files.is_empty() {
+            format!("No entries in {} match '{}'", dir_str, pattern)
+        } else {
+            format!("{} entries in {} matching '{}':\n{}", files.len(), dir_str, pattern, files.join("\n"))
+        };
+        Ok(ToolOutput::success(json!({ "path": dir_str, "pattern": pattern, "entries": files }), summary))
+    }
+}
+
+impl Default for FileSystemTool {
+    fn default() -> Self {
+        Self::new(crate::utils::workspace_dir())
+    }
+}
+
+fn is_protected(path: &Path) -> bool {
+    let Ok(cwd) = std::env::current_dir().and_then(|d| d.canonicalize()) else {
+        return false;
+    };
+    PROTECTED_PATHS.iter().any(|p| {
+        let protected = cwd.join(p);
+        path.starts_with(protected.canonicalize().unwrap_or(protected))
+    })
+}
+
+/// A single `@@ -a,b +c,d @@` hunk
+struct Hunk {
+    old_start: usize,
+    lines: Vec<(char, String)>,
+}
+
+fn parse_hunks(diff: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@") {
+            let old = header.split_whitespace().next()
+                .and_then(|r| r.strip_prefix('-'))
+                .ok_or_else(|| format!("malformed hunk header '{}'", line))?;
+            let old_start = old.split(',').next().and_then(|n| n.parse::<usize>().ok())
+                .ok_or_else(|| format!("malformed hunk header '{}'", line))?;
+            hunks.push(Hunk { old_start, lines: Vec::new() });
+        } else if line.starts_with("---") || line.starts_with("+++") || line.starts_with('\\') {
+            continue;
+        } else if let Some(hunk) = hunks.last_mut() {
+            let mut chars = line.chars();
+            match chars.next() {
+                Some(op @ (' ' | '-' | '+')) => hunk.lines.push((op, chars.as_str().to_string())),
+                // Editors and models often strip the space from empty context lines
+                None => hunk.lines.push((' ', String::new())),
+                Some(_) => return Err(format!("unexpected line in hunk: '{}'", line)),
+            }
+        }
+    }
+    if hunks.is_empty() {
+        return Err("no hunks found (expected '@@ -a,b +c,d @@' headers)".to_string());
+    }
+    Ok(hunks)
+}
+
+/// Apply a unified diff to `original`. Hunks are located at their stated line first and then
+/// by searching outwards, so diffs with stale line numbers still apply if the context matches.
+pub fn apply_unified_diff(original: &str, diff: &str) -> Result<(String, usize), String> {
+    let hunks = parse_hunks(diff)?;
+    let mut lines: Vec<String> = original.lines().map(|l| l.to_string()).collect();
+    // Line shift caused by hunks already applied
+    let mut offset: isize = 0;
+
+    for (idx, hunk) in hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk.lines.iter().filter(|(op, _)| *op != '+').map(|(_, l)| l.as_str()).collect();
+        let new: Vec<String> = hunk.lines.iter().filter(|(op, _)| *op != '-').map(|(_, l)| l.clone()).collect();
+
+        let expected = (hunk.old_start.max(1) as isize - 1 + offset).max(0) as usize;
+        let matches_at = |pos: usize| pos + old.len() <= lines.len() && lines[pos..pos + old.len()].iter().zip(&old).all(|(a, b)| a == b);
+        let position = (0..=lines.len())
+            .flat_map(|d| [expected.checked_add(d), expected.checked_sub(d)])
+            .flatten()
+            .take(2 * (lines.len() + 1))
+            .find(|&pos| matches_at(pos))
+            .ok_or_else(|| format!("hunk #{} context not found near line {}", idx + 1, hunk.old_start))?;
+
+        lines.splice(position..position + old.len(), new.iter().cloned());
+        offset += new.len() as isize - old.len() as isize;
+    }
+
+    let mut patched = lines.join("\n");
+    if original.ends_with('\n') || original.is_empty() {
+        patched.push('\n');
+    }
+    Ok((patched, hunks.len()))
+}
+
+#[async_trait]
+impl Tool for FileSystemTool {
+    fn name(&self) -> String {
+        "file_system".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Read, write, append and patch files, and list directories. \
+         Use 'patch' with a unified diff (@@ -a,b +c,d @@ hunks) for targeted edits instead of rewriting whole files. \
+         Paths are relative to the workspace directory.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["read", "write", "append", "patch", "list"],
+                    "description": "The operation to perform"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File path (or directory for 'list')"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to write or append"
+                },
+                "diff": {
+                    "type": "string",
+                    "description": "Unified diff to apply (for 'patch')"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Glob for 'list', e.g. '**/*.rs' (default '*')"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line to read (1-based)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line to read (inclusive)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "environment": "local filesystem",
+            "allowed_roots": self.allowed_roots,
+            "max_read_bytes": MAX_READ_BYTES
+        })
+    }
+
+    async fn security_oracle(&self, params: &Value) -> AgentResult<bool> {
+        match params["path"].as_str() {
+            Some(path) => Ok(self.resolve_path(path).is_ok()),
+            None => Ok(true),
+        }
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::FilesystemWrite]
+    }
+
+    fn has_side_effects(&self, params: &Value) -> bool {
+        !matches!(params["action"].as_str(), Some("read") | Some("list"))
+    }
+
+    async fn prepare_undo(&self, params: &Value) -> Option<ToolUndo> {
+        if !self.has_side_effects(params) {
+            return None;
+        }
+        let path = self.resolve_path(params["path"].as_str()?).ok()?;
+        let previous = match fs::read(&path).await {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            // Unreadable now means we could not restore it either
+            Err(_) => return None,
+        };
+        Some(ToolUndo::RestoreFile { path, previous })
+    }
+
+    fn dry_run(&self, params: &Value) -> ToolOutput {
+        let action = params["action"].as_str().unwrap_or("?");
+        let path = params["path"].as_str().unwrap_or("?");
+        let plan = match action {
+            "write" => format!("Would write {} bytes to {}", params["content"].as_str().map(str::len).unwrap_or(0), path),
+            "append" => format!("Would append {} bytes to {}", params["content"].as_str().map(str::len).unwrap_or(0), path),
+            "patch" => format!("Would apply a {}-line diff to {}", params["diff"].as_str().map(|d| d.lines().count()).unwrap_or(0), path),
+            _ => format!("Would run '{}' on {}", action, path),
+        };
+        ToolOutput::success(json!({ "dry_run": true, "action": action, "path": path }), format!("[DRY RUN] {}", plan))
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let action = Self::required_str(&params, "action")?;
+        match action {
+            "read" => self.read(&params).await,
+            "write" => self.write(&params, false).await,
+            "append" => self.write(&params, true).await,
+            "patch" => self.patch(&params).await,
+            "list" => self.list(&params).await,
+            _ => Ok(ToolOutput::failure(format!("Unknown action: {}", action))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_unified_diff_with_stale_line_numbers() {
+        let original = "fn main() {\n    let a = 1;\n    println!(\"{}\", a);\n}\n";
+        let diff = "--- a/main.rs\n+++ b/main.rs\n@@ -10,3 +10,3 @@\n fn main() {\n-    let a = 1;\n+    let a = 2;\n     println!(\"{}\", a);\n";
+        let (patched, hunks) = apply_unified_diff(original, diff).unwrap();
+        assert_eq!(hunks, 1);
+        assert_eq!(patched, "fn main() {\n    let a = 2;\n    println!(\"{}\", a);\n}\n");
+    }
+
+    #[test]
+    fn test_apply_unified_diff_rejects_mismatched_context() {
+        let diff = "@@ -1,1 +1,1 @@\n-missing\n+replacement\n";
+        assert!(apply_unified_diff("present\n", diff).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_paths_confined_to_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = FileSystemTool::new(dir.path());
+
+        let out = tool.execute(json!({ "action": "write", "path": "src/lib.rs", "content": "pub fn a() {}\n" })).await.unwrap();
+        assert!(out.success);
+        let out = tool.execute(json!({ "action": "list", "pattern": "**/*.rs" })).await.unwrap();
+        assert_eq!(out.data["entries"], json!(["src/lib.rs"]));
+
+        assert!(tool.execute(json!({ "action": "read", "path": "../outside.txt" })).await.is_err());
+        assert!(tool.execute(json!({ "action": "read", "path": "/etc/passwd" })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_agency_configuration_is_read_only() {
+        let tool = FileSystemTool::new(".");
+        for path in ["config/safety_policy.toml", "./custom_tools/planted.json", "skills/new/SKILL.md", ".env"] {
+            let err = tool.execute(json!({ "action": "write", "path": path, "content": "x" })).await.unwrap_err();
+            assert!(err.to_string().contains("cannot be modified"), "{}: {}", path, err);
+        }
+        assert!(tool.execute(json!({ "action": "patch", "path": "config/safety_policy.toml", "diff": "" })).await.is_err());
+        assert!(tool.execute(json!({ "action": "read", "path": "config/safety_policy.toml" })).await.unwrap().success);
+    }
+}
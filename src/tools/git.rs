@@ -0,0 +1,360 @@
+//! Git Tool
+//!
+//! Repository operations (status, diff, log, blame, branch, commit, stash) built on `git2`,
+//! so the Coder agent can inspect and commit its own changes during autonomous coding runs.
+
+use async_trait::async_trait;
+use git2::{BranchType, DiffFormat, DiffOptions, IndexAddOption, Repository, Signature, StatusOptions};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use super::{Tool, ToolOutput, ToolCapability};
+
+/// Diffs longer than this are truncated in the tool output
+const MAX_DIFF_CHARS: usize = 20_000;
+
+/// Tool for inspecting and committing to a git repository
+pub struct GitTool {
+    repo_path: PathBuf,
+}
+
+impl GitTool {
+    pub fn new(repo_path: impl Into<PathBuf>) -> Self {
+        Self { repo_path: repo_path.into() }
+    }
+
+    fn open(&self) -> Result<Repository, git2::Error> {
+        Repository::discover(&self.repo_path)
+    }
+
+    fn signature(repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+        repo.signature().or_else(|_| Signature::now("Agency", "agency@localhost"))
+    }
+
+    fn status(repo: &Repository) -> Result<ToolOutput, git2::Error> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = repo.statuses(Some(&mut opts))?;
+
+        let entries: Vec<Value> = statuses.iter().map(|entry| {
+            let s = entry.status();
+            let state = if s.is_index_new() || s.is_index_modified() || s.is_index_deleted() || s.is_index_renamed() {
+                "staged"
+            } else if s.is_wt_new() {
+                "untracked"
+            } else if s.is_wt_deleted() {
+                "deleted"
+            } else if s.is_conflicted() {
+                "conflicted"
+            } else {
+                "modified"
+            };
+            json!({ "path": entry.path().unwrap_or_default(), "state": state })
+        }).collect();
+
+        let branch = repo.head().ok().and_then(|h| h.shorthand().map(|s| s.to_string())).unwrap_or_else(|| "(no branch)".to_string());
+        let summary = if entries.is_empty() {
+            format!("On branch {}: working tree clean", branch)
+        } else {
+            let lines: Vec<String> = entries.iter().map(|e| format!("{:>10}  {}", e["state"].as_str().unwrap_or(""), e["path"].as_str().unwrap_or(""))).collect();
+            format!("On branch {}:\n{}", branch, lines.join("\n"))
+        };
+        Ok(ToolOutput::success(json!({ "branch": branch, "entries": entries }), summary))
+    }
+
+    fn diff(repo: &Repository, params: &Value) -> Result<ToolOutput, git2::Error> {
+        let staged = params["staged"].as_bool().unwrap_or(false);
+        let mut opts = DiffOptions::new();
+        if let Some(path) = params["path"].as_str() {
+            opts.pathspec(path);
+        }
+
+        let diff = if staged {
+            let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?
+        } else {
+            repo.diff_index_to_workdir(None, Some(&mut opts))?
+        };
+
+        let mut patch = String::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+
+        let stats = diff.stats()?;
+        let truncated = patch.len() > MAX_DIFF_CHARS;
+        if truncated {
+            let mut end = MAX_DIFF_CHARS;
+            while !patch.is_char_boundary(end) {
+                end -= 1;
+            }
+            patch.truncate(end);
+            patch.push_str("\n... (diff truncated)");
+        }
+
+        let summary = if patch.is_empty() {
+            format!("No {} changes", if staged { "staged" } else { "unstaged" })
+        } else {
+            format!("{} files changed, {} insertions(+), {} deletions(-)\n\n{}", stats.files_changed(), stats.insertions(), stats.deletions(), patch)
+        };
+        Ok(ToolOutput::success(json!({
+            "staged": staged,
+            "files_changed": stats.files_changed(),
+            "insertions": stats.insertions(),
+            "deletions": stats.deletions(),
+            "patch": patch,
+            "truncated": truncated
+        }), summary))
+    }
+
+    fn log(repo: &Repository, params: &Value) -> Result<ToolOutput, git2::Error> {
+        let limit = params["limit"].as_u64().unwrap_or(10).min(100) as usize;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push_head()?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk.take(limit) {
+            let commit = repo.find_commit(oid?)?;
+            commits.push(json!({
+                "id": commit.id().to_string()[..8].to_string(),
+                "author": commit.author().name().unwrap_or_default(),
+                "time": chrono::DateTime::from_timestamp(commit.time().seconds(), 0).map(|t| t.to_rfc3339()).unwrap_or_default(),
+                "summary": commit.summary().unwrap_or_default()
+            }));
+        }
+
+        let lines: Vec<String> = commits.iter()
+            .map(|c| format!("{} {} ({})", c["id"].as_str().unwrap_or(""), c["summary"].as_str().unwrap_or(""), c["author"].as_str().unwrap_or("")))
+            .collect();
+        Ok(ToolOutput::success(json!({ "commits": commits }), lines.join("\n")))
+    }
+
+    fn blame(repo: &Repository, params: &Value) -> Result<ToolOutput, AgentError> {
+        let path = params["path"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: path".to_string()))?;
+        let workdir = repo.workdir().ok_or_else(|| AgentError::Tool("Bare repositories are not supported".to_string()))?;
+        let content = std::fs::read_to_string(workdir.join(path))?;
+        let blame = repo.blame_file(std::path::Path::new(path), None).map_err(|e| AgentError::Tool(e.to_string()))?;
+
+        let start = params["start_line"].as_u64().unwrap_or(1).max(1) as usize;
+        let end = params["end_line"].as_u64().map(|n| n as usize).unwrap_or(start + 49);
+        let mut lines = Vec::new();
+        for (idx, text) in content.lines().enumerate().skip(start - 1).take(end.saturating_sub(start - 1)) {
+            let line_no = idx + 1;
+            let (id, author) = blame.get_line(line_no)
+                .map(|h| (h.final_commit_id().to_string()[..8].to_string(), h.final_signature().name().unwrap_or_default().to_string()))
+                .unwrap_or_else(|| ("00000000".to_string(), "Not Committed".to_string()));
+            lines.push(format!("{} ({:<16} {:>4}) {}", id, author, line_no, text));
+        }
+        Ok(ToolOutput::success(json!({ "path": path, "lines": lines }), lines.join("\n")))
+    }
+
+    fn branch(repo: &Repository, params: &Value) -> Result<ToolOutput, git2::Error> {
+        let Some(name) = params["name"].as_str() else {
+            let current = repo.head().ok().and_then(|h| h.shorthand().map(|s| s.to_string()));
+            let mut branches = Vec::new();
+            for branch in repo.branches(Some(BranchType::Local))? {
+                let (branch, _) = branch?;
+                if let Some(name) = branch.name()? {
+                    branches.push(name.to_string());
+                }
+            }
+            let summary = branches.iter()
+                .map(|b| if Some(b) == current.as_ref() { format!("* {}", b) } else { format!("  {}", b) })
+                .collect::<Vec<_>>()
+                .join("\n");
+            return Ok(ToolOutput::success(json!({ "current": current, "branches": branches }), summary));
+        };
+
+        if repo.find_branch(name, BranchType::Local).is_err() {
+            let head = repo.head()?.peel_to_commit()?;
+            repo.branch(name, &head, false)?;
+        }
+        if params["checkout"].as_bool().unwrap_or(false) {
+            let refname = format!("refs/heads/{}", name);
+            let target = repo.revparse_single(&refname)?;
+            repo.checkout_tree(&target, None)?;
+            repo.set_head(&refname)?;
+            return Ok(ToolOutput::success(json!({ "branch": name, "checked_out": true }), format!("Switched to branch '{}'", name)));
+        }
+        Ok(ToolOutput::success(json!({ "branch": name, "checked_out": false }), format!("Created branch '{}'", name)))
+    }
+
+    fn commit(repo: &Repository, params: &Value) -> Result<ToolOutput, AgentError> {
+        let message = params["message"].as_str()
+            .filter(|m| !m.trim().is_empty())
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: message".to_string()))?;
+        let git = |e: git2::Error| AgentError::Tool(e.to_string());
+
+        let mut index = repo.index().map_err(git)?;
+        match params["paths"].as_array() {
+            Some(paths) => {
+                let paths: Vec<&str> = paths.iter().filter_map(|p| p.as_str()).collect();
+                index.add_all(paths.iter(), IndexAddOption::DEFAULT, None).map_err(git)?;
+                index.update_all(paths.iter(), None).map_err(git)?;
+            }
+            None => {
+                index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None).map_err(git)?;
+                index.update_all(["*"].iter(), None).map_err(git)?;
+            }
+        }
+        index.write().map_err(git)?;
+
+        let tree = repo.find_tree(index.write_tree().map_err(git)?).map_err(git)?;
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        if let Some(ref parent) = parent {
+            if parent.tree_id() == tree.id() {
+                return Ok(ToolOutput::failure("Nothing to commit"));
+            }
+        }
+
+        let signature = Self::signature(repo).map_err(git)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).map_err(git)?;
+        info!("Committed {}: {}", oid, message);
+        let id = oid.to_string()[..8].to_string();
+        Ok(ToolOutput::success(json!({ "id": id, "message": message }), format!("Committed {}: {}", id, message)))
+    }
+
+    fn stash(repo: &mut Repository, params: &Value) -> Result<ToolOutput, git2::Error> {
+        if params["pop"].as_bool().unwrap_or(false) {
+            repo.stash_pop(0, None)?;
+            return Ok(ToolOutput::success(json!({ "popped": true }), "Restored the most recent stash"));
+        }
+        let signature = Self::signature(repo)?;
+        let message = params["message"].as_str().unwrap_or("agency stash");
+        let oid = repo.stash_save(&signature, message, None)?;
+        Ok(ToolOutput::success(json!({ "stash": oid.to_string() }), format!("Stashed working changes: {}", message)))
+    }
+}
+
+impl Default for GitTool {
+    fn default() -> Self {
+        Self::new(".")
+    }
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> String {
+        "git".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Git repository operations: 'status', 'diff' (staged or unstaged, optional path), 'log', \
+         'blame' (path + line range), 'branch' (list, or create/checkout by name), \
+         'commit' (message, optional paths; stages changes first) and 'stash' (save or pop).".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "blame", "branch", "commit", "stash"],
+                    "description": "The git operation to perform"
+                },
+                "path": { "type": "string", "description": "File path for 'diff' or 'blame'" },
+                "staged": { "type": "boolean", "description": "Diff the index instead of the working tree" },
+                "limit": { "type": "integer", "description": "Number of commits for 'log' (default 10)" },
+                "start_line": { "type": "integer", "description": "First line for 'blame'" },
+                "end_line": { "type": "integer", "description": "Last line for 'blame'" },
+                "name": { "type": "string", "description": "Branch to create or switch to" },
+                "checkout": { "type": "boolean", "description": "Switch to the branch after creating it" },
+                "message": { "type": "string", "description": "Commit or stash message" },
+                "paths": { "type": "array", "items": { "type": "string" }, "description": "Paths to stage for 'commit' (default: all changes)" },
+                "pop": { "type": "boolean", "description": "Pop the latest stash instead of saving" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "environment": "local git repository",
+            "repository": self.repo_path,
+            "remote_operations": false
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::FilesystemWrite]
+    }
+
+    fn has_side_effects(&self, params: &Value) -> bool {
+        match params["action"].as_str() {
+            Some("commit") | Some("stash") => true,
+            // Without a name, 'branch' only lists branches
+            Some("branch") => params["name"].is_string(),
+            _ => false,
+        }
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let action = params["action"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: action".to_string()))?
+            .to_string();
+        let mut repo = self.open().map_err(|e| AgentError::Tool(format!("Not a git repository: {}", e)))?;
+
+        tokio::task::spawn_blocking(move || {
+            let git = |e: git2::Error| AgentError::Tool(e.to_string());
+            match action.as_str() {
+                "status" => Self::status(&repo).map_err(git),
+                "diff" => Self::diff(&repo, &params).map_err(git),
+                "log" => Self::log(&repo, &params).map_err(git),
+                "blame" => Self::blame(&repo, &params),
+                "branch" => Self::branch(&repo, &params).map_err(git),
+                "commit" => Self::commit(&repo, &params),
+                "stash" => Self::stash(&mut repo, &params).map_err(git),
+                other => Ok(ToolOutput::failure(format!("Unknown action: {}", other))),
+            }
+        }).await.map_err(|e| AgentError::Execution(e.to_string()))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_commit_then_log_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+        let tool = GitTool::new(dir.path());
+
+        let status = tool.execute(json!({ "action": "status" })).await.unwrap();
+        assert_eq!(status.data["entries"][0]["state"], "untracked");
+
+        let commit = tool.execute(json!({ "action": "commit", "message": "Add a.txt" })).await.unwrap();
+        assert!(commit.success, "{}", commit.summary);
+
+        let log = tool.execute(json!({ "action": "log" })).await.unwrap();
+        assert_eq!(log.data["commits"][0]["summary"], "Add a.txt");
+
+        let status = tool.execute(json!({ "action": "status" })).await.unwrap();
+        assert!(status.summary.contains("working tree clean"));
+
+        let again = tool.execute(json!({ "action": "commit", "message": "noop" })).await.unwrap();
+        assert!(!again.success);
+    }
+
+    #[test]
+    fn test_only_mutating_actions_have_side_effects() {
+        let tool = GitTool::new(".");
+        for action in ["status", "diff", "log", "blame", "branch"] {
+            assert!(!tool.has_side_effects(&json!({ "action": action })), "{}", action);
+        }
+        assert!(tool.has_side_effects(&json!({ "action": "branch", "name": "feature" })));
+        assert!(tool.has_side_effects(&json!({ "action": "commit", "message": "m" })));
+        assert!(tool.has_side_effects(&json!({ "action": "stash", "pop": true })));
+    }
+}
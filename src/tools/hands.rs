@@ -8,7 +8,7 @@ use serde_json::{json, Value};
 use enigo::{Enigo, Mouse, Keyboard, Button, Direction, Coordinate, Key, Settings};
 use tracing::{info, debug};
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCapability};
 
 pub struct HandsTool;
 
@@ -62,6 +62,10 @@ impl Tool for HandsTool {
         true 
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().ok_or_else(|| AgentError::Validation("Missing 'action'".to_string()))?.to_string();
         
@@ -0,0 +1,208 @@
+//! HTTP Request Tool
+//!
+//! Structured REST calls (method, URL, headers, body, timeout) so agents stop shelling out to
+//! curl. Destinations are checked against the `DomainPolicy` both here and in `SafetyGuard`,
+//! including every redirect hop and every address a host name resolves to. Bodies are read
+//! up to `MAX_BODY_BYTES`.
+
+use async_trait::async_trait;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use crate::safety::{DomainPolicy, PolicyResolver};
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
+
+/// Response bodies longer than this are truncated
+const MAX_BODY_CHARS: usize = 20_000;
+/// Bytes read from a response before the rest is dropped
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const MAX_TIMEOUT_SECS: u64 = 120;
+
+/// Tool for calling HTTP APIs
+pub struct HttpTool {
+    client: Client,
+    policy: Arc<DomainPolicy>,
+}
+
+impl HttpTool {
+    pub fn new(policy: DomainPolicy) -> Self {
+        let policy = Arc::new(policy);
+        let redirect_policy = policy.clone();
+        let client = Client::builder()
+            .user_agent("rust_agency/http_request")
+            .dns_resolver(PolicyResolver::new(policy.clone()))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 5 {
+                    attempt.error("too many redirects")
+                } else if let Err(e) = redirect_policy.check_url(attempt.url().as_str()) {
+                    attempt.error(e.to_string())
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .unwrap_or_default();
+        Self { client, policy }
+    }
+}
+
+impl Default for HttpTool {
+    fn default() -> Self {
+        Self::new(DomainPolicy::from_env())
+    }
+}
+
+/// The body as text, reading at most `limit` bytes; also whether it was cut short
+async fn read_body(mut response: reqwest::Response, limit: usize) -> reqwest::Result<(String, bool)> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() > limit {
+            bytes.extend_from_slice(&chunk[..limit - bytes.len()]);
+            return Ok((String::from_utf8_lossy(&bytes).into_owned(), true));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), false))
+}
+
+#[async_trait]
+impl Tool for HttpTool {
+    fn name(&self) -> String {
+        "http_request".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Call an HTTP/REST API. Provide method, url, optional headers (object) and body \
+         (string or JSON). JSON responses are parsed; large bodies are truncated.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "enum": ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"],
+                    "description": "HTTP method (default GET)"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Absolute http(s) URL"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Request headers as name/value pairs"
+                },
+                "body": {
+                    "description": "Request body; objects and arrays are sent as JSON"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Request timeout in seconds (default 30, max 120)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "network": "outbound http/https",
+            "allow": self.policy.allow,
+            "deny": self.policy.deny,
+            "internal_addresses": "blocked unless allowlisted",
+            "max_body_chars": MAX_BODY_CHARS
+        })
+    }
+
+    async fn security_oracle(&self, params: &Value) -> AgentResult<bool> {
+        Ok(params["url"].as_str().map(|url| self.policy.check_url(url).is_ok()).unwrap_or(false))
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn has_side_effects(&self, params: &Value) -> bool {
+        let method = params["method"].as_str().unwrap_or("GET").to_uppercase();
+        !matches!(method.as_str(), "GET" | "HEAD" | "OPTIONS")
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(MAX_TIMEOUT_SECS + 10))
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let url = params["url"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: url".to_string()))?;
+        self.policy.check_url(url).map_err(|e| AgentError::Validation(e.to_string()))?;
+
+        let method_str = params["method"].as_str().unwrap_or("GET").to_uppercase();
+        let method = Method::from_bytes(method_str.as_bytes())
+            .map_err(|_| AgentError::Validation(format!("Invalid HTTP method: {}", method_str)))?;
+        let timeout = params["timeout_secs"].as_u64().unwrap_or(30).clamp(1, MAX_TIMEOUT_SECS);
+
+        let mut request = self.client.request(method, url).timeout(Duration::from_secs(timeout));
+        if let Some(headers) = params["headers"].as_object() {
+            for (name, value) in headers {
+                let value = value.as_str().map(|s| s.to_string()).unwrap_or_else(|| value.to_string());
+                request = request.header(name.as_str(), value);
+            }
+        }
+        request = match &params["body"] {
+            Value::Null => request,
+            Value::String(text) => request.body(text.clone()),
+            other => request.json(other),
+        };
+
+        info!("HTTP {} {}", method_str, url);
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => return Ok(ToolOutput::failure(format!("Request failed: {}", e))),
+        };
+
+        let status = response.status();
+        let final_url = response.url().to_string();
+        let content_type = response.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let (text, cut) = read_body(response, MAX_BODY_BYTES).await.map_err(|e| AgentError::Tool(e.to_string()))?;
+
+        let truncated = cut || text.chars().count() > MAX_BODY_CHARS;
+        let body_text: String = if truncated { text.chars().take(MAX_BODY_CHARS).collect() } else { text.clone() };
+        let body = if content_type.contains("json") && !truncated {
+            serde_json::from_str(&text).unwrap_or(Value::String(body_text.clone()))
+        } else {
+            Value::String(body_text.clone())
+        };
+
+        let data = json!({
+            "status": status.as_u16(),
+            "url": final_url,
+            "content_type": content_type,
+            "body": body,
+            "truncated": truncated
+        });
+        let summary = format!("HTTP {} {}\n\n{}{}", status.as_u16(), status.canonical_reason().unwrap_or(""), body_text,
+            if truncated { "\n... (body truncated)" } else { "" });
+
+        if status.is_success() {
+            Ok(ToolOutput::success(data, summary))
+        } else {
+            let mut output = ToolOutput::failure(format!("HTTP {} from {}", status.as_u16(), url));
+            output.data = data;
+            output.summary = summary;
+            Ok(output)
+        }
+    }
+}
@@ -1,21 +1,27 @@
 //! Model Context Protocol (MCP) Tool Integration
 //! 
 //! Allows rust_agency to act as an MCP client, connecting to external
-//! MCP servers over stdio and dynamically registering their tools.
+//! MCP servers over stdio or streamable HTTP and dynamically registering their tools.
+//! Crashed server processes are restarted and dropped HTTP sessions are reconnected.
 
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use futures_util::StreamExt;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command, ChildStdin, ChildStdout};
 use tokio::sync::Mutex;
 use std::sync::Arc;
-use tracing::{info, debug};
+use tracing::{info, debug, warn};
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use crate::orchestrator::AgencyEvent;
+use super::{Tool, ToolOutput, ToolCapability};
 
 /// JSON-RPC 2.0 Request
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,46 +58,262 @@ pub struct McpToolDefinition {
     pub input_schema: Value,
 }
 
+/// How to reach an MCP server (one entry of `config/mcp_servers.json`): a local `command`
+/// spoken to over stdio, or a remote `url` using the streamable HTTP transport
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    pub name: String,
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the server process
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Streamable HTTP endpoint; takes precedence over `command`
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Bearer token sent to `url`
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    /// Environment variable holding the bearer token, to keep it out of the config file
+    #[serde(default)]
+    pub bearer_token_env: Option<String>,
+    /// Consecutive respawns/reconnects allowed before the server is given up on; the
+    /// count resets once a connection has stayed up for `STABLE_UPTIME`
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+impl McpServerConfig {
+    fn bearer_token(&self) -> Option<String> {
+        self.bearer_token.clone()
+            .or_else(|| self.bearer_token_env.as_ref().and_then(|var| std::env::var(var).ok()))
+    }
+}
+
+/// A live server process and its pipes
+struct StdioConnection {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl StdioConnection {
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    async fn send(&mut self, message: &Value) -> anyhow::Result<()> {
+        self.stdin.write_all((serde_json::to_string(message)? + "\n").as_bytes()).await?;
+        self.stdin.flush().await?;
+        Ok(())
+    }
+}
+
+/// A streamable HTTP endpoint and the session it assigned us
+struct HttpConnection {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+    session_id: Option<String>,
+}
+
+impl HttpConnection {
+    async fn post(&self, message: &Value) -> reqwest::Result<reqwest::Response> {
+        let mut request = self.client.post(&self.url)
+            .header(reqwest::header::ACCEPT, "application/json, text/event-stream")
+            .json(message);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(session) = &self.session_id {
+            request = request.header("Mcp-Session-Id", session);
+        }
+        request.send().await
+    }
+}
+
+enum Connection {
+    Stdio(StdioConnection),
+    Http(HttpConnection),
+}
+
+impl Connection {
+    fn is_alive(&mut self) -> bool {
+        match self {
+            Self::Stdio(c) => c.is_alive(),
+            // HTTP failures surface on the next request and trigger a reconnect there
+            Self::Http(_) => true,
+        }
+    }
+
+    async fn notify(&mut self, message: &Value) -> anyhow::Result<()> {
+        match self {
+            Self::Stdio(c) => c.send(message).await,
+            Self::Http(c) => {
+                c.post(message).await?.error_for_status()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Result of a JSON-RPC response message: the `result`, or the server's error
+fn parse_response(message: &Value) -> anyhow::Result<Value> {
+    if let Some(error) = message.get("error") {
+        let err: JsonRpcError = serde_json::from_value(error.clone())?;
+        return Err(anyhow!("MCP Error: {} (code {})", err.message, err.code));
+    }
+    Ok(message["result"].clone())
+}
+
+/// Split complete Server-Sent Events off the front of `buffer`, returning their `data` payloads
+fn drain_sse_events(buffer: &mut Vec<u8>) -> Vec<String> {
+    let mut events = Vec::new();
+    while let Some(pos) = buffer.windows(2).position(|w| w == b"\n\n") {
+        // Only complete events are decoded, so multi-byte characters split across chunks survive
+        let bytes: Vec<u8> = buffer.drain(..pos + 2).collect();
+        let event = String::from_utf8_lossy(&bytes);
+        let data: Vec<&str> = event.lines()
+            .filter_map(|l| l.strip_prefix("data:"))
+            .map(|d| d.strip_prefix(' ').unwrap_or(d))
+            .collect();
+        if !data.is_empty() {
+            events.push(data.join("\n"));
+        }
+    }
+    events
+}
+
+/// Longest wait for the next chunk of an MCP event stream
+const SSE_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A connection that stayed up this long no longer counts towards `max_restarts`
+const STABLE_UPTIME: Duration = Duration::from_secs(300);
+
 /// MCP Server Manager
+///
+/// Owns the connection: spawns the process or opens the HTTP session, performs the MCP
+/// handshake, and restarts/reconnects (up to `max_restarts` times in a row) when it breaks.
 pub struct McpServer {
     name: String,
-    stdin: Mutex<ChildStdin>,
-    stdout: Mutex<BufReader<ChildStdout>>,
-    request_counter: Mutex<u64>,
+    config: McpServerConfig,
+    conn: Mutex<Option<Connection>>,
+    request_counter: AtomicU64,
     roots: Mutex<Vec<String>>,
-    _child: Mutex<Child>, // Keep child alive
+    restarts: AtomicU32,
+    /// When the current connection was established
+    connected_at: std::sync::Mutex<Instant>,
 }
 
 impl McpServer {
     pub async fn spawn(name: &str, command: &str, args: &[String]) -> anyhow::Result<Arc<Self>> {
-        info!("Spawning MCP server '{}' via {} {:?}...", name, command, args);
-        
-        let mut child = Command::new(command)
-            .args(args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit()) // Forward stderr to main logs
-            .spawn()
-            .context("Failed to spawn MCP server process")?;
+        Self::connect(McpServerConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.to_vec(),
+            max_restarts: default_max_restarts(),
+            ..Default::default()
+        }).await
+    }
 
-        let stdin = child.stdin.take().context("Failed to open stdin")?;
-        let stdout = child.stdout.take().context("Failed to open stdout")?;
+    /// Connect to a remote server over streamable HTTP
+    pub async fn connect_http(name: &str, url: &str, bearer_token: Option<String>) -> anyhow::Result<Arc<Self>> {
+        Self::connect(McpServerConfig {
+            name: name.to_string(),
+            url: Some(url.to_string()),
+            bearer_token,
+            max_restarts: default_max_restarts(),
+            ..Default::default()
+        }).await
+    }
 
+    pub async fn connect(config: McpServerConfig) -> anyhow::Result<Arc<Self>> {
         let server = Arc::new(Self {
-            name: name.to_string(),
-            stdin: Mutex::new(stdin),
-            stdout: Mutex::new(BufReader::new(stdout)),
-            request_counter: Mutex::new(0),
+            name: config.name.clone(),
+            config,
+            conn: Mutex::new(None),
+            request_counter: AtomicU64::new(0),
             roots: Mutex::new(Vec::new()),
-            _child: Mutex::new(child),
+            restarts: AtomicU32::new(0),
+            connected_at: std::sync::Mutex::new(Instant::now()),
         });
 
-        // Initialize MCP
-        server.initialize().await?;
-        
+        let conn = server.start().await?;
+        *server.conn.lock().await = Some(conn);
+        *server.connected_at.lock().unwrap() = Instant::now();
         Ok(server)
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the server is currently connected
+    pub async fn is_alive(&self) -> bool {
+        self.conn.lock().await.as_mut().is_some_and(|c| c.is_alive())
+    }
+
+    /// Stop the server process or end the HTTP session; the next call reconnects
+    pub async fn shutdown(&self) {
+        match self.conn.lock().await.take() {
+            Some(Connection::Stdio(mut conn)) => {
+                info!("Stopping MCP server '{}'", self.name);
+                let _ = conn.child.kill().await;
+            }
+            Some(Connection::Http(conn)) => {
+                if let Some(session) = &conn.session_id {
+                    let mut request = conn.client.delete(&conn.url).header("Mcp-Session-Id", session);
+                    if let Some(token) = &conn.token {
+                        request = request.bearer_auth(token);
+                    }
+                    let _ = request.send().await;
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Open the transport and run the MCP handshake
+    async fn start(&self) -> anyhow::Result<Connection> {
+        let mut conn = match &self.config.url {
+            Some(url) => {
+                info!("Connecting to MCP server '{}' at {}...", self.name, url);
+                Connection::Http(HttpConnection {
+                    client: reqwest::Client::builder().connect_timeout(Duration::from_secs(10)).build()?,
+                    url: url.clone(),
+                    token: self.config.bearer_token(),
+                    session_id: None,
+                })
+            }
+            None => {
+                info!("Spawning MCP server '{}' via {} {:?}...", self.name, self.config.command, self.config.args);
+
+                let mut child = Command::new(&self.config.command)
+                    .args(&self.config.args)
+                    .envs(&self.config.env)
+                    .kill_on_drop(true)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit()) // Forward stderr to main logs
+                    .spawn()
+                    .context("Failed to spawn MCP server process")?;
+
+                let stdin = child.stdin.take().context("Failed to open stdin")?;
+                let stdout = child.stdout.take().context("Failed to open stdout")?;
+                Connection::Stdio(StdioConnection { child, stdin, stdout: BufReader::new(stdout) })
+            }
+        };
+
+        self.initialize(&mut conn).await?;
+        Ok(conn)
+    }
+
     /// Add a root directory to this server
     pub async fn add_root(&self, path: &str) -> anyhow::Result<()> {
         let mut roots = self.roots.lock().await;
@@ -108,76 +330,171 @@ impl McpServer {
         Ok(())
     }
 
+    /// Send a request, restarting/reconnecting once if the transport has failed
     async fn call(&self, method: &str, params: Option<Value>) -> anyhow::Result<Value> {
-        let mut id_lock = self.request_counter.lock().await;
-        *id_lock += 1;
-        let id = *id_lock;
-        drop(id_lock);
+        // Requests are serialized so responses can't be read by the wrong caller
+        let mut guard = self.conn.lock().await;
+
+        for attempt in 0..2 {
+            if !guard.as_mut().is_some_and(|c| c.is_alive()) {
+                *guard = None;
+                if self.connected_at.lock().unwrap().elapsed() >= STABLE_UPTIME {
+                    // The last connection was healthy for a while; this is a new failure streak
+                    self.restarts.store(0, Ordering::SeqCst);
+                }
+                let restarts = self.restarts.fetch_add(1, Ordering::SeqCst) + 1;
+                if restarts > self.config.max_restarts {
+                    return Err(anyhow!("MCP server '{}' exceeded its restart limit ({})", self.name, self.config.max_restarts));
+                }
+                warn!("MCP server '{}' is not connected, restarting ({}/{})", self.name, restarts, self.config.max_restarts);
+                crate::emit_event!(AgencyEvent::StatusUpdate(format!("Restarting MCP server '{}'", self.name)));
+                tokio::time::sleep(Duration::from_millis(250 << (restarts - 1).min(5))).await;
+                *guard = Some(self.start().await?);
+                *self.connected_at.lock().unwrap() = Instant::now();
+            }
+
+            let conn = guard.as_mut().context("MCP connection unavailable")?;
+            match self.request(conn, method, params.clone()).await {
+                Ok(result) => return result,
+                Err(e) if attempt == 0 => {
+                    warn!("MCP server '{}' transport failed ({}), restarting", self.name, e);
+                    if let Some(Connection::Stdio(mut dead)) = guard.take() {
+                        let _ = dead.child.kill().await;
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(anyhow!("MCP server '{}' unavailable", self.name))
+    }
 
-        let request = JsonRpcRequest {
+    /// One JSON-RPC round trip. The outer error is a transport failure; the inner one
+    /// is an error reported by the server.
+    async fn request(&self, conn: &mut Connection, method: &str, params: Option<Value>) -> anyhow::Result<anyhow::Result<Value>> {
+        let id = self.request_counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let request = serde_json::to_value(JsonRpcRequest {
             jsonrpc: "2.0".to_string(),
             method: method.to_string(),
             params,
             id: json!(id),
-        };
-
-        let request_str = serde_json::to_string(&request)? + "\n";
-        debug!("MCP Request to {}: {}", self.name, request_str.trim());
+        })?;
+        debug!("MCP Request to {}: {}", self.name, request);
 
-        // Send request
-        {
-            let mut stdin = self.stdin.lock().await;
-            stdin.write_all(request_str.as_bytes()).await?;
-            stdin.flush().await?;
+        match conn {
+            Connection::Stdio(c) => self.stdio_round_trip(c, &request, id).await,
+            Connection::Http(c) => self.http_round_trip(c, &request, id).await,
         }
+    }
+
+    async fn stdio_round_trip(&self, conn: &mut StdioConnection, request: &Value, id: u64) -> anyhow::Result<anyhow::Result<Value>> {
+        conn.send(request).await?;
 
         // Listen for response
-        let mut reader = self.stdout.lock().await;
-        
         loop {
             let mut line = String::new();
-            reader.read_line(&mut line).await?;
+            conn.stdout.read_line(&mut line).await?;
             if line.is_empty() { return Err(anyhow!("MCP server disconnected")); }
+            if line.trim().is_empty() { continue; }
             
             debug!("MCP Data from {}: {}", self.name, line.trim());
-            let response: Value = serde_json::from_str(&line)?;
+            let message: Value = serde_json::from_str(&line)?;
 
             // Case 1: Response to our request
-            if response["id"] == json!(id) {
-                if let Some(error) = response.get("error") {
-                    let err: JsonRpcError = serde_json::from_value(error.clone())?;
-                    return Err(anyhow!("MCP Error: {} (code {})", err.message, err.code));
-                }
-                return Ok(response["result"].clone());
+            if message["id"] == json!(id) && message.get("method").is_none() {
+                return Ok(parse_response(&message));
             }
 
             // Case 2: Server-initiated request (e.g. roots/list)
-            if response.get("method").is_some() && response.get("id").is_some() {
-                let method = response["method"].as_str().unwrap_or("");
-                let req_id = response["id"].clone();
-                
-                if method == "roots/list" {
-                    let roots_guard = self.roots.lock().await;
-                    let roots_list: Vec<Value> = roots_guard.iter().map(|r| json!({ "uri": r })).collect();
-                    let res = json!({
-                        "jsonrpc": "2.0",
-                        "id": req_id,
-                        "result": { "roots": roots_list }
-                    });
-                    
-                    // Reply to server
-                    let mut stdin = self.stdin.lock().await;
-                    stdin.write_all((serde_json::to_string(&res)? + "\n").as_bytes()).await?;
-                    stdin.flush().await?;
-                    continue;
+            if let Some(reply) = self.handle_server_request(&message).await {
+                conn.send(&reply).await?;
+            }
+        }
+    }
+
+    async fn http_round_trip(&self, conn: &mut HttpConnection, request: &Value, id: u64) -> anyhow::Result<anyhow::Result<Value>> {
+        let response = conn.post(request).await?;
+        if let Some(session) = response.headers().get("mcp-session-id").and_then(|v| v.to_str().ok()) {
+            conn.session_id = Some(session.to_string());
+        }
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            // Reconnecting with the same credentials won't help
+            return Ok(Err(anyhow!("MCP server '{}' rejected our credentials ({})", self.name, status)));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND && conn.session_id.is_some() {
+            conn.session_id = None;
+            return Err(anyhow!("MCP session expired"));
+        }
+        if !status.is_success() {
+            return Err(anyhow!("MCP HTTP error {}", status));
+        }
+
+        let is_stream = response.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+        if !is_stream {
+            let messages = match response.json::<Value>().await? {
+                Value::Array(batch) => batch,
+                single => vec![single],
+            };
+            return messages.iter()
+                .find(|m| m["id"] == json!(id))
+                .map(|m| Ok(parse_response(m)))
+                .unwrap_or_else(|| Err(anyhow!("MCP response did not include request {}", id)));
+        }
+
+        // The server may interleave its own requests before answering ours
+        let mut stream = response.bytes_stream();
+        let mut buffer = Vec::new();
+        loop {
+            let chunk = match tokio::time::timeout(SSE_IDLE_TIMEOUT, stream.next()).await {
+                Ok(Some(chunk)) => chunk?,
+                Ok(None) => break,
+                // Not retried: the server may still be acting on the request
+                Err(_) => return Ok(Err(anyhow!("MCP server '{}' sent nothing for {}s", self.name, SSE_IDLE_TIMEOUT.as_secs()))),
+            };
+            // CR never occurs inside a UTF-8 sequence, so CRLF line endings can be dropped per chunk
+            buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+            for data in drain_sse_events(&mut buffer) {
+                debug!("MCP Event from {}: {}", self.name, data);
+                let message: Value = serde_json::from_str(&data)?;
+                if message["id"] == json!(id) && message.get("method").is_none() {
+                    return Ok(parse_response(&message));
+                }
+                if let Some(reply) = self.handle_server_request(&message).await {
+                    conn.post(&reply).await?;
                 }
             }
         }
+        Err(anyhow!("MCP event stream ended before the response"))
     }
 
-    async fn initialize(&self) -> anyhow::Result<()> {
+    /// Reply to a request the server sent us, if it is one we understand
+    async fn handle_server_request(&self, message: &Value) -> Option<Value> {
+        let method = message.get("method")?.as_str()?;
+        let req_id = message.get("id")?.clone();
+        match method {
+            "roots/list" => {
+                let roots_guard = self.roots.lock().await;
+                let roots_list: Vec<Value> = roots_guard.iter().map(|r| json!({ "uri": r })).collect();
+                Some(json!({ "jsonrpc": "2.0", "id": req_id, "result": { "roots": roots_list } }))
+            }
+            "ping" => Some(json!({ "jsonrpc": "2.0", "id": req_id, "result": {} })),
+            _ => Some(json!({ "jsonrpc": "2.0", "id": req_id, "error": { "code": -32601, "message": format!("Method not found: {}", method) } })),
+        }
+    }
+
+    async fn initialize(&self, conn: &mut Connection) -> anyhow::Result<()> {
+        // Streamable HTTP was introduced in the 2025-03-26 revision
+        let protocol_version = match conn {
+            Connection::Stdio(_) => "2024-11-05",
+            Connection::Http(_) => "2025-03-26",
+        };
         let params = json!({
-            "protocolVersion": "2024-11-05",
+            "protocolVersion": protocol_version,
             "capabilities": {
                 "roots": {
                     "listChanged": true
@@ -189,17 +506,13 @@ impl McpServer {
             }
         });
 
-        self.call("initialize", Some(params)).await?;
+        self.request(conn, "initialize", Some(params)).await??;
         
         // Send initialized notification
-        let notification = json!({
+        conn.notify(&json!({
             "jsonrpc": "2.0",
             "method": "notifications/initialized"
-        });
-        
-        let mut stdin = self.stdin.lock().await;
-        stdin.write_all((serde_json::to_string(&notification)? + "\n").as_bytes()).await?;
-        stdin.flush().await?;
+        })).await?;
 
         Ok(())
     }
@@ -246,6 +559,10 @@ impl Tool for McpProxyTool {
         self.definition.input_schema.clone()
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         info!("Executing MCP tool {}...", self.name());
         let result = self.server.call_tool(&self.definition.name, params).await
@@ -269,4 +586,47 @@ impl Tool for McpProxyTool {
             Ok(ToolOutput::success(result, summary))
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal MCP server that answers every request and exits after one `tools/list`
+    const FLAKY_SERVER: &str = r#"while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -z "$id" ] && continue
+  printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo","inputSchema":{"type":"object"}}]}}\n' "$id"
+  case "$line" in *tools/list*) exit 0;; esac
+done"#;
+
+    #[test]
+    fn test_drain_sse_events() {
+        let mut buffer = b"event: message\ndata: {\"id\":1}\n\ndata: {\"id\":\ndata: 2}\n\ndata: \xc3".to_vec();
+        assert_eq!(drain_sse_events(&mut buffer), vec!["{\"id\":1}".to_string(), "{\"id\":\n2}".to_string()]);
+        // The rest of a split `é` arrives in the next chunk
+        buffer.extend(b"\xa9\n\n");
+        assert_eq!(drain_sse_events(&mut buffer), vec!["é".to_string()]);
+        assert!(buffer.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_server_restarts_after_crash() {
+        let server = McpServer::spawn("flaky", "sh", &["-c".to_string(), FLAKY_SERVER.to_string()]).await.unwrap();
+
+        assert_eq!(server.list_tools().await.unwrap()[0].name, "echo");
+        // The process exited after answering; the next call respawns it transparently
+        assert_eq!(server.list_tools().await.unwrap().len(), 1);
+        assert_eq!(server.restarts.load(Ordering::SeqCst), 1);
+
+        // After a stable stretch the count starts over instead of accumulating
+        if let Some(earlier) = Instant::now().checked_sub(STABLE_UPTIME) {
+            *server.connected_at.lock().unwrap() = earlier;
+            assert_eq!(server.list_tools().await.unwrap().len(), 1);
+            assert_eq!(server.restarts.load(Ordering::SeqCst), 1);
+        }
+
+        server.shutdown().await;
+        assert!(!server.is_alive().await);
+    }
+}
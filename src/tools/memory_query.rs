@@ -8,7 +8,7 @@ use std::sync::Arc;
 use tracing::debug;
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCachePolicy};
 use crate::memory::Memory;
 
 /// Tool for querying the memory system
@@ -61,6 +61,10 @@ impl Tool for MemoryQueryTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::default().with_ttl(std::time::Duration::from_secs(60))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let query = params["query"]
             .as_str()
@@ -7,6 +7,13 @@ mod web_search;
 mod code_exec;
 mod memory_query;
 mod artifact;
+mod filesystem;
+mod git;
+mod sql;
+mod http;
+mod email;
+mod calendar;
+mod rss;
 mod sandbox;
 mod codebase;
 mod system;
@@ -18,10 +25,18 @@ mod speaker_rs;
 mod science;
 mod models;
 mod vision;
+mod uploads;
 mod mcp;
 mod skills;
+mod catalog;
 mod a2a;
 mod task_spawner;
+mod scheduler;
+mod permissions;
+mod tool_cache;
+mod analytics;
+mod side_effects;
+mod pipeline;
 mod watchdog;
 mod notify;
 mod swarm_bounty;
@@ -38,6 +53,13 @@ pub use speaker_rs::SpeakerRsTool;
 pub use code_exec::CodeExecTool;
 pub use memory_query::MemoryQueryTool;
 pub use artifact::ArtifactTool;
+pub use filesystem::FileSystemTool;
+pub use git::GitTool;
+pub use sql::SqlTool;
+pub use http::HttpTool;
+pub use email::{EmailTool, EmailConfig};
+pub use calendar::{CalendarTool, CalendarSource, CalendarEvent};
+pub use rss::{RssTool, FeedItem};
 pub use sandbox::SandboxTool;
 pub use codebase::CodebaseTool;
 pub use system::SystemTool;
@@ -45,13 +67,21 @@ pub use knowledge_graph::KnowledgeGraphTool;
 pub use agency_control::AgencyControlTool;
 pub use visualization::VisualizationTool;
 pub use science::ScienceTool;
-pub use models::ModelManager;
+pub use models::{ModelInfo, ModelManager};
 pub use vision::VisionTool;
+pub use uploads::{is_file_id, UploadKind, UploadStore, UploadedFile};
 pub use dynamic::{DynamicTool, ForgeTool};
 pub use a2a::{PeerAgentTool, RemoteAgencyTool, AnonymousAgencyTool};
-pub use mcp::{McpServer, McpProxyTool};
+pub use mcp::{McpServer, McpServerConfig, McpProxyTool};
 pub use skills::{MarkdownSkill, SkillLoader};
+pub use catalog::{fetch_catalog, install_catalog_items, item_digest, CatalogConfig, CatalogItem, FileChange, FileStatus, ItemKind, Trust};
 pub use task_spawner::TaskSpawnerTool;
+pub use scheduler::SchedulerTool;
+pub use permissions::{current_caller, ToolCapability, ToolCaller, PermissionGrant, PermissionPolicy};
+pub use tool_cache::{ToolCache, ToolCachePolicy, MAX_TOOL_CACHE_ENTRIES};
+pub use analytics::{ToolAnalytics, ToolCallRecord, ToolStats, TOOL_ANALYTICS};
+pub use side_effects::{RevertReport, SideEffectJournal, SideEffectRecord, ToolUndo};
+pub use pipeline::{PipelineTool, PipelineStep};
 pub use watchdog::WatchdogTool;
 pub use notify::NotifyTool;
 pub use swarm_bounty::SwarmBountyTool;
@@ -67,10 +97,39 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::path::{Path, PathBuf};
-use tokio::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, Notify, RwLock};
+
+/// Limit for a single tool call unless the tool declares its own
+pub const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 120;
+
+/// Capabilities that mark a tool as changing state outside the agency
+pub const SIDE_EFFECT_CAPABILITIES: [ToolCapability; 3] = [ToolCapability::FilesystemWrite, ToolCapability::Shell, ToolCapability::SpendMoney];
+
+tokio::task_local! {
+    /// Cancellation scope of the session a call runs in
+    static CANCEL_SCOPE: Arc<Notify>;
+}
+
+/// Run `fut` as its own cancellation scope: when it is dropped (e.g. its task is aborted
+/// because the user stopped the turn), tool calls in the scope are cancelled and calls
+/// of other sessions keep running
+pub async fn cancel_scope<F: Future>(fut: F) -> F::Output {
+    struct CancelOnDrop(Arc<Notify>);
+    impl Drop for CancelOnDrop {
+        fn drop(&mut self) {
+            self.0.notify_waiters();
+        }
+    }
+    let scope = Arc::new(Notify::new());
+    let _guard = CancelOnDrop(scope.clone());
+    CANCEL_SCOPE.scope(scope, fut).await
+}
 
 /// Output from a tool execution
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -117,6 +176,59 @@ impl ToolOutput {
             error: Some(error),
         }
     }
+
+    /// Failure listing schema violations so the agent can fix the call and retry
+    pub fn invalid_parameters(tool: &str, schema: &Value, violations: Vec<ParameterViolation>) -> Self {
+        let details: Vec<String> = violations.iter().map(|v| format!("{}: {}", v.path, v.message)).collect();
+        let error = format!("Invalid parameters for '{}': {}", tool, details.join("; "));
+        Self {
+            success: false,
+            data: json!({
+                "error": "invalid_parameters",
+                "tool": tool,
+                "violations": violations,
+                "expected_schema": schema,
+            }),
+            summary: format!("Error: {}", error),
+            error: Some(error),
+        }
+    }
+}
+
+/// A single mismatch between tool parameters and the tool's JSON schema
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParameterViolation {
+    /// JSON pointer to the offending value ("/" for the root object)
+    pub path: String,
+    pub message: String,
+}
+
+/// Validate `params` against a tool's JSON schema. Schemas that fail to compile are not enforced.
+pub fn validate_parameters(schema: &Value, params: &Value) -> Result<(), Vec<ParameterViolation>> {
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(v) => v,
+        Err(e) => {
+            tracing::debug!("Skipping parameter validation, schema does not compile: {}", e);
+            return Ok(());
+        }
+    };
+    let violations: Vec<ParameterViolation> = validator.iter_errors(params)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            ParameterViolation {
+                path: if path.is_empty() { "/".to_string() } else { path },
+                message: e.to_string(),
+            }
+        })
+        .collect();
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+/// Partial output streamed by a tool while it runs
+#[derive(Debug, Clone)]
+pub struct ToolChunk {
+    pub tool: String,
+    pub chunk: String,
 }
 
 /// A tool call request parsed from LLM output
@@ -150,6 +262,43 @@ pub trait Tool: Send + Sync {
         })
     }
 
+    /// Upper bound on a single call; `None` lets the call run until it finishes or is cancelled
+    fn timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(DEFAULT_TOOL_TIMEOUT_SECS))
+    }
+
+    /// Capabilities this tool needs, checked against the registry's `PermissionPolicy`
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadOnly]
+    }
+
+    /// How `ToolRegistry` may cache results; tools with side effects are never cached
+    fn cache_policy(&self) -> ToolCachePolicy {
+        if self.capabilities().iter().any(|c| SIDE_EFFECT_CAPABILITIES.contains(c)) {
+            ToolCachePolicy::no_cache()
+        } else {
+            ToolCachePolicy::default()
+        }
+    }
+
+    /// Whether this call changes state; such calls are simulated in dry-run mode
+    fn has_side_effects(&self, _params: &Value) -> bool {
+        self.capabilities().iter().any(|c| SIDE_EFFECT_CAPABILITIES.contains(c))
+    }
+
+    /// State needed to reverse this call, captured just before it runs; `None` if it cannot be undone
+    async fn prepare_undo(&self, _params: &Value) -> Option<ToolUndo> {
+        None
+    }
+
+    /// Describe what the call would do without doing it (dry-run mode)
+    fn dry_run(&self, params: &Value) -> ToolOutput {
+        ToolOutput::success(
+            json!({ "dry_run": true, "tool": self.name(), "parameters": params, "capabilities": self.capabilities() }),
+            format!("[DRY RUN] Would call '{}' with {}", self.name(), params)
+        )
+    }
+
     /// Perform a security check before execution (FPF SOTA Protection)
     async fn security_oracle(&self, _params: &Value) -> AgentResult<bool> {
         // Default: Passive (Assume safe or handled by validator)
@@ -159,18 +308,42 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with the given parameters
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput>;
 
+    /// Execute while sending partial output through `progress` as it is produced.
+    /// Long-running tools override this; the default just runs `execute`.
+    async fn execute_stream(&self, params: Value, _progress: mpsc::UnboundedSender<String>) -> AgentResult<ToolOutput> {
+        self.execute(params).await
+    }
+
     /// Whether this tool requires explicit human confirmation
     fn requires_confirmation(&self) -> bool {
         false
     }
+
+    /// Per-call confirmation, for tools where only some actions are consequential
+    fn requires_confirmation_for(&self, _params: &Value) -> bool {
+        self.requires_confirmation()
+    }
 }
 
 /// Registry for available tools with built-in caching
 pub struct ToolRegistry {
     tools: RwLock<HashMap<String, Arc<dyn Tool>>>,
-    cache: Arc<Mutex<HashMap<String, ToolOutput>>>,
+    cache: Arc<Mutex<ToolCache>>,
     custom_tools_dir: PathBuf,
     standard_tools_dir: PathBuf,
+    policy: RwLock<PermissionPolicy>,
+    /// Wakes every in-flight call on an emergency stop; a single session is stopped through its `cancel_scope`
+    cancel: Notify,
+    /// Simulate side-effecting calls instead of running them
+    dry_run: AtomicBool,
+    /// Side-effecting calls per turn, for rollback to a checkpoint
+    journal: SideEffectJournal,
+    /// Per-tool rate and concurrency limits from the safety policy
+    limiter: crate::safety::ToolLimiter,
+    /// Registered tools switched off at runtime: hidden from agents and refused when called
+    disabled: RwLock<HashSet<String>>,
+    /// Where every call is logged (`TOOL_ANALYTICS` unless injected)
+    analytics: Arc<ToolAnalytics>,
 }
 
 impl ToolRegistry {
@@ -178,12 +351,49 @@ impl ToolRegistry {
     pub fn new(custom_dir: impl Into<PathBuf>, standard_dir: impl Into<PathBuf>) -> Self {
         Self {
             tools: RwLock::new(HashMap::new()),
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(Mutex::new(ToolCache::default())),
             custom_tools_dir: custom_dir.into(),
             standard_tools_dir: standard_dir.into(),
+            policy: RwLock::new(PermissionPolicy::from_env()),
+            cancel: Notify::new(),
+            dry_run: AtomicBool::new(std::env::var("AGENCY_DRY_RUN").map(|v| v == "1" || v == "true").unwrap_or(false)),
+            journal: SideEffectJournal::default(),
+            limiter: crate::safety::ToolLimiter::from_env(),
+            disabled: RwLock::new(disabled_from_env()),
+            analytics: TOOL_ANALYTICS.clone(),
         }
     }
 
+    /// Log calls to `analytics` instead of the process-wide log
+    pub fn with_analytics(mut self, analytics: Arc<ToolAnalytics>) -> Self {
+        self.analytics = analytics;
+        self
+    }
+
+    /// Toggle dry-run mode: side-effecting tools return a plan instead of acting
+    pub fn set_dry_run(&self, enabled: bool) {
+        self.dry_run.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::SeqCst)
+    }
+
+    pub fn journal(&self) -> &SideEffectJournal {
+        &self.journal
+    }
+
+    /// Abort every tool call currently in flight, across all sessions (the kill switch).
+    /// Dropped calls kill their child processes; calls started afterwards are unaffected.
+    pub fn cancel_running(&self) {
+        self.cancel.notify_waiters();
+    }
+
+    /// Replace the tool permission policy
+    pub async fn set_policy(&self, policy: PermissionPolicy) {
+        *self.policy.write().await = policy;
+    }
+
     /// Register a tool
     #[allow(dead_code)]
     pub async fn register<T: Tool + 'static + Default>(&self) {
@@ -237,12 +447,48 @@ impl ToolRegistry {
         Ok(count)
     }
 
-    /// Get all tool names
+    /// Get all enabled tool names
     pub async fn tool_names(&self) -> Vec<String> {
+        let tools = self.tools.read().await;
+        let disabled = self.disabled.read().await;
+        tools.keys().filter(|name| !disabled.contains(*name)).cloned().collect()
+    }
+
+    /// Every registered tool name, enabled or not
+    pub async fn all_tool_names(&self) -> Vec<String> {
         let tools = self.tools.read().await;
         tools.keys().cloned().collect()
     }
 
+    pub async fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.read().await.contains(name)
+    }
+
+    /// Switch a registered tool on or off; returns whether its state changed
+    pub async fn set_enabled(&self, name: &str, enabled: bool) -> AgentResult<bool> {
+        if !self.tools.read().await.contains_key(name) {
+            return Err(crate::agent::AgentError::Validation(format!("Unknown tool '{}'", name)));
+        }
+        let mut disabled = self.disabled.write().await;
+        let changed = if enabled { disabled.remove(name) } else { disabled.insert(name.to_string()) };
+        if changed {
+            tracing::info!("Tool '{}' {}", name, if enabled { "enabled" } else { "disabled" });
+        }
+        Ok(changed)
+    }
+
+    /// Where a tool comes from: `custom` (forged, in the custom directory), `standard`
+    /// (promoted) or `builtin`
+    pub fn tool_source(&self, name: &str) -> &'static str {
+        if self.custom_tools_dir.join(format!("{}.json", name)).exists() {
+            "custom"
+        } else if self.standard_tools_dir.join(format!("{}.json", name)).exists() {
+            "standard"
+        } else {
+            "builtin"
+        }
+    }
+
     /// Generate a combined schema for all tools (for LLM prompt)
     #[allow(dead_code)]
     pub async fn generate_tools_prompt(&self) -> String {
@@ -259,7 +505,8 @@ impl ToolRegistry {
         let mut prompt = String::from("Available Tools:\n\n");
         
         let tools = self.tools.read().await;
-        let mut names: Vec<_> = allowed_names.iter().filter(|n| tools.contains_key(*n)).collect();
+        let disabled = self.disabled.read().await;
+        let mut names: Vec<_> = allowed_names.iter().filter(|n| tools.contains_key(*n) && !disabled.contains(*n)).collect();
         names.sort();
 
         for name in names {
@@ -281,28 +528,144 @@ impl ToolRegistry {
         prompt
     }
 
-    /// Get a specific tool by name
+    /// Generate a GBNF grammar constraining a ReAct turn to the given tools
+    pub async fn generate_grammar(&self, allowed_names: &[String], require_thought: bool) -> String {
+        let tools = self.tools.read().await;
+        let disabled = self.disabled.read().await;
+        let mut schemas: Vec<(String, serde_json::Value)> = allowed_names.iter()
+            .filter(|n| !disabled.contains(*n))
+            .filter_map(|n| tools.get(n).map(|t| (n.clone(), t.parameters())))
+            .collect();
+        schemas.sort_by(|a, b| a.0.cmp(&b.0));
+        crate::agent::grammar::react_grammar(&schemas, require_thought)
+    }
+
+    /// Definitions (`name`, `description`, `parameters`) of the given enabled tools, sorted
+    /// by name, for providers with native tool calling
+    pub async fn tool_definitions(&self, allowed_names: &[String]) -> Vec<serde_json::Value> {
+        let tools = self.tools.read().await;
+        let disabled = self.disabled.read().await;
+        let mut names: Vec<&String> = allowed_names.iter().filter(|n| tools.contains_key(*n) && !disabled.contains(*n)).collect();
+        names.sort();
+        names.dedup();
+        names.into_iter()
+            .map(|name| json!({ "name": name, "description": tools[name].description(), "parameters": tools[name].parameters() }))
+            .collect()
+    }
+
+    /// Get a specific enabled tool by name
     pub async fn get_tool(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        if !self.is_enabled(name).await {
+            return None;
+        }
+        self.get_registered_tool(name).await
+    }
+
+    /// Get a registered tool by name, even if disabled
+    pub async fn get_registered_tool(&self, name: &str) -> Option<Arc<dyn Tool>> {
         let tools = self.tools.read().await;
         tools.get(name).cloned()
     }
 
-    /// Execute a tool call with caching
+    /// Execute a tool call with caching, as the `system` caller
     pub async fn execute(&self, call: &ToolCall) -> AgentResult<ToolOutput> {
+        self.execute_as(call, &ToolCaller::system()).await
+    }
+
+    /// Execute a tool call on behalf of `caller`, enforcing the permission policy
+    pub async fn execute_as(&self, call: &ToolCall, caller: &ToolCaller) -> AgentResult<ToolOutput> {
+        self.execute_streaming_as(call, caller, None).await
+    }
+
+    /// Execute a tool call on behalf of `caller`, forwarding partial output to `progress`
+    #[tracing::instrument(name = "tool_call", skip_all, fields(tool = %call.name, agent = %caller.agent, turn_id = ?caller.turn_id, success = tracing::field::Empty))]
+    pub async fn execute_streaming_as(&self, call: &ToolCall, caller: &ToolCaller, progress: Option<mpsc::UnboundedSender<ToolChunk>>) -> AgentResult<ToolOutput> {
+        let started = std::time::Instant::now();
+        let mut result = self.execute_checked(call, caller, progress).await;
+        // Nothing a tool returns reaches an agent's context (or a remote provider) unscrubbed
+        if let Ok(ref mut output) = result {
+            crate::safety::SECRET_SCANNER.check_output(&call.name, output);
+            crate::safety::PII_SCRUBBER.scrub_output(&call.name, caller.turn_id.as_deref(), output);
+        }
+
+        let error = match &result {
+            Ok(output) if output.success => None,
+            Ok(output) => Some(output.error.clone().unwrap_or_else(|| output.summary.clone())),
+            Err(e) => Some(e.to_string()),
+        };
+        tracing::Span::current().record("success", error.is_none());
+        let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        crate::safety::AUDIT_LOG.record_tool(&call.name, &caller.agent, &call.parameters, error.is_none(), latency_ms);
+        crate::orchestrator::budget::meter_tool_call();
+        self.analytics.record(&ToolCallRecord {
+            timestamp: chrono::Utc::now(),
+            tool: call.name.clone(),
+            params_hash: ToolCallRecord::hash_params(&call.parameters),
+            latency_ms,
+            success: error.is_none(),
+            agent: caller.agent.clone(),
+            turn_id: caller.turn_id.clone(),
+            error,
+        }).await;
+
+        result
+    }
+
+    async fn execute_checked(&self, call: &ToolCall, caller: &ToolCaller, progress: Option<mpsc::UnboundedSender<ToolChunk>>) -> AgentResult<ToolOutput> {
+        crate::orchestrator::KILL_SWITCH.check()?;
+        if !self.is_enabled(&call.name).await {
+            return Ok(ToolOutput::failure(format!("Tool '{}' is disabled", call.name)));
+        }
         let cache_key = format!("{}:{}", call.name, serde_json::to_string(&call.parameters)?);
-        
+
+        let tool = {
+            let tools = self.tools.read().await;
+            tools.get(&call.name).cloned()
+        };
+
+        // Permissions are checked before the cache so a cached result never bypasses the policy
+        if let Some(ref tool) = tool {
+            if let Err(e) = self.policy.read().await.check(caller, &call.name, &tool.capabilities()) {
+                crate::emit_event!(AgencyEvent::BoundaryCrossing(crate::orchestrator::event_bus::FPFBoundClaim {
+                    quadrant: LadeQuadrant::A,
+                    claim_id: format!("ACL-{}", call.name),
+                    content: e.to_string(),
+                }));
+                return Ok(ToolOutput::failure(format!("Permission denied: {}", e)));
+            }
+        }
+
+        // Reject malformed parameters with an error the agent can correct
+        if let Some(ref tool) = tool {
+            if let Err(violations) = validate_parameters(&tool.parameters(), &call.parameters) {
+                return Ok(ToolOutput::invalid_parameters(&call.name, &tool.parameters(), violations));
+            }
+        }
+
+        if let Some(ref tool) = tool {
+            if self.is_dry_run() && tool.has_side_effects(&call.parameters) {
+                tracing::info!("Dry run: skipping side effects of '{}'", call.name);
+                return Ok(tool.dry_run(&call.parameters));
+            }
+        }
+
         // Check cache
+        let cache_policy = tool.as_ref().map(|t| t.cache_policy()).unwrap_or_else(ToolCachePolicy::no_cache);
         {
-            let cache = self.cache.lock().await;
-            if let Some(output) = cache.get(&cache_key) {
+            let mut cache = self.cache.lock().await;
+            if let Some(output) = cache.get(&cache_key, &cache_policy) {
                 tracing::debug!("Cache Hit for tool: {}", call.name);
-                return Ok(output.clone());
+                return Ok(output);
             }
         }
 
-        let tool = {
-            let tools = self.tools.read().await;
-            tools.get(&call.name).cloned()
+        // Held until the call returns so concurrent calls are counted
+        let _permit = match self.limiter.acquire(&call.name) {
+            Ok(permit) => permit,
+            Err(e) => {
+                tracing::info!("Tool call refused: {}", e);
+                return Ok(ToolOutput::failure(e.to_string()));
+            }
         };
 
         let result = match tool {
@@ -317,7 +680,69 @@ impl ToolRegistry {
                     }));
                     return Ok(ToolOutput::failure(format!("Security Oracle blocked execution of tool '{}'", call.name)));
                 }
-                tool.execute(call.parameters.clone()).await?
+
+                // Journaled before running so a call that fails halfway can still be reverted
+                if tool.has_side_effects(&call.parameters) {
+                    self.journal.record(SideEffectRecord {
+                        tool: call.name.clone(),
+                        parameters: call.parameters.clone(),
+                        turn_id: caller.turn_id.clone(),
+                        undo: tool.prepare_undo(&call.parameters).await,
+                        timestamp: chrono::Utc::now(),
+                    }).await;
+                }
+
+                let cancelled = self.cancel.notified();
+                tokio::pin!(cancelled);
+                cancelled.as_mut().enable();
+                let scope = CANCEL_SCOPE.try_with(Arc::clone).unwrap_or_default();
+                let scope_cancelled = scope.notified();
+                tokio::pin!(scope_cancelled);
+                scope_cancelled.as_mut().enable();
+                let limit = tool.timeout();
+                let (chunk_tx, mut chunk_rx) = mpsc::unbounded_channel();
+                let run = async {
+                    let stream = permissions::with_caller(caller.clone(), tool.execute_stream(call.parameters.clone(), chunk_tx));
+                    match limit {
+                        Some(limit) => tokio::time::timeout(limit, stream).await.ok(),
+                        None => Some(stream.await),
+                    }
+                };
+                tokio::pin!(run);
+
+                // Partial output is forwarded as it arrives and kept so an interrupted call still reports it
+                let mut partial = String::new();
+                let mut forward = |chunk: String| {
+                    crate::emit_event!(AgencyEvent::ToolProgress { tool: call.name.clone(), chunk: chunk.clone() });
+                    if let Some(tx) = &progress {
+                        let _ = tx.send(ToolChunk { tool: call.name.clone(), chunk: chunk.clone() });
+                    }
+                    partial.push_str(&chunk);
+                };
+                let finished = loop {
+                    tokio::select! {
+                        finished = &mut run => break Some(finished),
+                        Some(chunk) = chunk_rx.recv() => forward(chunk),
+                        _ = &mut cancelled => break None,
+                        _ = &mut scope_cancelled => break None,
+                    }
+                };
+                while let Ok(chunk) = chunk_rx.try_recv() {
+                    forward(chunk);
+                }
+                let with_partial = |msg: String| if partial.is_empty() { msg } else { format!("{}\nPartial output:\n{}", msg, partial) };
+
+                match finished {
+                    Some(Some(output)) => output?,
+                    Some(None) => {
+                        tracing::warn!("Tool '{}' timed out after {:?}", call.name, limit.unwrap_or_default());
+                        return Ok(ToolOutput::failure(with_partial(format!("Tool '{}' timed out after {}s", call.name, limit.unwrap_or_default().as_secs()))));
+                    }
+                    None => {
+                        tracing::info!("Tool '{}' cancelled", call.name);
+                        return Ok(ToolOutput::failure(with_partial(format!("Tool '{}' was cancelled", call.name))));
+                    }
+                }
             },
             None => ToolOutput::failure(format!("Unknown tool: {}", call.name)),
         };
@@ -325,7 +750,7 @@ impl ToolRegistry {
         // Update cache if successful or specific failure
         if result.success {
             let mut cache = self.cache.lock().await;
-            cache.insert(cache_key, result.clone());
+            cache.insert(&call.name, cache_key, result.clone(), &cache_policy);
         }
 
         Ok(result)
@@ -333,9 +758,19 @@ impl ToolRegistry {
 
     /// Execute multiple tool calls in parallel
     pub async fn execute_parallel(&self, calls: &[ToolCall]) -> Vec<AgentResult<ToolOutput>> {
+        self.execute_parallel_as(calls, &ToolCaller::system()).await
+    }
+
+    /// Execute multiple tool calls in parallel on behalf of `caller`
+    pub async fn execute_parallel_as(&self, calls: &[ToolCall], caller: &ToolCaller) -> Vec<AgentResult<ToolOutput>> {
+        self.execute_parallel_streaming_as(calls, caller, None).await
+    }
+
+    /// Execute multiple tool calls in parallel, forwarding partial output from all of them to `progress`
+    pub async fn execute_parallel_streaming_as(&self, calls: &[ToolCall], caller: &ToolCaller, progress: Option<mpsc::UnboundedSender<ToolChunk>>) -> Vec<AgentResult<ToolOutput>> {
         let mut futures = Vec::new();
         for call in calls {
-            futures.push(self.execute(call));
+            futures.push(self.execute_streaming_as(call, caller, progress.clone()));
         }
         futures_util::future::join_all(futures).await
     }
@@ -386,6 +821,15 @@ impl ToolRegistry {
     }
 }
 
+/// Tools to start disabled, from `AGENCY_DISABLED_TOOLS` (comma-separated names)
+fn disabled_from_env() -> HashSet<String> {
+    std::env::var("AGENCY_DISABLED_TOOLS").unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
 impl Default for ToolRegistry {
     fn default() -> Self {
         Self::new("custom_tools", "standard_tools")
@@ -419,6 +863,26 @@ mod tests {
         assert!(names.contains(&"mock_tool".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_disabled_tool_is_hidden_and_refused() {
+        let registry = ToolRegistry::default();
+        registry.register::<MockTool>().await;
+
+        assert!(registry.set_enabled("mock_tool", false).await.unwrap());
+        assert!(!registry.set_enabled("mock_tool", false).await.unwrap());
+        assert!(registry.set_enabled("missing", false).await.is_err());
+        assert!(!registry.tool_names().await.contains(&"mock_tool".to_string()));
+        assert!(registry.get_tool("mock_tool").await.is_none());
+        assert!(registry.get_registered_tool("mock_tool").await.is_some());
+
+        let call = ToolCall { name: "mock_tool".to_string(), parameters: json!({}) };
+        let output = registry.execute(&call).await.unwrap();
+        assert!(!output.success);
+
+        registry.set_enabled("mock_tool", true).await.unwrap();
+        assert!(registry.execute(&call).await.unwrap().success);
+    }
+
     #[tokio::test]
     async fn test_tool_execution_caching() {
         let registry = ToolRegistry::default();
@@ -438,6 +902,165 @@ mod tests {
         assert_eq!(res1, res2);
     }
 
+    #[tokio::test]
+    async fn test_permission_policy_blocks_before_cache() {
+        let registry = ToolRegistry::default();
+        registry.register::<MockTool>().await;
+        let call = ToolCall { name: "mock_tool".to_string(), parameters: json!({}) };
+        assert!(registry.execute(&call).await.unwrap().success);
+
+        let mut policy = PermissionPolicy::default();
+        policy.agents.insert("Researcher".to_string(), PermissionGrant { deny_tools: vec!["mock_tool".to_string()], ..Default::default() });
+        registry.set_policy(policy).await;
+
+        let denied = registry.execute_as(&call, &ToolCaller::new("Researcher", None)).await.unwrap();
+        assert!(!denied.success);
+        assert!(registry.execute(&call).await.unwrap().success);
+    }
+
+    struct SlowTool(Option<Duration>);
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> String { "slow_tool".to_string() }
+        fn description(&self) -> String { "Never finishes in time".to_string() }
+        fn parameters(&self) -> Value { json!({"type": "object"}) }
+        fn timeout(&self) -> Option<Duration> { self.0 }
+        async fn execute(&self, _params: Value) -> AgentResult<ToolOutput> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(ToolOutput::success_str("done"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_timeout_and_cancellation() {
+        let call = ToolCall { name: "slow_tool".to_string(), parameters: json!({}) };
+
+        let registry = ToolRegistry::default();
+        registry.register_instance(SlowTool(Some(Duration::from_millis(20)))).await;
+        let out = registry.execute(&call).await.unwrap();
+        assert!(!out.success);
+        assert!(out.summary.contains("timed out"));
+
+        let registry = Arc::new(ToolRegistry::default());
+        registry.register_instance(SlowTool(None)).await;
+        let running = tokio::spawn({
+            let registry = registry.clone();
+            let call = call.clone();
+            async move { registry.execute(&call).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        registry.cancel_running();
+        let out = running.await.unwrap().unwrap();
+        assert!(out.summary.contains("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_scope_leaves_other_sessions_running() {
+        let call = ToolCall { name: "slow_tool".to_string(), parameters: json!({}) };
+        let registry = Arc::new(ToolRegistry::default());
+        registry.register_instance(SlowTool(Some(Duration::from_millis(200)))).await;
+
+        let (stopped, other) = (registry.clone(), registry.clone());
+        let (call_a, call_b) = (call.clone(), call.clone());
+        let stopped = tokio::spawn(cancel_scope(async move { stopped.execute(&call_a).await }));
+        let other = tokio::spawn(cancel_scope(async move { other.execute(&call_b).await }));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stopped.abort();
+
+        assert!(stopped.await.unwrap_err().is_cancelled());
+        // The other session's call runs to its own timeout instead of being cancelled
+        let out = other.await.unwrap().unwrap();
+        assert!(out.summary.contains("timed out"));
+    }
+
+    struct StreamingTool;
+
+    #[async_trait]
+    impl Tool for StreamingTool {
+        fn name(&self) -> String { "streaming_tool".to_string() }
+        fn description(&self) -> String { "Streams lines then stalls".to_string() }
+        fn parameters(&self) -> Value { json!({"type": "object"}) }
+        fn timeout(&self) -> Option<Duration> { Some(Duration::from_millis(50)) }
+        async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+            self.execute_stream(params, mpsc::unbounded_channel().0).await
+        }
+        async fn execute_stream(&self, _params: Value, progress: mpsc::UnboundedSender<String>) -> AgentResult<ToolOutput> {
+            let _ = progress.send("step 1\n".to_string());
+            let _ = progress.send("step 2\n".to_string());
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(ToolOutput::success_str("done"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_chunks_are_forwarded() {
+        let registry = ToolRegistry::default();
+        registry.register_instance(StreamingTool).await;
+        let call = ToolCall { name: "streaming_tool".to_string(), parameters: json!({}) };
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let out = registry.execute_streaming_as(&call, &ToolCaller::system(), Some(tx)).await.unwrap();
+        assert!(!out.success);
+        assert!(out.summary.contains("Partial output:\nstep 1\nstep 2"));
+        assert_eq!(rx.recv().await.unwrap().chunk, "step 1\n");
+        assert_eq!(rx.recv().await.unwrap().chunk, "step 2\n");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_skips_side_effects() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ToolRegistry::default();
+        registry.register_instance(FileSystemTool::new(dir.path())).await;
+        registry.set_dry_run(true);
+
+        let call = ToolCall { name: "file_system".to_string(), parameters: json!({"action": "write", "path": "plan.txt", "content": "hi"}) };
+        let out = registry.execute(&call).await.unwrap();
+        assert!(out.success);
+        assert!(out.summary.starts_with("[DRY RUN]"));
+
+        assert!(!dir.path().join("plan.txt").exists());
+
+        // Reads still run, and find nothing was written
+        let call = ToolCall { name: "file_system".to_string(), parameters: json!({"action": "read", "path": "plan.txt"}) };
+        assert!(registry.execute(&call).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_stubs_mutating_network_calls() {
+        let registry = ToolRegistry::default();
+        registry.register_instance(HttpTool::default()).await;
+        registry.set_dry_run(true);
+
+        let call = ToolCall { name: "http_request".to_string(), parameters: json!({"url": "https://example.com/hooks", "method": "POST", "body": "{}"}) };
+        let out = registry.execute(&call).await.unwrap();
+        assert!(out.success);
+        assert!(out.summary.starts_with("[DRY RUN]"));
+
+        let http = HttpTool::default();
+        assert!(http.has_side_effects(&json!({"url": "https://example.com", "method": "delete"})));
+        assert!(!http.has_side_effects(&json!({"url": "https://example.com"})));
+    }
+
+    #[test]
+    fn test_parameter_validation() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "count": { "type": "integer" }, "mode": { "type": "string", "enum": ["a", "b"] } },
+            "required": ["mode"]
+        });
+        assert!(validate_parameters(&schema, &json!({"mode": "a", "count": 2})).is_ok());
+
+        let violations = validate_parameters(&schema, &json!({"count": "two"})).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|v| v.path == "/count"));
+        assert!(violations.iter().any(|v| v.path == "/" && v.message.contains("mode")));
+
+        let out = ToolOutput::invalid_parameters("demo", &schema, violations);
+        assert!(!out.success);
+        assert_eq!(out.data["error"], "invalid_parameters");
+    }
+
     #[tokio::test]
     async fn test_generate_tools_prompt() {
         let registry = ToolRegistry::default();
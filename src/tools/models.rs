@@ -10,15 +10,16 @@ use std::io::Write;
 use schemars::JsonSchema;
 
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::orchestrator::routing::{ModelRoute, RoutingMatrix, ROUTING};
+use crate::tools::{Tool, ToolOutput, ToolCapability};
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct ModelManagerParams {
-    /// The action to perform: 'list', 'select', 'add', or 'pull'.
+    /// The action to perform: 'list', 'select', 'add', 'pull', 'routing' or 'reload'.
     pub action: String,
     /// The name of the model (required for select, add, pull).
     pub name: Option<String>,
-    /// Scale class to set default for (tiny, standard, heavy, bitnet). Required for 'select'.
+    /// Scale class or agent role to set the model for (tiny, standard, heavy, coder, ...). Required for 'select'.
     pub class: Option<String>,
     /// HuggingFace repo ID. Required for 'add'.
     pub repo: Option<String>,
@@ -37,15 +38,145 @@ struct ModelConfig {
     description: Option<String>,
 }
 
+/// `config/agency_models.json`: candle models and the class each is the default for
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct Registry {
+pub struct Registry {
     models: Vec<ModelConfig>,
     defaults: std::collections::HashMap<String, String>,
 }
 
+/// A model the agency can run, in the shape of an OpenAI `/v1/models` entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    /// Provider type serving it (`ollama`, `candle`, `openai`, ...)
+    pub owned_by: String,
+    pub capabilities: ModelCapabilities,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// Tokens
+    pub context_window: usize,
+    /// Runs on this machine or its LAN
+    pub local: bool,
+    /// Scale classes and agent roles routed to it
+    pub routes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantized: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A model pulled into an Ollama server
+#[derive(Debug, Clone, PartialEq)]
+pub struct OllamaModel {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Ollama servers from `OLLAMA_HOSTS`, or `OLLAMA_HOST` and `OLLAMA_PORT`
+fn ollama_endpoints() -> Vec<(String, u16)> {
+    match std::env::var("OLLAMA_HOSTS") {
+        Ok(hosts) => hosts.split(',')
+            .filter_map(|h| reqwest::Url::parse(h.trim()).ok())
+            .map(|url| (format!("{}://{}", url.scheme(), url.host_str().unwrap_or("localhost")), url.port().unwrap_or(11434)))
+            .collect(),
+        Err(_) => vec![(
+            std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost".to_string()),
+            std::env::var("OLLAMA_PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(11434),
+        )],
+    }
+}
+
+/// Merge Ollama's local models, the candle registry and the routing matrix into one list.
+/// Routes without a provider are served by `default_provider`.
+pub fn build_catalog(ollama: &[OllamaModel], registry: Option<&Registry>, matrix: &RoutingMatrix, default_provider: &str) -> Vec<ModelInfo> {
+    let created = chrono::Utc::now().timestamp();
+    let mut catalog: Vec<ModelInfo> = Vec::new();
+    let mut entry = |id: &str, provider: &str| -> usize {
+        if let Some(index) = catalog.iter().position(|m| m.id == id) {
+            return index;
+        }
+        catalog.push(ModelInfo {
+            id: id.to_string(),
+            object: "model".to_string(),
+            created,
+            owned_by: provider.to_string(),
+            capabilities: ModelCapabilities {
+                context_window: crate::agent::tokenizer::context_window(id),
+                local: crate::agent::provider::is_local_provider(provider),
+                ..Default::default()
+            },
+        });
+        catalog.len() - 1
+    };
+
+    let mut indices = Vec::new();
+    for model in ollama {
+        indices.push((entry(&model.name, "ollama"), None, Some(model.size)));
+    }
+    for model in registry.map(|r| r.models.as_slice()).unwrap_or_default() {
+        indices.push((entry(&model.name, "candle"), Some(model), None));
+    }
+    let routes: Vec<(&String, &ModelRoute)> = matrix.classes.iter().chain(matrix.agents.iter()).collect();
+    let mut routed = Vec::new();
+    for (key, route) in routes {
+        routed.push((entry(&route.model, route.provider.as_deref().unwrap_or(default_provider)), key.clone()));
+    }
+    if let Some(registry) = registry {
+        for (class, name) in &registry.defaults {
+            if let Some(index) = catalog.iter().position(|m| &m.id == name) {
+                routed.push((index, class.clone()));
+            }
+        }
+    }
+
+    for (index, config, size) in indices {
+        let capabilities = &mut catalog[index].capabilities;
+        if let Some(config) = config {
+            capabilities.quantized = Some(config.is_quantized);
+            capabilities.description = config.description.clone();
+        }
+        capabilities.size_bytes = size.or(capabilities.size_bytes);
+    }
+    for (index, key) in routed {
+        let routes = &mut catalog[index].capabilities.routes;
+        if !routes.contains(&key) {
+            routes.push(key);
+        }
+    }
+    for model in &mut catalog {
+        model.capabilities.routes.sort();
+    }
+    catalog.sort_by(|a, b| a.id.cmp(&b.id));
+    catalog
+}
+
 pub struct ModelManager;
 
 impl ModelManager {
+    /// Every model the agency can run: pulled Ollama models, the candle registry and routed
+    /// remote models. Unreachable Ollama servers are skipped.
+    pub async fn catalog(&self) -> Vec<ModelInfo> {
+        let mut ollama = Vec::new();
+        for (host, port) in ollama_endpoints() {
+            match ollama_rs::Ollama::new(host.clone(), port).list_local_models().await {
+                Ok(models) => ollama.extend(models.into_iter().map(|m| OllamaModel { name: m.name, size: m.size })),
+                Err(e) => tracing::debug!("Ollama at {}:{} not listing models: {}", host, port, e),
+            }
+        }
+        let registry = self.load_registry().ok();
+        let default_provider = std::env::var("AGENCY_PROVIDER").unwrap_or_else(|_| {
+            if std::env::var("OPENAI_API_KEY").is_ok() { "openai".to_string() } else { "zai".to_string() }
+        });
+        build_catalog(&ollama, registry.as_ref(), &ROUTING.current(), &default_provider)
+    }
+
     fn load_registry(&self) -> AgentResult<Registry> {
         let file = File::open("config/agency_models.json").map_err(|e| AgentError::Io(e))?;
         let registry: Registry = serde_json::from_reader(file).map_err(|e| AgentError::Serde(e))?;
@@ -74,8 +205,8 @@ impl Tool for ModelManager {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["list", "select", "add", "pull"],
-                    "description": "The action to perform"
+                    "enum": ["list", "select", "add", "pull", "routing", "reload"],
+                    "description": "The action to perform. 'routing' shows the routing matrix; 'reload' re-reads it after the config file was edited."
                 },
                 "name": {
                     "type": "string",
@@ -83,8 +214,8 @@ impl Tool for ModelManager {
                 },
                 "class": {
                     "type": "string",
-                    "enum": ["tiny", "standard", "heavy"],
-                    "description": "The scale class to update (e.g. tiny, standard, heavy). Required for 'select'."
+                    "enum": ["logic", "tiny", "standard", "heavy", "coder", "reasoner", "researcher", "planner", "reviewer", "chat"],
+                    "description": "The scale class or agent role to update. Required for 'select'."
                 },
                 "repo": {
                     "type": "string",
@@ -99,6 +230,15 @@ impl Tool for ModelManager {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network, ToolCapability::FilesystemWrite]
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        // Model downloads can take arbitrarily long
+        None
+    }
+
     async fn execute(&self, params: serde_json::Value) -> AgentResult<ToolOutput> {
         let p: ModelManagerParams = serde_json::from_value(params).map_err(|e| AgentError::Serde(e))?;
         let mut registry = self.load_registry()?;
@@ -130,6 +270,8 @@ impl Tool for ModelManager {
 
                 registry.defaults.insert(class.clone(), name.clone());
                 self.save_registry(&registry)?;
+                // Routes take precedence over registry defaults, so keep the matrix in step
+                ROUTING.set_route_model(&class, &name)?;
                 Ok(ToolOutput::success(
                     json!({"class": class, "model": name}),
                     format!("Set {} as the default model for '{}' tasks.", name, class)
@@ -199,7 +341,67 @@ impl Tool for ModelManager {
                     format!("Successfully pulled weights for model '{}'.", name)
                 ))
             },
-            _ => Ok(ToolOutput::failure("Unknown action. Use 'list', 'select', 'add', or 'pull'.")),
+            "routing" => {
+                let matrix = ROUTING.current();
+                let mut table = format!("Router: {}\n\n| Route | Model | Provider | Context |\n|---|---|---|---|\n", matrix.router_model());
+                let mut routes: Vec<(&String, &ModelRoute)> = matrix.classes.iter().chain(matrix.agents.iter()).collect();
+                routes.sort_by(|a, b| a.0.cmp(b.0));
+                for (key, route) in routes {
+                    table.push_str(&format!("| {} | {} | {} | {} |\n",
+                        key,
+                        route.model,
+                        route.provider.as_deref().unwrap_or("default"),
+                        route.context_window.map(|w| w.to_string()).unwrap_or_default()
+                    ));
+                }
+                Ok(ToolOutput::success(serde_json::to_value(&*matrix).map_err(AgentError::Serde)?, table))
+            },
+            "reload" => {
+                let matrix = ROUTING.reload()?;
+                Ok(ToolOutput::success(
+                    serde_json::to_value(&*matrix).map_err(AgentError::Serde)?,
+                    format!("Reloaded the routing matrix from {}.", ROUTING.path().display())
+                ))
+            },
+            _ => Ok(ToolOutput::failure("Unknown action. Use 'list', 'select', 'add', 'pull', 'routing' or 'reload'.")),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_merges_sources() {
+        let registry: Registry = serde_json::from_value(json!({
+            "models": [{ "name": "qwen2.5-coder:0.5b", "repo": "Qwen/Qwen2.5-Coder-0.5B", "revision": "main",
+                         "tokenizer_repo": "Qwen/Qwen2.5-Coder-0.5B", "is_quantized": true, "quant_file": "q4.gguf", "description": "Tiny coder" }],
+            "defaults": { "tiny": "qwen2.5-coder:0.5b" }
+        })).unwrap();
+        let matrix: RoutingMatrix = serde_json::from_value(json!({
+            "classes": { "standard": { "model": "llama3.2:3b" }, "heavy": { "model": "gpt-4o", "provider": "openai" } },
+            "agents": { "coder": { "model": "qwen2.5-coder:0.5b" } }
+        })).unwrap();
+        let ollama = vec![OllamaModel { name: "llama3.2:3b".to_string(), size: 2_000_000_000 }];
+
+        let catalog = build_catalog(&ollama, Some(&registry), &matrix, "ollama");
+        let ids: Vec<&str> = catalog.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["gpt-4o", "llama3.2:3b", "qwen2.5-coder:0.5b"]);
+
+        let remote = &catalog[0];
+        assert_eq!(remote.owned_by, "openai");
+        assert!(!remote.capabilities.local);
+        assert_eq!(remote.capabilities.routes, vec!["heavy"]);
+
+        // A pulled Ollama model that is also routed appears once, with both
+        let pulled = &catalog[1];
+        assert_eq!((pulled.owned_by.as_str(), pulled.capabilities.size_bytes), ("ollama", Some(2_000_000_000)));
+        assert_eq!(pulled.capabilities.routes, vec!["standard"]);
+
+        let candle = &catalog[2];
+        assert_eq!(candle.owned_by, "candle");
+        assert_eq!(candle.capabilities.quantized, Some(true));
+        assert_eq!(candle.capabilities.routes, vec!["coder", "tiny"]);
+    }
+}
@@ -13,7 +13,7 @@ use tracing::{info, warn, error};
 
 use crate::agent::{AgentResult, AgentError};
 use crate::utils::sandbox::TOOL_SANDBOX_POLICY;
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability};
 
 pub struct MutationTool {
     src_dir: PathBuf,
@@ -158,6 +158,10 @@ impl Tool for MutationTool {
         true // Critical safety: Mutation always requires approval
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::FilesystemWrite]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("verify");
 
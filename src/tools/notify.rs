@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
 use crate::orchestrator::vocal_cords::VocalCords;
 
 pub struct NotifyTool {
@@ -43,6 +43,18 @@ impl Tool for NotifyTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn has_side_effects(&self, _params: &Value) -> bool {
+        true
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let message = params["message"].as_str()
             .ok_or_else(|| AgentError::Execution("Missing 'message'".to_string()))?;
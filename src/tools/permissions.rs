@@ -0,0 +1,218 @@
+//! Tool Permissions
+//!
+//! Capability-based access control for tools. Each tool declares the capabilities it needs;
+//! a policy file grants capabilities (and explicit allow/deny lists) per agent and per
+//! profile. `ToolRegistry` checks the policy before the tool's own `security_oracle`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What a tool can do to the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCapability {
+    /// Observes local state without side effects
+    ReadOnly,
+    /// Talks to remote hosts
+    Network,
+    /// Creates, modifies or deletes files
+    FilesystemWrite,
+    /// Runs arbitrary commands or code
+    Shell,
+    /// Spends money or other real-world resources
+    SpendMoney,
+}
+
+impl ToolCapability {
+    pub fn all() -> Vec<ToolCapability> {
+        vec![Self::ReadOnly, Self::Network, Self::FilesystemWrite, Self::Shell, Self::SpendMoney]
+    }
+}
+
+/// Who is invoking a tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCaller {
+    pub agent: String,
+    pub profile: Option<String>,
+    /// ReAct turn the call belongs to, for analytics
+    pub turn_id: Option<String>,
+}
+
+impl ToolCaller {
+    pub fn new(agent: impl Into<String>, profile: Option<String>) -> Self {
+        Self { agent: agent.into(), profile, turn_id: None }
+    }
+
+    pub fn with_turn(mut self, turn_id: impl Into<String>) -> Self {
+        self.turn_id = Some(turn_id.into());
+        self
+    }
+
+    /// Internal calls made outside an agent turn
+    pub fn system() -> Self {
+        Self::new("system", None)
+    }
+
+    pub fn for_config(config: &crate::agent::AgentConfig) -> Self {
+        let agent = config.agent_name.clone().unwrap_or_else(|| config.agent_type.to_string());
+        Self::new(agent, config.profile_name.clone())
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_CALLER: ToolCaller;
+}
+
+/// Run a tool call's future with `caller` visible to `current_caller`, so tools that call
+/// other tools (pipelines) act on the original caller's behalf
+pub(crate) async fn with_caller<F: std::future::Future>(caller: ToolCaller, fut: F) -> F::Output {
+    CURRENT_CALLER.scope(caller, fut).await
+}
+
+/// Caller of the tool call running on this task, if any
+pub fn current_caller() -> Option<ToolCaller> {
+    CURRENT_CALLER.try_with(|caller| caller.clone()).ok()
+}
+
+/// Capabilities and explicit tool lists granted to an agent or profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionGrant {
+    /// Granted capabilities; `None` inherits from the default grant
+    #[serde(default)]
+    pub capabilities: Option<Vec<ToolCapability>>,
+    /// Tools allowed regardless of their capabilities
+    #[serde(default)]
+    pub allow_tools: Vec<String>,
+    /// Tools that are never allowed
+    #[serde(default)]
+    pub deny_tools: Vec<String>,
+}
+
+/// Policy file contents, e.g.
+///
+/// ```json
+/// {
+///   "default": { "capabilities": ["read_only", "network", "filesystem_write", "shell"] },
+///   "agents": { "Researcher": { "capabilities": ["read_only", "network"] } },
+///   "profiles": { "kiosk": { "deny_tools": ["code_exec"] } }
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionPolicy {
+    #[serde(default = "PermissionPolicy::permissive_grant")]
+    pub default: PermissionGrant,
+    #[serde(default)]
+    pub agents: HashMap<String, PermissionGrant>,
+    #[serde(default)]
+    pub profiles: HashMap<String, PermissionGrant>,
+}
+
+impl Default for PermissionPolicy {
+    /// Everything is allowed until a policy file says otherwise
+    fn default() -> Self {
+        Self { default: Self::permissive_grant(), agents: HashMap::new(), profiles: HashMap::new() }
+    }
+}
+
+impl PermissionPolicy {
+    fn permissive_grant() -> PermissionGrant {
+        PermissionGrant { capabilities: Some(ToolCapability::all()), ..Default::default() }
+    }
+
+    /// Load a JSON (or YAML, by extension) policy file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let policy = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content)?,
+            _ => serde_json::from_str(&content)?,
+        };
+        Ok(policy)
+    }
+
+    /// Policy at `AGENCY_TOOL_POLICY` (default `tool_policy.json`), or the permissive default
+    pub fn from_env() -> Self {
+        let path = std::env::var("AGENCY_TOOL_POLICY").unwrap_or_else(|_| "tool_policy.json".to_string());
+        if !Path::new(&path).exists() {
+            return Self::default();
+        }
+        match Self::from_file(&path) {
+            Ok(policy) => {
+                tracing::info!("Loaded tool permission policy from {}", path);
+                policy
+            }
+            Err(e) => {
+                // Fail closed: a broken policy file must not silently grant everything
+                tracing::error!("Invalid tool policy {}: {}. Only read-only tools are allowed.", path, e);
+                Self {
+                    default: PermissionGrant { capabilities: Some(vec![ToolCapability::ReadOnly]), ..Default::default() },
+                    agents: HashMap::new(),
+                    profiles: HashMap::new(),
+                }
+            }
+        }
+    }
+
+    /// Check whether `caller` may run `tool` requiring `required` capabilities
+    pub fn check(&self, caller: &ToolCaller, tool: &str, required: &[ToolCapability]) -> Result<()> {
+        let agent = self.agents.get(&caller.agent);
+        let profile = caller.profile.as_ref().and_then(|p| self.profiles.get(p));
+        let grants: Vec<&PermissionGrant> = [Some(&self.default), agent, profile].into_iter().flatten().collect();
+
+        if grants.iter().any(|g| g.deny_tools.iter().any(|t| t == tool)) {
+            anyhow::bail!("Tool '{}' is denied for {}", tool, Self::describe(caller));
+        }
+        if grants.iter().any(|g| g.allow_tools.iter().any(|t| t == tool)) {
+            return Ok(());
+        }
+
+        // The agent grant replaces the default; a profile grant can only narrow it further
+        let base = agent.and_then(|g| g.capabilities.as_ref()).or(self.default.capabilities.as_ref());
+        let narrowed = profile.and_then(|g| g.capabilities.as_ref());
+        let missing: Vec<&ToolCapability> = required.iter()
+            .filter(|c| !base.is_some_and(|b| b.contains(c)) || narrowed.is_some_and(|n| !n.contains(c)))
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Tool '{}' needs {:?}, which is not granted to {}", tool, missing, Self::describe(caller));
+        }
+        Ok(())
+    }
+
+    fn describe(caller: &ToolCaller) -> String {
+        match &caller.profile {
+            Some(profile) => format!("agent '{}' (profile '{}')", caller.agent, profile),
+            None => format!("agent '{}'", caller.agent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_capabilities_and_lists() {
+        let policy: PermissionPolicy = serde_json::from_value(serde_json::json!({
+            "default": { "capabilities": ["read_only", "network", "filesystem_write", "shell"] },
+            "agents": { "Researcher": { "capabilities": ["read_only", "network"], "allow_tools": ["code_exec"] } },
+            "profiles": { "kiosk": { "capabilities": ["read_only"], "deny_tools": ["web_search"] } }
+        })).unwrap();
+
+        let coder = ToolCaller::new("Coder", None);
+        assert!(policy.check(&coder, "code_exec", &[ToolCapability::Shell]).is_ok());
+        assert!(policy.check(&coder, "wallet", &[ToolCapability::SpendMoney]).is_err());
+
+        let researcher = ToolCaller::new("Researcher", None);
+        assert!(policy.check(&researcher, "file_system", &[ToolCapability::FilesystemWrite]).is_err());
+        assert!(policy.check(&researcher, "code_exec", &[ToolCapability::Shell]).is_ok());
+
+        let kiosk = ToolCaller::new("Researcher", Some("kiosk".to_string()));
+        assert!(policy.check(&kiosk, "http_request", &[ToolCapability::Network]).is_err());
+        assert!(policy.check(&kiosk, "web_search", &[ToolCapability::ReadOnly]).is_err());
+        assert!(policy.check(&kiosk, "memory_query", &[ToolCapability::ReadOnly]).is_ok());
+
+        assert!(PermissionPolicy::default().check(&ToolCaller::system(), "wallet", &[ToolCapability::SpendMoney]).is_ok());
+    }
+}
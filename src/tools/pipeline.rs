@@ -0,0 +1,286 @@
+//! Pipeline Tool
+//!
+//! Runs a declared sequence of tool calls in one action. String parameters may reference
+//! earlier results with `{{prev.summary}}`, `{{steps.0.data.url}}` or `{{input.query}}`;
+//! a parameter that is exactly one placeholder receives the referenced JSON value as-is.
+//! Each step runs as the pipeline's own caller and passes the safety checks a direct call
+//! would; steps that need human approval are refused.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use crate::safety::SafetyGuard;
+use super::{current_caller, Tool, ToolCall, ToolCaller, ToolCachePolicy, ToolCapability, ToolOutput, ToolRegistry};
+
+/// Maximum steps in one pipeline
+const MAX_PIPELINE_STEPS: usize = 10;
+
+/// One tool call in a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub tool: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+pub struct PipelineTool {
+    registry: Arc<ToolRegistry>,
+    /// Named pipelines loaded from disk
+    named: HashMap<String, Vec<PipelineStep>>,
+    /// Checks every step like a direct call
+    safety: Arc<Mutex<SafetyGuard>>,
+}
+
+impl PipelineTool {
+    pub fn new(registry: Arc<ToolRegistry>) -> Self {
+        Self { registry, named: HashMap::new(), safety: Arc::new(Mutex::new(SafetyGuard::new())) }
+    }
+
+    /// Check steps with a shared guard (e.g. the Supervisor's) instead of a private one
+    pub fn with_safety(mut self, safety: Arc<Mutex<SafetyGuard>>) -> Self {
+        self.safety = safety;
+        self
+    }
+
+    /// Load named pipelines from a JSON object of `name -> [steps]` (`AGENCY_PIPELINES`, default `pipelines.json`)
+    pub fn with_named_pipelines(mut self, path: impl AsRef<Path>) -> Self {
+        if let Ok(content) = std::fs::read_to_string(path.as_ref()) {
+            match serde_json::from_str(&content) {
+                Ok(named) => self.named = named,
+                Err(e) => tracing::warn!("Ignoring invalid pipelines file {:?}: {}", path.as_ref(), e),
+            }
+        }
+        self
+    }
+
+    pub fn from_env(registry: Arc<ToolRegistry>) -> Self {
+        let path = std::env::var("AGENCY_PIPELINES").unwrap_or_else(|_| "pipelines.json".to_string());
+        Self::new(registry).with_named_pipelines(path)
+    }
+}
+
+/// Look up `prev.summary`, `steps.1.data.items.0.url`, `input.topic`, ...
+fn lookup(path: &str, context: &Value) -> Option<Value> {
+    let mut parts = path.split('.');
+    let mut current = match parts.next()? {
+        "prev" => context["steps"].as_array()?.last()?,
+        "steps" => context["steps"].get(parts.next()?.parse::<usize>().ok()?)?,
+        "input" => &context["input"],
+        _ => return None,
+    };
+    for part in parts {
+        current = match part.parse::<usize>() {
+            Ok(i) if current.is_array() => current.get(i)?,
+            _ => current.get(part)?,
+        };
+    }
+    Some(current.clone())
+}
+
+fn render_str(template: &str, context: &Value) -> Result<Value, String> {
+    let trimmed = template.trim();
+    if trimmed.starts_with("{{") && trimmed.ends_with("}}") && trimmed.matches("{{").count() == 1 {
+        let path = trimmed[2..trimmed.len() - 2].trim();
+        return lookup(path, context).ok_or_else(|| format!("Unresolved placeholder '{{{{{}}}}}'", path));
+    }
+
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..].find("}}").ok_or_else(|| "Unclosed '{{' in template".to_string())? + start;
+        let path = rest[start + 2..end].trim();
+        let value = lookup(path, context).ok_or_else(|| format!("Unresolved placeholder '{{{{{}}}}}'", path))?;
+        out.push_str(&rest[..start]);
+        match value {
+            Value::String(s) => out.push_str(&s),
+            other => out.push_str(&other.to_string()),
+        }
+        rest = &rest[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+/// Substitute placeholders in every string within `params`
+pub fn render_parameters(params: &Value, context: &Value) -> Result<Value, String> {
+    Ok(match params {
+        Value::String(s) => render_str(s, context)?,
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_parameters(v, context)).collect::<Result<_, _>>()?),
+        Value::Object(map) => Value::Object(map.iter()
+            .map(|(k, v)| Ok((k.clone(), render_parameters(v, context)?)))
+            .collect::<Result<_, String>>()?),
+        other => other.clone(),
+    })
+}
+
+#[async_trait]
+impl Tool for PipelineTool {
+    fn name(&self) -> String {
+        "pipeline".to_string()
+    }
+
+    fn description(&self) -> String {
+        let mut desc = "Run several tool calls in sequence in one action, feeding outputs into later inputs. \
+            Reference earlier results in string parameters with {{prev.summary}}, {{prev.data.<field>}}, \
+            {{steps.<index>.data.<field>}} or {{input.<field>}}. Stops at the first failing step.".to_string();
+        if !self.named.is_empty() {
+            let mut names: Vec<&String> = self.named.keys().collect();
+            names.sort();
+            desc.push_str(&format!(" Named pipelines: {}.", names.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")));
+        }
+        desc
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "description": "Tool calls to run in order",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "tool": { "type": "string" },
+                            "parameters": { "type": "object" }
+                        },
+                        "required": ["tool"]
+                    }
+                },
+                "pipeline": {
+                    "type": "string",
+                    "description": "Name of a predefined pipeline to run instead of 'steps'"
+                },
+                "input": {
+                    "type": "object",
+                    "description": "Values available to templates as {{input.<field>}}"
+                }
+            }
+        })
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
+    /// A pipeline can do whatever its steps can, so it is never treated as read-only
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        ToolCapability::all()
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        // Each step is bounded by its own tool's limit
+        None
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let steps: Vec<PipelineStep> = match (params.get("steps"), params["pipeline"].as_str()) {
+            (Some(steps), _) => serde_json::from_value(steps.clone())
+                .map_err(|e| AgentError::Validation(format!("Invalid pipeline steps: {}", e)))?,
+            (None, Some(name)) => match self.named.get(name) {
+                Some(steps) => steps.clone(),
+                None => return Ok(ToolOutput::failure(format!("Unknown pipeline: {}", name))),
+            },
+            (None, None) => return Err(AgentError::Validation("Provide either 'steps' or 'pipeline'".to_string())),
+        };
+        if steps.is_empty() || steps.len() > MAX_PIPELINE_STEPS {
+            return Ok(ToolOutput::failure(format!("A pipeline needs 1 to {} steps", MAX_PIPELINE_STEPS)));
+        }
+
+        // Steps act for whoever called the pipeline, so their permissions apply to every step
+        let caller = current_caller().unwrap_or_else(|| ToolCaller::new("pipeline", None));
+        let mut context = json!({ "input": params.get("input").cloned().unwrap_or(json!({})), "steps": [] });
+        let mut lines = Vec::new();
+
+        for (i, step) in steps.iter().enumerate() {
+            if step.tool == self.name() {
+                return Ok(ToolOutput::failure("Pipelines cannot be nested"));
+            }
+            let parameters = match render_parameters(&step.parameters, &context) {
+                Ok(p) => p,
+                Err(e) => return Ok(ToolOutput::failure(format!("Step {} ({}): {}", i, step.tool, e))),
+            };
+            if let Some(tool) = self.registry.get_tool(&step.tool).await {
+                let mut safety = self.safety.lock().await;
+                if tool.requires_confirmation_for(&parameters)
+                    || safety.needs_human_approval(&step.tool, &parameters, self.registry.clone()).await.is_some()
+                {
+                    return Ok(ToolOutput::failure(format!(
+                        "Step {} ({}) needs human approval; call it directly instead of in a pipeline", i, step.tool
+                    )));
+                }
+                if let Err(e) = safety.check_tool_safety(&step.tool, &parameters, self.registry.clone()).await {
+                    return Ok(ToolOutput::failure(format!("Step {} ({}) blocked: {}", i, step.tool, e)));
+                }
+            }
+
+            info!("Pipeline step {}: {}", i, step.tool);
+            let call = ToolCall { name: step.tool.clone(), parameters };
+            let output = match self.registry.execute_as(&call, &caller).await {
+                Ok(output) => output,
+                Err(e) => ToolOutput::failure(e.to_string()),
+            };
+            lines.push(format!("{}. {}: {}", i, step.tool, output.summary));
+            if let Some(all) = context["steps"].as_array_mut() {
+                all.push(json!({ "tool": step.tool, "success": output.success, "summary": output.summary, "data": output.data }));
+            }
+
+            if !output.success {
+                return Ok(ToolOutput {
+                    success: false,
+                    data: context["steps"].clone(),
+                    summary: format!("Pipeline stopped at step {} ({}):\n{}", i, step.tool, lines.join("\n")),
+                    error: output.error,
+                });
+            }
+        }
+
+        Ok(ToolOutput::success(context["steps"].clone(), format!("Pipeline finished {} steps:\n{}", steps.len(), lines.join("\n"))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_parameters() {
+        let context = json!({
+            "input": { "topic": "rust" },
+            "steps": [{ "summary": "found 2", "data": { "urls": ["https://a", "https://b"] } }]
+        });
+        let params = json!({
+            "query": "news about {{input.topic}}",
+            "url": "{{prev.data.urls.1}}",
+            "all": "{{steps.0.data.urls}}",
+            "note": "{{ prev.summary }} results",
+        });
+
+        let rendered = render_parameters(&params, &context).unwrap();
+        assert_eq!(rendered["query"], "news about rust");
+        assert_eq!(rendered["url"], "https://b");
+        assert_eq!(rendered["all"], json!(["https://a", "https://b"]));
+        assert_eq!(rendered["note"], "found 2 results");
+        assert!(render_parameters(&json!("{{steps.5.summary}}"), &context).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_steps_needing_approval_are_refused() {
+        let registry = Arc::new(ToolRegistry::default());
+        registry.register_instance(crate::tools::CodeExecTool::new()).await;
+        let pipeline = PipelineTool::new(registry.clone());
+        let output = pipeline.execute(json!({
+            "steps": [{ "tool": "code_exec", "parameters": { "language": "python", "code": "print(1)" } }]
+        })).await.unwrap();
+        assert!(!output.success);
+        assert!(output.summary.contains("needs human approval"));
+        assert_ne!(pipeline.capabilities(), vec![ToolCapability::ReadOnly]);
+    }
+}
@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::agent::{AgentResult, AgentError, provider::{SwitchableProvider, create_provider_by_type}};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCachePolicy};
 
 pub struct ProviderTool {
     provider: Arc<SwitchableProvider>,
@@ -46,6 +46,10 @@ impl Tool for ProviderTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("status");
 
@@ -0,0 +1,264 @@
+//! RSS/News Feed Tool
+//!
+//! Fetches RSS, Atom and JSON feeds, merges and deduplicates their items, and caches each
+//! feed in memory for a TTL. A cheap news source for the Researcher that does not go through
+//! the web search rate limiter.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::agent::AgentResult;
+use crate::safety::{DomainPolicy, PolicyResolver};
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
+
+const DEFAULT_FEEDS: [&str; 1] = ["https://hnrss.org/frontpage"];
+const MAX_AGE_HOURS: i64 = 24 * 365 * 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedItem {
+    pub title: String,
+    pub link: Option<String>,
+    pub summary: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub source: String,
+}
+
+struct CachedFeed {
+    fetched_at: Instant,
+    items: Vec<FeedItem>,
+}
+
+/// Tool for reading news feeds
+pub struct RssTool {
+    client: Client,
+    feeds: Vec<String>,
+    ttl: Duration,
+    policy: Arc<DomainPolicy>,
+    cache: Mutex<HashMap<String, CachedFeed>>,
+}
+
+impl RssTool {
+    pub fn new(feeds: Vec<String>) -> Self {
+        let policy = Arc::new(DomainPolicy::from_env());
+        let redirect_policy = policy.clone();
+        // Redirect targets and resolved addresses get the same policy check as the feed URL
+        let client = Client::builder()
+            .user_agent("rust_agency/rss")
+            .timeout(Duration::from_secs(20))
+            .dns_resolver(PolicyResolver::new(policy.clone()))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 5 {
+                    attempt.error("too many redirects")
+                } else if let Err(e) = redirect_policy.check_url(attempt.url().as_str()) {
+                    attempt.error(e.to_string())
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            feeds,
+            ttl: Duration::from_secs(900),
+            policy,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Feeds from `AGENCY_RSS_FEEDS` (comma-separated) and cache TTL from `AGENCY_RSS_TTL_SECS`
+    pub fn from_env() -> Self {
+        let feeds: Vec<String> = std::env::var("AGENCY_RSS_FEEDS").unwrap_or_default()
+            .split(',')
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+        let feeds = if feeds.is_empty() { DEFAULT_FEEDS.iter().map(|f| f.to_string()).collect() } else { feeds };
+        let mut tool = Self::new(feeds);
+        if let Some(secs) = std::env::var("AGENCY_RSS_TTL_SECS").ok().and_then(|v| v.parse().ok()) {
+            tool.ttl = Duration::from_secs(secs);
+        }
+        tool
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Parse any feed format supported by `feed-rs`
+    pub fn parse_feed(bytes: &[u8], source: &str) -> anyhow::Result<Vec<FeedItem>> {
+        let feed = feed_rs::parser::parse(bytes)?;
+        let source_title = feed.title.map(|t| t.content).unwrap_or_else(|| source.to_string());
+        Ok(feed.entries.into_iter().map(|entry| FeedItem {
+            title: entry.title.map(|t| t.content).unwrap_or_default().trim().to_string(),
+            link: entry.links.first().map(|l| l.href.clone()),
+            summary: entry.summary.map(|s| s.content)
+                .map(|s| crate::agent::truncate(&html_escape::decode_html_entities(&s), 300)),
+            published: entry.published.or(entry.updated),
+            source: source_title.clone(),
+        }).collect())
+    }
+
+    /// Drop repeats by normalized link (falling back to title), keeping the first occurrence
+    pub fn dedupe(items: Vec<FeedItem>) -> Vec<FeedItem> {
+        let mut seen = HashSet::new();
+        items.into_iter().filter(|item| {
+            let key = match &item.link {
+                Some(link) => link.trim_end_matches('/').trim_start_matches("https://").trim_start_matches("http://").to_lowercase(),
+                None => item.title.to_lowercase(),
+            };
+            seen.insert(key)
+        }).collect()
+    }
+
+    async fn fetch(&self, url: &str) -> anyhow::Result<Vec<FeedItem>> {
+        if let Some(cached) = self.cache.lock().await.get(url) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                debug!("RSS cache hit: {}", url);
+                return Ok(cached.items.clone());
+            }
+        }
+
+        self.policy.check_url(url)?;
+        let bytes = self.client.get(url).send().await?.error_for_status()?.bytes().await?;
+        let items = Self::parse_feed(&bytes, url)?;
+        self.cache.lock().await.insert(url.to_string(), CachedFeed { fetched_at: Instant::now(), items: items.clone() });
+        Ok(items)
+    }
+}
+
+impl Default for RssTool {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[async_trait]
+impl Tool for RssTool {
+    fn name(&self) -> String {
+        "rss_feed".to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("Read recent items from news feeds (RSS/Atom). Uses the configured feeds ({}) unless \
+                 'feeds' is given. Optional 'query' filters by keyword. Results are cached, so prefer \
+                 this over web_search for current news.", self.feeds.join(", "))
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "feeds": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Feed URLs to read instead of the configured ones"
+                },
+                "query": { "type": "string", "description": "Only items whose title or summary contain this text" },
+                "max_age_hours": { "type": "integer", "description": "Ignore items older than this" },
+                "limit": { "type": "integer", "description": "Maximum items to return (default 20)" }
+            }
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "network": "outbound http/https (feeds only)",
+            "feeds": self.feeds,
+            "cache_ttl_secs": self.ttl.as_secs()
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        // Feeds are cached per URL inside the tool
+        ToolCachePolicy::no_cache()
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let feeds: Vec<String> = params["feeds"].as_array()
+            .map(|a| a.iter().filter_map(|f| f.as_str().map(|s| s.to_string())).collect())
+            .filter(|f: &Vec<String>| !f.is_empty())
+            .unwrap_or_else(|| self.feeds.clone());
+        let limit = params["limit"].as_u64().unwrap_or(20).clamp(1, 100) as usize;
+        let query = params["query"].as_str().map(|q| q.to_lowercase());
+        // Capped at ten years so the subtraction cannot overflow
+        let cutoff = params["max_age_hours"].as_i64().map(|h| Utc::now() - ChronoDuration::hours(h.clamp(0, MAX_AGE_HOURS)));
+
+        let results = futures::future::join_all(feeds.iter().map(|url| self.fetch(url))).await;
+        let mut items = Vec::new();
+        let mut errors = Vec::new();
+        for (url, result) in feeds.iter().zip(results) {
+            match result {
+                Ok(feed_items) => items.extend(feed_items),
+                Err(e) => {
+                    warn!("RSS fetch failed for {}: {}", url, e);
+                    errors.push(format!("{}: {}", url, e));
+                }
+            }
+        }
+        if items.is_empty() && !errors.is_empty() {
+            return Ok(ToolOutput::failure(format!("All feeds failed:\n{}", errors.join("\n"))));
+        }
+
+        items.sort_by(|a, b| b.published.cmp(&a.published));
+        let items: Vec<FeedItem> = Self::dedupe(items).into_iter()
+            .filter(|item| cutoff.is_none_or(|c| item.published.is_none_or(|p| p >= c)))
+            .filter(|item| query.as_ref().is_none_or(|q| {
+                item.title.to_lowercase().contains(q)
+                    || item.summary.as_ref().is_some_and(|s| s.to_lowercase().contains(q))
+            }))
+            .take(limit)
+            .collect();
+
+        let lines: Vec<String> = items.iter().enumerate().map(|(i, item)| format!(
+            "{}. {} ({}){}",
+            i + 1,
+            item.title,
+            item.source,
+            item.link.as_ref().map(|l| format!("\n   {}", l)).unwrap_or_default()
+        )).collect();
+        let mut summary = if lines.is_empty() { "No matching feed items".to_string() } else { lines.join("\n") };
+        if !errors.is_empty() {
+            summary.push_str(&format!("\n\n({} feed(s) failed)", errors.len()));
+        }
+        Ok(ToolOutput::success(json!({ "items": items, "errors": errors }), summary))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Test News</title>
+<item><title>Rust 2.0 released</title><link>https://example.com/rust</link><pubDate>Mon, 03 Mar 2025 10:00:00 GMT</pubDate></item>
+<item><title>Rust 2.0 released (mirror)</title><link>http://example.com/rust/</link></item>
+<item><title>Tokio news</title><link>https://example.com/tokio</link></item>
+</channel></rss>"#;
+
+    #[test]
+    fn test_parse_and_dedupe() {
+        let items = RssTool::parse_feed(RSS.as_bytes(), "test").unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].source, "Test News");
+        assert!(items[0].published.is_some());
+
+        let unique = RssTool::dedupe(items);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[1].title, "Tokio news");
+    }
+}
@@ -6,7 +6,7 @@
 
 use async_trait::async_trait;
 use bollard::container::LogOutput;
-use bollard::models::ContainerCreateBody;
+use bollard::models::{ContainerCreateBody, HostConfig};
 use bollard::exec::{CreateExecOptions, StartExecResults};
 use bollard::Docker;
 use bollard::query_parameters::{
@@ -15,11 +15,33 @@ use bollard::query_parameters::{
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
 use tracing::{info, warn};
 
 use crate::agent::{AgentResult, AgentError};
-use crate::utils::sandbox::TOOL_SANDBOX_POLICY;
-use super::{Tool, ToolOutput};
+use crate::utils::container::{ContainerConfig, ContainerRuntime};
+use crate::utils::sandbox::SandboxProfile;
+use super::{Tool, ToolOutput, ToolCapability};
+
+/// Longest a local container run may take
+const SANDBOX_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Force-removes a sandbox container when dropped
+struct RemoveContainerOnDrop {
+    docker: Docker,
+    name: String,
+}
+
+impl Drop for RemoveContainerOnDrop {
+    fn drop(&mut self) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else { return };
+        let (docker, name) = (self.docker.clone(), std::mem::take(&mut self.name));
+        handle.spawn(async move {
+            let _ = docker.remove_container(&name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+        });
+    }
+}
 
 /// Backend providers for the sandbox
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -34,16 +56,30 @@ pub enum SandboxProvider {
 /// Unified Sandbox Tool
 pub struct SandboxTool {
     provider: SandboxProvider,
+    /// Images and resource caps for the local container provider
+    container: ContainerConfig,
+    /// Loosest confinement a call may run under
+    sandbox_profile: SandboxProfile,
 }
 
 impl SandboxTool {
     pub fn new(provider: SandboxProvider) -> Self {
-        Self { provider }
+        Self { provider, container: ContainerConfig::new(ContainerRuntime::Docker), sandbox_profile: SandboxProfile::default() }
+    }
+
+    pub fn with_container(mut self, config: ContainerConfig) -> Self {
+        self.container = config;
+        self
+    }
+
+    pub fn with_sandbox_profile(mut self, profile: SandboxProfile) -> Self {
+        self.sandbox_profile = profile;
+        self
     }
 
     #[cfg(target_os = "macos")]
-    async fn execute_macos_native(&self, code: &str, language: &str) -> AgentResult<ToolOutput> {
-        info!("Initializing MacOS Native sandbox (Seatbelt) for {}...", language);
+    async fn execute_macos_native(&self, code: &str, language: &str, profile: SandboxProfile) -> AgentResult<ToolOutput> {
+        info!("Initializing MacOS Native sandbox (Seatbelt, {}) for {}...", profile.key(), language);
         
         let temp_dir = tempfile::tempdir()
             .map_err(|e| AgentError::Io(e))?;
@@ -57,12 +93,13 @@ impl SandboxTool {
         std::fs::write(&script_path, code)
             .map_err(|e| AgentError::Io(e))?;
 
-        let workspace_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let workspace_dir = crate::utils::workspace_dir();
+        let workspace_dir = workspace_dir.canonicalize().unwrap_or(workspace_dir);
         let canonical_temp = temp_dir.path().canonicalize()
             .map_err(|e| AgentError::Io(e))?;
 
         let mut cmd_args = vec![
-            "-p".to_string(), TOOL_SANDBOX_POLICY.to_string(),
+            "-p".to_string(), profile.seatbelt_policy(),
             "-D".to_string(), format!("WORKSPACE_DIR={}", workspace_dir.to_string_lossy()),
             "--".to_string(),
         ];
@@ -85,6 +122,8 @@ impl SandboxTool {
         cmd_args.extend(run_cmd);
 
         let output = tokio::process::Command::new("/usr/bin/sandbox-exec")
+            .kill_on_drop(true)
+            .current_dir(&workspace_dir)
             .args(&cmd_args)
             .output()
             .await
@@ -104,18 +143,13 @@ impl SandboxTool {
         }
     }
 
-    async fn execute_local_docker(&self, code: &str, language: &str) -> AgentResult<ToolOutput> {
+    async fn execute_local_docker(&self, code: &str, language: &str, profile: SandboxProfile, progress: Option<&mpsc::UnboundedSender<String>>) -> AgentResult<ToolOutput> {
         info!("Initializing local Docker/Podman sandbox for {}...", language);
         
         let docker = Docker::connect_with_local_defaults()
             .map_err(|e| AgentError::Tool(format!("Failed to connect to Docker Desktop: {}", e)))?;
 
-        let image = match language {
-            "python" => "python:3.11-slim",
-            "rust" => "rust:1.75-slim",
-            "javascript" => "node:20-slim",
-            _ => "ubuntu:latest",
-        };
+        let image = self.container.image_for(language);
 
         // 0. Ensure image exists
         let mut pull_stream = docker.create_image(
@@ -135,11 +169,24 @@ impl SandboxTool {
 
         // 1. Create container
         let container_name = format!("agency-sandbox-{}", uuid::Uuid::new_v4());
+        let limits = &self.container;
+        let network = limits.network && profile.allows_network();
         let config = ContainerCreateBody {
             image: Some(image.to_string()),
             tty: Some(true),
             attach_stdout: Some(true),
             attach_stderr: Some(true),
+            network_disabled: Some(!network),
+            working_dir: limits.workdir.as_ref().map(|_| "/workspace".to_string()),
+            host_config: Some(HostConfig {
+                memory: Some((limits.memory_mb * 1024 * 1024) as i64),
+                nano_cpus: Some((limits.cpus * 1e9) as i64),
+                pids_limit: Some(limits.pids_limit as i64),
+                network_mode: (!network).then(|| "none".to_string()),
+                security_opt: Some(vec!["no-new-privileges".to_string()]),
+                binds: limits.workdir.as_ref().map(|dir| vec![format!("{}:/workspace", dir.display())]),
+                ..Default::default()
+            }),
             ..Default::default()
         };
 
@@ -151,62 +198,71 @@ impl SandboxTool {
             config
         ).await.map_err(|e| AgentError::Tool(format!("Failed to create Docker container: {}", e)))?;
 
-        // 2. Start container
-        docker.start_container(&container_name, None::<StartContainerOptions>)
-            .await.map_err(|e| AgentError::Tool(format!("Failed to start Docker container: {}", e)))?;
-
-        // 3. Prepare execution - We use a file-based approach to avoid shell escaping issues
-        let filename = match language {
-            "python" => "script.py",
-            "javascript" => "script.js",
-            "rust" => "main.rs",
-            _ => "script.sh",
-        };
-
-        // Escaping for the heredoc
-        let escaped_code = code.replace("'", "'\'\''");
-        let write_cmd = format!("cat << 'EOF' > {}
+        // Removed however this call ends: error, timeout or the turn being cancelled
+        let _cleanup = RemoveContainerOnDrop { docker: docker.clone(), name: container_name.clone() };
+        let run = async {
+            // 2. Start container
+            docker.start_container(&container_name, None::<StartContainerOptions>)
+                .await.map_err(|e| AgentError::Tool(format!("Failed to start Docker container: {}", e)))?;
+
+            // 3. Prepare execution - We use a file-based approach to avoid shell escaping issues
+            let filename = match language {
+                "python" => "script.py",
+                "javascript" => "script.js",
+                "rust" => "main.rs",
+                _ => "script.sh",
+            };
+
+            // Escaping for the heredoc
+            let escaped_code = code.replace("'", "'\'\''");
+            let write_cmd = format!("cat << 'EOF' > {}
 {}
 EOF", filename, escaped_code);
         
-        let exec_write = docker.create_exec(&container_name, CreateExecOptions {
-            attach_stdout: Some(true),
-            attach_stderr: Some(true),
-            cmd: Some(vec!["sh", "-c", &write_cmd]),
-            ..Default::default()
-        }).await.map_err(|e| AgentError::Tool(format!("Failed to create exec for write: {}", e)))?.id;
-        docker.start_exec(&exec_write, None).await.map_err(|e| AgentError::Tool(format!("Failed to start exec for write: {}", e)))?;
-
-        // 4. Run the code
-        let run_cmd = match language {
-            "python" => vec!["python3", filename],
-            "javascript" => vec!["node", filename],
-            "rust" => vec!["sh", "-c", "rustc main.rs && ./main"],
-            _ => vec!["sh", filename],
-        };
-
-        let exec_run = docker.create_exec(&container_name, CreateExecOptions {
-            attach_stdout: Some(true),
-            attach_stderr: Some(true),
-            cmd: Some(run_cmd),
-            ..Default::default()
-        }).await.map_err(|e| AgentError::Tool(format!("Failed to create exec for run: {}", e)))?.id;
-
-        let mut stdout = String::new();
-        let mut stderr = String::new();
-
-        if let StartExecResults::Attached { mut output, .. } = docker.start_exec(&exec_run, None).await.map_err(|e| AgentError::Tool(format!("Failed to start exec for run: {}", e)))? {
-            while let Some(Ok(msg)) = output.next().await {
-                match msg {
-                    LogOutput::StdOut { message } => stdout.push_str(&String::from_utf8_lossy(&message)),
-                    LogOutput::StdErr { message } => stderr.push_str(&String::from_utf8_lossy(&message)),
-                    _ => {}
+            let exec_write = docker.create_exec(&container_name, CreateExecOptions {
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                cmd: Some(vec!["sh", "-c", &write_cmd]),
+                ..Default::default()
+            }).await.map_err(|e| AgentError::Tool(format!("Failed to create exec for write: {}", e)))?.id;
+            docker.start_exec(&exec_write, None).await.map_err(|e| AgentError::Tool(format!("Failed to start exec for write: {}", e)))?;
+
+            // 4. Run the code
+            let run_cmd = match language {
+                "python" => vec!["python3", filename],
+                "javascript" => vec!["node", filename],
+                "rust" => vec!["sh", "-c", "rustc main.rs && ./main"],
+                _ => vec!["sh", filename],
+            };
+
+            let exec_run = docker.create_exec(&container_name, CreateExecOptions {
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                cmd: Some(run_cmd),
+                ..Default::default()
+            }).await.map_err(|e| AgentError::Tool(format!("Failed to create exec for run: {}", e)))?.id;
+
+            let mut stdout = String::new();
+            let mut stderr = String::new();
+
+            if let StartExecResults::Attached { mut output, .. } = docker.start_exec(&exec_run, None).await.map_err(|e| AgentError::Tool(format!("Failed to start exec for run: {}", e)))? {
+                while let Some(Ok(msg)) = output.next().await {
+                    let (buffer, message) = match msg {
+                        LogOutput::StdOut { message } => (&mut stdout, message),
+                        LogOutput::StdErr { message } => (&mut stderr, message),
+                        _ => continue,
+                    };
+                    let text = String::from_utf8_lossy(&message);
+                    if let Some(tx) = progress {
+                        let _ = tx.send(text.to_string());
+                    }
+                    buffer.push_str(&text);
                 }
             }
-        }
-
-        // 5. Cleanup
-        let _ = docker.remove_container(&container_name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+            Ok::<_, AgentError>((stdout, stderr))
+        };
+        let (stdout, stderr) = tokio::time::timeout(SANDBOX_TIMEOUT, run).await
+            .map_err(|_| AgentError::Execution(format!("Sandbox execution timed out after {} seconds", SANDBOX_TIMEOUT.as_secs())))??;
 
         if stderr.is_empty() || !stdout.is_empty() { 
              Ok(ToolOutput::success(
@@ -225,6 +281,10 @@ EOF", filename, escaped_code);
 
 impl Default for SandboxTool {
     fn default() -> Self {
+        // An explicit container backend takes precedence over the native sandbox
+        if let Some(config) = ContainerConfig::from_env() {
+            return Self::new(SandboxProvider::Local).with_container(config);
+        }
         #[cfg(target_os = "macos")]
         {
             Self::new(SandboxProvider::MacOSNative)
@@ -264,6 +324,11 @@ impl Tool for SandboxTool {
                     "type": "string",
                     "description": "Language: python, rust, javascript, shell",
                     "enum": ["python", "rust", "javascript", "shell"]
+                },
+                "sandbox_profile": {
+                    "type": "string",
+                    "description": "Optional stricter confinement for this call (cannot loosen the configured profile)",
+                    "enum": ["no-network", "workspace-only", "full"]
                 }
             },
             "required": ["action", "code", "language"]
@@ -280,9 +345,11 @@ impl Tool for SandboxTool {
         json!({
             "status": "constrained",
             "environment": env,
+            "sandbox_profile": self.sandbox_profile.key(),
             "resource_limits": {
-                "memory": "1GB",
-                "cpu": "1.0 core",
+                "memory": format!("{} MB", self.container.memory_mb),
+                "cpu": format!("{} core", self.container.cpus),
+                "network": self.container.network && self.sandbox_profile.allows_network(),
                 "timeout": "60s"
             },
             "side_effects": "none (stateless)",
@@ -290,21 +357,39 @@ impl Tool for SandboxTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        self.run(params, None).await
+    }
+
+    async fn execute_stream(&self, params: Value, progress: mpsc::UnboundedSender<String>) -> AgentResult<ToolOutput> {
+        self.run(params, Some(&progress)).await
+    }
+}
+
+impl SandboxTool {
+    async fn run(&self, params: Value, progress: Option<&mpsc::UnboundedSender<String>>) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("run");
         
         match action {
             "run" => {
                 let code = params["code"].as_str().ok_or_else(|| AgentError::Validation("Missing code parameter".to_string()))?;
                 let lang = params["language"].as_str().unwrap_or("python");
+                let profile = match self.sandbox_profile.narrow(params["sandbox_profile"].as_str()) {
+                    Ok(profile) => profile,
+                    Err(e) => return Ok(ToolOutput::failure(e)),
+                };
                 
                 match self.provider {
                     #[cfg(target_os = "macos")]
-                    SandboxProvider::MacOSNative => self.execute_macos_native(code, lang).await,
+                    SandboxProvider::MacOSNative => self.execute_macos_native(code, lang, profile).await,
                     #[cfg(not(target_os = "macos"))]
                     SandboxProvider::MacOSNative => Ok(ToolOutput::failure("MacOSNative provider only available on macOS")),
                     
-                    SandboxProvider::Local => self.execute_local_docker(code, lang).await,
+                    SandboxProvider::Local => self.execute_local_docker(code, lang, profile, progress).await,
                     SandboxProvider::Daytona => self.execute_daytona(code, lang).await,
                     SandboxProvider::E2B => Ok(ToolOutput::failure("E2B provider not yet configured")),
                 }
@@ -0,0 +1,134 @@
+//! Scheduler Tool
+//!
+//! Lets agents create, list and delete recurring prompts and autonomous goals. Schedules are
+//! persisted in the `ScheduleStore`; the orchestrator's cron runner picks them up and hands
+//! each firing to `Supervisor::handle` (or `run_autonomous_with_budget` for goals),
+//! publishing the result on the event bus.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::agent::{AgentResult, AgentError};
+use crate::orchestrator::scheduler::{ScheduleMode, ScheduleStore};
+use crate::orchestrator::ResourceBudget;
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
+
+pub struct SchedulerTool {
+    store: Arc<ScheduleStore>,
+}
+
+impl SchedulerTool {
+    pub fn new(store: Arc<ScheduleStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl Tool for SchedulerTool {
+    fn name(&self) -> String {
+        "scheduler".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Schedule recurring agency tasks. Actions: 'create' (name, cron, prompt), 'list' and \
+         'delete' (id). 'cron' is a standard cron expression in local time, e.g. '0 8 * * *' for \
+         every morning at 8; the prompt is run as a new request each time it fires. With \
+         mode 'goal' the prompt is an autonomous goal (e.g. nightly reindexing or memory \
+         maintenance) limited by max_cycles, max_time_seconds and max_tokens.".to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "list", "delete"],
+                    "description": "The scheduling operation to perform"
+                },
+                "name": { "type": "string", "description": "Short label for the schedule" },
+                "cron": { "type": "string", "description": "Cron expression (5 fields, or 6 with leading seconds)" },
+                "prompt": { "type": "string", "description": "The request (or goal) to run on each firing" },
+                "mode": { "type": "string", "enum": ["prompt", "goal"], "description": "'goal' runs the prompt autonomously (default 'prompt')" },
+                "max_cycles": { "type": "integer", "description": "Goal mode: most autonomous iterations per run (default 5)" },
+                "max_time_seconds": { "type": "integer", "description": "Goal mode: wall-clock limit per run (default 300)" },
+                "max_tokens": { "type": "integer", "description": "Goal mode: token limit per run" },
+                "id": { "type": "string", "description": "Schedule id for 'delete'" }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::FilesystemWrite]
+    }
+
+    fn has_side_effects(&self, params: &Value) -> bool {
+        params["action"].as_str() != Some("list")
+    }
+
+    /// A schedule runs prompts unattended long after this turn, so a person signs off on it
+    fn requires_confirmation_for(&self, params: &Value) -> bool {
+        params["action"].as_str() == Some("create")
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let action = params["action"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: action".to_string()))?;
+        let required = |name: &str| params[name].as_str()
+            .ok_or_else(|| AgentError::Validation(format!("Missing required parameter: {}", name)));
+
+        match action {
+            "create" => {
+                let prompt = required("prompt")?;
+                let cron = required("cron")?;
+                let name = params["name"].as_str().map(|s| s.to_string()).unwrap_or_else(|| crate::agent::truncate(prompt, 40));
+                let created = if params["mode"] == "goal" {
+                    let defaults = ResourceBudget::default();
+                    let budget = ResourceBudget {
+                        max_cycles: params["max_cycles"].as_u64().map(|n| n as usize).unwrap_or(defaults.max_cycles),
+                        max_tokens: params["max_tokens"].as_u64().map(|n| n as u32).or(defaults.max_tokens),
+                        max_time_seconds: params["max_time_seconds"].as_u64().unwrap_or(defaults.max_time_seconds),
+                    };
+                    self.store.add_goal(&name, cron, prompt, budget).await
+                } else {
+                    self.store.add(&name, cron, prompt).await
+                };
+                match created {
+                    Ok(schedule) => Ok(ToolOutput::success(
+                        json!({ "schedule": schedule }),
+                        format!("Scheduled '{}' ({}) with id {}", schedule.name, schedule.cron, schedule.id)
+                    )),
+                    Err(e) => Ok(ToolOutput::failure(format!("Failed to create schedule: {}", e))),
+                }
+            }
+            "list" => match self.store.list().await {
+                Ok(schedules) => {
+                    let lines: Vec<String> = schedules.iter().map(|s| format!(
+                        "{} — '{}' [{}]{} last run: {}",
+                        s.id, s.name, s.cron,
+                        if s.mode == ScheduleMode::Goal { " (goal)" } else { "" },
+                        s.last_run.map(|t| t.to_rfc3339()).unwrap_or_else(|| "never".to_string())
+                    )).collect();
+                    let summary = if lines.is_empty() { "No schedules".to_string() } else { lines.join("\n") };
+                    Ok(ToolOutput::success(json!({ "schedules": schedules }), summary))
+                }
+                Err(e) => Ok(ToolOutput::failure(format!("Failed to list schedules: {}", e))),
+            },
+            "delete" => {
+                let id = required("id")?;
+                match self.store.remove(id).await {
+                    Ok(true) => Ok(ToolOutput::success(json!({ "deleted": id }), format!("Deleted schedule {}", id))),
+                    Ok(false) => Ok(ToolOutput::failure(format!("No schedule with id {}", id))),
+                    Err(e) => Ok(ToolOutput::failure(format!("Failed to delete schedule: {}", e))),
+                }
+            }
+            other => Ok(ToolOutput::failure(format!("Unknown action: {}", other))),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Side-Effect Journal
+//!
+//! Every side-effecting tool call is recorded before it runs, together with what is needed
+//! to undo it where that is possible (a file's contents before a write). The Supervisor
+//! reverts a turn's entries back to a checkpoint when an escalation attempt fails or a
+//! paused call is rejected. Entries are kept in memory only.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// How to reverse a tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ToolUndo {
+    /// Put back a file's previous contents, or delete it if it did not exist
+    RestoreFile { path: PathBuf, previous: Option<Vec<u8>> },
+}
+
+impl ToolUndo {
+    pub async fn apply(&self) -> std::io::Result<()> {
+        match self {
+            ToolUndo::RestoreFile { path, previous: Some(bytes) } => tokio::fs::write(path, bytes).await,
+            ToolUndo::RestoreFile { path, previous: None } => match tokio::fs::remove_file(path).await {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideEffectRecord {
+    pub tool: String,
+    pub parameters: Value,
+    /// Turn the call belongs to (see `ToolCaller::turn_id`)
+    pub turn_id: Option<String>,
+    /// `None` when the call cannot be reversed (shell commands, payments, ...)
+    pub undo: Option<ToolUndo>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SideEffectRecord {
+    pub fn describe(&self) -> String {
+        match self.parameters["path"].as_str() {
+            Some(path) => format!("{} {}", self.tool, path),
+            None => self.tool.clone(),
+        }
+    }
+}
+
+/// Result of reverting a turn's side effects
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RevertReport {
+    pub reverted: Vec<String>,
+    /// Calls that could not be undone (no undo, or the undo failed)
+    pub irreversible: Vec<String>,
+}
+
+impl RevertReport {
+    pub fn is_empty(&self) -> bool {
+        self.reverted.is_empty() && self.irreversible.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct SideEffectJournal {
+    entries: Mutex<Vec<SideEffectRecord>>,
+}
+
+impl SideEffectJournal {
+    pub async fn record(&self, record: SideEffectRecord) {
+        self.entries.lock().await.push(record);
+    }
+
+    /// Number of entries recorded for `turn_id` so far; pass it to `revert` later
+    pub async fn mark(&self, turn_id: &str) -> usize {
+        self.entries.lock().await.iter().filter(|e| e.turn_id.as_deref() == Some(turn_id)).count()
+    }
+
+    /// Undo, newest first, the entries of `turn_id` recorded after `mark`, and drop them
+    pub async fn revert(&self, turn_id: &str, mark: usize) -> RevertReport {
+        let undone: Vec<SideEffectRecord> = {
+            let mut entries = self.entries.lock().await;
+            let mut seen = 0;
+            let mut kept = Vec::with_capacity(entries.len());
+            let mut undone = Vec::new();
+            for entry in entries.drain(..) {
+                if entry.turn_id.as_deref() == Some(turn_id) {
+                    seen += 1;
+                    if seen > mark {
+                        undone.push(entry);
+                        continue;
+                    }
+                }
+                kept.push(entry);
+            }
+            *entries = kept;
+            undone
+        };
+
+        let mut report = RevertReport::default();
+        for entry in undone.iter().rev() {
+            match entry.undo {
+                Some(ref undo) => match undo.apply().await {
+                    Ok(()) => report.reverted.push(entry.describe()),
+                    Err(e) => {
+                        tracing::warn!("Could not undo {}: {}", entry.describe(), e);
+                        report.irreversible.push(entry.describe());
+                    }
+                },
+                None => report.irreversible.push(entry.describe()),
+            }
+        }
+        report
+    }
+
+    /// Drop the entries of a finished turn
+    pub async fn forget(&self, turn_id: &str) {
+        self.entries.lock().await.retain(|e| e.turn_id.as_deref() != Some(turn_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(turn: &str, undo: Option<ToolUndo>) -> SideEffectRecord {
+        SideEffectRecord { tool: "file_system".into(), parameters: serde_json::json!({}), turn_id: Some(turn.into()), undo, timestamp: Utc::now() }
+    }
+
+    #[tokio::test]
+    async fn test_revert_restores_files_after_mark() {
+        let dir = tempdir().unwrap();
+        let existing = dir.path().join("a.txt");
+        let created = dir.path().join("b.txt");
+        std::fs::write(&existing, "v1").unwrap();
+
+        let journal = SideEffectJournal::default();
+        journal.record(record("t1", Some(ToolUndo::RestoreFile { path: existing.clone(), previous: None }))).await;
+        let mark = journal.mark("t1").await;
+        journal.record(record("t1", Some(ToolUndo::RestoreFile { path: existing.clone(), previous: Some(b"v1".to_vec()) }))).await;
+        std::fs::write(&existing, "v2").unwrap();
+        journal.record(record("t1", Some(ToolUndo::RestoreFile { path: created.clone(), previous: None }))).await;
+        std::fs::write(&created, "new").unwrap();
+        journal.record(record("t1", None)).await;
+        journal.record(record("t2", None)).await;
+
+        let report = journal.revert("t1", mark).await;
+        assert_eq!(report.reverted.len(), 2);
+        assert_eq!(report.irreversible.len(), 1);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "v1");
+        assert!(!created.exists());
+        // Entries before the mark and of other turns are untouched
+        assert_eq!(journal.mark("t1").await, 1);
+        assert_eq!(journal.mark("t2").await, 1);
+    }
+}
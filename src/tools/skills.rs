@@ -31,7 +31,11 @@ pub struct MarkdownSkill {
 impl MarkdownSkill {
     pub async fn load_from_file(path: &Path) -> anyhow::Result<Self> {
         let content = tokio::fs::read_to_string(path).await?;
-        
+        Self::parse(&content, path)
+    }
+
+    /// Parse a skill file's `content`; `path` is where it lives (or will)
+    pub fn parse(content: &str, path: &Path) -> anyhow::Result<Self> {
         // Extract frontmatter
         let parts: Vec<&str> = content.split("---").collect();
         if parts.len() < 3 {
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::agent::{Speaker, AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCachePolicy};
 
 /// Tool for generating speech using the native Rust Speaker
 pub struct SpeakerRsTool {
@@ -42,6 +42,10 @@ impl Tool for SpeakerRsTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let text = params["text"].as_str().ok_or_else(|| AgentError::Validation("Missing text parameter".to_string()))?;
         
@@ -0,0 +1,359 @@
+//! SQL Query Tool
+//!
+//! Runs SQL against SQLite files and Postgres connection strings so data questions can be
+//! answered directly. The tool is read-only unless constructed with write access, and large
+//! result sets are truncated before they reach the model. SQLite files are confined to the same
+//! allowlisted roots as the file system tool and are never created on open.
+
+use async_trait::async_trait;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use serde_json::{json, Map, Value};
+use tracing::info;
+
+use crate::agent::{AgentResult, AgentError};
+use super::{FileSystemTool, Tool, ToolOutput, ToolCapability, ToolCachePolicy};
+
+/// Longest cell value returned before truncation
+const MAX_CELL_CHARS: usize = 200;
+
+/// Leading keywords accepted in read-only mode
+const READ_ONLY_KEYWORDS: [&str; 7] = ["select", "with", "explain", "show", "values", "table", "pragma"];
+
+/// Tool for querying SQLite and Postgres databases
+pub struct SqlTool {
+    default_database: Option<String>,
+    read_write: bool,
+    max_rows: usize,
+    /// Allowlist that SQLite database paths are resolved against
+    files: FileSystemTool,
+}
+
+impl SqlTool {
+    /// Read-only tool without a default database, with SQLite files confined to the workspace
+    pub fn new() -> Self {
+        Self { default_database: None, read_write: false, max_rows: 50, files: FileSystemTool::default() }
+    }
+
+    /// Defaults from `AGENCY_SQL_DATABASE`, `AGENCY_SQL_READ_WRITE=1` and `AGENCY_FS_ALLOW`
+    pub fn from_env() -> Self {
+        let mut tool = Self::new();
+        tool.files = FileSystemTool::from_env();
+        tool.default_database = std::env::var("AGENCY_SQL_DATABASE").ok().filter(|d| !d.is_empty());
+        tool.read_write = std::env::var("AGENCY_SQL_READ_WRITE").map(|v| v == "1").unwrap_or(false);
+        tool
+    }
+
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.default_database = Some(database.into());
+        self
+    }
+
+    /// Additionally allow SQLite databases below `root`
+    pub fn with_allowed_root(mut self, root: impl Into<std::path::PathBuf>) -> Self {
+        self.files = self.files.with_allowed_root(root);
+        self
+    }
+
+    /// Allow INSERT/UPDATE/DDL statements
+    pub fn with_read_write(mut self, read_write: bool) -> Self {
+        self.read_write = read_write;
+        self
+    }
+
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = max_rows.max(1);
+        self
+    }
+
+    fn is_postgres(database: &str) -> bool {
+        database.starts_with("postgres://") || database.starts_with("postgresql://")
+    }
+
+    /// Reject anything but a single read statement when the tool is read-only
+    fn check_read_only(&self, sql: &str) -> AgentResult<()> {
+        if self.read_write {
+            return Ok(());
+        }
+        let statement = sql.trim().trim_end_matches(';');
+        if statement.contains(';') {
+            return Err(AgentError::Validation("Only a single statement is allowed in read-only mode".to_string()));
+        }
+        let keyword = statement.split_whitespace().next().unwrap_or("").to_lowercase();
+        if READ_ONLY_KEYWORDS.contains(&keyword.as_str()) {
+            Ok(())
+        } else {
+            Err(AgentError::Validation(format!("'{}' statements are not allowed: the SQL tool is read-only", keyword.to_uppercase())))
+        }
+    }
+
+    fn truncate_cell(text: &str) -> Value {
+        if text.chars().count() > MAX_CELL_CHARS {
+            Value::String(format!("{}…", text.chars().take(MAX_CELL_CHARS).collect::<String>()))
+        } else {
+            Value::String(text.to_string())
+        }
+    }
+
+    fn query_sqlite(path: &str, sql: &str, read_write: bool, max_rows: usize) -> anyhow::Result<QueryResult> {
+        let flags = if read_write {
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+        } else {
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+        };
+        let conn = Connection::open_with_flags(path, flags)?;
+        let mut stmt = conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+        if columns.is_empty() {
+            let affected = stmt.execute([])?;
+            return Ok(QueryResult { columns, rows: Vec::new(), truncated: false, affected: Some(affected as u64) });
+        }
+
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut cursor = stmt.query([])?;
+        while let Some(row) = cursor.next()? {
+            if rows.len() == max_rows {
+                truncated = true;
+                break;
+            }
+            let mut values = Vec::with_capacity(columns.len());
+            for i in 0..columns.len() {
+                values.push(match row.get_ref(i)? {
+                    ValueRef::Null => Value::Null,
+                    ValueRef::Integer(n) => json!(n),
+                    ValueRef::Real(f) => json!(f),
+                    ValueRef::Text(t) => Self::truncate_cell(&String::from_utf8_lossy(t)),
+                    ValueRef::Blob(b) => Value::String(format!("<blob {} bytes>", b.len())),
+                });
+            }
+            rows.push(values);
+        }
+        Ok(QueryResult { columns, rows, truncated, affected: None })
+    }
+
+    async fn query_postgres(url: &str, sql: &str, read_write: bool, max_rows: usize) -> anyhow::Result<QueryResult> {
+        let (client, connection) = tokio_postgres::connect(url, tokio_postgres::NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::warn!("Postgres connection error: {}", e);
+            }
+        });
+        if !read_write {
+            client.batch_execute("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY").await?;
+        }
+
+        let mut columns = Vec::new();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut affected = None;
+        for message in client.simple_query(sql).await? {
+            match message {
+                tokio_postgres::SimpleQueryMessage::RowDescription(desc) => {
+                    columns = desc.iter().map(|c| c.name().to_string()).collect();
+                }
+                tokio_postgres::SimpleQueryMessage::Row(row) => {
+                    if columns.is_empty() {
+                        columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                    }
+                    if rows.len() == max_rows {
+                        truncated = true;
+                        continue;
+                    }
+                    rows.push((0..row.len()).map(|i| row.get(i).map(Self::truncate_cell).unwrap_or(Value::Null)).collect());
+                }
+                tokio_postgres::SimpleQueryMessage::CommandComplete(n) => affected = Some(n),
+                _ => {}
+            }
+        }
+        if !columns.is_empty() {
+            affected = None;
+        }
+        Ok(QueryResult { columns, rows, truncated, affected })
+    }
+}
+
+impl Default for SqlTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct QueryResult {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    truncated: bool,
+    /// Rows affected by a statement that returns no result set
+    affected: Option<u64>,
+}
+
+impl QueryResult {
+    fn into_output(self) -> ToolOutput {
+        if let Some(affected) = self.affected {
+            return ToolOutput::success(json!({ "rows_affected": affected }), format!("Statement OK, {} rows affected", affected));
+        }
+
+        let records: Vec<Value> = self.rows.iter().map(|row| {
+            let mut record = Map::new();
+            for (column, value) in self.columns.iter().zip(row) {
+                record.insert(column.clone(), value.clone());
+            }
+            Value::Object(record)
+        }).collect();
+
+        let mut table = vec![self.columns.join(" | ")];
+        for row in &self.rows {
+            table.push(row.iter().map(|v| match v {
+                Value::String(s) => s.clone(),
+                Value::Null => "NULL".to_string(),
+                other => other.to_string(),
+            }).collect::<Vec<_>>().join(" | "));
+        }
+        let mut summary = format!("{} row(s)\n{}", self.rows.len(), table.join("\n"));
+        if self.truncated {
+            summary.push_str(&format!("\n... (truncated to {} rows; add LIMIT/WHERE to narrow the query)", self.rows.len()));
+        }
+        ToolOutput::success(json!({ "columns": self.columns, "rows": records, "truncated": self.truncated }), summary)
+    }
+}
+
+#[async_trait]
+impl Tool for SqlTool {
+    fn name(&self) -> String {
+        "sql_query".to_string()
+    }
+
+    fn description(&self) -> String {
+        let mode = if self.read_write { "read-write" } else { "read-only (SELECT/WITH/EXPLAIN only)" };
+        format!("Run SQL against a SQLite file or a Postgres connection string (postgres://...). \
+                 Mode: {}. Results are truncated to {} rows.", mode, self.max_rows)
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "The SQL statement to run"
+                },
+                "database": {
+                    "type": "string",
+                    "description": "SQLite file path or postgres:// URL (defaults to the configured database)"
+                },
+                "max_rows": {
+                    "type": "integer",
+                    "description": "Row limit for the result (capped by the tool limit)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn work_scope(&self) -> Value {
+        json!({
+            "status": "constrained",
+            "engines": ["sqlite", "postgres"],
+            "mode": if self.read_write { "read_write" } else { "read_only" },
+            "max_rows": self.max_rows,
+            "default_database": self.default_database.is_some()
+        })
+    }
+
+    async fn security_oracle(&self, params: &Value) -> AgentResult<bool> {
+        Ok(params["query"].as_str().map(|sql| self.check_read_only(sql).is_ok()).unwrap_or(true))
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        if self.read_write { vec![ToolCapability::FilesystemWrite, ToolCapability::Network] } else { vec![ToolCapability::ReadOnly, ToolCapability::Network] }
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        // Even a read-only connection sees writes made by other clients of the database
+        ToolCachePolicy::no_cache()
+    }
+
+    async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let sql = params["query"].as_str()
+            .ok_or_else(|| AgentError::Validation("Missing required parameter: query".to_string()))?
+            .to_string();
+        let database = params["database"].as_str().map(|s| s.to_string())
+            .or_else(|| self.default_database.clone())
+            .ok_or_else(|| AgentError::Validation("No database given and no default database configured".to_string()))?;
+        self.check_read_only(&sql)?;
+
+        let max_rows = params["max_rows"].as_u64().map(|n| (n as usize).clamp(1, self.max_rows)).unwrap_or(self.max_rows);
+        let read_write = self.read_write;
+        info!("SQL query on {}: {}", if Self::is_postgres(&database) { "postgres" } else { database.as_str() }, crate::agent::truncate(&sql, 120));
+
+        let result = if Self::is_postgres(&database) {
+            Self::query_postgres(&database, &sql, read_write, max_rows).await
+        } else {
+            let path = if read_write { self.files.resolve_writable(&database)? } else { self.files.resolve_path(&database)? };
+            if !path.is_file() {
+                return Err(AgentError::Validation(format!("SQLite database '{}' does not exist", database)));
+            }
+            let path = path.to_string_lossy().to_string();
+            tokio::task::spawn_blocking(move || Self::query_sqlite(&path, &sql, read_write, max_rows))
+                .await
+                .map_err(|e| AgentError::Execution(e.to_string()))?
+        };
+
+        match result {
+            Ok(result) => Ok(result.into_output()),
+            Err(e) => Ok(ToolOutput::failure(format!("Query failed: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE t (id INTEGER, name TEXT); INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c');").unwrap();
+        (dir, path.to_string_lossy().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_select_truncates_rows() {
+        let (dir, path) = fixture();
+        let tool = SqlTool::new().with_allowed_root(dir.path()).with_database(path).with_max_rows(2);
+
+        let out = tool.execute(json!({ "query": "SELECT id, name FROM t ORDER BY id" })).await.unwrap();
+        assert!(out.success, "{}", out.summary);
+        assert_eq!(out.data["rows"], json!([{ "id": 1, "name": "a" }, { "id": 2, "name": "b" }]));
+        assert_eq!(out.data["truncated"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_rejects_writes() {
+        let (dir, path) = fixture();
+        let tool = SqlTool::new().with_allowed_root(dir.path()).with_database(path.clone());
+        assert!(tool.execute(json!({ "query": "DELETE FROM t" })).await.is_err());
+        assert!(tool.execute(json!({ "query": "SELECT 1; DROP TABLE t" })).await.is_err());
+
+        let writer = SqlTool::new().with_allowed_root(dir.path()).with_database(path).with_read_write(true);
+        let out = writer.execute(json!({ "query": "DELETE FROM t WHERE id = 1" })).await.unwrap();
+        assert_eq!(out.data["rows_affected"], json!(1));
+    }
+
+    #[tokio::test]
+    async fn test_database_confined_to_allowed_roots() {
+        let (_dir, path) = fixture();
+        let other = tempfile::tempdir().unwrap();
+        let tool = SqlTool::new().with_allowed_root(other.path()).with_read_write(true);
+
+        // Outside the allowlist, even though the file exists
+        assert!(tool.execute(json!({ "query": "SELECT 1", "database": path })).await.is_err());
+
+        // Inside the allowlist, but opening must not create the file
+        let missing = other.path().join("new.db");
+        assert!(tool.execute(json!({ "query": "SELECT 1", "database": missing.to_string_lossy() })).await.is_err());
+        assert!(!missing.exists());
+    }
+}
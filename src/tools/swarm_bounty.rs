@@ -7,7 +7,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
 use crate::orchestrator::queue::TaskQueue;
 
 pub struct SwarmBountyTool {
@@ -58,6 +58,18 @@ impl Tool for SwarmBountyTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn has_side_effects(&self, _params: &Value) -> bool {
+        true
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let goal = params["goal"].as_str()
             .ok_or_else(|| AgentError::Validation("Missing 'goal'".to_string()))?;
@@ -9,7 +9,7 @@ use tracing::info;
 use sysinfo::System;
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCachePolicy, TOOL_ANALYTICS};
 use crate::memory::MemoryManager;
 
 /// Tool for monitoring system resources and awareness
@@ -94,8 +94,12 @@ impl Tool for SystemTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["status", "processes", "peripherals", "self_awareness"],
+                    "enum": ["status", "processes", "peripherals", "self_awareness", "tool_stats"],
                     "description": "The information to retrieve"
+                },
+                "since_hours": {
+                    "type": "integer",
+                    "description": "For tool_stats: only include calls from the last N hours"
                 }
             },
             "required": ["action"]
@@ -111,6 +115,10 @@ impl Tool for SystemTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("status");
         info!("SystemTool: Action = {}", action);
@@ -156,6 +164,17 @@ impl Tool for SystemTool {
                 let summary = format!("Agency Self-Awareness: Running as PID {} with {} MB RAM usage.", pid, process.memory() / 1024 / 1024);
                 Ok(ToolOutput::success(data, summary))
             },
+            "tool_stats" => {
+                let since = params["since_hours"].as_i64()
+                    .and_then(chrono::Duration::try_hours)
+                    .and_then(|window| chrono::Utc::now().checked_sub_signed(window));
+                let stats = TOOL_ANALYTICS.stats(since).await;
+                let mut summary = format!("Tool usage across {} tools:", stats.len());
+                for s in stats.iter().take(10) {
+                    summary.push_str(&format!("\n- {}: {} calls, {:.0}% failed, avg {:.0} ms", s.tool, s.calls, s.failure_rate * 100.0, s.avg_latency_ms));
+                }
+                Ok(ToolOutput::success(json!(stats), summary))
+            },
             _ => Ok(ToolOutput::failure("Unknown system action"))
         }
     }
@@ -1,14 +1,17 @@
 //! Task Spawner Tool
 //! 
 //! Allows agents to spawn new background tasks into the persistent queue.
-//! This enables "Cellular Division" of complex goals.
+//! This enables "Cellular Division" of complex goals. Spawns made during a turn
+//! are tracked as sub-agents of it (see `orchestrator::subagents`).
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCachePolicy};
+use crate::orchestrator::event_bus::current_turn;
 use crate::orchestrator::queue::TaskQueue;
+use crate::orchestrator::subagents::SUBAGENTS;
 
 pub struct TaskSpawnerTool {
     queue: Arc<dyn TaskQueue>,
@@ -27,34 +30,65 @@ impl Tool for TaskSpawnerTool {
     }
 
     fn description(&self) -> String {
-        "Spawn a new background task. Use this to break down complex goals into smaller, parallelizable sub-tasks. The task will be executed asynchronously.".to_string()
+        "Spawn a new background task. Use this to break down complex goals into smaller, parallelizable sub-tasks. The task will be executed asynchronously; finished sub-agents report back as observations, or check them with action 'status'.".to_string()
     }
 
     fn parameters(&self) -> Value {
         json!({
             "type": "object",
             "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["spawn", "status"],
+                    "description": "'spawn' (default) queues a sub-task; 'status' lists the sub-agents this turn spawned and their results."
+                },
                 "goal": {
                     "type": "string",
                     "description": "The description of the sub-task to perform."
                 }
-            },
-            "required": ["goal"]
+            }
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
+        let parent_turn = current_turn();
+        if params["action"] == "status" {
+            let children = parent_turn.as_deref().map(|turn| SUBAGENTS.children(turn)).unwrap_or_default();
+            let summary = children.iter().map(|c| c.observation()).collect::<Vec<_>>().join("\n");
+            if let Some(ref turn) = parent_turn {
+                // Listed results count as delivered
+                SUBAGENTS.take_results(turn);
+            }
+            return Ok(ToolOutput::success(
+                json!({ "subagents": children }),
+                if summary.is_empty() { "No sub-agents spawned in this turn.".to_string() } else { summary },
+            ));
+        }
+
         let goal = params["goal"].as_str()
             .ok_or_else(|| AgentError::Execution("Missing 'goal' parameter".to_string()))?;
 
-        // We wrap the goal in the standard payload structure
-        let payload = json!(goal);
+        let ticket = match SUBAGENTS.admit(parent_turn.as_deref()) {
+            Ok(ticket) => ticket,
+            Err(reason) => return Ok(ToolOutput::failure(format!("Spawn refused: {}", reason))),
+        };
+
+        // We wrap the goal in the standard payload structure, with its lineage
+        let payload = json!({ "goal": goal, "parent_turn": parent_turn, "depth": ticket.depth });
         
         match self.queue.enqueue("autonomous_goal", payload).await {
-            Ok(id) => Ok(ToolOutput::success(
-                json!({ "task_id": id, "status": "queued" }), 
-                format!("Task spawned successfully. ID: {}", id)
-            )),
+            Ok(id) => {
+                let depth = ticket.depth;
+                SUBAGENTS.register(&id, goal, ticket);
+                Ok(ToolOutput::success(
+                    json!({ "task_id": id, "status": "queued", "depth": depth }), 
+                    format!("Task spawned successfully. ID: {}", id)
+                ))
+            },
             Err(e) => Ok(ToolOutput::failure(format!("Failed to spawn task: {}", e))),
         }
     }
@@ -79,4 +113,22 @@ mod tests {
         assert!(res.success);
         assert_eq!(queue.count("pending").await.unwrap(), 1);
     }
+
+    #[tokio::test]
+    async fn test_spawned_task_is_tracked_under_turn() {
+        let tmp = NamedTempFile::new().unwrap();
+        let queue = Arc::new(SqliteTaskQueue::new(tmp.path()).await.unwrap());
+        let tool = TaskSpawnerTool::new(queue.clone());
+        let turn = uuid::Uuid::new_v4().to_string();
+
+        let res = crate::orchestrator::event_bus::in_turn(Some(turn.clone()), tool.execute(json!({
+            "goal": "Summarize the changelog"
+        }))).await.unwrap();
+
+        let task_id = res.data["task_id"].as_str().unwrap();
+        let record = SUBAGENTS.get(task_id).unwrap();
+        assert_eq!(record.parent_turn.as_deref(), Some(turn.as_str()));
+        assert_eq!(record.depth, 1);
+        assert_eq!(SUBAGENTS.children(&turn).len(), 1);
+    }
 }
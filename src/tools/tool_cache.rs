@@ -0,0 +1,145 @@
+//! Tool Result Cache
+//!
+//! Bounded cache for `ToolRegistry` results. Each tool declares a `ToolCachePolicy` (TTL,
+//! per-tool entry limit, or no caching at all); entries are evicted least-recently-used
+//! per tool and across the whole cache.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::ToolOutput;
+
+/// Upper bound on cached results across all tools
+pub const MAX_TOOL_CACHE_ENTRIES: usize = 1024;
+
+/// How results of a tool may be cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToolCachePolicy {
+    /// Whether results are cached at all
+    pub cacheable: bool,
+    /// How long a result stays valid; `None` keeps it until evicted
+    pub ttl: Option<Duration>,
+    /// Maximum cached results for this tool
+    pub max_entries: usize,
+}
+
+impl Default for ToolCachePolicy {
+    fn default() -> Self {
+        Self { cacheable: true, ttl: Some(Duration::from_secs(600)), max_entries: 64 }
+    }
+}
+
+impl ToolCachePolicy {
+    /// Never cache (side effects or live data)
+    pub fn no_cache() -> Self {
+        Self { cacheable: false, ttl: None, max_entries: 0 }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+struct CachedOutput {
+    tool: String,
+    output: ToolOutput,
+    inserted: Instant,
+    /// Access counter value at the last hit, for LRU ordering
+    last_access: u64,
+}
+
+/// LRU cache of tool outputs keyed by tool name and parameters
+#[derive(Default)]
+pub struct ToolCache {
+    entries: HashMap<String, CachedOutput>,
+    clock: u64,
+}
+
+impl ToolCache {
+    /// Return a fresh result, dropping it if it has expired
+    pub fn get(&mut self, key: &str, policy: &ToolCachePolicy) -> Option<ToolOutput> {
+        if !policy.cacheable {
+            return None;
+        }
+        let expired = self.entries.get(key)
+            .map(|e| policy.ttl.is_some_and(|ttl| e.inserted.elapsed() >= ttl))?;
+        if expired {
+            self.entries.remove(key);
+            return None;
+        }
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_access = self.clock;
+        Some(entry.output.clone())
+    }
+
+    pub fn insert(&mut self, tool: &str, key: String, output: ToolOutput, policy: &ToolCachePolicy) {
+        if !policy.cacheable || policy.max_entries == 0 {
+            return;
+        }
+        self.clock += 1;
+        self.entries.insert(key, CachedOutput { tool: tool.to_string(), output, inserted: Instant::now(), last_access: self.clock });
+
+        while self.entries.values().filter(|e| e.tool == tool).count() > policy.max_entries {
+            self.evict_lru(Some(tool));
+        }
+        while self.entries.len() > MAX_TOOL_CACHE_ENTRIES {
+            self.evict_lru(None);
+        }
+    }
+
+    fn evict_lru(&mut self, tool: Option<&str>) {
+        let oldest = self.entries.iter()
+            .filter(|(_, e)| tool.is_none_or(|t| e.tool == t))
+            .min_by_key(|(_, e)| e.last_access)
+            .map(|(k, _)| k.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_and_lru_eviction() {
+        let mut cache = ToolCache::default();
+        let out = ToolOutput::success_str("ok");
+
+        let expiring = ToolCachePolicy::default().with_ttl(Duration::ZERO);
+        cache.insert("news", "news:1".to_string(), out.clone(), &expiring);
+        assert!(cache.get("news:1", &expiring).is_none());
+        assert!(cache.is_empty());
+
+        let small = ToolCachePolicy::default().with_max_entries(2);
+        cache.insert("calc", "calc:1".to_string(), out.clone(), &small);
+        cache.insert("calc", "calc:2".to_string(), out.clone(), &small);
+        assert!(cache.get("calc:1", &small).is_some());
+        cache.insert("calc", "calc:3".to_string(), out.clone(), &small);
+        assert!(cache.get("calc:2", &small).is_none(), "least recently used entry is evicted");
+        assert!(cache.get("calc:1", &small).is_some());
+
+        cache.insert("exec", "exec:1".to_string(), out, &ToolCachePolicy::no_cache());
+        assert_eq!(cache.len(), 2);
+    }
+}
@@ -0,0 +1,177 @@
+//! Uploaded Files
+//!
+//! Files posted to `/v1/files` are kept under `artifacts/uploads/` as `<id>.<ext>` with a
+//! `<id>.json` record beside them. The `file-…` id is what the chat API, `vision` (as
+//! `image_source`) and document ingestion refer to.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::agent::{AgentError, AgentResult};
+
+pub const UPLOADS_DIR: &str = "artifacts/uploads";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadKind {
+    Image,
+    Pdf,
+    Text,
+    Binary,
+}
+
+impl UploadKind {
+    /// Detected from the content's magic bytes, falling back to the file name for text
+    pub fn detect(bytes: &[u8], filename: &str) -> (Self, &'static str) {
+        const IMAGES: &[(&[u8], &str)] = &[
+            (b"\x89PNG\r\n\x1a\n", "png"),
+            (b"\xff\xd8\xff", "jpg"),
+            (b"GIF87a", "gif"),
+            (b"GIF89a", "gif"),
+        ];
+        if let Some((_, ext)) = IMAGES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+            return (UploadKind::Image, ext);
+        }
+        if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            return (UploadKind::Image, "webp");
+        }
+        if bytes.starts_with(b"%PDF-") {
+            return (UploadKind::Pdf, "pdf");
+        }
+        // Text keeps a known extension; anything else is stored as `.txt`
+        let sample = &bytes[..bytes.len().min(8192)];
+        if !sample.contains(&0) && std::str::from_utf8(bytes).is_ok() {
+            const TEXT: &[&str] = &["md", "csv", "json", "html", "xml", "yaml", "yml", "toml", "rs", "py"];
+            let ext = Path::new(filename).extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+            return (UploadKind::Text, TEXT.iter().find(|t| **t == ext).copied().unwrap_or("txt"));
+        }
+        (UploadKind::Binary, "bin")
+    }
+
+    /// Whether the document ingestion pipeline can read it
+    pub fn is_document(self) -> bool {
+        matches!(self, UploadKind::Pdf | UploadKind::Text)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedFile {
+    pub id: String,
+    pub filename: String,
+    pub bytes: usize,
+    pub kind: UploadKind,
+    pub created_at: i64,
+    /// Where the content is stored
+    pub path: PathBuf,
+    /// Vector memory chunks from ingestion, for documents
+    #[serde(default)]
+    pub chunks: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+impl UploadedFile {
+    /// OpenAI file object, with the detected kind
+    pub fn to_openai(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "object": "file",
+            "bytes": self.bytes,
+            "created_at": self.created_at,
+            "filename": self.filename,
+            "purpose": if self.kind.is_document() { "assistants" } else { "vision" },
+            "kind": self.kind,
+            "chunks": self.chunks,
+        })
+    }
+}
+
+/// Whether `id` looks like an upload id
+pub fn is_file_id(id: &str) -> bool {
+    id.strip_prefix("file-").is_some_and(|hex| hex.len() == 24 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+pub struct UploadStore {
+    dir: PathBuf,
+}
+
+impl Default for UploadStore {
+    fn default() -> Self {
+        Self::new(UPLOADS_DIR)
+    }
+}
+
+impl UploadStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Save `bytes` under a new id
+    pub async fn store(&self, filename: &str, bytes: &[u8], owner: Option<&str>) -> AgentResult<UploadedFile> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let (kind, ext) = UploadKind::detect(bytes, filename);
+        let id = format!("file-{}", &uuid::Uuid::new_v4().simple().to_string()[..24]);
+        let path = self.dir.join(format!("{}.{}", id, ext));
+        tokio::fs::write(&path, bytes).await?;
+
+        let file = UploadedFile {
+            id,
+            // Only the last path component of the client's name is kept
+            filename: Path::new(filename).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| "upload".to_string()),
+            bytes: bytes.len(),
+            kind,
+            created_at: chrono::Utc::now().timestamp(),
+            path,
+            chunks: 0,
+            owner: owner.map(str::to_string),
+        };
+        self.save(&file).await?;
+        Ok(file)
+    }
+
+    /// Rewrite the record of `file`
+    pub async fn save(&self, file: &UploadedFile) -> AgentResult<()> {
+        let record = serde_json::to_vec_pretty(file)?;
+        tokio::fs::write(self.dir.join(format!("{}.json", file.id)), record).await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, id: &str) -> AgentResult<UploadedFile> {
+        if !is_file_id(id) {
+            return Err(AgentError::Validation(format!("Invalid file id '{}'", id)));
+        }
+        let record = tokio::fs::read(self.dir.join(format!("{}.json", id))).await
+            .map_err(|_| AgentError::Validation(format!("No such file '{}'", id)))?;
+        Ok(serde_json::from_slice(&record)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(UploadKind::detect(b"\x89PNG\r\n\x1a\n....", "photo"), (UploadKind::Image, "png"));
+        assert_eq!(UploadKind::detect(b"%PDF-1.7\n...", "paper.bin"), (UploadKind::Pdf, "pdf"));
+        assert_eq!(UploadKind::detect(b"# Notes\nhello", "notes.MD"), (UploadKind::Text, "md"));
+        assert_eq!(UploadKind::detect(b"plain", "x.exe"), (UploadKind::Text, "txt"));
+        assert_eq!(UploadKind::detect(&[0, 159, 146, 150], "blob"), (UploadKind::Binary, "bin"));
+    }
+
+    #[tokio::test]
+    async fn test_store_and_get() {
+        let dir = tempdir().unwrap();
+        let store = UploadStore::new(dir.path());
+        let file = store.store("../../etc/report.txt", b"quarterly numbers", Some("alice")).await.unwrap();
+        assert!(is_file_id(&file.id));
+        assert_eq!(file.filename, "report.txt");
+        assert!(file.path.starts_with(dir.path()));
+
+        let loaded = store.get(&file.id).await.unwrap();
+        assert_eq!(loaded.kind, UploadKind::Text);
+        assert_eq!(loaded.owner.as_deref(), Some("alice"));
+        assert!(store.get("file-../../secrets").await.is_err());
+    }
+}
@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::io::Cursor;
 
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCachePolicy, UploadKind, UploadStore};
 use screenshots::Screen;
 use nokhwa::pixel_format::RgbFormat;
 use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
@@ -23,7 +23,7 @@ use tokenizers::Tokenizer;
 pub struct VisionParams {
     /// The action to perform: 'capture_screen', 'capture_camera', or 'describe'.
     pub action: String,
-    /// For 'describe', the path to the image or 'last_capture'.
+    /// For 'describe', the path to the image, an uploaded `file-…` id, or 'last_capture'.
     pub image_source: Option<String>,
     /// For 'describe', the question about the image.
     pub prompt: Option<String>,
@@ -33,12 +33,15 @@ pub struct VisionParams {
 
 pub struct VisionTool {
     last_image: Arc<Mutex<Option<PathBuf>>>,
+    /// Resolves `file-…` ids from `/v1/files`
+    uploads: UploadStore,
 }
 
 impl Default for VisionTool {
     fn default() -> Self {
         Self {
             last_image: Arc::new(Mutex::new(None)),
+            uploads: UploadStore::default(),
         }
     }
 }
@@ -48,6 +51,27 @@ impl VisionTool {
         Self::default()
     }
 
+    pub fn with_uploads(mut self, uploads: UploadStore) -> Self {
+        self.uploads = uploads;
+        self
+    }
+
+    /// Path of an image source: the last capture, an uploaded image's id, or a path
+    async fn resolve_source(&self, source: &str) -> AgentResult<PathBuf> {
+        if source == "last_capture" {
+            let last = self.last_image.lock().await;
+            return last.clone().ok_or_else(|| AgentError::Validation("No image captured yet. Capture screen or camera first.".to_string()));
+        }
+        if crate::tools::is_file_id(source) {
+            let file = self.uploads.get(source).await?;
+            if file.kind != UploadKind::Image {
+                return Err(AgentError::Validation(format!("{} is not an image ({:?})", source, file.kind)));
+            }
+            return Ok(file.path);
+        }
+        Ok(PathBuf::from(source))
+    }
+
     async fn capture_screen(&self, display_id: Option<usize>) -> AgentResult<PathBuf> {
         let screens = Screen::all().map_err(|e| AgentError::Tool(format!("Failed to list screens: {}", e)))?;
         let screen = if let Some(id) = display_id {
@@ -207,7 +231,7 @@ impl Tool for VisionTool {
                 },
                 "image_source": {
                     "type": "string",
-                    "description": "For 'describe', path to image or 'last_capture' (default)."
+                    "description": "For 'describe', path to image, an uploaded file id (file-...), or 'last_capture' (default)."
                 },
                 "prompt": {
                     "type": "string",
@@ -222,6 +246,10 @@ impl Tool for VisionTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let p: VisionParams = serde_json::from_value(params).map_err(|e| AgentError::Serde(e))?;
         
@@ -242,12 +270,7 @@ impl Tool for VisionTool {
             },
             "describe" => {
                 let source = p.image_source.unwrap_or_else(|| "last_capture".to_string());
-                let path = if source == "last_capture" {
-                    let last = self.last_image.lock().await;
-                    last.clone().ok_or_else(|| AgentError::Validation("No image captured yet. Capture screen or camera first.".to_string()))?
-                } else {
-                    PathBuf::from(source)
-                };
+                let path = self.resolve_source(&source).await?;
                 
                 let prompt = p.prompt.unwrap_or_else(|| "Describe this image in detail.".to_string());
                 let description = self.describe_image(path, prompt).await?;
@@ -279,4 +302,17 @@ mod tests {
         assert_eq!(params.prompt.unwrap(), "What do you see?");
         assert_eq!(params.image_source.unwrap(), "test.png");
     }
+
+    #[tokio::test]
+    async fn test_resolve_uploaded_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = UploadStore::new(dir.path());
+        let image = uploads.store("cat.png", b"\x89PNG\r\n\x1a\nrest", None).await.unwrap();
+        let notes = uploads.store("notes.txt", b"not an image", None).await.unwrap();
+        let tool = VisionTool::new().with_uploads(UploadStore::new(dir.path()));
+
+        assert_eq!(tool.resolve_source(&image.id).await.unwrap(), image.path);
+        assert!(tool.resolve_source(&notes.id).await.is_err());
+        assert!(tool.resolve_source("last_capture").await.is_err());
+    }
 }
@@ -6,7 +6,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCapability};
 use crate::orchestrator::metabolism::{EconomicMetabolism, TransactionCategory, Network};
 
 pub struct WalletTool {
@@ -61,6 +61,10 @@ impl Tool for WalletTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::SpendMoney, ToolCapability::Network]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let action = params["action"].as_str().unwrap_or("check_balance");
         let network_str = params["network"].as_str().unwrap_or("bitcoin");
@@ -8,7 +8,7 @@ use serde_json::{json, Value};
 use tokio::process::Command;
 use std::path::PathBuf;
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability};
 
 pub struct WasmCompilerTool {
     work_dir: PathBuf,
@@ -58,6 +58,15 @@ impl Tool for WasmCompilerTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        // Cold cargo builds are slow
+        Some(std::time::Duration::from_secs(600))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let source_code = params["source_code"].as_str().ok_or_else(|| AgentError::Validation("Missing source_code".to_string()))?;
         let filename = params["filename"].as_str().unwrap_or("module");
@@ -9,7 +9,7 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 use crate::agent::{AgentResult, AgentError};
 use crate::runtime::wasm::WasmRuntime;
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability};
 
 pub struct WasmExecutorTool {
     runtime: Mutex<WasmRuntime>,
@@ -65,6 +65,10 @@ impl Tool for WasmExecutorTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Shell]
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let wasm_path_str = params["wasm_path"].as_str().ok_or_else(|| AgentError::Validation("Missing wasm_path".to_string()))?;
         let function_name = params["function_name"].as_str().unwrap_or("run");
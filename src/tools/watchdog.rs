@@ -8,7 +8,7 @@ use serde_json::{json, Value};
 use std::sync::Arc;
 use std::time::Duration;
 use crate::agent::{AgentResult, AgentError};
-use crate::tools::{Tool, ToolOutput};
+use crate::tools::{Tool, ToolOutput, ToolCachePolicy};
 use crate::orchestrator::sensory::SensoryCortex;
 
 pub struct WatchdogTool {
@@ -56,6 +56,10 @@ impl Tool for WatchdogTool {
         })
     }
 
+    fn cache_policy(&self) -> ToolCachePolicy {
+        ToolCachePolicy::no_cache()
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let method = params["method"].as_str()
             .ok_or_else(|| AgentError::Execution("Missing 'method'".to_string()))?;
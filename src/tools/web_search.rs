@@ -9,7 +9,7 @@ use serde_json::{json, Value};
 use tracing::{debug, warn};
 
 use crate::agent::{AgentResult, AgentError};
-use super::{Tool, ToolOutput};
+use super::{Tool, ToolOutput, ToolCapability, ToolCachePolicy};
 
 /// Web search tool using DuckDuckGo
 pub struct WebSearchTool {
@@ -158,6 +158,15 @@ impl Tool for WebSearchTool {
         })
     }
 
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::Network]
+    }
+
+    fn cache_policy(&self) -> ToolCachePolicy {
+        // Search results go stale quickly
+        ToolCachePolicy::default().with_ttl(std::time::Duration::from_secs(300))
+    }
+
     async fn execute(&self, params: Value) -> AgentResult<ToolOutput> {
         let query = params["query"]
             .as_str()
@@ -0,0 +1,39 @@
+//! Atomic File Writes
+//!
+//! State files are written to a sibling temp file and renamed over the original, so a crash
+//! mid-write leaves either the old contents or the new ones, never a truncated file.
+
+use std::path::{Path, PathBuf};
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `contents` in one step
+pub async fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+    tokio::fs::write(&temp, contents).await?;
+    if let Err(e) = tokio::fs::rename(&temp, path).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_atomic_replaces_and_cleans_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        write_atomic(&path, "old").await.unwrap();
+        write_atomic(&path, "new").await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), "new");
+        assert!(!temp_path(&path).exists());
+    }
+}
@@ -0,0 +1,190 @@
+//! Container Isolation
+//!
+//! Runs agent-generated code in a throwaway Docker/Podman container: one image per
+//! language, no network, memory/CPU/pid caps, and an optional workspace mount.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Container engine CLI to drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+/// Isolation settings for containerized execution
+#[derive(Debug, Clone)]
+pub struct ContainerConfig {
+    pub runtime: ContainerRuntime,
+    /// Image per language (`python`, `javascript`, `rust`, `shell`)
+    pub images: HashMap<String, String>,
+    pub memory_mb: u64,
+    pub cpus: f64,
+    pub pids_limit: u32,
+    pub network: bool,
+    /// Host directory mounted read-write at `/workspace`
+    pub workdir: Option<PathBuf>,
+}
+
+impl ContainerConfig {
+    pub fn new(runtime: ContainerRuntime) -> Self {
+        let images = [
+            ("python", "python:3.11-slim"),
+            ("javascript", "node:20-slim"),
+            ("rust", "rust:1.75-slim"),
+            ("shell", "ubuntu:latest"),
+        ].into_iter().map(|(l, i)| (l.to_string(), i.to_string())).collect();
+
+        Self { runtime, images, memory_mb: 512, cpus: 1.0, pids_limit: 256, network: false, workdir: None }
+    }
+
+    /// `AGENCY_CODE_EXEC_BACKEND=docker|podman` enables containers (anything else runs on the host).
+    /// Tuned by `AGENCY_CONTAINER_MEMORY_MB`, `AGENCY_CONTAINER_CPUS`, `AGENCY_CONTAINER_NETWORK`,
+    /// `AGENCY_CONTAINER_WORKDIR` and `AGENCY_CONTAINER_IMAGE_<LANGUAGE>`.
+    pub fn from_env() -> Option<Self> {
+        let runtime = match std::env::var("AGENCY_CODE_EXEC_BACKEND").ok()?.to_lowercase().as_str() {
+            "docker" => ContainerRuntime::Docker,
+            "podman" => ContainerRuntime::Podman,
+            _ => return None,
+        };
+        let mut config = Self::new(runtime);
+        if let Some(mb) = std::env::var("AGENCY_CONTAINER_MEMORY_MB").ok().and_then(|v| v.parse().ok()) {
+            config.memory_mb = mb;
+        }
+        if let Some(cpus) = std::env::var("AGENCY_CONTAINER_CPUS").ok().and_then(|v| v.parse().ok()) {
+            config.cpus = cpus;
+        }
+        config.network = std::env::var("AGENCY_CONTAINER_NETWORK").map(|v| v == "1" || v == "true").unwrap_or(false);
+        config.workdir = std::env::var("AGENCY_CONTAINER_WORKDIR").ok().map(PathBuf::from);
+        for language in ["python", "javascript", "rust", "shell"] {
+            if let Ok(image) = std::env::var(format!("AGENCY_CONTAINER_IMAGE_{}", language.to_uppercase())) {
+                config.images.insert(language.to_string(), image);
+            }
+        }
+        Some(config)
+    }
+
+    pub fn with_workdir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.workdir = Some(dir.into());
+        self
+    }
+
+    pub fn image_for(&self, language: &str) -> &str {
+        self.images.get(language).map(String::as_str).unwrap_or("ubuntu:latest")
+    }
+
+    /// Script filename and the command that runs it inside `/sandbox`
+    pub fn command_for(language: &str) -> (&'static str, Vec<&'static str>) {
+        match language {
+            "python" => ("script.py", vec!["python3", "/sandbox/script.py"]),
+            "javascript" => ("script.js", vec!["node", "/sandbox/script.js"]),
+            "rust" => ("main.rs", vec!["sh", "-c", "rustc /sandbox/main.rs -o /tmp/main && /tmp/main"]),
+            _ => ("script.sh", vec!["sh", "/sandbox/script.sh"]),
+        }
+    }
+
+    /// Arguments for `<runtime> run` with every isolation flag applied
+    pub fn run_args(&self, name: &str, language: &str, script_dir: &str) -> Vec<String> {
+        let mut args: Vec<String> = vec![
+            "run".into(), "--rm".into(), "--name".into(), name.into(),
+            "--memory".into(), format!("{}m", self.memory_mb),
+            "--cpus".into(), self.cpus.to_string(),
+            "--pids-limit".into(), self.pids_limit.to_string(),
+            "--security-opt".into(), "no-new-privileges".into(),
+            "-v".into(), format!("{}:/sandbox:ro", script_dir),
+        ];
+        if !self.network {
+            args.extend(["--network".into(), "none".into()]);
+        }
+        if let Some(workdir) = &self.workdir {
+            args.extend(["-v".into(), format!("{}:/workspace", workdir.display()), "-w".into(), "/workspace".into()]);
+        }
+        args.push(self.image_for(language).to_string());
+        args.extend(Self::command_for(language).1.into_iter().map(String::from));
+        args
+    }
+
+    /// Run `code` in a fresh container, returning (stdout, stderr, exit code)
+    pub async fn run(&self, language: &str, code: &str, limit: Duration) -> Result<(String, String, i32)> {
+        let script_dir = tempfile::tempdir().context("Failed to create script directory")?;
+        let (filename, _) = Self::command_for(language);
+        tokio::fs::write(script_dir.path().join(filename), code).await
+            .context("Failed to write script for container")?;
+
+        let name = format!("agency-exec-{}", uuid::Uuid::new_v4());
+        let args = self.run_args(&name, language, &script_dir.path().to_string_lossy());
+        tracing::debug!("Running in container: {} {:?}", self.runtime.binary(), args);
+        // Killing the CLI client does not stop the container itself, so it is removed on
+        // timeout and when the call is cancelled as well
+        let _cleanup = RemoveOnDrop { binary: self.runtime.binary(), name: name.clone() };
+
+        let run = Command::new(self.runtime.binary())
+            .kill_on_drop(true)
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null())
+            .output();
+
+        match tokio::time::timeout(limit, run).await {
+            Ok(Ok(output)) => Ok((
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+                output.status.code().unwrap_or(-1),
+            )),
+            Ok(Err(e)) => Err(anyhow::anyhow!("Failed to start {}: {}", self.runtime.binary(), e)),
+            Err(_) => Err(anyhow::anyhow!("Execution timed out after {} seconds", limit.as_secs())),
+        }
+    }
+}
+
+/// Force-removes a container when dropped; harmless if `--rm` already did
+struct RemoveOnDrop {
+    binary: &'static str,
+    name: String,
+}
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else { return };
+        let (binary, name) = (self.binary, std::mem::take(&mut self.name));
+        handle.spawn(async move {
+            let _ = Command::new(binary).args(["rm", "-f", &name])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_args_isolate_by_default() {
+        let config = ContainerConfig::new(ContainerRuntime::Podman).with_workdir("/tmp/project");
+        let args = config.run_args("job", "python", "/tmp/script").join(" ");
+
+        assert!(args.contains("--network none"));
+        assert!(args.contains("--memory 512m"));
+        assert!(args.contains("-v /tmp/script:/sandbox:ro"));
+        assert!(args.contains("-v /tmp/project:/workspace -w /workspace"));
+        assert!(args.ends_with("python:3.11-slim python3 /sandbox/script.py"));
+    }
+}
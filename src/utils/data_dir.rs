@@ -0,0 +1,52 @@
+//! Data Directory
+//!
+//! Where the agency keeps its state: `AGENCY_DATA_DIR`, or `data/` next to the working
+//! directory (the vault and identity key already live there).
+
+use std::path::{Path, PathBuf};
+
+pub fn data_dir() -> PathBuf {
+    std::env::var("AGENCY_DATA_DIR").ok().filter(|v| !v.is_empty()).map(PathBuf::from).unwrap_or_else(|| PathBuf::from("data"))
+}
+
+/// Directory agents work in (file edits, executed code): `AGENCY_WORKSPACE`, or `workspace/`
+/// in the data directory. Created if missing.
+pub fn workspace_dir() -> PathBuf {
+    let dir = std::env::var("AGENCY_WORKSPACE").ok().filter(|v| !v.is_empty()).map(PathBuf::from)
+        .unwrap_or_else(|| data_dir().join("workspace"));
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::warn!("Cannot create workspace directory {}: {}", dir.display(), e);
+    }
+    dir
+}
+
+/// Path of `file` in the data directory, creating the directory. A copy left in the working
+/// directory by an older version is used instead while the data directory has none.
+pub fn data_path(file: &str) -> PathBuf {
+    resolve(&data_dir(), file)
+}
+
+fn resolve(dir: &Path, file: &str) -> PathBuf {
+    let path = dir.join(file);
+    let legacy = Path::new(file);
+    if !path.exists() && legacy.exists() {
+        return legacy.to_path_buf();
+    }
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        tracing::warn!("Cannot create data directory {}: {}", dir.display(), e);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_creates_the_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("state");
+        assert_eq!(resolve(&data, "never_written_by_tests.db"), data.join("never_written_by_tests.db"));
+        assert!(data.is_dir());
+    }
+}
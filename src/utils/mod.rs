@@ -1,8 +1,13 @@
 //! Utils Module
+pub mod atomic_write;
+pub mod data_dir;
 pub mod sandbox;
+pub mod container;
 pub mod hardening;
 pub mod otel;
 pub mod toon;
 pub mod truncate;
 
+pub use atomic_write::write_atomic;
+pub use data_dir::{data_dir, data_path, workspace_dir};
 pub use truncate::truncate_text;
\ No newline at end of file
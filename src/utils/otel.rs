@@ -5,9 +5,10 @@
 //! Now includes Log Rotation (The Excretory System).
 
 use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace as sdktrace, Resource};
 use opentelemetry::trace::TracerProvider;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
 use tracing_appender::non_blocking::WorkerGuard;
 use std::error::Error;
 
@@ -21,29 +22,105 @@ impl Drop for OtelGuard {
     }
 }
 
+/// Wire format of the OTLP exporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpProtocol {
+    Grpc,
+    HttpProtobuf,
+}
+
+/// Span export settings, read from the standard OpenTelemetry variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtelConfig {
+    /// `AGENCY_OTEL=off` disables span export (console and file logs stay on)
+    pub enabled: bool,
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT`; the exporter's default (`localhost:4317`) when unset
+    pub endpoint: Option<String>,
+    /// `OTEL_EXPORTER_OTLP_PROTOCOL`: `grpc` (default) or `http/protobuf`
+    pub protocol: OtlpProtocol,
+    /// `OTEL_SERVICE_NAME`; overrides the name passed to `init_telemetry`
+    pub service_name: Option<String>,
+    /// `OTEL_TRACES_SAMPLER_ARG`: fraction of turns to trace, default 1.0
+    pub sample_ratio: f64,
+}
+
+impl OtelConfig {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(get: impl Fn(&str) -> Option<String>) -> Self {
+        let protocol = match get("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+            Some("http/protobuf") | Some("http") => OtlpProtocol::HttpProtobuf,
+            _ => OtlpProtocol::Grpc,
+        };
+        Self {
+            enabled: !matches!(get("AGENCY_OTEL").as_deref(), Some("off") | Some("false") | Some("0")),
+            endpoint: get("OTEL_EXPORTER_OTLP_ENDPOINT").filter(|e| !e.is_empty()),
+            protocol,
+            service_name: get("OTEL_SERVICE_NAME").filter(|s| !s.is_empty()),
+            sample_ratio: get("OTEL_TRACES_SAMPLER_ARG")
+                .and_then(|r| r.parse::<f64>().ok())
+                .map(|r| r.clamp(0.0, 1.0))
+                .unwrap_or(1.0),
+        }
+    }
+
+    fn build_exporter(&self) -> Result<opentelemetry_otlp::SpanExporter, opentelemetry::trace::TraceError> {
+        match self.protocol {
+            OtlpProtocol::Grpc => {
+                let mut builder = opentelemetry_otlp::new_exporter().tonic();
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(endpoint.clone());
+                }
+                builder.build_span_exporter()
+            }
+            OtlpProtocol::HttpProtobuf => {
+                let mut builder = opentelemetry_otlp::new_exporter().http();
+                if let Some(endpoint) = &self.endpoint {
+                    builder = builder.with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')));
+                }
+                builder.build_span_exporter()
+            }
+        }
+    }
+}
+
 pub fn init_telemetry(service_name: &str) -> Result<OtelGuard, Box<dyn Error>> {
+    init_telemetry_with_console(service_name, false)
+}
+
+/// Same as `init_telemetry`, optionally sending console logs to stderr so stdout stays
+/// free for protocol traffic (e.g. MCP over stdio)
+pub fn init_telemetry_with_console(service_name: &str, console_to_stderr: bool) -> Result<OtelGuard, Box<dyn Error>> {
     global::set_text_map_propagator(TraceContextPropagator::new());
 
-    // 1. Configure OTLP Span Exporter
-    let exporter = opentelemetry_otlp::new_exporter()
-        .tonic()
-        .build_span_exporter()?;
+    // 1. Configure the OTLP span exporter (turn -> step -> tool call spans)
+    let config = OtelConfig::from_env();
+    let service_name = config.service_name.clone().unwrap_or_else(|| service_name.to_string());
+    let telemetry = if config.enabled {
+        let exporter = config.build_exporter()?;
+
+        // 2. Configure Tracer Provider
+        let trace_config = sdktrace::Config::default()
+            .with_sampler(sdktrace::Sampler::ParentBased(Box::new(sdktrace::Sampler::TraceIdRatioBased(config.sample_ratio))))
+            .with_resource(Resource::new(vec![
+                KeyValue::new("service.name", service_name.clone()),
+                KeyValue::new("environment", "production"),
+            ]));
 
-    // 2. Configure Tracer Provider
-    let trace_config = sdktrace::Config::default().with_resource(Resource::new(vec![
-        KeyValue::new("service.name", service_name.to_string()),
-        KeyValue::new("environment", "production"),
-    ]));
+        let provider = sdktrace::TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_config(trace_config)
+            .build();
 
-    let provider = sdktrace::TracerProvider::builder()
-        .with_batch_exporter(exporter, runtime::Tokio)
-        .with_config(trace_config)
-        .build();
+        global::set_tracer_provider(provider.clone());
 
-    global::set_tracer_provider(provider.clone());
-    
-    let tracer = provider.tracer(service_name.to_string());
-    let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
+        let tracer = provider.tracer(service_name);
+        Some(tracing_opentelemetry::layer().with_tracer(tracer))
+    } else {
+        None
+    };
 
     // 3. Configure Log Rotation (The Excretory System)
     // Rotates logs daily, ensuring we don't fill the disk indefinitely.
@@ -54,6 +131,12 @@ pub fn init_telemetry(service_name: &str) -> Result<OtelGuard, Box<dyn Error>> {
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("rust_agency=info,opentelemetry=error"));
 
+    let console = if console_to_stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     // 5. Initialize Global Subscriber
     // We compose:
     // - Stdout layer (for immediate feedback)
@@ -62,9 +145,34 @@ pub fn init_telemetry(service_name: &str) -> Result<OtelGuard, Box<dyn Error>> {
     Registry::default()
         .with(filter)
         .with(telemetry)
-        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(tracing_subscriber::fmt::layer().with_target(false).with_writer(console))
         .with(tracing_subscriber::fmt::layer().with_writer(non_blocking).with_ansi(false))
         .init();
 
     Ok(OtelGuard { _log_guard: log_guard })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_config_from_env_vars() {
+        let vars: HashMap<&str, &str> = [
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://tempo:4318"),
+            ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/protobuf"),
+            ("OTEL_TRACES_SAMPLER_ARG", "2.5"),
+        ].into_iter().collect();
+        let config = OtelConfig::from_lookup(|k| vars.get(k).map(|v| v.to_string()));
+        assert!(config.enabled);
+        assert_eq!(config.endpoint.as_deref(), Some("http://tempo:4318"));
+        assert_eq!(config.protocol, OtlpProtocol::HttpProtobuf);
+        assert_eq!(config.sample_ratio, 1.0);
+
+        let off = OtelConfig::from_lookup(|k| (k == "AGENCY_OTEL").then(|| "off".to_string()));
+        assert!(!off.enabled);
+        assert_eq!(off.protocol, OtlpProtocol::Grpc);
+        assert_eq!(off.endpoint, None);
+    }
+}
@@ -1,6 +1,9 @@
 //! Sandbox Utilities (Seatbelt)
 //! 
-//! Centralizes macOS Seatbelt (sandbox-exec) policies and helpers.
+//! Centralizes macOS Seatbelt (sandbox-exec) policies and helpers, and the named sandbox
+//! profiles that also confine code on Linux (rlimits, Landlock, seccomp).
+
+use serde::{Deserialize, Serialize};
 
 pub const TOOL_SANDBOX_POLICY: &str = r#"
 (version 1)
@@ -30,3 +33,261 @@ pub const TOOL_SANDBOX_POLICY: &str = r#"
 
 (allow sysctl-read)
 "#;
+
+/// How tightly `code_exec` and `sandbox` confine the code they run. Selected in the agency
+/// profile (`sandbox_profile`) and per call; a call may only ask for a stricter profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxProfile {
+    /// Resource limits only
+    Full,
+    /// Writes limited to the workspace and temp directories; network allowed
+    #[default]
+    WorkspaceOnly,
+    /// As `workspace-only`, with no internet sockets
+    NoNetwork,
+}
+
+/// Resource ceilings applied to the child process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxLimits {
+    pub cpu_secs: u64,
+    /// Data segment (heap) size
+    pub memory_mb: u64,
+    /// Largest file the process may write
+    pub file_size_mb: u64,
+}
+
+impl SandboxProfile {
+    pub fn key(&self) -> &'static str {
+        match self {
+            SandboxProfile::Full => "full",
+            SandboxProfile::WorkspaceOnly => "workspace-only",
+            SandboxProfile::NoNetwork => "no-network",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        [SandboxProfile::Full, SandboxProfile::WorkspaceOnly, SandboxProfile::NoNetwork]
+            .into_iter()
+            .find(|p| p.key() == key)
+    }
+
+    /// The profile for one call: `requested` when it is at least as strict as `self`
+    pub fn narrow(self, requested: Option<&str>) -> Result<Self, String> {
+        let Some(key) = requested else { return Ok(self) };
+        let requested = Self::from_key(key).ok_or_else(|| format!("Unknown sandbox profile '{}'", key))?;
+        if requested < self {
+            return Err(format!("Sandbox profile '{}' is less strict than the configured '{}'", key, self.key()));
+        }
+        Ok(requested)
+    }
+
+    pub fn allows_network(&self) -> bool {
+        *self != SandboxProfile::NoNetwork
+    }
+
+    pub fn limits(&self) -> SandboxLimits {
+        match self {
+            SandboxProfile::Full => SandboxLimits { cpu_secs: 300, memory_mb: 8192, file_size_mb: 4096 },
+            _ => SandboxLimits { cpu_secs: 60, memory_mb: 2048, file_size_mb: 512 },
+        }
+    }
+
+    /// Seatbelt policy for `sandbox-exec`; takes the `WORKSPACE_DIR` parameter
+    pub fn seatbelt_policy(&self) -> String {
+        match self {
+            SandboxProfile::Full => "(version 1)\n(allow default)\n".to_string(),
+            SandboxProfile::WorkspaceOnly => TOOL_SANDBOX_POLICY.to_string(),
+            SandboxProfile::NoNetwork => TOOL_SANDBOX_POLICY.replace("(allow network-outbound)", "(deny network*)"),
+        }
+    }
+
+    /// Confine a command before it is spawned. On Linux: rlimits, no-new-privileges, a
+    /// Landlock write allowlist (workspace, temp, `/dev`) and a seccomp filter refusing
+    /// internet sockets. On macOS use `seatbelt_policy` with `sandbox-exec` instead.
+    #[cfg(target_os = "linux")]
+    pub fn confine(&self, cmd: &mut tokio::process::Command, workspace: &std::path::Path) {
+        let limits = self.limits();
+        let write_paths: Vec<std::ffi::CString> = match self {
+            SandboxProfile::Full => Vec::new(),
+            _ => [workspace.to_path_buf(), std::env::temp_dir(), std::path::PathBuf::from("/dev")]
+                .iter()
+                .filter_map(|p| std::ffi::CString::new(p.to_string_lossy().as_bytes()).ok())
+                .collect(),
+        };
+        if !write_paths.is_empty() && !linux::landlock_supported() {
+            tracing::warn!("Landlock unavailable on this kernel; '{}' sandbox cannot restrict writes", self.key());
+        }
+        let block_network = !self.allows_network();
+        // SAFETY: the closure runs in the forked child before exec and only makes syscalls
+        unsafe {
+            cmd.pre_exec(move || {
+                linux::set_limits(&limits)?;
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if !write_paths.is_empty() {
+                    linux::restrict_writes(&write_paths);
+                }
+                if block_network {
+                    linux::deny_internet_sockets()?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn confine(&self, _cmd: &mut tokio::process::Command, _workspace: &std::path::Path) {
+        tracing::warn!("No '{}' sandbox on this platform. Running unconfined.", self.key());
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::SandboxLimits;
+    use std::ffi::CString;
+    use std::io;
+
+    pub fn set_limits(limits: &SandboxLimits) -> io::Result<()> {
+        let set = |resource, value: u64| {
+            let rlim = libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t };
+            // SAFETY: plain syscall on a stack value
+            if unsafe { libc::setrlimit(resource, &rlim) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        };
+        set(libc::RLIMIT_CPU, limits.cpu_secs)?;
+        set(libc::RLIMIT_DATA, limits.memory_mb * 1024 * 1024)?;
+        set(libc::RLIMIT_FSIZE, limits.file_size_mb * 1024 * 1024)?;
+        set(libc::RLIMIT_CORE, 0)
+    }
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1;
+    const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+    /// WRITE_FILE, REMOVE_DIR, REMOVE_FILE and every MAKE_* right (Landlock ABI 1)
+    const LANDLOCK_WRITE_ACCESS: u64 = (1 << 1) | (1 << 4) | (1 << 5) | (0x7f << 6);
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    pub fn landlock_supported() -> bool {
+        // SAFETY: a version query reads no memory
+        unsafe {
+            libc::syscall(libc::SYS_landlock_create_ruleset, std::ptr::null::<RulesetAttr>(), 0usize, LANDLOCK_CREATE_RULESET_VERSION) > 0
+        }
+    }
+
+    /// Allow writes only beneath `paths`; a kernel without Landlock leaves writes unrestricted
+    pub fn restrict_writes(paths: &[CString]) {
+        let attr = RulesetAttr { handled_access_fs: LANDLOCK_WRITE_ACCESS };
+        // SAFETY: pointers refer to live, correctly sized values; fds are closed on every path
+        unsafe {
+            let ruleset = libc::syscall(libc::SYS_landlock_create_ruleset, &attr, std::mem::size_of::<RulesetAttr>(), 0u32) as i32;
+            if ruleset < 0 {
+                return;
+            }
+            for path in paths {
+                let fd = libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC);
+                if fd < 0 {
+                    continue;
+                }
+                let rule = PathBeneathAttr { allowed_access: LANDLOCK_WRITE_ACCESS, parent_fd: fd };
+                libc::syscall(libc::SYS_landlock_add_rule, ruleset, LANDLOCK_RULE_PATH_BENEATH, &rule, 0u32);
+                libc::close(fd);
+            }
+            libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0u32);
+            libc::close(ruleset);
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    /// Seccomp filter failing `socket(AF_INET|AF_INET6, ...)` and io_uring with EACCES.
+    /// Unix sockets keep working so local tooling is unaffected.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    pub fn deny_internet_sockets() -> io::Result<()> {
+        const LD_W_ABS: u16 = 0x20;
+        const JEQ_K: u16 = 0x15;
+        const JGE_K: u16 = 0x35;
+        const RET_K: u16 = 0x06;
+        // Offsets into `struct seccomp_data`
+        const NR: u32 = 0;
+        const ARCH: u32 = 4;
+        const ARG0: u32 = 16;
+        /// x32 ABI syscalls on x86_64 carry this bit
+        const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+        let op = |code, jt, jf, k| libc::sock_filter { code, jt, jf, k };
+        let deny = libc::SECCOMP_RET_ERRNO | libc::EACCES as u32;
+        let program = [
+            op(LD_W_ABS, 0, 0, ARCH),
+            op(JEQ_K, 1, 0, AUDIT_ARCH),
+            op(RET_K, 0, 0, libc::SECCOMP_RET_ERRNO | libc::EPERM as u32),
+            op(LD_W_ABS, 0, 0, NR),
+            op(JGE_K, 6, 0, X32_SYSCALL_BIT),
+            op(JEQ_K, 5, 0, libc::SYS_io_uring_setup as u32),
+            op(JEQ_K, 0, 3, libc::SYS_socket as u32),
+            op(LD_W_ABS, 0, 0, ARG0),
+            op(JEQ_K, 2, 0, libc::AF_INET as u32),
+            op(JEQ_K, 1, 0, libc::AF_INET6 as u32),
+            op(RET_K, 0, 0, libc::SECCOMP_RET_ALLOW),
+            op(RET_K, 0, 0, deny),
+        ];
+        let prog = libc::sock_fprog { len: program.len() as u16, filter: program.as_ptr() as *mut libc::sock_filter };
+        // SAFETY: `prog` points at `program`, which outlives the call; the kernel copies it
+        if unsafe { libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &prog as *const libc::sock_fprog) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    pub fn deny_internet_sockets() -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "no-network sandbox needs x86_64 or aarch64"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calls_can_only_narrow_the_profile() {
+        let configured = SandboxProfile::WorkspaceOnly;
+        assert_eq!(configured.narrow(None), Ok(SandboxProfile::WorkspaceOnly));
+        assert_eq!(configured.narrow(Some("no-network")), Ok(SandboxProfile::NoNetwork));
+        assert!(configured.narrow(Some("full")).unwrap_err().contains("less strict"));
+        assert!(configured.narrow(Some("open")).is_err());
+
+        assert!(SandboxProfile::NoNetwork.seatbelt_policy().contains("(deny network*)"));
+        assert!(!SandboxProfile::NoNetwork.allows_network());
+        let parsed: SandboxProfile = serde_json::from_str("\"workspace-only\"").unwrap();
+        assert_eq!(parsed, SandboxProfile::default());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_no_network_profile_refuses_inet_sockets() {
+        let workspace = tempfile::tempdir().unwrap();
+        let mut cmd = tokio::process::Command::new("python3");
+        cmd.args(["-c", "import socket; socket.socket(socket.AF_INET)"]);
+        SandboxProfile::NoNetwork.confine(&mut cmd, workspace.path());
+        let Ok(output) = cmd.output().await else { return }; // python3 not installed
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Permission denied"));
+    }
+}
@@ -0,0 +1,114 @@
+const ws = new WebSocket((location.protocol === 'https:' ? 'wss://' : 'ws://') + location.host + '/ws');
+const techContent = document.getElementById('tech-content');
+const plainContent = document.getElementById('plain-content');
+const assuranceLog = document.getElementById('assurance-log');
+const rValue = document.getElementById('r-value');
+const chatInput = document.getElementById('chat-input');
+const sendBtn = document.getElementById('send-btn');
+const stopBtn = document.getElementById('stop-btn');
+
+let currentTechBlock = null;
+let currentPlainBlock = null;
+let currentPlainRaw = '';
+let isAnswerMode = false;
+
+marked.setOptions({
+    highlight: function(code, lang) {
+        if (lang && hljs.getLanguage(lang)) { try { return hljs.highlight(code, { language: lang }).value; } catch (err) {} } 
+        try { return hljs.highlightAuto(code).value; } catch (err) {} 
+        return '';
+    },
+    breaks: true,
+    gfm: true
+});
+
+ws.onmessage = (e) => { 
+    const data = e.data;
+    if (data.startsWith('METRICS:')) { try { const m = JSON.parse(data.substring(8)); document.getElementById('uptime-val').textContent = m.since; document.getElementById('memory-val').textContent = m.memory + ' Turns'; } catch (err) {} } 
+    else if (data.startsWith('THOUGHT:') || (!isAnswerMode && data.startsWith('TOKEN:'))) {
+        const token = data.startsWith('TOKEN:') ? data.substring(6) : data.substring(8);
+        if (!currentTechBlock) { currentTechBlock = document.createElement('span'); techContent.appendChild(currentTechBlock); }
+        currentTechBlock.textContent += token;
+        document.getElementById('tech-scroll').scrollTop = techContent.scrollHeight;
+        if (data.startsWith('THOUGHT:')) isAnswerMode = false;
+    } else if (data.startsWith('ANSWER:') || (isAnswerMode && data.startsWith('TOKEN:'))) {
+        isAnswerMode = true;
+        const token = data.startsWith('TOKEN:') ? data.substring(6) : data.substring(7);
+        if (!currentPlainBlock) { currentPlainBlock = document.createElement('div'); currentPlainBlock.className = 'message-nexus'; plainContent.appendChild(currentPlainBlock); currentPlainRaw = ''; } 
+        let clean = token.replace(/[[A-Z]ANSWER]|ANSWER:/gi, '');
+        if (currentPlainRaw === '') clean = clean.replace(/^]\s*/, '');
+        currentPlainRaw += clean;
+        currentPlainBlock.innerHTML = marked.parse(currentPlainRaw);
+        currentPlainBlock.querySelectorAll('pre code').forEach((block) => hljs.highlightElement(block));
+        document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
+    } else if (data.startsWith('FINAL_ANSWER:')) {
+        const answer = data.substring(13);
+        if (!currentPlainBlock || currentPlainRaw.trim() === '') {
+            isAnswerMode = true;
+            if (!currentPlainBlock) { currentPlainBlock = document.createElement('div'); currentPlainBlock.className = 'message-nexus'; plainContent.appendChild(currentPlainBlock); }
+            currentPlainRaw = answer;
+            currentPlainBlock.innerHTML = marked.parse(currentPlainRaw);
+            currentPlainBlock.querySelectorAll('pre code').forEach((block) => hljs.highlightElement(block));
+            document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
+        }
+    } else if (data.startsWith('RELIABILITY:')) {
+        const val = parseFloat(data.substring(12));
+        rValue.textContent = val.toFixed(2);
+        logAssurance('Audit', 'R-Score: ' + val.toFixed(2));
+    } else if (data.startsWith('PUBLICATION_UPDATE:')) { try { const pc = JSON.parse(data.substring(19)); logAssurance('PC-Update', `${pc.pc_type}: ${JSON.stringify(pc.value)} ${pc.unit || ''} (Ed: ${pc.edition})`); } catch (err) {} }
+    else if (data.startsWith('BOUNDARY_CROSSING:')) { try { const claim = JSON.parse(data.substring(18)); logAssurance('Security', `🚨 [Quadrant ${claim.quadrant}] ${claim.claim_id}: ${claim.content}`, 'var(--accent-warn)'); } catch (err) {} }
+    else if (data.startsWith('ASSURANCE:')) { try { const a = JSON.parse(data.substring(10)); logAssurance('Telemetry', `Latency: ${a.latency}ms`); logAssurance('Telemetry', `Tool Calls: ${a.tools}`); logAssurance('Telemetry', `Evidence Nodes: ${a.evidence}`); logAssurance('Telemetry', `Scale Class: ${a.scale}`); logAssurance('Telemetry', `Model: ${a.model}`); document.getElementById('model-val').textContent = a.model; } catch (err) {} }
+    else if (data.startsWith('STATE:MODEL:')) { document.getElementById('model-val').textContent = data.substring(12); }
+    else if (data.startsWith('STATE:')) {
+        if (data.startsWith('STATE:ANSWER_START')) { isAnswerMode = true; currentPlainBlock = null; currentPlainRaw = ''; if (currentTechBlock) { const full = currentTechBlock.textContent; const match = full.match(/[[A-Z]ANSWER]*|ANSWER:?$/i); if (match) currentTechBlock.textContent = full.substring(0, match.index).trim(); } } 
+        else if (data.startsWith('STATE:THOUGHT_START')) { isAnswerMode = false; currentTechBlock = null; } 
+        else if (data.startsWith('STATE:TURN_COMPLETE') || data.startsWith('STATE:STOPPED')) { isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; sendBtn.style.display = 'inline-block'; stopBtn.style.display = 'none'; } 
+        else if (data.startsWith('STATE:ABORTED')) { isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; }
+        logAssurance('System', data);
+    } else if (data.startsWith('🚀 Request')) {
+        isAnswerMode = false; currentTechBlock = null; currentPlainBlock = null; currentPlainRaw = ''; 
+        techContent.innerHTML += '<div style="color:#444; margin:15px 0; border-top:1px solid #222; padding-top:10px;">--- NEW TURN ---</div>'; 
+        assuranceLog.innerHTML = ''; rValue.textContent = '1.00'; sendBtn.style.display = 'none'; stopBtn.style.display = 'inline-block';
+        logAssurance('System', data);
+    }
+};
+
+function logAssurance(source, msg, color) { 
+    const div = document.createElement('div');
+    div.style.marginBottom = '5px';
+    if (color) div.style.color = color;
+    div.innerHTML = `<span style="color:#333;">[${new Date().toLocaleTimeString()}]</span> <b>${source}:</b> ${msg}`;
+    assuranceLog.appendChild(div);
+    assuranceLog.scrollTop = assuranceLog.scrollHeight;
+}
+
+function sendQuery() { 
+    const val = chatInput.value.trim();
+    if (!val) return;
+    const div = document.createElement('div');
+    div.className = 'message-user';
+    div.textContent = '> ' + val;
+    plainContent.appendChild(div);
+    ws.send(JSON.stringify({ type: 'query', content: val }));
+    chatInput.value = '';
+    document.getElementById('plain-scroll').scrollTop = plainContent.scrollHeight;
+}
+
+function stopInference() { 
+    ws.send(JSON.stringify({ type: 'stop' }));
+}
+
+chatInput.addEventListener('keypress', (e) => { if (e.key === 'Enter') sendQuery(); });
+
+async function clearMemory() { 
+    if (!confirm('Wipe episodic memory?')) return;
+    await fetch('/v1/memory/clear', { method: 'POST' });
+    location.reload();
+}
+
+// Initial header values; live ones then arrive over the WebSocket
+fetch('/v1/bootstrap').then((r) => r.json()).then((b) => {
+    document.getElementById('model-val').textContent = b.model;
+    document.getElementById('uptime-val').textContent = b.since;
+    document.getElementById('memory-val').textContent = b.memory + ' Turns';
+}).catch(() => {});